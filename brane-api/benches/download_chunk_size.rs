@@ -0,0 +1,86 @@
+//  DOWNLOAD CHUNK SIZE.rs
+//
+//  Description:
+//!   Benchmarks streaming a large package image to a response body with
+//!   different chunk sizes (see `--download-chunk-size`).
+//!
+//!   Run with `cargo bench -p brane-api --bench download_chunk_size`. The
+//!   size of the image (in MiB) can be set with `BENCH_IMAGE_SIZE`
+//!   (default: 1024).
+//
+
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use brane_api::packages::stream_file;
+use tokio::fs as tfs;
+use tokio::runtime::Runtime;
+use warp::hyper::Body;
+use warp::hyper::body::HttpBody as _;
+
+
+/***** CONSTANTS *****/
+/// The default size of the image, in MiB.
+const DEFAULT_IMAGE_SIZE: u64 = 1024;
+
+/// The chunk sizes to compare, in bytes. The 16 KiB one is what was hardcoded before.
+const CHUNK_SIZES: [usize; 4] = [16 * 1024, 64 * 1024, 256 * 1024, 1024 * 1024];
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Streams the given file to a body with the given chunk size while draining it, returning how long that took.
+async fn stream(path: &Path, size: u64, chunk_size: usize) -> Duration {
+    let (mut sender, mut body): (_, Body) = Body::channel();
+    let start: Instant = Instant::now();
+
+    // Drain the body in the background, like a client would
+    let receiver = tokio::spawn(async move {
+        let mut received: u64 = 0;
+        while let Some(chunk) = body.data().await {
+            received += chunk.expect("Failed to receive chunk").len() as u64;
+        }
+        received
+    });
+
+    // Stream the file to it
+    let mut handle: tfs::File = tfs::File::open(path).await.expect("Failed to open image");
+    stream_file(path, &mut handle, size, chunk_size, &mut sender).await.expect("Failed to stream image");
+    drop(sender);
+    assert_eq!(receiver.await.expect("Failed to join receiver"), size);
+    start.elapsed()
+}
+
+
+
+
+
+/***** ENTRYPOINT *****/
+fn main() {
+    let size_mib: u64 = std::env::var("BENCH_IMAGE_SIZE").ok().and_then(|size| size.parse().ok()).unwrap_or(DEFAULT_IMAGE_SIZE);
+    let size: u64 = size_mib * 1024 * 1024;
+
+    // Write a fake image of the given size
+    let dir = tempfile::TempDir::new().expect("Failed to create temporary directory");
+    let path: PathBuf = dir.path().join("image.tar");
+    {
+        let mut handle = std::io::BufWriter::new(std::fs::File::create(&path).expect("Failed to create image"));
+        let block: Vec<u8> = (0..1024 * 1024).map(|i: usize| (i % 251) as u8).collect();
+        for _ in 0..size_mib {
+            handle.write_all(&block).expect("Failed to write image");
+        }
+        handle.flush().expect("Failed to flush image");
+    }
+
+    // Warm up the page cache so the first chunk size isn't penalized, then time each of them
+    let runtime: Runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().expect("Failed to create runtime");
+    runtime.block_on(stream(&path, size, CHUNK_SIZES[0]));
+    println!("Streaming a {size_mib} MiB image:");
+    for chunk_size in CHUNK_SIZES {
+        let elapsed: Duration = runtime.block_on(stream(&path, size, chunk_size));
+        println!("  {:>5} KiB chunks: {:>10.2?} ({:.0} MiB/s)", chunk_size / 1024, elapsed, size_mib as f64 / elapsed.as_secs_f64());
+    }
+}
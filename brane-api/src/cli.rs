@@ -1,3 +1,4 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 use clap::Parser;
@@ -19,4 +20,14 @@ pub(crate) struct Cli {
         env = "NODE_CONFIG_PATH"
     )]
     pub(crate) node_config_path: PathBuf,
+
+    /// The size of the chunks in which package images are streamed to clients
+    #[clap(
+        long,
+        default_value = "262144",
+        help = "The size (in bytes) of the chunks in which package images are read and streamed to clients. Larger chunks mean fewer system calls \
+                on fast networks, but each concurrent download holds one chunk in memory.",
+        env = "DOWNLOAD_CHUNK_SIZE"
+    )]
+    pub(crate) download_chunk_size: NonZeroUsize,
 }
@@ -123,9 +123,27 @@ pub enum PackageError {
     /// Failed to serialize the types in a PackageInfo.
     #[error("Failed to serialize types in package '{name}'")]
     TypesSerializeError { name: String, source: serde_json::Error },
+    /// Failed to serialize the labels in a PackageInfo.
+    #[error("Failed to serialize labels in package '{name}'")]
+    LabelsSerializeError { name: String, source: serde_json::Error },
     /// The given PackageInfo did not have a digest registered.
     #[error("Package '{name}' does not have a digest specified")]
     MissingDigest { name: String },
+    /// Failed to deserialize the functions of a package row.
+    #[error("Failed to deserialize functions in package '{name}'")]
+    FunctionsDeserializeError { name: String, source: serde_json::Error },
+    /// Failed to deserialize the types of a package row.
+    #[error("Failed to deserialize types in package '{name}'")]
+    TypesDeserializeError { name: String, source: serde_json::Error },
+    /// Failed to deserialize the labels of a package row.
+    #[error("Failed to deserialize labels in package '{name}'")]
+    LabelsDeserializeError { name: String, source: serde_json::Error },
+    /// Failed to parse the kind of a package row.
+    #[error("Failed to parse '{raw}' as a package kind in package '{name}'")]
+    KindParseError { name: String, raw: String, source: specifications::package::PackageKindError },
+    /// The creation time of a package row was not a valid timestamp.
+    #[error("Package '{name}' has an invalid creation time ({millis}ms since the Unix epoch)")]
+    InvalidCreationTime { name: String, millis: i64 },
 
     /// Failed to define the `brane.package` type in the Scylla database.
     #[error("Failed to define the 'brane.package' type in the Scylla database")]
@@ -149,6 +167,21 @@ pub enum PackageError {
     /// Failed to query the database for the file of the given package.
     #[error("Failed to get path of package '{name}', version {version}")]
     PathQueryError { name: String, version: Version, source: scylla::transport::errors::QueryError },
+    /// Failed to query the database for the metadata of all packages.
+    #[error("Failed to query metadata of all packages from the Scylla database")]
+    PackagesQueryError { source: scylla::transport::errors::QueryError },
+    /// Failed to parse a row returned by the database as a package when querying all packages.
+    #[error("Failed to parse a package returned by the Scylla database")]
+    PackagesRowError { source: scylla::cql_to_rust::FromRowError },
+    /// The packages in the database did not form a valid package index.
+    #[error("Packages in the Scylla database do not form a valid package index")]
+    PackageIndexError { source: specifications::package::PackageIndexError },
+    /// Failed to query the database for the metadata of the given package.
+    #[error("Failed to query metadata of package '{name}' from the Scylla database")]
+    PackageQueryError { name: String, source: scylla::transport::errors::QueryError },
+    /// Failed to parse a row returned by the database as a package.
+    #[error("Failed to parse package '{name}' returned by the Scylla database")]
+    PackageRowError { name: String, source: scylla::cql_to_rust::FromRowError },
     /// The given package was unknown.
     #[error("No package '{name}' exists (or has version {version})")]
     UnknownPackage { name: String, version: Version },
@@ -158,6 +191,9 @@ pub enum PackageError {
     /// Failed to open a file.
     #[error("Failed to open file '{}'", path.display())]
     FileOpenError { path: PathBuf, source: std::io::Error },
+    /// Failed to seek to the start of the requested range in a file.
+    #[error("Failed to seek to byte {} in file '{}'", offset, path.display())]
+    FileSeekError { path: PathBuf, offset: u64, source: std::io::Error },
     /// Failed to read a file.
     #[error("Failed to read file '{}'", path.display())]
     FileReadError { path: PathBuf, source: std::io::Error },
@@ -189,6 +225,9 @@ pub enum PackageError {
     /// Failed to re-open the downloaded tarfile to extract it.
     #[error("Failed to re-open new tar file '{}'", path.display())]
     TarReopenError { path: PathBuf, source: std::io::Error },
+    /// The uploaded archive had a content type we don't know how to decompress.
+    #[error("Unsupported content type '{raw}' for package archive (expected 'application/gzip', 'application/zstd' or 'application/x-tar')")]
+    UnsupportedContentType { raw: String },
     /// Failed to get the list of entries in the tar file.
     #[error("Failed to get list of entries in tar file '{}'", path.display())]
     TarEntriesError { path: PathBuf, source: std::io::Error },
@@ -14,12 +14,16 @@
  *   Contains code for the health part of the brane API.
 **/
 
-use warp::http::HeaderValue;
+use log::{debug, warn};
+use warp::http::{HeaderValue, StatusCode};
 use warp::hyper::Body;
 use warp::reply::Response;
 use warp::{Rejection, Reply};
 
+use crate::spec::Context;
 
+
+/***** LIBRARY *****/
 pub async fn handle() -> Result<impl Reply, Rejection> {
     let mut response = Response::new(Body::from("OK!\n"));
 
@@ -28,4 +32,30 @@ pub async fn handle() -> Result<impl Reply, Rejection> {
     Ok(response)
 }
 
+
+
+/// Handles a readiness probe, which only reports the API as ready if its Scylla database can be reached.
+///
+/// # Arguments
+/// - `context`: The Context that contains the Scylla session to check.
+///
+/// # Returns
+/// A reply with status 200 OK if the database answered a trivial query, or 503 SERVICE UNAVAILABLE otherwise.
+pub async fn ready(context: Context) -> Result<impl Reply, Rejection> {
+    debug!("Checking Scylla connectivity for readiness probe...");
+    let (status, body): (StatusCode, &'static str) = match context.scylla.query("SELECT now() FROM system.local", &[]).await {
+        Ok(_) => (StatusCode::OK, "OK!\n"),
+        Err(err) => {
+            warn!("Readiness probe failed: Scylla is unreachable: {err}");
+            (StatusCode::SERVICE_UNAVAILABLE, "Scylla unreachable\n")
+        },
+    };
+
+    // Construct the response
+    let mut response = Response::new(Body::from(body));
+    *response.status_mut() = status;
+    response.headers_mut().insert("Content-Length", HeaderValue::from(body.len()));
+    Ok(response)
+}
+
 /*******/
@@ -110,7 +110,13 @@ async fn main() {
     let node_config_path: PathBuf = opts.node_config_path;
     let scylla = Arc::new(scylla);
     let proxy: Arc<ProxyClient> = Arc::new(ProxyClient::new(central.services.prx.address()));
-    let context = warp::any().map(move || Context { node_config_path: node_config_path.clone(), scylla: scylla.clone(), proxy: proxy.clone() });
+    let download_chunk_size: usize = opts.download_chunk_size.get();
+    let context = warp::any().map(move || Context {
+        node_config_path: node_config_path.clone(),
+        scylla: scylla.clone(),
+        proxy: proxy.clone(),
+        download_chunk_size,
+    });
 
     let schema = Schema::new(Query {}, Mutations {}, EmptySubscription::new());
     let graphql_filter = juniper_warp::make_graphql_filter(schema, context.clone().boxed());
@@ -134,15 +140,32 @@ async fn main() {
         .and(warp::path::param())
         .and(warp::path::param())
         .and(warp::path::end())
+        .and(warp::header::optional::<String>("range"))
         .and(context.clone())
         .and_then(packages::download);
+    let package_info = warp::path("packages")
+        .and(warp::get())
+        .and(warp::path::param())
+        .and(warp::path::param())
+        .and(warp::path("info"))
+        .and(warp::path::end())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(context.clone())
+        .and_then(packages::info);
+    let list_packages = warp::path("packages")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(context.clone())
+        .and_then(packages::list);
     let upload_package = warp::path("packages")
         .and(warp::path::end())
         .and(warp::post())
         .and(warp::filters::body::stream())
+        .and(warp::header::optional::<String>("content-type"))
         .and(context.clone())
         .and_then(packages::upload);
-    let packages = download_package.or(upload_package);
+    let packages = download_package.or(package_info.or(list_packages.or(upload_package)));
 
     // Configure infra
     let list_registries =
@@ -163,12 +186,13 @@ async fn main() {
         .and_then(infra::get_capabilities);
     let infra = get_registry.or(list_registries.or(get_capabilities));
 
-    // Configure the health & version
+    // Configure the health, readiness & version
     let health = warp::path("health").and(warp::path::end()).and_then(health::handle);
+    let ready = warp::path("ready").and(warp::path::end()).and(context.clone()).and_then(health::ready);
     let version = warp::path("version").and(warp::path::end()).and_then(version::handle);
 
     // Construct the final routes
-    let routes = data.or(packages.or(infra.or(health.or(version.or(graphql))))).with(warp::log("brane-api"));
+    let routes = data.or(packages.or(infra.or(health.or(ready.or(version.or(graphql)))))).with(warp::log("brane-api"));
 
     // Run the server
     let handle = warp::serve(routes).try_bind_with_graceful_shutdown(central.services.api.bind, async {
@@ -13,26 +13,32 @@
 //
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use async_compression::tokio::bufread::GzipDecoder;
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
 use brane_cfg::info::Info as _;
 use brane_cfg::node::{CentralConfig, NodeConfig, NodeKind};
 use bytes::Buf;
+use chrono::{DateTime, TimeZone as _, Utc};
 use log::{debug, error, info, warn};
 use rand::Rng;
 use rand::distr::Alphanumeric;
 use scylla::macros::{FromUserType, IntoUserType};
-use scylla::{SerializeCql, Session};
-use specifications::package::PackageInfo;
+use scylla::transport::errors::{DbError, QueryError};
+use scylla::{IntoTypedRows as _, SerializeCql, Session};
+use specifications::common::{Function, Type};
+use specifications::package::{PackageCompression, PackageIndex, PackageInfo, PackageKind};
 use specifications::version::Version;
 // use tar::Archive;
 use tempfile::TempDir;
 use tokio::fs as tfs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, SeekFrom};
 use tokio_stream::StreamExt;
 use tokio_tar::{Archive, Entries, Entry};
 use uuid::Uuid;
@@ -46,6 +52,94 @@ pub use crate::errors::PackageError as Error;
 use crate::spec::Context;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_etag_matches() {
+        let etag: &str = "\"abc-123\"";
+
+        // Exact, weak and listed matches
+        assert!(etag_matches("\"abc-123\"", etag));
+        assert!(etag_matches("W/\"abc-123\"", etag));
+        assert!(etag_matches("\"other\", \"abc-123\"", etag));
+        assert!(etag_matches("*", etag));
+
+        // Anything else is stale
+        assert!(!etag_matches("\"other\"", etag));
+        assert!(!etag_matches("abc-123", etag));
+        assert!(!etag_matches("\"abc-1234\"", etag));
+        assert!(!etag_matches("", etag));
+    }
+
+    #[test]
+    fn test_is_transient_scylla_error() {
+        // Overloaded, starting or unreachable nodes may recover
+        assert!(is_transient_scylla_error(&QueryError::DbError(DbError::Overloaded, "overloaded".into())));
+        assert!(is_transient_scylla_error(&QueryError::DbError(DbError::IsBootstrapping, "bootstrapping".into())));
+        assert!(is_transient_scylla_error(&QueryError::TimeoutError));
+        assert!(is_transient_scylla_error(&QueryError::RequestTimeout("timeout".into())));
+        assert!(is_transient_scylla_error(&QueryError::UnableToAllocStreamId));
+
+        // Broken queries never will
+        assert!(!is_transient_scylla_error(&QueryError::DbError(DbError::SyntaxError, "syntax".into())));
+        assert!(!is_transient_scylla_error(&QueryError::DbError(DbError::Invalid, "invalid".into())));
+        assert!(!is_transient_scylla_error(&QueryError::InvalidMessage("garbage".into())));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_query_with_retry() {
+        // Succeeds once the transient errors stop, after waiting for the doubling backoff of the failed attempts
+        let start = tokio::time::Instant::now();
+        let mut attempts: u32 = 0;
+        let res: Result<u32, QueryError> = query_with_retry("testing", || {
+            attempts += 1;
+            let attempt: u32 = attempts;
+            async move { if attempt < 3 { Err(QueryError::TimeoutError) } else { Ok(attempt) } }
+        })
+        .await;
+        assert!(matches!(res, Ok(3)));
+        assert_eq!(start.elapsed(), SCYLLA_INITIAL_BACKOFF * 3);
+
+        // Gives up after the retries run out, with the last error
+        let mut attempts: u32 = 0;
+        let res: Result<(), QueryError> = query_with_retry("testing", || {
+            attempts += 1;
+            async { Err(QueryError::TimeoutError) }
+        })
+        .await;
+        assert!(matches!(res, Err(QueryError::TimeoutError)));
+        assert_eq!(attempts, SCYLLA_MAX_RETRIES + 1);
+
+        // Non-transient errors are returned immediately
+        let mut attempts: u32 = 0;
+        let res: Result<(), QueryError> = query_with_retry("testing", || {
+            attempts += 1;
+            async { Err(QueryError::DbError(DbError::SyntaxError, "syntax".into())) }
+        })
+        .await;
+        assert!(matches!(res, Err(QueryError::DbError(DbError::SyntaxError, _))));
+        assert_eq!(attempts, 1);
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The number of times a query that failed with a transient Scylla error is retried before giving up.
+const SCYLLA_MAX_RETRIES: u32 = 4;
+/// The time to wait before the first retry of a failed Scylla query. Doubled after every attempt.
+const SCYLLA_INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+
+
+
+
+
 /***** HELPER MACROS *****/
 /// Macro that early quits from a warp function by printing the error and then returning a 500.
 macro_rules! fail {
@@ -87,6 +181,9 @@ macro_rules! fail {
 
 /***** AUXILLARY STRUCTS *****/
 /// Defines the contents of a single Scylla database row that describes a package.
+///
+/// Note that the fields are read in the order of the `brane.package` type. Fields that were added to the type later (i.e., `labels_as_json`) are
+/// thus at the end, and optional to support packages inserted before they existed.
 #[derive(Clone, IntoUserType, FromUserType, SerializeCql)]
 pub struct PackageUdt {
     pub created: i64,
@@ -100,6 +197,7 @@ pub struct PackageUdt {
     pub owners: Vec<String>,
     pub types_as_json: String,
     pub version: String,
+    pub labels_as_json: Option<String>,
 }
 
 impl TryFrom<PackageInfo> for PackageUdt {
@@ -111,6 +209,8 @@ impl TryFrom<PackageInfo> for PackageUdt {
             serde_json::to_string(&package.functions).map_err(|source| Error::FunctionsSerializeError { name: package.name.clone(), source })?;
         let types_as_json: String =
             serde_json::to_string(&package.types).map_err(|source| Error::TypesSerializeError { name: package.name.clone(), source })?;
+        let labels_as_json: String =
+            serde_json::to_string(&package.labels).map_err(|source| Error::LabelsSerializeError { name: package.name.clone(), source })?;
 
         // Assert that there is a digest
         let digest: String = package.digest.ok_or_else(|| Error::MissingDigest { name: package.name.clone() })?;
@@ -128,6 +228,54 @@ impl TryFrom<PackageInfo> for PackageUdt {
             owners: package.owners,
             types_as_json,
             version: package.version.to_string(),
+            labels_as_json: Some(labels_as_json),
+        })
+    }
+}
+
+
+
+
+
+impl TryFrom<PackageUdt> for PackageInfo {
+    type Error = Error;
+
+    fn try_from(row: PackageUdt) -> Result<Self, Self::Error> {
+        // Deserialize the functions and types from JSON again
+        let functions: HashMap<String, Function> =
+            serde_json::from_str(&row.functions_as_json).map_err(|source| Error::FunctionsDeserializeError { name: row.name.clone(), source })?;
+        let types: HashMap<String, Type> =
+            serde_json::from_str(&row.types_as_json).map_err(|source| Error::TypesDeserializeError { name: row.name.clone(), source })?;
+        // Packages inserted before labels were kept don't have any
+        let labels: HashMap<String, String> = match &row.labels_as_json {
+            Some(labels) => serde_json::from_str(labels).map_err(|source| Error::LabelsDeserializeError { name: row.name.clone(), source })?,
+            None => HashMap::new(),
+        };
+
+        // Parse the kind, version and creation time
+        let kind: PackageKind =
+            PackageKind::from_str(&row.kind).map_err(|source| Error::KindParseError { name: row.name.clone(), raw: row.kind.clone(), source })?;
+        let version: Version = Version::from_str(&row.version).map_err(|source| Error::VersionParseError { raw: row.version.clone(), source })?;
+
+        let created: DateTime<Utc> = Utc
+            .timestamp_millis_opt(row.created)
+            .single()
+            .ok_or_else(|| Error::InvalidCreationTime { name: row.name.clone(), millis: row.created })?;
+
+        // We can then simply populate the package info
+        Ok(Self {
+            created,
+            id: row.id,
+            digest: Some(row.digest),
+            name: row.name,
+            version,
+            kind,
+            owners: row.owners,
+            description: row.description,
+            detached: row.detached,
+            functions,
+            types,
+            labels,
         })
     }
 }
@@ -137,6 +285,56 @@ impl TryFrom<PackageInfo> for PackageUdt {
 
 
 /***** AUXILLARY FUNCTIONS *****/
+/// Decides whether the given Scylla error is transient, i.e., whether it makes sense to retry the query that caused it.
+///
+/// # Arguments
+/// - `err`: The [`QueryError`] to examine.
+///
+/// # Returns
+/// True if the error is caused by (temporary) cluster load or unavailability, or false if it's a syntax- or application error.
+fn is_transient_scylla_error(err: &QueryError) -> bool {
+    match err {
+        QueryError::DbError(err, _) => matches!(
+            err,
+            DbError::Unavailable { .. } | DbError::Overloaded | DbError::IsBootstrapping | DbError::ReadTimeout { .. } | DbError::WriteTimeout { .. }
+        ),
+        QueryError::IoError(_) | QueryError::TimeoutError | QueryError::RequestTimeout(_) | QueryError::UnableToAllocStreamId => true,
+        _ => false,
+    }
+}
+
+/// Runs the given Scylla query, retrying it with exponential backoff if it fails with a transient error.
+///
+/// # Arguments
+/// - `what`: Some description of the query for logging purposes.
+/// - `query`: A closure that performs the query. Called once per attempt.
+///
+/// # Returns
+/// The result of the first successful attempt.
+///
+/// # Errors
+/// This function errors with the query's error if it was not transient, or if it still failed after [`SCYLLA_MAX_RETRIES`] retries.
+async fn query_with_retry<T, F, R>(what: &str, mut query: F) -> Result<T, QueryError>
+where
+    F: FnMut() -> R,
+    R: Future<Output = Result<T, QueryError>>,
+{
+    let mut backoff: Duration = SCYLLA_INITIAL_BACKOFF;
+    let mut attempt: u32 = 0;
+    loop {
+        match query().await {
+            Ok(res) => return Ok(res),
+            Err(err) if attempt < SCYLLA_MAX_RETRIES && is_transient_scylla_error(&err) => {
+                attempt += 1;
+                warn!("Transient failure while {what} (attempt {attempt}/{SCYLLA_MAX_RETRIES}): {err} (retrying in {backoff:?})");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            },
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 /// Ensures that the packages table is present in the given Scylla database.
 ///
 /// # Arguments
@@ -149,8 +347,8 @@ impl TryFrom<PackageInfo> for PackageUdt {
 /// This function errors if the communication with the given database failed too.
 pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
     // Define the `brane.package` type
-    scylla
-        .query(
+    query_with_retry("defining package type", || {
+        scylla.query(
             "CREATE TYPE IF NOT EXISTS brane.package (
                 created bigint
             , description text
@@ -163,15 +361,32 @@ pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
             , owners list<text>
             , types_as_json text
             , version text
+            , labels_as_json text
         )",
             &[],
         )
-        .await
-        .map_err(|source| Error::PackageTypeDefineError { source })?;
+    })
+    .await
+    .map_err(|source| Error::PackageTypeDefineError { source })?;
+
+    // Types defined before labels were kept miss them; add them at the end (where `PackageUdt` expects them)
+    let fields = query_with_retry("reading package type", || {
+        scylla.query("SELECT field_names FROM system_schema.types WHERE keyspace_name = 'brane' AND type_name = 'package'", &[])
+    })
+    .await
+    .map_err(|source| Error::PackageTypeDefineError { source })?;
+    let has_labels: bool =
+        fields.rows.unwrap_or_default().into_typed::<(Vec<String>,)>().flatten().any(|(names,)| names.iter().any(|name| name == "labels_as_json"));
+    if !has_labels {
+        debug!("Adding 'labels_as_json' to the 'brane.package' type...");
+        query_with_retry("adding labels to package type", || scylla.query("ALTER TYPE brane.package ADD labels_as_json text", &[]))
+            .await
+            .map_err(|source| Error::PackageTypeDefineError { source })?;
+    }
 
     // Define  the `brane.packages` table
-    scylla
-        .query(
+    query_with_retry("defining packages table", || {
+        scylla.query(
             "CREATE TABLE IF NOT EXISTS brane.packages (
               name text
             , version text
@@ -181,8 +396,9 @@ pub async fn ensure_db_table(scylla: &Session) -> Result<(), Error> {
         )",
             &[],
         )
-        .await
-        .map_err(|source| Error::PackageTableDefineError { source })?;
+    })
+    .await
+    .map_err(|source| Error::PackageTableDefineError { source })?;
 
     // Done
     Ok(())
@@ -209,8 +425,9 @@ async fn insert_package_into_db(scylla: &Arc<Session>, package: &PackageInfo, pa
     let package: PackageUdt = package.clone().try_into()?;
 
     // Insert it
-    scylla
-        .query(
+    let file: String = path.to_string_lossy().to_string();
+    query_with_retry("inserting package", || {
+        scylla.query(
             "INSERT INTO brane.packages (
               name
             , version
@@ -218,10 +435,11 @@ async fn insert_package_into_db(scylla: &Arc<Session>, package: &PackageInfo, pa
             , package
         ) VALUES(?, ?, ?, ?)
         ",
-            (&package.name, &package.version, path.to_string_lossy().to_string(), &package),
+            (&package.name, &package.version, &file, &package),
         )
-        .await
-        .map_err(|source| Error::PackageInsertError { name: package.name, source })?;
+    })
+    .await
+    .map_err(|source| Error::PackageInsertError { name: package.name, source })?;
 
     // Done
     Ok(())
@@ -231,26 +449,128 @@ async fn insert_package_into_db(scylla: &Arc<Session>, package: &PackageInfo, pa
 
 
 
+/// Parses the value of a `Range`-header of the form `bytes=START-` or `bytes=START-END`.
+///
+/// # Arguments
+/// - `raw`: The raw value of the header.
+/// - `length`: The total length of the file that is being requested.
+///
+/// # Returns
+/// `Ok(Some((start, end)))` with the (inclusive) byte range to send, or `Ok(None)` if the header could not be parsed and should be ignored.
+///
+/// # Errors
+/// This function errors (with `Err(())`) if the header was valid but the range is not satisfiable for a file of the given length.
+fn parse_range(raw: &str, length: u64) -> Result<Option<(u64, u64)>, ()> {
+    // Only accept single byte ranges with a start
+    let Some(range) = raw.trim().strip_prefix("bytes=") else { return Ok(None) };
+    let Some((start, end)) = range.split_once('-') else { return Ok(None) };
+    let Ok(start) = start.trim().parse::<u64>() else { return Ok(None) };
+    let end: u64 = if end.trim().is_empty() {
+        length.saturating_sub(1)
+    } else {
+        match end.trim().parse::<u64>() {
+            Ok(end) => end.min(length.saturating_sub(1)),
+            Err(_) => return Ok(None),
+        }
+    };
+
+    // Assert it's satisfiable
+    if start >= length || start > end {
+        return Err(());
+    }
+    Ok(Some((start, end)))
+}
+
+
+
+
+
+/// Checks whether the value of an `If-None-Match`-header matches the given entity tag.
+///
+/// # Arguments
+/// - `raw`: The raw value of the header. May be a comma-separated list of (possibly weak) entity tags, or `*`.
+/// - `etag`: The (quoted) entity tag of the current version of the resource.
+///
+/// # Returns
+/// True if the client's cached version is current (i.e., we may reply with 304 Not Modified), or false otherwise.
+fn etag_matches(raw: &str, etag: &str) -> bool {
+    raw.split(',').map(str::trim).any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+
+
+
+
+/// Streams (a part of) an opened file to a response body in chunks.
+///
+/// # Arguments
+/// - `path`: The path of the file, used for debugging and errors.
+/// - `handle`: The file to stream, which is read from its current position.
+/// - `to_send`: The number of bytes to stream.
+/// - `chunk_size`: The size (in bytes) of the chunks to read and send.
+/// - `body_sender`: The [`Sender`] of the body to stream the chunks to.
+///
+/// # Errors
+/// This function errors if we failed to read the file or to send a chunk. A client that disconnects mid-stream is not an error; streaming then
+/// simply stops.
+pub async fn stream_file(path: &Path, handle: &mut tfs::File, to_send: u64, chunk_size: usize, body_sender: &mut Sender) -> Result<(), Error> {
+    // (Most of the code, but edited for not that library cuz it crashes during compilation, has been pulled from https://docs.rs/stream-body/latest/stream_body/)
+    let mut buf: Vec<u8> = vec![0; chunk_size];
+    let mut remaining: u64 = to_send;
+    while remaining > 0 {
+        // Stop as soon as the client is gone, instead of reading chunks that can't be sent anymore (this releases the file handle)
+        if let Err(err) = std::future::poll_fn(|cx| body_sender.poll_ready(cx)).await {
+            debug!("Client stopped downloading '{}' ({}); stopping stream", path.display(), err);
+            return Ok(());
+        }
+
+        // Read the chunk
+        let max: usize = buf.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let bytes: usize = handle.read(&mut buf[..max]).await.map_err(|source| Error::FileReadError { path: path.into(), source })?;
+        if bytes == 0 {
+            break;
+        }
+
+        // Send that with the body
+        if let Err(source) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await {
+            // A client that disconnects mid-stream is not our error
+            if source.is_closed() {
+                debug!("Client stopped downloading '{}' ({}); stopping stream", path.display(), source);
+                return Ok(());
+            }
+            return Err(Error::FileSendError { path: path.into(), source });
+        }
+        remaining -= bytes as u64;
+    }
+    Ok(())
+}
+
 /***** LIBRARY *****/
 /// Downloads a file from the `brane-api` "registry" to the client.
 ///
 /// # Arguments
 /// - `name`: The name of the package (container) to download.
 /// - `version`: The version of the package (container) to download. May be 'latest'.
+/// - `range`: The value of the `Range`-header, if any. If given as `bytes=START-`, only the bytes from `START` onwards are sent (with a 206 Partial Content).
 /// - `context`: The Context that describes some properties of the running environment, such as the location where the container images are stored.
 ///
 /// # Returns
-/// A reply with as body the container archive. This archive will likely not be compressed (for now).
+/// A reply with as body the container archive (or the requested part of it). This archive is not compressed (for now), which is advertised in the
+/// [`PackageCompression::HEADER`]-header.
 ///
 /// # Errors
 /// This function errors if resolving a 'latest' version failed, the requested package/version pair did not exist, the Scylla database was unreachable or we failed to read the image file.
-pub async fn download(name: String, version: String, context: Context) -> Result<impl Reply, Rejection> {
+pub async fn download(name: String, version: String, range: Option<String>, context: Context) -> Result<impl Reply, Rejection> {
     info!("Handling GET on '/packages/{}/{}' (i.e., pull package)", name, version);
 
     // Attempt to resolve the version from the Scylla database in the context
     debug!("Resolving version '{}'...", version);
     let version: Version = if version.to_lowercase() == "latest" {
-        let versions = match context.scylla.query("SELECT version FROM brane.packages WHERE name=?", vec![&name]).await {
+        let versions = match query_with_retry("querying versions", || {
+            context.scylla.query("SELECT version FROM brane.packages WHERE name=?", vec![&name])
+        })
+        .await
+        {
             Ok(versions) => versions,
             Err(source) => {
                 fail!(Error::VersionsQueryError { name, source });
@@ -296,27 +616,31 @@ pub async fn download(name: String, version: String, context: Context) -> Result
 
     // With the version resolved, query the filename
     debug!("Retrieving filename for package '{}'@{}", name, version);
-    let file: PathBuf =
-        match context.scylla.query("SELECT file FROM brane.packages WHERE name=? AND version=?", vec![&name, &version.to_string()]).await {
-            Ok(file) => {
-                if let Some(rows) = file.rows {
-                    if rows.is_empty() {
-                        error!("{}", Error::UnknownPackage { name, version });
-                        return Err(warp::reject::not_found());
-                    }
-                    if rows.len() > 1 {
-                        panic!("Database contains {} entries with the same name & version ('{}' & '{}')", rows.len(), name, version);
-                    }
-                    rows[0].columns[0].as_ref().unwrap().as_text().unwrap().into()
-                } else {
+    let sversion: String = version.to_string();
+    let file: PathBuf = match query_with_retry("querying package path", || {
+        context.scylla.query("SELECT file FROM brane.packages WHERE name=? AND version=?", vec![&name, &sversion])
+    })
+    .await
+    {
+        Ok(file) => {
+            if let Some(rows) = file.rows {
+                if rows.is_empty() {
                     error!("{}", Error::UnknownPackage { name, version });
                     return Err(warp::reject::not_found());
                 }
-            },
-            Err(source) => {
-                fail!(Error::PathQueryError { name, version, source });
-            },
-        };
+                if rows.len() > 1 {
+                    panic!("Database contains {} entries with the same name & version ('{}' & '{}')", rows.len(), name, version);
+                }
+                rows[0].columns[0].as_ref().unwrap().as_text().unwrap().into()
+            } else {
+                error!("{}", Error::UnknownPackage { name, version });
+                return Err(warp::reject::not_found());
+            }
+        },
+        Err(source) => {
+            fail!(Error::PathQueryError { name, version, source });
+        },
+    };
 
     // Retrieve the size of the file for the content length
     let length: u64 = match tfs::metadata(&file).await {
@@ -326,11 +650,31 @@ pub async fn download(name: String, version: String, context: Context) -> Result
         },
     };
 
+    // Resolve the part of the file to send
+    let range: Option<(u64, u64)> = match range.as_deref().map(|raw| (raw, parse_range(raw, length))) {
+        Some((_, Ok(Some(range)))) => Some(range),
+        Some((raw, Ok(None))) => {
+            warn!("Ignoring unsupported Range header '{raw}'");
+            None
+        },
+        Some((raw, Err(()))) => {
+            debug!("Range '{raw}' is not satisfiable for file of {length} bytes");
+            let mut response: Response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            response.headers_mut().insert("Content-Range", HeaderValue::from_str(&format!("bytes */{length}")).unwrap());
+            return Ok(response);
+        },
+        None => None,
+    };
+    let (start, end): (u64, u64) = range.unwrap_or((0, length.saturating_sub(1)));
+    let to_send: u64 = if length == 0 { 0 } else { end - start + 1 };
+
     // Open a stream to said file
     debug!("Sending back reply with compressed archive...");
     let (mut body_sender, body): (Sender, Body) = Body::channel();
 
     // Spawn a tokio task that handles the rest while we return the response header
+    let chunk_size: usize = context.download_chunk_size;
     tokio::spawn(async move {
         // Open the archive file to read
         let mut handle: tfs::File = match tfs::File::open(&file).await {
@@ -340,25 +684,16 @@ pub async fn download(name: String, version: String, context: Context) -> Result
             },
         };
 
-        // Read it chunk-by-chunk
-        // (The size of the buffer, like most of the code but edited for not that library cuz it crashes during compilation, has been pulled from https://docs.rs/stream-body/latest/stream_body/)
-        let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
-        loop {
-            // Read the chunk
-            let bytes: usize = match handle.read(&mut buf).await {
-                Ok(bytes) => bytes,
-                Err(source) => {
-                    fail!(Error::FileReadError { path: file, source });
-                },
-            };
-            if bytes == 0 {
-                break;
+        // Skip to the start of the requested range, if any
+        if start > 0 {
+            if let Err(source) = handle.seek(SeekFrom::Start(start)).await {
+                fail!(Error::FileSeekError { path: file, offset: start, source });
             }
+        }
 
-            // Send that with the body
-            if let Err(source) = body_sender.send_data(Bytes::copy_from_slice(&buf[..bytes])).await {
-                fail!(Error::FileSendError { path: file, source });
-            }
+        // Stream it chunk-by-chunk
+        if let Err(err) = stream_file(&file, &mut handle, to_send, chunk_size, &mut body_sender).await {
+            fail!(err);
         }
 
         // Done
@@ -368,7 +703,152 @@ pub async fn download(name: String, version: String, context: Context) -> Result
     // Done (at least, this task is)
     let mut response: Response = Response::new(body);
     response.headers_mut().insert("Content-Disposition", HeaderValue::from_static("attachment; filename=image.tar"));
-    response.headers_mut().insert("Content-Length", HeaderValue::from(length));
+    response.headers_mut().insert("Accept-Ranges", HeaderValue::from_static("bytes"));
+    response.headers_mut().insert(PackageCompression::HEADER, HeaderValue::from_str(&PackageCompression::None.to_string()).unwrap());
+    response.headers_mut().insert("Content-Length", HeaderValue::from(to_send));
+    if range.is_some() {
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+        response.headers_mut().insert("Content-Range", HeaderValue::from_str(&format!("bytes {start}-{end}/{length}")).unwrap());
+    }
+    Ok(response)
+}
+
+/// Returns the metadata of all packages in the `brane-api` "registry" as a list of [`PackageInfo`]s.
+///
+/// Like [`info()`], the reply carries an `ETag` (see [`PackageIndex::etag()`]), such that clients can cache the package index and revalidate
+/// it by sending it back in an `If-None-Match`-header. If it still matches, a `304 Not Modified` is returned instead of the metadata.
+///
+/// # Arguments
+/// - `if_none_match`: The value of the `If-None-Match`-header, if any.
+/// - `context`: The Context that describes some properties of the running environment, such as the Scylla database to query.
+///
+/// # Returns
+/// A reply with as body the [`PackageInfo`]s as a JSON list, or an empty reply with status 304 if the client's index is current.
+///
+/// # Errors
+/// This function errors if the Scylla database was unreachable or a package in it was invalid.
+pub async fn list(if_none_match: Option<String>, context: Context) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on '/packages' (i.e., get package index)");
+
+    // Retrieve all packages
+    debug!("Retrieving metadata for all packages");
+    let rows = match query_with_retry("querying packages", || context.scylla.query("SELECT package FROM brane.packages", &[])).await {
+        Ok(rows) => rows,
+        Err(source) => {
+            fail!(Error::PackagesQueryError { source });
+        },
+    };
+    let mut packages: Vec<PackageInfo> = vec![];
+    for row in rows.rows.unwrap_or_default().into_typed::<(PackageUdt,)>() {
+        match row.map_err(|source| Error::PackagesRowError { source }).and_then(|(row,)| row.try_into()) {
+            Ok(info) => packages.push(info),
+            Err(err) => {
+                fail!(err);
+            },
+        }
+    }
+    let index: PackageIndex = match PackageIndex::from_packages(packages) {
+        Ok(index) => index,
+        Err(source) => {
+            fail!(Error::PackageIndexError { source });
+        },
+    };
+
+    // Only send the metadata if the client's cached index is out-of-date
+    let etag: String = index.etag();
+    let mut response: Response = match &if_none_match {
+        Some(raw) if etag_matches(raw, &etag) => {
+            debug!("Client's package index is current");
+            let mut response: Response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            response
+        },
+        _ => warp::reply::json(&index.packages.values().collect::<Vec<&PackageInfo>>()).into_response(),
+    };
+    response.headers_mut().insert("ETag", HeaderValue::from_str(&etag).unwrap());
+    Ok(response)
+}
+
+/// Returns the metadata of a package in the `brane-api` "registry" as a [`PackageInfo`].
+///
+/// The reply carries an `ETag` (see [`PackageInfo::etag()`]), such that clients can cache the metadata and revalidate it by sending it back in
+/// an `If-None-Match`-header. If it still matches, a `304 Not Modified` is returned instead of the metadata.
+///
+/// # Arguments
+/// - `name`: The name of the package to get the metadata of.
+/// - `version`: The version of the package to get the metadata of. May be 'latest'.
+/// - `if_none_match`: The value of the `If-None-Match`-header, if any.
+/// - `context`: The Context that describes some properties of the running environment, such as the Scylla database to query.
+///
+/// # Returns
+/// A reply with as body the package's [`PackageInfo`] as JSON, or an empty reply with status 304 if the client's version is current.
+///
+/// # Errors
+/// This function errors if the requested package/version pair did not exist, the Scylla database was unreachable or the package in it was
+/// invalid.
+pub async fn info(name: String, version: String, if_none_match: Option<String>, context: Context) -> Result<impl Reply, Rejection> {
+    info!("Handling GET on '/packages/{}/{}/info' (i.e., get package metadata)", name, version);
+
+    // Parse the version, if it's not 'latest'
+    let target: Option<Version> = if version.to_lowercase() == "latest" {
+        None
+    } else {
+        match Version::from_str(&version) {
+            Ok(version) => Some(version),
+            Err(source) => {
+                fail!(Error::VersionParseError { raw: version, source });
+            },
+        }
+    };
+
+    // Find the requested version among all versions of the package
+    debug!("Retrieving metadata for package '{}'@{}", name, version);
+    let rows =
+        match query_with_retry("querying package", || context.scylla.query("SELECT package FROM brane.packages WHERE name=?", vec![&name])).await {
+            Ok(rows) => rows,
+            Err(source) => {
+                fail!(Error::PackageQueryError { name, source });
+            },
+        };
+    let mut package: Option<PackageInfo> = None;
+    for row in rows.rows.unwrap_or_default().into_typed::<(PackageUdt,)>() {
+        let info: PackageInfo = match row.map_err(|source| Error::PackageRowError { name: name.clone(), source }).and_then(|(row,)| row.try_into()) {
+            Ok(info) => info,
+            Err(err) => {
+                fail!(err);
+            },
+        };
+        let newer: bool = match (&target, &package) {
+            (Some(target), _) => info.version == *target,
+            (None, Some(latest)) => info.version > latest.version,
+            (None, None) => true,
+        };
+        if newer {
+            package = Some(info);
+        }
+    }
+    let Some(package) = package else {
+        match target {
+            Some(version) => error!("{}", Error::UnknownPackage { name, version }),
+            None => error!("{}", Error::NoVersionsFound { name }),
+        }
+        return Err(warp::reject::not_found());
+    };
+
+    // Only send the metadata if the client's cached version is out-of-date
+    let etag: Option<String> = package.etag();
+    let mut response: Response = match (&etag, &if_none_match) {
+        (Some(etag), Some(raw)) if etag_matches(raw, etag) => {
+            debug!("Client's metadata of package '{}'@{} is current", package.name, package.version);
+            let mut response: Response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::NOT_MODIFIED;
+            response
+        },
+        _ => warp::reply::json(&package).into_response(),
+    };
+    if let Some(etag) = etag {
+        response.headers_mut().insert("ETag", HeaderValue::from_str(&etag).unwrap());
+    }
     Ok(response)
 }
 
@@ -376,6 +856,8 @@ pub async fn download(name: String, version: String, context: Context) -> Result
 ///
 /// # Arguments
 /// - `package_archive`: The Bytes of the package archive to store somewhere.
+/// - `content_type`: The value of the `Content-Type`-header, if any, which determines how the archive is compressed (see
+///   [`PackageCompression::from_content_type()`]). Assumed to be gzip if omitted.
 /// - `context`: The Context that stores properties about the environment, such as the directory where we store the container files.
 ///
 /// # Returns
@@ -383,7 +865,7 @@ pub async fn download(name: String, version: String, context: Context) -> Result
 ///
 /// # Errors
 /// This function errors if we fail to either write the package info to the Scylla database or the package archive to the local filesystem.
-pub async fn upload<S, B>(package_archive: S, context: Context) -> Result<impl Reply, Rejection>
+pub async fn upload<S, B>(package_archive: S, content_type: Option<String>, context: Context) -> Result<impl Reply, Rejection>
 where
     S: StreamExt<Item = Result<B, warp::Error>> + Unpin,
     B: Buf,
@@ -391,6 +873,19 @@ where
     info!("Handling POST on '/packages' (i.e., upload new package)");
     let mut package_archive = package_archive;
 
+    // Find out how the archive is compressed
+    let compression: PackageCompression = match content_type {
+        Some(raw) => match PackageCompression::from_content_type(&raw) {
+            Some(compression) => compression,
+            None => {
+                error!("{}", Error::UnsupportedContentType { raw });
+                return Ok(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            },
+        },
+        None => PackageCompression::Gzip,
+    };
+    debug!("Package archive is compressed with: {compression}");
+
 
 
     /* Step 0: Load config files */
@@ -429,7 +924,7 @@ where
     let id: String = rand::rng().sample_iter(&Alphanumeric).take(8).map(char::from).collect();
 
     // Attempt to open a new file
-    let tar_path: PathBuf = tempdir_path.join(format!("{id}.tar.gz"));
+    let tar_path: PathBuf = tempdir_path.join(format!("{id}.archive"));
     let mut handle = match tfs::File::create(&tar_path).await {
         Ok(handle) => handle,
         Err(source) => {
@@ -475,8 +970,13 @@ where
         };
 
         // Wrap it in the unarchiver & decompressor
-        let dec: GzipDecoder<BufReader<tfs::File>> = GzipDecoder::new(BufReader::new(handle));
-        let mut tar: Archive<GzipDecoder<_>> = Archive::new(dec);
+        let reader: BufReader<tfs::File> = BufReader::new(handle);
+        let dec: Box<dyn AsyncRead + Send + Unpin> = match compression {
+            PackageCompression::Gzip => Box::new(GzipDecoder::new(reader)),
+            PackageCompression::Zstd => Box::new(ZstdDecoder::new(reader)),
+            PackageCompression::None => Box::new(reader),
+        };
+        let mut tar: Archive<Box<dyn AsyncRead + Send + Unpin>> = Archive::new(dec);
 
         // Iterate over the entries in the stream
         let mut entries: Entries<_> = match tar.entries() {
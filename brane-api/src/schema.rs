@@ -40,6 +40,7 @@ pub struct Package {
     pub version: String,
     pub functions_as_json: Option<String>,
     pub types_as_json: Option<String>,
+    pub labels_as_json: Option<String>,
 }
 
 impl From<PackageUdt> for Package {
@@ -58,6 +59,7 @@ impl From<PackageUdt> for Package {
             version: row.version,
             functions_as_json: Some(row.functions_as_json),
             types_as_json: Some(row.types_as_json),
+            labels_as_json: row.labels_as_json,
         }
     }
 }
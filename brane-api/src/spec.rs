@@ -29,4 +29,6 @@ pub struct Context {
     pub scylla: Arc<Session>,
     /// The proxy client through which we send our requests.
     pub proxy: Arc<ProxyClient>,
+    /// The size (in bytes) of the chunks in which package images are streamed to clients.
+    pub download_chunk_size: usize,
 }
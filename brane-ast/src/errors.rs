@@ -19,6 +19,7 @@ use std::io::Write;
 use brane_dsl::ast::Expr;
 use brane_dsl::spec::MergeStrategy;
 use brane_dsl::{DataType, TextRange};
+use brane_shr::formatters::DidYouMeanFormatter;
 use console::{Style, style};
 use specifications::version::Version;
 
@@ -511,8 +512,13 @@ pub enum ResolveError {
     #[error("Failed to parse package version")]
     VersionParseError { source: specifications::version::ParseError, range: TextRange },
     /// The given package/version pair was not found.
-    #[error("Package '{}' does not exist{}", name, if !version.is_latest() { format!(" or has no version '{version}'") } else { String::new() })]
-    UnknownPackageError { name: String, version: Version, range: TextRange },
+    #[error(
+        "Package '{}' does not exist{}{}",
+        name,
+        if !version.is_latest() { format!(" or has no version '{version}'") } else { String::new() },
+        DidYouMeanFormatter::new(suggestions)
+    )]
+    UnknownPackageError { name: String, version: Version, suggestions: Vec<String>, range: TextRange },
     /// Failed to declare an imported package function
     #[error("Could not import function '{name}' from package '{package_name}'")]
     FunctionImportError { package_name: String, name: String, source: brane_dsl::errors::SymbolTableError, range: TextRange },
@@ -565,7 +571,7 @@ pub enum ResolveError {
     UnknownField { class_name: String, name: String, range: TextRange },
 
     /// A data structure did not have a string literal as 'name' field.
-    #[error("Data class can only take String literals as name")]
+    #[error("Data and IntermediateResult classes can only take String literals as name")]
     DataIncorrectExpr { range: TextRange },
     /// An unknown dataset was references.
     #[error("No location has access to data asset '{name}'")]
@@ -382,12 +382,10 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
 
         Instance { properties, st_entry, .. } => {
             // Note down whether this happens to be a Data or an IntermediateResult
-            let is_data: bool = {
+            // NOTE: Explicit IntermediateResults are used to refer to (possibly multiple) results of earlier tasks by pattern
+            let (is_data, is_result): (bool, bool) = {
                 let entry: Ref<ClassEntry> = st_entry.as_ref().unwrap().borrow();
-                if entry.signature.name == BuiltinClasses::IntermediateResult.name() {
-                    panic!("Didn't expect an explicit IntermediateResult instantiation");
-                }
-                entry.signature.name == BuiltinClasses::Data.name()
+                (entry.signature.name == BuiltinClasses::Data.name(), entry.signature.name == BuiltinClasses::IntermediateResult.name())
             };
 
             // Recurse into the properties to traverse the expressions there
@@ -396,7 +394,7 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
                 pass_expr(&mut p.value, table);
 
                 // While at it, note if we find 'name' - and if we do, its value
-                if is_data && &p.name.value == "name" {
+                if (is_data || is_result) && &p.name.value == "name" {
                     name = Some(if let Expr::Literal { literal: brane_dsl::ast::Literal::String { value, .. } } = &*p.value {
                         value.clone()
                     } else {
@@ -406,11 +404,11 @@ fn pass_expr(expr: &mut Expr, table: &DataState) -> HashSet<Data> {
             }
 
             // If we are a data, then return the name as an identifier
-            if is_data {
-                if let Some(id) = name {
-                    HashSet::from([Data::Data(id)])
-                } else {
-                    panic!("Got a Data/IntermediateResult without a `name`; this should never happen");
+            if is_data || is_result {
+                match name {
+                    Some(id) if is_data => HashSet::from([Data::Data(id)]),
+                    Some(id) => HashSet::from([Data::IntermediateResult(id)]),
+                    None => panic!("Got a Data/IntermediateResult without a `name`; this should never happen"),
                 }
             } else {
                 HashSet::new()
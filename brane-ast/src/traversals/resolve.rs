@@ -22,6 +22,7 @@ use brane_dsl::data_type::{ClassSignature, FunctionSignature};
 use brane_dsl::spec::MergeStrategy;
 use brane_dsl::symbol_table::{ClassEntry, FunctionEntry, SymbolTableEntry, VarEntry};
 use brane_dsl::{DataType, SymbolTable, TextRange};
+use brane_shr::utilities::closest_names;
 use enum_debug::EnumDebug as _;
 use log::trace;
 use specifications::data::DataIndex;
@@ -171,7 +172,10 @@ fn pass_stmt(
             let info: &PackageInfo = match package_index.get(&name.value, if !semver.is_latest() { Some(&semver) } else { None }) {
                 Some(info) => info,
                 None => {
-                    errors.push(Error::UnknownPackageError { name: name.value.clone(), version: semver, range: range.clone() });
+                    // Only suggest other packages if it's not just the version that is unknown
+                    let suggestions: Vec<String> =
+                        if package_index.latest.contains_key(&name.value) { vec![] } else { closest_names(&name.value, package_index.latest.keys()) };
+                    errors.push(Error::UnknownPackageError { name: name.value.clone(), version: semver, suggestions, range: range.clone() });
                     return;
                 },
             };
@@ -714,13 +718,13 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
                 pass_expr(state, data_index, &mut p.value, symbol_table, errors);
             }
 
-            // Finally, check if this dataset exists
-            if entry.signature.name == BuiltinClasses::Data.name() {
+            // Finally, check if this dataset exists (or, for explicit results, if it at least has a literal name to match results with)
+            if entry.signature.name == BuiltinClasses::Data.name() || entry.signature.name == BuiltinClasses::IntermediateResult.name() {
                 // Get the identifier stored within
                 let name: &Expr = properties
                     .iter()
                     .find_map(|p| if &p.name.value == "name" { Some(&p.value) } else { None })
-                    .expect("Builtin class Data has no field 'name' (seems like that's not been properly updated)");
+                    .expect("Builtin class Data/IntermediateResult has no field 'name' (seems like that's not been properly updated)");
                 let sname: &str = match name {
                     Literal { literal: brane_dsl::ast::Literal::String { value, .. } } => value,
                     name => {
@@ -729,6 +733,11 @@ fn pass_expr(state: &CompileState, data_index: &DataIndex, expr: &mut Expr, symb
                     },
                 };
 
+                // Results only exist at runtime, so we cannot check them here
+                if entry.signature.name == BuiltinClasses::IntermediateResult.name() {
+                    return;
+                }
+
                 // Attempt to find it in the data index
                 // let info: &DataInfo = match data_index.get(sname) {
                 //     Some(info) => info,
@@ -867,4 +876,25 @@ pub mod tests {
             println!("{}\n\n", (0..80).map(|_| '-').collect::<String>());
         });
     }
+
+    /// Tests that unknown packages get suggestions, but unknown versions of known packages don't.
+    #[test]
+    fn test_resolve_unknown_package() {
+        let pindex: PackageIndex = create_package_index();
+        let dindex: DataIndex = create_data_index();
+        let code: &str = "import hello_wrld; import hello_world[42.0.0];";
+        let errs: Vec<AstError> = match compile_program_to(code.as_bytes(), &pindex, &dindex, &ParserOptions::bscript(), CompileStage::Resolve) {
+            CompileResult::Err(errs) => errs,
+            _ => panic!("Expected resolving unknown packages to fail"),
+        };
+
+        let suggestions: Vec<Vec<String>> = errs
+            .into_iter()
+            .map(|err| match err {
+                AstError::ResolveError(Error::UnknownPackageError { suggestions, .. }) => suggestions,
+                err => panic!("Expected an unknown package error, got '{err}'"),
+            })
+            .collect();
+        assert_eq!(suggestions, vec![vec!["hello_world".to_string()], vec![]]);
+    }
 }
@@ -20,6 +20,7 @@ use std::process::Command;
 use specifications::arch::Arch;
 
 use crate::errors::BuildError;
+use crate::spec::BuildArg;
 
 
 /***** COMMON MACROS *****/
@@ -46,6 +47,9 @@ macro_rules! writeln_build {
 pub const BRANELET_URL: &str =
     concat!("https://github.com/braneframework/brane/releases/download/", concat!("v", env!("CARGO_PKG_VERSION")), "/branelet");
 
+/// The build arguments that Brane sets itself when building an image, and which can thus not be given by the user.
+pub const RESERVED_BUILD_ARGS: [&str; 2] = ["BRANELET_ARCH", "JUICEFS_ARCH"];
+
 
 
 
@@ -93,7 +97,35 @@ pub fn clean_directory(package_dir: &Path, files: Vec<&str>) {
 ///
 /// # Errors
 /// This function fails if Buildx could not be test-ran, it could not run the Docker build command or the Docker build command did not return a successfull exit code.
+#[inline]
 pub fn build_docker_image<P: AsRef<Path>>(arch: Arch, package_dir: P, tag: String) -> Result<(), BuildError> {
+    build_docker_image_with_context(arch, package_dir, None, tag, &[])
+}
+
+/// Builds the docker image in the given package directory, optionally using a separate build context.
+///
+/// # Generic types
+///  - `P`: The Path-like type of the container directory path.
+///
+/// # Arguments
+///  - `arch`: The architecture for which to build this image.
+///  - `package_dir`: The build directory for this image. It should contain the `Dockerfile`, and will receive the `image.tar`.
+///  - `context`: If given, uses this (absolute) directory as build context instead of `package_dir`. The `./container` directory in the
+///    package directory is then available as the named build context `brane` (i.e., through `COPY --from=brane ...`).
+///  - `tag`: Tag to give to the image so we can find it later (probably just `<package name>:<package version>`)
+///  - `build_args`: Any additional `--build-arg`s to pass to the build (besides the [`RESERVED_BUILD_ARGS`]).
+///
+/// # Errors
+/// This function fails if Buildx could not be test-ran, it could not run the Docker build command or the Docker build command did not return a successfull exit code.
+pub fn build_docker_image_with_context<P: AsRef<Path>>(
+    arch: Arch,
+    package_dir: P,
+    context: Option<&Path>,
+    tag: String,
+    build_args: &[BuildArg],
+) -> Result<(), BuildError> {
+    let package_dir: &Path = package_dir.as_ref();
+
     // Prepare the command to check for buildx (and launch the buildx image, presumably)
     let mut command = Command::new("docker");
     command.arg("buildx");
@@ -123,7 +155,22 @@ pub fn build_docker_image<P: AsRef<Path>>(arch: Arch, package_dir: P, tag: Strin
     command.arg(format!("BRANELET_ARCH={}", arch.brane()));
     command.arg("--build-arg");
     command.arg(format!("JUICEFS_ARCH={}", arch.juicefs()));
-    command.arg(".");
+    for arg in build_args {
+        command.arg("--build-arg");
+        command.arg(arg.to_string());
+    }
+    match context {
+        Some(context) => {
+            command.arg("--file");
+            command.arg("Dockerfile");
+            command.arg("--build-context");
+            command.arg(format!("brane={}", package_dir.join("container").display()));
+            command.arg(context);
+        },
+        None => {
+            command.arg(".");
+        },
+    }
     command.current_dir(package_dir);
     let output = command.status().map_err(|source| BuildError::ImageBuildLaunchError { command: format!("{command:?}"), source })?;
 
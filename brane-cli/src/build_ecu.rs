@@ -10,12 +10,51 @@ use console::style;
 use dialoguer::Confirm;
 use path_clean::clean as clean_path;
 use specifications::arch::Arch;
-use specifications::container::{ContainerInfo, LocalContainerInfo};
+use specifications::container::{ContainerInfo, LocalContainerInfo, PackageManager};
 use specifications::package::PackageInfo;
 
-use crate::build_common::{BRANELET_URL, build_docker_image, clean_directory};
+use crate::build_common::{BRANELET_URL, build_docker_image_with_context, clean_directory};
 use crate::errors::BuildError;
-use crate::utils::ensure_package_dir;
+use crate::spec::{BuildArg, Label};
+use crate::utils::{ensure_package_dir, out_of_space_or};
+
+
+/***** AUXILLARY *****/
+/// Defines the options of building an ECU package that are not about which package to build (see [`handle()`]).
+///
+/// The defaults build the package as described by its container file, cleaning up after ourselves.
+#[derive(Clone, Debug, Default)]
+pub struct BuildOptions {
+    /// Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
+    pub branelet_path: Option<PathBuf>,
+    /// Determines whether or not to keep the build files after building.
+    pub keep_files: bool,
+    /// If true, will not ask to convert CRLF files but instead just do it.
+    pub convert_crlf: bool,
+    /// Any custom labels to attach to the image (and record in the package info).
+    pub labels: Vec<Label>,
+    /// If given, also writes the generated Dockerfile to this path.
+    pub dockerfile_out: Option<PathBuf>,
+    /// If true, only prepares the Dockerfile (written to `dockerfile_out`) and its build context (in the same directory), and skips the actual
+    /// build.
+    pub dry_run: bool,
+    /// If given, wraps this existing Dockerfile (built with the package's context as build context) instead of generating one from the container
+    /// file.
+    pub from_dockerfile: Option<PathBuf>,
+    /// If true, does not install Brane's default dependencies (`fuse` and `iptables`) in the generated Dockerfile, trusting the base image to
+    /// provide them.
+    pub no_default_deps: bool,
+    /// Any additional `KEY=VALUE` build arguments to declare in the generated Dockerfile and pass to the Docker build.
+    pub build_args: Vec<BuildArg>,
+    /// If given, overrides the default arguments that branelet passes to the entrypoint (see `entrypoint.args` in the container file).
+    pub entrypoint_args: Option<Vec<String>>,
+    /// If non-empty, overrides the owners of the package (see `owners` in the container file). If neither gives any owners, the current OS user
+    /// (i.e., `$USER`) is recorded instead.
+    pub owners: Vec<String>,
+}
+
+
+
 
 
 /***** BUILD FUNCTIONS *****/
@@ -23,26 +62,68 @@ use crate::utils::ensure_package_dir;
 ///  - `arch`: The architecture to compile this image for.
 ///  - `context`: The directory to copy additional files (executable, working directory files) from.
 ///  - `file`: Path to the package's main file (a container file, in this case).
-///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
-///  - `keep_files`: Determines whether or not to keep the build files after building.
-///  - `convert_crlf`: If true, will not ask to convert CRLF files but instead just do it.
+///  - `opts`: The [`BuildOptions`] that determine how to build it.
 ///
 /// # Errors
 /// This function may error for many reasons.
-pub async fn handle(
-    arch: Arch,
-    context: PathBuf,
-    file: PathBuf,
-    branelet_path: Option<PathBuf>,
-    keep_files: bool,
-    convert_crlf: bool,
-) -> Result<(), BuildError> {
+pub async fn handle(arch: Arch, context: PathBuf, file: PathBuf, mut opts: BuildOptions) -> Result<(), BuildError> {
     debug!("Building ecu package from container file '{}'...", file.display());
     debug!("Using {} as build context", context.display());
 
     // Read the package into a ContainerInfo.
     let handle = File::open(&file).map_err(|source| BuildError::ContainerInfoOpenError { file: file.clone(), source })?;
-    let document = ContainerInfo::from_reader(handle).map_err(|source| BuildError::ContainerInfoParseError { file: file.clone(), source })?;
+    let mut document = ContainerInfo::from_reader(handle).map_err(|source| BuildError::ContainerInfoParseError { file: file.clone(), source })?;
+    if let Some(entrypoint_args) = opts.entrypoint_args.take() {
+        debug!("Overriding default entrypoint arguments with {:?}", entrypoint_args);
+        document.entrypoint.args = Some(entrypoint_args);
+    }
+    if !opts.owners.is_empty() {
+        debug!("Overriding owners with {:?}", opts.owners);
+        document.owners = Some(std::mem::take(&mut opts.owners));
+    } else if document.owners.as_ref().map(Vec::is_empty).unwrap_or(true) {
+        if let Ok(user) = std::env::var("USER") {
+            debug!("No owners given; defaulting to the current user '{}'", user);
+            document.owners = Some(vec![user]);
+        }
+    }
+
+    // If we're only interested in the Dockerfile, prepare it and everything it refers to next to it, and stop before touching the package
+    // directory
+    if opts.dry_run {
+        let BuildOptions { branelet_path, convert_crlf, labels, dockerfile_out, from_dockerfile, no_default_deps, build_args, .. } = opts;
+        let dockerfile_out: PathBuf = dockerfile_out.unwrap_or_else(|| PathBuf::from("Dockerfile"));
+        let build_dir: &Path = match dockerfile_out.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let dockerfile = match &from_dockerfile {
+            Some(from_dockerfile) => wrap_dockerfile(from_dockerfile, &document, &context, branelet_path.is_some(), &labels)?,
+            None => generate_dockerfile(&document, &context, branelet_path.is_some(), &labels, no_default_deps, &build_args)?,
+        };
+        prepare_directory(&document, dockerfile, &dockerfile_out, branelet_path, &context, build_dir, convert_crlf)?;
+
+        // Tell the user how to build it themselves
+        let mut command: String = format!("docker buildx build --file {}", dockerfile_out.display());
+        for arg in [format!("BRANELET_ARCH={}", arch.brane()), format!("JUICEFS_ARCH={}", arch.juicefs())]
+            .into_iter()
+            .chain(build_args.iter().map(BuildArg::to_string))
+        {
+            command.push_str(&format!(" --build-arg {arg}"));
+        }
+        if from_dockerfile.is_some() {
+            command.push_str(&format!(" --build-context brane={} {}", build_dir.join("container").display(), context.display()));
+        } else {
+            command.push_str(&format!(" {}", build_dir.display()));
+        }
+        println!(
+            "Wrote Dockerfile for container (ECU) package {} to '{}' and its build context to '{}'.",
+            style(&document.name).bold().cyan(),
+            dockerfile_out.display(),
+            build_dir.join("container").display()
+        );
+        println!("Build it with: {}", style(command).bold());
+        return Ok(());
+    }
 
     // Prepare package directory
     let package_dir = ensure_package_dir(&document.name, Some(&document.version), true).map_err(|source| BuildError::PackageDirError { source })?;
@@ -51,7 +132,7 @@ pub async fn handle(
     {
         let _lock = FileLock::lock(&document.name, document.version, package_dir.join(".lock"))
             .map_err(|source| BuildError::LockCreateError { name: document.name.clone(), source })?;
-        build(arch, document, context, &package_dir, branelet_path, keep_files, convert_crlf).await?;
+        build(arch, document, context, &package_dir, opts).await?;
     };
 
     // Done
@@ -67,30 +148,31 @@ pub async fn handle(
 ///  - `document`: The ContainerInfo document describing the package.
 ///  - `context`: The directory to copy additional files (executable, working directory files) from.
 ///  - `package_dir`: The package directory to use as the build folder.
-///  - `branelet_path`: Optional path to a custom branelet executable. If left empty, will pull the standard one from Github instead.
-///  - `keep_files`: Determines whether or not to keep the build files after building.
-///  - `convert_crlf`: If true, will not ask to convert CRLF files but instead just do it.
+///  - `opts`: The [`BuildOptions`] that determine how to build it. Its `dry_run`, `entrypoint_args` and `owners` are ignored, as they have
+///    already been handled by [`handle()`].
 ///
 /// # Errors
 /// This function may error for many reasons.
-async fn build(
-    arch: Arch,
-    document: ContainerInfo,
-    context: PathBuf,
-    package_dir: &Path,
-    branelet_path: Option<PathBuf>,
-    keep_files: bool,
-    convert_crlf: bool,
-) -> Result<(), BuildError> {
+async fn build(arch: Arch, document: ContainerInfo, context: PathBuf, package_dir: &Path, opts: BuildOptions) -> Result<(), BuildError> {
+    let BuildOptions { branelet_path, keep_files, convert_crlf, labels, dockerfile_out, from_dockerfile, no_default_deps, build_args, .. } = opts;
+
     // Prepare the build directory
-    let dockerfile = generate_dockerfile(&document, &context, branelet_path.is_some())?;
-    prepare_directory(&document, dockerfile, branelet_path, &context, package_dir, convert_crlf)?;
+    let dockerfile = match &from_dockerfile {
+        Some(from_dockerfile) => wrap_dockerfile(from_dockerfile, &document, &context, branelet_path.is_some(), &labels)?,
+        None => generate_dockerfile(&document, &context, branelet_path.is_some(), &labels, no_default_deps, &build_args)?,
+    };
+    if let Some(dockerfile_out) = dockerfile_out {
+        write_dockerfile(&dockerfile, &dockerfile_out)?;
+    }
+    prepare_directory(&document, dockerfile, &package_dir.join("Dockerfile"), branelet_path, &context, package_dir, convert_crlf)?;
     debug!("Successfully prepared package directory.");
 
     // Build Docker image
     let tag = format!("{}:{}", document.name, document.version);
     debug!("Building image '{}' in directory '{}'", tag, package_dir.display());
-    match build_docker_image(arch, package_dir, tag) {
+    // A user-provided Dockerfile expects its own build context; our files are then given as a named context
+    let build_context: Option<&Path> = if from_dockerfile.is_some() { Some(context.as_path()) } else { None };
+    match build_docker_image_with_context(arch, package_dir, build_context, tag, &build_args) {
         Ok(_) => {
             println!(
                 "Successfully built version {} of container (ECU) package {}.",
@@ -100,6 +182,7 @@ async fn build(
 
             // Create a PackageInfo and resolve the hash
             let mut package_info = PackageInfo::from(document);
+            package_info.labels = labels.into_iter().map(|l| (l.key, l.value)).collect();
             match brane_tsk::docker::get_digest(package_dir.join("image.tar")).await {
                 Ok(digest) => {
                     package_info.digest = Some(digest);
@@ -157,10 +240,22 @@ async fn build(
 ///  * `document`: The ContainerInfo describing the package to build.
 ///  * `context`: The directory to find the executable in.
 ///  * `override_branelet`: Whether or not to override the branelet executable. If so, assumes the new one is copied to the temporary build folder by the time the DockerFile is run.
+///  * `labels`: Any custom labels to emit as `LABEL` instructions.
+///  * `no_default_deps`: Whether to skip installing the default dependencies (`fuse` and `iptables`). If there are no custom dependencies either, the package manager is not invoked at all.
+///  * `build_args`: Any additional build arguments to declare with `ARG`, both before `FROM` (so they can parameterize the base image) and after it.
+///
+/// The package manager used to install dependencies is taken from the container info, or deduced from the base image if it doesn't say (see [`PackageManager::from_base_image()`]).
 ///
 /// **Returns**  
 /// A String that is the new DockerFile on success, or a BuildError otherwise.
-fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branelet: bool) -> Result<String, BuildError> {
+fn generate_dockerfile(
+    document: &ContainerInfo,
+    context: &Path,
+    override_branelet: bool,
+    labels: &[Label],
+    no_default_deps: bool,
+    build_args: &[BuildArg],
+) -> Result<String, BuildError> {
     let mut contents = String::new();
 
     // Get the base image from the document
@@ -168,11 +263,22 @@ fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branel
 
     // Add default heading
     writeln_build!(contents, "# Generated by Brane")?;
+    for arg in build_args {
+        writeln_build!(contents, "ARG {}", arg.key)?;
+    }
     writeln_build!(contents, "FROM {}", base)?;
 
+    // Add any custom labels
+    for label in labels {
+        writeln_build!(contents, "LABEL {}=\"{}\"", label.key, label.value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))?;
+    }
+
     // Set the architecture build args
     writeln_build!(contents, "ARG BRANELET_ARCH")?;
     writeln_build!(contents, "ARG JUICEFS_ARCH")?;
+    for arg in build_args {
+        writeln_build!(contents, "ARG {}", arg.key)?;
+    }
 
     // Add environment variables
     if let Some(environment) = &document.environment {
@@ -181,24 +287,32 @@ fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branel
         }
     }
 
-    // Add dependencies; write the apt-get RUN command with space for packages
-    if base.starts_with("alpine") {
-        write_build!(contents, "RUN apk add --no-cache ")?;
-    } else {
-        write_build!(
-            contents,
-            "RUN apt-get update && DEBIAN_FRONTEND=noninteractive apt-get install -y --allow-change-held-packages --allow-downgrades "
-        )?;
-    }
-    // Default dependencies
-    write_build!(contents, "fuse iptables ")?;
-    // Custom dependencies
-    if let Some(dependencies) = &document.dependencies {
+    // Add dependencies; the defaults only if not told otherwise
+    let package_manager: PackageManager = document.package_manager.unwrap_or_else(|| PackageManager::from_base_image(&base));
+    debug!("Using package manager '{}' for base image '{}'", package_manager, base);
+    let mut dependencies: Vec<&str> = if no_default_deps || package_manager == PackageManager::Unmanaged { vec![] } else { vec!["fuse", "iptables"] };
+    if let Some(custom) = &document.dependencies {
+        dependencies.extend(custom.iter().map(String::as_str));
+    }
+    if !dependencies.is_empty() {
+        // Write the RUN command with space for packages
+        match package_manager {
+            PackageManager::Apk => write_build!(contents, "RUN apk add --no-cache ")?,
+            PackageManager::Apt => write_build!(
+                contents,
+                "RUN apt-get update && DEBIAN_FRONTEND=noninteractive apt-get install -y --allow-change-held-packages --allow-downgrades "
+            )?,
+            PackageManager::Unmanaged => {
+                return Err(BuildError::NoPackageManager { base, dependencies: dependencies.into_iter().map(String::from).collect() });
+            },
+        }
         for dependency in dependencies {
             write_build!(contents, "{} ", dependency)?;
         }
+        writeln_build!(contents)?;
+    } else {
+        debug!("Skipping dependency installation (no default or custom dependencies)");
     }
-    writeln_build!(contents)?;
 
     // Add the branelet executable
     if override_branelet {
@@ -229,14 +343,7 @@ fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branel
     writeln_build!(contents, "WORKDIR /opt/wd")?;
 
     // Copy the entrypoint executable
-    let entrypoint = clean_path(&document.entrypoint.exec);
-    if entrypoint.to_string_lossy().contains("..") {
-        return Err(BuildError::UnsafePath { path: entrypoint });
-    }
-    let entrypoint = context.join(entrypoint);
-    if !entrypoint.exists() || !entrypoint.is_file() {
-        return Err(BuildError::MissingExecutable { path: entrypoint });
-    }
+    check_entrypoint(document, context)?;
     writeln_build!(contents, "RUN chmod +x /opt/wd/{}", &document.entrypoint.exec)?;
 
     // Add the post-installation script
@@ -254,6 +361,165 @@ fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branel
     Ok(contents)
 }
 
+/// Wraps an existing, user-provided Dockerfile such that it builds a Brane package.
+///
+/// The user's Dockerfile is kept as-is, and instructions are appended to its final stage that add the branelet executable and the package's
+/// working directory, and that set branelet as the entrypoint. Any `ENTRYPOINT` of the user is overridden (with a warning), since branelet
+/// has to be the one starting the package's `entrypoint.exec`. The user's `USER` is restored after our instructions, which need root.
+///
+/// **Arguments**
+///  * `path`: The path to the user's Dockerfile.
+///  * `document`: The ContainerInfo describing the package to build.
+///  * `context`: The directory to find the executable in (also the build context of the user's Dockerfile).
+///  * `override_branelet`: Whether or not to override the branelet executable. If so, assumes the new one is copied to the temporary build folder by the time the DockerFile is run.
+///  * `labels`: Any custom labels to emit as `LABEL` instructions.
+///
+/// **Returns**  
+/// A String that is the new DockerFile on success, or a BuildError otherwise.
+fn wrap_dockerfile(path: &Path, document: &ContainerInfo, context: &Path, override_branelet: bool, labels: &[Label]) -> Result<String, BuildError> {
+    let mut contents: String = fs::read_to_string(path).map_err(|source| BuildError::DockerfileReadError { path: path.into(), source })?;
+
+    // Analyse the final stage of the user's Dockerfile for instructions that would conflict with ours
+    let mut has_from: bool = false;
+    let mut user: Option<String> = None;
+    let mut entrypoint: Option<String> = None;
+    let mut instr: String = String::new();
+    for line in contents.lines() {
+        // Comments and empty lines are ignored, even in the middle of a continued instruction
+        let line: &str = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(line) = line.strip_suffix('\\') {
+            instr.push_str(line);
+            instr.push(' ');
+            continue;
+        }
+        instr.push_str(line);
+
+        let (keyword, args): (&str, &str) = instr.split_once(char::is_whitespace).unwrap_or((&instr, ""));
+        match keyword.to_ascii_uppercase().as_str() {
+            "FROM" => {
+                has_from = true;
+                user = None;
+                entrypoint = None;
+            },
+            "USER" => user = Some(args.trim().into()),
+            "ENTRYPOINT" => entrypoint = Some(args.trim().into()),
+            _ => {},
+        }
+        instr.clear();
+    }
+    if !has_from {
+        return Err(BuildError::DockerfileNoFrom { path: path.into() });
+    }
+    if let Some(entrypoint) = entrypoint {
+        warn!(
+            "Dockerfile '{}' sets 'ENTRYPOINT {}', which is replaced by Brane's branelet; it runs '{}' (the container file's entrypoint) instead",
+            path.display(),
+            entrypoint,
+            document.entrypoint.exec
+        );
+    }
+    if document.base.is_some() || document.dependencies.is_some() {
+        warn!("Ignoring 'base' and 'dependencies' in the container file; these are up to the given Dockerfile '{}'", path.display());
+    }
+    check_entrypoint(document, context)?;
+
+    // Now append our part
+    if !contents.ends_with('\n') {
+        writeln_build!(contents)?;
+    }
+    writeln_build!(contents)?;
+    writeln_build!(contents, "# Added by Brane")?;
+    writeln_build!(contents, "USER root")?;
+    for label in labels {
+        writeln_build!(contents, "LABEL {}=\"{}\"", label.key, label.value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))?;
+    }
+    writeln_build!(contents, "ARG BRANELET_ARCH")?;
+    if let Some(environment) = &document.environment {
+        for (key, value) in environment {
+            writeln_build!(contents, "ENV {}={}", key, value)?;
+        }
+    }
+
+    // Add the branelet executable, from our named build context if it's a custom one
+    if override_branelet {
+        writeln_build!(contents, "COPY --from=brane branelet /branelet")?;
+    } else {
+        writeln_build!(contents, "ADD {}-$BRANELET_ARCH /branelet", BRANELET_URL)?;
+    }
+    writeln_build!(contents, "RUN chmod +x /branelet")?;
+    if let Some(install) = &document.install {
+        for line in install {
+            writeln_build!(contents, "RUN {}", line)?;
+        }
+    }
+
+    // Copy the package files (not as the archive, since `ADD` can't extract from other contexts)
+    writeln_build!(contents, "COPY --from=brane wd /opt/wd")?;
+    writeln_build!(contents, "WORKDIR /opt/wd")?;
+    writeln_build!(contents, "RUN chmod +x /opt/wd/{}", &document.entrypoint.exec)?;
+    if let Some(unpack) = &document.unpack {
+        for line in unpack {
+            writeln_build!(contents, "RUN {}", line)?;
+        }
+    }
+
+    // Restore the user's user, then make sure branelet is what runs (without any user CMD as arguments)
+    if let Some(user) = user {
+        writeln_build!(contents, "USER {}", user)?;
+    }
+    writeln_build!(contents, "ENTRYPOINT [\"/branelet\"]")?;
+    writeln_build!(contents, "CMD []")?;
+
+    // Done!
+    debug!("Using DockerFile:\n\n{}\n{}\n{}\n\n", (0..80).map(|_| '-').collect::<String>(), &contents, (0..80).map(|_| '-').collect::<String>());
+    Ok(contents)
+}
+
+/// Checks that the entrypoint executable of the package does not escape and exists in the given context.
+///
+/// **Arguments**
+///  * `document`: The ContainerInfo describing the package to build.
+///  * `context`: The directory to find the executable in.
+///
+/// **Returns**  
+/// Nothing if the entrypoint is fine, or a BuildError otherwise.
+fn check_entrypoint(document: &ContainerInfo, context: &Path) -> Result<(), BuildError> {
+    let entrypoint = clean_path(&document.entrypoint.exec);
+    if entrypoint.to_string_lossy().contains("..") {
+        return Err(BuildError::UnsafePath { path: entrypoint });
+    }
+    let entrypoint = context.join(entrypoint);
+    if !entrypoint.exists() || !entrypoint.is_file() {
+        return Err(BuildError::MissingExecutable { path: entrypoint });
+    }
+    Ok(())
+}
+
+/// Writes a generated Dockerfile to the given path.
+///
+/// **Arguments**
+///  * `dockerfile`: The generated DockerFile to write.
+///  * `path`: The path of the file to write it to.
+///
+/// **Returns**  
+/// Nothing if the file was written successfully, or a BuildError otherwise.
+fn write_dockerfile(dockerfile: &str, path: &Path) -> Result<(), BuildError> {
+    debug!("Writing Dockerfile to '{}'...", path.display());
+    match File::create(path) {
+        Ok(ref mut handle) => {
+            write!(handle, "{dockerfile}")
+                .map_err(|source| out_of_space_or(path, source, |source| BuildError::DockerfileWriteError { path: path.into(), source }))?;
+        },
+        Err(source) => {
+            return Err(BuildError::DockerfileCreateError { path: path.into(), source });
+        },
+    };
+    Ok(())
+}
+
 /// **Edited: now returning BuildErrors.**
 ///
 /// Prepares the build directory for building the package.
@@ -261,6 +527,7 @@ fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branel
 /// **Arguments**
 ///  * `document`: The ContainerInfo document carrying metadata about the package.
 ///  * `dockerfile`: The generated DockerFile that will be used to build the package.
+///  * `dockerfile_path`: The path to write the DockerFile to (typically `Dockerfile` in the `package_dir`).
 ///  * `branelet_path`: The optional branelet path in case we want it overriden.
 ///  * `context`: The directory to copy additional files (executable, working directory files) from.
 ///  * `package_info`: The generated PackageInfo from the ContainerInfo document.
@@ -272,22 +539,14 @@ fn generate_dockerfile(document: &ContainerInfo, context: &Path, override_branel
 fn prepare_directory(
     document: &ContainerInfo,
     dockerfile: String,
+    dockerfile_path: &Path,
     branelet_path: Option<PathBuf>,
     context: &Path,
     package_dir: &Path,
     convert_crlf: bool,
 ) -> Result<(), BuildError> {
-    // Write Dockerfile to package directory
-    let file_path = package_dir.join("Dockerfile");
-    debug!("Writing Dockerfile to '{}'...", file_path.display());
-    match File::create(&file_path) {
-        Ok(ref mut handle) => {
-            write!(handle, "{dockerfile}").map_err(|source| BuildError::DockerfileWriteError { path: file_path, source })?;
-        },
-        Err(source) => {
-            return Err(BuildError::DockerfileCreateError { path: file_path, source });
-        },
-    };
+    // Write the Dockerfile
+    write_dockerfile(&dockerfile, dockerfile_path)?;
 
     // Create the container directory
     let container_dir = package_dir.join("container");
@@ -302,7 +561,9 @@ fn prepare_directory(
             std::fs::canonicalize(&branelet_path).map_err(|source| BuildError::BraneletCanonicalizeError { path: branelet_path, source })?;
         let target = container_dir.join("branelet");
         debug!("Copying custom branelet '{}' to '{}'...", original.display(), target.display());
-        fs::copy(&original, &target).map_err(|source| BuildError::BraneletCopyError { original, target, source })?;
+        fs::copy(&original, &target).map_err(|source| {
+            out_of_space_or(&target, source, |source| BuildError::BraneletCopyError { original, target: target.clone(), source })
+        })?;
     }
 
     // Create a workdirectory and make sure it's empty
@@ -378,7 +639,7 @@ fn prepare_directory(
                 // Copy only the file
                 debug!("Copying file '{}' to '{}'...", original.display(), target.display());
                 if let Err(source) = fs::copy(&original, &target) {
-                    return Err(BuildError::WdFileCopyError { original, target, source });
+                    return Err(out_of_space_or(&target, source, |source| BuildError::WdFileCopyError { original, target: target.clone(), source }));
                 }
 
                 // Analyse if we have to CRLF-to-LF this file
@@ -496,9 +757,9 @@ fn prepare_directory(
                             }
 
                             // Now write the new buffer to the thing
-                            lf_handle
-                                .write(&lf_buffer[..lf_buffer_len])
-                                .map_err(|source| BuildError::WdFileWriteError { path: lf_path.clone(), source })?;
+                            lf_handle.write(&lf_buffer[..lf_buffer_len]).map_err(|source| {
+                                out_of_space_or(&lf_path, source, |source| BuildError::WdFileWriteError { path: lf_path.clone(), source })
+                            })?;
                             lf_buffer_len = 0;
 
                             // Refresh the input buffer
@@ -22,12 +22,17 @@ use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD;
 use brane_cfg::certs::load_all;
 use brane_shr::formatters::PrettyListFormatter;
+use brane_shr::utilities::closest_names;
+use chrono::{DateTime, Utc};
 use console::{Alignment, pad_str, style};
 use dialoguer::Confirm;
 use enum_debug::EnumDebug;
+use error_trace::ErrorTrace as _;
 use prettytable::Table;
 use prettytable::format::FormatBuilder;
 use rustls::{Certificate, PrivateKey};
+use serde::Serialize;
+use specifications::data::glob_matches;
 use x509_parser::certificate::X509Certificate;
 use x509_parser::extensions::{ParsedExtension, X509Extension};
 use x509_parser::oid_registry::OID_X509_EXT_KEY_USAGE;
@@ -36,7 +41,8 @@ use x509_parser::x509::X509Name;
 
 pub use crate::errors::CertsError as Error;
 use crate::instance::InstanceInfo;
-use crate::utils::{ensure_instances_dir, get_instance_dir};
+use crate::spec::ReportFormat;
+use crate::utils::{ensure_instances_dir, get_instance_dir, get_instance_names};
 
 
 /***** HELPER FUNCTIONS *****/
@@ -52,7 +58,10 @@ fn resolve_instance(name: Option<String>) -> Result<(String, PathBuf), Error> {
         match get_instance_dir(&name) {
             Ok(path) => match path.exists() {
                 true => Ok((name, path)),
-                false => Err(Error::UnknownInstance { name }),
+                false => {
+                    let suggestions: Vec<String> = closest_names(&name, get_instance_names());
+                    Err(Error::UnknownInstance { name, suggestions })
+                },
             },
             Err(source) => Err(Error::InstanceDirError { source }),
         }
@@ -67,139 +76,170 @@ fn resolve_instance(name: Option<String>) -> Result<(String, PathBuf), Error> {
     }
 }
 
-/// Reads a certificate and extracts the issued usage and, if present, the domain for which it is intended.
+/// Resolves the instances to show certificates of.
 ///
 /// # Arguments
-/// - `cert`: The raw Certificate to analyze.
-/// - `path`: The path to this certificate. Only used for debugging purposes.
-/// - `i`: The number of this certificate in that file.
+/// - `instance_name`: The name of the instance to resolve. If omitted, we default to the active instance.
+/// - `all`: If given, ignores `instance_name` and returns all instances instead.
 ///
 /// # Returns
-/// A tuple of the issued usage and the name of the domain for which it is intended (or `None` if the latter was missing).
+/// The names and paths of the resolved instances.
 ///
 /// # Errors
-/// This function may error if we failed to parse the certificate or extract the required fields.
-fn analyse_cert(cert: &Certificate, path: impl Into<PathBuf>, i: usize) -> Result<(CertificateKind, Option<String>), Error> {
-    let path = path.into();
-
-    // Attempt to parse the certificate as a real x509 one
-    let cert: X509Certificate = match X509Certificate::from_der(&cert.0) {
-        Ok((_, cert)) => cert,
-        Err(source) => {
-            return Err(Error::CertParseError { path, i, source });
-        },
-    };
-
-    // Try to find the list of allowed usages
-    let exts: HashMap<_, _> = cert.extensions_map().map_err(|source| Error::CertExtensionsError { path: path.clone(), i, source })?;
-    let usage: &X509Extension = exts.get(&OID_X509_EXT_KEY_USAGE).ok_or_else(|| Error::CertNoKeyUsageError { path: path.clone(), i })?;
-
-    // Attempt to find the CA one
-    let kind: CertificateKind = match usage.parsed_extension() {
-        ParsedExtension::KeyUsage(ext) => {
-            let ds: bool = ext.digital_signature();
-            let cs: bool = ext.crl_sign();
-            if ds && !cs {
-                CertificateKind::Client
-            } else if !ds && cs {
-                CertificateKind::Ca
-            } else if ds && cs {
-                CertificateKind::Both
-            } else {
-                return Err(Error::CertNoUsageError { path, i });
-            }
-        },
-
-        // Error values
-        _ => {
-            unreachable!();
-        },
-    };
-
-    // Now attempt to extract the name from the issuer field
-    let mut domain_name: Option<String> = None;
-    let issuer: &X509Name = cert.issuer();
-    for name in issuer.iter_common_name() {
-        // Get it as a string
-        let name: &str = name.as_str().map_err(|source| Error::CertIssuerCaError { path: path.clone(), i, source })?;
+/// This function may error if we failed to read the instances directory or failed to resolve the given/active instance.
+fn resolve_instances(instance_name: Option<String>, all: bool) -> Result<Vec<(String, PathBuf)>, Error> {
+    if !all {
+        return Ok(vec![resolve_instance(instance_name)?]);
+    }
 
-        // Extract the real name if any
-        if name.len() >= 7 && &name[..7] == "CA for " {
-            domain_name = Some(name[7..].into());
+    // Get the instances dir
+    debug!("Finding instances...");
+    let instances_dir: PathBuf = ensure_instances_dir(true).map_err(|source| Error::InstancesDirError { source })?;
+
+    // Iterate over it
+    let entries: ReadDir =
+        fs::read_dir(&instances_dir).map_err(|source| Error::DirReadError { what: "instances", path: instances_dir.clone(), source })?;
+    let mut instances: Vec<(String, PathBuf)> = Vec::with_capacity(entries.size_hint().1.unwrap_or(entries.size_hint().0));
+    for (i, entry) in entries.enumerate() {
+        // Unwrap the entry
+        let entry: DirEntry =
+            entry.map_err(|source| Error::DirEntryReadError { what: "instances", path: instances_dir.clone(), entry: i, source })?;
+
+        // Do some checks on whether this is an instance or not
+        let entry_path: PathBuf = entry.path();
+        if !entry_path.is_dir() {
+            debug!("Skipping entry '{}' (not a directory)", entry_path.display());
+            continue;
+        }
+        if !entry_path.join("info.yml").is_file() {
+            debug!("Skipping entry '{}' (no nested info.yml file)", entry_path.display());
+            continue;
         }
+
+        // Now add the entry
+        instances.push((entry.file_name().to_string_lossy().into(), entry_path));
     }
 
-    // Done
-    Ok((kind, domain_name))
+    // Return those
+    Ok(instances)
 }
 
+/// Finds the domains for which certificates are defined in the given instance.
+///
+/// # Arguments
+/// - `instance_path`: The path to the instance directory to search.
+///
+/// # Returns
+/// The names of the domains and the paths to their directories (which contain a `ca.pem` and `client-id.pem` file).
+///
+/// # Errors
+/// This function may error if we failed to create or read the instance's certificate directory.
+fn find_domains(instance_path: &Path) -> Result<Vec<(String, PathBuf)>, Error> {
+    debug!("Finding domains in instance '{}'...", instance_path.display());
+
+    // Ensure the certs directory exists
+    let certs_dir: PathBuf = instance_path.join("certs");
+    if !certs_dir.exists() {
+        fs::create_dir_all(&certs_dir).map_err(|source| Error::CertsDirCreateError { path: certs_dir.clone(), source })?;
+    }
 
+    // Iterate over the things in the 'certs' directory
+    let entries: ReadDir =
+        fs::read_dir(&certs_dir).map_err(|source| Error::DirReadError { what: "certificates", path: certs_dir.clone(), source })?;
+    let mut domains: Vec<(String, PathBuf)> = Vec::new();
+    for (i, entry) in entries.enumerate() {
+        // Unwrap the entry
+        let entry = entry.map_err(|source| Error::DirEntryReadError { what: "certificates", path: certs_dir.clone(), entry: i, source })?;
+
+        // Do some checks on whether this is a certificate directory or not
+        let entry_path: PathBuf = entry.path();
+        if !entry_path.is_dir() {
+            debug!("Skipping entry '{}' (not a directory)", entry_path.display());
+            continue;
+        }
+        if !entry_path.join("ca.pem").is_file() {
+            debug!("Skipping entry '{}' (no nested ca.pem file)", entry_path.display());
+            continue;
+        }
+        if !entry_path.join("client-id.pem").is_file() {
+            debug!("Skipping entry '{}' (no nested client-id.pem file)", entry_path.display());
+            continue;
+        }
 
-
-
-/***** HELPER ENUMS *****/
-/// Defines the possible certificate types we are interested in.
-#[derive(Clone, Copy, Debug, EnumDebug, Eq, Hash, PartialEq)]
-enum CertificateKind {
-    /// It's both suited as a CA certificate _and_ a client certificate.
-    Both,
-    /// It's an authority certificate (used to verify the remote's identity)
-    Ca,
-    /// It's a client certificate (used to verify ourselves for the remote)
-    Client,
+        domains.push((entry.file_name().to_string_lossy().into(), entry_path));
+    }
+    Ok(domains)
 }
 
-
-
-
-
-/***** SERVICE FUNCTIONS *****/
-/// Retrieves the path to the certificate directory of the active instance.
+/// Matches the given domain names against the certificate directories in the given instance.
+///
+/// Every name may be a pattern with `*` and `?` wildcards. Names and patterns that match nothing are reported and skipped.
 ///
 /// # Arguments
-/// - `domain`: The name of the domain for which we want to get certificates.
+/// - `instance_path`: The path of the instance to find the certificate directories in.
+/// - `domain_names`: The names or patterns to match.
 ///
 /// # Returns
-/// The path to the directory with the certificates of the active instance.
+/// The name and path of every matched domain, in the order they were matched and without duplicates.
 ///
 /// # Errors
-/// This function may error if there was no active instance or we failed to get/read its directory.
-pub fn get_active_certs_dir(domain: impl AsRef<Path>) -> Result<PathBuf, Error> {
-    // Attempt to get the active link
-    let name = InstanceInfo::get_active_name().map_err(|source| Error::ActiveInstanceReadError { source })?;
-    let active_path: PathBuf = InstanceInfo::get_instance_path(&name).map_err(|source| Error::InstancePathError { name, source })?;
-
-    // Return the path within
-    Ok(active_path.join("certs").join(domain))
+/// This function errors if we failed to read the instance's certificate directory.
+fn match_domains(instance_path: &Path, domain_names: &[String]) -> Result<Vec<(String, PathBuf)>, Error> {
+    // Collect the domains, without requiring them to be complete (we might be removing half-written ones)
+    let certs_dir: PathBuf = instance_path.join("certs");
+    let mut existing: Vec<String> = Vec::new();
+    if certs_dir.exists() {
+        let entries: ReadDir =
+            fs::read_dir(&certs_dir).map_err(|source| Error::DirReadError { what: "certificates", path: certs_dir.clone(), source })?;
+        for (i, entry) in entries.enumerate() {
+            let entry = entry.map_err(|source| Error::DirEntryReadError { what: "certificates", path: certs_dir.clone(), entry: i, source })?;
+            if entry.path().is_dir() {
+                existing.push(entry.file_name().to_string_lossy().into());
+            }
+        }
+    }
+    existing.sort();
+
+    // Match them
+    let mut domains: Vec<(String, PathBuf)> = Vec::new();
+    for pattern in domain_names {
+        let mut matched: bool = false;
+        // Like shells, wildcards don't match hidden directories
+        for name in existing.iter().filter(|name| (!name.starts_with('.') || pattern.starts_with('.')) && glob_matches(pattern, name)) {
+            matched = true;
+            if !domains.iter().any(|(n, _)| n == name) {
+                domains.push((name.clone(), certs_dir.join(name)));
+            }
+        }
+        if !matched {
+            if pattern.contains(['*', '?']) {
+                println!("Pattern {} does not match any domain (skipping)", style(pattern).yellow().bold());
+            } else {
+                println!("Domain {} does not have any certificates (skipping)", style(pattern).yellow().bold());
+            }
+        }
+    }
+    Ok(domains)
 }
 
-
-
-
-
-/***** SUBCOMMANDS *****/
-/// Adds the given certificate(s) as the certificate(s) for the given domain.
+/// Loads the CA certificate, client certificate and client key for a domain from the given files.
 ///
 /// # Arguments
-/// - `instance_name`: The name of the instance for which to add them. If omitted, we should default to the active instance.
-/// - `paths`: The paths of the certificate files to add.
-/// - `domain_name`: The name of the domain to add. If it is not present, then the function is supposed to deduce it from the given certificates.
-/// - `force`: If given, does not ask for permission to override an existing certificate but just does it$^{TM}$.
+/// - `paths`: The paths of the certificate files to load.
+/// - `domain_name`: The name of the domain to load them for. If omitted, it is deduced from the certificates instead.
+///
+/// # Returns
+/// A tuple of the CA certificate, the client certificate, the client key and the name of the domain.
 ///
 /// # Errors
-/// This function errors if we failed to read any of the certificates, parse them, if not all the required certificates were given, if we failed to write them and create the directory structure _or_ if we are asked to deduce the domain name but failed.
-pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, mut domain_name: Option<String>, force: bool) -> Result<(), Error> {
-    info!("Adding certificate file(s) '{:?}'...", paths);
-
-    // Resolve the instance first
-    let (instance_name, instance_path): (String, PathBuf) = resolve_instance(instance_name)?;
-    debug!("Adding for instance: '{}' ({})", instance_name, instance_path.display());
-
+/// This function errors if we failed to read any of the certificates, if not all the required certificates were given, or if we are asked
+/// to deduce the domain name but failed.
+fn load_certs(paths: &[PathBuf], mut domain_name: Option<String>) -> Result<(Certificate, Certificate, PrivateKey, String), Error> {
     // First attempt to load the given certificates using rustls
     let mut ca_cert: Option<Certificate> = None;
     let mut client_cert: Option<Certificate> = None;
     let mut client_key: Option<PrivateKey> = None;
-    for path in &paths {
+    for path in paths {
         debug!("Reading certificate '{}'...", path.display());
 
         // Load any certificate and key we can find in this file
@@ -322,32 +362,22 @@ pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, mut domain_name:
             return Err(Error::NoDomainName);
         },
     };
+    Ok((ca_cert, client_cert, client_key, domain_name))
+}
 
-    // Otherwise, start adding directory structures
-    let certs_path: PathBuf = instance_path.join("certs").join(&domain_name);
-    if certs_path.exists() {
-        if !certs_path.is_dir() {
-            return Err(Error::CertsDirNotADir { path: certs_path });
-        }
-        if !force {
-            // Assert we are allowed to override it
-            debug!("Asking for confirmation...");
-            println!(
-                "A certificate for domain {} in instance {} already exists. Overwrite?",
-                style(&domain_name).cyan().bold(),
-                style(&instance_name).cyan().bold()
-            );
-            let consent: bool = Confirm::new().interact().map_err(|source| Error::ConfirmationError { source })?;
-            if !consent {
-                println!("Not overwriting, aborted.");
-                return Ok(());
-            }
-            fs::remove_dir_all(&certs_path).map_err(|source| Error::CertsDirRemoveError { path: certs_path.clone(), source })?;
-        }
-    }
-
+/// Writes the certificates of a domain to the given directory, creating it if needed.
+///
+/// # Arguments
+/// - `certs_path`: The directory to write the `ca.pem` and `client-id.pem` files to.
+/// - `ca_cert`: The CA certificate to write.
+/// - `client_cert`: The client certificate to write.
+/// - `client_key`: The client key to write (next to the client certificate).
+///
+/// # Errors
+/// This function errors if we failed to create the directory or to write any of the files.
+fn write_certs(certs_path: &Path, ca_cert: &Certificate, client_cert: &Certificate, client_key: &PrivateKey) -> Result<(), Error> {
     debug!("Creating directory '{}'...", certs_path.display());
-    fs::create_dir_all(&certs_path).map_err(|source| Error::CertsDirCreateError { path: certs_path.clone(), source })?;
+    fs::create_dir_all(certs_path).map_err(|source| Error::CertsDirCreateError { path: certs_path.into(), source })?;
 
     // Now write the CA certificates first
     {
@@ -360,7 +390,7 @@ pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, mut domain_name:
         // Write the CA certificate with all the bells and whistles
         writeln!(handle, "-----BEGIN CERTIFICATE-----").map_err(|source| Error::FileWriteError { what: "ca", path: ca_path.clone(), source })?;
 
-        for chunk in STANDARD.encode(ca_cert.0).as_bytes().chunks(64) {
+        for chunk in STANDARD.encode(&ca_cert.0).as_bytes().chunks(64) {
             handle.write(chunk).map_err(|source| Error::FileWriteError { what: "ca", path: ca_path.clone(), source })?;
             writeln!(handle).map_err(|source| Error::FileWriteError { what: "ca", path: ca_path.clone(), source })?;
         }
@@ -384,7 +414,7 @@ pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, mut domain_name:
             source,
         })?;
 
-        for chunk in STANDARD.encode(client_cert.0).as_bytes().chunks(64) {
+        for chunk in STANDARD.encode(&client_cert.0).as_bytes().chunks(64) {
             handle.write(chunk).map_err(|source| Error::FileWriteError { what: "client ID", path: client_path.clone(), source })?;
             writeln!(handle).map_err(|source| Error::FileWriteError { what: "client ID", path: client_path.clone(), source })?;
         }
@@ -401,7 +431,7 @@ pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, mut domain_name:
             source,
         })?;
 
-        for chunk in STANDARD.encode(client_key.0).as_bytes().chunks(64) {
+        for chunk in STANDARD.encode(&client_key.0).as_bytes().chunks(64) {
             handle.write(chunk).map_err(|source| Error::FileWriteError { what: "client ID", path: client_path.clone(), source })?;
             writeln!(handle).map_err(|source| Error::FileWriteError { what: "client ID", path: client_path.clone(), source })?;
         }
@@ -411,22 +441,342 @@ pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, mut domain_name:
             source,
         })?;
     }
+    Ok(())
+}
+
+/// Reads the certificates in the given file and determines how long each of them is still valid.
+///
+/// # Arguments
+/// - `instance`: The name of the instance to which the file belongs.
+/// - `domain`: The name of the domain to which the file belongs.
+/// - `kind`: The kind of certificate(s) in the file (i.e., `ca` or `client`).
+/// - `path`: The path to the file to read.
+/// - `warn_days`: Certificates that expire within this many days are reported as expiring.
+///
+/// # Returns
+/// One [`ExpiryEntry`] per certificate in the file. If the file could not be read or parsed, a single entry with [`ExpiryStatus::ParseError`]
+/// is returned instead, such that broken certificates are reported too.
+fn check_cert_file(instance: &str, domain: &str, kind: &'static str, path: &Path, warn_days: u32) -> Vec<ExpiryEntry> {
+    let entry = |not_after: Option<String>, days_remaining: Option<i64>, status: ExpiryStatus, message: Option<String>| ExpiryEntry {
+        instance: instance.into(),
+        domain: domain.into(),
+        kind,
+        not_after,
+        days_remaining,
+        status,
+        message,
+    };
+
+    // Load the certificates in the file
+    let certs: Vec<Certificate> = match load_all(path) {
+        Ok((certs, _)) if certs.is_empty() => {
+            return vec![entry(None, None, ExpiryStatus::ParseError, Some(format!("No certificates found in '{}'", path.display())))];
+        },
+        Ok((certs, _)) => certs,
+        Err(source) => {
+            let err = Error::PemLoadError { path: path.into(), source };
+            return vec![entry(None, None, ExpiryStatus::ParseError, Some(err.trace().to_string()))];
+        },
+    };
+
+    // Analyse each of them
+    let now: i64 = Utc::now().timestamp();
+    let mut entries: Vec<ExpiryEntry> = Vec::with_capacity(certs.len());
+    for (i, cert) in certs.into_iter().enumerate() {
+        let not_after: i64 = match X509Certificate::from_der(&cert.0) {
+            Ok((_, cert)) => cert.validity().not_after.timestamp(),
+            Err(source) => {
+                let err = Error::CertParseError { path: path.into(), i, source };
+                entries.push(entry(None, None, ExpiryStatus::ParseError, Some(err.trace().to_string())));
+                continue;
+            },
+        };
+
+        let days_remaining: i64 = (not_after - now).div_euclid(86400);
+        let status: ExpiryStatus = if not_after <= now {
+            ExpiryStatus::Expired
+        } else if days_remaining < i64::from(warn_days) {
+            ExpiryStatus::Expiring
+        } else {
+            ExpiryStatus::Ok
+        };
+        let not_after: Option<String> = DateTime::<Utc>::from_timestamp(not_after, 0).map(|not_after| not_after.to_rfc3339());
+        entries.push(entry(not_after, Some(days_remaining), status, None));
+    }
+    entries
+}
+
+/// Reads a certificate and extracts the issued usage and, if present, the domain for which it is intended.
+///
+/// # Arguments
+/// - `cert`: The raw Certificate to analyze.
+/// - `path`: The path to this certificate. Only used for debugging purposes.
+/// - `i`: The number of this certificate in that file.
+///
+/// # Returns
+/// A tuple of the issued usage and the name of the domain for which it is intended (or `None` if the latter was missing).
+///
+/// # Errors
+/// This function may error if we failed to parse the certificate or extract the required fields.
+fn analyse_cert(cert: &Certificate, path: impl Into<PathBuf>, i: usize) -> Result<(CertificateKind, Option<String>), Error> {
+    let path = path.into();
+
+    // Attempt to parse the certificate as a real x509 one
+    let cert: X509Certificate = match X509Certificate::from_der(&cert.0) {
+        Ok((_, cert)) => cert,
+        Err(source) => {
+            return Err(Error::CertParseError { path, i, source });
+        },
+    };
+
+    // Try to find the list of allowed usages
+    let exts: HashMap<_, _> = cert.extensions_map().map_err(|source| Error::CertExtensionsError { path: path.clone(), i, source })?;
+    let usage: &X509Extension = exts.get(&OID_X509_EXT_KEY_USAGE).ok_or_else(|| Error::CertNoKeyUsageError { path: path.clone(), i })?;
+
+    // Attempt to find the CA one
+    let kind: CertificateKind = match usage.parsed_extension() {
+        ParsedExtension::KeyUsage(ext) => {
+            let ds: bool = ext.digital_signature();
+            let cs: bool = ext.crl_sign();
+            if ds && !cs {
+                CertificateKind::Client
+            } else if !ds && cs {
+                CertificateKind::Ca
+            } else if ds && cs {
+                CertificateKind::Both
+            } else {
+                return Err(Error::CertNoUsageError { path, i });
+            }
+        },
+
+        // Error values
+        _ => {
+            unreachable!();
+        },
+    };
+
+    // Now attempt to extract the name from the issuer field
+    let mut domain_name: Option<String> = None;
+    let issuer: &X509Name = cert.issuer();
+    for name in issuer.iter_common_name() {
+        // Get it as a string
+        let name: &str = name.as_str().map_err(|source| Error::CertIssuerCaError { path: path.clone(), i, source })?;
+
+        // Extract the real name if any
+        if name.len() >= 7 && &name[..7] == "CA for " {
+            domain_name = Some(name[7..].into());
+        }
+    }
+
+    // Done
+    Ok((kind, domain_name))
+}
+
+
+
+
+
+/***** HELPER ENUMS *****/
+/// Defines how close to expiry a certificate is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ExpiryStatus {
+    /// The certificate is valid for a while still.
+    Ok,
+    /// The certificate expires soon.
+    Expiring,
+    /// The certificate has expired.
+    Expired,
+    /// The certificate could not be read or parsed.
+    ParseError,
+}
+
+/// Defines the possible certificate types we are interested in.
+#[derive(Clone, Copy, Debug, EnumDebug, Eq, Hash, PartialEq)]
+enum CertificateKind {
+    /// It's both suited as a CA certificate _and_ a client certificate.
+    Both,
+    /// It's an authority certificate (used to verify the remote's identity)
+    Ca,
+    /// It's a client certificate (used to verify ourselves for the remote)
+    Client,
+}
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// A single row in the report of `brane certs check`.
+#[derive(Clone, Debug, Serialize)]
+struct ExpiryEntry {
+    /// The name of the instance the certificate belongs to.
+    instance: String,
+    /// The name of the domain the certificate belongs to.
+    domain: String,
+    /// The kind of the certificate (`ca` or `client`).
+    kind: &'static str,
+    /// The moment after which the certificate is no longer valid, as an RFC 3339 timestamp.
+    not_after: Option<String>,
+    /// The number of (whole) days until the certificate expires. Negative if it already has.
+    days_remaining: Option<i64>,
+    /// How close to expiry the certificate is.
+    status: ExpiryStatus,
+    /// Why the certificate could not be read or parsed, if it couldn't.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+
+
+
+
+/***** SERVICE FUNCTIONS *****/
+/// Retrieves the path to the certificate directory of the active instance.
+///
+/// # Arguments
+/// - `domain`: The name of the domain for which we want to get certificates.
+///
+/// # Returns
+/// The path to the directory with the certificates of the active instance.
+///
+/// # Errors
+/// This function may error if there was no active instance or we failed to get/read its directory.
+pub fn get_active_certs_dir(domain: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    // Attempt to get the active link
+    let name = InstanceInfo::get_active_name().map_err(|source| Error::ActiveInstanceReadError { source })?;
+    let active_path: PathBuf = InstanceInfo::get_instance_path(&name).map_err(|source| Error::InstancePathError { name, source })?;
+
+    // Return the path within
+    Ok(active_path.join("certs").join(domain))
+}
+
+
+
+
+
+/***** SUBCOMMANDS *****/
+/// Adds the given certificate(s) as the certificate(s) for the given domain.
+///
+/// # Arguments
+/// - `instance_name`: The name of the instance for which to add them. If omitted, we should default to the active instance.
+/// - `paths`: The paths of the certificate files to add.
+/// - `domain_name`: The name of the domain to add. If it is not present, then the function is supposed to deduce it from the given certificates.
+/// - `force`: If given, does not ask for permission to override an existing certificate but just does it$^{TM}$.
+///
+/// # Errors
+/// This function errors if we failed to read any of the certificates, parse them, if not all the required certificates were given, if we failed to write them and create the directory structure _or_ if we are asked to deduce the domain name but failed.
+pub fn add(instance_name: Option<String>, paths: Vec<PathBuf>, domain_name: Option<String>, force: bool) -> Result<(), Error> {
+    info!("Adding certificate file(s) '{:?}'...", paths);
+
+    // Resolve the instance first
+    let (instance_name, instance_path): (String, PathBuf) = resolve_instance(instance_name)?;
+    debug!("Adding for instance: '{}' ({})", instance_name, instance_path.display());
+
+    // Load the certificates first
+    let (ca_cert, client_cert, client_key, domain_name): (Certificate, Certificate, PrivateKey, String) = load_certs(&paths, domain_name)?;
+
+    // Next, start adding directory structures
+    let certs_path: PathBuf = instance_path.join("certs").join(&domain_name);
+    if certs_path.exists() {
+        if !certs_path.is_dir() {
+            return Err(Error::CertsDirNotADir { path: certs_path });
+        }
+        if !force {
+            // Assert we are allowed to override it
+            debug!("Asking for confirmation...");
+            println!(
+                "A certificate for domain {} in instance {} already exists. Overwrite?",
+                style(&domain_name).cyan().bold(),
+                style(&instance_name).cyan().bold()
+            );
+            let consent: bool = Confirm::new().interact().map_err(|source| Error::ConfirmationError { source })?;
+            if !consent {
+                println!("Not overwriting, aborted.");
+                return Ok(());
+            }
+            fs::remove_dir_all(&certs_path).map_err(|source| Error::CertsDirRemoveError { path: certs_path.clone(), source })?;
+        }
+    }
+
+    write_certs(&certs_path, &ca_cert, &client_cert, &client_key)?;
 
     // Done!
     println!("Successfully added certificates for domain {} in instance {}", style(domain_name).cyan().bold(), style(instance_name).cyan().bold());
     Ok(())
 }
 
+/// Replaces the certificates of an existing domain with new ones, e.g., after the domain rotated them.
+///
+/// The new certificates are written next to the old ones first, and only swapped in once that succeeded. If swapping fails, the old
+/// certificates are restored.
+///
+/// # Arguments
+/// - `instance_name`: The name of the instance in which to renew them. If omitted, we should default to the active instance.
+/// - `paths`: The paths of the new certificate files.
+/// - `domain_name`: The name of the domain to renew the certificates of.
+///
+/// # Errors
+/// This function errors if the domain has no certificates yet, if we failed to read or parse the new certificates, if not all the required
+/// certificates were given or if we failed to write them.
+pub fn renew(instance_name: Option<String>, paths: Vec<PathBuf>, domain_name: String) -> Result<(), Error> {
+    info!("Renewing certificates of domain '{}' with file(s) '{:?}'...", domain_name, paths);
+
+    // Resolve the instance and the domain in it
+    let (instance_name, instance_path): (String, PathBuf) = resolve_instance(instance_name)?;
+    let certs_path: PathBuf = instance_path.join("certs").join(&domain_name);
+    if !certs_path.is_dir() {
+        return Err(Error::UnknownDomain { instance: instance_name, domain: domain_name });
+    }
+
+    // Load the new certificates; this warns if they appear to be for another domain
+    let (ca_cert, client_cert, client_key, _): (Certificate, Certificate, PrivateKey, String) = load_certs(&paths, Some(domain_name.clone()))?;
+
+    // Write them next to the old ones, which we leave alone if this fails
+    let new_path: PathBuf = instance_path.join("certs").join(format!(".{domain_name}.new"));
+    let old_path: PathBuf = instance_path.join("certs").join(format!(".{domain_name}.old"));
+    for path in [&new_path, &old_path] {
+        if path.exists() {
+            fs::remove_dir_all(path).map_err(|source| Error::CertsDirRemoveError { path: path.clone(), source })?;
+        }
+    }
+    if let Err(err) = write_certs(&new_path, &ca_cert, &client_cert, &client_key) {
+        if let Err(err) = fs::remove_dir_all(&new_path) {
+            warn!("Failed to remove partially written certificates '{}': {}", new_path.display(), err);
+        }
+        return Err(err);
+    }
+
+    // Swap them in, restoring the old ones if that fails
+    debug!("Swapping '{}' for '{}'...", certs_path.display(), new_path.display());
+    fs::rename(&certs_path, &old_path).map_err(|source| Error::CertsDirRenameError { from: certs_path.clone(), to: old_path.clone(), source })?;
+    if let Err(source) = fs::rename(&new_path, &certs_path) {
+        if let Err(err) = fs::rename(&old_path, &certs_path) {
+            error!("Failed to restore old certificates '{}' to '{}': {}", old_path.display(), certs_path.display(), err);
+        }
+        return Err(Error::CertsDirRenameError { from: new_path, to: certs_path, source });
+    }
+    if let Err(err) = fs::remove_dir_all(&old_path) {
+        warn!("Failed to remove old certificates '{}': {}", old_path.display(), err);
+    }
+
+    // Done!
+    println!("Successfully renewed certificates for domain {} in instance {}", style(domain_name).cyan().bold(), style(instance_name).cyan().bold());
+    Ok(())
+}
+
 /// Removes the certificate(s) for the given domain.
 ///
 /// # Arguments
-/// - `domain_names`: The name(s) of the domain(s) for which to remove the certificates.
+/// - `domain_names`: The name(s) of the domain(s) for which to remove the certificates. These may be globs (e.g., `test-*`), which are
+///   matched against the domains in the instance.
 /// - `instance_name`: The name of the instance for which to remove them. If omitted, we should default to the active instance.
 /// - `force`: If given, does not ask for confirmation but just does it$^{TM}$.
+/// - `dry_run`: If given, only prints the directories that would be removed instead of removing them.
 ///
 /// # Errors
 /// This function fails if we failed to find any directories or failed to remove them.
-pub fn remove(domain_names: Vec<String>, instance_name: Option<String>, force: bool) -> Result<(), Error> {
+pub fn remove(domain_names: Vec<String>, instance_name: Option<String>, force: bool, dry_run: bool) -> Result<(), Error> {
     info!("Removing certificate file(s) '{:?}'...", domain_names);
 
     // Do nothing if no names are given
@@ -439,13 +789,33 @@ pub fn remove(domain_names: Vec<String>, instance_name: Option<String>, force: b
     let (instance_name, instance_path): (String, PathBuf) = resolve_instance(instance_name)?;
     debug!("Removing for instance: '{}' ({})", instance_name, instance_path.display());
 
+    // Resolve the (patterns of) names to the certificate directories in the instance
+    let domains: Vec<(String, PathBuf)> = match_domains(&instance_path, &domain_names)?;
+    if domains.is_empty() {
+        println!("No certificates to remove.");
+        return Ok(());
+    }
+
+    // Only show what we would remove, if told to
+    if dry_run {
+        for (name, certs_dir) in domains {
+            println!(
+                "Would remove '{}' (domain {} in instance {})",
+                certs_dir.display(),
+                style(name).cyan().bold(),
+                style(&instance_name).cyan().bold()
+            );
+        }
+        return Ok(());
+    }
+
     // Ask the user for permission, if needed
     if !force {
         debug!("Asking for confirmation...");
         println!(
             "Are you sure you want to remove the certificates for domain{} {}?",
-            if domain_names.len() > 1 { "s" } else { "" },
-            PrettyListFormatter::new(domain_names.iter().map(|n| style(n).bold().cyan()), "and")
+            if domains.len() > 1 { "s" } else { "" },
+            PrettyListFormatter::new(domains.iter().map(|(n, _)| style(n).bold().cyan()), "and")
         );
         let consent: bool = Confirm::new().interact().map_err(|source| Error::ConfirmationError { source })?;
 
@@ -456,18 +826,10 @@ pub fn remove(domain_names: Vec<String>, instance_name: Option<String>, force: b
     }
 
     // We can continue, so let's remove them
-    for name in domain_names {
+    for (name, certs_dir) in domains {
         debug!("Removing certs for domain '{}' in instance '{}'...", name, instance_name);
-
-        // Attempt to remove it if it exists
-        let certs_dir: PathBuf = instance_path.join("certs").join(&name);
-        if certs_dir.exists() {
-            if let Err(err) = fs::remove_dir_all(&certs_dir) {
-                warn!("Failed to remove directory '{}': {} (skipping)", certs_dir.display(), err);
-                continue;
-            }
-        } else {
-            println!("Domain {} does not have any certificates (skipping)", style(name).yellow().bold());
+        if let Err(err) = fs::remove_dir_all(&certs_dir) {
+            warn!("Failed to remove directory '{}': {} (skipping)", certs_dir.display(), err);
             continue;
         }
 
@@ -498,80 +860,12 @@ pub fn list(instance_name: Option<String>, all: bool) -> Result<(), Error> {
     table.set_format(format);
     table.add_row(row!["INSTANCE", "DOMAIN", "CA", "CLIENT"]);
 
-    // Find the instances to show
-    let instances: Vec<(String, PathBuf)> = if all {
-        // Get the instances dir
-        debug!("Finding instances...");
-        let instances_dir: PathBuf = ensure_instances_dir(true).map_err(|source| Error::InstancesDirError { source })?;
-
-        // Iterate over it
-        let entries: ReadDir =
-            fs::read_dir(&instances_dir).map_err(|source| Error::DirReadError { what: "instances", path: instances_dir.clone(), source })?;
-        let mut instances: Vec<(String, PathBuf)> = Vec::with_capacity(entries.size_hint().1.unwrap_or(entries.size_hint().0));
-        for (i, entry) in entries.enumerate() {
-            // Unwrap the entry
-            let entry: DirEntry =
-                entry.map_err(|source| Error::DirEntryReadError { what: "instances", path: instances_dir.clone(), entry: i, source })?;
-
-            // Do some checks on whether this is an instance or not
-            let entry_path: PathBuf = entry.path();
-            if !entry_path.is_dir() {
-                debug!("Skipping entry '{}' (not a directory)", entry_path.display());
-                continue;
-            }
-            if !entry_path.join("info.yml").is_file() {
-                debug!("Skipping entry '{}' (no nested info.yml file)", entry_path.display());
-                continue;
-            }
-
-            // Now add the entry
-            instances.push((entry.file_name().to_string_lossy().into(), entry_path));
-        }
-
-        // Return those
-        instances
-    } else {
-        // Resolve the instance first
-        let (instance_name, instance_path): (String, PathBuf) = resolve_instance(instance_name)?;
-        vec![(instance_name, instance_path)]
-    };
-
-    // Search each of those instances for domains
-    debug!("Finding domains in instances {:?}...", instances.iter().map(|(n, p)| format!("'{}' ({})", n, p.display())).collect::<Vec<String>>());
-    for (name, path) in instances {
-        // Ensure the certs directory exists
-        let certs_dir: PathBuf = path.join("certs");
-        if !certs_dir.exists() {
-            fs::create_dir_all(&certs_dir).map_err(|source| Error::CertsDirCreateError { path: certs_dir.clone(), source })?;
-        }
-
-        // Iterate over the things in the 'certs' directory
-        let entries: ReadDir =
-            fs::read_dir(&certs_dir).map_err(|source| Error::DirReadError { what: "certificates", path: certs_dir.clone(), source })?;
-
-        for (i, entry) in entries.enumerate() {
-            // Unwrap the entry
-            let entry = entry.map_err(|source| Error::DirEntryReadError { what: "certificates", path: certs_dir.clone(), entry: i, source })?;
-
-            // Do some checks on whether this is a certificate directory or not
-            let entry_path: PathBuf = entry.path();
-            if !entry_path.is_dir() {
-                debug!("Skipping entry '{}' (not a directory)", entry_path.display());
-                continue;
-            }
-            let ca_path: PathBuf = entry_path.join("ca.pem");
-            if !ca_path.is_file() {
-                debug!("Skipping entry '{}' (no nested ca.pem file)", entry_path.display());
-                continue;
-            }
-            let client_path: PathBuf = entry_path.join("client-id.pem");
-            if !client_path.is_file() {
-                debug!("Skipping entry '{}' (no nested client-id.pem file)", entry_path.display());
-                continue;
-            }
-
+    // Find the domains in the instances to show
+    for (name, path) in resolve_instances(instance_name, all)? {
+        for (domain_name, domain_path) in find_domains(&path)? {
             // Cast the things to string
-            let domain_name: String = entry.file_name().to_string_lossy().into();
+            let ca_path: PathBuf = domain_path.join("ca.pem");
+            let client_path: PathBuf = domain_path.join("client-id.pem");
             let ca_path: Cow<str> = ca_path.to_string_lossy();
             let client_path: Cow<str> = client_path.to_string_lossy();
 
@@ -588,3 +882,130 @@ pub fn list(instance_name: Option<String>, all: bool) -> Result<(), Error> {
     table.printstd();
     Ok(())
 }
+
+
+
+/// Exports the CA certificate of a domain (e.g., to share it with a peer).
+///
+/// Only the certificates in the stored `ca.pem` are written, re-encoded as PEM; any key material is never exported.
+///
+/// # Arguments
+/// - `domain_name`: The name of the domain of which to export the CA certificate.
+/// - `instance_name`: The name of the instance in which the domain is defined. If omitted, we should default to the active instance.
+/// - `output`: The path to write the certificate to. If omitted, it is written to stdout instead.
+///
+/// # Errors
+/// This function fails if the domain has no certificates, if its `ca.pem` file contains a private key or no certificates at all, or if we
+/// failed to write the result.
+pub fn export(domain_name: String, instance_name: Option<String>, output: Option<PathBuf>) -> Result<(), Error> {
+    info!("Exporting CA certificate of domain '{}'...", domain_name);
+
+    // Resolve the instance and domain first
+    let (instance_name, instance_path): (String, PathBuf) = resolve_instance(instance_name)?;
+    let ca_path: PathBuf = instance_path.join("certs").join(&domain_name).join("ca.pem");
+    if !ca_path.is_file() {
+        return Err(Error::UnknownDomain { instance: instance_name, domain: domain_name });
+    }
+
+    // Load the certificates, refusing to go on if there's a key in there that doesn't belong there
+    debug!("Reading CA certificate '{}'...", ca_path.display());
+    let (certs, keys): (Vec<Certificate>, Vec<PrivateKey>) =
+        load_all(&ca_path).map_err(|source| Error::PemLoadError { path: ca_path.clone(), source })?;
+    if !keys.is_empty() {
+        return Err(Error::CaContainsKey { path: ca_path });
+    }
+    if certs.is_empty() {
+        return Err(Error::NoCaCertInFile { path: ca_path });
+    }
+
+    // Re-encode only the certificates
+    let mut pem: String = String::new();
+    for cert in certs {
+        pem.push_str("-----BEGIN CERTIFICATE-----\n");
+        for chunk in STANDARD.encode(cert.0).as_bytes().chunks(64) {
+            pem.push_str(&String::from_utf8_lossy(chunk));
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+    }
+
+    // Write it where it needs to go
+    match output {
+        Some(output) => {
+            fs::write(&output, pem).map_err(|source| Error::FileWriteError { what: "exported CA", path: output.clone(), source })?;
+            println!(
+                "Exported CA certificate of domain {} in instance {} to '{}'",
+                style(&domain_name).cyan().bold(),
+                style(&instance_name).cyan().bold(),
+                output.display()
+            );
+        },
+        None => print!("{pem}"),
+    }
+    Ok(())
+}
+
+
+
+/// Reports when the certificates of domains expire.
+///
+/// # Arguments
+/// - `instance_name`: The name of the instance of which to check them. If omitted, we should default to the active instance.
+/// - `all`: If given, checks all certificates across instances.
+/// - `warn_days`: Certificates that expire within this many days are reported as expiring.
+/// - `format`: The [`ReportFormat`] in which to print the report.
+///
+/// # Errors
+/// This function fails if we failed to find the instances or domains. Certificates that can't be read or parsed
+/// are reported with a `parse_error` status instead.
+pub fn check(instance_name: Option<String>, all: bool, warn_days: u32, format: ReportFormat) -> Result<(), Error> {
+    info!("Checking certificate expiry...");
+
+    // Collect the report
+    let mut entries: Vec<ExpiryEntry> = Vec::new();
+    for (name, path) in resolve_instances(instance_name, all)? {
+        for (domain_name, domain_path) in find_domains(&path)? {
+            entries.extend(check_cert_file(&name, &domain_name, "ca", &domain_path.join("ca.pem"), warn_days));
+            entries.extend(check_cert_file(&name, &domain_name, "client", &domain_path.join("client-id.pem"), warn_days));
+        }
+    }
+
+    // Print it
+    if format == ReportFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&entries).map_err(|source| Error::ReportSerializeError { source })?);
+        return Ok(());
+    }
+    let table_format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
+    let mut table = Table::new();
+    table.set_format(table_format);
+    table.add_row(row!["INSTANCE", "DOMAIN", "KIND", "NOT AFTER", "DAYS LEFT", "STATUS"]);
+    for entry in &entries {
+        let instance_name: Cow<str> = pad_str(&entry.instance, 20, Alignment::Left, Some(".."));
+        let domain_name: Cow<str> = pad_str(&entry.domain, 20, Alignment::Left, Some(".."));
+        let not_after: &str = entry.not_after.as_deref().unwrap_or("-");
+        let days_remaining: String = entry.days_remaining.map(|days| days.to_string()).unwrap_or_else(|| "-".into());
+        let status: String = match entry.status {
+            ExpiryStatus::Ok => style("ok").green().to_string(),
+            ExpiryStatus::Expiring => style("expiring").yellow().bold().to_string(),
+            ExpiryStatus::Expired => style("expired").red().bold().to_string(),
+            ExpiryStatus::ParseError => style("parse error").red().bold().to_string(),
+        };
+        table.add_row(row![instance_name, domain_name, entry.kind, not_after, days_remaining, status]);
+    }
+    table.printstd();
+
+    // Explain any broken certificates below the table
+    for entry in entries.iter().filter(|entry| entry.message.is_some()) {
+        println!(
+            "{}: {} certificate of domain {} in instance {}: {}",
+            style("error").red().bold(),
+            entry.kind,
+            style(&entry.domain).bold(),
+            style(&entry.instance).bold(),
+            entry.message.as_deref().unwrap_or_default()
+        );
+    }
+
+    // Done
+    Ok(())
+}
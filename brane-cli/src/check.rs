@@ -10,36 +10,231 @@
 //
 //  Description:
 //!   Implements the `brane check`-subcommand, which attempts to validate
-//!   a workflow against remote policy.
+//!   a workflow against remote policy, and the `brane workflow
+//!   explain-plan`-subcommand, which shows how the remote planner would
+//!   run it.
 //
 
+use std::ffi::OsString;
 use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use std::{fs, io};
 
+use brane_ast::ast::Edge;
 use brane_ast::{CompileResult, Workflow};
 use brane_dsl::{Language, ParserOptions};
-use console::style;
-use error_trace::trace;
+use chrono::Local;
+use console::{Term, style};
+use error_trace::{ErrorTrace as _, trace};
 use log::{debug, info};
-use specifications::data::DataIndex;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as _};
+use specifications::data::{AvailabilityKind, DataIndex, PreprocessKind};
 use specifications::driving::{CheckReply, CheckRequest, DriverServiceClient};
 use specifications::package::PackageIndex;
 use specifications::profiling::{self};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
 pub use crate::errors::CheckError as Error;
 use crate::instance::InstanceInfo;
+use crate::utils::{connect_driver, get_data_index, get_package_index};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use brane_ast::DataType;
+    use brane_ast::ast::{ComputeTaskDef, FunctionDef, SymTable, TaskDef};
+    use brane_ast::locations::Locations;
+    use prost::Message as _;
+    use specifications::data::{AccessKind, DataName};
+    use specifications::version::Version;
+
+    use super::*;
+
+
+    /// Returns a compute task called `name` for the planned workflow.
+    fn task(name: &str) -> TaskDef {
+        TaskDef::Compute(ComputeTaskDef {
+            package:      "test".into(),
+            version:      Version::new(1, 0, 0),
+            function:     Box::new(FunctionDef { name: name.into(), args: vec![], ret: DataType::Void }),
+            args_names:   vec![],
+            requirements: HashSet::new(),
+        })
+    }
+
+    #[test]
+    fn test_explain_plan() {
+        console::set_colors_enabled(false);
+
+        // A workflow that transfers the result of a task on one domain to a task on another
+        let mut table: SymTable = SymTable::new();
+        table.tasks = vec![task("preprocess"), task("train")];
+        let graph: Vec<Edge> = vec![
+            Edge::Node {
+                task: 0,
+                locs: Locations::All,
+                at: Some("hospital".into()),
+                input: HashMap::from([(
+                    DataName::Data("patients".into()),
+                    Some(AvailabilityKind::Available { how: AccessKind::File { path: "/data/patients".into() } }),
+                )]),
+                result: Some("result_1".into()),
+                metadata: HashSet::new(),
+                next: 1,
+            },
+            Edge::Node {
+                task: 1,
+                locs: Locations::All,
+                at: Some("umc".into()),
+                input: HashMap::from([
+                    (
+                        DataName::IntermediateResult("result_1".into()),
+                        Some(AvailabilityKind::Unavailable {
+                            how: PreprocessKind::TransferRegistryTar {
+                                location: "hospital".into(),
+                                dataname: DataName::IntermediateResult("result_1".into()),
+                            },
+                        }),
+                    ),
+                    (DataName::Data("weights".into()), None),
+                ]),
+                result: None,
+                metadata: HashSet::new(),
+                next: 2,
+            },
+            Edge::Stop {},
+        ];
+        let workflow: Workflow = Workflow::new("test".into(), table, graph, HashMap::new());
+
+        // The plan survives being sent back in a CheckReply
+        let reply =
+            CheckReply { verdict: true, who: None, reasons: vec![], profile: None, plan: Some(serde_json::to_string(&workflow).unwrap()) };
+        let reply: CheckReply = CheckReply::decode(reply.encode_to_vec().as_slice()).unwrap();
+        let plan: Workflow = serde_json::from_str(reply.plan.as_ref().unwrap()).unwrap();
+        let tasks: Vec<PlannedTask> = planned_tasks(&plan);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].name, "train");
+        assert_eq!(tasks[1].location.as_deref(), Some("umc"));
+        let inputs: Vec<(String, Option<String>)> = vec![("data:weights".into(), None), ("result:result_1".into(), Some("hospital".into()))];
+        assert_eq!(tasks[1].inputs, inputs);
+
+        // Both renderings show the placements and the transfer
+        assert_eq!(
+            plan_json("test", &reply, Some(tasks.as_slice())),
+            serde_json::json!({
+                "workflow": "test",
+                "verdict": true,
+                "who": null,
+                "reasons": [],
+                "tasks": [
+                    {
+                        "task": "preprocess",
+                        "location": "hospital",
+                        "inputs": [{ "name": "data:patients", "transfer_from": null }],
+                        "result": "result_1",
+                    },
+                    {
+                        "task": "train",
+                        "location": "umc",
+                        "inputs": [
+                            { "name": "data:weights", "transfer_from": null },
+                            { "name": "result:result_1", "transfer_from": "hospital" },
+                        ],
+                        "result": null,
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            plan_text("test", &reply, Some(tasks.as_slice())),
+            "Plan for workflow test:
+ 1. Task preprocess runs on hospital
+     - Input data:patients is available locally
+     - Produces result_1
+ 2. Task train runs on umc
+     - Input data:weights is available locally
+     - Input result:result_1 is transferred from hospital
+
+Plan was accepted by all domains
+
+"
+        );
+
+        // A workflow denied while planning has no plan, but the denial is shown
+        let reply = CheckReply { verdict: false, who: Some("umc".into()), reasons: vec!["No training".into()], profile: None, plan: None };
+        assert_eq!(plan_json("test", &reply, None)["tasks"], serde_json::Value::Null);
+        assert_eq!(
+            plan_text("test", &reply, None),
+            "Workflow test could not be planned
+
+Plan was rejected by at least one domain
+ > Checker of domain umc rejected workflow
+   Reasons for denial:
+    - No training
+
+"
+        );
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The time to wait after a change to the watched file before re-checking, such that rapid saves only trigger one check.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+
+
+
+
+/***** HELPERS *****/
+/// Remote state that is reused between subsequent checks of the same workflow (i.e., in `--watch` mode), to keep iterating fast.
+#[derive(Default)]
+struct CheckCache {
+    /// The active instance.
+    instance: Option<InstanceInfo>,
+    /// The package index to compile against, either of the instance or loaded from a file.
+    pindex:   Option<PackageIndex>,
+    /// The data index to compile against, either of the instance or loaded from a file.
+    dindex:   Option<DataIndex>,
+    /// A connection to the driver of the instance.
+    client:   Option<DriverServiceClient>,
+}
+
+/// A single task in a planned workflow, as reported by `brane workflow explain-plan`.
+struct PlannedTask {
+    /// The name of the task.
+    name:     String,
+    /// The domain where the planner placed the task, if any.
+    location: Option<String>,
+    /// The inputs of the task, each with the domain it is transferred from if it is not already at `location`.
+    inputs:   Vec<(String, Option<String>)>,
+    /// The intermediate result the task produces, if any.
+    result:   Option<String>,
+}
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
 /// Compiles the given source text for the given remote instance.
 ///
 /// # Arguments
-/// - `instance`: The [`InstanceInfo`] describing the instance for which we will compile.
+/// - `instance`: The [`InstanceInfo`] describing the instance for which we will compile, or [`None`] if we compile offline.
 /// - `input`: Some description of where the input comes from (used for debugging).
 /// - `source`: The raw source text.
 /// - `language`: The [`Language`] as which to parse the `source` text.
 /// - `user`: An override to set the end user of the workflow result instead of hte instance one.
+/// - `pindex`: The package index to compile against, if we already have it. Otherwise, it is retrieved from the `instance` and stored in it.
+/// - `dindex`: The data index to compile against, if we already have it. Otherwise, it is retrieved from the `instance` and stored in it.
 ///
 /// # Returns
 /// A compiled [`Workflow`].
@@ -48,19 +243,39 @@ use crate::instance::InstanceInfo;
 ///
 /// # Errors
 /// This function errors if we failed to get remote packages/datasets, or if the input was not valid BraneScript/Bakery.
-async fn compile(instance: &InstanceInfo, input: &str, source: String, language: Language, user: Option<String>) -> Result<Workflow, Error> {
+async fn compile(
+    instance: Option<&InstanceInfo>,
+    input: &str,
+    source: String,
+    language: Language,
+    user: Option<String>,
+    pindex: &mut Option<PackageIndex>,
+    dindex: &mut Option<DataIndex>,
+) -> Result<Workflow, Error> {
     // Read the package index from the remote first
-    let url: String = format!("{}/graphql", instance.api);
-    debug!("Retrieving package index from '{url}'");
-    let pindex: PackageIndex = brane_tsk::api::get_package_index(&url).await.map_err(|source| Error::PackageIndexRetrieve { url, source })?;
+    let pindex: &PackageIndex = match (pindex, instance) {
+        (Some(pindex), _) => pindex,
+        (pindex, Some(instance)) => {
+            let url: String = format!("{}/graphql", instance.api);
+            debug!("Retrieving package index from '{url}'");
+            pindex.insert(get_package_index(&url).await.map_err(|source| Error::PackageIndexRetrieve { url, source })?)
+        },
+        (None, None) => unreachable!("Offline checks are only done with both indices given"),
+    };
 
     // Next up, the data index
-    let url: String = format!("{}/data/info", instance.api);
-    debug!("Retrieving data index from '{url}'");
-    let dindex: DataIndex = brane_tsk::api::get_data_index(&url).await.map_err(|source| Error::DataIndexRetrieve { url, source })?;
+    let dindex: &DataIndex = match (dindex, instance) {
+        (Some(dindex), _) => dindex,
+        (dindex, Some(instance)) => {
+            let url: String = format!("{}/data/info", instance.api);
+            debug!("Retrieving data index from '{url}'");
+            dindex.insert(get_data_index(&url).await.map_err(|source| Error::DataIndexRetrieve { url, source })?)
+        },
+        (None, None) => unreachable!("Offline checks are only done with both indices given"),
+    };
 
     // Hit the Brane compiler
-    match brane_ast::compile_program(source.as_bytes(), &pindex, &dindex, &ParserOptions::new(language)) {
+    match brane_ast::compile_program(source.as_bytes(), pindex, dindex, &ParserOptions::new(language)) {
         CompileResult::Workflow(mut wf, warns) => {
             // Emit the warnings before continuing
             for warn in warns {
@@ -68,7 +283,7 @@ async fn compile(instance: &InstanceInfo, input: &str, source: String, language:
             }
 
             // Inject a user
-            wf.user = Arc::new(Some(user.unwrap_or_else(|| instance.user.clone())));
+            wf.user = Arc::new(user.or_else(|| instance.map(|instance| instance.user.clone())));
 
             // OK
             Ok(wf)
@@ -90,98 +305,266 @@ async fn compile(instance: &InstanceInfo, input: &str, source: String, language:
     }
 }
 
+/// Reads the source text of a workflow.
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+///
+/// # Returns
+/// A tuple of a description of the input (used for debugging) and the source text itself.
+///
+/// # Errors
+/// This function errors if we failed to read the input.
+fn load_input(file: &str) -> Result<(String, String), Error> {
+    debug!("Loading input from '{file}'...");
+    if file == "-" {
+        // Read from stdin
+        let mut source: String = String::new();
+        io::stdin().read_to_string(&mut source).map_err(|source| Error::InputStdinRead { source })?;
+        Ok(("<stdin>".into(), source))
+    } else {
+        // Read from a file
+        match fs::read_to_string(file) {
+            Ok(source) => Ok((file.into(), source)),
+            Err(err) => Err(Error::InputFileRead { path: file.into(), source: err }),
+        }
+    }
+}
+
+/// Collects the tasks of a planned workflow, in the order in which they appear in the graph (followed by those in any function bodies).
+///
+/// # Arguments
+/// - `workflow`: The planned [`Workflow`] to collect the tasks of.
+///
+/// # Returns
+/// A list of [`PlannedTask`]s describing where each task runs and which data is moved for it.
+fn planned_tasks(workflow: &Workflow) -> Vec<PlannedTask> {
+    let mut funcs: Vec<(&usize, &Vec<Edge>)> = workflow.funcs.iter().collect();
+    funcs.sort_by_key(|(id, _)| **id);
 
+    let mut tasks: Vec<PlannedTask> = Vec::new();
+    for edge in workflow.graph.iter().chain(funcs.into_iter().flat_map(|(_, edges)| edges)) {
+        let Edge::Node { task, at, input, result, .. } = edge else { continue };
 
+        let mut inputs: Vec<(String, Option<String>)> = input
+            .iter()
+            .map(|(name, avail)| match avail {
+                Some(AvailabilityKind::Unavailable { how: PreprocessKind::TransferRegistryTar { location, .. } }) => {
+                    (name.to_string(), Some(location.clone()))
+                },
+                Some(AvailabilityKind::Available { .. }) | None => (name.to_string(), None),
+            })
+            .collect();
+        inputs.sort();
+        tasks.push(PlannedTask {
+            name: workflow.table.tasks.get(*task).map(|def| def.name()).unwrap_or("<unknown>").into(),
+            location: at.clone(),
+            inputs,
+            result: result.clone(),
+        });
+    }
+    tasks
+}
 
+/// Renders the plan of a workflow as reported by `brane workflow explain-plan --json`.
+///
+/// # Arguments
+/// - `workflow_id`: The identifier of the planned workflow.
+/// - `reply`: The [`CheckReply`] with the verdict on the plan.
+/// - `tasks`: The [`PlannedTask`]s in the plan, or [`None`] if the workflow could not be planned.
+///
+/// # Returns
+/// A JSON object with the verdict and the placement and transfers of every task.
+fn plan_json(workflow_id: &str, reply: &CheckReply, tasks: Option<&[PlannedTask]>) -> serde_json::Value {
+    let tasks: Option<Vec<serde_json::Value>> = tasks.map(|tasks| {
+        tasks
+            .iter()
+            .map(|task| {
+                let inputs: Vec<serde_json::Value> =
+                    task.inputs.iter().map(|(name, from)| serde_json::json!({ "name": name, "transfer_from": from })).collect();
+                serde_json::json!({
+                    "task": task.name,
+                    "location": task.location,
+                    "inputs": inputs,
+                    "result": task.result,
+                })
+            })
+            .collect()
+    });
+    serde_json::json!({
+        "workflow": workflow_id,
+        "verdict": reply.verdict,
+        "who": reply.who,
+        "reasons": reply.reasons,
+        "tasks": tasks,
+    })
+}
 
-/***** LIBRARY *****/
-/// Handles the `brane check`-subcommand, which attempts to validate a workflow against remote policy.
+/// Renders the plan of a workflow as reported by `brane workflow explain-plan`.
+///
+/// # Arguments
+/// - `workflow_id`: The identifier of the planned workflow.
+/// - `reply`: The [`CheckReply`] with the verdict on the plan.
+/// - `tasks`: The [`PlannedTask`]s in the plan, or [`None`] if the workflow could not be planned.
+///
+/// # Returns
+/// The (styled) text explaining where every task runs, which data is moved for it and why the plan was accepted or rejected.
+fn plan_text(workflow_id: &str, reply: &CheckReply, tasks: Option<&[PlannedTask]>) -> String {
+    let mut text: String = String::new();
+
+    // Print the placements and transfers
+    match tasks {
+        Some([]) => text.push_str(&format!("Workflow {} calls no tasks\n", style(workflow_id).bold().cyan())),
+        Some(tasks) => {
+            text.push_str(&format!("Plan for workflow {}:\n", style(workflow_id).bold().cyan()));
+            for (i, task) in tasks.iter().enumerate() {
+                text.push_str(&format!(
+                    " {}. Task {} runs on {}\n",
+                    i + 1,
+                    style(&task.name).bold(),
+                    task.location.as_ref().map(|loc| style(loc.as_str()).bold().cyan()).unwrap_or_else(|| style("<unplanned>").dim())
+                ));
+                for (name, from) in &task.inputs {
+                    match from {
+                        Some(from) => {
+                            text.push_str(&format!("     - Input {} is transferred from {}\n", style(name).bold(), style(from).bold().cyan()))
+                        },
+                        None => text.push_str(&format!("     - Input {} is available locally\n", style(name).bold())),
+                    }
+                }
+                if let Some(result) = &task.result {
+                    text.push_str(&format!("     - Produces {}\n", style(result).bold()));
+                }
+            }
+        },
+        None => text.push_str(&format!("Workflow {} could not be planned\n", style(workflow_id).bold().cyan())),
+    }
+    text.push('\n');
+
+    // Explain the verdict
+    if reply.verdict {
+        text.push_str(&format!("Plan was {} by all domains\n", style("accepted").bold().green()));
+    } else {
+        text.push_str(&format!("Plan was {} by at least one domain\n", style("rejected").bold().red()));
+        if let Some(who) = &reply.who {
+            text.push_str(&format!(" > Checker of domain {} rejected workflow\n", style(who).bold().cyan()));
+            if !reply.reasons.is_empty() {
+                text.push_str("   Reasons for denial:\n");
+                for reason in &reply.reasons {
+                    text.push_str(&format!("    - {}\n", style(reason).bold()));
+                }
+            }
+        }
+    }
+    text.push('\n');
+    text
+}
+
+/// Prints the local and, if any, remote profile timings of a check.
+///
+/// # Arguments
+/// - `prof`: The local [`ProfileScope`](profiling::ProfileScope) to print.
+/// - `remote`: The serialized remote profile as sent back in the [`CheckReply`], if any.
+fn print_profile(prof: profiling::ProfileScope, remote: Option<String>) {
+    println!();
+    println!("{}", (0..80).map(|_| '-').collect::<String>());
+    println!("LOCAL PROFILE RESULTS:");
+    println!("{}", prof.display());
+    if let Some(remote) = remote {
+        // Attempt to parse it
+        match serde_json::from_str::<profiling::ProfileScope>(&remote) {
+            Ok(remote) => {
+                // Print
+                println!();
+                println!("REMOTE PROFILE RESULTS:");
+                println!("{}", remote.display());
+            },
+            Err(err) => warn!("{}", trace!(("Failed to deserialize profile information in CheckReply"), err)),
+        }
+    }
+    println!("{}", (0..80).map(|_| '-').collect::<String>());
+    println!();
+
+    // Drop it to avoid writing it again
+    std::mem::forget(prof);
+}
+
+/// Compiles the given workflow and checks it against the policy in the active instance, printing the verdict.
 ///
 /// # Arguments
 /// - `file`: The path to the file to load as input. `-` means stdin.
 /// - `language`: The [`Language`] of the input file.
 /// - `user`: An override for the user in the instance file, if any.
 /// - `profile`: If true, show profile timings of the request if available.
+/// - `offline`: If true, only compiles the workflow against the indices in the `cache` without ever touching the instance. This means that
+///   policy is not checked.
+/// - `cache`: Any remote state retrieved by previous checks, which is reused. Anything not in it yet is retrieved and added.
 ///
 /// # Errors
 /// This function errors if we failed to perform the check.
-pub async fn handle(file: String, language: Language, user: Option<String>, profile: bool) -> Result<(), Error> {
-    info!("Handling 'brane check {}'", if file == "-" { "<stdin>" } else { file.as_str() });
-
-
+async fn check(file: &str, language: Language, user: Option<String>, profile: bool, offline: bool, cache: &mut CheckCache) -> Result<(), Error> {
     /***** PREPARATION *****/
     let prof: profiling::ProfileScope = profiling::ProfileScope::new("Local preparation");
 
     // Resolve the input file to a source string
-    debug!("Loading input from '{file}'...");
-    let load = prof.time("Input loading");
-    let (input, source): (String, String) = if file == "-" {
-        // Read from stdin
-        let mut source: String = String::new();
-        io::stdin().read_to_string(&mut source).map_err(|source| Error::InputStdinRead { source })?;
-        ("<stdin>".into(), source)
-    } else {
-        // Read from a file
-        match fs::read_to_string(&file) {
-            Ok(source) => (file, source),
-            Err(err) => return Err(Error::InputFileRead { path: file.into(), source: err }),
-        }
-    };
-    load.stop();
+    let (input, source): (String, String) = prof.time_func("Input loading", || load_input(file))?;
 
-    // Get the current instance
-    debug!("Retrieving active instance info...");
-    let instance: InstanceInfo =
-        prof.time_func("Instance resolution", InstanceInfo::from_active_path).map_err(|source| Error::ActiveInstanceInfoLoad { source })?;
+    // Get the current instance, unless we don't need it
+    let instance: Option<&InstanceInfo> = match &mut cache.instance {
+        _ if offline => None,
+        Some(instance) => Some(instance),
+        instance => {
+            debug!("Retrieving active instance info...");
+            Some(instance.insert(
+                prof.time_func("Instance resolution", InstanceInfo::from_active_path).map_err(|source| Error::ActiveInstanceInfoLoad { source })?,
+            ))
+        },
+    };
 
     // Attempt to compile the input
     debug!("Compiling source text to Brane WIR...");
     let workflow: Workflow = prof
-        .time_fut("Workflow compilation", compile(&instance, &input, source, language, user))
+        .time_fut("Workflow compilation", compile(instance, &input, source, language, user, &mut cache.pindex, &mut cache.dindex))
         .await
         .map_err(|source| Error::WorkflowCompile { input: input.clone(), source: Box::new(source) })?;
 
+    // Without an instance, compiling is all we can do
+    let Some(instance) = instance else {
+        if profile {
+            print_profile(prof, None);
+        }
+        println!(
+            "Workflow {} {} against the given package and data index (policy is not checked without an instance)",
+            style(&workflow.id).bold().cyan(),
+            style("compiles").bold().green()
+        );
+        println!();
+        return Ok(());
+    };
+
     let sworkflow: String =
         prof.time_func("Workflow serialization", || serde_json::to_string(&workflow)).map_err(|source| Error::WorkflowSerialize { input, source })?;
 
     // Connect to the driver
     debug!("Connecting to driver '{}'...", instance.drv);
     let rem = prof.time("Driver time");
-    let mut client: DriverServiceClient = DriverServiceClient::connect(instance.drv.to_string())
-        .await
-        .map_err(|source| Error::DriverConnect { address: instance.drv.clone(), source })?;
+    let client: &mut DriverServiceClient = match &mut cache.client {
+        Some(client) => client,
+        client => client
+            .insert(connect_driver(instance.drv.to_string()).await.map_err(|source| Error::DriverConnect { address: instance.drv.clone(), source })?),
+    };
 
     // Send the request
     debug!("Sending check request to driver '{}' and awaiting response...", instance.drv);
     let res: CheckReply = match client.check(CheckRequest { workflow: sworkflow }).await {
         Ok(res) => res.into_inner(),
-        Err(source) => return Err(Error::DriverCheck { address: instance.drv, source }),
+        Err(source) => return Err(Error::DriverCheck { address: instance.drv.clone(), source }),
     };
     rem.stop();
 
     // FIRST: Print profile information if available
     if profile {
-        println!();
-        println!("{}", (0..80).map(|_| '-').collect::<String>());
-        println!("LOCAL PROFILE RESULTS:");
-        println!("{}", prof.display());
-        if let Some(prof) = res.profile {
-            // Attempt to parse it
-            match serde_json::from_str::<profiling::ProfileScope>(&prof) {
-                Ok(prof) => {
-                    // Print
-                    println!();
-                    println!("REMOTE PROFILE RESULTS:");
-                    println!("{}", prof.display());
-                },
-                Err(err) => warn!("{}", trace!(("Failed to deserialize profile information in CheckReply"), err)),
-            }
-        }
-        println!("{}", (0..80).map(|_| '-').collect::<String>());
-        println!();
-
-        // Drop both of them to avoid writing them again
-        std::mem::forget(prof);
+        print_profile(prof, res.profile);
     }
 
     // Consider the verdict
@@ -205,3 +588,161 @@ pub async fn handle(file: String, language: Language, user: Option<String>, prof
     // Either way, the request itself was a success
     Ok(())
 }
+
+
+
+
+
+/***** LIBRARY *****/
+/// Handles the `brane check`-subcommand, which attempts to validate a workflow against remote policy.
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+/// - `language`: The [`Language`] of the input file.
+/// - `user`: An override for the user in the instance file, if any.
+/// - `profile`: If true, show profile timings of the request if available.
+/// - `package_index`: If given, compiles against the package index in this file instead of the instance's.
+/// - `data_index`: If given, compiles against the data index in this file instead of the instance's. If `package_index` is given too, the
+///   instance is not used at all, meaning that the workflow is only compiled and policy is not checked.
+/// - `watch`: If true, keeps watching `file` and re-checks it whenever it changes, until Ctrl+C is pressed. The instance's package and
+///   data index are only retrieved once in this mode.
+///
+/// # Errors
+/// This function errors if we failed to perform the check. In `watch` mode, failed checks are reported instead, and this function only
+/// errors if we failed to watch the file.
+pub async fn handle(
+    file: String,
+    language: Language,
+    user: Option<String>,
+    profile: bool,
+    package_index: Option<PathBuf>,
+    data_index: Option<PathBuf>,
+    watch: bool,
+) -> Result<(), Error> {
+    info!("Handling 'brane check {}'", if file == "-" { "<stdin>" } else { file.as_str() });
+
+    // Load any indices given locally
+    let mut cache: CheckCache = CheckCache::default();
+    if let Some(path) = package_index {
+        debug!("Loading package index from '{}'...", path.display());
+        cache.pindex = Some(PackageIndex::from_path(&path).map_err(|source| Error::PackageIndexLoad { path, source })?);
+    }
+    if let Some(path) = data_index {
+        debug!("Loading data index from '{}'...", path.display());
+        cache.dindex = Some(DataIndex::from_path(&path).map_err(|source| Error::DataIndexLoad { path, source })?);
+    }
+    let offline: bool = cache.pindex.is_some() && cache.dindex.is_some();
+
+    if !watch {
+        return check(&file, language, user, profile, offline, &mut cache).await;
+    }
+    if file == "-" {
+        return Err(Error::WatchStdin);
+    }
+
+    // Watch the file's directory instead of the file itself, as editors tend to replace files on save
+    let path: PathBuf = PathBuf::from(&file);
+    let dir: &Path = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let (tx, mut rx): (UnboundedSender<()>, UnboundedReceiver<()>) = mpsc::unbounded_channel();
+    let name: Option<OsString> = path.file_name().map(|name| name.to_os_string());
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+        Ok(event) => {
+            if (event.kind.is_create() || event.kind.is_modify()) && event.paths.iter().any(|path| path.file_name() == name.as_deref()) {
+                // Only fails if we're done watching anyway
+                let _ = tx.send(());
+            }
+        },
+        Err(err) => warn!("{}", trace!(("Failed to watch for changes"), err)),
+    })
+    .map_err(|source| Error::WatcherCreate { source })?;
+    watcher.watch(dir, RecursiveMode::NonRecursive).map_err(|source| Error::Watch { path: dir.into(), source })?;
+
+    // Keep checking until we're told to stop
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        // Failing checks are the point of watching, so report them instead of quitting
+        tokio::select! {
+            res = check(&file, language, user.clone(), profile, offline, &mut cache) => {
+                if let Err(err) = res {
+                    error!("{}", err.trace());
+                }
+            },
+            res = &mut ctrl_c => return res.map_err(|source| Error::CtrlC { source }),
+        }
+        println!("Watching '{}' for changes (press Ctrl+C to stop)...", style(&file).bold());
+
+        // Wait for a change, and then until the changes settle down
+        tokio::select! {
+            change = rx.recv() => {
+                if change.is_none() {
+                    return Ok(());
+                }
+            },
+            res = &mut ctrl_c => return res.map_err(|source| Error::CtrlC { source }),
+        }
+        while let Ok(Some(())) = tokio::time::timeout(WATCH_DEBOUNCE, rx.recv()).await {}
+
+        // Start with a fresh screen for the new results
+        if let Err(err) = Term::stdout().clear_screen() {
+            debug!("Failed to clear the terminal: {err}");
+        }
+        println!("[{}] '{}' changed; checking again...", Local::now().format("%H:%M:%S"), style(&file).bold());
+    }
+}
+
+/// Handles the `brane workflow explain-plan`-subcommand, which has the remote planner plan a workflow and prints the result without executing
+/// it.
+///
+/// # Arguments
+/// - `file`: The path to the file to load as input. `-` means stdin.
+/// - `language`: The [`Language`] of the input file.
+/// - `user`: An override for the user in the instance file, if any.
+/// - `json`: If true, prints the plan as JSON instead of as text.
+///
+/// # Errors
+/// This function errors if we failed to compile the workflow or to have it planned by the instance. A workflow being denied is not an
+/// error; its denial is printed as part of the plan instead.
+pub async fn explain_plan(file: String, language: Language, user: Option<String>, json: bool) -> Result<(), Error> {
+    info!("Handling 'brane workflow explain-plan {}'", if file == "-" { "<stdin>" } else { file.as_str() });
+
+    // Compile the workflow against the active instance
+    let (input, source): (String, String) = load_input(&file)?;
+    debug!("Retrieving active instance info...");
+    let instance: InstanceInfo = InstanceInfo::from_active_path().map_err(|source| Error::ActiveInstanceInfoLoad { source })?;
+    debug!("Compiling source text to Brane WIR...");
+    let workflow: Workflow = compile(Some(&instance), &input, source, language, user, &mut None, &mut None)
+        .await
+        .map_err(|source| Error::WorkflowCompile { input: input.clone(), source: Box::new(source) })?;
+    let sworkflow: String = serde_json::to_string(&workflow).map_err(|source| Error::WorkflowSerialize { input, source })?;
+
+    // Have the driver plan (and check) it
+    debug!("Connecting to driver '{}'...", instance.drv);
+    let mut client: DriverServiceClient =
+        connect_driver(instance.drv.to_string()).await.map_err(|source| Error::DriverConnect { address: instance.drv.clone(), source })?;
+    debug!("Sending check request to driver '{}' and awaiting response...", instance.drv);
+    let res: CheckReply = match client.check(CheckRequest { workflow: sworkflow }).await {
+        Ok(res) => res.into_inner(),
+        Err(source) => return Err(Error::DriverCheck { address: instance.drv.clone(), source }),
+    };
+
+    // Parse the plan, which is only missing if the planner itself already denied the workflow
+    let tasks: Option<Vec<PlannedTask>> = match &res.plan {
+        Some(plan) => {
+            let plan: Workflow = serde_json::from_str(plan).map_err(|source| Error::PlanDeserialize { address: instance.drv.clone(), source })?;
+            Some(planned_tasks(&plan))
+        },
+        None if res.verdict => return Err(Error::PlanMissing { address: instance.drv.clone() }),
+        None => None,
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&plan_json(&workflow.id, &res, tasks.as_deref())).map_err(|source| Error::PlanSerialize { source })?
+        );
+    } else {
+        print!("{}", plan_text(&workflow.id, &res, tasks.as_deref()));
+    }
+    Ok(())
+}
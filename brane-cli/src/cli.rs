@@ -1,11 +1,15 @@
 use std::path::PathBuf;
 
-use brane_cli::spec::{API_DEFAULT_VERSION, Hostname, VersionFix};
-use brane_tsk::docker::ClientVersion;
+use brane_cli::spec::{
+    API_DEFAULT_VERSION, Age, BuildArg, ByteSize, Checksum, CompileFormat, CompletionKind, EntrypointArgs, Hostname, InspectFormat, Label, LintRule,
+    ReportFormat, TlsVersion, VersionFix,
+};
+use brane_tsk::docker::{ClientVersion, DockerHost, DockerOptions};
 use brane_tsk::spec::AppId;
 use clap::Parser;
 use specifications::arch::Arch;
-use specifications::version::Version as SemVersion;
+use specifications::package::PackageCompression;
+use specifications::version::{Version as SemVersion, VersionConstraint};
 
 /***** ARGUMENTS *****/
 #[derive(Parser)]
@@ -15,6 +19,38 @@ pub(crate) struct Cli {
     pub(crate) debug: bool,
     #[clap(long, action, help = "Skip dependencies check")]
     pub(crate) skip_check: bool,
+    #[clap(
+        long,
+        global = true,
+        action,
+        help = "Disable coloured output. Colour is also disabled if the 'NO_COLOR' environment variable is set, or if the output is not a terminal."
+    )]
+    pub(crate) no_color: bool,
+    #[clap(
+        long,
+        global = true,
+        action,
+        help = "Do not verify the TLS certificate of the instance. Only use this for throwaway development instances with self-signed certificates; \
+                it is refused for commands that write persistent state."
+    )]
+    pub(crate) insecure_skip_verify: bool,
+    #[clap(
+        long,
+        global = true,
+        value_name = "VERSION",
+        help = "The minimum TLS version to negotiate with the instance ('1.2' or '1.3'); connections that would use an older version are refused. \
+                Unencrypted connections (e.g., 'http://' APIs and the gRPC driver connection) are refused too. Defaults to the active instance's \
+                'min_tls_version' default, if any."
+    )]
+    pub(crate) min_tls_version: Option<TlsVersion>,
+    #[clap(
+        long,
+        global = true,
+        env = "BRANE_USER_AGENT",
+        value_name = "AGENT",
+        help = "The 'User-Agent' header to send with every HTTP request to the instance or registry. Defaults to 'brane-cli/<VERSION>'."
+    )]
+    pub(crate) user_agent: Option<String>,
     #[clap(subcommand)]
     pub(crate) sub_command: SubCommand,
 }
@@ -28,10 +64,32 @@ pub(crate) enum SubCommand {
         subcommand: CertsSubcommand,
     },
 
+    #[clap(name = "__complete", hide = true, about = "Lists the local names of the given kind that start with PREFIX, for shell completion.")]
+    Complete {
+        #[clap(name = "KIND", help = "The kind of names to list ('package', 'dataset' or 'instance').")]
+        kind:   CompletionKind,
+        #[clap(name = "PREFIX", default_value = "", help = "The partial argument typed so far.")]
+        prefix: String,
+    },
+
     #[clap(name = "cwl", about = "Parses and prints a CWL file")]
     Cwl {
         #[clap(help = "Path to the CWL file")]
         file: PathBuf,
+        #[clap(
+            long,
+            value_name = "DIR",
+            help = "If given, builds the tool and then runs it with DIR as its output directory. Afterwards, its outputs are collected from DIR by \
+                    evaluating the `outputBinding.glob` patterns of its outputs, and printed as JSON."
+        )]
+        collect_outputs: Option<PathBuf>,
+        #[clap(
+            long,
+            value_name = "FILE",
+            requires = "collect_outputs",
+            help = "A CWL job file (JSON or YAML) with the tool's inputs, used to evaluate expressions like `$(inputs.x)` in output globs."
+        )]
+        inputs: Option<PathBuf>,
     },
 
     #[clap(name = "data", about = "Data-related commands.")]
@@ -41,6 +99,21 @@ pub(crate) enum SubCommand {
         subcommand: DataSubcommand,
     },
 
+    #[clap(name = "doctor", about = "Diagnoses the local environment (Docker, BuildKit, directories and the active instance) and suggests fixes.")]
+    Doctor {
+        /// The Docker socket location.
+        #[clap(
+            short = 's',
+            long,
+            help = "The Docker socket path or DOCKER_HOST-style URL (unix://, npipe:// or tcp://) with which we communicate with the daemon. \
+                    Defaults to DOCKER_HOST if that is set to such a URL (without DOCKER_TLS_VERIFY), or to the platform's default socket otherwise."
+        )]
+        docker_socket:  Option<DockerHost>,
+        /// The Docker client version.
+        #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
+        client_version: ClientVersion,
+    },
+
     #[clap(name = "instance", about = "Commands that relate to connecting to remote instances.")]
     Instance {
         /// Subcommand further
@@ -99,6 +172,145 @@ pub(crate) enum SubCommand {
     },
 }
 
+impl SubCommand {
+    /// Returns the name of this subcommand if it writes persistent state (locally or on the instance).
+    ///
+    /// # Returns
+    /// The name of the subcommand if it does, or [`None`] if it's a read-only command.
+    pub(crate) fn persistent_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Certs { subcommand } => match subcommand {
+                CertsSubcommand::Add { .. } => Some("certs add"),
+                CertsSubcommand::Renew { .. } => Some("certs renew"),
+                CertsSubcommand::Remove { dry_run, .. } => (!dry_run).then_some("certs remove"),
+                CertsSubcommand::List { .. } | CertsSubcommand::Check { .. } | CertsSubcommand::Export { .. } => None,
+            },
+            Self::Complete { .. } => None,
+            Self::Cwl { .. } => Some("cwl"),
+            Self::Data { subcommand } => match subcommand {
+                DataSubcommand::Build { .. } => Some("data build"),
+                DataSubcommand::Download { .. } => Some("data download"),
+                DataSubcommand::ImportArchive { .. } => Some("data import-archive"),
+                DataSubcommand::Remove { .. } => Some("data remove"),
+                DataSubcommand::Export { .. }
+                | DataSubcommand::List { .. }
+                | DataSubcommand::Search { .. }
+                | DataSubcommand::Path { .. }
+                | DataSubcommand::Verify { .. } => None,
+            },
+            Self::Doctor { .. } => None,
+            Self::Instance { subcommand } => match subcommand {
+                InstanceSubcommand::Add { .. } => Some("instance add"),
+                InstanceSubcommand::Remove { .. } => Some("instance remove"),
+                InstanceSubcommand::Select { .. } => Some("instance select"),
+                InstanceSubcommand::Edit { .. } => Some("instance edit"),
+                InstanceSubcommand::SetDefaultUser { .. } => Some("instance set-default-user"),
+                InstanceSubcommand::List { .. } => None,
+                InstanceSubcommand::Status { .. } => None,
+            },
+            Self::Package { subcommand } => match subcommand {
+                PackageSubcommand::Build { .. } => Some("package build"),
+                PackageSubcommand::Import { .. } => Some("package import"),
+                PackageSubcommand::ImportArchive { .. } => Some("package import-archive"),
+                PackageSubcommand::Index { repair } => repair.then_some("package index --repair"),
+                PackageSubcommand::Load { .. } => Some("package load"),
+                PackageSubcommand::Pull { .. } => Some("package pull"),
+                PackageSubcommand::Push { .. } => Some("package push"),
+                PackageSubcommand::Remove { .. } => Some("package remove"),
+                PackageSubcommand::Tag { .. } => Some("package tag"),
+                PackageSubcommand::Unpublish { .. } => Some("package unpublish"),
+                PackageSubcommand::Export { .. }
+                | PackageSubcommand::Inspect { .. }
+                | PackageSubcommand::List { .. }
+                | PackageSubcommand::Test { .. }
+                | PackageSubcommand::Search { .. }
+                | PackageSubcommand::Which { .. } => None,
+            },
+            Self::Upgrade { .. } => Some("upgrade"),
+            Self::Workflow { subcommand } => match subcommand {
+                // Running may download the result dataset and (locally) retain intermediate results or pull package images
+                WorkflowSubcommand::Repl { .. } => Some("workflow repl"),
+                WorkflowSubcommand::Run { dry_run, .. } => (!dry_run).then_some("workflow run"),
+                WorkflowSubcommand::Check { .. }
+                | WorkflowSubcommand::Compile { .. }
+                | WorkflowSubcommand::ExplainPlan { .. }
+                | WorkflowSubcommand::Lint { .. } => None,
+            },
+            Self::Verify { .. } | Self::Version { .. } => None,
+        }
+    }
+
+    /// Returns whether this subcommand needs a running Docker daemon (and thus whether we should check for it before running).
+    ///
+    /// # Returns
+    /// True if the subcommand builds, loads or runs containers, or false if it can do without Docker.
+    pub(crate) fn requires_docker(&self) -> bool {
+        match self {
+            Self::Package { subcommand } => match subcommand {
+                PackageSubcommand::Build { dry_run, .. } => !*dry_run,
+                PackageSubcommand::Import { .. }
+                | PackageSubcommand::Load { .. }
+                | PackageSubcommand::Remove { .. }
+                | PackageSubcommand::Tag { .. }
+                | PackageSubcommand::Test { .. } => true,
+                PackageSubcommand::Export { .. }
+                | PackageSubcommand::ImportArchive { .. }
+                | PackageSubcommand::Index { .. }
+                | PackageSubcommand::Inspect { .. }
+                | PackageSubcommand::List { .. }
+                | PackageSubcommand::Pull { .. }
+                | PackageSubcommand::Push { .. }
+                | PackageSubcommand::Search { .. }
+                | PackageSubcommand::Unpublish { .. }
+                | PackageSubcommand::Which { .. } => false,
+            },
+            Self::Workflow { subcommand } => match subcommand {
+                // Only local execution spawns containers
+                WorkflowSubcommand::Repl { remote, .. } => !*remote,
+                WorkflowSubcommand::Run { remote, dry_run, .. } => !*remote && !*dry_run,
+                WorkflowSubcommand::Check { .. }
+                | WorkflowSubcommand::Compile { .. }
+                | WorkflowSubcommand::ExplainPlan { .. }
+                | WorkflowSubcommand::Lint { .. } => false,
+            },
+            // The doctor diagnoses Docker itself, so it must run even if the daemon is down
+            Self::Certs { .. }
+            | Self::Complete { .. }
+            | Self::Cwl { .. }
+            | Self::Data { .. }
+            | Self::Doctor { .. }
+            | Self::Instance { .. }
+            | Self::Upgrade { .. }
+            | Self::Verify { .. }
+            | Self::Version { .. } => false,
+        }
+    }
+
+    /// Returns whether this subcommand builds images, and thus needs the Docker Buildx plugin on top of Docker itself.
+    pub(crate) fn requires_buildx(&self) -> bool { matches!(self, Self::Package { subcommand: PackageSubcommand::Build { .. } }) }
+
+    /// Returns the options with which this subcommand connects to the Docker daemon, if it lets the user choose them.
+    ///
+    /// # Returns
+    /// The [`DockerOptions`] given to the subcommand, or [`None`] if it uses the local defaults (or doesn't use Docker at all).
+    pub(crate) fn docker_options(&self) -> Option<DockerOptions> {
+        match self {
+            Self::Doctor { docker_socket, client_version }
+            | Self::Package {
+                subcommand:
+                    PackageSubcommand::Remove { docker_socket, client_version, .. }
+                    | PackageSubcommand::Tag { docker_socket, client_version, .. }
+                    | PackageSubcommand::Test { docker_socket, client_version, .. },
+            }
+            | Self::Workflow {
+                subcommand:
+                    WorkflowSubcommand::Repl { docker_socket, client_version, .. } | WorkflowSubcommand::Run { docker_socket, client_version, .. },
+            } => Some(DockerOptions { host: DockerHost::resolve(docker_socket.clone()), version: *client_version }),
+            _ => None,
+        }
+    }
+}
+
 /// Defines the subcommands for the `instance certs` subommand
 #[derive(Parser)]
 pub(crate) enum CertsSubcommand {
@@ -137,12 +349,38 @@ pub(crate) enum CertsSubcommand {
         #[clap(short, long, help = "If given, does not ask for permission before overwriting old certificates. Use at your own risk.")]
         force: bool,
     },
+    #[clap(
+        name = "renew",
+        about = "Replaces the CA/client certificates of a domain in this instance with rotated ones. The old certificates are kept until the new \
+                 ones have been validated and written."
+    )]
+    Renew {
+        /// The path(s) to the new certificate(s) to load.
+        #[clap(
+            name = "PATHS",
+            help = "The path(s) to the new certificate(s) to load. Like with `brane certs add`, this should include at least the CA certificate and \
+                    a signed client certificate with its key."
+        )]
+        paths:    Vec<PathBuf>,
+        /// The domain to renew the certificates of.
+        #[clap(short, long, help = "The name of the domain to renew the certificates of. It must already have certificates in the instance.")]
+        domain:   String,
+        /// The instance in which to renew them.
+        #[clap(
+            short,
+            long,
+            help = "The name of the instance to renew the certificates in. If omitted, will renew them in the active instance instead (i.e., the \
+                    one set with `brane instance select`). Use 'brane instance list' for an overview."
+        )]
+        instance: Option<String>,
+    },
     #[clap(name = "remove", about = "Removes the certificates for a certain domain within this instance.")]
     Remove {
         /// The name(s) of the certificate(s) to remove.
         #[clap(
             name = "DOMAINS",
-            help = "The name(s) of the domain(s) for which to remove the certificates. If in doubt, consult `brane certs list`."
+            help = "The name(s) of the domain(s) for which to remove the certificates. Each may be a glob with '*' and '?' wildcards (e.g., \
+                    'test-*') to remove all matching domains. If in doubt, consult `brane certs list`."
         )]
         domains: Vec<String>,
 
@@ -157,7 +395,10 @@ pub(crate) enum CertsSubcommand {
 
         /// Whether to query for permission or not (but negated).
         #[clap(short, long, help = "If given, does not ask for permission before removing the certificates. Use at your own risk.")]
-        force: bool,
+        force:   bool,
+        /// Whether to only show what would be removed.
+        #[clap(long, help = "If given, only lists the certificate directories that would be removed, without removing anything.")]
+        dry_run: bool,
     },
 
     #[clap(name = "list", about = "Lists the domains for which certificates are given.")]
@@ -175,6 +416,51 @@ pub(crate) enum CertsSubcommand {
         #[clap(short, long, conflicts_with = "instance", help = "If given, shows all certificates across all instances.")]
         all:      bool,
     },
+    #[clap(name = "export", about = "Exports the CA certificate of a domain as PEM, e.g., to share it with a peer. Never exports any keys.")]
+    Export {
+        /// The domain of which to export the CA certificate.
+        #[clap(short, long, help = "The name of the domain of which to export the CA certificate. If in doubt, consult `brane certs list`.")]
+        domain:   String,
+        /// The instance in which the domain is defined.
+        #[clap(
+            short,
+            long,
+            help = "The name of the instance to export the certificate from. If omitted, will export from the active instance instead (i.e., the \
+                    one set with `brane instance select`). Use 'brane instance list' for an overview."
+        )]
+        instance: Option<String>,
+        /// Where to write the certificate to.
+        #[clap(short, long, help = "The path to write the CA certificate to. If omitted, writes it to stdout instead.")]
+        output:   Option<PathBuf>,
+    },
+    #[clap(name = "check", about = "Reports when the certificates of domains expire.")]
+    Check {
+        /// The instance of which to check the certificates
+        #[clap(
+            short,
+            long,
+            conflicts_with = "all",
+            help = "The name of the instance to check the registered certificates of. If omitted, will check the active instance instead (i.e., the \
+                    one set with `brane instance select`). Use 'brane instance list' for an overview."
+        )]
+        instance: Option<String>,
+        /// Whether to check all instances or only the given/active one.
+        #[clap(short, long, conflicts_with = "instance", help = "If given, checks all certificates across all instances.")]
+        all: bool,
+        /// The number of days before expiry from which to warn.
+        #[clap(short, long, default_value = "30", help = "Certificates that expire within this many days are reported as expiring.")]
+        warn_days: u32,
+        /// The output format.
+        #[clap(
+            short,
+            long,
+            value_enum,
+            default_value_t = ReportFormat::Human,
+            help = "The format in which to print the report. Can be 'human' (a table) or 'json' (an array of objects with the instance, domain, \
+                    kind, not_after, days_remaining and status of every certificate, e.g., for monitoring)."
+        )]
+        format: ReportFormat,
+    },
 }
 
 /// Defines the subsubcommands for the data subcommand.
@@ -200,12 +486,28 @@ pub(crate) enum DataSubcommand {
     #[clap(name = "download", about = "Attempts to download one (or more) dataset(s) from the remote instance.")]
     Download {
         /// A use-case registry to use for downloading the data.
-        #[clap(help = "A use-case registry to use for downloading the data.")]
-        use_case: String,
+        #[clap(
+            long,
+            help = "A use-case registry to use for downloading the data. If omitted, uses the 'use_case' default of the active instance (see its \
+                    'info.yml'). If neither is given, the first positional argument is taken as the use-case instead."
+        )]
+        use_case: Option<String>,
 
         /// The name of the datasets to download.
-        #[clap(name = "DATASETS", help = "The datasets to attempt to download.")]
+        #[clap(name = "DATASETS", help = "The datasets to attempt to download (optionally preceded by the use-case; see '--use-case').")]
         names: Vec<String>,
+        /// Whether to download all remote datasets instead.
+        #[clap(
+            short,
+            long,
+            help = "If given, downloads every dataset in the remote data index that is not yet available locally. Datasets available at multiple \
+                    locations are downloaded from the first (alphabetically) unless given with '--locs'. Asks for confirmation unless '--yes' is \
+                    given."
+        )]
+        all:   bool,
+        /// Whether to skip the confirmation of `--all`.
+        #[clap(short, long, help = "If given, does not ask for confirmation before downloading all datasets with '--all'.")]
+        yes:   bool,
 
         /// The user that is performing the download
         #[clap(short, long, help = "The user that is performing the download")]
@@ -219,12 +521,80 @@ pub(crate) enum DataSubcommand {
         #[clap(short, long, help = "If given, proxies the transfer through the given proxy.")]
         proxy_addr: Option<String>,
         /// If given, forces the data transfer even if it's locally available.
-        #[clap(short, long, action, help = "If given, will always attempt to transfer data remotely, even if it's already available locally.")]
-        force:      bool,
+        #[clap(
+            short,
+            long,
+            action,
+            help = "If given, will always attempt to transfer data remotely, even if it's already available locally. With '--all', this downloads \
+                    every remote dataset again."
+        )]
+        force: bool,
+        /// If given, keeps the downloaded archive next to the extracted dataset.
+        #[clap(
+            long,
+            help = "If given, also keeps the downloaded archive as 'data.tar.gz' in the dataset directory. By default, datasets are extracted while \
+                    downloading without storing the archive."
+        )]
+        keep_archive: bool,
+        #[clap(
+            long,
+            value_name = "SIZE",
+            help = "If given, refuses to download datasets larger than SIZE (e.g., '10G'; 'K', 'M', 'G' and 'T' are powers of 1024). Defaults to \
+                    the active instance's 'max_download_size' default, if any."
+        )]
+        max_download_size: Option<ByteSize>,
+        #[clap(
+            long,
+            help = "If given, allows downloading datasets whose size the remote does not advertise even if there is a maximum download size, which \
+                    is then enforced while downloading instead. Without a maximum, these are always allowed."
+        )]
+        allow_unknown_size: bool,
+        /// Whether to fail instead of prompting for a location.
+        #[clap(
+            long,
+            help = "If given, never prompts for the location to download a dataset from. Instead, fails (listing the locations that have it) if a \
+                    dataset is available at multiple locations and none is given with '--locs'. With '--all', instead stops at the first dataset \
+                    that fails to download, and requires '--yes'."
+        )]
+        strict_data: bool,
+    },
+
+    #[clap(name = "export", about = "Exports a locally available dataset to a portable archive.")]
+    Export {
+        #[clap(name = "NAME", help = "The name of the dataset to export.")]
+        name:   String,
+        #[clap(
+            short,
+            long,
+            help = "The path of the '.tar.gz' archive to write. The archive includes the dataset's files and its 'data.yml', and can be imported on \
+                    another machine with 'brane data import-archive'."
+        )]
+        output: PathBuf,
+    },
+
+    #[clap(name = "import-archive", about = "Imports a dataset from an archive created with `brane data export`.")]
+    ImportArchive {
+        #[clap(name = "FILE", help = "Path to the '.tar.gz' archive to import. Refuses to import a dataset that already exists locally.")]
+        file: PathBuf,
     },
 
     #[clap(name = "list", about = "Shows the locally known datasets.")]
-    List {},
+    List {
+        /// Only show datasets created at most this long ago.
+        #[clap(
+            long,
+            help = "If given, only shows datasets created within this duration (e.g., '7d'). Accepts a number followed by 'd' (days), 'h' (hours) \
+                    or 'm' (minutes)."
+        )]
+        since:  Option<Age>,
+        /// Only show datasets created at least this long ago.
+        #[clap(
+            long,
+            help = "If given, only shows datasets created longer ago than this duration (e.g., '30d'), to find stale datasets. Accepts a number \
+                    followed by 'd' (days), 'h' (hours) or 'm' (minutes)."
+        )]
+        before: Option<Age>,
+    },
 
     #[clap(name = "search", about = "Shows the datasets known in the remote instance.")]
     Search {},
@@ -246,6 +616,23 @@ pub(crate) enum DataSubcommand {
         #[clap(short, long, action, help = "If given, does not ask the user for confirmation but just removes the dataset (use at your own risk!)")]
         force: bool,
     },
+
+    #[clap(
+        name = "verify",
+        about = "Checks the files of locally available datasets against the sizes and checksums recorded when they were built or downloaded."
+    )]
+    Verify {
+        #[clap(name = "DATASETS", help = "The name(s) of the dataset(s) to verify.")]
+        names: Vec<String>,
+        #[clap(
+            short,
+            long,
+            conflicts_with = "DATASETS",
+            required_unless_present = "DATASETS",
+            help = "If given, verifies all local datasets instead."
+        )]
+        all:   bool,
+    },
 }
 
 /// Defines the subcommands for the instance subommand
@@ -320,6 +707,12 @@ pub(crate) enum InstanceSubcommand {
         #[clap(short, long, help = "If given, shows an additional column in the table that shows whether this instance is online or not.")]
         show_status: bool,
     },
+    #[clap(name = "status", about = "Probes the API, driver and indices of the active instance and reports on their health.")]
+    Status {
+        /// Whether to print the report as JSON.
+        #[clap(long, help = "If given, prints the report as JSON (e.g., for dashboards) instead of as human-readable text.")]
+        json: bool,
+    },
     #[clap(name = "select", about = "Switches to the registered instance with the given name.")]
     Select {
         /// The instnace's name to switch to.
@@ -327,7 +720,7 @@ pub(crate) enum InstanceSubcommand {
         name: String,
     },
 
-    #[clap(name = "edit", about = "Changes some properties of an instance.")]
+    #[clap(name = "edit", about = "Changes some properties of an instance. Properties that are not given are left untouched.")]
     Edit {
         /// The instance's name to edit.
         #[clap(
@@ -354,6 +747,15 @@ pub(crate) enum InstanceSubcommand {
         )]
         user:     Option<String>,
     },
+    #[clap(name = "set-default-user", about = "Changes only the user as which to login to an instance.")]
+    SetDefaultUser {
+        /// The name of the user as which we login.
+        #[clap(name = "USER", help = "The name as which to login to the instance.")]
+        user: String,
+        /// The instance's name to edit.
+        #[clap(short, long, help = "The name of the instance to change the user of. If omitted, changes the user of the active instance.")]
+        name: Option<String>,
+    },
 }
 
 #[derive(Parser)]
@@ -383,6 +785,74 @@ pub(crate) enum PackageSubcommand {
                     it."
         )]
         crlf_ok: bool,
+        #[clap(
+            short,
+            long = "label",
+            help = "A custom 'key=value' label to attach to the package image (e.g., 'org.opencontainers.image.source=https://...'). Labels are \
+                    also recorded in the package info. May be given multiple times."
+        )]
+        labels: Vec<Label>,
+        #[clap(long, help = "If given, also writes the generated Dockerfile to this path (e.g., to audit or customize it).")]
+        dockerfile_out: Option<PathBuf>,
+        #[clap(
+            long,
+            requires = "dockerfile_out",
+            help = "If given, only generates the Dockerfile (see '--dockerfile-out') and its build context, and skips the actual Docker build. No \
+                    package is created. For container packages, the build context is written to the directory of '--dockerfile-out'."
+        )]
+        dry_run: bool,
+        #[clap(
+            long,
+            value_name = "PATH",
+            help = "If given, builds the package from this existing Dockerfile instead of generating one from the container file. Brane's branelet \
+                    and the package files are added on top of its final stage (replacing any ENTRYPOINT), and the working directory is used as its \
+                    build context. Only for container (ECU) packages."
+        )]
+        from_dockerfile: Option<PathBuf>,
+        #[clap(
+            long,
+            help = "If given, does not install the default dependencies (fuse and iptables) in the generated Dockerfile, trusting the base image to \
+                    provide them. Useful for minimal bases without apt or apk. Only for container (ECU) packages."
+        )]
+        no_default_deps: bool,
+        #[clap(
+            long = "build-arg",
+            value_name = "KEY=VALUE",
+            help = "A build argument to declare in the generated Dockerfile and pass to the Docker build (e.g., to parameterize versions in the \
+                    base image or install steps). May be given multiple times. Only for container (ECU) packages."
+        )]
+        build_args: Vec<BuildArg>,
+        #[clap(
+            long,
+            allow_hyphen_values = true,
+            value_name = "ARGS",
+            help = "Default arguments that branelet always passes to the entrypoint (e.g., \"--quiet --name 'hello world'\"), overriding \
+                    'entrypoint.args' in the container file. They are split like a shell would (so quotes group arguments), but are passed to the \
+                    entrypoint without a shell. Only for container (ECU) packages."
+        )]
+        entrypoint_args: Option<EntrypointArgs>,
+        #[clap(
+            long = "owner",
+            value_name = "NAME",
+            help = "An owner to record for the package, overriding 'owners' in the container file (e.g., to stamp the committing user in CI). May \
+                    be given multiple times. If neither this nor the container file gives any owners, the current OS user is used. Only for \
+                    container (ECU) packages."
+        )]
+        owners: Vec<String>,
+    },
+
+    #[clap(name = "export", about = "Export a local package to a portable archive")]
+    Export {
+        #[clap(name = "NAME", help = "Name of the package")]
+        name:    String,
+        #[clap(name = "VERSION", default_value = "latest", help = "Version of the package")]
+        version: SemVersion,
+        #[clap(
+            short,
+            long,
+            help = "The path of the '.tar.gz' archive to write. The archive includes a 'SHA256SUMS' manifest with the digest of every file in it."
+        )]
+        output:  PathBuf,
     },
 
     #[clap(name = "import", about = "Import a package")]
@@ -416,6 +886,29 @@ pub(crate) enum PackageSubcommand {
         crlf_ok: bool,
     },
 
+    #[clap(name = "import-archive", about = "Import a package from an archive created with `brane package export`")]
+    ImportArchive {
+        #[clap(name = "FILE", help = "Path to the '.tar.gz' archive to import")]
+        file:   PathBuf,
+        #[clap(
+            long,
+            help = "If given, checks every file in the archive against its 'SHA256SUMS' manifest before installing the package, and refuses to \
+                    install it if any file is missing, unlisted or has a different digest. Note that the manifest is not signed, so this detects \
+                    corrupted transfers but not an archive deliberately tampered with (manifest included)."
+        )]
+        verify: bool,
+    },
+
+    #[clap(name = "index", about = "Check the local package index for broken entries")]
+    Index {
+        #[clap(
+            long,
+            help = "If given, rebuilds the local package index from scratch by removing the broken entries (e.g., versions without a valid \
+                    'package.yml') from the packages directory. Without it, they are only reported."
+        )]
+        repair: bool,
+    },
+
     #[clap(name = "inspect", about = "Inspect a package")]
     Inspect {
         #[clap(name = "NAME", help = "Name of the package")]
@@ -431,6 +924,16 @@ pub(crate) enum PackageSubcommand {
             help = "Any alternative syntax to use for printed classes and functions. Can be 'bscript', 'bakery' or 'custom'."
         )]
         syntax: String,
+        /// The output format.
+        #[clap(
+            short,
+            long,
+            value_enum,
+            default_value_t = InspectFormat::Human,
+            help = "The format in which to print the package. Can be 'human' (pretty-printed, respecting '--syntax') or 'markdown' (a Markdown \
+                    document with the package's classes and functions, e.g., for documentation sites)."
+        )]
+        format: InspectFormat,
     },
 
     #[clap(name = "list", about = "List packages")]
@@ -442,9 +945,15 @@ pub(crate) enum PackageSubcommand {
     #[clap(name = "load", about = "Load a package locally")]
     Load {
         #[clap(name = "NAME", help = "Name of the package")]
-        name:    String,
+        name: String,
         #[clap(short, long, default_value = "latest", help = "Version of the package")]
         version: SemVersion,
+        #[clap(
+            long,
+            help = "If given, loads the package even if a newer version of it is already loaded in the local Docker daemon. Without it, such \
+                    downgrades are refused."
+        )]
+        allow_downgrade: bool,
     },
 
     // #[clap(name = "logout", about = "Log out from a registry")]
@@ -454,9 +963,36 @@ pub(crate) enum PackageSubcommand {
         #[clap(
             name = "PACKAGES",
             help = "Specify one or more packages to pull from a remote. You can either give a package as 'NAME' or 'NAME:VERSION', where VERSION is \
-                    assumed to be 'latest' if omitted."
+                    assumed to be 'latest' if omitted or empty. If NAME contains colons, the last one separates it from VERSION."
+        )]
+        packages:      Vec<String>,
+        #[clap(
+            long,
+            value_name = "sha256:HEX",
+            help = "If given, verifies that the downloaded package archive has this checksum before it is extracted. Can only be used when pulling \
+                    a single package."
+        )]
+        checksum:      Option<Checksum>,
+        #[clap(
+            long,
+            conflicts_with = "checksum",
+            help = "If given, only pulls the package's metadata (enough to compile and check workflows against it) and not its image. The image is \
+                    pulled when the package is first loaded or run."
+        )]
+        only_metadata: bool,
+        #[clap(
+            long,
+            value_name = "SIZE",
+            help = "If given, refuses to download package images larger than SIZE (e.g., '10G'; 'K', 'M', 'G' and 'T' are powers of 1024). Defaults \
+                    to the active instance's 'max_download_size' default, if any."
+        )]
+        max_download_size: Option<ByteSize>,
+        #[clap(
+            long,
+            help = "If given, allows downloading package images whose size the remote does not advertise even if there is a maximum download size, \
+                    which is then enforced while downloading instead. Without a maximum, these are always allowed."
         )]
-        packages: Vec<String>,
+        allow_unknown_size: bool,
     },
 
     #[clap(name = "push", about = "Push a package to a registry")]
@@ -464,44 +1000,72 @@ pub(crate) enum PackageSubcommand {
         #[clap(
             name = "PACKAGES",
             help = "Specify one or more packages to push to a remote. You can either give a package as 'NAME' or 'NAME:VERSION', where VERSION is \
-                    assumed to be 'latest' if omitted."
+                    assumed to be 'latest' if omitted or empty. If NAME contains colons, the last one separates it from VERSION."
+        )]
+        packages:  Vec<String>,
+        /// Whether to stop at the first failed upload.
+        #[clap(
+            long,
+            help = "If given, stops at the first package that fails to upload instead of attempting the rest. Packages that were already pushed are \
+                    reported."
+        )]
+        fail_fast: bool,
+        /// How to compress the package archives.
+        #[clap(
+            long,
+            default_value = "gzip",
+            help = "How to compress package archives before uploading them. Can be 'gzip', 'zstd' (faster for large packages) or 'none' (for \
+                    packages with already-compressed content)."
         )]
-        packages: Vec<String>,
+        compress:  PackageCompression,
     },
 
     #[clap(name = "remove", about = "Remove a local package.")]
     Remove {
         #[clap(short, long, help = "Don't ask for confirmation before removal.")]
-        force:    bool,
+        force:      bool,
         #[clap(
             name = "PACKAGES",
             help = "Specify one or more packages to remove to a remote. You can either give a package as 'NAME' or 'NAME:VERSION', where ALL \
                     versions of the packages will be removed if VERSION is omitted.."
         )]
-        packages: Vec<String>,
+        packages:   Vec<String>,
+        #[clap(
+            short,
+            long,
+            help = "Only remove the versions of the given packages that match this semver constraint (e.g., '<1.0' or '>=1.2, <2'). Packages must \
+                    then be given as 'NAME' only. Refuses to remove every version of a package unless '--force' is given too."
+        )]
+        constraint: Option<VersionConstraint>,
 
         /// The Docker socket location.
-        #[cfg(unix)]
         #[clap(
             short = 's',
             long,
-            default_value = "/var/run/docker.sock",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "The Docker socket path or DOCKER_HOST-style URL (unix://, npipe:// or tcp://) with which we communicate with the daemon. \
+                    Defaults to DOCKER_HOST if that is set to such a URL (without DOCKER_TLS_VERIFY), or to the platform's default socket otherwise."
         )]
-        docker_socket:  PathBuf,
+        docker_socket:  Option<DockerHost>,
+        /// The Docker client version.
+        #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
+        client_version: ClientVersion,
+    },
+
+    #[clap(name = "tag", about = "Create a new version of a local package that aliases an existing one, without rebuilding it")]
+    Tag {
+        #[clap(name = "PACKAGE", help = "The package to tag, as 'NAME' or 'NAME:VERSION'. If VERSION is omitted, the latest version is tagged.")]
+        package:     String,
+        #[clap(name = "NEW_VERSION", help = "The new version to create. Must not exist already.")]
+        new_version: SemVersion,
+
         /// The Docker socket location.
-        #[cfg(windows)]
         #[clap(
             short = 's',
             long,
-            default_value = "//./pipe/docker_engine",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "The Docker socket path or DOCKER_HOST-style URL (unix://, npipe:// or tcp://) with which we communicate with the daemon. \
+                    Defaults to DOCKER_HOST if that is set to such a URL (without DOCKER_TLS_VERIFY), or to the platform's default socket otherwise."
         )]
-        docker_socket:  PathBuf,
-        /// The Docker socket location.
-        #[cfg(not(any(unix, windows)))]
-        #[clap(short = 's', long, help = "The path to the Docker socket with which we communicate with the dameon.")]
-        docker_socket:  PathBuf,
+        docker_socket:  Option<DockerHost>,
         /// The Docker client version.
         #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
         client_version: ClientVersion,
@@ -522,39 +1086,57 @@ pub(crate) enum PackageSubcommand {
         show_result: Option<PathBuf>,
 
         /// The Docker socket location.
-        #[cfg(unix)]
         #[clap(
             short = 's',
             long,
-            default_value = "/var/run/docker.sock",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "The Docker socket path or DOCKER_HOST-style URL (unix://, npipe:// or tcp://) with which we communicate with the daemon. \
+                    Defaults to DOCKER_HOST if that is set to such a URL (without DOCKER_TLS_VERIFY), or to the platform's default socket otherwise."
         )]
-        docker_socket:   PathBuf,
-        /// The Docker socket location.
-        #[cfg(windows)]
-        #[clap(
-            short = 's',
-            long,
-            default_value = "//./pipe/docker_engine",
-            help = "The path to the Docker socket with which we communicate with the dameon."
-        )]
-        docker_socket:   PathBuf,
-        /// The Docker socket location.
-        #[cfg(not(any(unix, windows)))]
-        #[clap(short = 's', long, help = "The path to the Docker socket with which we communicate with the dameon.")]
-        docker_socket:   PathBuf,
+        docker_socket:   Option<DockerHost>,
         /// The Docker client version.
         #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
         client_version:  ClientVersion,
         /// Whether to keep container after running or not.
         #[clap(short = 'k', long, help = "If given, does not remove containers after execution. This is useful for debugging them.")]
         keep_containers: bool,
+        /// The number of times to retry the task if it fails transiently.
+        #[clap(
+            long,
+            default_value = "0",
+            help = "The number of times to re-execute the task if it fails with a retryable error (e.g., a nonzero exit code or Docker hiccup). \
+                    Errors like unknown packages are never retried."
+        )]
+        max_retries:     u32,
+        /// Whether to sample the resource usage of the task.
+        #[clap(
+            long,
+            help = "If given, samples the CPU and memory usage of the task container while it runs and reports its peak memory and average CPU \
+                    usage afterwards."
+        )]
+        stats:           bool,
+        /// Whether to fail instead of pulling the image of the package if it has not been pulled yet.
+        #[clap(
+            long,
+            help = "If given, fails if the image of the package has not been pulled yet (e.g., after 'brane package pull --only-metadata') instead \
+                    of pulling it from the registry."
+        )]
+        no_auto_fetch: bool,
     },
 
     #[clap(name = "search", about = "Search a registry for packages")]
     Search {
         #[clap(name = "TERM", help = "Term to use as search criteria")]
-        term: Option<String>,
+        term:   Option<String>,
+        #[clap(long, value_name = "N", help = "If given, shows at most N of the matching packages.")]
+        limit:  Option<usize>,
+        #[clap(long, value_name = "M", default_value = "0", help = "The number of matching packages to skip before showing any.")]
+        offset: usize,
+        #[clap(
+            long,
+            help = "If given, prints the shown packages as JSON (together with the total number of matches) instead of as a table. Use with \
+                    '--limit' and '--offset' to page through the entire registry."
+        )]
+        json:   bool,
     },
 
     #[clap(name = "unpublish", about = "Remove a package from a registry")]
@@ -566,6 +1148,17 @@ pub(crate) enum PackageSubcommand {
         #[clap(short, long, action, help = "Don't ask for confirmation")]
         force:   bool,
     },
+
+    #[clap(name = "which", about = "Print the location of a local package on disk")]
+    Which {
+        #[clap(
+            name = "PACKAGE",
+            help = "The package to locate, given as 'NAME' or 'NAME:VERSION', where VERSION is assumed to be 'latest' if omitted."
+        )]
+        package: String,
+        #[clap(short, long, help = "If given, prints the path of the package's 'image.tar' instead of its directory.")]
+        image:   bool,
+    },
 }
 
 #[derive(Parser)]
@@ -586,6 +1179,98 @@ pub(crate) enum WorkflowSubcommand {
 
         #[clap(long, help = "If given, shows profile times if they are available.")]
         profile: bool,
+        /// A package index file to use instead of the instance's.
+        #[clap(
+            long,
+            help = "If given, compiles against the package index in this JSON file instead of retrieving it from the instance. If '--data-index' is \
+                    given too, the instance is not contacted at all; the workflow is then only compiled, without checking policy."
+        )]
+        package_index: Option<PathBuf>,
+        /// A data index file to use instead of the instance's.
+        #[clap(
+            long,
+            help = "If given, compiles against the data index in this JSON (or YAML) file instead of retrieving it from the instance. The file may \
+                    also be a saved response of the instance's '/data/info' endpoint. If '--package-index' is given too, the instance is not \
+                    contacted at all; the workflow is then only compiled, without checking policy."
+        )]
+        data_index: Option<PathBuf>,
+        /// Whether to keep checking the file whenever it changes.
+        #[clap(
+            short,
+            long,
+            help = "If given, keeps watching FILE and checks it again whenever it is saved, until Ctrl+C is pressed. The instance's package and \
+                    data index are only retrieved once."
+        )]
+        watch: bool,
+    },
+
+    #[clap(
+        name = "explain-plan",
+        about = "Has the planner of the current remote instance plan a workflow and shows where each task would run and which data would be moved, \
+                 without executing it. If a domain denies the plan, its reasons are shown as well."
+    )]
+    ExplainPlan {
+        #[clap(name = "FILE", help = "Path to the file to plan. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        #[clap(short, long, help = "If given, uses the given user as end user of a workflow instead of the one in the instance file.")]
+        user: Option<String>,
+        /// Whether to print the plan as JSON.
+        #[clap(long, help = "If given, prints the plan and the verdict as JSON instead of as text.")]
+        json: bool,
+    },
+
+    #[clap(
+        name = "compile",
+        about = "Compiles a workflow against the local package and data index and writes it out, either as Brane's internal representation or as a \
+                 CWL document."
+    )]
+    Compile {
+        #[clap(name = "FILE", help = "Path to the file to compile. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        /// The output format.
+        #[clap(
+            short = 'f',
+            long,
+            value_enum,
+            default_value_t = CompileFormat::Json,
+            help = "The format in which to write the compiled workflow. Can be 'json' (Brane's internal workflow representation) or 'cwl' (a packed \
+                    CWL Workflow document with a CommandLineTool per task). Workflows with constructs that have no CWL equivalent (e.g., branches, \
+                    loops or parallel statements) cannot be exported to CWL."
+        )]
+        output_format: CompileFormat,
+        /// The output file.
+        #[clap(short, long, help = "If given, writes the compiled workflow to this file instead of stdout.")]
+        output: Option<PathBuf>,
+    },
+
+    #[clap(
+        name = "lint",
+        about = "Compiles a workflow against the local package and data index and reports common anti-patterns, such as unused variables or tasks \
+                 without a plannable location. Does not need an instance."
+    )]
+    Lint {
+        #[clap(name = "FILE", help = "Path to the file to lint. Use '-' to read from stdin instead.")]
+        file:   String,
+        #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
+        bakery: bool,
+
+        /// The rules to suppress.
+        #[clap(
+            short = 'A',
+            long,
+            help = "Suppresses warnings of the given rule. Can be given multiple times. Rules are 'unproduced-result', 'unresolvable-location' \
+                    (correctness), 'unused-result' and 'unused-variable' (style)."
+        )]
+        allow: Vec<LintRule>,
+        /// Whether to fail on warnings.
+        #[clap(short = 'D', long, help = "If given, exits with an error if any (unsuppressed) warnings were reported.")]
+        deny_warnings: bool,
     },
 
     #[clap(name = "repl", about = "Start an interactive DSL session")]
@@ -593,8 +1278,12 @@ pub(crate) enum WorkflowSubcommand {
         #[clap(short, long, value_names = &["address[:port]"], help = "If given, proxies any data transfers to this machine through the proxy at the given address. Irrelevant if not running remotely.")]
         proxy_addr: Option<String>,
 
-        #[clap(name = "use case")]
-        use_case: String,
+        #[clap(
+            name = "use case",
+            help = "A use-case registry to use for downloading the data. If omitted, uses the 'use_case' default of the active instance (see its \
+                    'info.yml')."
+        )]
+        use_case: Option<String>,
 
         #[clap(short, long, help = "Create a remote REPL session to the instance you are currently logged-in to (see `brane login`)")]
         remote: bool,
@@ -608,29 +1297,22 @@ pub(crate) enum WorkflowSubcommand {
 
         #[clap(long, help = "If given, shows profile times if they are available.")]
         profile: bool,
-
-        /// The Docker socket location.
-        #[cfg(unix)]
+        /// The file to write the profile timings to.
         #[clap(
-            short = 's',
             long,
-            default_value = "/var/run/docker.sock",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "If given, writes the timings of every snippet in the session (compilation, planning and execution, including per-task timings) \
+                    as JSON to this file when the REPL exits."
         )]
-        docker_socket:   PathBuf,
+        profile_output: Option<PathBuf>,
+
         /// The Docker socket location.
-        #[cfg(windows)]
         #[clap(
             short = 's',
             long,
-            default_value = "//./pipe/docker_engine",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "The Docker socket path or DOCKER_HOST-style URL (unix://, npipe:// or tcp://) with which we communicate with the daemon. \
+                    Defaults to DOCKER_HOST if that is set to such a URL (without DOCKER_TLS_VERIFY), or to the platform's default socket otherwise."
         )]
-        docker_socket:   PathBuf,
-        /// The Docker socket location.
-        #[cfg(not(any(unix, windows)))]
-        #[clap(short = 's', long, help = "The path to the Docker socket with which we communicate with the dameon.")]
-        docker_socket:   PathBuf,
+        docker_socket:   Option<DockerHost>,
         /// The Docker client version.
         #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
         client_version:  ClientVersion,
@@ -639,13 +1321,17 @@ pub(crate) enum WorkflowSubcommand {
         keep_containers: bool,
     },
 
-    #[clap(name = "run", about = "Run a DSL script locally")]
+    #[clap(name = "run", about = "Run a DSL script locally", allow_missing_positional = true)]
     Run {
         #[clap(short, long, value_names = &["address[:port]"], help = "If given, proxies any data transfers to this machine through the proxy at the given address. Irrelevant if not running remotely.")]
         proxy_addr: Option<String>,
 
-        #[clap(name = "use case", help = "A use-case registry to use for downloading the data.")]
-        use_case: String,
+        #[clap(
+            name = "use case",
+            help = "A use-case registry to use for downloading the data. If omitted (i.e., only FILE is given), uses the 'use_case' default of the \
+                    active instance (see its 'info.yml')."
+        )]
+        use_case: Option<String>,
 
         #[clap(short, long, action, help = "Use Bakery instead of BraneScript")]
         bakery: bool,
@@ -669,35 +1355,128 @@ pub(crate) enum WorkflowSubcommand {
 
         #[clap(long, help = "If given, shows profile times if they are available.")]
         profile: bool,
-
-        /// The Docker socket location.
-        #[cfg(unix)]
+        /// The file to write the profile timings to.
         #[clap(
-            short = 's',
             long,
-            default_value = "/var/run/docker.sock",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "If given, writes the timings of the run (compilation, planning and execution, including per-task timings) as JSON to this file."
         )]
-        docker_socket:   PathBuf,
+        profile_output: Option<PathBuf>,
+        #[clap(
+            long,
+            value_name = "DOMAIN",
+            requires = "remote",
+            help = "If given, forces the planner to place every task on the given location (as if the entire workflow were wrapped in an \
+                    On-struct). Useful for testing against a specific domain."
+        )]
+        location: Option<String>,
+
         /// The Docker socket location.
-        #[cfg(windows)]
         #[clap(
             short = 's',
             long,
-            default_value = "//./pipe/docker_engine",
-            help = "The path to the Docker socket with which we communicate with the dameon."
+            help = "The Docker socket path or DOCKER_HOST-style URL (unix://, npipe:// or tcp://) with which we communicate with the daemon. \
+                    Defaults to DOCKER_HOST if that is set to such a URL (without DOCKER_TLS_VERIFY), or to the platform's default socket otherwise."
         )]
-        docker_socket:   PathBuf,
-        /// The Docker socket location.
-        #[cfg(not(any(unix, windows)))]
-        #[clap(short = 's', long, help = "The path to the Docker socket with which we communicate with the dameon.")]
-        docker_socket:   PathBuf,
+        docker_socket:     Option<DockerHost>,
         /// The Docker client version.
         #[clap(short='v', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The API version with which we connect.")]
-        client_version:  ClientVersion,
+        client_version:    ClientVersion,
         /// Whether to keep container after running or not.
         #[clap(short = 'k', long, help = "If given, does not remove containers after execution. This is useful for debugging them.")]
-        keep_containers: bool,
+        keep_containers:   bool,
+        /// When to clean up kept containers.
+        #[clap(
+            long,
+            value_name = "DURATION",
+            requires = "keep_containers",
+            help = "If given, kept containers are removed by the first local run after this much time has passed (e.g., '30m', '2h' or '1d'). Only \
+                    relevant with '--keep-containers'."
+        )]
+        cleanup_after: Option<Age>,
+        /// Whether to retain intermediate results as datasets after running or not.
+        #[clap(
+            long,
+            conflicts_with_all = ["remote", "dry_run"],
+            help = "If given, retains any intermediate results produced by a local run as local datasets (named after the result and a unique \
+                    run ID) instead of discarding them, also if the run fails. Use 'brane data list' to see them and 'brane data remove' to clean \
+                    them up."
+        )]
+        keep_intermediate: bool,
+        /// The number of times to retry a task if it fails transiently.
+        #[clap(
+            long,
+            default_value = "0",
+            conflicts_with = "remote",
+            help = "The number of times to re-execute a task if it fails with a retryable error (e.g., a nonzero exit code or Docker hiccup) before \
+                    giving up. Errors like unknown packages are never retried. Only relevant for local runs."
+        )]
+        max_retries:       u32,
+        /// Whether to sample the resource usage of tasks.
+        #[clap(
+            long,
+            conflicts_with_all = ["remote", "dry_run"],
+            help = "If given, samples the CPU and memory usage of every task container while it runs and reports the peak memory and average \
+                    CPU usage per task at the end. Only relevant for local runs."
+        )]
+        stats:             bool,
+        /// Whether to fail instead of pulling the images of packages that have not been pulled yet.
+        #[clap(
+            long,
+            conflicts_with_all = ["remote", "dry_run"],
+            help = "If given, fails if the image of a package has not been pulled yet (e.g., after 'brane package pull --only-metadata') \
+                    instead of pulling it from the registry. Only relevant for local runs."
+        )]
+        no_auto_fetch: bool,
+        /// Whether to fail instead of downloading datasets that are not locally available.
+        #[clap(
+            long,
+            requires = "remote",
+            help = "If given, fails (listing the locations that have it) if the workflow returns a dataset that is not locally available, instead \
+                    of downloading it from one of them. Requires '--remote'."
+        )]
+        strict_data: bool,
+        /// Whether to always fetch the indices of the instance instead of reusing recently fetched ones.
+        #[clap(
+            long,
+            conflicts_with = "dry_run",
+            help = "If given, always fetches the package and data indices of the instance instead of reusing the ones fetched by a run against the \
+                    same instance less than a minute (or, for the data index, ten seconds) ago. Only relevant for remote runs."
+        )]
+        no_index_cache: bool,
+
+        /// Whether to write a JSON summary of the run to stdout.
+        #[clap(
+            long,
+            help = "If given, writes a JSON document summarizing the run (status, per-task results of local runs, returned value and produced \
+                    datasets) to stdout instead of the human-readable result."
+        )]
+        json: bool,
+        /// A file to write a JSON summary of the run to.
+        #[clap(
+            long,
+            value_names = &["PATH"],
+            help = "If given, writes a JSON document summarizing the run to the given file, in addition to the human-readable result."
+        )]
+        result_json: Option<PathBuf>,
+
+        /// The inputs to bind to the workflow's parameters.
+        #[clap(
+            long = "input",
+            value_name = "NAME=VALUE",
+            help = "Binds the given value to the top-level parameter NAME of the workflow (i.e., a top-level `let` with a literal value). The value \
+                    must be of the type of that literal, which is inferred from the literal itself (not from how the workflow uses the parameter). \
+                    Can be given multiple times. Overrides any value read from the environment."
+        )]
+        inputs: Vec<String>,
+        /// The prefix of environment variables to read inputs from.
+        #[clap(
+            long,
+            value_name = "PREFIX",
+            default_value = brane_cli::inputs::DEFAULT_INPUT_ENV_PREFIX,
+            help = "Binds any environment variable named PREFIX followed by the name of a top-level parameter (as-is or in uppercase) to that \
+                    parameter. Set to an empty string to ignore the environment."
+        )]
+        input_env_prefix: String,
     },
 }
 
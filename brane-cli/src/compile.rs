@@ -0,0 +1,116 @@
+//  COMPILE.rs
+//
+//  Description:
+//!   Implements the `brane workflow compile`-subcommand, which compiles
+//!   a workflow against the local indices and writes it out as Brane WIR
+//!   or as a CWL document.
+//
+
+use std::fs;
+use std::io::{self, Read as _};
+use std::path::PathBuf;
+
+use brane_ast::{CompileResult, ParserOptions, Workflow};
+use brane_dsl::Language;
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
+
+use crate::cwl_export::{self, CwlDocument};
+pub use crate::errors::CompileError as Error;
+use crate::spec::CompileFormat;
+use crate::utils::{ensure_datasets_dir, ensure_packages_dir};
+
+
+/***** LIBRARY *****/
+/// Reads the given workflow file and compiles it against the local package and data index.
+///
+/// Any compiler warnings or errors are printed to stderr.
+///
+/// # Arguments
+/// - `file`: The path to the workflow file to compile. Can also be '-', in which case it is read from stdin instead.
+/// - `language`: The language with which to compile the file.
+///
+/// # Returns
+/// The name of the input (for use in messages), the compiled [`Workflow`] and the local [`PackageIndex`] it was compiled against.
+///
+/// # Errors
+/// This function errors if we failed to read the input, load the local indices or compile the workflow.
+pub fn compile_local(file: String, language: Language) -> Result<(String, Workflow, PackageIndex), Error> {
+    // Resolve the input file to a source string
+    debug!("Loading input from '{file}'...");
+    let (input, source): (String, String) = if file == "-" {
+        let mut source: String = String::new();
+        io::stdin().read_to_string(&mut source).map_err(|source| Error::InputStdinRead { source })?;
+        ("<stdin>".into(), source)
+    } else {
+        match fs::read_to_string(&file) {
+            Ok(source) => (file, source),
+            Err(err) => return Err(Error::InputFileRead { path: file.into(), source: err }),
+        }
+    };
+
+    // Load the local indices
+    let packages_dir: PathBuf = ensure_packages_dir(false).map_err(|source| Error::PackagesDirError { source })?;
+    let datasets_dir: PathBuf = ensure_datasets_dir(false).map_err(|source| Error::DatasetsDirError { source })?;
+    let pindex: PackageIndex = brane_tsk::local::get_package_index(packages_dir).map_err(|source| Error::LocalPackageIndexError { source })?;
+    let dindex: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| Error::LocalDataIndexError { source })?;
+
+    // Compile the workflow
+    debug!("Compiling source text to Brane WIR...");
+    let workflow: Workflow = match brane_ast::compile_program(source.as_bytes(), &pindex, &dindex, &ParserOptions::new(language)) {
+        CompileResult::Workflow(wf, warns) => {
+            for warn in warns {
+                warn.prettyprint(&input, &source);
+            }
+            wf
+        },
+        CompileResult::Err(errs) => {
+            for err in errs {
+                err.prettyprint(&input, &source);
+            }
+            return Err(Error::AstCompile { input });
+        },
+        CompileResult::Eof(err) => {
+            err.prettyprint(&input, source);
+            return Err(Error::AstCompile { input });
+        },
+
+        // The rest does not occur for this variation of the function
+        CompileResult::Program(_, _) | CompileResult::Unresolved(_, _) => unreachable!(),
+    };
+    Ok((input, workflow, pindex))
+}
+
+/// Handles the `brane workflow compile`-subcommand.
+///
+/// # Arguments
+/// - `file`: The path to the workflow file to compile. Can also be '-', in which case it is read from stdin instead.
+/// - `language`: The language with which to compile the file.
+/// - `output_format`: The [`CompileFormat`] to write the compiled workflow in.
+/// - `output`: If given, writes the compiled workflow to this file instead of stdout.
+///
+/// # Errors
+/// This function errors if we failed to read the input, compile it, convert it to the given format or write the output.
+pub fn handle(file: String, language: Language, output_format: CompileFormat, output: Option<PathBuf>) -> Result<(), Error> {
+    info!("Handling 'brane workflow compile {}'", if file == "-" { "<stdin>" } else { file.as_str() });
+    let (input, workflow, pindex): (String, Workflow, PackageIndex) = compile_local(file, language)?;
+
+    // Serialize it in the requested format
+    let sworkflow: String = match output_format {
+        CompileFormat::Cwl => {
+            debug!("Exporting workflow as CWL...");
+            let doc: CwlDocument = cwl_export::export(&workflow, &pindex).map_err(|source| Error::CwlExport { input: input.clone(), source })?;
+            serde_yaml::to_string(&doc).map_err(|source| Error::CwlSerialize { input, source })?
+        },
+        CompileFormat::Json => serde_json::to_string_pretty(&workflow).map_err(|source| Error::WorkflowSerialize { input, source })?,
+    };
+
+    // Write it
+    match output {
+        Some(path) => fs::write(&path, sworkflow).map_err(|source| Error::OutputFileWrite { path, source }),
+        None => {
+            println!("{sworkflow}");
+            Ok(())
+        },
+    }
+}
@@ -0,0 +1,117 @@
+//  COMPLETIONS.rs
+//
+//  Description:
+//!   Implements the hidden `brane __complete`-subcommand, which lists the
+//!   locally known package, dataset or instance names that start with a
+//!   given prefix. The shell completion scripts call it to complete those
+//!   arguments dynamically.
+//
+
+use std::fs;
+use std::path::Path;
+
+use specifications::version::Version;
+
+use crate::spec::CompletionKind;
+use crate::utils::{get_datasets_dir, get_instance_names, get_package_dir, get_packages_dir};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_matching() {
+        let names: Vec<String> = vec!["hello_world".into(), "arch".into(), "hello".into(), "Hello".into()];
+        assert_eq!(matching(names.clone(), "hel"), vec!["hello".to_string(), "hello_world".to_string()]);
+        assert_eq!(matching(names.clone(), ""), vec!["Hello".to_string(), "arch".to_string(), "hello".to_string(), "hello_world".to_string()]);
+        assert!(matching(names, "x").is_empty());
+    }
+
+    #[test]
+    fn test_kind_parse() {
+        for kind in CompletionKind::ALL {
+            assert_eq!(kind.to_string().parse::<CompletionKind>().unwrap(), kind);
+        }
+        assert!("packages".parse::<CompletionKind>().is_err());
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Lists the names of the directories nested in the given directory.
+///
+/// Since this is only used for completion, any failure to read the directory is treated as it being empty.
+fn dir_names(dir: &Path) -> Vec<String> {
+    match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Keeps the names that start with the given prefix, sorted and deduplicated.
+fn matching(names: Vec<String>, prefix: &str) -> Vec<String> {
+    let mut names: Vec<String> = names.into_iter().filter(|name| name.starts_with(prefix)).collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Lists the locally known names of the given kind that start with the given prefix.
+///
+/// For packages, a prefix containing a colon (e.g., `hello_world:1.`) completes the versions of that package instead, as `NAME:VERSION`.
+///
+/// # Arguments
+/// - `kind`: The [`CompletionKind`] of names to list.
+/// - `prefix`: The partial argument typed so far.
+///
+/// # Returns
+/// The matching names, sorted (versions in version order). Any failure to read the local indices results in fewer (or no) names rather
+/// than an error.
+pub fn complete(kind: CompletionKind, prefix: &str) -> Vec<String> {
+    match kind {
+        CompletionKind::Package => match prefix.split_once(':') {
+            Some((name, _)) => {
+                let mut versions: Vec<Version> = match get_package_dir(name, None) {
+                    Ok(package_dir) => brane_tsk::local::get_package_versions(name, &package_dir).unwrap_or_default(),
+                    Err(_) => vec![],
+                };
+                // Keep these in version order rather than sorting them as strings
+                versions.sort();
+                versions.into_iter().map(|version| format!("{name}:{version}")).filter(|pair| pair.starts_with(prefix)).collect()
+            },
+            None => matching(get_packages_dir().map(|dir| dir_names(&dir)).unwrap_or_default(), prefix),
+        },
+        CompletionKind::Dataset => matching(get_datasets_dir().map(|dir| dir_names(&dir)).unwrap_or_default(), prefix),
+        CompletionKind::Instance => matching(get_instance_names(), prefix),
+    }
+}
+
+
+
+/// Handles the hidden `brane __complete`-subcommand.
+///
+/// Prints the matching names one per line, such that shell completion scripts can use them as-is.
+///
+/// # Arguments
+/// - `kind`: The [`CompletionKind`] of names to list.
+/// - `prefix`: The partial argument typed so far.
+pub fn handle(kind: CompletionKind, prefix: String) {
+    for name in complete(kind, &prefix) {
+        println!("{name}");
+    }
+}
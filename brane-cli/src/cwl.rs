@@ -1,24 +1,279 @@
 use std::collections::HashMap;
 use std::fs::{self, create_dir_all, File, write};
 use std::io::BufReader;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::fmt::Write as _;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use cwl::v11::CwlDocument;
 use specifications::version::Version;
 use specifications::package::{PackageInfo, PackageKind};
 use specifications::common::{Function, Type};
+use brane_cli::cwl_expr::{self, ExprContext};
 use brane_cli::errors::BuildError;
+use serde_json::{Value as JsonValue, json};
+use serde_yaml::Value as YamlValue;
+use specifications::data::glob_matches;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Parses the outputs of the given CWL tool and collects them from the given directory.
+    fn collect_yaml(document: &str, workdir: &Path, inputs: JsonValue) -> Result<serde_json::Map<String, JsonValue>> {
+        let document: YamlValue = serde_yaml::from_str(document).unwrap();
+        let ctx = ExprContext { inputs, self_: JsonValue::Null, runtime: json!({ "outdir": workdir.display().to_string() }) };
+        collect_outputs(&parse_glob_outputs(&document)?, workdir, &ctx)
+    }
+
+    #[test]
+    fn test_output_shape() {
+        let shape = |raw: &str| output_shape(&serde_yaml::from_str(raw).unwrap());
+        assert_eq!(shape("File"), Some(OutputShape::File));
+        assert_eq!(shape("File?"), Some(OutputShape::OptionalFile));
+        assert_eq!(shape("File[]"), Some(OutputShape::FileArray));
+        assert_eq!(shape("[null, File]"), Some(OutputShape::OptionalFile));
+        assert_eq!(shape("{type: array, items: File}"), Some(OutputShape::FileArray));
+        assert_eq!(shape("string"), None);
+        assert_eq!(shape("{type: array, items: string}"), None);
+    }
+
+    #[test]
+    fn test_collect_outputs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for file in ["a.txt", "b.txt", ".hidden.txt", "c.csv"] {
+            fs::write(dir.path().join(file), file).unwrap();
+        }
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("d.txt"), "d").unwrap();
+
+        let outputs = collect_yaml(
+            r#"
+class: CommandLineTool
+outputs:
+  single: { type: File, outputBinding: { glob: a.txt } }
+  many: { type: "File[]", outputBinding: { glob: "*.txt" } }
+  listed: { type: "File[]", outputBinding: { glob: ["c.*", "sub/*.txt"] } }
+  missing: { type: "File?", outputBinding: { glob: "nope.*" } }
+  from_input: { type: File, outputBinding: { glob: "$(inputs.name).txt" } }
+  stdout: { type: stdout }
+"#,
+            dir.path(),
+            json!({ "name": "b" }),
+        )
+        .unwrap();
+        let path = |file: &str| json!({ "class": "File", "path": dir.path().join(file).display().to_string() });
+        assert_eq!(outputs.get("single"), Some(&path("a.txt")));
+        // Hidden files are not matched by wildcards
+        assert_eq!(outputs.get("many"), Some(&json!([path("a.txt"), path("b.txt")])));
+        assert_eq!(outputs.get("listed"), Some(&json!([path("c.csv"), path("sub/d.txt")])));
+        assert_eq!(outputs.get("missing"), Some(&JsonValue::Null));
+        assert_eq!(outputs.get("from_input"), Some(&path("b.txt")));
+        assert_eq!(outputs.get("stdout"), None);
+    }
+
+    #[test]
+    fn test_collect_outputs_cardinality() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for file in ["a.txt", "b.txt"] {
+            fs::write(dir.path().join(file), file).unwrap();
+        }
+
+        // A single file may not be matched by multiple files, nor by none at all
+        assert!(collect_yaml("outputs: { out: { type: File, outputBinding: { glob: '*.txt' } } }", dir.path(), json!({})).is_err());
+        assert!(collect_yaml("outputs: { out: { type: 'File?', outputBinding: { glob: '*.txt' } } }", dir.path(), json!({})).is_err());
+        assert!(collect_yaml("outputs: { out: { type: File, outputBinding: { glob: '*.csv' } } }", dir.path(), json!({})).is_err());
+    }
+}
+
+/// The shape of the value a CWL output binds to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OutputShape {
+    /// Exactly one file.
+    File,
+    /// At most one file.
+    OptionalFile,
+    /// Any number of files.
+    FileArray,
+}
+
+/// A CWL output that is collected from the working directory via `outputBinding.glob`.
+#[derive(Clone, Debug)]
+struct GlobOutput {
+    /// The identifier of the output.
+    id:    String,
+    /// The shape of the value it binds to.
+    shape: OutputShape,
+    /// The glob patterns, relative to the working directory.
+    globs: Vec<String>,
+}
+
+/// Resolves the shape of a CWL output type, if it is one that we collect from files.
+fn output_shape(ty: &YamlValue) -> Option<OutputShape> {
+    match ty {
+        YamlValue::String(ty) => match ty.as_str() {
+            "File" => Some(OutputShape::File),
+            "File?" => Some(OutputShape::OptionalFile),
+            "File[]" | "File[]?" => Some(OutputShape::FileArray),
+            _ => None,
+        },
+        YamlValue::Mapping(map) => {
+            if map.get("type").and_then(YamlValue::as_str) == Some("array") && map.get("items").and_then(YamlValue::as_str) == Some("File") {
+                Some(OutputShape::FileArray)
+            } else {
+                None
+            }
+        },
+        // `[null, File]` is the long form of `File?`
+        YamlValue::Sequence(tys) => {
+            let (nulls, rest): (Vec<&YamlValue>, Vec<&YamlValue>) = tys.iter().partition(|t| t.as_str() == Some("null"));
+            match (nulls.len(), rest.as_slice()) {
+                (1, [ty]) => match output_shape(ty)? {
+                    OutputShape::File => Some(OutputShape::OptionalFile),
+                    shape => Some(shape),
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Parses the outputs of a CWL CommandLineTool that have an `outputBinding.glob`.
+///
+/// Outputs may be given either as a map from identifier to output, or as a list of outputs with an `id` field. Outputs without a glob (e.g.,
+/// `stdout`) or with a non-file type are skipped.
+fn parse_glob_outputs(document: &YamlValue) -> Result<Vec<GlobOutput>> {
+    let outputs: Vec<(String, &YamlValue)> = match document.get("outputs") {
+        Some(YamlValue::Mapping(map)) => map.iter().filter_map(|(id, output)| Some((id.as_str()?.to_string(), output))).collect(),
+        Some(YamlValue::Sequence(list)) => list
+            .iter()
+            .map(|output| {
+                let id: &str = output.get("id").and_then(YamlValue::as_str).context("❌ CWL output in list has no 'id'")?;
+                Ok((id.trim_start_matches('#').to_string(), output))
+            })
+            .collect::<Result<_>>()?,
+        Some(_) => bail!("❌ CWL 'outputs' is neither a map nor a list"),
+        None => vec![],
+    };
+
+    let mut res: Vec<GlobOutput> = Vec::with_capacity(outputs.len());
+    for (id, output) in outputs {
+        let Some(glob) = output.get("outputBinding").and_then(|b| b.get("glob")) else { continue };
+        let globs: Vec<String> = match glob {
+            YamlValue::String(glob) => vec![glob.clone()],
+            YamlValue::Sequence(globs) => globs.iter().filter_map(|g| g.as_str().map(String::from)).collect(),
+            _ => bail!("❌ 'outputBinding.glob' of CWL output '{id}' is not a string or a list of strings"),
+        };
+        let Some(shape) = output.get("type").and_then(output_shape) else {
+            println!("⚠️ Skipping CWL output '{id}': only File, File? and File[] outputs can be collected");
+            continue;
+        };
+        res.push(GlobOutput { id, shape, globs });
+    }
+    Ok(res)
+}
+
+/// Evaluates a glob pattern against the given working directory, returning the matching files in sorted order.
+fn eval_glob(workdir: &Path, glob: &str) -> Result<Vec<PathBuf>> {
+    let mut candidates: Vec<PathBuf> = vec![workdir.to_path_buf()];
+    for component in glob.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        let mut next: Vec<PathBuf> = Vec::new();
+        for dir in candidates.into_iter().filter(|c| c.is_dir()) {
+            for entry in fs::read_dir(&dir).with_context(|| format!("❌ Failed to read directory '{}'", dir.display()))? {
+                let entry = entry.with_context(|| format!("❌ Failed to read entry in directory '{}'", dir.display()))?;
+                let name: String = entry.file_name().to_string_lossy().into_owned();
+                // Like shells, wildcards don't match hidden files
+                if name.starts_with('.') && !component.starts_with('.') {
+                    continue;
+                }
+                if glob_matches(component, &name) {
+                    next.push(entry.path());
+                }
+            }
+        }
+        candidates = next;
+    }
+    let mut files: Vec<PathBuf> = candidates.into_iter().filter(|c| c.is_file()).collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Evaluates any expressions in a glob, which may result in zero or more patterns.
+fn eval_glob_expr(glob: &str, ctx: &ExprContext) -> Result<Vec<String>> {
+    if !cwl_expr::contains_expression(glob) {
+        return Ok(vec![glob.into()]);
+    }
+    match cwl_expr::evaluate(glob, ctx).with_context(|| format!("❌ Failed to evaluate glob '{glob}'"))? {
+        JsonValue::Null => Ok(vec![]),
+        JsonValue::String(glob) => Ok(vec![glob]),
+        JsonValue::Array(globs) => globs
+            .into_iter()
+            .map(|g| match g {
+                JsonValue::String(g) => Ok(g),
+                other => bail!("❌ Glob '{glob}' evaluated to a list containing non-string {other}"),
+            })
+            .collect(),
+        other => bail!("❌ Glob '{glob}' evaluated to non-string {other}"),
+    }
+}
+
+/// Collects the files for every glob-bound output of a tool that ran in `workdir`.
+///
+/// A `File` output must match exactly one file and a `File?` output at most one; a `File[]` output collects every match.
+fn collect_outputs(outputs: &[GlobOutput], workdir: &Path, ctx: &ExprContext) -> Result<serde_json::Map<String, JsonValue>> {
+    let file = |path: &Path| json!({ "class": "File", "path": path.display().to_string() });
+
+    let mut res: serde_json::Map<String, JsonValue> = serde_json::Map::new();
+    for output in outputs {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for glob in output.globs.iter().map(|g| eval_glob_expr(g, ctx)).collect::<Result<Vec<_>>>()?.iter().flatten() {
+            for path in eval_glob(workdir, glob)? {
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+
+        let value: JsonValue = match (output.shape, files.as_slice()) {
+            (OutputShape::FileArray, files) => JsonValue::Array(files.iter().map(|f| file(f)).collect()),
+            (OutputShape::File | OutputShape::OptionalFile, [path]) => file(path),
+            (OutputShape::OptionalFile, []) => JsonValue::Null,
+            (OutputShape::File, []) => bail!("❌ CWL output '{}' matched no files for glob {:?}", output.id, output.globs),
+            (_, files) => bail!(
+                "❌ CWL output '{}' is a single File, but glob {:?} matched {} files: {}",
+                output.id,
+                output.globs,
+                files.len(),
+                files.iter().map(|f| format!("'{}'", f.display())).collect::<Vec<_>>().join(", ")
+            ),
+        };
+        res.insert(output.id.clone(), value);
+    }
+    Ok(res)
+}
 
 /// Parses a CWL file and generates a Brane-compatible package directory & Docker image.
-pub async fn handle(path: PathBuf) -> Result<()> {
+///
+/// If `dockerfile_out` is given, the generated Dockerfile is also written there. If `dry_run` is true, only the package directory (which is
+/// the Dockerfile's build context) is generated and the Docker image is not built.
+///
+/// Returns the name of the built image, if any.
+pub async fn handle(path: PathBuf, dockerfile_out: Option<PathBuf>, dry_run: bool) -> Result<Option<String>> {
     // Open and parse CWL
     let file = File::open(&path).context("❌ Failed to open CWL file")?;
     let reader = BufReader::new(file);
     let document = CwlDocument::from_reader(reader).context("❌ Failed to parse CWL document")?;
 
+    // Refuse documents with expressions we can't evaluate rather than mis-translating them
+    let raw: YamlValue = serde_yaml::from_reader(BufReader::new(File::open(&path).context("❌ Failed to open CWL file")?))
+        .context("❌ Failed to parse CWL document")?;
+    cwl_expr::check_document(&raw).context("❌ CWL document uses unsupported expressions")?;
+
     match &document {
         CwlDocument::CommandLineTool(tool) => {
             println!("✅ Parsed CWL CommandLineTool");
@@ -44,7 +299,8 @@ pub async fn handle(path: PathBuf) -> Result<()> {
             write(out_dir.join("Package.toml"), toml).context("❌ Failed to write Package.toml")?;
 
             // --- entry.sh ---
-            let entry = "#!/bin/bash\ncwltool hello_world.cwl\n";
+            // Outputs end up in the directory the tool is started in (`/app` by default, see `run()`)
+            let entry = "#!/bin/bash\ncwltool --outdir \"$PWD\" /app/hello_world.cwl \"$@\"\n";
             write(out_dir.join("entry.sh"), entry).context("❌ Failed to write entry.sh")?;
 
             // --- Dockerfile ---
@@ -58,9 +314,16 @@ RUN chmod +x entry.sh
 CMD ["./entry.sh"]
 "#;
             write(out_dir.join("Dockerfile"), dockerfile).context("❌ Failed to write Dockerfile")?;
+            if let Some(dockerfile_out) = &dockerfile_out {
+                write(dockerfile_out, dockerfile).with_context(|| format!("❌ Failed to write Dockerfile to '{}'", dockerfile_out.display()))?;
+            }
 
             // --- Copy CWL ---
             fs::copy(&path, out_dir.join("hello_world.cwl")).context("❌ Failed to copy CWL file")?;
+            if dry_run {
+                println!("📝 Build context written to: {} (build it with: docker build {})", out_dir.display(), out_dir.display());
+                return Ok(None);
+            }
 
             // --- Docker build ---
             println!("🐳 Building Docker image...");
@@ -95,19 +358,76 @@ CMD ["./entry.sh"]
             package_info.to_path(out_dir.join("package.yml")).context("❌ Failed to write package.yml")?;
 
             println!("📦 Brane CWL package available at: {}\\", out_dir.display());
-        }
+            Ok(Some(image_name))
+        },
         _ => {
             println!("⚠️ Unsupported CWL class: {:?}", document);
-        }
+            Ok(None)
+        },
+    }
+}
+
+/// Builds a CWL CommandLineTool, runs it with `workdir` as its output directory and then collects its outputs from there (see
+/// [`collect()`]).
+///
+/// The tool's own output is redirected to stderr, to keep it apart from the collected outputs.
+pub async fn run(path: PathBuf, workdir: PathBuf, inputs: Option<PathBuf>) -> Result<()> {
+    let Some(image_name) = handle(path.clone(), None, false).await? else {
+        bail!("❌ Can only run a CWL CommandLineTool");
+    };
+
+    create_dir_all(&workdir).with_context(|| format!("❌ Failed to create directory '{}'", workdir.display()))?;
+    let workdir: PathBuf = fs::canonicalize(&workdir).with_context(|| format!("❌ Failed to resolve directory '{}'", workdir.display()))?;
+    let mut command = Command::new("docker");
+    command.args(["run", "--rm", "--workdir", "/out", "--volume"]).arg(format!("{}:/out", workdir.display()));
+    if let Some(inputs) = &inputs {
+        let inputs: PathBuf = fs::canonicalize(inputs).with_context(|| format!("❌ Failed to resolve CWL job file '{}'", inputs.display()))?;
+        command.arg("--volume").arg(format!("{}:/app/job:ro", inputs.display()));
+    }
+    command.args(["--entrypoint", "/app/entry.sh"]).arg(&image_name);
+    if inputs.is_some() {
+        command.arg("/app/job");
     }
+    println!("🚀 Running {image_name} in {}...", workdir.display());
+    let status = command.stdout(Stdio::from(std::io::stderr())).status().context("❌ Failed to invoke docker run")?;
+    if !status.success() {
+        bail!("❌ CWL tool failed ({status})");
+    }
+
+    collect(path, workdir, inputs)
+}
+
+/// Collects the outputs of a CWL CommandLineTool that ran in `workdir` by evaluating their `outputBinding.glob` patterns.
+///
+/// Globs may refer to the job's inputs (e.g., `$(inputs.name).txt`), which are read from the optional `inputs` job file (JSON or YAML).
+///
+/// Prints the collected outputs as a JSON object mapping every output identifier to a CWL `File` object, a list of them or `null`.
+pub fn collect(path: PathBuf, workdir: PathBuf, inputs: Option<PathBuf>) -> Result<()> {
+    let file = File::open(&path).context("❌ Failed to open CWL file")?;
+    let document: YamlValue = serde_yaml::from_reader(BufReader::new(file)).context("❌ Failed to parse CWL document")?;
+    if document.get("class").and_then(YamlValue::as_str) != Some("CommandLineTool") {
+        bail!("❌ Can only collect outputs of a CWL CommandLineTool");
+    }
+
+    let inputs: JsonValue = match inputs {
+        Some(inputs) => {
+            let file = File::open(&inputs).with_context(|| format!("❌ Failed to open CWL job file '{}'", inputs.display()))?;
+            serde_yaml::from_reader(BufReader::new(file)).with_context(|| format!("❌ Failed to parse CWL job file '{}'", inputs.display()))?
+        },
+        None => json!({}),
+    };
+    let ctx = ExprContext { inputs, self_: JsonValue::Null, runtime: json!({ "outdir": workdir.display().to_string() }) };
 
+    let outputs: Vec<GlobOutput> = parse_glob_outputs(&document)?;
+    let collected = collect_outputs(&outputs, &workdir, &ctx)?;
+    println!("{}", serde_json::to_string_pretty(&collected).context("❌ Failed to serialize collected outputs")?);
     Ok(())
 }
 
 /// `brane package build` calls this entry point for CWL packages.
-pub fn build(_workdir: PathBuf, file: PathBuf) -> Result<(), BuildError> {
+pub fn build(_workdir: PathBuf, file: PathBuf, dockerfile_out: Option<PathBuf>, dry_run: bool) -> Result<(), BuildError> {
     println!("🛠️  Building Brane CWL package...");
-    futures::executor::block_on(handle(file))
+    futures::executor::block_on(handle(file, dockerfile_out, dry_run))
         .map_err(|e| BuildError::PackageInfoFromOpenAPIError { source: e })
 }
 
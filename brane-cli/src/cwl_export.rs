@@ -0,0 +1,634 @@
+//  CWL EXPORT.rs
+//
+//  Description:
+//!   Implements exporting a compiled BraneScript [`Workflow`] as a CWL
+//!   Workflow document, i.e., the reverse of importing CWL tools as
+//!   packages.
+//!
+//!   Every task becomes a `CommandLineTool` that runs the package image
+//!   through `branelet`, and every call of a task becomes a step in the
+//!   CWL `Workflow`. Constructs that have no static CWL equivalent (e.g.,
+//!   branches, loops or dynamic parallelism) are reported as
+//!   un-exportable instead of being dropped.
+//
+
+use std::collections::{HashMap, HashSet};
+
+use brane_ast::ast::{ComputeTaskDef, Edge, EdgeInstr, TaskDef};
+use brane_ast::{DataType, Workflow};
+use brane_shr::utilities::closest_names;
+use serde::Serialize;
+use specifications::data::{AvailabilityKind, DataName};
+use specifications::package::{PackageIndex, PackageInfo};
+
+pub use crate::errors::CwlExportError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_ast::{CompileResult, ParserOptions};
+    use brane_dsl::Language;
+    use brane_shr::utilities::{create_data_index, create_package_index};
+
+    use super::*;
+
+
+    /// Compiles the given BraneScript snippet against the test packages and datasets, and exports it.
+    fn export_snippet(source: &str) -> Result<CwlDocument, Error> {
+        let pindex: PackageIndex = create_package_index();
+        let workflow: Workflow =
+            match brane_ast::compile_program(source.as_bytes(), &pindex, &create_data_index(), &ParserOptions::new(Language::BraneScript)) {
+                CompileResult::Workflow(wf, _) => wf,
+                _ => panic!("Failed to compile test snippet"),
+            };
+        export(&workflow, &pindex)
+    }
+
+    #[test]
+    fn test_export_chain() {
+        let doc: CwlDocument = export_snippet(
+            "import data_test;\nlet first := run_script(new Data{ name := \"Test\" });\nlet second := run_script(first);\nreturn second;\n",
+        )
+        .unwrap();
+        assert_eq!(doc.cwl_version, CWL_VERSION);
+
+        // The task is called twice, but only becomes a single tool
+        assert_eq!(doc.graph.len(), 2);
+        let workflow: &CwlWorkflow = match &doc.graph[0] {
+            CwlProcess::Workflow(workflow) => workflow,
+            CwlProcess::CommandLineTool(_) => panic!("Expected the workflow to come first"),
+        };
+        let tool: &CwlTool = match &doc.graph[1] {
+            CwlProcess::CommandLineTool(tool) => tool,
+            CwlProcess::Workflow(_) => panic!("Expected a tool after the workflow"),
+        };
+        assert_eq!(tool.id, "data_test_1_0_0_run_script");
+        assert_eq!(tool.inputs.iter().map(|i| i.id.as_str()).collect::<Vec<&str>>(), vec!["dataset"]);
+
+        // The dataset becomes a workflow input, and the second step reads the output of the first
+        assert_eq!(workflow.inputs.iter().map(|i| i.id.as_str()).collect::<Vec<&str>>(), vec!["dataset_Test"]);
+        assert_eq!(workflow.steps.iter().map(|s| s.id.as_str()).collect::<Vec<&str>>(), vec!["run_script_1", "run_script_2"]);
+        assert_eq!(workflow.steps[0].inputs[0].source.as_deref(), Some("dataset_Test"));
+        assert_eq!(workflow.steps[1].inputs[0].source.as_deref(), Some("run_script_1/output"));
+        assert!(workflow.steps.iter().all(|s| s.run == "#data_test_1_0_0_run_script"));
+        assert_eq!(workflow.outputs.len(), 2);
+    }
+
+    #[test]
+    fn test_export_unsupported() {
+        match export_snippet("import data_test;\nif (true) { run_script(new Data{ name := \"Test\" }); }\n") {
+            Err(Error::UnsupportedConstructs { constructs }) => {
+                assert_eq!(constructs.len(), 1);
+                assert!(constructs[0].starts_with("conditional branch"));
+            },
+            Err(err) => panic!("Expected an UnsupportedConstructs error, got '{err}'"),
+            Ok(_) => panic!("Exported a workflow with a branch"),
+        }
+    }
+
+    #[test]
+    fn test_to_cwl_type() {
+        assert!(matches!(to_cwl_type(&DataType::Integer), Some(CwlType::Named("long"))));
+        assert!(matches!(to_cwl_type(&DataType::IntermediateResult), Some(CwlType::Named("Directory"))));
+        assert!(matches!(
+            to_cwl_type(&DataType::Array { elem_type: Box::new(DataType::String) }),
+            Some(CwlType::Array { ty: "array", items }) if matches!(**items, CwlType::Named("string"))
+        ));
+        assert!(to_cwl_type(&DataType::Class { name: "Foo".into() }).is_none());
+        assert_eq!(sanitize_id("data_test:1.0.0/run-script"), "data_test_1_0_0_run_script");
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The version of CWL documents we generate.
+pub const CWL_VERSION: &str = "v1.2";
+
+/// The ID of the toplevel workflow in the generated document.
+pub const CWL_WORKFLOW_ID: &str = "main";
+
+/// The name of the output of every generated tool.
+const OUTPUT_ID: &str = "output";
+
+
+
+
+
+/***** CWL DOCUMENT *****/
+/// Defines a (packed) CWL document, which contains the workflow and all the tools it uses.
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlDocument {
+    /// The version of CWL this document adheres to.
+    #[serde(rename = "cwlVersion")]
+    pub cwl_version: &'static str,
+    /// The workflow and tools in this document.
+    #[serde(rename = "$graph")]
+    pub graph: Vec<CwlProcess>,
+}
+
+/// Defines the processes that may occur in a [`CwlDocument`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "class")]
+pub enum CwlProcess {
+    /// A tool that runs a single package function.
+    CommandLineTool(CwlTool),
+    /// The workflow that chains the tools together.
+    Workflow(CwlWorkflow),
+}
+
+/// Defines a CWL `CommandLineTool`, which we generate for every task in the workflow.
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlTool {
+    /// The identifier of the tool.
+    pub id: String,
+    /// A human-readable label for the tool.
+    pub label: String,
+    /// The requirements of the tool (i.e., its container).
+    pub requirements: Vec<CwlRequirement>,
+    /// The arguments to pass to the container's entrypoint (`branelet`).
+    pub arguments: Vec<String>,
+    /// The inputs of the tool, one per function argument.
+    pub inputs: Vec<CwlParameter>,
+    /// The outputs of the tool (at most one).
+    pub outputs: Vec<CwlParameter>,
+}
+
+/// Defines the requirements we may put on a [`CwlTool`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "class")]
+pub enum CwlRequirement {
+    /// The tool runs in the given container.
+    DockerRequirement {
+        /// The image to run.
+        #[serde(rename = "dockerPull")]
+        docker_pull: String,
+        /// The path in the container where outputs are written (i.e., Brane's results folder).
+        #[serde(rename = "dockerOutputDirectory")]
+        docker_output_directory: String,
+    },
+    /// The tool uses JavaScript expressions to encode its arguments.
+    InlineJavascriptRequirement {},
+}
+
+/// Defines an input or output parameter of a tool or workflow.
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlParameter {
+    /// The identifier of the parameter.
+    pub id: String,
+    /// The type of the parameter.
+    #[serde(rename = "type")]
+    pub ty: CwlType,
+    /// For tool outputs, how to collect the output.
+    #[serde(rename = "outputBinding", skip_serializing_if = "Option::is_none")]
+    pub output_binding: Option<CwlOutputBinding>,
+    /// For workflow outputs, the step output that provides it.
+    #[serde(rename = "outputSource", skip_serializing_if = "Option::is_none")]
+    pub output_source: Option<String>,
+}
+
+/// Defines how to collect the output of a tool.
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlOutputBinding {
+    /// The glob (relative to the output directory) that matches the output.
+    pub glob: String,
+}
+
+/// Defines the CWL types that Brane types map to.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum CwlType {
+    /// A named type (e.g., `string`, `Directory` or `Any`).
+    Named(&'static str),
+    /// An array of some other type.
+    Array {
+        /// Always `array`.
+        #[serde(rename = "type")]
+        ty:    &'static str,
+        /// The type of the elements.
+        items: Box<CwlType>,
+    },
+}
+
+/// Defines the CWL `Workflow` that chains the tools together.
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlWorkflow {
+    /// The identifier of the workflow.
+    pub id:      String,
+    /// The inputs of the workflow, i.e., any argument or dataset that isn't known statically.
+    pub inputs:  Vec<CwlParameter>,
+    /// The outputs of the workflow, i.e., any intermediate result produced.
+    pub outputs: Vec<CwlParameter>,
+    /// The steps in the workflow, one per task call.
+    pub steps:   Vec<CwlStep>,
+}
+
+/// Defines a single step in a [`CwlWorkflow`].
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlStep {
+    /// The identifier of the step.
+    pub id:     String,
+    /// The tool that this step runs.
+    pub run:    String,
+    /// The inputs to the tool.
+    #[serde(rename = "in")]
+    pub inputs: Vec<CwlStepInput>,
+    /// The outputs of the tool that this step exposes.
+    pub out:    Vec<String>,
+}
+
+/// Defines a single input to a [`CwlStep`].
+#[derive(Clone, Debug, Serialize)]
+pub struct CwlStepInput {
+    /// The identifier of the tool input this provides.
+    pub id:      String,
+    /// The workflow input or step output to take the value from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source:  Option<String>,
+    /// A constant value (if the value is known statically).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Turns the given name into something that is safe to use as a CWL identifier.
+///
+/// # Arguments
+/// - `name`: The name to sanitize.
+///
+/// # Returns
+/// The name with every character that isn't alphanumeric or an underscore replaced by an underscore.
+fn sanitize_id(name: &str) -> String { name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect() }
+
+/// Maps a Brane [`DataType`] to its CWL equivalent.
+///
+/// # Arguments
+/// - `data_type`: The [`DataType`] to map.
+///
+/// # Returns
+/// The matching [`CwlType`], or [`None`] if the type has no CWL equivalent (e.g., classes or functions).
+fn to_cwl_type(data_type: &DataType) -> Option<CwlType> {
+    match data_type {
+        DataType::Boolean => Some(CwlType::Named("boolean")),
+        DataType::Integer => Some(CwlType::Named("long")),
+        DataType::Real => Some(CwlType::Named("double")),
+        DataType::String | DataType::Semver => Some(CwlType::Named("string")),
+        DataType::Data | DataType::IntermediateResult => Some(CwlType::Named("Directory")),
+        DataType::Array { elem_type } => Some(CwlType::Array { ty: "array", items: Box::new(to_cwl_type(elem_type)?) }),
+        DataType::Any | DataType::Numeric | DataType::Addable | DataType::NonVoid => Some(CwlType::Named("Any")),
+        DataType::Void | DataType::Callable | DataType::Function { .. } | DataType::Class { .. } => None,
+    }
+}
+
+/// Returns whether the given [`DataType`] refers to a dataset or intermediate result.
+#[inline]
+fn is_data(data_type: &DataType) -> bool { matches!(data_type, DataType::Data | DataType::IntermediateResult) }
+
+/// Extracts the constant values pushed for the trailing arguments of a task call.
+///
+/// Arguments are pushed on the stack in order right before the call, so any constants at the end of the preceding linear edge correspond to the last
+/// arguments of the task. We stop at the first instruction that isn't a constant, since we cannot statically know what it computes.
+///
+/// # Arguments
+/// - `edges`: The list of edges that contains the call.
+/// - `pc`: The index of the call in `edges`.
+/// - `arity`: The number of arguments of the called task.
+///
+/// # Returns
+/// A list with, for every argument, its value if it is a constant.
+fn constant_args(edges: &[Edge], pc: usize, arity: usize) -> Vec<Option<serde_json::Value>> {
+    let mut values: Vec<Option<serde_json::Value>> = vec![None; arity];
+    if pc == 0 {
+        return values;
+    }
+    if let Edge::Linear { instrs, next } = &edges[pc - 1] {
+        if *next != pc {
+            return values;
+        }
+        for (i, instr) in instrs.iter().rev().take(arity).enumerate() {
+            let value: serde_json::Value = match instr {
+                EdgeInstr::Boolean { value } => (*value).into(),
+                EdgeInstr::Integer { value } => (*value).into(),
+                EdgeInstr::Real { value } => (*value).into(),
+                EdgeInstr::String { value } => value.clone().into(),
+                _ => break,
+            };
+            values[arity - 1 - i] = Some(value);
+        }
+    }
+    values
+}
+
+
+
+
+
+/***** EXPORTER *****/
+/// Keeps track of the CWL document while we walk the workflow.
+struct Exporter<'w> {
+    /// The workflow we're exporting.
+    workflow: &'w Workflow,
+    /// The package index to resolve tasks' packages in.
+    pindex:   &'w PackageIndex,
+
+    /// The tools generated so far, together with the task they were generated for.
+    tools: Vec<(usize, CwlTool)>,
+    /// The inputs of the workflow generated so far.
+    inputs: Vec<CwlParameter>,
+    /// The outputs of the workflow generated so far.
+    outputs: Vec<CwlParameter>,
+    /// The steps of the workflow generated so far.
+    steps: Vec<CwlStep>,
+    /// Maps intermediate results to the steps that produce them.
+    results: HashMap<String, String>,
+    /// Counts how often every function has been called (to generate unique step IDs).
+    calls: HashMap<String, usize>,
+    /// Any constructs that we encountered but cannot export.
+    unsupported: Vec<String>,
+}
+
+impl<'w> Exporter<'w> {
+    /// Returns the ID of the tool for the given task, generating it if we haven't already.
+    ///
+    /// # Arguments
+    /// - `task`: The index of the task in the workflow's symbol table.
+    /// - `def`: The definition of that task.
+    ///
+    /// # Errors
+    /// This function errors if the task's package is unknown, or if it has arguments that cannot be represented in CWL.
+    fn tool(&mut self, task: usize, def: &ComputeTaskDef) -> Result<String, Error> {
+        if let Some((_, tool)) = self.tools.iter().find(|(t, _)| *t == task) {
+            return Ok(tool.id.clone());
+        }
+        let id: String = sanitize_id(&format!("{}_{}_{}", def.package, def.version, def.function.name));
+
+        // Resolve the package to find its kind
+        let info: &PackageInfo = self.pindex.get(&def.package, Some(&def.version)).ok_or_else(|| Error::UnknownPackage {
+            name: def.package.clone(),
+            version: def.version,
+            suggestions: if self.pindex.latest.contains_key(&def.package) { vec![] } else { closest_names(&def.package, self.pindex.latest.keys()) },
+        })?;
+        let kind: String = info.kind.into();
+
+        // Generate the inputs, as well as the expression that encodes them as Brane arguments
+        let mut inputs: Vec<CwlParameter> = Vec::with_capacity(def.args_names.len());
+        let mut args: Vec<String> = Vec::with_capacity(def.args_names.len());
+        for (name, data_type) in def.args_names.iter().zip(def.function.args.iter()) {
+            let ty: CwlType = to_cwl_type(data_type).ok_or_else(|| Error::UnsupportedArgumentType {
+                task: def.function.name.clone(),
+                arg:  name.clone(),
+                ty:   data_type.to_string(),
+            })?;
+            inputs.push(CwlParameter { id: name.clone(), ty, output_binding: None, output_source: None });
+
+            // Datasets are passed as the path where they are mounted
+            let key: String = serde_json::Value::from(name.as_str()).to_string();
+            args.push(if is_data(data_type) { format!("{key}: inputs.{name}.path") } else { format!("{key}: inputs.{name}") });
+        }
+
+        // Generate the output based on the return type
+        let outputs: Vec<CwlParameter> = match &def.function.ret {
+            DataType::Void => vec![],
+            ret if is_data(ret) => vec![CwlParameter {
+                id: OUTPUT_ID.into(),
+                ty: CwlType::Named("Directory"),
+                output_binding: Some(CwlOutputBinding { glob: ".".into() }),
+                output_source: None,
+            }],
+            // Other values are reported by branelet on stdout
+            _ => vec![CwlParameter { id: OUTPUT_ID.into(), ty: CwlType::Named("stdout"), output_binding: None, output_source: None }],
+        };
+
+        // Build the tool
+        let tool: CwlTool = CwlTool {
+            id: id.clone(),
+            label: format!("{}:{} / {}", def.package, def.version, def.function.name),
+            requirements: vec![
+                CwlRequirement::DockerRequirement {
+                    docker_pull: format!("{}:{}", def.package, def.version),
+                    docker_output_directory: "/result".into(),
+                },
+                CwlRequirement::InlineJavascriptRequirement {},
+            ],
+            arguments: vec![
+                "--application-id".into(),
+                "cwl".into(),
+                "--location-id".into(),
+                "localhost".into(),
+                "--job-id".into(),
+                "1".into(),
+                kind,
+                def.function.name.clone(),
+                format!("$(btoa(JSON.stringify({{ {} }})))", args.join(", ")),
+            ],
+            inputs,
+            outputs,
+        };
+        self.tools.push((task, tool));
+        Ok(id)
+    }
+
+    /// Adds an input to the workflow if it doesn't exist yet.
+    ///
+    /// # Arguments
+    /// - `id`: The identifier of the input.
+    /// - `ty`: The type of the input.
+    fn input(&mut self, id: &str, ty: CwlType) {
+        if !self.inputs.iter().any(|i| i.id == id) {
+            self.inputs.push(CwlParameter { id: id.into(), ty, output_binding: None, output_source: None });
+        }
+    }
+
+    /// Exports a single task call as a step.
+    ///
+    /// # Arguments
+    /// - `edges`: The list of edges that contains the call.
+    /// - `pc`: The index of the call in `edges`.
+    /// - `task`: The index of the called task in the workflow's symbol table.
+    /// - `input`: The datasets and intermediate results that are input to the call.
+    /// - `result`: The intermediate result that the call produces, if any.
+    ///
+    /// # Errors
+    /// This function errors if the task cannot be represented as a tool, or if we cannot determine which data argument gets which input.
+    fn step(
+        &mut self,
+        edges: &[Edge],
+        pc: usize,
+        task: usize,
+        input: &HashMap<DataName, Option<AvailabilityKind>>,
+        result: Option<&str>,
+    ) -> Result<(), Error> {
+        let workflow: &'w Workflow = self.workflow;
+        let def: &'w ComputeTaskDef = match &workflow.table.tasks[task] {
+            TaskDef::Compute(def) => def,
+            TaskDef::Transfer => {
+                self.unsupported.push(format!("transfer task at edge {pc}"));
+                return Ok(());
+            },
+        };
+        let run: String = self.tool(task, def)?;
+
+        // Generate a unique ID for this step
+        let count: &mut usize = self.calls.entry(def.function.name.clone()).or_insert(0);
+        *count += 1;
+        let id: String = sanitize_id(&format!("{}_{}", def.function.name, count));
+
+        // Find out which data arguments get which inputs
+        let data_args: Vec<usize> = def.function.args.iter().enumerate().filter(|(_, ty)| is_data(ty)).map(|(i, _)| i).collect();
+        let mut data_inputs: Vec<&DataName> = input.keys().collect();
+        data_inputs.sort_by(|lhs, rhs| lhs.name().cmp(rhs.name()));
+        if !data_inputs.is_empty() && (data_args.len() != 1 || data_inputs.len() != 1) {
+            return Err(Error::AmbiguousDataInputs { task: def.function.name.clone(), args: data_args.len(), inputs: data_inputs.len() });
+        }
+
+        // Generate the step inputs
+        let constants: Vec<Option<serde_json::Value>> = constant_args(edges, pc, def.args_names.len());
+        let mut inputs: Vec<CwlStepInput> = Vec::with_capacity(def.args_names.len());
+        for (i, (name, data_type)) in def.args_names.iter().zip(def.function.args.iter()).enumerate() {
+            let source: String = if is_data(data_type) && !data_inputs.is_empty() {
+                // Wire it to the step producing it or to a workflow input for the dataset
+                match data_inputs[0] {
+                    DataName::IntermediateResult(name) if self.results.contains_key(name) => format!("{}/{}", self.results[name], OUTPUT_ID),
+                    data_name => {
+                        let source: String = sanitize_id(&format!("{}_{}", if data_name.is_data() { "dataset" } else { "result" }, data_name.name()));
+                        self.input(&source, CwlType::Named("Directory"));
+                        source
+                    },
+                }
+            } else if let Some(value) = constants[i].clone() {
+                inputs.push(CwlStepInput { id: name.clone(), source: None, default: Some(value) });
+                continue;
+            } else {
+                // Expose it as a workflow input (the type is known to be supported since we generated the tool already)
+                let source: String = format!("{id}_{name}");
+                self.input(&source, to_cwl_type(data_type).unwrap());
+                source
+            };
+            inputs.push(CwlStepInput { id: name.clone(), source: Some(source), default: None });
+        }
+
+        // Note the result, if any
+        let out: Vec<String> = if matches!(def.function.ret, DataType::Void) { vec![] } else { vec![OUTPUT_ID.into()] };
+        if let Some(result) = result {
+            self.results.insert(result.into(), id.clone());
+            self.outputs.push(CwlParameter {
+                id: format!("{id}_{OUTPUT_ID}"),
+                ty: CwlType::Named("Directory"),
+                output_binding: None,
+                output_source: Some(format!("{id}/{OUTPUT_ID}")),
+            });
+        }
+
+        // Done
+        self.steps.push(CwlStep { id, run: format!("#{run}"), inputs, out });
+        Ok(())
+    }
+
+    /// Walks the given list of edges, exporting any task calls we find along the way.
+    ///
+    /// # Arguments
+    /// - `edges`: The list of edges to walk.
+    ///
+    /// # Errors
+    /// This function errors if any of the task calls cannot be exported.
+    fn walk(&mut self, edges: &[Edge]) -> Result<(), Error> {
+        let mut done: HashSet<usize> = HashSet::new();
+        let mut pc: usize = 0;
+        while pc < edges.len() && done.insert(pc) {
+            match &edges[pc] {
+                Edge::Node { task, input, result, next, .. } => {
+                    self.step(edges, pc, *task, input, result.as_deref())?;
+                    pc = *next;
+                },
+                // Calls to builtins or task-less functions only compute BraneScript values, which we cannot represent anyway
+                Edge::Linear { next, .. } | Edge::Call { next, .. } | Edge::Join { next, .. } => pc = *next,
+                Edge::Stop {} | Edge::Return { .. } => break,
+
+                Edge::Branch { merge, .. } => {
+                    self.unsupported.push(format!("conditional branch at edge {pc}"));
+                    match merge {
+                        Some(merge) => pc = *merge,
+                        None => break,
+                    }
+                },
+                Edge::Parallel { merge, .. } => {
+                    self.unsupported.push(format!("parallel statement (dynamic parallelism) at edge {pc}"));
+                    pc = *merge;
+                },
+                Edge::Loop { next, .. } => {
+                    self.unsupported.push(format!("loop at edge {pc}"));
+                    match next {
+                        Some(next) => pc = *next,
+                        None => break,
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Exports the given compiled workflow as a (packed) CWL document.
+///
+/// # Arguments
+/// - `workflow`: The compiled [`Workflow`] to export.
+/// - `pindex`: The [`PackageIndex`] to resolve the workflow's packages in.
+///
+/// # Returns
+/// A [`CwlDocument`] with a `Workflow` (with ID [`CWL_WORKFLOW_ID`]) and a `CommandLineTool` for every task it calls.
+///
+/// # Errors
+/// This function errors if the workflow contains any construct that cannot be represented in CWL (e.g., branches, loops, parallel statements or
+/// task calls in functions), if a task has arguments without a CWL equivalent or if a task's package is unknown.
+pub fn export(workflow: &Workflow, pindex: &PackageIndex) -> Result<CwlDocument, Error> {
+    let mut exporter: Exporter = Exporter {
+        workflow,
+        pindex,
+
+        tools: vec![],
+        inputs: vec![],
+        outputs: vec![],
+        steps: vec![],
+        results: HashMap::new(),
+        calls: HashMap::new(),
+        unsupported: vec![],
+    };
+
+    // Functions are inlined in neither BraneScript nor CWL, so we can't export any tasks called in them
+    let mut funcs: Vec<(&usize, &Vec<Edge>)> = workflow.funcs.iter().collect();
+    funcs.sort_by_key(|(id, _)| **id);
+    for (id, edges) in funcs {
+        if edges.iter().any(|e| matches!(e, Edge::Node { .. })) {
+            exporter.unsupported.push(format!("task call in function '{}'", workflow.table.funcs[*id].name));
+        }
+    }
+
+    // Walk the main graph
+    exporter.walk(&workflow.graph)?;
+    if !exporter.unsupported.is_empty() {
+        return Err(Error::UnsupportedConstructs { constructs: exporter.unsupported });
+    }
+
+    // Assemble the document
+    let mut graph: Vec<CwlProcess> = Vec::with_capacity(1 + exporter.tools.len());
+    graph.push(CwlProcess::Workflow(CwlWorkflow {
+        id:      CWL_WORKFLOW_ID.into(),
+        inputs:  exporter.inputs,
+        outputs: exporter.outputs,
+        steps:   exporter.steps,
+    }));
+    graph.extend(exporter.tools.into_iter().map(|(_, tool)| CwlProcess::CommandLineTool(tool)));
+    Ok(CwlDocument { cwl_version: CWL_VERSION, graph })
+}
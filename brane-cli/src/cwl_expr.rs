@@ -0,0 +1,402 @@
+//  CWL EXPR.rs
+//
+//  Description:
+//!   Implements evaluation of CWL expressions, i.e., `$(...)` and `${...}`
+//!   fragments in strings of a CWL document.
+//!
+//!   Simple parameter references (e.g., `$(inputs.x)` or
+//!   `$(inputs["x"].path)`) are always supported. Anything else is a
+//!   JavaScript expression as enabled by `InlineJavascriptRequirement`,
+//!   which is only evaluated when built with the `cwl-js` feature; without
+//!   it, such expressions are reported instead of being mis-translated.
+//
+
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+
+pub use crate::errors::CwlExprError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+
+    #[test]
+    fn test_fragments() {
+        use Fragment::*;
+        assert_eq!(fragments("plain").unwrap(), vec![Literal("plain".into())]);
+        assert_eq!(fragments("").unwrap(), vec![]);
+        assert_eq!(fragments("$(inputs.x).txt").unwrap(), vec![Expression("inputs.x".into()), Literal(".txt".into())]);
+        assert_eq!(fragments("a${return 1;}b").unwrap(), vec![Literal("a".into()), Body("return 1;".into()), Literal("b".into())]);
+        // Escapes and lone dollars are literal
+        assert_eq!(fragments("\\$(inputs.x) costs $5").unwrap(), vec![Literal("$(inputs.x) costs $5".into())]);
+        // Nested parentheses and closers in string literals don't end the expression
+        assert_eq!(fragments("$(f(g(1)))").unwrap(), vec![Expression("f(g(1))".into())]);
+        assert_eq!(fragments("$(inputs[')'])").unwrap(), vec![Expression("inputs[')']".into())]);
+        assert_eq!(fragments("${ return \"}\"; }").unwrap(), vec![Body(" return \"}\"; ".into())]);
+
+        // Errors
+        assert!(matches!(fragments("$(inputs.x"), Err(Error::Unterminated { .. })));
+        assert!(matches!(fragments("${ return '}"), Err(Error::Unterminated { .. })));
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        use Segment::*;
+        assert_eq!(parse_reference("inputs"), Some(("inputs", vec![])));
+        assert_eq!(parse_reference(" inputs.x "), Some(("inputs", vec![Field("x".into())])));
+        assert_eq!(
+            parse_reference("inputs.files[0]['base name'].path"),
+            Some(("inputs", vec![Field("files".into()), Index(0), Field("base name".into()), Field("path".into())]))
+        );
+        assert_eq!(parse_reference("self[\"x\"]"), Some(("self", vec![Field("x".into())])));
+        assert_eq!(parse_reference("runtime.outdir"), Some(("runtime", vec![Field("outdir".into())])));
+
+        // Anything else needs JS
+        assert_eq!(parse_reference("outputs.x"), None);
+        assert_eq!(parse_reference("inputs.x + 1"), None);
+        assert_eq!(parse_reference("inputs.x()"), None);
+        assert_eq!(parse_reference("inputs.0"), None);
+        assert_eq!(parse_reference("inputs[x]"), None);
+        assert_eq!(parse_reference("inputs['x\\'y']"), None);
+        assert_eq!(parse_reference("inputs[0"), None);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let ctx = ExprContext {
+            inputs:  json!({ "name": "out", "n": 3, "files": [{ "path": "/a" }, { "path": "/b" }] }),
+            self_:   JsonValue::Null,
+            runtime: json!({ "outdir": "/tmp/out" }),
+        };
+        assert_eq!(evaluate("plain", &ctx).unwrap(), json!("plain"));
+        assert_eq!(evaluate("$(inputs.name).txt", &ctx).unwrap(), json!("out.txt"));
+        assert_eq!(evaluate("$(runtime.outdir)/$(inputs.n)", &ctx).unwrap(), json!("/tmp/out/3"));
+        // A lone expression keeps its type
+        assert_eq!(evaluate("$(inputs.n)", &ctx).unwrap(), json!(3));
+        assert_eq!(evaluate("$(inputs.files[1])", &ctx).unwrap(), json!({ "path": "/b" }));
+        assert_eq!(evaluate("$(inputs.files.length)", &ctx).unwrap(), json!(2));
+        assert_eq!(evaluate("$(inputs.name.length)", &ctx).unwrap(), json!(3));
+        // Missing values are null, like absent optional inputs
+        assert_eq!(evaluate("$(inputs.missing)", &ctx).unwrap(), JsonValue::Null);
+        assert_eq!(evaluate("$(inputs.files[5])", &ctx).unwrap(), JsonValue::Null);
+
+        // Errors
+        assert!(matches!(evaluate("$(inputs.n.x)", &ctx), Err(Error::UnknownReference { .. })));
+        #[cfg(not(feature = "cwl-js"))]
+        assert!(matches!(evaluate("$(inputs.n + 1)", &ctx), Err(Error::JsUnsupported { .. })));
+        #[cfg(feature = "cwl-js")]
+        {
+            assert_eq!(evaluate("$(inputs.n + 1)", &ctx).unwrap(), json!(4));
+            assert_eq!(evaluate("${ return inputs.name.toUpperCase(); }", &ctx).unwrap(), json!("OUT"));
+        }
+    }
+
+    #[test]
+    fn test_check_document() {
+        let document = |raw: &str| -> YamlValue { serde_yaml::from_str(raw).unwrap() };
+        assert!(needs_js("$(inputs.x + 1)").unwrap());
+        assert!(needs_js("${ return 1; }").unwrap());
+        assert!(!needs_js("$(inputs.x).txt").unwrap());
+        assert!(!needs_js("plain").unwrap());
+
+        assert!(check_document(&document("outputs: { out: { outputBinding: { glob: '$(inputs.x)' } } }")).is_ok());
+        assert!(matches!(check_document(&document("arguments: ['$(inputs.x']")), Err(Error::Unterminated { .. })));
+        let js = check_document(&document("arguments: [{ valueFrom: '$(inputs.x * 2)' }]"));
+        if cfg!(feature = "cwl-js") {
+            assert!(js.is_ok());
+        } else {
+            assert!(matches!(js, Err(Error::JsUnsupported { .. })));
+        }
+    }
+}
+
+
+/***** HELPERS *****/
+/// A piece of a string that may contain CWL expressions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Fragment {
+    /// Literal text.
+    Literal(String),
+    /// The contents of a `$(...)` expression.
+    Expression(String),
+    /// The contents of a `${...}` function body.
+    Body(String),
+}
+
+/// A step in a parameter reference.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    /// A field access, either as `.field` or `['field']`.
+    Field(String),
+    /// An array index, as `[0]`.
+    Index(usize),
+}
+
+/// Splits a string into literal text and expressions.
+///
+/// A backslash before a `$` escapes it.
+fn fragments(raw: &str) -> Result<Vec<Fragment>, Error> {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut res: Vec<Fragment> = Vec::new();
+    let mut literal: String = String::new();
+    let mut i: usize = 0;
+    while i < chars.len() {
+        match (chars[i], chars.get(i + 1)) {
+            ('\\', Some('$')) => {
+                literal.push('$');
+                i += 2;
+            },
+            ('$', Some(open @ ('(' | '{'))) => {
+                let close: char = if *open == '(' { ')' } else { '}' };
+                let start: usize = i + 2;
+
+                // Find the matching close, skipping over JS string literals
+                let mut depth: usize = 1;
+                let mut quote: Option<char> = None;
+                let mut j: usize = start;
+                while j < chars.len() {
+                    let c: char = chars[j];
+                    match quote {
+                        Some(_) if c == '\\' => j += 1,
+                        Some(q) if c == q => quote = None,
+                        Some(_) => {},
+                        None if c == '"' || c == '\'' || c == '`' => quote = Some(c),
+                        None if c == *open => depth += 1,
+                        None if c == close => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        },
+                        None => {},
+                    }
+                    j += 1;
+                }
+                if j >= chars.len() {
+                    return Err(Error::Unterminated { raw: raw.into() });
+                }
+
+                if !literal.is_empty() {
+                    res.push(Fragment::Literal(std::mem::take(&mut literal)));
+                }
+                let inner: String = chars[start..j].iter().collect();
+                res.push(if *open == '(' { Fragment::Expression(inner) } else { Fragment::Body(inner) });
+                i = j + 1;
+            },
+            (c, _) => {
+                literal.push(c);
+                i += 1;
+            },
+        }
+    }
+    if !literal.is_empty() {
+        res.push(Fragment::Literal(literal));
+    }
+    Ok(res)
+}
+
+/// Parses a CWL parameter reference (e.g., `inputs.x[0]['y']`) into its root and segments.
+///
+/// Returns [`None`] if the expression is not a plain parameter reference, i.e., it needs a JS engine.
+fn parse_reference(expr: &str) -> Option<(&str, Vec<Segment>)> {
+    let expr: &str = expr.trim();
+    let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let root_len: usize = expr.find(|c: char| !is_ident_char(c)).unwrap_or(expr.len());
+    let root: &str = &expr[..root_len];
+    if !matches!(root, "inputs" | "self" | "runtime") {
+        return None;
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut rest: &str = &expr[root_len..];
+    while !rest.is_empty() {
+        if let Some(field) = rest.strip_prefix('.') {
+            let len: usize = field.find(|c: char| !is_ident_char(c)).unwrap_or(field.len());
+            if len == 0 || field.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            segments.push(Segment::Field(field[..len].into()));
+            rest = &field[len..];
+        } else if let Some(index) = rest.strip_prefix('[') {
+            let end: usize = index.find(']')?;
+            let inner: &str = &index[..end];
+            let segment: Segment = if let Some(quoted) =
+                inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')).or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+            {
+                if quoted.contains(['\'', '"', '\\']) {
+                    return None;
+                }
+                Segment::Field(quoted.into())
+            } else {
+                Segment::Index(inner.parse().ok()?)
+            };
+            segments.push(segment);
+            rest = &index[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some((root, segments))
+}
+
+
+
+/***** LIBRARY *****/
+/// The values that CWL expressions may refer to.
+#[derive(Clone, Debug, Default)]
+pub struct ExprContext {
+    /// The job's input object, available as `inputs`.
+    pub inputs:  JsonValue,
+    /// The context-dependent `self` value (e.g., the glob results in `outputEval`).
+    pub self_:   JsonValue,
+    /// The runtime object (e.g., `outdir`), available as `runtime`.
+    pub runtime: JsonValue,
+}
+
+impl ExprContext {
+    /// Resolves a parameter reference in this context.
+    ///
+    /// # Arguments
+    /// - `reference`: The raw reference, used for errors.
+    /// - `root`: The root object to start from (`inputs`, `self` or `runtime`).
+    /// - `segments`: The steps to take from the root.
+    ///
+    /// # Errors
+    /// This function errors if a step is taken into a value that is not an object or array. Missing fields resolve to `null`, like absent
+    /// optional inputs do.
+    fn resolve(&self, reference: &str, root: &str, segments: &[Segment]) -> Result<JsonValue, Error> {
+        let mut value: &JsonValue = match root {
+            "inputs" => &self.inputs,
+            "self" => &self.self_,
+            "runtime" => &self.runtime,
+            _ => unreachable!(),
+        };
+        for segment in segments {
+            value = match (value, segment) {
+                (JsonValue::Array(a), Segment::Field(f)) if f == "length" => return Ok(a.len().into()),
+                (JsonValue::String(s), Segment::Field(f)) if f == "length" => return Ok(s.chars().count().into()),
+                (JsonValue::Object(o), Segment::Field(f)) => match o.get(f) {
+                    Some(value) => value,
+                    None => return Ok(JsonValue::Null),
+                },
+                (JsonValue::Array(a), Segment::Index(i)) => match a.get(*i) {
+                    Some(value) => value,
+                    None => return Ok(JsonValue::Null),
+                },
+                _ => return Err(Error::UnknownReference { reference: reference.into() }),
+            };
+        }
+        Ok(value.clone())
+    }
+}
+
+/// Returns whether the given string contains any CWL expressions.
+pub fn contains_expression(raw: &str) -> bool { raw.contains("$(") || raw.contains("${") }
+
+/// Returns whether the given string needs a JavaScript engine to evaluate, i.e., it contains expressions that are not plain parameter
+/// references.
+///
+/// # Errors
+/// This function errors if the string contains an unterminated expression.
+pub fn needs_js(raw: &str) -> Result<bool, Error> {
+    Ok(fragments(raw)?.iter().any(|f| match f {
+        Fragment::Literal(_) => false,
+        Fragment::Expression(expr) => parse_reference(expr).is_none(),
+        Fragment::Body(_) => true,
+    }))
+}
+
+/// Checks that all expressions in a (raw) CWL document can be evaluated by this build.
+///
+/// # Errors
+/// This function errors if the document uses JavaScript expressions but we are built without the `cwl-js` feature, or if an expression is
+/// malformed.
+pub fn check_document(document: &YamlValue) -> Result<(), Error> {
+    match document {
+        YamlValue::String(raw) => {
+            if !cfg!(feature = "cwl-js") && needs_js(raw)? {
+                return Err(Error::JsUnsupported { expr: raw.clone() });
+            }
+            // Catch unterminated expressions even if we have JS
+            fragments(raw)?;
+            Ok(())
+        },
+        YamlValue::Sequence(values) => values.iter().try_for_each(check_document),
+        YamlValue::Mapping(map) => map.values().try_for_each(check_document),
+        YamlValue::Tagged(tagged) => check_document(&tagged.value),
+        _ => Ok(()),
+    }
+}
+
+/// Evaluates all expressions in the given string.
+///
+/// If the string consists of exactly one expression, its value is returned as-is (e.g., an array or a `File` object). Otherwise, the
+/// expressions are interpolated into the string, with non-string values serialized as JSON.
+///
+/// # Errors
+/// This function errors if an expression is malformed, if a parameter reference does not resolve, or if a JavaScript expression is
+/// encountered that we can't (or failed to) evaluate.
+pub fn evaluate(raw: &str, ctx: &ExprContext) -> Result<JsonValue, Error> {
+    let eval = |fragment: &Fragment| -> Result<JsonValue, Error> {
+        match fragment {
+            Fragment::Literal(text) => Ok(JsonValue::String(text.clone())),
+            Fragment::Expression(expr) => match parse_reference(expr) {
+                Some((root, segments)) => ctx.resolve(expr.trim(), root, &segments),
+                None => eval_js(&format!("({expr})"), expr, ctx),
+            },
+            Fragment::Body(body) => eval_js(&format!("(function() {{{body}}})()"), body, ctx),
+        }
+    };
+
+    let fragments: Vec<Fragment> = fragments(raw)?;
+    match fragments.as_slice() {
+        [] => Ok(JsonValue::String(String::new())),
+        [fragment] => eval(fragment),
+        fragments => {
+            let mut res: String = String::new();
+            for fragment in fragments {
+                match eval(fragment)? {
+                    JsonValue::String(s) => res.push_str(&s),
+                    value => res.push_str(&value.to_string()),
+                }
+            }
+            Ok(JsonValue::String(res))
+        },
+    }
+}
+
+/// Evaluates a JavaScript expression with the embedded engine.
+///
+/// # Arguments
+/// - `code`: The code to evaluate.
+/// - `expr`: The expression as written in the document, used for errors.
+/// - `ctx`: The values to expose as `inputs`, `self` and `runtime`.
+#[cfg(feature = "cwl-js")]
+fn eval_js(code: &str, expr: &str, ctx: &ExprContext) -> Result<JsonValue, Error> {
+    use boa_engine::property::Attribute;
+    use boa_engine::{Context, JsString, JsValue, Source};
+
+    let js_err = |err: boa_engine::JsError| Error::JsEval { expr: expr.into(), err: err.to_string() };
+
+    let mut context: Context = Context::default();
+    for (name, value) in [("inputs", &ctx.inputs), ("self", &ctx.self_), ("runtime", &ctx.runtime)] {
+        let value: JsValue = JsValue::from_json(value, &mut context).map_err(js_err)?;
+        context.register_global_property(JsString::from(name), value, Attribute::all()).map_err(js_err)?;
+    }
+
+    let value: JsValue = context.eval(Source::from_bytes(code)).map_err(js_err)?;
+    if value.is_undefined() {
+        return Ok(JsonValue::Null);
+    }
+    value.to_json(&mut context).map_err(js_err)
+}
+
+/// Stand-in for the embedded engine when built without the `cwl-js` feature.
+#[cfg(not(feature = "cwl-js"))]
+fn eval_js(_code: &str, expr: &str, _ctx: &ExprContext) -> Result<JsonValue, Error> { Err(Error::JsUnsupported { expr: expr.into() }) }
@@ -13,37 +13,377 @@
 //
 
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use brane_ast::Workflow;
 use brane_ast::ast::Edge;
 use brane_shr::fs::copy_dir_recursively_async;
-use brane_shr::utilities::is_ip_addr;
+use brane_shr::utilities::{closest_names, is_ip_addr};
 use brane_tsk::spec::LOCALHOST;
 use chrono::Utc;
 use console::{Alignment, Term, pad_str, style};
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::{Confirm, Select};
-use indicatif::HumanDuration;
+use error_trace::ErrorTrace as _;
+use futures_util::stream;
+use indicatif::{HumanBytes, HumanDuration};
 use prettytable::Table;
 use prettytable::format::FormatBuilder;
 use rand::prelude::IteratorRandom;
 use reqwest::tls::{Certificate, Identity};
 use reqwest::{Client, ClientBuilder, Proxy};
-use specifications::data::{AccessKind, AssetInfo, DataIndex, DataInfo, DataName};
+use sha2::{Digest as _, Sha256};
+use specifications::data::{AccessKind, AssetInfo, DataIndex, DataInfo, DataName, FileChecksum};
 use specifications::registering::DownloadAssetRequest;
 use tempfile::TempDir;
 use tokio::fs as tfs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{self as tio, AsyncWriteExt as _};
 use tokio_stream::StreamExt;
+use tokio_util::io::StreamReader;
 
 use crate::errors::DataError;
 use crate::instance::InstanceInfo;
-use crate::utils::{ensure_dataset_dir, ensure_datasets_dir, get_dataset_dir};
+use crate::spec::{Age, DownloadLimit};
+use crate::utils::{
+    assert_valid_entry_name, configure_client, ensure_dataset_dir, ensure_datasets_dir, get_data_index, get_dataset_dir, http_client,
+    out_of_space_or, shr_out_of_space_or,
+};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: PathBuf = dir.path().join("data");
+        fs::create_dir_all(data.join("nested")).unwrap();
+        fs::write(data.join("a.txt"), "Hello, world!").unwrap();
+        fs::write(data.join("nested").join("b.txt"), "").unwrap();
+        #[cfg(unix)]
+        {
+            // A cycle must not make us loop forever, and links to files are hashed as the file
+            std::os::unix::fs::symlink(&data, data.join("nested").join("cycle")).unwrap();
+            std::os::unix::fs::symlink(data.join("a.txt"), data.join("link.txt")).unwrap();
+        }
+
+        let checksums: BTreeMap<PathBuf, FileChecksum> = compute_checksums(&data).unwrap();
+        let mut expected: Vec<PathBuf> = vec![PathBuf::from("a.txt"), PathBuf::from("nested").join("b.txt")];
+        if cfg!(unix) {
+            expected.push(PathBuf::from("link.txt"));
+        }
+        expected.sort();
+        assert_eq!(checksums.keys().cloned().collect::<Vec<PathBuf>>(), expected);
+        assert_eq!(checksums[&PathBuf::from("a.txt")].size, 13);
+        assert_eq!(checksums[&PathBuf::from("a.txt")].sha256, "315f5bdb76d078c43b8ac0064e4a0164612b1fce77c869345bfc94c75894edd3");
+        assert!(check_checksums(&data, &checksums).is_empty());
+
+        // A single file is recorded under the empty path
+        let single: BTreeMap<PathBuf, FileChecksum> = compute_checksums(&data.join("a.txt")).unwrap();
+        assert_eq!(single.keys().cloned().collect::<Vec<PathBuf>>(), vec![PathBuf::new()]);
+
+        // Changes are reported
+        fs::write(data.join("a.txt"), "Hello, World!").unwrap();
+        fs::remove_file(data.join("nested").join("b.txt")).unwrap();
+        let problems: Vec<String> = check_checksums(&data, &checksums);
+        assert_eq!(problems.len(), if cfg!(unix) { 3 } else { 2 });
+        assert!(problems.iter().any(|p| p.contains("b.txt") && p.ends_with("is missing")));
+        assert!(problems.iter().any(|p| p.contains("a.txt") && p.ends_with("(checksum mismatch)")));
+    }
+
+    #[test]
+    fn test_select_location() {
+        let access = |locs: &[&str]| -> HashMap<String, AccessKind> {
+            locs.iter().map(|l| (l.to_string(), AccessKind::File { path: PathBuf::from("/data") })).collect()
+        };
+
+        // The only location is picked regardless of strictness
+        assert_eq!(select_location("test", &access(&["hospital"]), false).unwrap(), "hospital");
+        assert_eq!(select_location("test", &access(&["hospital"]), true).unwrap(), "hospital");
+
+        // Multiple locations are refused in strict mode, listed in a stable order
+        match select_location("test", &access(&["umc", "hospital", "amc"]), true) {
+            Err(DataError::AmbiguousLocation { name, locs }) => {
+                assert_eq!(name, "test");
+                assert_eq!(locs, vec!["amc", "hospital", "umc"]);
+            },
+            res => panic!("Expected AmbiguousLocation, got {res:?}"),
+        }
+    }
+
+    /// Returns the info of a local dataset called `name` with its files at `path`.
+    fn local_info(name: &str, path: &Path) -> DataInfo {
+        DataInfo {
+            name: name.into(),
+            owners: None,
+            description: None,
+            created: Utc::now(),
+            access: HashMap::from([(LOCALHOST.into(), AccessKind::File { path: path.into() })]),
+            checksums: Some(compute_checksums(path).unwrap()),
+            size: None,
+        }
+    }
+
+    /// Writes the given `(path, contents or symlink target)` entries as a dataset archive.
+    fn write_archive(path: &Path, info: &DataInfo, files: &[(&str, &str)], links: &[(&str, &str)]) {
+        let info_path: PathBuf = path.with_extension("yml");
+        info.to_path(&info_path).unwrap();
+        let enc = flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(enc);
+        tar.append_path_with_name(&info_path, "data.yml").unwrap();
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            tar.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        for (name, target) in links {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            tar.append_link(&mut header, name, target).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: PathBuf = dir.path().join("source");
+        fs::create_dir_all(data.join("nested")).unwrap();
+        fs::write(data.join("a.txt"), "Hello, world!").unwrap();
+        fs::write(data.join("nested").join("b.txt"), "").unwrap();
+        let info: DataInfo = local_info("test", &data);
+
+        // Round-trip the dataset through an archive
+        let archive: PathBuf = dir.path().join("test.tar.gz");
+        let datasets_dir: PathBuf = dir.path().join("datasets");
+        fs::create_dir(&datasets_dir).unwrap();
+        export_dataset(&info, &archive).await.unwrap();
+        let imported: DataInfo = import_dataset(&archive, &datasets_dir).await.unwrap();
+        assert_eq!(imported.name, "test");
+        assert_eq!(imported.checksums, info.checksums);
+        let path: &PathBuf = match imported.access.get(LOCALHOST) {
+            Some(AccessKind::File { path }) => path,
+            None => panic!("Imported dataset is not available locally"),
+        };
+        assert!(path.starts_with(datasets_dir.join("test")));
+        assert_eq!(fs::read_to_string(path.join("a.txt")).unwrap(), "Hello, world!");
+        assert!(check_checksums(path, info.checksums.as_ref().unwrap()).is_empty());
+        match DataInfo::from_path(datasets_dir.join("test").join("data.yml")).unwrap().access.get(LOCALHOST) {
+            Some(AccessKind::File { path: written }) => assert_eq!(written, path),
+            None => panic!("Written dataset is not available locally"),
+        }
+
+        // Importing it again does not overwrite it
+        assert!(matches!(import_dataset(&archive, &datasets_dir).await, Err(DataError::DuplicateDatasetError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_import_malicious() {
+        let dir = tempfile::tempdir().unwrap();
+        let datasets_dir: PathBuf = dir.path().join("datasets").join("inner");
+        fs::create_dir_all(&datasets_dir).unwrap();
+        let mut info: DataInfo = DataInfo {
+            name: String::new(),
+            owners: None,
+            description: None,
+            created: Utc::now(),
+            access: HashMap::from([(LOCALHOST.into(), AccessKind::File { path: "data".into() })]),
+            checksums: None,
+            size: None,
+        };
+
+        // Names that escape the datasets directory are refused
+        for name in ["../evil", "..", "nested/evil", ""] {
+            info.name = name.into();
+            let archive: PathBuf = dir.path().join("name.tar.gz");
+            write_archive(&archive, &info, &[("data/a.txt", "Hello, world!")], &[]);
+            assert!(matches!(import_dataset(&archive, &datasets_dir).await, Err(DataError::IllegalDatasetName { .. })), "{name:?}");
+        }
+        assert!(!dir.path().join("datasets").join("evil").exists());
+
+        // So are symbolic links, which could point anywhere
+        #[cfg(unix)]
+        {
+            info.name = "test".into();
+            let archive: PathBuf = dir.path().join("link.tar.gz");
+            write_archive(&archive, &info, &[("data/a.txt", "Hello, world!")], &[("data/passwd", "/etc/passwd")]);
+            assert!(matches!(import_dataset(&archive, &datasets_dir).await, Err(DataError::ArchiveSymlink { .. })));
+            assert!(!datasets_dir.join("test").exists());
+        }
+
+        // And data outside of the archive's data directory
+        info.name = "test".into();
+        info.access = HashMap::from([(LOCALHOST.into(), AccessKind::File { path: "../../etc".into() })]);
+        let archive: PathBuf = dir.path().join("access.tar.gz");
+        write_archive(&archive, &info, &[("data/a.txt", "Hello, world!")], &[]);
+        assert!(matches!(import_dataset(&archive, &datasets_dir).await, Err(DataError::IllegalArchiveAccess { .. })));
+        assert!(!datasets_dir.join("test").exists());
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes the size and SHA-256 hash of a single file.
+fn checksum_file(path: &Path) -> Result<FileChecksum, io::Error> {
+    let mut hasher: Sha256 = Sha256::new();
+    let size: u64 = io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(FileChecksum { size, sha256: hex::encode(hasher.finalize()) })
+}
+
+/// Records the size and checksum of every file in a dataset.
+///
+/// Symbolic links to files are hashed as the file they point to, but symbolic links to directories are not followed (to avoid cycles). The
+/// dataset itself may be a symbolic link, though.
+///
+/// # Arguments
+/// - `path`: The path of the dataset, which may be a single file or a directory.
+///
+/// # Returns
+/// A map of the paths of the files relative to `path` (or an empty path if `path` is a file) to their [`FileChecksum`]s.
+///
+/// # Errors
+/// This function errors if we failed to walk the dataset or read any of its files.
+fn compute_checksums(path: &Path) -> Result<BTreeMap<PathBuf, FileChecksum>, DataError> {
+    let mut checksums: BTreeMap<PathBuf, FileChecksum> = BTreeMap::new();
+    let mut todo: Vec<PathBuf> = vec![PathBuf::new()];
+    while let Some(rel) = todo.pop() {
+        let abs: PathBuf = if rel.as_os_str().is_empty() { path.into() } else { path.join(&rel) };
+        let meta: fs::Metadata = if rel.as_os_str().is_empty() { fs::metadata(&abs) } else { fs::symlink_metadata(&abs) }
+            .map_err(|source| DataError::ChecksumError { path: abs.clone(), source })?;
+        if meta.is_dir() {
+            let entries = fs::read_dir(&abs).map_err(|source| DataError::ChecksumError { path: abs.clone(), source })?;
+            for entry in entries {
+                let entry = entry.map_err(|source| DataError::ChecksumError { path: abs.clone(), source })?;
+                todo.push(rel.join(entry.file_name()));
+            }
+        } else if meta.is_symlink() && abs.is_dir() {
+            warn!("Not following symbolic link to directory '{}' while computing checksums", abs.display());
+        } else {
+            let checksum: FileChecksum = checksum_file(&abs).map_err(|source| DataError::ChecksumError { path: abs, source })?;
+            checksums.insert(rel, checksum);
+        }
+    }
+    Ok(checksums)
+}
+
+/// Checks the files of a dataset against the checksums recorded when it was built.
+///
+/// # Arguments
+/// - `path`: The path of the dataset.
+/// - `checksums`: The [`FileChecksum`]s recorded for it (see [`compute_checksums()`]).
+///
+/// # Returns
+/// A description of every file that is missing or changed, which is empty if the dataset is intact.
+fn check_checksums(path: &Path, checksums: &BTreeMap<PathBuf, FileChecksum>) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    for (rel, expected) in checksums {
+        let abs: PathBuf = if rel.as_os_str().is_empty() { path.into() } else { path.join(rel) };
+        match checksum_file(&abs) {
+            Ok(got) if got.size != expected.size => {
+                problems.push(format!("'{}' changed size (expected {} bytes, got {} bytes)", abs.display(), expected.size, got.size))
+            },
+            Ok(got) if got.sha256 != expected.sha256 => problems.push(format!("'{}' changed contents (checksum mismatch)", abs.display())),
+            Ok(_) => {},
+            Err(err) if err.kind() == io::ErrorKind::NotFound => problems.push(format!("'{}' is missing", abs.display())),
+            Err(err) => problems.push(format!("'{}' could not be read: {}", abs.display(), err)),
+        }
+    }
+    problems
+}
+
+/// Runs [`compute_checksums()`] on a blocking thread, as hashing a whole dataset may take a while.
+///
+/// # Arguments
+/// - `path`: The path of the dataset, which may be a single file or a directory.
+///
+/// # Returns
+/// A map of the paths of the files relative to `path` (or an empty path if `path` is a file) to their [`FileChecksum`]s.
+///
+/// # Errors
+/// This function errors if we failed to walk the dataset or read any of its files.
+async fn compute_checksums_async(path: PathBuf) -> Result<BTreeMap<PathBuf, FileChecksum>, DataError> {
+    let path_clone: PathBuf = path.clone();
+    tokio::task::spawn_blocking(move || compute_checksums(&path_clone)).await.map_err(|source| DataError::ChecksumJoinError { path, source })?
+}
+
+/// Finds a symbolic link in the given directory, without following any.
+///
+/// # Arguments
+/// - `dir`: The directory to search.
+///
+/// # Returns
+/// The path of the first symbolic link found, or [`None`] if there are none.
+///
+/// # Errors
+/// This function errors if we failed to read any of the (nested) directories.
+fn find_symlink(dir: &Path) -> Result<Option<PathBuf>, DataError> {
+    let mut todo: Vec<PathBuf> = vec![dir.into()];
+    while let Some(dir) = todo.pop() {
+        let entries = fs::read_dir(&dir).map_err(|source| DataError::ArchiveEntryError { path: dir.clone(), source })?;
+        for entry in entries {
+            let entry = entry.map_err(|source| DataError::ArchiveEntryError { path: dir.clone(), source })?;
+            let path: PathBuf = entry.path();
+            let ftype: fs::FileType = entry.file_type().map_err(|source| DataError::ArchiveEntryError { path: path.clone(), source })?;
+            if ftype.is_symlink() {
+                return Ok(Some(path));
+            } else if ftype.is_dir() {
+                todo.push(path);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Selects the location to download a dataset from if the user didn't give one.
+///
+/// # Arguments
+/// - `name`: The name of the dataset.
+/// - `access`: The (non-empty) map of locations where the dataset is available.
+/// - `strict`: If given, fails instead of asking the user if the dataset is available at multiple locations.
+///
+/// # Returns
+/// The only location where the dataset is available, or the one picked by the user.
+///
+/// # Errors
+/// This function errors if the dataset is available at multiple locations and `strict` is given, or if we failed to ask the user.
+fn select_location(name: &str, access: &HashMap<String, AccessKind>, strict: bool) -> Result<String, DataError> {
+    if access.len() == 1 {
+        return Ok(access.keys().next().unwrap().clone());
+    }
+    if strict {
+        let mut locs: Vec<String> = access.keys().cloned().collect();
+        locs.sort();
+        return Err(DataError::AmbiguousLocation { name: name.into(), locs });
+    }
+
+    // Prepare the prompt with beautiful themes and such
+    let colorful = ColorfulTheme::default();
+    let items: Vec<&String> = access.keys().collect();
+    let mut prompt = Select::with_theme(&colorful);
+    prompt = prompt.items(&items).with_prompt("Select download location").default(0usize);
+
+    // Ask the user
+    match prompt.interact_on_opt(&Term::stderr()) {
+        Ok(res) => Ok(res.map(|i| items[i].clone()).unwrap_or_else(|| items[0].clone())),
+        Err(source) => Err(DataError::DataSelectError { source }),
+    }
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -58,6 +398,9 @@ use crate::utils::{ensure_dataset_dir, ensure_datasets_dir, get_dataset_dir};
 /// - `data_dir`: The directory to download the dataset to.
 /// - `name`: The name of the dataset to download.
 /// - `access`: The locations where it is available.
+/// - `keep_archive`: If true, also writes the downloaded archive to `data.tar.gz` in `data_dir`. Otherwise, the archive is extracted as it comes
+///   in without ever being written to disk.
+/// - `limit`: The [`DownloadLimit`] that the download must adhere to before it is started.
 ///
 /// # Returns
 /// The AccessKind with how to download the dataset if it was downloaded successfully, or `None` if it wasn't available.
@@ -74,6 +417,8 @@ pub async fn download_data(
     name: impl AsRef<str>,
     workflow: Workflow,
     access: &HashMap<String, AccessKind>,
+    keep_archive: bool,
+    limit: &DownloadLimit,
 ) -> Result<Option<AccessKind>, DataError> {
     let api_endpoint: &str = api_endpoint.as_ref();
     let certs_dir: &Path = certs_dir.as_ref();
@@ -90,8 +435,12 @@ pub async fn download_data(
 
     // Send a GET-request to resolve that location to a delegate
     let registry_addr = format!("{api_endpoint}/infra/registries/{location}");
-    let res =
-        reqwest::get(&registry_addr).await.map_err(|source| DataError::RequestError { what: "registry", address: registry_addr.clone(), source })?;
+    let client: Client = http_client().map_err(|source| DataError::HttpClientError { source })?;
+    let res = client.get(&registry_addr).send().await.map_err(|source| DataError::RequestError {
+        what: "registry",
+        address: registry_addr.clone(),
+        source,
+    })?;
 
     // Attempt to get its body if it was a success
     if !res.status().is_success() {
@@ -130,10 +479,6 @@ pub async fn download_data(
     /* Step 3: Prepare the filesystem */
     debug!("Preparing filesystem...");
 
-    // Make sure the temporary tarfile directory exists
-    let tar_dir = TempDir::new().map_err(|source| DataError::TempDirError { source })?;
-    let tar_path = tar_dir.path().join(format!("data_{name}.tar.gz"));
-
     // Make sure the old data path doesn't exist anymore
     let data_path = data_dir.join("data");
     if data_path.exists() {
@@ -143,11 +488,14 @@ pub async fn download_data(
         tfs::remove_dir_all(&data_path).await.map_err(|source| DataError::DirRemoveError { what: "target data", path: data_path.clone(), source })?;
     }
 
+    // The archive itself is only written to disk if we're asked to keep it
+    let tar_path: Option<PathBuf> = if keep_archive { Some(data_dir.join("data.tar.gz")) } else { None };
+
     /* Step 4: Build the client. */
     let download_addr: String = format!("{registry_addr}/data/download/{name}");
     debug!("Sending download request to '{}'...", download_addr);
     let mut client: ClientBuilder =
-        Client::builder().use_rustls_tls().add_root_certificate(ca_cert).identity(identity).tls_sni(!is_ip_addr(&download_addr));
+        configure_client(Client::builder()).use_rustls_tls().add_root_certificate(ca_cert).identity(identity).tls_sni(!is_ip_addr(&download_addr));
 
     if let Some(proxy_addr) = proxy_addr {
         client = client.proxy(Proxy::all(proxy_addr).map_err(|source| DataError::ProxyCreateError { address: proxy_addr.into(), source })?);
@@ -171,27 +519,71 @@ pub async fn download_data(
     if !res.status().is_success() {
         return Err(DataError::RequestFailure { address: download_addr, code: res.status(), message: res.text().await.ok() });
     }
+    limit.check(&download_addr, res.content_length())?;
+
+    /* Step 5: Extract the download stream as it comes in */
+    debug!("Unpacking download stream from '{}' to '{}'...", download_addr, data_path.display());
+    let archive: Option<tfs::File> = match &tar_path {
+        Some(tar_path) => {
+            debug!("Keeping downloaded archive as '{}'", tar_path.display());
+            Some(tfs::File::create(tar_path).await.map_err(|source| DataError::TarCreateError { path: tar_path.clone(), source })?)
+        },
+        None => None,
+    };
 
-    /* Step 5: Download the raw file in parts */
-    debug!("Downloading file to '{}'...", tar_path.display());
-    {
-        let mut handle = tfs::File::create(&tar_path).await.map_err(|source| DataError::TarCreateError { path: tar_path.clone(), source })?;
-
-        let mut stream = res.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            // Unwrap the chunk
-            let mut chunk = chunk.map_err(|source| DataError::DownloadStreamError { address: download_addr.clone(), source })?;
-
-            // Write it to the file
-            handle.write_all_buf(&mut chunk).await.map_err(|source| DataError::TarWriteError { path: tar_path.clone(), source })?;
+    // Tee the chunks to the kept archive (if any) while passing them on to the extractor, holding them to the limit as they come in
+    let addr: &str = &download_addr;
+    let chunks = stream::unfold((res.bytes_stream(), archive, 0u64), |(mut chunks, mut archive, mut received)| async move {
+        let chunk = match chunks.next().await {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(err)) => return Some((Err(std::io::Error::other(err)), (chunks, archive, received))),
+            None => {
+                // Make sure the kept archive is complete on disk before we're done
+                if let Some(mut handle) = archive {
+                    if let Err(err) = handle.flush().await {
+                        return Some((Err(err), (chunks, None, received)));
+                    }
+                }
+                return None;
+            },
+        };
+        received += chunk.len() as u64;
+        if let Err(err) = limit.check_received(addr, received) {
+            return Some((Err(std::io::Error::other(err)), (chunks, archive, received)));
         }
+        if let Some(handle) = &mut archive {
+            if let Err(err) = handle.write_all(&chunk).await {
+                return Some((Err(err), (chunks, archive, received)));
+            }
+        }
+        Some((Ok(chunk), (chunks, archive, received)))
+    });
+    let mut reader = StreamReader::new(Box::pin(chunks));
+    let mut result: Result<(), DataError> =
+        brane_shr::fs::unarchive_reader_async(&download_addr, &mut reader, &data_path).await.map_err(|source| DataError::TarExtractError { source });
+    if let (Ok(()), Some(tar_path)) = (&result, &tar_path) {
+        // The extractor may stop reading before the end of the stream (e.g., at trailing padding), so drain it to complete the archive
+        result = tio::copy(&mut reader, &mut tio::sink())
+            .await
+            .map(|_| ())
+            .map_err(|source| out_of_space_or(tar_path, source, |source| DataError::TarWriteError { path: tar_path.clone(), source }));
+    }
+    if let Err(err) = result {
+        // Don't leave a half-extracted dataset (or archive) behind
+        if data_path.exists() {
+            if let Err(err) = tfs::remove_dir_all(&data_path).await {
+                warn!("Failed to remove partially extracted dataset '{}': {}", data_path.display(), err);
+            }
+        }
+        if let Some(tar_path) = &tar_path {
+            if let Err(err) = tfs::remove_file(tar_path).await {
+                warn!("Failed to remove partially downloaded archive '{}': {}", tar_path.display(), err);
+            }
+        }
+        return Err(err);
     }
 
-    /* Step 6: Extract the tar. */
-    debug!("Unpacking '{}' to '{}'...", tar_path.display(), data_path.display());
-    brane_shr::fs::unarchive_async(tar_path, &data_path).await.map_err(|source| DataError::TarExtractError { source })?;
-
-    /* Step 7: In the case of brane-cli, also write a DataInfo. */
+    /* Step 6: In the case of brane-cli, also write a DataInfo. */
     let access = AccessKind::File { path: data_path };
     {
         let info_path = data_dir.join("data.yml");
@@ -205,6 +597,14 @@ pub async fn download_data(
             created: Utc::now(),
 
             access: HashMap::from([(LOCALHOST.into(), access.clone())]),
+
+            checksums: Some(
+                compute_checksums_async(match &access {
+                    AccessKind::File { path } => path.clone(),
+                })
+                .await?,
+            ),
+            size:      None,
         };
 
         // Write it
@@ -237,6 +637,7 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
     /* Step 1: Read the input */
     // Parse the input file as a AssetFile (which is a datafile but with user info attached to it).
     let mut info: AssetInfo = AssetInfo::from_path(file).map_err(|source| DataError::AssetFileError { path: file.into(), source })?;
+    assert_valid_entry_name(&info.name).map_err(|source| DataError::IllegalDatasetName { source })?;
     // Inject the current time if not already
     info.created = Utc::now();
 
@@ -271,50 +672,207 @@ pub async fn build(file: impl AsRef<Path>, workdir: impl AsRef<Path>, _keep_file
     // Simple use our ensure thing for this
     let build_dir: PathBuf = ensure_dataset_dir(&info.name, true).map_err(|source| DataError::DatasetDirCreateError { source })?;
 
-    /* Step 3: Move any files if we don't want no links. */
-    if no_links {
-        match &mut info.access {
-            AccessKind::File { ref mut path } => {
-                // Perform the copy
-                let target: PathBuf = build_dir.join(path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "data".into()));
-                copy_dir_recursively_async(&path, &target).await.map_err(|source| DataError::DataCopyError { source })?;
-
-                // Update the path to the target
-                *path = target;
-            },
+    let result: Result<DataInfo, DataError> = async {
+        /* Step 3: Move any files if we don't want no links. */
+        if no_links {
+            match &mut info.access {
+                AccessKind::File { ref mut path } => {
+                    // Perform the copy
+                    let target: PathBuf = build_dir.join(path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "data".into()));
+                    copy_dir_recursively_async(&path, &target)
+                        .await
+                        .map_err(|source| shr_out_of_space_or(source, |source| DataError::DataCopyError { source }))?;
+
+                    // Update the path to the target
+                    *path = target;
+                },
+            }
         }
-    }
 
-    /* Step 4: Write the AssetInfo to a DataInfo. */
-    let data_info: DataInfo = info.into();
+        /* Step 4: Write the AssetInfo to a DataInfo, recording what the files look like now. */
+        let checksums: BTreeMap<PathBuf, FileChecksum> = match &info.access {
+            AccessKind::File { path } => compute_checksums_async(path.clone()).await?,
+        };
+        let mut data_info: DataInfo = info.into();
+        data_info.checksums = Some(checksums);
 
-    data_info.to_path(build_dir.join("data.yml")).map_err(|source| DataError::DataInfoWriteError { source })?;
+        data_info.to_path(build_dir.join("data.yml")).map_err(|source| DataError::DataInfoWriteError { source })?;
+        Ok(data_info)
+    }
+    .await;
+    let data_info: DataInfo = match result {
+        Ok(data_info) => data_info,
+        Err(err) => {
+            // Don't leave a half-built dataset behind
+            if let Err(err) = tfs::remove_dir_all(&build_dir).await {
+                warn!("Failed to remove partially built dataset '{}': {}", build_dir.display(), err);
+            }
+            return Err(err);
+        },
+    };
 
     /* Step 5: Done */
     println!("Successfully built dataset {}", style(&data_info.name).bold().cyan());
     Ok(())
 }
 
+/// Downloads a single dataset from a remote host.
+///
+/// # Arguments
+/// - `instance_info`: The active instance to download from.
+/// - `info`: The remote DataInfo of the dataset to download.
+/// - `loc`: If given, the location to download the dataset from. Otherwise, asks the user if there are multiple.
+/// - `use_case`: The use-case registry to use for downloading the data.
+/// - `user`: The user that is performing the download.
+/// - `proxy_addr`: The proxy address to proxy the transfer through, if any.
+/// - `force`: Forces a download, even if the dataset is already available.
+/// - `keep_archive`: Whether to keep the downloaded archive next to the extracted dataset.
+/// - `limit`: The [`DownloadLimit`] that the download must adhere to.
+/// - `strict`: If given, fails instead of asking the user to pick a location if there are multiple.
+///
+/// # Returns
+/// Nothing, but does create a new local entry for the dataset upon success.
+///
+/// # Errors
+/// This function may error if the download failed for any reason.
+#[allow(clippy::too_many_arguments)]
+async fn download_one(
+    instance_info: &InstanceInfo,
+    info: &DataInfo,
+    loc: Option<&String>,
+    use_case: &str,
+    user: &str,
+    proxy_addr: &Option<String>,
+    force: bool,
+    keep_archive: bool,
+    limit: &DownloadLimit,
+    strict: bool,
+) -> Result<(), DataError> {
+    let name: &str = &info.name;
+
+    debug!("Selecting download location for '{}'...", name);
+    let loc: String = {
+        // Make sure the dataset is available _somewhere_
+        if info.access.is_empty() {
+            return Err(DataError::UnavailableDataset { name: name.into(), locs: vec![] });
+        }
+        // If we're given one, use it
+        if let Some(loc) = loc {
+            loc.clone()
+        } else {
+            // More effort is needed
+
+            // ...unless it's available locally
+            if !force && info.access.contains_key(LOCALHOST) {
+                println!("Dataset {} is already locally available; not initiating a download", style(name).cyan().bold());
+                return Ok(());
+            }
+
+            // Now, pick the only one or ask the user (if we may)
+            select_location(name, &info.access, strict)?
+        }
+    };
+
+    println!("Downloading {} from {}...", style(name).bold().cyan(), style(&loc).bold().cyan());
+
+    // Create an access map with only the location entry
+    let mut access: HashMap<String, AccessKind> = HashMap::with_capacity(1);
+    if let Some(a) = info.access.get(&loc) {
+        access.insert(loc, a.clone());
+    } else {
+        let suggestions: Vec<String> = closest_names(&loc, info.access.keys());
+        return Err(DataError::UnknownLocation { name: loc, suggestions });
+    }
+
+    // Fetch the method of its availability
+    let access: AccessKind = match info.access.get(LOCALHOST) {
+        Some(access) => access.clone(),
+        None => {
+            let mut workflow = Workflow::with_random_id(
+                Default::default(),
+                vec![Edge::Return { result: HashSet::from([DataName::Data(name.into())]) }],
+                Default::default(),
+            );
+
+            *Arc::get_mut(&mut workflow.user).expect("Could not set user on workflow") = Some(user.into());
+
+            // Get the certificate path
+            let certs_dir: PathBuf = match InstanceInfo::get_active_name() {
+                Ok(name) => match InstanceInfo::get_instance_path(&name) {
+                    Ok(path) => path.join("certs"),
+                    Err(source) => {
+                        return Err(DataError::InstancePathError { name, source });
+                    },
+                },
+                Err(source) => {
+                    return Err(DataError::ActiveInstanceReadError { source });
+                },
+            };
+
+            // Get the path to download it to
+            let data_dir: PathBuf = ensure_dataset_dir(name, true).map_err(|source| DataError::DatasetDirError { name: name.into(), source })?;
+
+            // Run the download
+            download_data(
+                instance_info.api.to_string(),
+                proxy_addr,
+                certs_dir,
+                data_dir,
+                use_case.into(),
+                name,
+                workflow,
+                &access,
+                keep_archive,
+                limit,
+            )
+            .await?
+            .ok_or_else(|| DataError::UnavailableDataset { name: name.into(), locs: info.access.keys().cloned().collect() })?
+        },
+    };
+
+    // Write the method of access
+    println!("Download {}", style("success").bold().cyan());
+    match access {
+        AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
+    }
+
+    // Done
+    Ok(())
+}
+
 /// Downloads a dataset from one or more remote hosts.
 ///
 /// # Arguments
 /// - `names`: The names of the dataset to download.
+/// - `all`: If given, ignores `names` and downloads every dataset in the remote data index that is not yet available locally. Datasets
+///   available at multiple locations are then downloaded from the first one (alphabetically) unless given in `locs`.
+/// - `yes`: If given, skips the confirmation asked before downloading `all` datasets.
 /// - `locs`: A name=loc keymap to specify locations for each dataset.
 /// - `proxy_addr`: The proxy address to proxy the transfer through, if any.
-/// - `force`: Forces a download, even if the dataset is already available.
+/// - `force`: Forces a download, even if the dataset is already available. When used with `all`, downloads all remote datasets.
+/// - `keep_archive`: Whether to keep the downloaded archive of each dataset next to the extracted dataset.
+/// - `limit`: The [`DownloadLimit`] that each download must adhere to.
+/// - `strict`: If given, fails instead of asking the user to pick a location for datasets available at multiple locations. When used with
+///   `all`, also stops at the first failure instead of collecting them.
 ///
 /// # Returns
 /// The method for accessing the new data file. Clearly, this means it also creates a new local entry for a dataset upon success.
 ///
 /// # Errors
-/// This function may error if the download failed for any reason.
+/// This function may error if the download failed for any reason. When downloading `all` datasets, failures are collected and reported at the end.
+#[allow(clippy::too_many_arguments)]
 pub async fn download(
     names: Vec<String>,
+    all: bool,
+    yes: bool,
     locs: Vec<String>,
     use_case: String,
     user: String,
     proxy_addr: &Option<String>,
     force: bool,
+    keep_archive: bool,
+    limit: DownloadLimit,
+    strict: bool,
 ) -> Result<(), DataError> {
     // Parse the locations into a map
     let mut locations: HashMap<String, String> = HashMap::with_capacity(locs.len());
@@ -336,118 +894,263 @@ pub async fn download(
 
     // Fetch a new, remote DataIndex to get up-to-date entries
     let data_addr: String = format!("{}/data/info", instance_info.api);
-    let index: DataIndex =
-        brane_tsk::api::get_data_index(&data_addr).await.map_err(|source| DataError::RemoteDataIndexError { address: data_addr, source })?;
-
-    // Iterate over the to-be-downloaded datasets
-    for name in names {
-        // Make sure we know it
-        let info: &DataInfo = index.get(&name).ok_or_else(|| DataError::UnknownDataset { name: name.clone() })?;
-
-        debug!("Selecting download location for '{}'...", name);
-        let loc: String = {
-            // Make sure the dataset is available _somewhere_
-            if info.access.is_empty() {
-                return Err(DataError::UnavailableDataset { name: name.clone(), locs: vec![] });
-            }
-            // If we're given one, use it
-            if let Some(loc) = locations.get(&name) {
-                loc.clone()
-            } else {
-                // More effort is needed
+    let index: DataIndex = get_data_index(&data_addr).await.map_err(|source| DataError::RemoteDataIndexError { address: data_addr, source })?;
+
+    // If we're not downloading everything, iterate over the to-be-downloaded datasets
+    if !all {
+        for name in names {
+            // Make sure we know it
+            let info: &DataInfo = index.get(&name).ok_or_else(|| DataError::UnknownDataset {
+                name: name.clone(),
+                suggestions: closest_names(&name, index.iter().map(|info| &info.name)),
+            })?;
+            download_one(&instance_info, info, locations.get(&name), &use_case, &user, proxy_addr, force, keep_archive, &limit, strict).await?;
+        }
+        return Ok(());
+    }
 
-                // ...unless it's available locally
-                if !force && info.access.contains_key(LOCALHOST) {
-                    println!("Dataset {} is already locally available; not initiating a download", style(name).cyan().bold());
-                    return Ok(());
-                }
+    // Otherwise, find everything that is not yet locally available
+    let datasets_dir: PathBuf = ensure_datasets_dir(true).map_err(|source| DataError::DatasetsError { source })?;
+    let local: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| DataError::LocalDataIndexError { source })?;
+    let mut todo: Vec<&DataInfo> = index.iter().filter(|info| force || local.get(&info.name).is_none()).collect();
+    todo.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+    if todo.is_empty() {
+        println!("All remote datasets are already locally available.");
+        return Ok(());
+    }
 
-                // Now, pick the only one or ask the user
-                if info.access.len() == 1 {
-                    info.access.keys().next().unwrap().clone()
-                } else {
-                    // Prepare the prompt with beautiful themes and such
-                    let colorful = ColorfulTheme::default();
-                    let items: Vec<&String> = info.access.keys().collect();
-                    let mut prompt = Select::with_theme(&colorful);
-                    prompt = prompt.items(&items).with_prompt("Select download location").default(0usize);
-
-                    // Ask the user
-                    match prompt.interact_on_opt(&Term::stderr()) {
-                        Ok(res) => res.map(|i| items[i].clone()).unwrap_or_else(|| items[0].clone()),
-                        Err(source) => {
-                            return Err(DataError::DataSelectError { source });
-                        },
-                    }
-                }
-            }
-        };
+    // Pick the location of every dataset up front, since we won't ask the user for each of them
+    let mut todo_locs: Vec<String> = Vec::with_capacity(todo.len());
+    for info in &todo {
+        let loc: Option<&String> = locations.get(&info.name).or_else(|| info.access.keys().min());
+        todo_locs.push(loc.cloned().ok_or_else(|| DataError::UnavailableDataset { name: info.name.clone(), locs: vec![] })?);
+    }
 
-        println!("Downloading {} from {}...", style(&name).bold().cyan(), style(&loc).bold().cyan());
+    // Report what we're about to do and ask for confirmation
+    let size: u64 = todo.iter().filter_map(|info| info.size).sum();
+    let unknown: usize = todo.iter().filter(|info| info.size.is_none()).count();
+    println!(
+        "About to download {} dataset(s) totalling {}{}:",
+        todo.len(),
+        style(HumanBytes(size)).bold(),
+        if unknown > 0 { format!(" (+ {unknown} of unknown size)") } else { String::new() }
+    );
+    for (info, loc) in todo.iter().zip(&todo_locs) {
+        println!(
+            " - {} from {} ({})",
+            style(&info.name).bold().cyan(),
+            style(loc).bold().cyan(),
+            info.size.map(|size| HumanBytes(size).to_string()).unwrap_or_else(|| "unknown size".into())
+        );
+    }
+    if !yes {
+        let consent: bool = Confirm::new().interact().map_err(|source| DataError::DownloadConfirmationError { source })?;
+        if !consent {
+            println!("Aborted.");
+            return Ok(());
+        }
+    }
 
-        // Create an access map with only the location entry
-        let mut access: HashMap<String, AccessKind> = HashMap::with_capacity(1);
-        if let Some(a) = info.access.get(&loc) {
-            access.insert(loc, a.clone());
-        } else {
-            return Err(DataError::UnknownLocation { name: loc });
+    // Download them all, collecting any failures
+    let mut failed: Vec<String> = Vec::new();
+    for (info, loc) in todo.iter().zip(&todo_locs) {
+        if let Err(err) = download_one(&instance_info, info, Some(loc), &use_case, &user, proxy_addr, force, keep_archive, &limit, strict).await {
+            if strict {
+                return Err(err);
+            }
+            error!("{}", err.trace());
+            failed.push(info.name.clone());
         }
+    }
 
-        // Fetch the method of its availability
-        let access: AccessKind = match info.access.get(LOCALHOST) {
-            Some(access) => access.clone(),
-            None => {
-                let mut workflow = Workflow::with_random_id(
-                    Default::default(),
-                    vec![Edge::Return { result: HashSet::from([DataName::Data(name.clone())]) }],
-                    Default::default(),
-                );
-
-                *Arc::get_mut(&mut workflow.user).expect("Could not set user on workflow") = Some(user.clone());
-
-                // Get the certificate path
-                let certs_dir: PathBuf = match InstanceInfo::get_active_name() {
-                    Ok(name) => match InstanceInfo::get_instance_path(&name) {
-                        Ok(path) => path.join("certs"),
-                        Err(source) => {
-                            return Err(DataError::InstancePathError { name, source });
-                        },
-                    },
-                    Err(source) => {
-                        return Err(DataError::ActiveInstanceReadError { source });
-                    },
-                };
+    // Report the result
+    if !failed.is_empty() {
+        return Err(DataError::BatchDownloadError { total: todo.len(), failed });
+    }
+    println!("Successfully downloaded {} dataset(s)", style(todo.len()).bold().cyan());
+    Ok(())
+}
 
-                // Get the path to download it to
-                let data_dir: PathBuf =
-                    ensure_dataset_dir(&name, true).map_err(|source| DataError::DatasetDirError { name: name.clone(), source })?;
+/// Exports a local dataset to a portable `.tar.gz` archive.
+///
+/// The archive contains the dataset's files in a `data` directory, next to a `data.yml` with its [`DataInfo`]. The access path in the
+/// latter is made relative to the archive, such that it can be resolved again by [`import_archive()`] on another machine.
+///
+/// # Arguments
+/// - `name`: The name of the dataset to export.
+/// - `output`: The path of the archive to write.
+///
+/// # Errors
+/// This function errors if the dataset is unknown or not locally available, or if we failed to stage or archive it.
+pub async fn export(name: String, output: PathBuf) -> Result<(), DataError> {
+    debug!("Exporting dataset '{}' to '{}'", name, output.display());
 
-                // Run the download
-                download_data(instance_info.api.to_string(), proxy_addr, certs_dir, data_dir, use_case.clone(), &name, workflow, &access)
-                    .await?
-                    .ok_or_else(|| DataError::UnavailableDataset { name, locs: info.access.keys().cloned().collect() })?
-            },
-        };
+    // Resolve the dataset
+    let datasets_dir: PathBuf = ensure_datasets_dir(false).map_err(|source| DataError::DatasetsError { source })?;
+    let index: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| DataError::LocalDataIndexError { source })?;
+    let info: &DataInfo = index
+        .get(&name)
+        .ok_or_else(|| DataError::UnknownDataset { name: name.clone(), suggestions: closest_names(&name, index.iter().map(|info| &info.name)) })?;
+    export_dataset(info, &output).await?;
 
-        // Write the method of access
-        println!("Download {}", style("success").bold().cyan());
-        match access {
-            AccessKind::File { path } => println!("(It's available under '{}')", path.display()),
-        }
-    }
+    // Done
+    println!("Successfully exported dataset {} to '{}'", style(&name).bold().cyan(), output.display());
+    Ok(())
+}
+
+/// Archives the given local dataset to the given file.
+///
+/// # Arguments
+/// - `info`: The [`DataInfo`] of the dataset to export.
+/// - `output`: The path of the archive to write.
+///
+/// # Errors
+/// This function errors if the dataset is not available locally or if we failed to stage or archive its files.
+async fn export_dataset(info: &DataInfo, output: &Path) -> Result<(), DataError> {
+    let path: &Path = match info.access.get(LOCALHOST) {
+        Some(AccessKind::File { path }) => path,
+        None => return Err(DataError::UnavailableDataset { name: info.name.clone(), locs: info.access.keys().cloned().collect() }),
+    };
+
+    // Stage the files in the same layout as a downloaded dataset
+    let tmp: TempDir = TempDir::new().map_err(|source| DataError::TempDirError { source })?;
+    let stage_dir: PathBuf = tmp.path().join(&info.name);
+    let data_dir: PathBuf = stage_dir.join("data");
+    let rel_path: PathBuf = if path.is_dir() {
+        copy_dir_recursively_async(path, &data_dir).await.map_err(|source| DataError::DataCopyError { source })?;
+        PathBuf::from("data")
+    } else {
+        let file_name: PathBuf = path.file_name().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("data"));
+        tfs::create_dir_all(&data_dir).await.map_err(|source| DataError::DirCreateError { what: "staged data", path: data_dir.clone(), source })?;
+        tfs::copy(path, data_dir.join(&file_name)).await.map_err(|source| DataError::DataFileCopyError {
+            path: path.into(),
+            target: data_dir.join(&file_name),
+            source,
+        })?;
+        Path::new("data").join(file_name)
+    };
+
+    // Write the info with the relative path, then archive the lot
+    let mut export_info: DataInfo = info.clone();
+    export_info.access = HashMap::from([(LOCALHOST.into(), AccessKind::File { path: rel_path })]);
+    export_info.to_path(stage_dir.join("data.yml")).map_err(|source| DataError::DataInfoWriteError { source })?;
+    brane_shr::fs::archive_async(&stage_dir, output, true).await.map_err(|source| DataError::ArchiveError {
+        dir: stage_dir.clone(),
+        path: output.into(),
+        source,
+    })
+}
+
+/// Imports a dataset from an archive produced by [`export()`] as a new local dataset.
+///
+/// If the archive records checksums for the dataset's files, these are verified before the dataset is installed.
+///
+/// # Arguments
+/// - `file`: The path of the archive to import.
+///
+/// # Errors
+/// This function errors if we failed to extract the archive, if its `data.yml` is missing or malformed, if it contains symbolic links or an
+/// illegal dataset name, if the files don't match their checksums or if a dataset with the same name already exists locally.
+pub async fn import_archive(file: PathBuf) -> Result<(), DataError> {
+    debug!("Importing dataset archive '{}'", file.display());
+    let datasets_dir: PathBuf = ensure_datasets_dir(true).map_err(|source| DataError::DatasetsError { source })?;
+    let info: DataInfo = import_dataset(&file, &datasets_dir).await?;
 
     // Done
+    println!("Successfully imported dataset {}", style(&info.name).bold().cyan());
     Ok(())
 }
 
+/// Imports a dataset from an archive produced by [`export()`] into the given datasets directory.
+///
+/// The archive is untrusted: the dataset's name must be a plain directory name, and the archive may not contain any symbolic links.
+///
+/// # Arguments
+/// - `file`: The path of the archive to import.
+/// - `datasets_dir`: The directory to add the dataset to.
+///
+/// # Returns
+/// The [`DataInfo`] of the imported dataset.
+///
+/// # Errors
+/// This function errors if we failed to extract the archive, if it is malformed or malicious, if the files don't match their checksums, if a
+/// dataset with the same name already exists or if we failed to install it.
+async fn import_dataset(file: &Path, datasets_dir: &Path) -> Result<DataInfo, DataError> {
+    // Extract the archive
+    let tmp: TempDir = TempDir::new().map_err(|source| DataError::TempDirError { source })?;
+    let stage_dir: PathBuf = tmp.path().join("dataset");
+    brane_shr::fs::unarchive_async(file, &stage_dir).await.map_err(|source| DataError::UnarchiveError {
+        path: file.into(),
+        dir: stage_dir.clone(),
+        source,
+    })?;
+    if let Some(link) = find_symlink(&stage_dir)? {
+        return Err(DataError::ArchiveSymlink { path: file.into(), link });
+    }
+    let info_path: PathBuf = stage_dir.join("data.yml");
+    let mut info: DataInfo = DataInfo::from_path(&info_path).map_err(|source| DataError::DataInfoReadError { path: info_path, source })?;
+    assert_valid_entry_name(&info.name).map_err(|source| DataError::IllegalDatasetName { source })?;
+
+    // Only accept paths that stay within the archive's data directory
+    let rel_path: PathBuf = match info.access.get(LOCALHOST) {
+        Some(AccessKind::File { path })
+            if path.starts_with("data") && path.components().all(|c| matches!(c, Component::Normal(_))) && stage_dir.join(path).exists() =>
+        {
+            path.clone()
+        },
+        _ => return Err(DataError::IllegalArchiveAccess { path: file.into(), name: info.name }),
+    };
+    if let Some(checksums) = &info.checksums {
+        let (data_path, checksums): (PathBuf, BTreeMap<PathBuf, FileChecksum>) = (stage_dir.join(&rel_path), checksums.clone());
+        let problems: Vec<String> = tokio::task::spawn_blocking(move || check_checksums(&data_path, &checksums))
+            .await
+            .map_err(|source| DataError::ChecksumJoinError { path: stage_dir.join(&rel_path), source })?;
+        if !problems.is_empty() {
+            for problem in problems {
+                println!("  - {problem}");
+            }
+            return Err(DataError::IntegrityError { total: 1, failed: vec![info.name] });
+        }
+    }
+
+    // Refuse to overwrite an existing dataset
+    let dataset_dir: PathBuf = datasets_dir.join(&info.name);
+    if dataset_dir.exists() {
+        return Err(DataError::DuplicateDatasetError { name: info.name });
+    }
+    tfs::create_dir(&dataset_dir).await.map_err(|source| DataError::DirCreateError { what: "dataset", path: dataset_dir.clone(), source })?;
+
+    // Move the files in and point the info to them
+    let result: Result<(), DataError> = async {
+        copy_dir_recursively_async(stage_dir.join("data"), dataset_dir.join("data"))
+            .await
+            .map_err(|source| shr_out_of_space_or(source, |source| DataError::DataCopyError { source }))?;
+        info.access = HashMap::from([(LOCALHOST.into(), AccessKind::File { path: dataset_dir.join(&rel_path) })]);
+        info.to_path(dataset_dir.join("data.yml")).map_err(|source| DataError::DataInfoWriteError { source })
+    }
+    .await;
+    if let Err(err) = result {
+        // Don't leave a half-imported dataset behind, or it blocks the next attempt
+        if let Err(err) = tfs::remove_dir_all(&dataset_dir).await {
+            warn!("Failed to remove partially imported dataset '{}': {}", dataset_dir.display(), err);
+        }
+        return Err(err);
+    }
+    Ok(info)
+}
+
 /// Lists all locally built/available datasets.
 ///
+/// # Arguments
+/// - `since`: If given, only lists datasets created at most this long ago.
+/// - `before`: If given, only lists datasets created at least this long ago.
+///
 /// # Returns
 /// Nothing, but does print a neat table to stdout.
 ///
 /// # Errors
 /// This function may error if we somehow failed to discover all the files.
-pub fn list() -> Result<(), DataError> {
+pub fn list(since: Option<Age>, before: Option<Age>) -> Result<(), DataError> {
     // Prepare display table.
     let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
     let mut table = Table::new();
@@ -462,6 +1165,12 @@ pub fn list() -> Result<(), DataError> {
     let index: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| DataError::LocalDataIndexError { source })?;
 
     for d in index {
+        // Skip it if it's outside of the requested age range
+        let age: u64 = (now - d.created.timestamp()).max(0) as u64;
+        if since.map(|since| age > since.0.as_secs()).unwrap_or(false) || before.map(|before| age < before.0.as_secs()).unwrap_or(false) {
+            continue;
+        }
+
         // Add the name/id of the dataset
         let name = pad_str(&d.name, 20, Alignment::Left, Some(".."));
 
@@ -488,7 +1197,7 @@ pub fn list() -> Result<(), DataError> {
         );
 
         // Fetch the created (or rather, elapsed)
-        let elapsed = Duration::from_secs((now - d.created.timestamp()) as u64);
+        let elapsed = Duration::from_secs(age);
         let created = format!("{} ago", HumanDuration(elapsed));
         let created = pad_str(&created, 15, Alignment::Left, Some(".."));
 
@@ -539,7 +1248,7 @@ pub fn path(datasets: Vec<impl AsRef<str>>) -> Result<(), DataError> {
                 return Err(DataError::UnavailableDataset { name: d.into(), locs: info.access.keys().cloned().collect() });
             }
         } else {
-            return Err(DataError::UnknownDataset { name: d.into() });
+            return Err(DataError::UnknownDataset { name: d.into(), suggestions: closest_names(d, index.iter().map(|info| &info.name)) });
         }
     }
 
@@ -587,3 +1296,67 @@ pub fn remove(datasets: Vec<impl AsRef<str>>, force: bool) -> Result<(), DataErr
     // Done
     Ok(())
 }
+
+/// Verifies the integrity of locally available datasets against the checksums recorded when they were built or downloaded.
+///
+/// # Arguments
+/// - `names`: The names of the datasets to verify.
+/// - `all`: If given, ignores `names` and verifies all local datasets.
+///
+/// # Returns
+/// Nothing, but does print the result for every dataset to stdout. Datasets without recorded checksums are reported as such, but not as a
+/// failure.
+///
+/// # Errors
+/// This function errors if we failed to read the local datasets, if any of `names` is unknown or if any dataset has missing or changed
+/// files.
+pub fn verify(names: Vec<String>, all: bool) -> Result<(), DataError> {
+    // Get the local DataIndex
+    let datasets_dir: PathBuf = ensure_datasets_dir(false).map_err(|source| DataError::DatasetsError { source })?;
+    let index: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| DataError::LocalDataIndexError { source })?;
+
+    // Resolve what to verify
+    let mut todo: Vec<&DataInfo> = if all {
+        index.iter().collect()
+    } else {
+        let mut todo: Vec<&DataInfo> = Vec::with_capacity(names.len());
+        for name in &names {
+            todo.push(index.get(name).ok_or_else(|| DataError::UnknownDataset {
+                name: name.clone(),
+                suggestions: closest_names(name, index.iter().map(|info| &info.name)),
+            })?);
+        }
+        todo
+    };
+    todo.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+    // Check them one-by-one
+    let mut failed: Vec<String> = Vec::new();
+    for info in &todo {
+        let path: &Path = match info.access.get(LOCALHOST) {
+            Some(AccessKind::File { path }) => path,
+            None => return Err(DataError::UnavailableDataset { name: info.name.clone(), locs: info.access.keys().cloned().collect() }),
+        };
+        let Some(checksums) = &info.checksums else {
+            println!("{}: {}", style(&info.name).bold().cyan(), style("no checksum recorded").yellow());
+            continue;
+        };
+
+        let problems: Vec<String> = check_checksums(path, checksums);
+        if problems.is_empty() {
+            println!("{}: {} ({} file(s))", style(&info.name).bold().cyan(), style("OK").bold().green(), checksums.len());
+        } else {
+            println!("{}: {}", style(&info.name).bold().cyan(), style(format!("{} problem(s)", problems.len())).bold().red());
+            for problem in problems {
+                println!("  - {problem}");
+            }
+            failed.push(info.name.clone());
+        }
+    }
+
+    // Report the result
+    if !failed.is_empty() {
+        return Err(DataError::IntegrityError { total: todo.len(), failed });
+    }
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//  DOCTOR.rs
+//
+//  Description:
+//!   Implements the `brane doctor` command, which runs a number of
+//!   environment checks and reports what's wrong (and how to fix it).
+//
+
+use std::path::PathBuf;
+
+use bollard::Docker;
+use brane_tsk::docker::{self, DockerOptions};
+use console::style;
+use error_trace::ErrorTrace as _;
+
+pub use crate::errors::DoctorError as Error;
+use crate::errors::UtilError;
+use crate::instance::InstanceInfo;
+use crate::utils::{DependencyError, check_buildx_version, check_docker_daemon, check_docker_version, get_config_dir, get_data_dir, http_client};
+use crate::{MIN_BUILDX_VERSION, MIN_DOCKER_VERSION};
+
+
+/***** HELPER STRUCTS *****/
+/// Defines the possible outcomes of a single check.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Status {
+    /// The check passed.
+    Pass,
+    /// The check found something that is not fatal, but worth knowing.
+    Warn,
+    /// The check found something that will prevent Brane from working.
+    Fail,
+}
+
+/// Defines the result of a single check.
+#[derive(Clone, Debug)]
+struct Check {
+    /// The outcome of the check.
+    status:  Status,
+    /// A short description of what we checked.
+    what:    &'static str,
+    /// A message describing what we found.
+    message: String,
+    /// If not passed, a hint on how to fix it.
+    hint:    Option<String>,
+}
+impl Check {
+    /// Constructor for a passed Check.
+    #[inline]
+    fn pass(what: &'static str, message: impl Into<String>) -> Self { Self { status: Status::Pass, what, message: message.into(), hint: None } }
+
+    /// Constructor for a Check with a warning.
+    #[inline]
+    fn warn(what: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: Status::Warn, what, message: message.into(), hint: Some(hint.into()) }
+    }
+
+    /// Constructor for a failed Check.
+    #[inline]
+    fn fail(what: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { status: Status::Fail, what, message: message.into(), hint: Some(hint.into()) }
+    }
+
+    /// Prints this check to stdout as a checklist item.
+    fn print(&self) {
+        let marker: String = match self.status {
+            Status::Pass => style("[ OK ]").green().bold().to_string(),
+            Status::Warn => style("[WARN]").yellow().bold().to_string(),
+            Status::Fail => style("[FAIL]").red().bold().to_string(),
+        };
+        println!("{} {}: {}", marker, style(self.what).bold(), self.message);
+        if let Some(hint) = &self.hint {
+            println!("       {} {}", style("->").dim(), hint);
+        }
+    }
+}
+
+
+
+
+
+/***** CHECKS *****/
+/// Checks whether the Docker daemon is reachable and recent enough.
+///
+/// # Arguments
+/// - `docker_opts`: Configuration for how to connect to the local Docker daemon.
+///
+/// # Returns
+/// A list of [`Check`]s describing the results.
+async fn check_docker(docker_opts: &DockerOptions) -> Vec<Check> {
+    // Connect to the daemon
+    let docker: Docker = match docker::connect_local(docker_opts) {
+        Ok(docker) => docker,
+        Err(err) => {
+            return vec![Check::fail(
+                "Docker daemon",
+                format!("{}", err.trace()),
+                format!("Make sure Docker is installed and running, and that '{}' is accessible to you", docker_opts.host),
+            )];
+        },
+    };
+    match check_docker_daemon(&docker).await {
+        Ok(()) => {},
+        Err(err @ UtilError::DockerNotResponding { .. }) => {
+            return vec![Check::fail("Docker daemon", err.to_string(), "Restart the Docker daemon (e.g., 'sudo systemctl restart docker')")];
+        },
+        Err(err) => {
+            return vec![Check::fail(
+                "Docker daemon",
+                format!("{}: {}", DependencyError::DockerNotInstalled, err.trace()),
+                format!(
+                    "Start the Docker daemon (e.g., 'sudo systemctl start docker') and make sure you have access to '{}' (e.g., by being in the \
+                     'docker' group)",
+                    docker_opts.host
+                ),
+            )];
+        },
+    }
+    let mut checks: Vec<Check> = vec![Check::pass("Docker daemon", format!("Reachable at '{}'", docker_opts.host))];
+
+    // Check its version
+    checks.push(match check_docker_version(&docker).await {
+        Ok(Ok(version)) => Check::pass("Docker version", format!("{version}")),
+        Ok(Err(err)) => Check::fail("Docker version", err.to_string(), format!("Upgrade Docker to version {MIN_DOCKER_VERSION} or later")),
+        Err(err) => Check::warn("Docker version", format!("{}", err.trace()), "Check that your Docker installation is not corrupted"),
+    });
+
+    // Done
+    checks
+}
+
+/// Checks whether the Buildx plugin is installed and recent enough.
+///
+/// # Returns
+/// A [`Check`] describing the result.
+fn check_buildx() -> Check {
+    match check_buildx_version() {
+        Ok(Ok(version)) => Check::pass("BuildKit", format!("Buildx plugin version {version}")),
+        Ok(Err(err)) => {
+            Check::fail("BuildKit", err.to_string(), format!("Upgrade the Docker Buildx plugin to version {MIN_BUILDX_VERSION} or later"))
+        },
+        Err(err) => Check::fail(
+            "BuildKit",
+            format!("{}: {}", DependencyError::BuildkitNotInstalled, err),
+            "Install the Docker Buildx plugin (see https://docs.docker.com/build/install-buildx/)",
+        ),
+    }
+}
+
+/// Checks whether one of Brane's local directories exists.
+///
+/// # Arguments
+/// - `what`: What kind of directory we're checking (used for reporting).
+/// - `dir`: The result of resolving the directory.
+///
+/// # Returns
+/// A [`Check`] describing the result.
+fn check_dir(what: &'static str, dir: Result<PathBuf, crate::errors::UtilError>) -> Check {
+    match dir {
+        Ok(dir) if dir.is_dir() => Check::pass(what, format!("'{}'", dir.display())),
+        Ok(dir) if dir.exists() => Check::fail(
+            what,
+            format!("'{}' exists but is not a directory", dir.display()),
+            "Move or remove the file so Brane can create the directory",
+        ),
+        Ok(dir) => Check::warn(what, format!("'{}' does not exist yet", dir.display()), "It is created automatically the first time it is needed"),
+        Err(err) => {
+            Check::fail(what, format!("{}", err.trace()), "Make sure your user has a home directory with standard configuration/data folders")
+        },
+    }
+}
+
+/// Checks whether an instance is selected and whether it is reachable.
+///
+/// # Returns
+/// A list of [`Check`]s describing the results.
+async fn check_instance() -> Vec<Check> {
+    const ADD_HINT: &str = "Add one with 'brane instance add <HOSTNAME> --use' or select an existing one with 'brane instance select <NAME>'";
+
+    // See if there is an active instance
+    match InstanceInfo::active_instance_exists() {
+        Ok(true) => {},
+        Ok(false) => {
+            return vec![Check::warn("Active instance", "No instance selected (only local commands will work)", ADD_HINT)];
+        },
+        Err(err) => {
+            return vec![Check::fail("Active instance", format!("{}", err.trace()), ADD_HINT)];
+        },
+    }
+    let (name, info): (String, InstanceInfo) = match (InstanceInfo::get_active_name(), InstanceInfo::from_active_path()) {
+        (Ok(name), Ok(info)) => (name, info),
+        (Err(err), _) | (_, Err(err)) => {
+            return vec![Check::fail("Active instance", format!("{}", err.trace()), "Re-select or re-add the instance with 'brane instance select'")];
+        },
+    };
+    let mut checks: Vec<Check> = vec![Check::pass("Active instance", format!("'{name}'"))];
+
+    // Check if we can reach it
+    let health_addr: String = format!("{}/health", info.api);
+    let hint: String = format!("Check that the instance is running and that its address '{}' is correct (see 'brane instance edit')", info.api);
+    checks.push(match http_client() {
+        Ok(client) => match client.get(&health_addr).send().await {
+            Ok(res) if res.status().is_success() => Check::pass("Instance reachable", format!("'{}' is healthy", info.api)),
+            Ok(res) => Check::fail("Instance reachable", format!("'{}' responded with status {}", health_addr, res.status()), hint),
+            Err(err) => Check::fail("Instance reachable", format!("Failed to reach '{health_addr}': {err}"), hint),
+        },
+        Err(err) => Check::fail("Instance reachable", format!("{}", err.trace()), hint),
+    });
+
+    // Done
+    checks
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Runs all environment checks and prints a checklist of the results, including hints on how to fix any problems.
+///
+/// # Arguments
+/// - `docker_opts`: Configuration for how to connect to the local Docker daemon.
+///
+/// # Errors
+/// This function errors if any of the checks failed (warnings are not considered failures).
+pub async fn handle(docker_opts: DockerOptions) -> Result<(), Error> {
+    // Run all the checks
+    let mut checks: Vec<Check> = check_docker(&docker_opts).await;
+    checks.push(check_buildx());
+    checks.push(check_dir("Config directory", get_config_dir()));
+    checks.push(check_dir("Data directory", get_data_dir()));
+    checks.extend(check_instance().await);
+
+    // Print them
+    println!();
+    for check in &checks {
+        check.print();
+    }
+    println!();
+
+    // Summarize
+    let warned: usize = checks.iter().filter(|c| c.status == Status::Warn).count();
+    let failed: usize = checks.iter().filter(|c| c.status == Status::Fail).count();
+    println!(
+        "{} passed, {} warning(s), {} failed",
+        style(checks.len() - warned - failed).green().bold(),
+        style(warned).yellow().bold(),
+        style(failed).red().bold()
+    );
+    if failed > 0 {
+        return Err(Error::ChecksFailed { failed });
+    }
+    Ok(())
+}
@@ -15,12 +15,77 @@
 use std::error::Error;
 use std::path::PathBuf;
 
-use brane_shr::formatters::{BlockFormatter, PrettyListFormatter};
+use brane_shr::formatters::{BlockFormatter, DidYouMeanFormatter, PrettyListFormatter};
 use reqwest::StatusCode;
 use specifications::address::Address;
 use specifications::container::{ContainerInfoError, Image, LocalContainerInfoError};
 use specifications::package::{PackageInfoError, PackageKindError};
-use specifications::version::{ParseError as VersionParseError, Version};
+use specifications::version::{ParseError as VersionParseError, Version, VersionConstraint};
+
+use crate::spec::{ByteSize, Checksum, CompletionKind, LintRule};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_exit_code() {
+        // Usage errors, directly and through the run command
+        assert_eq!(CliError::DownloadNoDatasets.exit_code(), CliError::EXIT_USAGE);
+        assert_eq!(CliError::StrictDataWithoutYes.exit_code(), CliError::EXIT_USAGE);
+        let err = PackageError::ConstraintWithVersion { name: "test".into(), version: Version::new(1, 0, 0) };
+        assert_eq!(CliError::PackageError { source: err }.exit_code(), CliError::EXIT_USAGE);
+
+        // Missing or invalid configuration, directly and while loading the active instance for a subcommand
+        let err = VerifyError::ConfigFailed {
+            source: brane_cfg::info::InfoError::InputOpenError { path: "infra.yml".into(), err: std::io::Error::other("gone") },
+        };
+        assert_eq!(CliError::VerifyError { source: err }.exit_code(), CliError::EXIT_USAGE);
+        let err = DataError::InstanceInfoError { source: InstanceError::NoActiveInstance };
+        assert_eq!(CliError::DataError { source: err }.exit_code(), CliError::EXIT_USAGE);
+        let err = RegistryError::InstanceInfoError { source: InstanceError::UnknownInstance { name: "test".into(), suggestions: vec![] } };
+        assert_eq!(CliError::RegistryError { source: err }.exit_code(), CliError::EXIT_USAGE);
+        let err = RunError::InputError { input: "wf.bs".into(), source: InputError::IllegalInput { raw: "x".into() } };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_USAGE);
+
+        // Policy denials, directly and somewhere in the chain of causes
+        let err = RunError::ExecDenied { source: Box::new(std::io::Error::other("no")) };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_DENIED);
+        let denied = brane_tsk::errors::PlanError::CheckerDenied { domain: "hospital".into(), reasons: vec![] };
+        let err = RunError::ExecError { source: Box::new(OfflineVmError::PlanError { source: denied }) };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_DENIED);
+
+        // Docker failures
+        let err = UtilError::DockerNotResponding { timeout: std::time::Duration::from_secs(5) };
+        assert_eq!(CliError::UtilError { source: err }.exit_code(), CliError::EXIT_DOCKER);
+        let docker = brane_tsk::errors::DockerError::ContainerNoNetwork { name: "task".into() };
+        let err = RunError::ExecError { source: Box::new(docker) };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_DOCKER);
+
+        // Network failures
+        let err = RunError::CommandRequestError { address: "http://localhost:50053".into(), source: tonic::Status::unavailable("down") };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_NETWORK);
+
+        // Unavailable datasets, directly, while downloading and while planning
+        let err = DataError::UnavailableDataset { name: "test".into(), locs: vec![] };
+        assert_eq!(CliError::DataError { source: err }.exit_code(), CliError::EXIT_UNAVAILABLE);
+        let err = RunError::DataDownloadError { source: DataError::UnavailableDataset { name: "test".into(), locs: vec![] } };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_UNAVAILABLE);
+        let unavailable = brane_tsk::errors::PlanError::DatasetUnavailable { name: "test".into(), locs: vec!["hospital".into()] };
+        let err = RunError::ExecError { source: Box::new(OfflineVmError::PlanError { source: unavailable }) };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_UNAVAILABLE);
+
+        // Anything else
+        let err = RunError::StdinReadError { source: std::io::Error::other("closed") };
+        assert_eq!(CliError::RunError { source: err }.exit_code(), CliError::EXIT_FAILURE);
+    }
+}
+
+
+
 
 
 /***** GLOBALS *****/
@@ -34,6 +99,18 @@ lazy_static! {
 
 /***** ERROR ENUMS *****/
 /// Collects toplevel and uncategorized errors in the brane-cli package.
+///
+/// When `brane` fails with one of these, it exits with a code that tells scripts what kind of failure it was (see
+/// [`CliError::exit_code()`]):
+/// - `1` ([`CliError::EXIT_FAILURE`]): Any error not listed below.
+/// - `2` ([`CliError::EXIT_USAGE`]): The command was called wrongly, e.g., with conflicting or missing arguments, or the configuration it
+///   needs is missing or invalid, e.g., no instance is selected or its `info.yml` cannot be loaded. This is also what `clap` exits with if
+///   the arguments fail to parse.
+/// - `3` ([`CliError::EXIT_NETWORK`]): Communicating with a remote (e.g., the registry, API or driver of an instance) failed.
+/// - `4` ([`CliError::EXIT_DOCKER`]): Communicating with the local Docker daemon failed, or it is unavailable.
+/// - `5` ([`CliError::EXIT_DENIED`]): The workflow was denied by the policy of one of the involved domains.
+/// - `6` ([`CliError::EXIT_UNAVAILABLE`]): A dataset is not available locally and could not (or, with `--strict-data`, would not) be
+///   downloaded.
 #[derive(Debug, thiserror::Error)]
 pub enum CliError {
     // Toplevel errors for the subcommands
@@ -46,15 +123,24 @@ pub enum CliError {
     /// Errors that occur when validating workflow against policy.
     #[error(transparent)]
     CheckError { source: CheckError },
+    /// Errors that occur when compiling a workflow.
+    #[error(transparent)]
+    CompileError { source: CompileError },
     /// Errors that occur during any of the data(-related) command(s)
     #[error(transparent)]
     DataError { source: DataError },
+    /// Errors that occur during the doctor command
+    #[error(transparent)]
+    DoctorError { source: DoctorError },
     /// Errors that occur during the import command
     #[error(transparent)]
     ImportError { source: ImportError },
     /// Errors that occur during identity management.
     #[error(transparent)]
     InstanceError { source: InstanceError },
+    /// Errors that occur when linting a workflow.
+    #[error(transparent)]
+    LintError { source: LintError },
     /// Errors that occur during some package command
     #[error(transparent)]
     PackageError { source: PackageError },
@@ -99,6 +185,104 @@ pub enum CliError {
     /// Could not parse a NAME:VERSION pair
     #[error("Could not parse '{raw}'")]
     PackagePairParseError { raw: String, source: specifications::version::ParseError },
+    /// `--insecure-skip-verify` was given to a command that writes persistent state.
+    #[error(
+        "Refusing to run '{what}' with '--insecure-skip-verify', since it writes persistent state; import the instance's CA with 'brane certs add' \
+         instead"
+    )]
+    InsecureSkipVerifyNotAllowed { what: &'static str },
+    /// No use-case was given, and the active instance does not define a default one.
+    #[error(
+        "No use-case given for '{what}', and the active instance does not define a default one; give it as an argument or set 'defaults.use_case' \
+         in the instance's 'info.yml'"
+    )]
+    MissingUseCase { what: &'static str },
+    /// Both `--all` and specific datasets were given to `data download`.
+    #[error("Cannot give both '--all' and specific datasets to download")]
+    DownloadAllWithDatasets,
+    /// Neither `--all` nor any datasets were given to `data download`.
+    #[error("No datasets given to download (give at least one, or use '--all')")]
+    DownloadNoDatasets,
+    /// `--strict-data` was given to `data download --all` without `--yes`, which would ask for confirmation.
+    #[error("Cannot download all datasets with '--strict-data' without '--yes', since that would ask for confirmation")]
+    StrictDataWithoutYes,
+}
+
+impl CliError {
+    /// The exit code for workflows that were denied by policy.
+    pub const EXIT_DENIED: i32 = 5;
+    /// The exit code for errors communicating with the local Docker daemon.
+    pub const EXIT_DOCKER: i32 = 4;
+    /// The exit code for errors that don't fall in any of the other categories.
+    pub const EXIT_FAILURE: i32 = 1;
+    /// The exit code for errors communicating with a remote.
+    pub const EXIT_NETWORK: i32 = 3;
+    /// The exit code for datasets that are not locally available.
+    pub const EXIT_UNAVAILABLE: i32 = 6;
+    /// The exit code for errors in how the command was called.
+    pub const EXIT_USAGE: i32 = 2;
+
+    /// Returns the exit code with which `brane` should terminate when it fails with this error.
+    ///
+    /// Subcommand errors are categorized by the first error in their chain of causes that is a failure to load the instance, a policy denial
+    /// or a network or Docker failure.
+    ///
+    /// # Returns
+    /// One of the `EXIT_*`-codes of [`CliError`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::PackageFileCanonicalizeError { .. }
+            | Self::WorkdirCanonicalizeError { .. }
+            | Self::IllegalPackageKind { .. }
+            | Self::PackagePairParseError { .. }
+            | Self::InsecureSkipVerifyNotAllowed { .. }
+            | Self::MissingUseCase { .. }
+            | Self::DownloadAllWithDatasets
+            | Self::DownloadNoDatasets
+            | Self::StrictDataWithoutYes => Self::EXIT_USAGE,
+            Self::RunError { source: RunError::InputError { .. } }
+            | Self::PackageError { source: PackageError::ConstraintWithVersion { .. } }
+            | Self::VerifyError { source: VerifyError::ConfigFailed { .. } } => Self::EXIT_USAGE,
+            // These are transparent, so they don't show up in the chain of causes below
+            Self::InstanceError { source }
+            | Self::RegistryError { source: RegistryError::InstanceInfoError { source } }
+            | Self::RunError { source: RunError::InstanceInfoError { source } }
+            | Self::VersionError { source: VersionError::InstanceInfoError { source } }
+                if source.is_load_error() =>
+            {
+                Self::EXIT_USAGE
+            },
+            Self::RunError { source: RunError::ExecDenied { .. } } => Self::EXIT_DENIED,
+            Self::UtilError { source: UtilError::DockerNotResponding { .. } } => Self::EXIT_DOCKER,
+            Self::DataError { source: DataError::UnavailableDataset { .. } | DataError::AmbiguousLocation { .. } }
+            | Self::RunError {
+                source: RunError::UnavailableDataset { .. } | RunError::DataDownloadError { source: DataError::UnavailableDataset { .. } },
+            } => Self::EXIT_UNAVAILABLE,
+
+            // The subcommand errors are transparent, so their chain starts at their cause
+            _ => {
+                let mut source: Option<&(dyn Error + 'static)> = Some(self);
+                while let Some(err) = source {
+                    if err.downcast_ref::<InstanceError>().is_some_and(InstanceError::is_load_error) {
+                        return Self::EXIT_USAGE;
+                    }
+                    match err.downcast_ref::<brane_tsk::errors::PlanError>() {
+                        Some(brane_tsk::errors::PlanError::CheckerDenied { .. }) => return Self::EXIT_DENIED,
+                        Some(brane_tsk::errors::PlanError::DatasetUnavailable { .. }) => return Self::EXIT_UNAVAILABLE,
+                        _ => {},
+                    }
+                    if err.is::<bollard::errors::Error>() || err.is::<brane_tsk::errors::DockerError>() {
+                        return Self::EXIT_DOCKER;
+                    }
+                    if err.is::<reqwest::Error>() || err.is::<tonic::Status>() || err.is::<tonic::transport::Error>() {
+                        return Self::EXIT_NETWORK;
+                    }
+                    source = err.source();
+                }
+                Self::EXIT_FAILURE
+            },
+        }
+    }
 }
 
 /// Collects errors during the build subcommand
@@ -127,9 +311,18 @@ pub enum BuildError {
     #[error("Failed to create lockfile for package '{name}'")]
     LockCreateError { name: String, source: brane_shr::fs::Error },
 
+    /// The disk ran full while writing something.
+    #[error(transparent)]
+    OutOfSpace {
+        #[from]
+        source: OutOfSpaceError,
+    },
     /// Could not write to the DockerFile string.
     #[error("Could not write to the internal DockerFile")]
     DockerfileStrWriteError { source: std::fmt::Error },
+    /// Dependencies were given for a base image without a package manager
+    #[error("Cannot install dependencies {} in base image '{}' because it has no package manager (set 'packageManager' in the container file if it does)", PrettyListFormatter::new(dependencies.iter().map(|dep| format!("'{dep}'")), "and"), base)]
+    NoPackageManager { base: String, dependencies: Vec<String> },
     /// A given filepath escaped the working directory
     #[error("File '{}' tries to escape package working directory; consider moving Brane's working directory up (using --workdir) and avoid '..'", path.display())]
     UnsafePath { path: PathBuf },
@@ -137,6 +330,12 @@ pub enum BuildError {
     #[error("Could not find the package entrypoint '{}'", path.display())]
     MissingExecutable { path: PathBuf },
 
+    /// Could not read a user-provided Dockerfile.
+    #[error("Could not read Dockerfile '{}'", path.display())]
+    DockerfileReadError { path: PathBuf, source: std::io::Error },
+    /// A user-provided Dockerfile does not have any build stage.
+    #[error("Dockerfile '{}' does not contain a FROM instruction", path.display())]
+    DockerfileNoFrom { path: PathBuf },
     /// Could not create the Dockerfile in the build directory.
     #[error("Could not create Dockerfile '{}'", path.display())]
     DockerfileCreateError { path: PathBuf, source: std::io::Error },
@@ -307,8 +506,8 @@ pub enum CertsError {
     #[error("Failed to get instance directory")]
     InstanceDirError { source: UtilError },
     /// An unknown instance was given.
-    #[error("Unknown instance '{name}'")]
-    UnknownInstance { name: String },
+    #[error("Unknown instance '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownInstance { name: String, suggestions: Vec<String> },
     /// Failed to read the directory behind the active instance link.
     #[error("Failed to read active instance")]
     ActiveInstanceReadError { source: InstanceError },
@@ -342,6 +541,9 @@ pub enum CertsError {
     /// Failed to create the certificates directory.
     #[error("Failed to create certificate directory '{}'", path.display())]
     CertsDirCreateError { path: PathBuf, source: std::io::Error },
+    /// Failed to move a certificates directory.
+    #[error("Failed to move certificate directory '{}' to '{}'", from.display(), to.display())]
+    CertsDirRenameError { from: PathBuf, to: PathBuf, source: std::io::Error },
     /// Failed to open the given file in append mode.
     #[error("Failed to open {} file '{}' for appending", what, path.display())]
     FileOpenError { what: &'static str, path: PathBuf, source: std::io::Error },
@@ -358,6 +560,20 @@ pub enum CertsError {
     /// Failed to read a specific entry within the directory with instances.
     #[error("Failed to read entry {} in {} directory '{}'", entry, what, path.display())]
     DirEntryReadError { what: &'static str, path: PathBuf, entry: usize, source: std::io::Error },
+
+    /// The given domain has no certificates in the given instance.
+    #[error("Domain '{domain}' has no certificates in instance '{instance}' (see 'brane certs list')")]
+    UnknownDomain { instance: String, domain: String },
+    /// A CA certificate file to export contains private key material.
+    #[error("Refusing to export CA certificate file '{}' because it contains a private key", path.display())]
+    CaContainsKey { path: PathBuf },
+    /// A CA certificate file to export contains no certificates.
+    #[error("CA certificate file '{}' does not contain any certificates", path.display())]
+    NoCaCertInFile { path: PathBuf },
+
+    /// Failed to serialize the expiry report.
+    #[error("Failed to serialize certificate expiry report as JSON")]
+    ReportSerializeError { source: serde_json::Error },
 }
 
 /// Defines errors originating from the `brane check`-subcommand.
@@ -369,6 +585,9 @@ pub enum CheckError {
     /// The compile step from `brane_ast` failed.
     #[error("Failed to compile workflow '{input}' (see output above)")]
     AstCompile { input: String },
+    /// Failed to load the data index from a file.
+    #[error("Failed to load data index from '{}'", path.display())]
+    DataIndexLoad { path: PathBuf, source: specifications::data::DataIndexError },
     /// Failed to retrieve the data index.
     #[error("Failed to retrieve data index from '{url}'")]
     DataIndexRetrieve { url: String, source: brane_tsk::api::Error },
@@ -384,9 +603,33 @@ pub enum CheckError {
     /// Failed to read the input from stdin.
     #[error("Failed to read input from stdin")]
     InputStdinRead { source: std::io::Error },
+    /// Failed to load the package index from a file.
+    #[error("Failed to load package index from '{}'", path.display())]
+    PackageIndexLoad { path: PathBuf, source: specifications::package::PackageIndexError },
     /// Failed to retrieve the package index.
     #[error("Failed to retrieve package index from '{url}'")]
     PackageIndexRetrieve { url: String, source: brane_tsk::api::Error },
+    /// Failed to deserialize the plan returned by the driver.
+    #[error("Failed to deserialize plan returned by driver '{address}'")]
+    PlanDeserialize { address: Address, source: serde_json::Error },
+    /// The driver accepted a workflow without returning its plan.
+    #[error("Driver '{address}' accepted the workflow but did not return its plan (is the instance up-to-date?)")]
+    PlanMissing { address: Address },
+    /// Failed to serialize the explained plan.
+    #[error("Failed to serialize plan as JSON")]
+    PlanSerialize { source: serde_json::Error },
+    /// Failed to wait for Ctrl+C while watching.
+    #[error("Failed to wait for Ctrl+C")]
+    CtrlC { source: std::io::Error },
+    /// Failed to watch the input file for changes.
+    #[error("Failed to watch '{}' for changes", path.display())]
+    Watch { path: PathBuf, source: notify::Error },
+    /// Failed to create a file watcher.
+    #[error("Failed to create file watcher")]
+    WatcherCreate { source: notify::Error },
+    /// Asked to watch stdin, which cannot change.
+    #[error("Cannot watch stdin for changes; give a file to check instead")]
+    WatchStdin,
     /// Failed to compile a given workflow.
     #[error("Failed to compile workflow '{input}'")]
     WorkflowCompile { input: String, source: Box<Self> },
@@ -395,9 +638,86 @@ pub enum CheckError {
     WorkflowSerialize { input: String, source: serde_json::Error },
 }
 
+/// Defines errors originating from the `brane workflow compile`-subcommand.
+#[derive(Debug, thiserror::Error)]
+pub enum CompileError {
+    /// The compile step from `brane_ast` failed.
+    #[error("Failed to compile workflow '{input}' (see output above)")]
+    AstCompile { input: String },
+    /// Failed to export the workflow as CWL.
+    #[error("Failed to export workflow '{input}' as CWL")]
+    CwlExport { input: String, source: CwlExportError },
+    /// Failed to serialize the CWL document.
+    #[error("Failed to serialize CWL document for workflow '{input}'")]
+    CwlSerialize { input: String, source: serde_yaml::Error },
+    /// Failed to get the local datasets directory.
+    #[error("Failed to get local datasets directory")]
+    DatasetsDirError { source: UtilError },
+    /// Failed to read the input from the given file.
+    #[error("Failed to read input file '{}'", path.display())]
+    InputFileRead { path: PathBuf, source: std::io::Error },
+    /// Failed to read the input from stdin.
+    #[error("Failed to read input from stdin")]
+    InputStdinRead { source: std::io::Error },
+    /// Failed to get the local data index.
+    #[error("Failed to get local data index")]
+    LocalDataIndexError { source: brane_tsk::local::Error },
+    /// Failed to get the local package index.
+    #[error("Failed to get local package index")]
+    LocalPackageIndexError { source: brane_tsk::local::Error },
+    /// Failed to write the output to the given file.
+    #[error("Failed to write output file '{}'", path.display())]
+    OutputFileWrite { path: PathBuf, source: std::io::Error },
+    /// Failed to get the local packages directory.
+    #[error("Failed to get local packages directory")]
+    PackagesDirError { source: UtilError },
+    /// Failed to serialize the compiled workflow.
+    #[error("Failed to serialize workflow '{input}'")]
+    WorkflowSerialize { input: String, source: serde_json::Error },
+}
+
+/// Defines errors that occur when exporting a workflow as CWL.
+#[derive(Debug, thiserror::Error)]
+pub enum CwlExportError {
+    /// A task has data arguments but we can't tell which input is which.
+    #[error(
+        "Cannot determine which of the {inputs} dataset(s)/intermediate result(s) given to task '{task}' map to which of its {args} data argument(s)"
+    )]
+    AmbiguousDataInputs { task: String, args: usize, inputs: usize },
+    /// A task's package is not known locally.
+    #[error("Unknown package '{name}' (or it does not have version {version}){}", DidYouMeanFormatter::new(suggestions))]
+    UnknownPackage { name: String, version: Version, suggestions: Vec<String> },
+    /// A task has an argument whose type has no CWL equivalent.
+    #[error("Argument '{arg}' of task '{task}' has type {ty}, which cannot be represented in CWL")]
+    UnsupportedArgumentType { task: String, arg: String, ty: String },
+    /// The workflow uses constructs that cannot be represented in CWL.
+    #[error("Workflow cannot be exported to CWL because it contains unsupported constructs:{}", constructs.iter().map(|c| format!("\n - {c}")).collect::<String>())]
+    UnsupportedConstructs { constructs: Vec<String> },
+}
+
+/// Defines errors that occur when evaluating CWL expressions.
+#[derive(Debug, thiserror::Error)]
+pub enum CwlExprError {
+    /// An expression was opened but never closed.
+    #[error("Unterminated CWL expression in '{raw}'")]
+    Unterminated { raw: String },
+    /// A parameter reference does not resolve to a value.
+    #[error("CWL parameter reference '$({reference})' does not resolve to a value")]
+    UnknownReference { reference: String },
+    /// The document uses JavaScript expressions but we were built without an engine.
+    #[error("JS expressions unsupported; rebuild with --features cwl-js (found '{expr}')")]
+    JsUnsupported { expr: String },
+    /// A JavaScript expression failed to evaluate.
+    #[error("Failed to evaluate CWL JavaScript expression '{expr}': {err}")]
+    JsEval { expr: String, err: String },
+}
+
 /// Collects errors during the build subcommand
 #[derive(Debug, thiserror::Error)]
 pub enum DataError {
+    /// Failed to build the HTTP client to reach the instance with.
+    #[error("Failed to build HTTP client")]
+    HttpClientError { source: UtilError },
     /// Failed to sent the GET-request to fetch the dfelegate.
     #[error("Failed to send {what} request to '{address}'")]
     RequestError { what: &'static str, address: String, source: reqwest::Error },
@@ -428,9 +748,6 @@ pub enum DataError {
     /// A directory could not be created.
     #[error("Failed to create {} directory '{}'", what, path.display())]
     DirCreateError { what: &'static str, path: PathBuf, source: std::io::Error },
-    /// Failed to create a temporary directory.
-    #[error("Failed to create temporary directory")]
-    TempDirError { source: std::io::Error },
     /// Failed to create the dataset directory.
     #[error("Failed to create dataset directory for dataset '{name}'")]
     DatasetDirError { name: String, source: UtilError },
@@ -440,13 +757,10 @@ pub enum DataError {
     /// Failed to create a new reqwest client
     #[error("Failed to create new client")]
     ClientCreateError { source: reqwest::Error },
-    /// Failed to reach the next chunk of data.
-    #[error("Failed to get next chunk in download stream from '{address}'")]
-    DownloadStreamError { address: String, source: reqwest::Error },
-    /// Failed to create the file to which we write the download stream.
+    /// Failed to create the file to which we keep the downloaded archive.
     #[error("Failed to create tarball file '{}'", path.display())]
     TarCreateError { path: PathBuf, source: std::io::Error },
-    /// Failed to write to the file where we write the download stream.
+    /// Failed to write to the file where we keep the downloaded archive.
     #[error("Failed to write to tarball file '{}'", path.display())]
     TarWriteError { path: PathBuf, source: std::io::Error },
     /// Failed to extract the downloaded tar.
@@ -478,6 +792,21 @@ pub enum DataError {
     /// A dataset with the given name already exists.
     #[error("A dataset with the name '{name}' already exists locally")]
     DuplicateDatasetError { name: String },
+    /// The name of a dataset cannot be used as a local dataset name.
+    #[error("Illegal dataset name")]
+    IllegalDatasetName { source: UtilError },
+    /// The disk ran full while writing something.
+    #[error(transparent)]
+    OutOfSpace {
+        #[from]
+        source: OutOfSpaceError,
+    },
+    /// The download was refused because of its size.
+    #[error(transparent)]
+    DownloadLimit {
+        #[from]
+        source: DownloadLimitError,
+    },
     /// Failed to copy the data directory over.
     #[error("Failed to data directory")]
     DataCopyError { source: brane_shr::fs::Error },
@@ -504,15 +833,36 @@ pub enum DataError {
     #[error("Failed to ask the user (you!) to select a download location")]
     DataSelectError { source: dialoguer::Error },
     /// We encountered a location we did not know
-    #[error("Unknown location '{name}'")]
-    UnknownLocation { name: String },
+    #[error("Unknown location '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownLocation { name: String, suggestions: Vec<String> },
 
     /// The given dataset was unknown to us.
-    #[error("Unknown dataset '{name}'")]
-    UnknownDataset { name: String },
+    #[error("Unknown dataset '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownDataset { name: String, suggestions: Vec<String> },
+    /// Failed to ask the user for consent before downloading all datasets.
+    #[error("Failed to ask the user (you) for confirmation before downloading all datasets")]
+    DownloadConfirmationError { source: dialoguer::Error },
+    /// Some datasets failed to download when downloading in bulk.
+    #[error("Failed to download {} out of {} dataset(s): {}", failed.len(), total, failed.iter().map(|n| format!("'{n}'")).collect::<Vec<String>>().join(", "))]
+    BatchDownloadError { total: usize, failed: Vec<String> },
+    /// Failed to compute the checksum of a file in a dataset.
+    #[error("Failed to compute checksum of '{}'", path.display())]
+    ChecksumError { path: PathBuf, source: std::io::Error },
+    /// Failed to wait for the thread computing the checksums of a dataset.
+    #[error("Failed to join the thread computing checksums of '{}'", path.display())]
+    ChecksumJoinError { path: PathBuf, source: tokio::task::JoinError },
+    /// Some datasets have missing or changed files.
+    #[error("{} out of {} dataset(s) failed verification: {}", failed.len(), total, failed.iter().map(|n| format!("'{n}'")).collect::<Vec<String>>().join(", "))]
+    IntegrityError { total: usize, failed: Vec<String> },
     /// the given dataset was known but not locally available.
     #[error("Dataset '{}' is unavailable{}", name, if !locs.is_empty() { format!("; try {} instead", locs.iter().map(|l| format!("'{l}'")).collect::<Vec<String>>().join(", ")) } else { String::new() })]
     UnavailableDataset { name: String, locs: Vec<String> },
+    /// The given dataset is available at multiple locations, and we may not ask the user which one to use.
+    #[error(
+        "Dataset '{name}' is available at multiple locations ({}); specify one with '--locs' (not prompting because '--strict-data' is given)",
+        PrettyListFormatter::new(locs.iter().map(|l| format!("'{l}'")), "and")
+    )]
+    AmbiguousLocation { name: String, locs: Vec<String> },
 
     /// Failed to ask the user for consent before removing the dataset.
     #[error("Failed to ask the user (you) for confirmation before removing a dataset")]
@@ -523,6 +873,39 @@ pub enum DataError {
     /// Failed to serialize workflow
     #[error("Could not serialize workflow when: {context}")]
     WorkflowSerializeError { context: String, source: serde_json::Error },
+
+    /// Failed to create a temporary directory to stage a dataset archive in.
+    #[error("Failed to create temporary directory")]
+    TempDirError { source: std::io::Error },
+    /// Failed to copy a single-file dataset to the staging directory.
+    #[error("Failed to copy dataset file '{}' to '{}'", path.display(), target.display())]
+    DataFileCopyError { path: PathBuf, target: PathBuf, source: std::io::Error },
+    /// Failed to archive a dataset.
+    #[error("Failed to archive dataset '{}' to '{}'", dir.display(), path.display())]
+    ArchiveError { dir: PathBuf, path: PathBuf, source: brane_shr::fs::Error },
+    /// Failed to unarchive a dataset.
+    #[error("Failed to extract dataset archive '{}' to '{}'", path.display(), dir.display())]
+    UnarchiveError { path: PathBuf, dir: PathBuf, source: brane_shr::fs::Error },
+    /// Failed to read the DataInfo in a dataset archive.
+    #[error("Failed to read DataInfo file '{}' (is this an archive created with 'brane data export'?)", path.display())]
+    DataInfoReadError { path: PathBuf, source: specifications::data::DataInfoError },
+    /// The DataInfo in a dataset archive does not point to the files in it.
+    #[error("Dataset archive '{}' does not contain the files of dataset '{name}' where its 'data.yml' says they are", path.display())]
+    IllegalArchiveAccess { path: PathBuf, name: String },
+    /// A dataset archive contains a symbolic link.
+    #[error("Dataset archive '{}' contains symbolic link '{}', which is not allowed", path.display(), link.display())]
+    ArchiveSymlink { path: PathBuf, link: PathBuf },
+    /// Failed to inspect the extracted contents of a dataset archive.
+    #[error("Failed to inspect extracted archive entry '{}'", path.display())]
+    ArchiveEntryError { path: PathBuf, source: std::io::Error },
+}
+
+/// Collects errors relating to the doctor command.
+#[derive(Debug, thiserror::Error)]
+pub enum DoctorError {
+    /// One or more checks failed.
+    #[error("{failed} environment check(s) failed; see the hints above on how to fix them")]
+    ChecksFailed { failed: usize },
 }
 
 /// Collects errors during the import subcommand
@@ -579,6 +962,9 @@ pub enum InstanceError {
          '--unchecked')"
     )]
     RequestError { address: String, source: reqwest::Error },
+    /// Failed to build the HTTP client to reach the instance with.
+    #[error("Failed to build HTTP client")]
+    HttpClientError { source: UtilError },
     /// The remote instance was not alive (at least, API/health was not)
     #[error("Remote instance at '{}' is not alive (returned {} ({}){})", address, code, code.canonical_reason().unwrap_or("???"), if let Some(err) = err { format!("\n\nResponse:\n{}\n", BlockFormatter::new(err)) } else { String::new() })]
     InstanceNotAliveError { address: String, code: StatusCode, err: Option<String> },
@@ -601,8 +987,8 @@ pub enum InstanceError {
     ActiveInstanceTargetError { path: PathBuf, source: std::io::Error },
 
     /// The given instance is unknown to us.
-    #[error("Unknown instance '{name}'")]
-    UnknownInstance { name: String },
+    #[error("Unknown instance '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownInstance { name: String, suggestions: Vec<String> },
     /// The given instance exists but is not a directory.
     #[error("Instance directory '{}' exists but is not a directory", path.display())]
     InstanceNotADirError { path: PathBuf },
@@ -625,6 +1011,58 @@ pub enum InstanceError {
     /// No instance is active
     #[error("No active instance is set (run 'brane instance select' first)")]
     NoActiveInstance,
+
+    /// Failed to serialize the status report.
+    #[error("Failed to serialize instance status report as JSON")]
+    StatusSerializeError { source: serde_json::Error },
+    /// One of the critical components of an instance is down.
+    #[error("Instance '{name}' is not healthy (its API and/or driver is down)")]
+    InstanceUnhealthy { name: String },
+}
+impl InstanceError {
+    /// Returns whether this error means that an instance (or the active one) could not be loaded, i.e., that it is missing or misconfigured.
+    pub fn is_load_error(&self) -> bool {
+        matches!(
+            self,
+            Self::NoActiveInstance
+                | Self::UnknownInstance { .. }
+                | Self::InstanceNotADirError { .. }
+                | Self::ActiveInstanceNotAFileError { .. }
+                | Self::ActiveInstanceReadError { .. }
+                | Self::ActiveInstanceTargetError { .. }
+                | Self::InstanceInfoOpenError { .. }
+                | Self::InstanceInfoReadError { .. }
+                | Self::InstanceInfoParseError { .. }
+        )
+    }
+}
+
+/// Defines errors that occur when linting a workflow.
+#[derive(Debug, thiserror::Error)]
+pub enum LintError {
+    /// Failed to compile the workflow to lint.
+    #[error("Failed to compile workflow to lint")]
+    Compile { source: CompileError },
+    /// The workflow had warnings and we were asked to fail on them.
+    #[error("Workflow '{input}' has {count} lint warning(s) (see output above)")]
+    Warnings { input: String, count: usize },
+}
+
+/// Defines errors that occur when binding inputs to a workflow's parameters.
+#[derive(Debug, thiserror::Error)]
+pub enum InputError {
+    /// An input was not given as `NAME=VALUE`.
+    #[error("Illegal input '{raw}': expected NAME=VALUE")]
+    IllegalInput { raw: String },
+    /// An input was given for a parameter the workflow does not have.
+    #[error("Unknown workflow parameter '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownInput { name: String, suggestions: Vec<String> },
+    /// The value of an input could not be coerced to the type of its parameter.
+    #[error("Illegal value '{raw}' for parameter '{name}' from {origin}: expected a value of type {expected}")]
+    IllegalValue { name: String, origin: String, expected: brane_dsl::DataType, raw: String },
+    /// Inputs were given for a language that does not support them.
+    #[error("Binding inputs is not supported for {language} workflows")]
+    UnsupportedLanguage { language: brane_dsl::Language },
 }
 
 /// Lists the errors that can occur when trying to do stuff with packages
@@ -648,9 +1086,18 @@ pub enum PackageError {
     /// Failed to ask for the user's consent
     #[error("Failed to ask for your consent")]
     ConsentError { source: dialoguer::Error },
+    /// A package was given with a specific version together with a version constraint.
+    #[error("Cannot give a version for package '{name}' ({version}) when removing with '--constraint'; give it as 'NAME' only")]
+    ConstraintWithVersion { name: String, version: Version },
+    /// A version constraint matched every version of a package, which we only remove when forced
+    #[error("Constraint '{constraint}' matches every version of package '{name}'; give '--force' to remove them all anyway")]
+    ConstraintMatchesAll { name: String, constraint: VersionConstraint },
     /// Failed to remove a package directory
     #[error("Failed to remove package '{}' (version {}) at '{}'", name, version, dir.display())]
     PackageRemoveError { name: String, version: Version, dir: PathBuf, source: std::io::Error },
+    /// Failed to remove a broken entry from the packages directory
+    #[error("Failed to remove broken package index entry '{}'", path.display())]
+    BrokenEntryRemoveError { path: PathBuf, source: std::io::Error },
     /// Failed to get the versions of a package
     #[error("Failed to get versions of package '{}' (at '{}')", name, dir.display())]
     VersionsError { name: String, dir: PathBuf, source: std::io::Error },
@@ -666,6 +1113,57 @@ pub enum PackageError {
     /// Could not remove the given image from the Docker daemon
     #[error("Failed to remove image '{}' from the local Docker daemon", image.digest().unwrap_or("<no digest given>"))]
     DockerRemoveError { image: Box<Image>, source: brane_tsk::errors::DockerError },
+    /// Failed to acquire the lock on a package directory
+    #[error("Failed to lock package '{name}' (version {version})")]
+    PackageLockError { name: String, version: Version, source: brane_shr::fs::Error },
+    /// Failed to get the locally installed versions of a package
+    #[error("Failed to get the locally installed versions of package '{name}'")]
+    LocalVersionsError { name: String, source: brane_tsk::local::Error },
+    /// Refused to load an older version of a package over a newer one
+    #[error(
+        "Refusing to load version {version} of package '{name}' because newer version {newer} is already loaded in the local Docker daemon (use \
+         '--allow-downgrade' to load it anyway)"
+    )]
+    DowngradeError { name: String, version: Version, newer: Version },
+    /// Failed to create a temporary directory to stage a package archive in.
+    #[error("Failed to create temporary directory")]
+    TempDirError { source: std::io::Error },
+    /// Failed to copy a package directory to the staging directory.
+    #[error("Failed to copy package directory '{}' to '{}'", source_dir.display(), target.display())]
+    PackageCopyError { source_dir: PathBuf, target: PathBuf, source: brane_shr::fs::Error },
+    /// Failed to remove a file from the staging directory.
+    #[error("Failed to remove staged file '{}'", path.display())]
+    StagedFileRemoveError { path: PathBuf, source: std::io::Error },
+    /// Failed to generate or verify the checksum manifest of a package.
+    #[error("Failed to {what} the checksum manifest of package directory '{}'", dir.display())]
+    ChecksumError { what: &'static str, dir: PathBuf, source: brane_tsk::errors::ChecksumError },
+    /// Failed to archive a package.
+    #[error("Failed to archive package '{}' to '{}'", dir.display(), path.display())]
+    ArchiveError { dir: PathBuf, path: PathBuf, source: brane_shr::fs::Error },
+    /// Failed to unarchive a package.
+    #[error("Failed to extract package archive '{}' to '{}'", path.display(), dir.display())]
+    UnarchiveError { path: PathBuf, dir: PathBuf, source: brane_shr::fs::Error },
+    /// The package in an archive is already installed locally.
+    #[error("Package '{name}' (version {version}) is already installed locally (remove it first to import it from an archive)")]
+    PackageExistsError { name: String, version: Version },
+    /// Attempted to tag a package with an unresolved version.
+    #[error("Cannot tag package '{name}' as version 'latest'; give a concrete version instead")]
+    TagLatestError { name: String },
+    /// The version to tag a package with already exists.
+    #[error("Package '{name}' already has a version {version}")]
+    TagExistsError { name: String, version: Version },
+    /// Failed to alias a file of one package version in another.
+    #[error("Failed to link or copy '{}' to '{}'", original.display(), target.display())]
+    TagFileError { original: PathBuf, target: PathBuf, source: std::io::Error },
+    /// Failed to read the files of a package version.
+    #[error("Failed to read package directory '{}'", dir.display())]
+    PackageDirReadError { dir: PathBuf, source: std::io::Error },
+    /// Failed to write the package info of a new package version.
+    #[error("Failed to write package info to '{}'", path.display())]
+    PackageInfoWriteError { path: PathBuf, source: specifications::package::PackageInfoError },
+    /// Failed to tag the image of a package with a new version.
+    #[error("Failed to tag image '{}' in the local Docker daemon", image.name())]
+    DockerTagError { image: Box<Image>, source: brane_tsk::errors::DockerError },
 }
 
 /// Collects errors during the registry subcommands
@@ -674,6 +1172,9 @@ pub enum RegistryError {
     /// Wrapper error indeed.
     #[error(transparent)]
     InstanceInfoError { source: InstanceError },
+    /// Failed to build the HTTP client to reach the instance with.
+    #[error("Failed to build HTTP client")]
+    HttpClientError { source: UtilError },
 
     /// Failed to successfully send the package pull request
     #[error("Could not send the request to pull pacakge to '{url}'")]
@@ -681,9 +1182,6 @@ pub enum RegistryError {
     /// The request was sent successfully, but the server replied with a non-200 access code
     #[error("Request to pull package from '{}' was met with status code {} ({})", url, status.as_u16(), status.canonical_reason().unwrap_or("???"))]
     PullRequestFailure { url: String, status: reqwest::StatusCode },
-    /// The request did not have a content length specified
-    #[error("Response from '{url}' did not have 'Content-Length' header set")]
-    MissingContentLength { url: String },
     /// Failed to convert the content length from raw bytes to string
     #[error("Could not convert content length received from '{url}' to string")]
     ContentLengthStrError { url: String, source: reqwest::header::ToStrError },
@@ -696,12 +1194,51 @@ pub enum RegistryError {
     /// Failed to write the downloaded package to the given file
     #[error("Could not write package downloaded from '{}' to '{}'", url, path.display())]
     PackageWriteError { url: String, path: PathBuf, source: std::io::Error },
+    /// A checksum was given, but for more than one package
+    #[error("A checksum can only be given when pulling a single package (got {count} packages)")]
+    ChecksumMultiplePackages { count: usize },
+    /// The downloaded package did not match the given checksum
+    #[error("Checksum of package downloaded from '{url}' does not match: expected {expected}, got {got}")]
+    ChecksumMismatch { url: String, expected: Checksum, got: Checksum },
+    /// Failed to compute the checksum of the downloaded package
+    #[error("Could not compute checksum of package downloaded from '{}' to '{}'", url, path.display())]
+    ChecksumComputeError { url: String, path: PathBuf, source: std::io::Error },
+    /// The disk ran full while writing something.
+    #[error(transparent)]
+    OutOfSpace {
+        #[from]
+        source: OutOfSpaceError,
+    },
+    /// The download was refused because of its size.
+    #[error(transparent)]
+    DownloadLimit {
+        #[from]
+        source: DownloadLimitError,
+    },
     /// Failed to create the package directory
     #[error("Could not create package directory '{}'", path.display())]
     PackageDirCreateError { path: PathBuf, source: std::io::Error },
+    /// Failed to acquire the lock on a package directory
+    #[error("Failed to lock package '{name}' (version {version})")]
+    PackageLockError { name: String, version: Version, source: brane_shr::fs::Error },
     /// Failed to copy the downloaded package over
     #[error("Could not copy package from '{}' to '{}'", original.display(), target.display())]
     PackageCopyError { original: PathBuf, target: PathBuf, source: std::io::Error },
+    /// Failed to write the marker that a package's image has not been pulled.
+    #[error("Could not write image absence marker '{}'", path.display())]
+    ImageAbsentWriteError { path: PathBuf, source: std::io::Error },
+    /// Failed to remove the marker that a package's image has not been pulled.
+    #[error("Could not remove image absence marker '{}'", path.display())]
+    ImageAbsentRemoveError { path: PathBuf, source: std::io::Error },
+    /// Failed to compute the digest of a pulled image.
+    #[error("Could not read digest of pulled image '{}'", path.display())]
+    ImageDigestError { path: PathBuf, source: brane_tsk::docker::Error },
+    /// A pulled image did not have the digest we expected.
+    #[error("Pulled image of package '{name}' (version {version}) has digest '{got}', but expected '{expected}'")]
+    ImageDigestMismatch { name: String, version: Version, expected: String, got: String },
+    /// Failed to move a pulled image into place.
+    #[error("Could not install pulled image as '{}'", path.display())]
+    ImageInstallError { path: PathBuf, source: std::io::Error },
     /// Failed to send GraphQL request for package info
     #[error("Could not send a GraphQL request to '{url}'")]
     GraphQLRequestError { url: String, source: reqwest::Error },
@@ -723,6 +1260,9 @@ pub enum RegistryError {
     /// Could not parse the types as proper PackageInfo types
     #[error("Could not parse '{raw}' (received from '{url}') as package types")]
     TypesParseError { url: String, raw: String, source: serde_json::Error },
+    /// Could not parse the labels as proper PackageInfo labels
+    #[error("Could not parse '{raw}' (received from '{url}') as package labels")]
+    LabelsParseError { url: String, raw: String, source: serde_json::Error },
     /// Could not create a file for the PackageInfo
     #[error("Could not create PackageInfo file '{}'", path.display())]
     PackageInfoCreateError { path: PathBuf, source: std::io::Error },
@@ -748,12 +1288,18 @@ pub enum RegistryError {
     /// Could not compress the package file
     #[error("Could not compress package '{}' (version {}) to '{}'", name, version, path.display())]
     CompressionError { name: String, version: Version, path: PathBuf, source: std::io::Error },
+    /// The registry advertised a compression scheme we don't know.
+    #[error("Could not parse compression scheme '{raw}' advertised by '{url}'")]
+    CompressionParseError { url: String, raw: String, source: specifications::package::PackageCompressionParseError },
     /// Failed to re-open the compressed package file
     #[error("Could not re-open compressed package archive '{}'", path.display())]
     PackageArchiveOpenError { path: PathBuf, source: std::io::Error },
     /// Failed to upload the compressed file to the instance
     #[error("Could not upload compressed package archive '{}' to '{}'", path.display(), endpoint)]
     UploadError { path: PathBuf, endpoint: String, source: reqwest::Error },
+    /// The instance refused the uploaded package
+    #[error("Failed to push package '{}' (version {}) to '{}': server responded with status code {} ({}){}", name, version, endpoint, status.as_u16(), status.canonical_reason().unwrap_or("???"), if let Some(text) = text { format!(": {text}") } else { String::new() })]
+    UploadFailure { name: String, version: Version, endpoint: String, status: reqwest::StatusCode, text: Option<String> },
 }
 
 /// Collects errors during the repl subcommand
@@ -808,6 +1354,12 @@ pub enum RunError {
     /// Failed to create a temporary intermediate results directory.
     #[error("Failed to create new temporary directory as an intermediate result directory")]
     ResultsDirCreateError { source: std::io::Error },
+    /// Failed to read the intermediate results directory.
+    #[error("Failed to read intermediate results directory '{}'", path.display())]
+    ResultsDirReadError { path: PathBuf, source: std::io::Error },
+    /// Failed to retain an intermediate result as a local dataset.
+    #[error("Failed to retain intermediate result '{name}' as dataset '{data_name}'")]
+    KeepIntermediateError { name: String, data_name: String, source: brane_tsk::errors::CommitError },
 
     /// Failed to fetch the login file.
     #[error(transparent)]
@@ -859,8 +1411,8 @@ pub enum RunError {
     ExecError { source: Box<dyn Error> },
 
     /// The returned dataset was unknown.
-    #[error("Unknown dataset '{name}'")]
-    UnknownDataset { name: String },
+    #[error("Unknown dataset '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownDataset { name: String, suggestions: Vec<String> },
     /// The returend dataset was known but not available locally.
     #[error("Unavailable dataset '{}'{}", name, if !locs.is_empty() { format!("; it is available at {}", PrettyListFormatter::new(locs.iter().map(|l| format!("'{l}'")), "or")) } else { String::new() })]
     UnavailableDataset { name: String, locs: Vec<String> },
@@ -874,9 +1426,25 @@ pub enum RunError {
     /// Failed to read the source from a given file
     #[error("Failed to read source from file '{}'", path.display())]
     FileReadError { path: PathBuf, source: std::io::Error },
+    /// Failed to bind the given inputs to the workflow's parameters.
+    #[error("Failed to bind inputs to workflow '{input}'")]
+    InputError { input: String, source: InputError },
     /// Failed to load the login file.
     #[error(transparent)]
     LoginFileError { source: UtilError },
+
+    /// Failed to serialize the run summary.
+    #[error("Failed to serialize run summary")]
+    SummarySerializeError { source: serde_json::Error },
+    /// Failed to write the run summary to a file.
+    #[error("Failed to write run summary to '{}'", path.display())]
+    SummaryWriteError { path: PathBuf, source: std::io::Error },
+    /// Failed to serialize the profile timings.
+    #[error("Failed to serialize profile timings")]
+    ProfileSerializeError { source: serde_json::Error },
+    /// Failed to write the profile timings to a file.
+    #[error("Failed to write profile timings to '{}'", path.display())]
+    ProfileWriteError { path: PathBuf, source: std::io::Error },
 }
 
 /// Collects errors during the test subcommand.
@@ -896,8 +1464,8 @@ pub enum TestError {
     #[error("Dataset '{}' is unavailable{}", name, if !locs.is_empty() { format!( "; however, locations {} do (try to get download permission to those datasets)", locs.iter().map(|l| format!("'{l}'")).collect::<Vec<String>>().join(", ")) } else { String::new() })]
     DatasetUnavailable { name: String, locs: Vec<String> },
     /// The given dataset was unknown to us.
-    #[error("Unknown dataset '{name}'")]
-    UnknownDataset { name: String },
+    #[error("Unknown dataset '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownDataset { name: String, suggestions: Vec<String> },
     /// Failed to get the general package directory.
     #[error("Failed to get packages directory")]
     PackagesDirError { source: UtilError },
@@ -946,6 +1514,9 @@ pub enum VersionError {
     /// Could not open the login file
     #[error(transparent)]
     InstanceInfoError { source: InstanceError },
+    /// Failed to build the HTTP client to reach the instance with.
+    #[error("Failed to build HTTP client")]
+    HttpClientError { source: UtilError },
     /// Could not perform the request
     #[error("Could not perform request to '{url}'")]
     RequestError { url: String, source: reqwest::Error },
@@ -963,9 +1534,15 @@ pub enum UtilError {
     /// Could not connect to the local Docker instance
     #[error("Could not connect to local Docker instance")]
     DockerConnectionFailed { source: bollard::errors::Error },
+    /// Could not connect to the Docker instance with the given options
+    #[error("Could not connect to Docker instance")]
+    DockerConnectError { source: brane_tsk::docker::Error },
     /// Could not get the version of the Docker daemon
     #[error("Could not get version of the local Docker instance")]
     DockerVersionError { source: bollard::errors::Error },
+    /// The Docker daemon accepted our connection but did not answer in time
+    #[error("Docker daemon not responding (no answer within {} seconds)", timeout.as_secs())]
+    DockerNotResponding { timeout: std::time::Duration },
     /// The docker daemon returned something, but not the version
     #[error("Local Docker instance doesn't report a version number")]
     DockerNoVersion,
@@ -1080,9 +1657,16 @@ pub enum UtilError {
     #[error("Brane instance directory '{}' for instance '{}' not found", path.display(), name)]
     BraneInstanceDirNotFound { path: PathBuf, name: String },
 
+    /// The given name cannot be used as the name of a local package or dataset.
+    #[error("The given name '{name}' is not a valid name; it must be a single, non-empty path component without separators")]
+    InvalidEntryName { name: String },
     /// The given name is not a valid bakery name.
     #[error("The given name '{name}' is not a valid name; expected alphanumeric or underscore characters")]
     InvalidBakeryName { name: String },
+
+    /// Failed to build the HTTP client used to talk to instances.
+    #[error("Failed to build HTTP client")]
+    HttpClientBuildError { source: reqwest::Error },
 }
 
 /// Defines errors that relate to finding our directories.
@@ -1107,6 +1691,114 @@ pub enum HostnameParseError {
     HostnameContainsPath { raw: String },
 }
 
+/// Defines errors that occur when parsing ages (e.g., `7d`).
+#[derive(Debug, thiserror::Error)]
+pub enum AgeParseError {
+    /// The age was empty.
+    #[error("Age cannot be empty (expected a number followed by 'd', 'h' or 'm')")]
+    Empty,
+    /// The age had an unknown unit.
+    #[error("Unknown unit '{unit}' in age '{raw}' (expected 'd', 'h' or 'm')")]
+    UnknownUnit { raw: String, unit: char },
+    /// The number in the age was not a valid number.
+    #[error("Failed to parse '{raw}' as a non-negative number")]
+    NumberParseError { raw: String, source: std::num::ParseIntError },
+    /// The age does not fit in a duration.
+    #[error("Age '{raw}' is too large")]
+    Overflow { raw: String },
+}
+
+/// Defines errors that occur when parsing sizes in bytes (e.g., `10G`).
+#[derive(Debug, thiserror::Error)]
+pub enum ByteSizeParseError {
+    /// There was no number in the size.
+    #[error("Size '{raw}' has no number (expected a number optionally followed by 'K', 'M', 'G' or 'T')")]
+    Empty { raw: String },
+    /// The number in the size was not a valid number.
+    #[error("Failed to parse '{raw}' as a non-negative number optionally followed by 'K', 'M', 'G' or 'T'")]
+    NumberParseError { raw: String, source: std::num::ParseIntError },
+    /// The size did not fit in 64 bits.
+    #[error("Size '{raw}' is too large")]
+    Overflow { raw: String },
+}
+
+/// Defines errors that occur when parsing checksums (e.g., `sha256:<HEX>`).
+#[derive(Debug, thiserror::Error)]
+pub enum ChecksumParseError {
+    /// The checksum did not say which algorithm it used.
+    #[error("Missing algorithm in checksum '{raw}' (expected 'sha256:<HEX>')")]
+    MissingAlgorithm { raw: String },
+    /// The checksum used an algorithm we don't support.
+    #[error("Unsupported checksum algorithm '{raw}' (only 'sha256' is supported)")]
+    UnknownAlgorithm { raw: String },
+    /// The digest was not valid hexadecimal.
+    #[error("Failed to parse '{raw}' as a hexadecimal digest")]
+    DigestParseError { raw: String, source: hex::FromHexError },
+    /// The digest was not as long as a SHA-256 digest.
+    #[error("Checksum '{raw}' has a digest of {len} bytes, but a SHA-256 digest is 32 bytes")]
+    IllegalLength { raw: String, len: usize },
+}
+
+/// Defines errors that occur when parsing lint rule names.
+#[derive(Debug, thiserror::Error)]
+pub enum LintRuleParseError {
+    /// The rule was not one we know.
+    #[error("Unknown lint rule '{raw}' (expected {})", PrettyListFormatter::new(LintRule::ALL.iter().map(|rule| format!("'{rule}'")), "or"))]
+    Unknown { raw: String },
+}
+
+/// Defines errors that occur when parsing the kind of name to complete.
+#[derive(Debug, thiserror::Error)]
+pub enum CompletionKindParseError {
+    /// The kind was not one we know.
+    #[error("Unknown completion kind '{raw}' (expected {})", PrettyListFormatter::new(CompletionKind::ALL.iter().map(|kind| format!("'{kind}'")), "or"))]
+    Unknown { raw: String },
+}
+
+/// Defines errors that occur when parsing TLS versions.
+#[derive(Debug, thiserror::Error)]
+pub enum TlsVersionParseError {
+    /// The version was not one we know.
+    #[error("Unknown TLS version '{raw}' (expected '1.2' or '1.3')")]
+    Unknown { raw: String },
+}
+
+/// Defines errors that occur when parsing package labels.
+#[derive(Debug, thiserror::Error)]
+pub enum LabelParseError {
+    /// The label was not a key/value pair.
+    #[error("Missing '=' in key/value pair '{raw}'")]
+    MissingEquals { raw: String },
+    /// The key of the label was empty.
+    #[error("Key/value pair '{raw}' has an empty key")]
+    EmptyKey { raw: String },
+    /// The key of the label contained an illegal character.
+    #[error("Label key '{raw}' contains illegal character '{c}' (only alphanumerics, '.', '-', '_' and '/' are allowed)")]
+    IllegalKeyChar { raw: String, c: char },
+}
+
+/// Defines errors that occur when parsing build arguments.
+#[derive(Debug, thiserror::Error)]
+pub enum BuildArgParseError {
+    /// The build argument was not a valid key/value pair.
+    #[error(transparent)]
+    Pair { source: LabelParseError },
+    /// The key of the build argument contained an illegal character.
+    #[error("Build argument '{raw}' contains illegal character '{c}' (only alphanumerics and '_' are allowed)")]
+    IllegalKeyChar { raw: String, c: char },
+    /// The build argument is one that Brane sets itself.
+    #[error("Build argument '{key}' is set by Brane itself and cannot be overridden")]
+    Reserved { key: String },
+}
+
+/// Defines errors that occur when parsing entrypoint arguments.
+#[derive(Debug, thiserror::Error)]
+pub enum EntrypointArgsParseError {
+    /// The arguments could not be split like a shell would (e.g., an unterminated quote).
+    #[error("Failed to split entrypoint arguments '{raw}' (unterminated quote or trailing backslash?)")]
+    Unsplittable { raw: String },
+}
+
 /// Declares errors that relate to the offline VM.
 #[derive(Debug, thiserror::Error)]
 pub enum OfflineVmError {
@@ -1134,3 +1826,37 @@ pub enum DelegatesError {
     #[error("Failed to parse response body '{raw}' sent by '{address}' as a delegate map")]
     ResponseParseError { address: String, raw: String, source: serde_json::Error },
 }
+
+/// Wraps an I/O error that occurred because a disk ran full, naming where it happened and what to do about it.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Ran out of disk space (or disk quota) while writing '{}' (on the volume mounted at '{}'); free up some space there, or point TMPDIR \
+     (temporary files) or XDG_DATA_HOME (packages and datasets) to another volume",
+    path.display(),
+    volume.display()
+)]
+pub struct OutOfSpaceError {
+    /// The path that we failed to write.
+    pub path:   PathBuf,
+    /// The mount point of the volume that is full, as far as we could tell.
+    pub volume: PathBuf,
+    /// The original error.
+    pub source: std::io::Error,
+}
+
+/// Defines errors that occur when a download is refused because of its size (see `--max-download-size`).
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadLimitError {
+    /// The download is larger than allowed.
+    #[error("Refusing to download {size} from '{url}', as it exceeds the maximum download size of {max} (see '--max-download-size')")]
+    TooLarge { url: String, size: ByteSize, max: ByteSize },
+    /// The remote did not say how large the download is.
+    #[error(
+        "Refusing to download from '{url}', as it does not advertise its size and cannot be checked against the maximum download size of {max} \
+         (give '--allow-unknown-size' to download it anyway)"
+    )]
+    UnknownSize { url: String, max: ByteSize },
+    /// The download turned out to be larger than allowed while it was in progress.
+    #[error("Aborted download from '{url}', as it exceeded the maximum download size of {max} (see '--max-download-size')")]
+    Exceeded { url: String, max: ByteSize },
+}
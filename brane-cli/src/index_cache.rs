@@ -0,0 +1,263 @@
+//  INDEX CACHE.rs
+//
+//  Description:
+//!   Implements a small on-disk cache of the package and data indices of
+//!   an instance, such that running many workflows in a row against the
+//!   same instance does not fetch them every time.
+//!
+//!   The cache lives in the instance's directory and is keyed on the
+//!   instance's name and its API address, so that switching instances
+//!   (or re-pointing one) never reuses stale indices. It is best-effort:
+//!   any failure to read or write it is logged and treated as a miss.
+//!
+//!   Once the cache expires, the package index in it is revalidated with
+//!   the instance (by its `ETag`) instead of downloaded again. The data
+//!   index expires sooner, since datasets change on the instance without
+//!   the CLI knowing (e.g., when a workflow commits a result).
+//
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use specifications::data::DataIndex;
+use specifications::package::{PackageIndex, PackageInfo};
+
+use crate::instance::InstanceInfo;
+use crate::utils::{get_instance_dir, revalidate_package_index};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_index_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path: PathBuf = dir.path().join(INDEX_CACHE_FILE);
+        let now: DateTime<Utc> = Utc::now();
+        let pindex: PackageIndex = PackageIndex::empty();
+        let dindex: DataIndex = DataIndex::from_infos(vec![]).unwrap();
+
+        // Nothing cached yet
+        assert!(load_from(&path, "http://a:50051", now).is_none());
+
+        // Hits within the TTL for the same address only, with the data index expiring first
+        store_to(&path, "http://a:50051", now, &pindex, &dindex);
+        assert!(matches!(load_from(&path, "http://a:50051", now + chrono::Duration::seconds(9)), Some((_, Some(_)))));
+        assert!(matches!(load_from(&path, "http://a:50051", now + chrono::Duration::seconds(11)), Some((_, None))));
+        assert!(matches!(load_from(&path, "http://a:50051", now + chrono::Duration::seconds(59)), Some((_, None))));
+        assert!(load_from(&path, "http://b:50051", now).is_none());
+
+        // Expires after the TTL (and when it appears to be from the future)
+        assert!(load_from(&path, "http://a:50051", now + chrono::Duration::seconds(61)).is_none());
+        assert!(load_from(&path, "http://a:50051", now - chrono::Duration::seconds(1)).is_none());
+
+        // Expired indices can still be read for revalidation
+        assert!(read_from(&path, "http://a:50051").is_some());
+        assert!(read_from(&path, "http://b:50051").is_none());
+
+        // Garbage is a miss, and removing it is idempotent
+        fs::write(&path, "not json").unwrap();
+        assert!(load_from(&path, "http://a:50051", now).is_none());
+        remove(&path);
+        remove(&path);
+        assert!(!path.exists());
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// How long cached indices remain valid.
+pub const INDEX_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How long the cached data index remains valid, which is shorter than [`INDEX_CACHE_TTL`] since the CLI can't tell when datasets change.
+pub const DATA_INDEX_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// The name of the cache file in an instance's directory.
+pub const INDEX_CACHE_FILE: &str = "index-cache.json";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// The contents of the cache file.
+#[derive(Deserialize, Serialize)]
+struct CachedIndices {
+    /// The API address the indices were fetched from.
+    api:      String,
+    /// When the indices were fetched.
+    fetched:  DateTime<Utc>,
+    /// The packages in the package index.
+    packages: Vec<PackageInfo>,
+    /// The data index.
+    data:     DataIndex,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reads the indices from the given cache file if they were fetched from the given address, regardless of their age.
+///
+/// Returns the indices together with when they were fetched.
+fn read_from(path: &Path, api: &str) -> Option<(PackageIndex, DataIndex, DateTime<Utc>)> {
+    let raw: String = fs::read_to_string(path).ok()?;
+    let cached: CachedIndices = match serde_json::from_str(&raw) {
+        Ok(cached) => cached,
+        Err(err) => {
+            debug!("Ignoring malformed index cache '{}': {}", path.display(), err);
+            return None;
+        },
+    };
+    if cached.api != api {
+        debug!("Ignoring index cache '{}' for other API address '{}'", path.display(), cached.api);
+        return None;
+    }
+    match PackageIndex::from_packages(cached.packages) {
+        Ok(pindex) => Some((pindex, cached.data, cached.fetched)),
+        Err(err) => {
+            debug!("Ignoring index cache '{}' with invalid package index: {}", path.display(), err);
+            None
+        },
+    }
+}
+
+/// Reads the indices from the given cache file if they were fetched from the given address less than [`INDEX_CACHE_TTL`] before `now`.
+///
+/// The data index is only returned if it was fetched less than [`DATA_INDEX_CACHE_TTL`] before `now`.
+fn load_from(path: &Path, api: &str, now: DateTime<Utc>) -> Option<(PackageIndex, Option<DataIndex>)> {
+    let (pindex, dindex, fetched): (PackageIndex, DataIndex, DateTime<Utc>) = read_from(path, api)?;
+    let age: Duration = (now - fetched).to_std().ok()?;
+    if age >= INDEX_CACHE_TTL {
+        debug!("Ignoring index cache '{}' of {}s old", path.display(), age.as_secs());
+        return None;
+    }
+    Some((pindex, if age < DATA_INDEX_CACHE_TTL { Some(dindex) } else { None }))
+}
+
+/// Writes the given indices as fetched from the given address at `now` to the given cache file.
+fn store_to(path: &Path, api: &str, now: DateTime<Utc>, pindex: &PackageIndex, dindex: &DataIndex) {
+    let cached =
+        CachedIndices { api: api.into(), fetched: now, packages: pindex.packages.values().cloned().collect(), data: dindex.clone() };
+    let raw: String = match serde_json::to_string(&cached) {
+        Ok(raw) => raw,
+        Err(err) => {
+            debug!("Failed to serialize index cache: {err}");
+            return;
+        },
+    };
+    if let Err(err) = fs::write(path, raw) {
+        debug!("Failed to write index cache '{}': {}", path.display(), err);
+    }
+}
+
+/// Removes the given cache file, if any.
+fn remove(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(_) => {},
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+        Err(err) => debug!("Failed to remove index cache '{}': {}", path.display(), err),
+    }
+}
+
+/// Returns the path of the cache file of the given instance, if we can find it.
+fn cache_path(instance: &str) -> Option<PathBuf> {
+    match get_instance_dir(instance) {
+        Ok(dir) => Some(dir.join(INDEX_CACHE_FILE)),
+        Err(err) => {
+            debug!("Failed to get directory of instance '{instance}'; not caching indices: {err}");
+            None
+        },
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Returns the cached indices of the given instance if they were fetched from the given API address less than [`INDEX_CACHE_TTL`] ago.
+///
+/// # Arguments
+/// - `instance`: The name of the instance whose indices to return.
+/// - `api`: The address of the instance's `brane-api` service.
+///
+/// # Returns
+/// The cached package and data index, or [`None`] if there are none (or they are stale, or we failed to read them). The data index is
+/// [`None`] on its own if it is older than [`DATA_INDEX_CACHE_TTL`]; it should then be fetched again, but not cached, such that the package
+/// index still expires in time.
+pub fn load(instance: &str, api: &str) -> Option<(PackageIndex, Option<DataIndex>)> {
+    let res = load_from(&cache_path(instance)?, api, Utc::now());
+    if res.is_some() {
+        debug!("Using cached indices of instance '{instance}'");
+    }
+    res
+}
+
+/// Revalidates the cached package index of the given instance against its API, regardless of its age.
+///
+/// This is cheaper than downloading the index again, as the API only sends it back if it has changed (see
+/// [`revalidate_package_index()`]).
+///
+/// # Arguments
+/// - `instance`: The name of the instance whose package index to revalidate.
+/// - `api`: The address of the instance's `brane-api` service.
+///
+/// # Returns
+/// The current package index, or [`None`] if there is no cached one or we failed to revalidate it. In that case, it should be downloaded as
+/// usual.
+pub async fn revalidate(instance: &str, api: &str) -> Option<PackageIndex> {
+    let (cached, _, _): (PackageIndex, DataIndex, DateTime<Utc>) = read_from(&cache_path(instance)?, api)?;
+    match revalidate_package_index(format!("{api}/packages"), &cached).await {
+        Ok(None) => {
+            debug!("Cached package index of instance '{instance}' is still current");
+            Some(cached)
+        },
+        Ok(Some(pindex)) => {
+            debug!("Cached package index of instance '{instance}' has changed");
+            Some(pindex)
+        },
+        Err(err) => {
+            debug!("Failed to revalidate cached package index of instance '{instance}': {err}");
+            None
+        },
+    }
+}
+
+/// Caches the given indices of the given instance.
+///
+/// # Arguments
+/// - `instance`: The name of the instance whose indices to cache.
+/// - `api`: The address of the `brane-api` service the indices were fetched from.
+/// - `pindex`: The package index to cache.
+/// - `dindex`: The data index to cache.
+pub fn store(instance: &str, api: &str, pindex: &PackageIndex, dindex: &DataIndex) {
+    if let Some(path) = cache_path(instance) {
+        store_to(&path, api, Utc::now(), pindex, dindex);
+    }
+}
+
+/// Drops the cached indices of the active instance, if any.
+///
+/// Used after changing the packages or datasets of the instance (e.g., pushing or unpublishing a package) so the next run sees the change.
+pub fn invalidate_active() {
+    match InstanceInfo::get_active_name() {
+        Ok(instance) => {
+            if let Some(path) = cache_path(&instance) {
+                remove(&path);
+            }
+        },
+        Err(err) => debug!("Failed to get active instance; not invalidating index cache: {err}"),
+    }
+}
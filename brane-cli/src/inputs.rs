@@ -0,0 +1,316 @@
+//  INPUTS.rs
+//
+//  Description:
+//!   Implements binding inputs to the top-level parameters of a workflow
+//!   before it is run, either from `--input`-flags or from environment
+//!   variables.
+//!
+//!   BraneScript has no dedicated syntax for parameters. Instead, any
+//!   top-level `let` that assigns a boolean, integer, real or string
+//!   literal is considered a parameter with that literal as its default,
+//!   e.g., `let epochs := 10;`. Binding an input replaces that literal
+//!   (and only that, so anything else on its line is kept). The type of
+//!   a parameter is inferred from its default literal.
+//
+
+use std::collections::HashMap;
+
+use brane_dsl::ast::{Expr, Literal, Stmt};
+use brane_dsl::{DataType, Language, ParserOptions, TextPos};
+use brane_shr::utilities::closest_names;
+use log::{debug, warn};
+use specifications::package::PackageIndex;
+
+pub use crate::errors::InputError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Binds the given flags and environment to the given BraneScript snippet.
+    fn bind_bscript(source: &str, inputs: &[&str], env: &[(&str, &str)]) -> Result<String, Error> {
+        bind_from(
+            source,
+            Language::BraneScript,
+            inputs.iter().map(|i| i.to_string()).collect(),
+            DEFAULT_INPUT_ENV_PREFIX,
+            env.iter().map(|(k, v)| (k.to_string(), v.to_string())),
+        )
+    }
+
+    #[test]
+    fn test_bind_flag() {
+        let source: &str = "let epochs := 10;\n    let name := \"a\";\nprintln(name);\n";
+        assert_eq!(bind_bscript(source, &[], &[]).unwrap(), source);
+        assert_eq!(
+            bind_bscript(source, &["epochs=3", "name=x \"y\""], &[]).unwrap(),
+            "let epochs := 3;\n    let name := \"x \\\"y\\\"\";\nprintln(name);\n"
+        );
+
+        // Only the literal is replaced, keeping comments and other statements on its line
+        let source: &str = "let epochs := 10; // The number of epochs\nlet name := \"a;b\\\"\"; println(name);\n";
+        assert_eq!(
+            bind_bscript(source, &["epochs=3", "name=c"], &[]).unwrap(),
+            "let epochs := 3; // The number of epochs\nlet name := \"c\"; println(name);\n"
+        );
+    }
+
+    #[test]
+    fn test_bind_env() {
+        let source: &str = "let rate := 0.5;\nlet verbose := false;\n";
+        let env: [(&str, &str); 3] = [("BRANE_INPUT_RATE", "2"), ("BRANE_INPUT_verbose", "true"), ("HOME", "/root")];
+        assert_eq!(bind_bscript(source, &[], &env).unwrap(), "let rate := 2.0;\nlet verbose := true;\n");
+        // Flags take precedence over the environment
+        assert_eq!(bind_bscript(source, &["rate=1.25"], &env).unwrap(), "let rate := 1.25;\nlet verbose := true;\n");
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        let source: &str = "let epochs := 10; println(epochs);\nlet name := \"a\";\n";
+        assert!(matches!(bind_bscript(source, &["name"], &[]), Err(Error::IllegalInput { .. })));
+        assert!(matches!(bind_bscript(source, &["nam=b"], &[]), Err(Error::UnknownInput { suggestions, .. }) if suggestions == vec!["name"]));
+        assert!(matches!(
+            bind_bscript("let epochs := 10;\n", &[], &[("BRANE_INPUT_EPOCHS", "ten")]),
+            Err(Error::IllegalValue { expected: DataType::Integer, .. })
+        ));
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The prefix of the environment variables read as workflow inputs if none is given.
+pub const DEFAULT_INPUT_ENV_PREFIX: &str = "BRANE_INPUT_";
+
+
+
+
+
+/***** HELPER STRUCTS *****/
+/// Describes a single parameter of a workflow.
+#[derive(Clone, Debug)]
+struct Parameter {
+    /// The name of the parameter.
+    name:      String,
+    /// The type of the parameter, as given by its default value.
+    data_type: DataType,
+    /// The byte range (start inclusive, end exclusive) of the default value in the source text.
+    literal:   (usize, usize),
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Finds the default literal of the `let`-statement at the given position in the source text.
+///
+/// # Arguments
+/// - `source`: The source text of the workflow.
+/// - `pos`: The (one-indexed) line and (byte) column at which the `let`-statement starts.
+///
+/// # Returns
+/// The byte range (start inclusive, end exclusive) of the literal, or [`None`] if there is no `let`-statement at that position.
+fn literal_span(source: &str, pos: &TextPos) -> Option<(usize, usize)> {
+    // Find the start of the statement, then of the value after its ':='
+    let line: usize = source.split_inclusive('\n').take(pos.line.checked_sub(1)?).map(str::len).sum();
+    let start: usize = line + pos.col.checked_sub(1)?;
+    if !source.get(start..)?.starts_with("let") {
+        return None;
+    }
+    let assign: usize = start + source[start..].find(":=")? + 2;
+    let value: usize = source.len() - source[assign..].trim_start().len();
+
+    // Then find where the literal ends, taking care of quotes and escapes in strings
+    let rest: &str = &source[value..];
+    let len: usize = if let Some(string) = rest.strip_prefix('"') {
+        let mut escaped: bool = false;
+        let (end, _) = string.char_indices().find(|(_, c)| {
+            let end: bool = !escaped && *c == '"';
+            escaped = !escaped && *c == '\\';
+            end
+        })?;
+        end + 2
+    } else {
+        rest.find(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'))).unwrap_or(rest.len())
+    };
+    Some((value, value + len))
+}
+
+/// Finds the parameters of the given workflow.
+///
+/// # Arguments
+/// - `source`: The source text of the workflow.
+/// - `language`: The language in which the workflow is written.
+///
+/// # Returns
+/// The parameters of the workflow, or [`None`] if it did not parse. We leave reporting that to the compiler.
+fn parameters(source: &str, language: Language) -> Option<Vec<Parameter>> {
+    let program = match brane_dsl::parse(source, &PackageIndex::empty(), &ParserOptions::new(language)) {
+        Ok(program) => program,
+        Err(err) => {
+            debug!("Failed to parse workflow to find its parameters: {err}");
+            return None;
+        },
+    };
+
+    Some(
+        program
+            .block
+            .stmts
+            .iter()
+            .filter_map(|stmt| {
+                let Stmt::LetAssign { name, value: Expr::Literal { literal }, range, .. } = stmt else { return None };
+                if !matches!(literal, Literal::Boolean { .. } | Literal::Integer { .. } | Literal::Real { .. } | Literal::String { .. }) {
+                    return None;
+                }
+
+                let Some(span) = literal_span(source, &range.start) else {
+                    debug!("Failed to find the default value of parameter '{}' in the workflow; not binding it", name.value);
+                    return None;
+                };
+                Some(Parameter { name: name.value.clone(), data_type: literal.data_type(), literal: span })
+            })
+            .collect(),
+    )
+}
+
+/// Coerces a raw input to a BraneScript literal of the parameter's type.
+///
+/// # Arguments
+/// - `param`: The parameter to coerce the value for.
+/// - `origin`: Where the value came from, for error messages.
+/// - `raw`: The value to coerce.
+///
+/// # Errors
+/// This function errors if the value is not a valid value of the parameter's type.
+fn coerce(param: &Parameter, origin: &str, raw: &str) -> Result<String, Error> {
+    let illegal =
+        || Error::IllegalValue { name: param.name.clone(), origin: origin.into(), expected: param.data_type.clone(), raw: raw.into() };
+    match param.data_type {
+        DataType::Boolean => raw.trim().parse::<bool>().map(|value| value.to_string()).map_err(|_| illegal()),
+        DataType::Integer => raw.trim().parse::<i64>().map(|value| value.to_string()).map_err(|_| illegal()),
+        DataType::Real => {
+            let value: f64 = raw.trim().parse().map_err(|_| illegal())?;
+            if !value.is_finite() {
+                return Err(illegal());
+            }
+            // Always write a real as such, or it would be parsed as an integer
+            let value: String = value.to_string();
+            Ok(if value.contains('.') { value } else { format!("{value}.0") })
+        },
+        DataType::String => {
+            let mut value: String = String::with_capacity(raw.len() + 2);
+            value.push('"');
+            for c in raw.chars() {
+                match c {
+                    '\\' => value.push_str("\\\\"),
+                    '"' => value.push_str("\\\""),
+                    '\n' => value.push_str("\\n"),
+                    '\t' => value.push_str("\\t"),
+                    '\r' => value.push_str("\\r"),
+                    c => value.push(c),
+                }
+            }
+            value.push('"');
+            Ok(value)
+        },
+        _ => unreachable!("Only parameters of primitive types are collected"),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Binds inputs to the top-level parameters of the given workflow.
+///
+/// Inputs are read from the environment variables starting with `env_prefix` followed by the
+/// parameter's name (either as-is or in uppercase), and from the given `NAME=VALUE` inputs. The
+/// latter take precedence.
+///
+/// # Arguments
+/// - `source`: The source text of the workflow.
+/// - `language`: The language in which the workflow is written.
+/// - `inputs`: The inputs given on the command line, as `NAME=VALUE`.
+/// - `env_prefix`: The prefix of environment variables that are read as inputs.
+///
+/// # Returns
+/// The source text with the defaults of the bound parameters replaced by the given values. Line
+/// numbers are preserved, so any compile errors still point to the right place.
+///
+/// Note that the type of a parameter is inferred from its default value, and that the values are
+/// coerced to that type.
+///
+/// # Errors
+/// This function errors if an input names an unknown parameter or has a value that cannot be
+/// coerced to the parameter's type.
+#[inline]
+pub fn bind(source: &str, language: Language, inputs: Vec<String>, env_prefix: &str) -> Result<String, Error> {
+    bind_from(source, language, inputs, env_prefix, std::env::vars())
+}
+
+/// Binds inputs to the top-level parameters of the given workflow, reading environment variables
+/// from the given iterator instead of the environment.
+///
+/// See [`bind()`] for more information.
+pub fn bind_from(
+    source: &str,
+    language: Language,
+    inputs: Vec<String>,
+    env_prefix: &str,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<String, Error> {
+    let env: Vec<(String, String)> =
+        if env_prefix.is_empty() { vec![] } else { env.into_iter().filter(|(key, _)| key.starts_with(env_prefix)).collect() };
+    if inputs.is_empty() && env.is_empty() {
+        return Ok(source.into());
+    }
+    if language != Language::BraneScript {
+        return Err(Error::UnsupportedLanguage { language });
+    }
+    let Some(params) = parameters(source, language) else { return Ok(source.into()) };
+
+    // Collect the values to bind, with the flags overriding the environment
+    let mut values: HashMap<usize, String> = HashMap::new();
+    for (key, raw) in env {
+        let name: &str = &key[env_prefix.len()..];
+        match params.iter().position(|param| param.name == name || param.name.to_uppercase() == name) {
+            Some(i) => {
+                values.insert(i, coerce(&params[i], &format!("environment variable '{key}'"), &raw)?);
+            },
+            None => warn!("Ignoring environment variable '{key}': workflow has no parameter '{name}'"),
+        }
+    }
+    for input in inputs {
+        let Some((name, raw)) = input.split_once('=') else { return Err(Error::IllegalInput { raw: input }) };
+        match params.iter().position(|param| param.name == name) {
+            Some(i) => {
+                values.insert(i, coerce(&params[i], "'--input'", raw)?);
+            },
+            None => {
+                return Err(Error::UnknownInput { name: name.into(), suggestions: closest_names(name, params.iter().map(|param| &param.name)) });
+            },
+        }
+    }
+
+    // Replace the default values of the bound parameters
+    let mut bound: Vec<(&Parameter, String)> = values.into_iter().map(|(i, value)| (&params[i], value)).collect();
+    bound.sort_by_key(|(param, _)| param.literal.0);
+    let mut result: String = String::with_capacity(source.len());
+    let mut last: usize = 0;
+    for (param, value) in bound {
+        debug!("Binding parameter '{}' to {value}", param.name);
+        result.push_str(&source[last..param.literal.0]);
+        result.push_str(&value);
+        last = param.literal.1;
+    }
+    result.push_str(&source[last..]);
+    Ok(result)
+}
@@ -19,19 +19,35 @@ use std::fs::{self, DirEntry, File, ReadDir};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use brane_shr::formatters::PrettyListFormatter;
+use brane_shr::utilities::closest_names;
 use console::{Alignment, pad_str, style};
 use dialoguer::Confirm;
+use error_trace::ErrorTrace as _;
 use log::{debug, info, warn};
 use prettytable::Table;
 use prettytable::format::FormatBuilder;
 use serde::{Deserialize, Serialize};
 use specifications::address::Address;
+use specifications::data::DataIndex;
+use specifications::package::PackageIndex;
 
 pub use crate::errors::InstanceError as Error;
-use crate::spec::Hostname;
-use crate::utils::{ensure_instance_dir, ensure_instances_dir, get_active_instance_link, get_instance_dir};
+use crate::spec::{ByteSize, Hostname, TlsVersion};
+use crate::utils::{
+    connect_driver, ensure_instance_dir, ensure_instances_dir, get_active_instance_link, get_data_index, get_instance_dir, get_instance_names,
+    get_package_index, http_client,
+};
+
+
+/***** CONSTANTS *****/
+/// The time we give each component of an instance to respond in `brane instance status`.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
@@ -69,11 +85,75 @@ fn read_active_instance_link() -> Result<String, Error> {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct InstanceInfo {
     /// The place where we can find the API service for this instance.
-    pub api:  Address,
+    pub api:      Address,
     /// The place where we can find the driver service for this instance.
-    pub drv:  Address,
+    pub drv:      Address,
     /// A username to send with workflow requests as receiver of the final result.
-    pub user: String,
+    pub user:     String,
+    /// Default values for workflow-related flags when working with this instance.
+    #[serde(default, skip_serializing_if = "InstanceDefaults::is_empty")]
+    pub defaults: InstanceDefaults,
+}
+
+/// Defines default values for flags of `brane workflow run`, `brane workflow check` and `brane workflow repl` (and for the global
+/// `--min-tls-version` and the `--max-download-size` of `brane data download` and `brane package pull`) that are used when this instance is
+/// active.
+///
+/// Flags given explicitly on the command line always take precedence over these.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct InstanceDefaults {
+    /// The address to proxy data transfers through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_addr: Option<String>,
+    /// The use-case registry to use for downloading data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_case: Option<String>,
+    /// Whether to show profile times.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<bool>,
+    /// The minimum TLS version to negotiate with the instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_tls_version: Option<TlsVersion>,
+    /// The maximum size of a single dataset or package download.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_download_size: Option<ByteSize>,
+}
+impl InstanceDefaults {
+    /// Returns the defaults of the active instance.
+    ///
+    /// # Returns
+    /// The defaults of the active instance, or empty defaults if there is no active instance or we failed to read it (the latter is only logged, as
+    /// the commands that actually need the instance will complain about it themselves).
+    pub fn active() -> Self {
+        match InstanceInfo::active_instance_exists() {
+            Ok(true) => {},
+            Ok(false) => return Self::default(),
+            Err(err) => {
+                debug!("Failed to check for active instance; not using instance defaults: {err}");
+                return Self::default();
+            },
+        }
+        match InstanceInfo::from_active_path() {
+            Ok(info) => info.defaults,
+            Err(err) => {
+                debug!("Failed to read active instance; not using instance defaults: {err}");
+                Self::default()
+            },
+        }
+    }
+
+    /// Returns whether no defaults are set at all.
+    ///
+    /// # Returns
+    /// True if all defaults are unset, or false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.proxy_addr.is_none()
+            && self.use_case.is_none()
+            && self.profile.is_none()
+            && self.min_tls_version.is_none()
+            && self.max_download_size.is_none()
+    }
 }
 
 impl InstanceInfo {
@@ -329,8 +409,9 @@ pub async fn add(
 
         // Do a simple HTTP call to the health
         let health_addr: String = format!("{api}/health");
+        let client: reqwest::Client = http_client().map_err(|source| Error::HttpClientError { source })?;
         let res: reqwest::Response =
-            reqwest::get(&health_addr).await.map_err(|source| Error::RequestError { address: health_addr.clone(), source })?;
+            client.get(&health_addr).send().await.map_err(|source| Error::RequestError { address: health_addr.clone(), source })?;
 
         if !res.status().is_success() {
             return Err(Error::InstanceNotAliveError { address: health_addr, code: res.status(), err: res.text().await.ok() });
@@ -339,7 +420,7 @@ pub async fn add(
 
     // Create a new InstanceInfo
     debug!("Writing InstanceInfo...");
-    let info: InstanceInfo = InstanceInfo { api, drv, user };
+    let info: InstanceInfo = InstanceInfo { api, drv, user, defaults: InstanceDefaults::default() };
 
     // Write it to wherever it wants to be
     info.to_default_path(&name)?;
@@ -461,6 +542,9 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
     // Fetch the instances directory
     let instances_dir: PathBuf = ensure_instances_dir(true).map_err(|source| Error::InstancesDirError { source })?;
 
+    // Prepare the client for the health checks, if any
+    let client: Option<reqwest::Client> = if show_status { Some(http_client().map_err(|source| Error::HttpClientError { source })?) } else { None };
+
     // Fetch the active link, if any
     let active_name: Option<String> = if InstanceInfo::active_instance_exists()? {
         // Get the name in the link
@@ -527,12 +611,12 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
         );
 
         // Either get the reachability and then add the row, or add the row immediately (depending on what the user wants us to do)
-        if show_status {
+        if let Some(client) = &client {
             // Get the status
             let status: String = 'reach: {
                 // Do a simple HTTP call to the health and see where we fail
                 let health_addr: String = format!("{api_addr}/health");
-                let res: reqwest::Response = match reqwest::get(&health_addr).await {
+                let res: reqwest::Response = match client.get(&health_addr).send().await {
                     Ok(res) => res,
                     Err(_) => {
                         break 'reach style("UNREACHABLE").red().bold().to_string();
@@ -560,6 +644,199 @@ pub async fn list(show_status: bool) -> Result<(), Error> {
     Ok(())
 }
 
+/// Defines the state of a single component of an instance, as reported by `brane instance status`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    /// The component responded as expected.
+    Ok,
+    /// The component responded, but not successfully.
+    Unhealthy,
+    /// The component could not be reached (or did not respond in time).
+    Unreachable,
+    /// The component was not probed because something it depends on is down.
+    Skipped,
+}
+impl ComponentState {
+    /// Returns a styled, human-readable version of this state.
+    fn styled(&self) -> String {
+        match self {
+            Self::Ok => style("OK").green().bold().to_string(),
+            Self::Unhealthy => style("UNHEALTHY").yellow().bold().to_string(),
+            Self::Unreachable => style("UNREACHABLE").red().bold().to_string(),
+            Self::Skipped => style("SKIPPED").dim().to_string(),
+        }
+    }
+}
+
+/// Describes the result of probing a single component of an instance.
+#[derive(Clone, Debug, Serialize)]
+pub struct ComponentStatus {
+    /// The address that was probed.
+    pub address:    String,
+    /// Whether the component is reachable and healthy.
+    pub state:      ComponentState,
+    /// How long the component took to respond, in milliseconds, if it responded.
+    pub latency_ms: Option<u64>,
+    /// The number of items the component reported (e.g., packages in an index), if applicable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count:      Option<usize>,
+    /// A description of what went wrong, if anything.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error:      Option<String>,
+}
+impl ComponentStatus {
+    /// Constructor for a ComponentStatus of a component that wasn't probed.
+    fn skipped(address: String) -> Self { Self { address, state: ComponentState::Skipped, latency_ms: None, count: None, error: None } }
+}
+
+/// Describes the aggregate health of an instance, as reported by `brane instance status`.
+#[derive(Clone, Debug, Serialize)]
+pub struct InstanceStatus {
+    /// The name of the instance.
+    pub instance: String,
+    /// Whether all critical components (the API and the driver) are up.
+    pub healthy:  bool,
+    /// The status of the API service.
+    pub api:      ComponentStatus,
+    /// The status of the driver service.
+    pub driver:   ComponentStatus,
+    /// The status of the package index (served by the API).
+    pub packages: ComponentStatus,
+    /// The status of the data index (served by the API).
+    pub datasets: ComponentStatus,
+}
+
+/// Runs a probe of a component with [`PROBE_TIMEOUT`], measuring how long it took.
+///
+/// # Arguments
+/// - `address`: The address that is probed, recorded in the result.
+/// - `probe`: The future doing the actual probing. Should resolve to a count, if any, on success, or the state and reason on failure.
+///
+/// # Returns
+/// The [`ComponentStatus`] describing the result.
+async fn probe<F>(address: String, probe: F) -> ComponentStatus
+where
+    F: std::future::Future<Output = Result<Option<usize>, (ComponentState, String)>>,
+{
+    let start: Instant = Instant::now();
+    let res = tokio::time::timeout(PROBE_TIMEOUT, probe).await;
+    let latency_ms: u64 = start.elapsed().as_millis() as u64;
+    match res {
+        Ok(Ok(count)) => ComponentStatus { address, state: ComponentState::Ok, latency_ms: Some(latency_ms), count, error: None },
+        Ok(Err((state, err))) => ComponentStatus {
+            address,
+            state,
+            latency_ms: if state == ComponentState::Unreachable { None } else { Some(latency_ms) },
+            count: None,
+            error: Some(err),
+        },
+        Err(_) => ComponentStatus {
+            address,
+            state: ComponentState::Unreachable,
+            latency_ms: None,
+            count: None,
+            error: Some(format!("No response within {}s", PROBE_TIMEOUT.as_secs())),
+        },
+    }
+}
+
+/// Probes the active instance's API, driver and indices and reports on their health.
+///
+/// Partial failures are reported per component rather than aborting; the indices are only fetched if the API is healthy.
+///
+/// # Arguments
+/// - `json`: If true, prints the report as JSON instead of as human-readable text.
+///
+/// # Errors
+/// This function errors if there is no active instance, if we failed to build an HTTP client, or if any critical component (the API or the
+/// driver) is down.
+pub async fn status(json: bool) -> Result<(), Error> {
+    info!("Probing active instance...");
+
+    // Load the active instance
+    let name: String = InstanceInfo::get_active_name()?;
+    let info: InstanceInfo = InstanceInfo::from_active_path()?;
+    let client: reqwest::Client = http_client().map_err(|source| Error::HttpClientError { source })?;
+
+    // Probe the API like `brane instance add` does
+    let health_addr: String = format!("{}/health", info.api);
+    debug!("Probing API at '{health_addr}'...");
+    let api: ComponentStatus = probe(health_addr.clone(), async {
+        let res: reqwest::Response = client.get(&health_addr).send().await.map_err(|err| (ComponentState::Unreachable, err.trace().to_string()))?;
+        if !res.status().is_success() {
+            return Err((ComponentState::Unhealthy, format!("Returned status code {}", res.status())));
+        }
+        Ok(None)
+    })
+    .await;
+
+    // Probe the driver by connecting to it
+    let drv_addr: String = info.drv.to_string();
+    debug!("Probing driver at '{drv_addr}'...");
+    let driver: ComponentStatus = probe(drv_addr.clone(), async {
+        connect_driver(drv_addr.clone()).await.map_err(|err| (ComponentState::Unreachable, err.trace().to_string()))?;
+        Ok(None)
+    })
+    .await;
+
+    // Count what's in the indices, but only if the API is there to serve them
+    let package_addr: String = format!("{}/graphql", info.api);
+    let data_addr: String = format!("{}/data/info", info.api);
+    let (packages, datasets): (ComponentStatus, ComponentStatus) = if api.state == ComponentState::Ok {
+        debug!("Fetching package index from '{package_addr}'...");
+        let packages: ComponentStatus = probe(package_addr.clone(), async {
+            let index: PackageIndex = get_package_index(&package_addr).await.map_err(|err| (ComponentState::Unhealthy, err.trace().to_string()))?;
+            Ok(Some(index.packages.len()))
+        })
+        .await;
+        debug!("Fetching data index from '{data_addr}'...");
+        let datasets: ComponentStatus = probe(data_addr.clone(), async {
+            let index: DataIndex = get_data_index(&data_addr).await.map_err(|err| (ComponentState::Unhealthy, err.trace().to_string()))?;
+            Ok(Some(index.iter().count()))
+        })
+        .await;
+        (packages, datasets)
+    } else {
+        (ComponentStatus::skipped(package_addr), ComponentStatus::skipped(data_addr))
+    };
+
+    // Report it
+    let status = InstanceStatus {
+        instance: name,
+        healthy: api.state == ComponentState::Ok && driver.state == ComponentState::Ok,
+        api,
+        driver,
+        packages,
+        datasets,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status).map_err(|source| Error::StatusSerializeError { source })?);
+    } else {
+        println!("Instance {}:", style(&status.instance).bold().cyan());
+        for (what, component) in [("API", &status.api), ("Driver", &status.driver), ("Packages", &status.packages), ("Datasets", &status.datasets)] {
+            let mut line: String =
+                format!("  {} {}", pad_str(what, 10, Alignment::Left, None), pad_str(&component.state.styled(), 12, Alignment::Left, None));
+            if let Some(latency_ms) = component.latency_ms {
+                line.push_str(&format!(" {latency_ms}ms"));
+            }
+            if let Some(count) = component.count {
+                line.push_str(&format!(" ({count} known)"));
+            }
+            if let Some(err) = &component.error {
+                line.push_str(&format!(" {}", style(err).dim()));
+            }
+            println!("{line}");
+        }
+    }
+
+    // Fail if a critical component is down
+    if !status.healthy {
+        return Err(Error::InstanceUnhealthy { name: status.instance });
+    }
+    Ok(())
+}
+
 /// Changes the active instance to the current one.
 ///
 /// # Arguments
@@ -576,7 +853,8 @@ pub fn select(name: String) -> Result<(), Error> {
 
     // Assert it exists (as a directory).
     if !dir.exists() {
-        return Err(Error::UnknownInstance { name });
+        let suggestions: Vec<String> = closest_names(&name, get_instance_names());
+        return Err(Error::UnknownInstance { name, suggestions });
     }
     if !dir.is_dir() {
         return Err(Error::InstanceNotADirError { path: dir });
@@ -598,6 +876,9 @@ pub fn select(name: String) -> Result<(), Error> {
 
 /// Edits an existing instance to change its properties.
 ///
+/// Only the properties that are given are changed; all others (including the ports when only the hostname is changed, and vice versa) are
+/// preserved as they are in the instance's `info.yml`.
+///
 /// # Arguments
 /// - `name`: The name of the instance to edit. If omitted, should use the active instance instead.
 /// - `hostname`: Whether to change the hostname of the instance and, if so, what to change it to.
@@ -642,6 +923,12 @@ pub fn edit(
     debug!("Loading instance file...");
     let mut info: InstanceInfo = InstanceInfo::from_path(instance_path.as_path())?;
 
+    // Don't bother rewriting the file if there's nothing to change
+    if hostname.is_none() && api_port.is_none() && drv_port.is_none() && user.is_none() {
+        println!("Nothing to change.");
+        return Ok(());
+    }
+
     // Adapt whatever is necessary
     debug!("Updating information...");
     if let Some(hostname) = hostname {
@@ -652,11 +939,11 @@ pub fn edit(
     }
     if let Some(port) = api_port {
         println!("Updating API service port to {}...", style(port).cyan().bold());
-        info.api = Address::Hostname(info.api.domain().into(), port);
+        *info.api.port_mut() = port;
     }
     if let Some(port) = drv_port {
         println!("Updating driver service port to {}...", style(port).cyan().bold());
-        info.drv = Address::Hostname(info.drv.domain().into(), port);
+        *info.drv.port_mut() = port;
     }
     if let Some(user) = user {
         println!("Updating username to {}...", style(&user).cyan().bold());
@@ -12,9 +12,17 @@ pub mod build_common;
 pub mod build_ecu;
 pub mod certs;
 pub mod check;
+pub mod compile;
+pub mod completions;
+pub mod cwl_export;
+pub mod cwl_expr;
 pub mod data;
+pub mod doctor;
 pub mod errors;
+pub mod index_cache;
+pub mod inputs;
 pub mod instance;
+pub mod lint;
 pub mod old_configs;
 pub mod packages;
 pub mod planner;
@@ -37,3 +45,9 @@ pub const MIN_DOCKER_VERSION: specifications::version::Version = specifications:
 
 /// The minimum Buildx version required by the Brane CLI command-line tool
 pub const MIN_BUILDX_VERSION: specifications::version::Version = specifications::version::Version::new(0, 7, 0);
+
+/// How long to wait for another process to release a package directory lock before giving up
+pub const PACKAGE_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// The file in a package directory that marks that its image has not been pulled (yet), i.e., only its metadata is present
+pub const IMAGE_ABSENT_FILE: &str = "image.absent";
@@ -0,0 +1,224 @@
+//  LINT.rs
+//
+//  Description:
+//!   Implements the `brane workflow lint`-subcommand, which compiles a
+//!   workflow against the local indices and reports common anti-patterns
+//!   found in the compiled representation.
+//
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::{Display, Formatter, Result as FResult};
+
+use brane_ast::Workflow;
+use brane_ast::ast::{Edge, EdgeInstr};
+use brane_ast::locations::Locations;
+use brane_dsl::Language;
+use console::style;
+use specifications::data::DataName;
+
+use crate::compile;
+pub use crate::errors::LintError as Error;
+use crate::spec::LintRule;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_ast::{CompileResult, ParserOptions};
+    use specifications::data::DataIndex;
+    use specifications::package::PackageIndex;
+
+    use super::*;
+
+
+    /// Compiles the given BraneScript snippet without any packages or datasets.
+    fn compile(source: &str) -> Workflow {
+        match brane_ast::compile_program(
+            source.as_bytes(),
+            &PackageIndex::empty(),
+            &DataIndex::from_infos(vec![]).unwrap(),
+            &ParserOptions::new(Language::BraneScript),
+        ) {
+            CompileResult::Workflow(wf, _) => wf,
+            _ => panic!("Failed to compile test snippet"),
+        }
+    }
+
+    #[test]
+    fn test_unused_variable() {
+        let lints: Vec<Lint> = lint(&compile("let x := 1;\nlet y := 2;\nlet _z := 3;\nprintln(y);\n"));
+        assert_eq!(lints, vec![Lint { rule: LintRule::UnusedVariable, message: "Variable 'x' is declared but never read".into() }]);
+    }
+
+    #[test]
+    fn test_rule_parse() {
+        for rule in LintRule::ALL {
+            assert_eq!(rule.to_string().parse::<LintRule>().unwrap(), rule);
+        }
+        assert!("unused".parse::<LintRule>().is_err());
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A single warning reported by the linter.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Lint {
+    /// The rule that was violated.
+    pub rule:    LintRule,
+    /// A human-readable description of the violation.
+    pub message: String,
+}
+impl Display for Lint {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{}[{}] ({}): {}", style("warning").yellow().bold(), self.rule, self.rule.category(), self.message)
+    }
+}
+
+
+
+/// Analyses a compiled workflow for common anti-patterns.
+///
+/// Variables whose name starts with an underscore (and `self` parameters) are never reported as unused.
+///
+/// # Arguments
+/// - `workflow`: The [`Workflow`] to analyse.
+///
+/// # Returns
+/// The warnings found, ordered by rule.
+pub fn lint(workflow: &Workflow) -> Vec<Lint> {
+    let mut lints: BTreeSet<Lint> = BTreeSet::new();
+
+    // Collect what is declared, read and produced throughout the graph and any function bodies
+    let mut declared: BTreeSet<usize> = BTreeSet::new();
+    let mut read: HashSet<usize> = HashSet::new();
+    let mut produced: BTreeSet<&str> = BTreeSet::new();
+    let mut consumed: BTreeSet<&str> = BTreeSet::new();
+    for edge in workflow.graph.iter().chain(workflow.funcs.values().flatten()) {
+        match edge {
+            Edge::Node { task, locs, input, result, .. } => {
+                let name: &str = workflow.table.tasks.get(*task).map(|def| def.name()).unwrap_or("<unknown>");
+
+                // The planner needs exactly one location, which it can only infer from the datasets the task reads
+                match locs {
+                    Locations::Restricted(locs) if locs.len() > 1 => {
+                        lints.insert(Lint {
+                            rule:    LintRule::UnresolvableLocation,
+                            message: format!(
+                                "Task '{name}' may run at any of {} locations ({}), but the planner needs exactly one",
+                                locs.len(),
+                                locs.join(", ")
+                            ),
+                        });
+                    },
+                    Locations::All if !input.keys().any(|data| matches!(data, DataName::Data(_))) => {
+                        lints.insert(Lint {
+                            rule:    LintRule::UnresolvableLocation,
+                            message: format!(
+                                "Task '{name}' has no location annotation and reads no datasets to infer one from; it cannot be planned on an \
+                                 instance without an `#[on(...)]`"
+                            ),
+                        });
+                    },
+                    _ => {},
+                }
+
+                consumed.extend(input.keys().filter_map(intermediate_result));
+                if let Some(result) = result {
+                    produced.insert(result);
+                }
+            },
+            Edge::Linear { instrs, .. } => {
+                for instr in instrs {
+                    match instr {
+                        EdgeInstr::VarDec { def } => {
+                            declared.insert(*def);
+                        },
+                        EdgeInstr::VarGet { def } => {
+                            read.insert(*def);
+                        },
+                        _ => {},
+                    }
+                }
+            },
+            Edge::Call { input, .. } => consumed.extend(input.iter().filter_map(intermediate_result)),
+            Edge::Return { result } => consumed.extend(result.iter().filter_map(intermediate_result)),
+            _ => {},
+        }
+    }
+
+    // Report the variables that are never read
+    for def in declared.into_iter().filter(|def| !read.contains(def)) {
+        let name: &str = workflow.table.vars.get(def).map(|var| var.name.as_str()).unwrap_or("<unknown>");
+        if name == "self" || name.starts_with('_') {
+            continue;
+        }
+        lints.insert(Lint { rule: LintRule::UnusedVariable, message: format!("Variable '{name}' is declared but never read") });
+    }
+
+    // Report the results that are read without being produced (by this workflow or a previous snippet), or vice versa
+    for name in consumed.difference(&produced).filter(|name| !workflow.table.results.contains_key(**name)) {
+        lints.insert(Lint { rule: LintRule::UnproducedResult, message: format!("Intermediate result '{name}' is read but never produced") });
+    }
+    for name in produced.difference(&consumed) {
+        lints.insert(Lint {
+            rule:    LintRule::UnusedResult,
+            message: format!("Intermediate result '{name}' is produced but never read, committed or returned"),
+        });
+    }
+
+    // Done
+    lints.into_iter().collect()
+}
+
+/// Returns the name of the given [`DataName`] if it is an intermediate result.
+#[inline]
+fn intermediate_result(name: &DataName) -> Option<&str> {
+    match name {
+        DataName::IntermediateResult(name) => Some(name),
+        DataName::Data(_) => None,
+    }
+}
+
+
+
+/// Handles the `brane workflow lint`-subcommand.
+///
+/// # Arguments
+/// - `file`: The path to the workflow file to lint. Can also be '-', in which case it is read from stdin instead.
+/// - `language`: The language with which to compile the file.
+/// - `allow`: The rules of which to suppress any warnings.
+/// - `deny_warnings`: If true, fails if any (unsuppressed) warnings were reported.
+///
+/// # Errors
+/// This function errors if we failed to compile the workflow, or if `deny_warnings` is given and there were warnings.
+pub fn handle(file: String, language: Language, allow: Vec<LintRule>, deny_warnings: bool) -> Result<(), Error> {
+    info!("Handling 'brane workflow lint {}'", if file == "-" { "<stdin>" } else { file.as_str() });
+
+    // Compile the workflow like `brane workflow compile` does
+    let (input, workflow, _): (String, Workflow, _) = compile::compile_local(file, language).map_err(|source| Error::Compile { source })?;
+
+    // Lint it, then report what's not suppressed
+    debug!("Linting workflow '{}'...", input);
+    let (suppressed, lints): (Vec<Lint>, Vec<Lint>) = lint(&workflow).into_iter().partition(|lint| allow.contains(&lint.rule));
+    for lint in &lints {
+        println!("{lint}");
+    }
+    let suppressed: String = if suppressed.is_empty() { String::new() } else { format!(" ({} suppressed)", suppressed.len()) };
+    if lints.is_empty() {
+        println!("No lint warnings for '{input}'{suppressed}");
+        return Ok(());
+    }
+    println!();
+    println!("{} warning(s) for '{}'{}", style(lints.len()).bold().yellow(), input, suppressed);
+
+    // Fail if told to
+    if deny_warnings {
+        return Err(Error::Warnings { input, count: lints.len() });
+    }
+    Ok(())
+}
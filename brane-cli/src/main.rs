@@ -23,18 +23,23 @@ use std::process;
 use std::str::FromStr;
 
 use anyhow::Result;
-use brane_cli::errors::{CliError, ImportError};
-use brane_cli::{build_ecu, certs, check, data, instance, packages, registry, repl, run, test, upgrade, verify, version};
+use brane_cli::build_ecu::BuildOptions;
+use brane_cli::errors::{CliError, ImportError, UtilError};
+use brane_cli::instance::InstanceDefaults;
+use brane_cli::spec::DownloadLimit;
+use brane_cli::{
+    build_ecu, certs, check, compile, completions, data, doctor, instance, lint, packages, registry, repl, run, test, upgrade, verify, version,
+};
 use brane_dsl::Language;
 use brane_shr::fs::DownloadSecurity;
-use brane_tsk::docker::DockerOptions;
+use brane_tsk::docker::{DockerHost, DockerOptions};
 use clap::Parser;
 use cli::*;
 use dotenvy::dotenv;
 use error_trace::ErrorTrace as _;
 use humanlog::{DebugMode, HumanLogger};
 // use git2::Repository;
-use log::{error, info};
+use log::{error, info, warn};
 use specifications::arch::Arch;
 use specifications::package::PackageKind;
 use specifications::version::Version as SemVersion;
@@ -49,6 +54,9 @@ async fn main() -> Result<()> {
     dotenv().ok();
     let options = cli::Cli::parse();
 
+    // Decide on colours before anything styled is printed
+    brane_cli::utils::init_colors(options.no_color);
+
     // Prepare the logger
     if let Err(err) = HumanLogger::terminal(if options.debug { DebugMode::Debug } else { DebugMode::HumanFriendly }).init() {
         eprintln!("WARNING: Failed to setup logger: {err} (no logging for this session)");
@@ -60,13 +68,17 @@ async fn main() -> Result<()> {
         setup_panic!();
     }
 
-    // Check dependencies if not withheld from doing so
-    if !options.skip_check {
-        match brane_cli::utils::check_dependencies().await {
+    // Check dependencies if not withheld from doing so and the subcommand actually needs them
+    if !options.skip_check && options.sub_command.requires_docker() {
+        match brane_cli::utils::check_dependencies(options.sub_command.docker_options().as_ref(), options.sub_command.requires_buildx()).await {
             Ok(Ok(())) => {},
             Ok(Err(err)) => {
                 eprintln!("Dependencies not met: {err}");
-                process::exit(1);
+                process::exit(CliError::EXIT_DOCKER);
+            },
+            Err(err @ UtilError::DockerNotResponding { .. }) => {
+                eprintln!("{err}");
+                process::exit(CliError::EXIT_DOCKER);
             },
             Err(err) => {
                 eprintln!("Could not check for dependencies: {err}");
@@ -80,7 +92,13 @@ async fn main() -> Result<()> {
         Ok(_) => process::exit(0),
         Err(err) => {
             error!("{}", err.trace());
-            process::exit(1);
+            if let Some(version) = brane_cli::utils::refused_min_tls_version(&err) {
+                error!(
+                    "The connection did not use TLS {version} or later, which is required by '--min-tls-version' (or the active instance's \
+                     default); note that this also refuses unencrypted connections"
+                );
+            }
+            process::exit(err.exit_code());
         },
     }
 }
@@ -96,6 +114,21 @@ async fn main() -> Result<()> {
 /// Nothing if the subcommand executed successfully (they are self-contained), or a CliError otherwise.
 async fn run(options: Cli) -> Result<(), CliError> {
     use SubCommand::*;
+
+    // Only allow skipping TLS verification for read-only commands
+    if options.insecure_skip_verify {
+        if let Some(what) = options.sub_command.persistent_name() {
+            return Err(CliError::InsecureSkipVerifyNotAllowed { what });
+        }
+        brane_cli::utils::set_insecure_skip_verify();
+    }
+    if let Some(version) = options.min_tls_version.or_else(|| InstanceDefaults::active().min_tls_version) {
+        brane_cli::utils::set_min_tls_version(version);
+    }
+    if let Some(agent) = options.user_agent {
+        brane_cli::utils::set_user_agent(agent);
+    }
+
     match options.sub_command {
         Certs { subcommand } => {
             use CertsSubcommand::*;
@@ -103,13 +136,22 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 Add { paths, domain, instance, force } => {
                     certs::add(instance, paths, domain, force).map_err(|source| CliError::CertsError { source })?;
                 },
-                Remove { domains, instance, force } => {
-                    certs::remove(domains, instance, force).map_err(|source| CliError::CertsError { source })?;
+                Renew { paths, domain, instance } => {
+                    certs::renew(instance, paths, domain).map_err(|source| CliError::CertsError { source })?;
+                },
+                Remove { domains, instance, force, dry_run } => {
+                    certs::remove(domains, instance, force, dry_run).map_err(|source| CliError::CertsError { source })?;
                 },
 
                 List { instance, all } => {
                     certs::list(instance, all).map_err(|source| CliError::CertsError { source })?;
                 },
+                Export { domain, instance, output } => {
+                    certs::export(domain, instance, output).map_err(|source| CliError::CertsError { source })?;
+                },
+                Check { instance, all, warn_days, format } => {
+                    certs::check(instance, all, warn_days, format).map_err(|source| CliError::CertsError { source })?;
+                },
             }
         },
         Data { subcommand } => {
@@ -126,16 +168,64 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     .await
                     .map_err(|source| CliError::DataError { source })?;
                 },
-                Download { names, locs, use_case, user, proxy_addr, force } => {
+                Download {
+                    mut names,
+                    all,
+                    yes,
+                    locs,
+                    use_case,
+                    user,
+                    proxy_addr,
+                    force,
+                    keep_archive,
+                    max_download_size,
+                    allow_unknown_size,
+                    strict_data,
+                } => {
                     let user = user.unwrap_or_else(|| {
                         std::env::var("USER").expect("Currently we require the user to be set. This should default to the logged in user")
                     });
 
-                    data::download(names, locs, use_case, user, &proxy_addr, force).await.map_err(|source| CliError::DataError { source })?;
+                    // Resolve the use-case from the flag, the active instance or (for backwards compatibility) the first positional
+                    let defaults: InstanceDefaults = InstanceDefaults::active();
+                    let use_case: String = match (use_case, defaults.use_case) {
+                        (Some(use_case), _) => use_case,
+                        // The legacy form `download <USE_CASE> --all` would otherwise be read as a dataset next to '--all'
+                        (None, Some(_)) if all && names.len() == 1 => {
+                            warn!("Interpreting '{}' as the use-case; give it with '--use-case' instead", names[0]);
+                            names.remove(0)
+                        },
+                        // The legacy form `download <USE_CASE> <DATASETS>...` with the default use-case would otherwise download it as a dataset
+                        (None, Some(default)) if names.len() > 1 && names[0] == default => {
+                            warn!("Interpreting '{}' as the use-case; give it with '--use-case' instead", names[0]);
+                            names.remove(0)
+                        },
+                        (None, Some(default)) => default,
+                        (None, None) if !names.is_empty() => names.remove(0),
+                        (None, None) => return Err(CliError::MissingUseCase { what: "data download" }),
+                    };
+                    if all && !names.is_empty() {
+                        return Err(CliError::DownloadAllWithDatasets);
+                    } else if !all && names.is_empty() {
+                        return Err(CliError::DownloadNoDatasets);
+                    } else if all && strict_data && !yes {
+                        return Err(CliError::StrictDataWithoutYes);
+                    }
+
+                    let limit = DownloadLimit { max_size: max_download_size.or(defaults.max_download_size), allow_unknown_size };
+                    data::download(names, all, yes, locs, use_case, user, &proxy_addr, force, keep_archive, limit, strict_data)
+                        .await
+                        .map_err(|source| CliError::DataError { source })?;
                 },
 
-                List {} => {
-                    data::list().map_err(|source| CliError::DataError { source })?;
+                Export { name, output } => {
+                    data::export(name, output).await.map_err(|source| CliError::DataError { source })?;
+                },
+                ImportArchive { file } => {
+                    data::import_archive(file).await.map_err(|source| CliError::DataError { source })?;
+                },
+                List { since, before } => {
+                    data::list(since, before).map_err(|source| CliError::DataError { source })?;
                 },
                 Search {} => {
                     eprintln!("search is not yet implemented.");
@@ -148,8 +238,16 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 Remove { names, force } => {
                     data::remove(names, force).map_err(|source| CliError::DataError { source })?;
                 },
+                Verify { names, all } => {
+                    data::verify(names, all).map_err(|source| CliError::DataError { source })?;
+                },
             }
         },
+        Doctor { docker_socket, client_version } => {
+            doctor::handle(DockerOptions { host: DockerHost::resolve(docker_socket), version: client_version })
+                .await
+                .map_err(|source| CliError::DoctorError { source })?;
+        },
         Instance { subcommand } => {
             // Switch on the subcommand
             use InstanceSubcommand::*;
@@ -175,6 +273,9 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 List { show_status } => {
                     instance::list(show_status).await.map_err(|source| CliError::InstanceError { source })?;
                 },
+                Status { json } => {
+                    instance::status(json).await.map_err(|source| CliError::InstanceError { source })?;
+                },
                 Select { name } => {
                     instance::select(name).map_err(|source| CliError::InstanceError { source })?;
                 },
@@ -182,12 +283,31 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 Edit { name, hostname, api_port, drv_port, user } => {
                     instance::edit(name, hostname, api_port, drv_port, user).map_err(|source| CliError::InstanceError { source })?;
                 },
+                SetDefaultUser { user, name } => {
+                    instance::edit(name, None, None, None, Some(user)).map_err(|source| CliError::InstanceError { source })?;
+                },
             }
         },
 
         Package { subcommand } => {
             match subcommand {
-                PackageSubcommand::Build { arch, workdir, file, kind, init, keep_files, crlf_ok } => {
+                PackageSubcommand::Build {
+                    arch,
+                    workdir,
+                    file,
+                    kind,
+                    init,
+                    keep_files,
+                    crlf_ok,
+                    labels,
+                    dockerfile_out,
+                    dry_run,
+                    from_dockerfile,
+                    no_default_deps,
+                    build_args,
+                    entrypoint_args,
+                    owners,
+                } => {
                     // Resolve the working directory
                     let workdir = match workdir {
                         Some(workdir) => workdir,
@@ -210,11 +330,25 @@ async fn run(options: Cli) -> Result<(), CliError> {
 
                     // Build a new package with it
                     match kind {
-                        PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, keep_files, crlf_ok)
+                        PackageKind::Ecu => {
+                            build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, BuildOptions {
+                                branelet_path: init,
+                                keep_files,
+                                convert_crlf: crlf_ok,
+                                labels,
+                                dockerfile_out,
+                                dry_run,
+                                from_dockerfile,
+                                no_default_deps,
+                                build_args,
+                                entrypoint_args: entrypoint_args.map(|args| args.0),
+                                owners,
+                            })
                             .await
-                            .map_err(|source| CliError::BuildError { source })?,
+                            .map_err(|source| CliError::BuildError { source })?
+                        },
                         PackageKind::Cwl => {
-                                cwl::build(workdir, file)
+                                cwl::build(workdir, file, dockerfile_out, dry_run)
                                     .map_err(|source| CliError::BuildError { source })?
                             },
                             _ => eprintln!("Unsupported package kind: {kind}"),
@@ -228,11 +362,19 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     // Download the file
                     let tar_path: PathBuf = dir.path().join("repo.tar.gz");
                     let dir_path: PathBuf = dir.path().join("repo");
-                    brane_shr::fs::download_file_async(&url, &tar_path, DownloadSecurity { checksum: None, https: true }, None).await.map_err(
-                        |source| CliError::ImportError {
-                            source: ImportError::RepoCloneError { repo: url.clone(), target: dir_path.clone(), source },
-                        },
-                    )?;
+                    let (prgs, mut progress) = brane_shr::fs::download_progress_bar(format!("Downloading {repo} ({branch})..."));
+                    let res = brane_shr::fs::download_file_async(
+                        &url,
+                        &tar_path,
+                        DownloadSecurity { checksum: None, https: true },
+                        None,
+                        Some(&mut progress),
+                    )
+                    .await;
+                    prgs.finish_and_clear();
+                    res.map_err(|source| CliError::ImportError {
+                        source: ImportError::RepoCloneError { repo: url.clone(), target: dir_path.clone(), source },
+                    })?;
                     brane_shr::fs::unarchive_async(&tar_path, &dir_path).await.map_err(|source| CliError::ImportError {
                         source: ImportError::RepoCloneError { repo: url.clone(), target: dir_path.clone(), source },
                     })?;
@@ -273,22 +415,35 @@ async fn run(options: Cli) -> Result<(), CliError> {
 
                     // Build a new package with it
                     match kind {
-                        PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, init, false, crlf_ok)
-                            .await
-                            .map_err(|source| CliError::BuildError { source })?,
+                        PackageKind::Ecu => build_ecu::handle(arch.unwrap_or(Arch::HOST), workdir, file, BuildOptions {
+                            branelet_path: init,
+                            convert_crlf: crlf_ok,
+                            ..Default::default()
+                        })
+                        .await
+                        .map_err(|source| CliError::BuildError { source })?,
                         _ => eprintln!("Unsupported package kind: {kind}"),
                     }
                 },
-                PackageSubcommand::Inspect { name, version, syntax } => {
-                    packages::inspect(name, version, syntax).map_err(|source| CliError::OtherError { source })?;
+                PackageSubcommand::Export { name, version, output } => {
+                    packages::export(name, version, output).await.map_err(|source| CliError::PackageError { source })?;
+                },
+                PackageSubcommand::ImportArchive { file, verify } => {
+                    packages::import_archive(file, verify).await.map_err(|source| CliError::PackageError { source })?;
+                },
+                PackageSubcommand::Index { repair } => {
+                    packages::index(repair).map_err(|source| CliError::PackageError { source })?;
+                },
+                PackageSubcommand::Inspect { name, version, syntax, format } => {
+                    packages::inspect(name, version, syntax, format).map_err(|source| CliError::OtherError { source })?;
                 },
                 PackageSubcommand::List { latest } => {
                     packages::list(latest).map_err(|source| CliError::OtherError { source: anyhow::anyhow!(source) })?;
                 },
-                PackageSubcommand::Load { name, version } => {
-                    packages::load(name, version).await.map_err(|source| CliError::OtherError { source })?;
+                PackageSubcommand::Load { name, version, allow_downgrade } => {
+                    packages::load(name, version, allow_downgrade).await.map_err(|source| CliError::OtherError { source })?;
                 },
-                PackageSubcommand::Pull { packages } => {
+                PackageSubcommand::Pull { packages, checksum, only_metadata, max_download_size, allow_unknown_size } => {
                     // Parse the NAME:VERSION pairs into a name and a version
                     if packages.is_empty() {
                         println!("Nothing to do.");
@@ -303,9 +458,10 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     }
 
                     // Now delegate the parsed pairs to the actual pull() function
-                    registry::pull(parsed).await.map_err(|source| CliError::RegistryError { source })?;
+                    let limit = DownloadLimit { max_size: max_download_size.or(InstanceDefaults::active().max_download_size), allow_unknown_size };
+                    registry::pull(parsed, checksum, only_metadata, limit).await.map_err(|source| CliError::RegistryError { source })?;
                 },
-                PackageSubcommand::Push { packages } => {
+                PackageSubcommand::Push { packages, fail_fast, compress } => {
                     // Parse the NAME:VERSION pairs into a name and a version
                     if packages.is_empty() {
                         println!("Nothing to do.");
@@ -319,9 +475,9 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     }
 
                     // Now delegate the parsed pairs to the actual push() function
-                    registry::push(parsed).await.map_err(|source| CliError::RegistryError { source })?;
+                    registry::push(parsed, fail_fast, compress).await.map_err(|source| CliError::RegistryError { source })?;
                 },
-                PackageSubcommand::Remove { force, packages, docker_socket, client_version } => {
+                PackageSubcommand::Remove { force, packages, constraint, docker_socket, client_version } => {
                     // Parse the NAME:VERSION pairs into a name and a version
                     if packages.is_empty() {
                         println!("Nothing to do.");
@@ -329,23 +485,57 @@ async fn run(options: Cli) -> Result<(), CliError> {
                     }
                     let mut parsed: Vec<(String, SemVersion)> = Vec::with_capacity(packages.len());
                     for package in packages {
-                        parsed.push(
-                            SemVersion::from_package_pair(&package).map_err(|source| CliError::PackagePairParseError { raw: package, source })?,
-                        );
+                        let (name, version): (String, SemVersion) = SemVersion::from_package_pair(&package)
+                            .map_err(|source| CliError::PackagePairParseError { raw: package.clone(), source })?;
+                        parsed.push((name, version));
                     }
 
                     // Now delegate the parsed pairs to the actual remove() function
-                    packages::remove(force, parsed, DockerOptions { socket: docker_socket, version: client_version })
+                    packages::remove(force, parsed, constraint, DockerOptions {
+                        host:    DockerHost::resolve(docker_socket),
+                        version: client_version,
+                    })
+                    .await
+                    .map_err(|source| CliError::PackageError { source })?;
+                },
+                PackageSubcommand::Tag { package, new_version, docker_socket, client_version } => {
+                    let (name, version): (String, SemVersion) =
+                        SemVersion::from_package_pair(&package).map_err(|source| CliError::PackagePairParseError { raw: package, source })?;
+                    packages::tag(name, version, new_version, DockerOptions { host: DockerHost::resolve(docker_socket), version: client_version })
                         .await
                         .map_err(|source| CliError::PackageError { source })?;
                 },
-                PackageSubcommand::Test { name, version, show_result, docker_socket, client_version, keep_containers } => {
-                    test::handle(name, version, show_result, DockerOptions { socket: docker_socket, version: client_version }, keep_containers)
-                        .await
-                        .map_err(|source| CliError::TestError { source })?;
+                PackageSubcommand::Test {
+                    name,
+                    version,
+                    show_result,
+                    docker_socket,
+                    client_version,
+                    keep_containers,
+                    max_retries,
+                    stats,
+                    no_auto_fetch,
+                } => {
+                    test::handle(
+                        name,
+                        version,
+                        show_result,
+                        DockerOptions { host: DockerHost::resolve(docker_socket), version: client_version },
+                        keep_containers,
+                        max_retries,
+                        stats,
+                        !no_auto_fetch,
+                    )
+                    .await
+                    .map_err(|source| CliError::TestError { source })?;
+                },
+                PackageSubcommand::Search { term, limit, offset, json } => {
+                    registry::search(term, limit, offset, json).await.map_err(|source| CliError::OtherError { source })?;
                 },
-                PackageSubcommand::Search { term } => {
-                    registry::search(term).await.map_err(|source| CliError::OtherError { source })?;
+                PackageSubcommand::Which { package, image } => {
+                    let (name, version): (String, SemVersion) =
+                        SemVersion::from_package_pair(&package).map_err(|source| CliError::PackagePairParseError { raw: package, source })?;
+                    packages::which(name, version, image).map_err(|source| CliError::PackageError { source })?;
                 },
                 PackageSubcommand::Unpublish { name, version, force } => {
                     registry::unpublish(name, version, force).await.map_err(|source| CliError::OtherError { source })?;
@@ -396,15 +586,42 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 version::handle().await.map_err(|source| CliError::VersionError { source })?;
             }
         },
-        Cwl { file } => {
-            cwl::handle(file).await.map_err(|source| CliError::OtherError { source })?;
+        Complete { kind, prefix } => completions::handle(kind, prefix),
+        Cwl { file, collect_outputs, inputs } => {
+            if let Some(dir) = collect_outputs {
+                cwl::run(file, dir, inputs).await.map_err(|source| CliError::OtherError { source })?;
+            } else {
+                cwl::handle(file, None, false).await.map_err(|source| CliError::OtherError { source })?;
+            }
         },
         Workflow { subcommand } => match subcommand {
-            WorkflowSubcommand::Check { file, bakery, user, profile } => {
-                check::handle(file, if bakery { Language::Bakery } else { Language::BraneScript }, user, profile)
+            WorkflowSubcommand::Check { file, bakery, user, profile, package_index, data_index, watch } => {
+                let defaults: InstanceDefaults = InstanceDefaults::active();
+                check::handle(
+                    file,
+                    if bakery { Language::Bakery } else { Language::BraneScript },
+                    user,
+                    profile || defaults.profile.unwrap_or(false),
+                    package_index,
+                    data_index,
+                    watch,
+                )
+                .await
+                .map_err(|source| CliError::CheckError { source })?;
+            },
+            WorkflowSubcommand::Compile { file, bakery, output_format, output } => {
+                compile::handle(file, if bakery { Language::Bakery } else { Language::BraneScript }, output_format, output)
+                    .map_err(|source| CliError::CompileError { source })?;
+            },
+            WorkflowSubcommand::ExplainPlan { file, bakery, user, json } => {
+                check::explain_plan(file, if bakery { Language::Bakery } else { Language::BraneScript }, user, json)
                     .await
                     .map_err(|source| CliError::CheckError { source })?;
             },
+            WorkflowSubcommand::Lint { file, bakery, allow, deny_warnings } => {
+                lint::handle(file, if bakery { Language::Bakery } else { Language::BraneScript }, allow, deny_warnings)
+                    .map_err(|source| CliError::LintError { source })?;
+            },
             WorkflowSubcommand::Repl {
                 proxy_addr,
                 use_case,
@@ -413,20 +630,26 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 remote,
                 attach,
                 profile,
+                profile_output,
                 docker_socket,
                 client_version,
                 keep_containers,
             } => {
+                // Fill in anything not given from the active instance's defaults
+                let defaults: InstanceDefaults = InstanceDefaults::active();
+                let use_case: String = use_case.or(defaults.use_case).ok_or(CliError::MissingUseCase { what: "workflow repl" })?;
+
                 repl::start(
-                    proxy_addr,
+                    proxy_addr.or(defaults.proxy_addr),
                     remote,
                     use_case,
                     attach,
                     if bakery { Language::Bakery } else { Language::BraneScript },
                     clear,
-                    profile,
-                    DockerOptions { socket: docker_socket, version: client_version },
+                    profile || defaults.profile.unwrap_or(false),
+                    DockerOptions { host: DockerHost::resolve(docker_socket), version: client_version },
                     keep_containers,
+                    profile_output,
                 )
                 .await
                 .map_err(|source| CliError::ReplError { source })?;
@@ -439,20 +662,50 @@ async fn run(options: Cli) -> Result<(), CliError> {
                 dry_run,
                 remote,
                 profile,
+                profile_output,
+                location,
                 docker_socket,
                 client_version,
                 keep_containers,
+                cleanup_after,
+                keep_intermediate,
+                max_retries,
+                stats,
+                no_auto_fetch,
+                strict_data,
+                no_index_cache,
+                json,
+                result_json,
+                inputs,
+                input_env_prefix,
             } => {
+                // Fill in anything not given from the active instance's defaults
+                let defaults: InstanceDefaults = InstanceDefaults::active();
+                let use_case: String = use_case.or(defaults.use_case).ok_or(CliError::MissingUseCase { what: "workflow run" })?;
+
                 run::handle(
-                    proxy_addr,
+                    proxy_addr.or(defaults.proxy_addr),
                     if bakery { Language::Bakery } else { Language::BraneScript },
                     use_case,
                     file,
                     dry_run,
                     remote,
-                    profile,
-                    DockerOptions { socket: docker_socket, version: client_version },
+                    profile || defaults.profile.unwrap_or(false),
+                    location,
+                    DockerOptions { host: DockerHost::resolve(docker_socket), version: client_version },
                     keep_containers,
+                    cleanup_after.map(|age| age.0),
+                    keep_intermediate,
+                    max_retries,
+                    stats,
+                    !no_auto_fetch,
+                    strict_data,
+                    !no_index_cache,
+                    json,
+                    result_json,
+                    profile_output,
+                    inputs,
+                    input_env_prefix,
                 )
                 .await
                 .map_err(|source| CliError::RunError { source })?;
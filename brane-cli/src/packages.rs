@@ -1,4 +1,5 @@
 use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -8,10 +9,14 @@ use bollard::image::{ImportImageOptions, TagImageOptions};
 use bollard::models::BuildInfo;
 use brane_dsl::DataType;
 use brane_shr::formatters::PrettyListFormatter;
+use brane_shr::fs::FileLock;
+use brane_tsk::checksums;
 use brane_tsk::docker::{self, DockerOptions};
+use brane_tsk::local::get_package_versions;
 use chrono::{Local, Utc};
 use console::{Alignment, pad_str, style};
 use dialoguer::Confirm;
+use error_trace::ErrorTrace as _;
 use fs_extra::dir;
 use futures_util::stream::TryStreamExt;
 use indicatif::{DecimalBytes, HumanDuration};
@@ -19,13 +24,41 @@ use prettytable::Table;
 use prettytable::format::FormatBuilder;
 use specifications::container::Image;
 use specifications::package::PackageInfo;
-use specifications::version::Version;
+use specifications::version::{Version, VersionConstraint};
+use tempfile::TempDir;
+use tokio::fs as tfs;
 use tokio::fs::File as TFile;
 use tokio_stream::StreamExt;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
 use crate::errors::PackageError;
-use crate::utils::{ensure_package_dir, ensure_packages_dir};
+use crate::spec::{DownloadLimit, InspectFormat};
+use crate::utils::{assert_valid_entry_name, ensure_package_dir, ensure_packages_dir, get_package_dir};
+use crate::{IMAGE_ABSENT_FILE, PACKAGE_LOCK_TIMEOUT, registry};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use brane_tsk::docker::{API_DEFAULT_VERSION, ClientVersion, DockerHost};
+
+    use super::*;
+
+
+    #[tokio::test]
+    async fn test_remove_constraint_with_version() {
+        // A constraint only applies to packages given without a version, so giving one is a usage error before anything is removed
+        let constraint: VersionConstraint = VersionConstraint::from_str("<2").unwrap();
+        let docker_opts = DockerOptions { host: DockerHost::default(), version: ClientVersion(API_DEFAULT_VERSION) };
+        let packages: Vec<(String, Version)> = vec![("test".into(), Version::latest()), ("test".into(), Version::new(1, 0, 0))];
+        assert!(matches!(
+            remove(false, packages, Some(constraint), docker_opts).await,
+            Err(PackageError::ConstraintWithVersion { name, version }) if name == "test" && version == Version::new(1, 0, 0)
+        ));
+    }
+}
+
+
 
 
 /***** HELPER FUNCTIONS *****/
@@ -56,6 +89,102 @@ fn insert_package_in_list(infos: &mut Vec<PackageInfo>, info: PackageInfo) {
 }
 /*******/
 
+/// Escapes the given text such that it can be safely put in a Markdown table cell.
+///
+/// # Arguments
+/// - `text`: The text to escape.
+///
+/// # Returns
+/// A new string with pipes escaped and newlines collapsed to spaces.
+fn md_cell(text: impl AsRef<str>) -> String { text.as_ref().replace('|', "\\|").replace(['\r', '\n'], " ") }
+
+/// Renders the given package as a Markdown document, suitable for dropping into a documentation site.
+///
+/// # Arguments
+/// - `info`: The PackageInfo describing the package to render.
+///
+/// # Returns
+/// A string with the Markdown document.
+fn inspect_markdown(info: &PackageInfo) -> String {
+    let mut out: String = String::new();
+
+    // Write the header
+    out.push_str(&format!("# Package `{}`\n\n", info.name));
+    if !info.description.trim().is_empty() {
+        out.push_str(&format!("{}\n\n", info.description.trim()));
+    }
+    out.push_str(&format!("- **Version:** {}\n", info.version));
+    out.push_str(&format!("- **Kind:** {}\n", info.kind));
+    if !info.owners.is_empty() {
+        out.push_str(&format!("- **Owners:** {}\n", info.owners.join(", ")));
+    }
+    out.push_str(&format!("- **Created:** {}\n", info.created.to_rfc3339()));
+    out.push('\n');
+
+    // Write the classes
+    out.push_str("## Classes\n\n");
+    let mut types: Vec<&String> = info.types.keys().collect();
+    types.sort_by_key(|t| t.to_lowercase());
+    for name in &types {
+        let info = info.types.get(*name).unwrap();
+        out.push_str(&format!("### `{name}`\n\n"));
+        if info.properties.is_empty() {
+            out.push_str("_This class has no properties._\n\n");
+            continue;
+        }
+        out.push_str("| Property | Type | Optional |\n");
+        out.push_str("|----------|------|----------|\n");
+        for p in &info.properties {
+            out.push_str(&format!(
+                "| `{}` | `{}` | {} |\n",
+                md_cell(&p.name),
+                md_cell(DataType::from(&p.data_type).to_string()),
+                if p.optional.unwrap_or(false) { "yes" } else { "no" }
+            ));
+        }
+        out.push('\n');
+    }
+    if types.is_empty() {
+        out.push_str("_This package provides no classes._\n\n");
+    }
+
+    // Write the functions
+    out.push_str("## Functions\n\n");
+    let mut funcs: Vec<&String> = info.functions.keys().collect();
+    funcs.sort_by_key(|t| t.to_lowercase());
+    for name in &funcs {
+        let func = info.functions.get(*name).unwrap();
+        out.push_str(&format!("### `{name}`\n\n"));
+        out.push_str(&format!(
+            "```\nfunc {}({}) -> {}\n```\n\n",
+            name,
+            func.parameters.iter().map(|p| format!("{}: {}", p.name, DataType::from(&p.data_type))).collect::<Vec<String>>().join(", "),
+            DataType::from(&func.return_type)
+        ));
+        if !func.parameters.is_empty() {
+            out.push_str("| Parameter | Type | Optional | Default |\n");
+            out.push_str("|-----------|------|----------|---------|\n");
+            for p in &func.parameters {
+                out.push_str(&format!(
+                    "| `{}` | `{}` | {} | {} |\n",
+                    md_cell(&p.name),
+                    md_cell(DataType::from(&p.data_type).to_string()),
+                    if p.optional.unwrap_or(false) { "yes" } else { "no" },
+                    p.default.as_ref().map(|d| format!("`{}`", md_cell(d.to_string()))).unwrap_or_else(|| "-".into())
+                ));
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("**Returns:** `{}`\n\n", DataType::from(&func.return_type)));
+    }
+    if funcs.is_empty() {
+        out.push_str("_This package provides no functions._\n\n");
+    }
+
+    // Done
+    out
+}
+
 
 
 
@@ -67,14 +196,24 @@ fn insert_package_in_list(infos: &mut Vec<PackageInfo>, info: PackageInfo) {
 /// - `name`: The name of the package to inspect.
 /// - `version`: The version of the package to inspect.
 /// - `syntax`: The mode of syntax to use for classes & functions. Can be 'bscript', 'bakery' or 'custom'.
+/// - `format`: The output format to use. [`InspectFormat::Markdown`] ignores `syntax`.
 ///
 /// # Returns
 /// Nothing
-pub fn inspect(name: String, version: Version, syntax: String) -> Result<()> {
+pub fn inspect(name: String, version: Version, syntax: String, format: InspectFormat) -> Result<()> {
     let package_dir = ensure_package_dir(&name, Some(&version), false)?;
     let package_file = package_dir.join("package.yml");
 
     if let Ok(info) = PackageInfo::from_path(package_file) {
+        // Switch on the requested output format
+        match format {
+            InspectFormat::Human => {},
+            InspectFormat::Markdown => {
+                print!("{}", inspect_markdown(&info));
+                return Ok(());
+            },
+        }
+
         // _Neatly_ print it
         println!();
         println!(
@@ -263,6 +402,92 @@ pub fn list(latest: bool) -> Result<(), PackageError> {
 
 
 
+/// Checks the local package index for broken entries, and optionally repairs it.
+///
+/// Broken entries (see [`brane_tsk::local::scan_package_index()`]) make every command that needs the local package index fail. Since that
+/// index is always collected from the packages directory, removing them from it is enough to rebuild the index.
+///
+/// # Arguments
+/// - `repair`: If true, removes the broken entries from the packages directory. Otherwise, they are only reported.
+///
+/// # Errors
+/// This function errors if we failed to read the packages directory or, when repairing, failed to remove a broken entry.
+pub fn index(repair: bool) -> Result<(), PackageError> {
+    // Get the directory with the packages
+    let packages_dir: PathBuf = match ensure_packages_dir(false) {
+        Ok(dir) => dir,
+        Err(_) => {
+            println!("No packages found.");
+            return Ok(());
+        },
+    };
+
+    // Scan it for broken entries
+    let (index, broken) = brane_tsk::local::scan_package_index(&packages_dir).map_err(|source| PackageError::IndexError { source })?;
+    for (_, err) in &broken {
+        println!("{}: {}", style("warning").yellow().bold(), err.trace());
+    }
+    if broken.is_empty() {
+        println!("Local package index is healthy ({} package version(s))", style(index.packages.len()).bold().cyan());
+        return Ok(());
+    }
+    if !repair {
+        println!();
+        println!(
+            "Found {} broken entr{} in the local package index; run `brane package index --repair` to remove them",
+            style(broken.len()).bold().yellow(),
+            if broken.len() == 1 { "y" } else { "ies" }
+        );
+        return Ok(());
+    }
+
+    // Remove them
+    let mut removed: usize = 0;
+    for (path, _) in broken {
+        // Don't remove a version that is still being built or pulled
+        let is_version: bool = path.parent() != Some(packages_dir.as_path());
+        let has_version: bool = path.file_name().is_some_and(|version| Version::from_str(&version.to_string_lossy()).is_ok());
+        let _lock: Option<FileLock> = match has_version {
+            true if is_version => match FileLock::try_lock(path.join(".lock")) {
+                Ok(Some(lock)) => Some(lock),
+                Ok(None) => {
+                    println!("{}: skipped '{}', which is in use by another operation", style("warning").yellow().bold(), path.display());
+                    continue;
+                },
+                Err(err) => {
+                    println!("{}: skipped '{}', which is in use ({})", style("warning").yellow().bold(), path.display(), err);
+                    continue;
+                },
+            },
+            _ => None,
+        };
+
+        debug!("Removing broken package index entry '{}'...", path.display());
+        fs::remove_dir_all(&path).map_err(|source| PackageError::BrokenEntryRemoveError { path: path.clone(), source })?;
+        println!("Removed '{}'", path.display());
+        removed += 1;
+
+        // If that was the last version of its package, the package is gone too
+        if let Some(package_dir) = path.parent().filter(|_| is_version) {
+            if fs::read_dir(package_dir).is_ok_and(|mut entries| entries.next().is_none()) {
+                fs::remove_dir(package_dir).map_err(|source| PackageError::BrokenEntryRemoveError { path: package_dir.into(), source })?;
+            }
+        }
+    }
+
+    // Done
+    println!();
+    println!(
+        "Repaired the local package index by removing {} broken entr{} ({} package version(s) left)",
+        style(removed).bold().cyan(),
+        if removed == 1 { "y" } else { "ies" },
+        style(index.packages.len()).bold().cyan()
+    );
+    Ok(())
+}
+
+
+
 /// **Edited: now working with new versions.**
 ///
 /// Loads the given package to the local Docker daemon.
@@ -270,10 +495,11 @@ pub fn list(latest: bool) -> Result<(), PackageError> {
 /// **Arguments**
 ///  * `name`: The name of the package to load.
 ///  * `version`: The Version of the package to load. Might be an unresolved 'latest'.
+///  * `allow_downgrade`: If true, loads the package even if a newer version of it is already loaded in the local Docker daemon.
 ///
 /// **Returns**  
 /// Nothing on success, or else an error.
-pub async fn load(name: String, version: Version) -> Result<()> {
+pub async fn load(name: String, version: Version, allow_downgrade: bool) -> Result<()> {
     debug!("Loading package '{}' (version {})", name, &version);
 
     let package_dir = ensure_package_dir(&name, Some(&version), false)?;
@@ -281,6 +507,18 @@ pub async fn load(name: String, version: Version) -> Result<()> {
         return Err(anyhow!("Package not found."));
     }
 
+    // If only the metadata was pulled, pull the image first
+    if package_dir.join(IMAGE_ABSENT_FILE).exists() {
+        let package_info = PackageInfo::from_path(package_dir.join("package.yml"))?;
+        println!("Image of package {} (version {}) has not been pulled yet; pulling it now...", style(&name).bold().cyan(), package_info.version);
+        registry::pull(vec![(package_info.name, package_info.version)], None, false, DownloadLimit::implicit()).await?;
+    }
+
+    // Make sure nobody is modifying the package while we import it
+    let _lock: FileLock = FileLock::lock_timeout(package_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+        .await
+        .map_err(|source| PackageError::PackageLockError { name: name.clone(), version, source })?;
+
     let package_info = PackageInfo::from_path(package_dir.join("package.yml"))?;
     let image = format!("{}:{}", package_info.name, package_info.version);
     let image_file = package_dir.join("image.tar");
@@ -293,6 +531,29 @@ pub async fn load(name: String, version: Version) -> Result<()> {
         return Ok(());
     }
 
+    // Refuse to clobber a newer version that is already loaded, unless told otherwise
+    let versions_dir = package_dir.parent().unwrap_or(&package_dir);
+    let versions: Vec<Version> =
+        get_package_versions(&name, versions_dir).map_err(|source| PackageError::LocalVersionsError { name: name.clone(), source })?;
+    let mut newer: Option<Version> = None;
+    for v in versions.into_iter().filter(|v| *v > package_info.version) {
+        if docker.inspect_image(&format!("{}:{}", package_info.name, v)).await.is_ok() && newer.map(|n| v > n).unwrap_or(true) {
+            newer = Some(v);
+        }
+    }
+    if let Some(newer) = newer {
+        if !allow_downgrade {
+            return Err(PackageError::DowngradeError { name, version: package_info.version, newer }.into());
+        }
+        println!(
+            "{} Loading version {} of package {} even though newer version {} is already loaded in the local Docker daemon.",
+            style("WARNING:").bold().yellow(),
+            style(&package_info.version).bold(),
+            style(&name).bold().cyan(),
+            style(&newer).bold()
+        );
+    }
+
     println!("Image doesn't exist in Docker deamon: importing...");
     let options = ImportImageOptions { quiet: true };
 
@@ -329,6 +590,255 @@ pub async fn load(name: String, version: Version) -> Result<()> {
 
 
 
+/// Prints the location of a local package on disk.
+///
+/// # Arguments
+/// - `name`: The name of the package to locate.
+/// - `version`: The version of the package to locate. Might be an unresolved 'latest'.
+/// - `image`: If true, prints the path of the package's `image.tar` instead of its directory.
+///
+/// # Returns
+/// Nothing, but does print the path to stdout in a machine-readable fashion.
+///
+/// # Errors
+/// This function errors if the package (or the given version of it) does not exist.
+pub fn which(name: String, version: Version, image: bool) -> Result<(), PackageError> {
+    // Resolve the package first to give accurate errors
+    ensure_package_dir(&name, None, false).map_err(|source| PackageError::PackageError { name: name.clone(), source })?;
+    let package_dir: PathBuf =
+        ensure_package_dir(&name, Some(&version), false).map_err(|source| PackageError::PackageVersionError { name, version, source })?;
+
+    // Print the requested path
+    if image {
+        println!("{}", package_dir.join("image.tar").display());
+    } else {
+        println!("{}", package_dir.display());
+    }
+    Ok(())
+}
+
+/// Exports a local package to a portable `.tar.gz` archive.
+///
+/// The archive contains the package's files and a [`checksums::MANIFEST_NAME`] manifest listing the SHA-256 digest of every one of them, such
+/// that the receiving end can check the package hasn't been tampered with (see [`import_archive()`]).
+///
+/// # Arguments
+/// - `name`: The name of the package to export.
+/// - `version`: The version of the package to export. Might be an unresolved 'latest'.
+/// - `output`: The path of the archive to write.
+///
+/// # Errors
+/// This function errors if the package does not exist or if we failed to stage, hash or archive it.
+pub async fn export(name: String, version: Version, output: PathBuf) -> Result<(), PackageError> {
+    debug!("Exporting package '{}' (version {}) to '{}'", name, version, output.display());
+
+    // Resolve the package
+    let package_dir: PathBuf = ensure_package_dir(&name, Some(&version), false).map_err(|source| PackageError::PackageVersionError {
+        name: name.clone(),
+        version,
+        source,
+    })?;
+    let package_info_path: PathBuf = package_dir.join("package.yml");
+    let package_info: PackageInfo =
+        PackageInfo::from_path(package_info_path.clone()).map_err(|source| PackageError::PackageInfoError { path: package_info_path, source })?;
+
+    // Stage a copy of it while nobody is modifying it
+    let tmp: TempDir = TempDir::new().map_err(|source| PackageError::TempDirError { source })?;
+    let stage_dir: PathBuf = tmp.path().join(format!("{}-{}", package_info.name, package_info.version));
+    {
+        let _lock: FileLock = FileLock::lock_timeout(package_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+            .await
+            .map_err(|source| PackageError::PackageLockError { name: name.clone(), version: package_info.version, source })?;
+        brane_shr::fs::copy_dir_recursively_async(&package_dir, &stage_dir).await.map_err(|source| PackageError::PackageCopyError {
+            source_dir: package_dir.clone(),
+            target: stage_dir.clone(),
+            source,
+        })?;
+    }
+    let lock_path: PathBuf = stage_dir.join(".lock");
+    if lock_path.exists() {
+        tfs::remove_file(&lock_path).await.map_err(|source| PackageError::StagedFileRemoveError { path: lock_path, source })?;
+    }
+
+    // Write the manifest, then archive the lot
+    checksums::write_manifest(&stage_dir).await.map_err(|source| PackageError::ChecksumError { what: "generate", dir: stage_dir.clone(), source })?;
+    brane_shr::fs::archive_async(&stage_dir, &output, true).await.map_err(|source| PackageError::ArchiveError {
+        dir: stage_dir.clone(),
+        path: output.clone(),
+        source,
+    })?;
+
+    // Done
+    println!(
+        "Successfully exported version {} of package {} to '{}'",
+        style(&package_info.version).bold().cyan(),
+        style(&package_info.name).bold().cyan(),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Imports a package from an archive produced by [`export()`] into the local repository.
+///
+/// # Arguments
+/// - `file`: The path of the archive to import.
+/// - `verify`: If true, checks every file in the archive against its [`checksums::MANIFEST_NAME`] manifest before installing anything.
+///
+/// # Errors
+/// This function errors if we failed to extract the archive, if verification is enabled and fails, or if the package is already installed.
+pub async fn import_archive(file: PathBuf, verify: bool) -> Result<(), PackageError> {
+    debug!("Importing package archive '{}'", file.display());
+
+    // Extract the archive
+    let tmp: TempDir = TempDir::new().map_err(|source| PackageError::TempDirError { source })?;
+    let stage_dir: PathBuf = tmp.path().join("package");
+    brane_shr::fs::unarchive_async(&file, &stage_dir).await.map_err(|source| PackageError::UnarchiveError {
+        path: file.clone(),
+        dir: stage_dir.clone(),
+        source,
+    })?;
+
+    // Check it hasn't been tampered with before we trust anything in it
+    if verify {
+        checksums::verify_manifest(&stage_dir).await.map_err(|source| PackageError::ChecksumError {
+            what: "verify",
+            dir: stage_dir.clone(),
+            source,
+        })?;
+    }
+    let package_info_path: PathBuf = stage_dir.join("package.yml");
+    let package_info: PackageInfo =
+        PackageInfo::from_path(package_info_path.clone()).map_err(|source| PackageError::PackageInfoError { path: package_info_path, source })?;
+    let (name, version): (String, Version) = (package_info.name, package_info.version);
+    assert_valid_entry_name(&name).map_err(|source| PackageError::UtilError { source })?;
+
+    // Refuse to overwrite an existing package
+    let package_dir: PathBuf = get_package_dir(&name, Some(&version)).map_err(|source| PackageError::UtilError { source })?;
+    if package_dir.join("package.yml").exists() {
+        return Err(PackageError::PackageExistsError { name, version });
+    }
+    let package_dir: PathBuf = ensure_package_dir(&name, Some(&version), true).map_err(|source| PackageError::UtilError { source })?;
+
+    // Move the files in, except for the manifest
+    let _lock: FileLock = FileLock::lock_timeout(package_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+        .await
+        .map_err(|source| PackageError::PackageLockError { name: name.clone(), version, source })?;
+    let manifest_path: PathBuf = stage_dir.join(checksums::MANIFEST_NAME);
+    if manifest_path.exists() {
+        tfs::remove_file(&manifest_path).await.map_err(|source| PackageError::StagedFileRemoveError { path: manifest_path, source })?;
+    }
+    brane_shr::fs::copy_dir_recursively_async(&stage_dir, &package_dir).await.map_err(|source| PackageError::PackageCopyError {
+        source_dir: stage_dir.clone(),
+        target: package_dir.clone(),
+        source,
+    })?;
+
+    // Done
+    println!("Successfully imported version {} of package {}", style(&version).bold().cyan(), style(&name).bold().cyan());
+    Ok(())
+}
+
+/// Creates a new version of a local package that aliases an existing one, i.e., without rebuilding it.
+///
+/// The files of the existing version are hardlinked into the new version (or copied if that's not possible, e.g., across filesystems),
+/// and its image is tagged with the new version if it's loaded in the local Docker daemon.
+///
+/// # Arguments
+/// - `name`: The name of the package to tag.
+/// - `version`: The existing version of the package to alias. Might be an unresolved 'latest'.
+/// - `new_version`: The version to create.
+/// - `docker_opts`: Configuration for how to connect to the local Docker daemon.
+///
+/// # Errors
+/// This function errors if the existing version does not exist, if the new version already exists, or if we failed to alias the files or
+/// the image.
+pub async fn tag(name: String, version: Version, new_version: Version, docker_opts: DockerOptions) -> Result<(), PackageError> {
+    debug!("Tagging package '{}' (version {}) as version {}", name, version, new_version);
+    if new_version.is_latest() {
+        return Err(PackageError::TagLatestError { name });
+    }
+
+    // Resolve the existing package
+    let package_dir: PathBuf = ensure_package_dir(&name, Some(&version), false).map_err(|source| PackageError::PackageVersionError {
+        name: name.clone(),
+        version,
+        source,
+    })?;
+    let _lock: FileLock = FileLock::lock_timeout(package_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+        .await
+        .map_err(|source| PackageError::PackageLockError { name: name.clone(), version, source })?;
+    let package_info_path: PathBuf = package_dir.join("package.yml");
+    let mut package_info: PackageInfo = PackageInfo::from_path(package_info_path.clone())
+        .map_err(|source| PackageError::PackageInfoError { path: package_info_path.clone(), source })?;
+    let version: Version = package_info.version;
+
+    // Refuse to overwrite an existing version
+    let new_dir: PathBuf = get_package_dir(&name, Some(&new_version)).map_err(|source| PackageError::UtilError { source })?;
+    if new_dir.exists() {
+        return Err(PackageError::TagExistsError { name, version: new_version });
+    }
+    let new_dir: PathBuf = ensure_package_dir(&name, Some(&new_version), true).map_err(|source| PackageError::UtilError { source })?;
+    let _new_lock: FileLock = FileLock::lock_timeout(new_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+        .await
+        .map_err(|source| PackageError::PackageLockError { name: name.clone(), version: new_version, source })?;
+
+    let result: Result<bool, PackageError> = async {
+        // Alias the files, except for the lock and the package info (which gets the new version)
+        let entries = fs::read_dir(&package_dir).map_err(|source| PackageError::PackageDirReadError { dir: package_dir.clone(), source })?;
+        for entry in entries {
+            let entry = entry.map_err(|source| PackageError::PackageDirReadError { dir: package_dir.clone(), source })?;
+            let file_name = entry.file_name();
+            if file_name == ".lock" || file_name == "package.yml" {
+                continue;
+            }
+
+            let original: PathBuf = entry.path();
+            let target: PathBuf = new_dir.join(&file_name);
+            if original.is_dir() {
+                brane_shr::fs::copy_dir_recursively_async(&original, &target).await.map_err(|source| PackageError::PackageCopyError {
+                    source_dir: original.clone(),
+                    target: target.clone(),
+                    source,
+                })?;
+            } else if fs::hard_link(&original, &target).is_err() {
+                fs::copy(&original, &target).map_err(|source| PackageError::TagFileError { original, target, source })?;
+            }
+        }
+        package_info.version = new_version;
+        let new_info_path: PathBuf = new_dir.join("package.yml");
+        package_info.to_path(&new_info_path).map_err(|source| PackageError::PackageInfoWriteError { path: new_info_path, source })?;
+
+        // Tag the image too if it's already loaded
+        let image: Image = Image::new(&name, Some(version.to_string()), package_info.digest.clone());
+        docker::tag_image(&docker_opts, &image, new_version.to_string())
+            .await
+            .map_err(|source| PackageError::DockerTagError { image: Box::new(image), source })
+    }
+    .await;
+    let tagged: bool = match result {
+        Ok(tagged) => tagged,
+        Err(err) => {
+            // Don't leave a half-tagged version behind
+            if let Err(err) = fs::remove_dir_all(&new_dir) {
+                warn!("Failed to remove partially tagged package directory '{}': {}", new_dir.display(), err);
+            }
+            return Err(err);
+        },
+    };
+    if !tagged {
+        debug!("Image of package '{}' (version {}) is not loaded; it will be loaded with the new version when first used", name, version);
+    }
+
+    // Done
+    println!(
+        "Successfully tagged version {} of package {} as version {}",
+        style(&version).bold().cyan(),
+        style(&name).bold().cyan(),
+        style(&new_version).bold().cyan()
+    );
+    Ok(())
+}
+
 /// **Edited: now working with new versions.**
 ///
 /// Removes the given list of packages from the local repository.
@@ -336,11 +846,25 @@ pub async fn load(name: String, version: Version) -> Result<()> {
 /// # Arguments
 ///  - `force`: Whether or not to force removal (remove the image from the Docker daemon even if there are still containers using it).
 ///  - `packages`: The list of (name, Version) pairs to remove.
+///  - `constraint`: If given, only removes the versions of packages that match it. The packages must then be given without a version. Unless
+///    `force` is given, refuses to remove all versions of a package this way.
 ///  - `docker_opts`: Configuration for how to connect to the local Docker daemon.
 ///
 /// # Returns  
 /// Nothing on success, or else an error.
-pub async fn remove(force: bool, packages: Vec<(String, Version)>, docker_opts: DockerOptions) -> Result<(), PackageError> {
+pub async fn remove(
+    force: bool,
+    packages: Vec<(String, Version)>,
+    constraint: Option<VersionConstraint>,
+    docker_opts: DockerOptions,
+) -> Result<(), PackageError> {
+    // A constraint selects versions itself, so it can't be combined with explicit ones
+    if constraint.is_some() {
+        if let Some((name, version)) = packages.iter().find(|(_, version)| !version.is_latest()) {
+            return Err(PackageError::ConstraintWithVersion { name: name.clone(), version: *version });
+        }
+    }
+
     // Iterate over the packages
     for (name, version) in packages {
         // Remove without confirmation if explicity stated package version.
@@ -363,6 +887,11 @@ pub async fn remove(force: bool, packages: Vec<(String, Version)>, docker_opts:
                 }
             }
 
+            // Lock the package directory so we don't remove it from under a concurrent build, pull or load
+            let _lock: FileLock = FileLock::lock_timeout(package_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+                .await
+                .map_err(|source| PackageError::PackageLockError { name: name.clone(), version, source })?;
+
             // If we got permission, get the digest of this version
             let package_info_path = package_dir.join("package.yml");
             let package_info = PackageInfo::from_path(package_info_path.clone())
@@ -434,6 +963,23 @@ pub async fn remove(force: bool, packages: Vec<(String, Version)>, docker_opts:
             },
         };
 
+        // Only keep the versions matching the constraint, if any
+        let versions: Vec<Version> = match &constraint {
+            Some(constraint) => {
+                let total: usize = versions.len();
+                let versions: Vec<Version> = versions.into_iter().filter(|version| constraint.matches(version)).collect();
+                if versions.is_empty() {
+                    println!("No versions of package {} match '{}'", style(&name).bold().cyan(), style(constraint).bold());
+                    continue;
+                }
+                if versions.len() == total && !force {
+                    return Err(PackageError::ConstraintMatchesAll { name, constraint: constraint.clone() });
+                }
+                versions
+            },
+            None => versions,
+        };
+
         // Ask for permission, if --force is not provided
         if !force {
             println!("Are you sure you want to remove the following version(s) of package {}?", style(&name).bold().cyan());
@@ -447,6 +993,16 @@ pub async fn remove(force: bool, packages: Vec<(String, Version)>, docker_opts:
             }
         }
 
+        // Lock all versions first so we don't remove any of them from under a concurrent build, pull or load
+        let mut locks: Vec<FileLock> = Vec::with_capacity(versions.len());
+        for version in &versions {
+            locks.push(
+                FileLock::lock_timeout(package_dir.join(version.to_string()).join(".lock"), PACKAGE_LOCK_TIMEOUT)
+                    .await
+                    .map_err(|source| PackageError::PackageLockError { name: name.clone(), version: *version, source })?,
+            );
+        }
+
         // Check if image is locally loaded in Docker and if so, remove it there first
         for version in &versions {
             // Get the digest of this version
@@ -461,6 +1017,36 @@ pub async fn remove(force: bool, packages: Vec<(String, Version)>, docker_opts:
         }
 
         // Remove the package files
+        if constraint.is_some() {
+            // Only remove the matched versions, and the package itself if none are left
+            for version in versions {
+                let version_dir: PathBuf = package_dir.join(version.to_string());
+                fs::remove_dir_all(&version_dir).map_err(|source| PackageError::PackageRemoveError {
+                    name: name.clone(),
+                    version,
+                    dir: version_dir,
+                    source,
+                })?;
+                println!("Removed version {} of package {}", style(&version).bold().cyan(), style(&name).bold().cyan());
+            }
+            match fs::read_dir(&package_dir) {
+                Ok(versions) => {
+                    if versions.count() == 0 {
+                        fs::remove_dir_all(&package_dir).map_err(|source| PackageError::PackageRemoveError {
+                            name: name.clone(),
+                            version,
+                            dir: package_dir,
+                            source,
+                        })?;
+                        println!("Successfully removed package {}", style(&name).bold().cyan());
+                    }
+                },
+                Err(source) => {
+                    return Err(PackageError::VersionsError { name, dir: package_dir, source });
+                },
+            }
+            continue;
+        }
         fs::remove_dir_all(&package_dir).map_err(|source| PackageError::PackageRemoveError {
             name: name.clone(),
             version,
@@ -20,8 +20,10 @@ use std::sync::Arc;
 
 use brane_ast::Workflow;
 use brane_ast::ast::{Edge, SymTable};
+use brane_shr::utilities::closest_names;
 use brane_tsk::errors::PlanError;
 use brane_tsk::spec::{LOCALHOST, Planner};
+use brane_tsk::tools::expand_result_patterns;
 use log::debug;
 use parking_lot::Mutex;
 use specifications::data::{AccessKind, AvailabilityKind, DataIndex, DataName};
@@ -69,7 +71,8 @@ fn plan_edges(
                 *at = Some(LOCALHOST.into());
                 debug!("Task '{}' planned at '{}'", table.tasks[*task].name(), LOCALHOST);
 
-                // For all dataset/intermediate result inputs, we assert they are available on the local location
+                // Expand any intermediate result patterns to the results they match, then assert all inputs are available on the local location
+                expand_result_patterns(input, &table.results, deferred)?;
                 for (name, avail) in input {
                     OfflinePlanner::plan_data(name, avail, dindex, &table.results, deferred)?;
                 }
@@ -220,6 +223,7 @@ fn plan_deferred(table: &SymTable, edges: &mut [Edge], pc: usize, merge: Option<
             // This is the node where it all revolves around, in the end
             Edge::Node { input, next, .. } => {
                 // This next trick involves checking if the node has any unresolved results as input, then trying to resolve them
+                expand_result_patterns(input, &table.results, false)?;
                 for (name, avail) in input {
                     // Continue if it already has a resolved availability
                     if avail.is_some() {
@@ -387,7 +391,10 @@ impl OfflinePlanner {
                         return Err(PlanError::DatasetUnavailable { name: name.clone(), locs: vec![] });
                     }
                 } else {
-                    return Err(PlanError::UnknownDataset { name: name.clone() });
+                    return Err(PlanError::UnknownDataset {
+                        name: name.clone(),
+                        suggestions: closest_names(name, dindex.iter().map(|info| &info.name)),
+                    });
                 }
             },
 
@@ -1,22 +1,27 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 
 use anyhow::Result;
+use brane_shr::fs::FileLock;
+use brane_tsk::docker::{self, sha256_file};
 use brane_tsk::local::get_package_versions;
 use chrono::{DateTime, Utc};
 use console::{Alignment, pad_str, style};
 use dialoguer::Confirm;
 use flate2::Compression;
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use graphql_client::{GraphQLQuery, Response};
 use indicatif::{ProgressBar, ProgressStyle};
 use prettytable::Table;
 use prettytable::format::FormatBuilder;
 use reqwest::{self, Body, Client};
-use specifications::package::{PackageInfo, PackageKind};
+use specifications::package::{PackageCompression, PackageInfo, PackageKind};
 use specifications::version::Version;
 use tokio::fs::File as TokioFile;
 use tokio_util::codec::{BytesCodec, FramedRead};
@@ -24,13 +29,369 @@ use uuid::Uuid;
 
 use crate::errors::RegistryError;
 use crate::instance::InstanceInfo;
-use crate::utils::{ensure_package_dir, ensure_packages_dir, get_packages_dir};
+use crate::spec::{Checksum, DownloadLimit};
+use crate::utils::{ensure_package_dir, ensure_packages_dir, get_packages_dir, http_client, out_of_space_or};
+use crate::{IMAGE_ABSENT_FILE, PACKAGE_LOCK_TIMEOUT, index_cache};
 
 
 type DateTimeUtc = DateTime<Utc>;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let contents: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        for compression in [PackageCompression::Gzip, PackageCompression::Zstd, PackageCompression::None] {
+            let archive = dir.path().join(format!("archive.{compression}"));
+            let target = dir.path().join(format!("image.{compression}.tar"));
+
+            // Compress it like `push()` does, then decompress it like `pull()` does
+            {
+                let handle: File = File::create(&archive).unwrap();
+                let mut writer: Box<dyn Write> = compress_writer(compression, &handle).unwrap();
+                writer.write_all(&contents).unwrap();
+            }
+            decompress_archive(compression, &archive, &target).unwrap();
+            assert_eq!(fs::read(&target).unwrap(), contents, "Roundtrip failed for {compression}");
+        }
+    }
+
+    #[test]
+    fn test_paginate() {
+        let items: Vec<usize> = (0..10).collect();
+        assert_eq!(paginate(items.clone(), 0, None), items);
+        assert_eq!(paginate(items.clone(), 3, Some(4)), vec![3, 4, 5, 6]);
+        assert_eq!(paginate(items.clone(), 8, Some(4)), vec![8, 9]);
+        assert!(paginate(items, 12, Some(4)).is_empty());
+    }
+
+    #[test]
+    fn test_sort_by_name_version() {
+        let mut items: Vec<(&str, &str)> =
+            vec![("b", "1.0.0"), ("a", "1.10.0"), ("a", "invalid"), ("a", "1.9.0"), ("c", "0.1.0"), ("a", "1.9.0-alpha")];
+        sort_by_name_version(&mut items, |item| *item);
+        assert_eq!(items, vec![("a", "1.9.0-alpha"), ("a", "1.9.0"), ("a", "1.10.0"), ("a", "invalid"), ("b", "1.0.0"), ("c", "0.1.0")]);
+    }
+}
+
+
+
+
+
 /***** HELPER FUNCTIONS *****/
+/// Wraps the given file in a writer that compresses everything written to it.
+///
+/// The compression is finished when the returned writer is dropped.
+///
+/// # Arguments
+/// - `compression`: The [`PackageCompression`] to compress with.
+/// - `handle`: The file to write the compressed data to.
+///
+/// # Errors
+/// This function errors if we failed to setup the compressor.
+fn compress_writer(compression: PackageCompression, handle: &File) -> std::io::Result<Box<dyn Write + '_>> {
+    Ok(match compression {
+        PackageCompression::Gzip => Box::new(GzEncoder::new(handle, Compression::fast())),
+        PackageCompression::Zstd => Box::new(zstd::Encoder::new(handle, 0)?.auto_finish()),
+        PackageCompression::None => Box::new(handle),
+    })
+}
+
+/// Writes a downloaded package archive to the given target, decompressing it if necessary.
+///
+/// # Arguments
+/// - `compression`: The [`PackageCompression`] of the downloaded archive.
+/// - `source`: The path of the downloaded archive.
+/// - `target`: The path to write the (uncompressed) image to.
+///
+/// # Errors
+/// This function errors if we failed to read, decompress or write the archive.
+fn decompress_archive(compression: PackageCompression, source: &Path, target: &Path) -> std::io::Result<()> {
+    let handle: File = File::open(source)?;
+    let mut reader: Box<dyn Read> = match compression {
+        PackageCompression::Gzip => Box::new(GzDecoder::new(handle)),
+        PackageCompression::Zstd => Box::new(zstd::Decoder::new(handle)?),
+        PackageCompression::None => Box::new(handle),
+    };
+    let mut target: File = File::create(target)?;
+    std::io::copy(&mut reader, &mut target)?;
+    Ok(())
+}
+
+/// Sorts search results by name and then by version, such that paging through them with [`paginate()`] is stable.
+///
+/// Versions that cannot be parsed are sorted after those that can, by their text.
+///
+/// # Arguments
+/// - `items`: The results to sort.
+/// - `key`: Returns the name and version of a result.
+fn sort_by_name_version<T>(items: &mut [T], key: impl Fn(&T) -> (&str, &str)) {
+    items.sort_by(|a, b| {
+        let ((aname, aversion), (bname, bversion)): ((&str, &str), (&str, &str)) = (key(a), key(b));
+        aname.cmp(bname).then_with(|| match (Version::from_str(aversion), Version::from_str(bversion)) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => aversion.cmp(bversion),
+        })
+    });
+}
+
+/// Selects a single page of search results.
+///
+/// # Arguments
+/// - `items`: All the results, in the order in which they are paged.
+/// - `offset`: The number of results to skip.
+/// - `limit`: The maximum number of results on the page, if any.
+///
+/// # Returns
+/// The results on the page, which may be fewer than `limit` (or none at all) if we run out.
+fn paginate<T>(items: Vec<T>, offset: usize, limit: Option<usize>) -> Vec<T> {
+    items.into_iter().skip(offset).take(limit.unwrap_or(usize::MAX)).collect()
+}
+
+/// Downloads the (compressed) package archive with a package's image to a temporary file.
+///
+/// # Arguments
+/// - `client`: The [`Client`] to download with.
+/// - `url`: The endpoint of the package in the registry.
+/// - `checksum`: If given, the checksum that the downloaded (raw) package archive must have.
+/// - `limit`: The [`DownloadLimit`] that the download must adhere to before it is started.
+///
+/// # Returns
+/// The temporary file with the archive, which is removed when dropped, and how it is compressed.
+///
+/// # Errors
+/// This function errors if the download failed, if it is refused by `limit`, if we failed to write it or if it does not match `checksum`.
+async fn download_image(
+    client: &Client,
+    url: &str,
+    checksum: Option<&Checksum>,
+    limit: &DownloadLimit,
+) -> Result<(tempfile::NamedTempFile, PackageCompression), RegistryError> {
+    let mut temp_file = tempfile::NamedTempFile::new().expect("Failed to create temporary file.");
+    let mut package_archive: reqwest::Response =
+        client.get(url).send().await.map_err(|source| RegistryError::PullRequestError { url: url.into(), source })?;
+    if package_archive.status() != reqwest::StatusCode::OK {
+        return Err(RegistryError::PullRequestFailure { url: url.into(), status: package_archive.status() });
+    }
+
+    // Find out how the archive is compressed (registries that don't say never compress)
+    let compression: PackageCompression = match package_archive.headers().get(PackageCompression::HEADER) {
+        Some(raw) => {
+            let raw: String = String::from_utf8_lossy(raw.as_bytes()).into_owned();
+            PackageCompression::from_str(&raw).map_err(|source| RegistryError::CompressionParseError { url: url.into(), raw, source })?
+        },
+        None => PackageCompression::None,
+    };
+    debug!("Package archive is compressed with: {compression}");
+
+    // Fetch the content length from the response headers, if the registry advertises it
+    let content_length: Option<u64> = match package_archive.headers().get("content-length") {
+        Some(content_length) => {
+            let content_length = content_length.to_str().map_err(|source| RegistryError::ContentLengthStrError { url: url.into(), source })?;
+            Some(content_length.parse().map_err(|source| RegistryError::ContentLengthParseError {
+                url: url.into(),
+                raw: content_length.into(),
+                source,
+            })?)
+        },
+        None => None,
+    };
+    limit.check(url, content_length)?;
+
+    // Write package archive to temporary file
+    let progress = match content_length {
+        Some(content_length) => {
+            let progress = ProgressBar::new(content_length);
+            progress.set_style(
+                ProgressStyle::default_bar()
+                    .template("Downloading... [{elapsed_precise}] {bar:40.cyan/blue} {percent}/100%")
+                    .unwrap()
+                    .progress_chars("##-"),
+            );
+            progress
+        },
+        None => {
+            let progress = ProgressBar::new_spinner();
+            progress.set_style(ProgressStyle::default_spinner().template("Downloading... [{elapsed_precise}] {bytes}").unwrap());
+            progress
+        },
+    };
+
+    let mut received: u64 = 0;
+    while let Some(chunk) = package_archive.chunk().await.map_err(|source| RegistryError::PackageDownloadError { url: url.into(), source })? {
+        received += chunk.len() as u64;
+        limit.check_received(url, received)?;
+        progress.inc(chunk.len() as u64);
+        temp_file.write_all(&chunk).map_err(|source| {
+            out_of_space_or(temp_file.path(), source, |source| RegistryError::PackageWriteError {
+                url: url.into(),
+                path: temp_file.path().into(),
+                source,
+            })
+        })?;
+    }
+
+    progress.finish();
+
+    // Verify the archive before we touch the package directory; the temporary file is removed when dropped
+    if let Some(expected) = checksum {
+        debug!("Verifying package archive checksum...");
+        let compute_err = |source| RegistryError::ChecksumComputeError { url: url.into(), path: temp_file.path().into(), source };
+        let mut handle: TokioFile = TokioFile::open(temp_file.path()).await.map_err(compute_err)?;
+        let got: Checksum = Checksum(sha256_file(&mut handle).await.map_err(compute_err)?);
+        if &got != expected {
+            return Err(RegistryError::ChecksumMismatch { url: url.into(), expected: expected.clone(), got });
+        }
+    }
+    Ok((temp_file, compression))
+}
+
+/// Fetches the metadata of a package from the Brane API's GraphQL endpoint.
+///
+/// # Arguments
+/// - `client`: The [`Client`] to fetch with.
+/// - `url`: The endpoint of the package in the registry, used for errors.
+/// - `name`: The name of the package to fetch the metadata of.
+/// - `version`: The version of the package to fetch the metadata of. May be 'latest'.
+///
+/// # Returns
+/// The package's metadata as a [`PackageInfo`].
+///
+/// # Errors
+/// This function errors if the request failed or if the API returned no (or invalid) metadata.
+async fn fetch_package_info(client: &Client, url: &str, name: &str, version: Version) -> Result<PackageInfo, RegistryError> {
+    // Compile the GraphQL schema
+    #[derive(GraphQLQuery)]
+    #[graphql(schema_path = "src/graphql/api_schema.json", query_path = "src/graphql/get_package.graphql", response_derives = "Debug")]
+    pub struct GetPackage;
+
+    let graphql_endpoint = get_graphql_endpoint()?;
+    debug!("Fetching package metadata from '{}'...", graphql_endpoint);
+
+    // Prepare GraphQL query.
+    let variables = get_package::Variables { name: name.into(), version: version.to_string() };
+    let graphql_query = GetPackage::build_query(variables);
+
+    // Request/response for GraphQL query.
+    let graphql_response = client
+        .post(&graphql_endpoint)
+        .json(&graphql_query)
+        .send()
+        .await
+        .map_err(|source| RegistryError::GraphQLRequestError { url: graphql_endpoint.clone(), source })?;
+    let graphql_response: Response<get_package::ResponseData> =
+        graphql_response.json().await.map_err(|source| RegistryError::GraphQLResponseError { url: graphql_endpoint.clone(), source })?;
+
+    // Attempt to parse the response data as a PackageInfo
+    let Some(data) = graphql_response.data else {
+        // The server did not return a package info at all :(
+        return Err(RegistryError::NoPackageInfo { url: url.into() });
+    };
+
+    // Extract the packages from the list
+    let package = data.packages.first().ok_or_else(|| RegistryError::NoPackageInfo { url: url.into() })?;
+
+    // Parse the package kind first
+    let kind = PackageKind::from_str(&package.kind).map_err(|source| RegistryError::KindParseError {
+        url: url.into(),
+        raw: package.kind.clone(),
+        source,
+    })?;
+
+    // Next, the version
+    let version = Version::from_str(&package.version).map_err(|source| RegistryError::VersionParseError {
+        url: url.into(),
+        raw: package.version.clone(),
+        source,
+    })?;
+
+    let functions: HashMap<String, specifications::common::Function> = match package.functions_as_json.as_ref() {
+        Some(functions) => serde_json::from_str(functions).map_err(|source| RegistryError::FunctionsParseError {
+            url: url.into(),
+            raw: functions.clone(),
+            source,
+        })?,
+        None => HashMap::new(),
+    };
+
+    let types: HashMap<String, specifications::common::Type> = match package.types_as_json.as_ref() {
+        Some(types) => {
+            serde_json::from_str(types).map_err(|source| RegistryError::TypesParseError { url: url.into(), raw: types.clone(), source })?
+        },
+        None => HashMap::new(),
+    };
+
+    let labels: HashMap<String, String> = match package.labels_as_json.as_ref() {
+        Some(labels) => {
+            serde_json::from_str(labels).map_err(|source| RegistryError::LabelsParseError { url: url.into(), raw: labels.clone(), source })?
+        },
+        None => HashMap::new(),
+    };
+
+    // Finally, combine everything in a fully-fledged PackageInfo
+    Ok(PackageInfo {
+        created: package.created,
+        description: package.description.clone().unwrap_or_default(),
+        detached: package.detached,
+        digest: package.digest.clone(),
+        functions,
+        id: package.id,
+        kind,
+        name: package.name.clone(),
+        owners: package.owners.clone(),
+        types,
+        version,
+        labels,
+    })
+}
+
+/// Revalidates the locally known metadata of a package against the registry, using its `ETag`.
+///
+/// # Arguments
+/// - `client`: The [`Client`] to revalidate with.
+/// - `url`: The endpoint of the package in the registry.
+/// - `cached`: The [`PackageInfo`] we already have of this package version.
+///
+/// # Returns
+/// `Some(None)` if `cached` is still current, `Some(Some(info))` with the new metadata if it has changed, or [`None`] if the registry could
+/// not tell us (e.g., because it does not support revalidation). In the latter case, the metadata should be fetched as usual.
+async fn revalidate_package_info(client: &Client, url: &str, cached: &PackageInfo) -> Option<Option<PackageInfo>> {
+    let etag: String = cached.etag()?;
+    let info_url: String = format!("{url}/info");
+    debug!("Revalidating package metadata with '{}' (ETag {})...", info_url, etag);
+
+    // Any failure here just means we fall back to fetching the metadata
+    let res: reqwest::Response = match client.get(&info_url).header("If-None-Match", &etag).send().await {
+        Ok(res) => res,
+        Err(err) => {
+            debug!("Failed to revalidate package metadata with '{info_url}': {err}");
+            return None;
+        },
+    };
+    match res.status() {
+        reqwest::StatusCode::NOT_MODIFIED => Some(None),
+        reqwest::StatusCode::OK => match res.json::<PackageInfo>().await {
+            Ok(info) => Some(Some(info)),
+            Err(err) => {
+                debug!("Failed to parse package metadata from '{info_url}': {err}");
+                None
+            },
+        },
+        status => {
+            debug!("Registry '{info_url}' returned {status} when revalidating package metadata");
+            None
+        },
+    }
+}
+
 /// Get the GraphQL endpoint of the Brane API.
 ///
 /// # Returns
@@ -69,162 +430,179 @@ pub fn get_data_endpoint() -> Result<String, RegistryError> {
 
 
 
-/// Pulls packages from a remote registry to the local registry.
+/// Pulls a single package from a remote registry to the local registry, without reporting it to the user.
+///
+/// The metadata is revalidated before anything is downloaded, so that a version that is already installed and up-to-date is not downloaded
+/// again (unless a `checksum` is given, as that can only be checked against a download).
 ///
 /// # Arguments
-/// - `packages`: The list of `NAME[:VERSION]` pairs indicating what to pull.
+/// - `name`: The name of the package to pull.
+/// - `version`: The version of the package to pull. May be 'latest'.
+/// - `checksum`: If given, the checksum that the downloaded (raw) package archive must have.
+/// - `digest`: If given, the digest that the (decompressed) image must have. It is verified before the package directory is touched.
+/// - `only_metadata`: If true, only pulls the package info and marks the image as absent (see [`IMAGE_ABSENT_FILE`]).
+/// - `limit`: The [`DownloadLimit`] that the image download must adhere to.
+///
+/// # Returns
+/// The (resolved) version of the package that was pulled.
 ///
 /// # Errors
-/// This function may error for about a million different reasons, chief of which are the remote not being reachable, the user not being logged-in, not being able to write to the package folder, etc.
-pub async fn pull(packages: Vec<(String, Version)>) -> Result<(), RegistryError> {
-    // Compile the GraphQL schema
-    #[derive(GraphQLQuery)]
-    #[graphql(schema_path = "src/graphql/api_schema.json", query_path = "src/graphql/get_package.graphql", response_derives = "Debug")]
-    pub struct GetPackage;
+/// This function errors if the remote was not reachable, if the download did not match `checksum` or `digest`, or if we failed to write the
+/// package to the package folder.
+pub async fn pull_package(
+    name: &str,
+    version: Version,
+    checksum: Option<&Checksum>,
+    digest: Option<&str>,
+    only_metadata: bool,
+    limit: &DownloadLimit,
+) -> Result<Version, RegistryError> {
+    debug!("Pulling package '{}' version {}", name, version);
+
+    // Get the package directory
+    let packages_dir = get_packages_dir().map_err(|source| RegistryError::PackagesDirError { source })?;
+    let package_dir = packages_dir.join(name);
+
+    // Create the target endpoint for this package
+    let url = format!("{}/{}/{}", get_packages_endpoint()?, name, version);
+    let client: Client = http_client().map_err(|source| RegistryError::HttpClientError { source })?;
+
+    // Retrieve the package information from the API first, which also resolves 'latest'
+    let mut package_info: Option<PackageInfo> = None;
+    let version: Version = if version.is_latest() {
+        let latest: PackageInfo = fetch_package_info(&client, &url, name, version).await?;
+        let version: Version = latest.version;
+        package_info = Some(latest);
+        version
+    } else {
+        version
+    };
+    let version_dir = package_dir.join(version.to_string());
+    if package_info.is_none() {
+        // Don't fetch the metadata if what we have of this version is still current
+        let revalidated: Option<Option<PackageInfo>> = match PackageInfo::from_path(version_dir.join("package.yml")) {
+            Ok(cached) => revalidate_package_info(&client, &url, &cached).await,
+            Err(_) => None,
+        };
+        package_info = match revalidated {
+            Some(package_info) => package_info,
+            None => Some(fetch_package_info(&client, &url, name, version).await?),
+        };
+    }
 
-    // Iterate over the packages
-    for (name, version) in packages {
-        debug!("Pulling package '{}' version {}", name, version);
+    // If we already have this build of the package (and its image, if we want it), there is nothing left to pull
+    let image_path: PathBuf = version_dir.join("image.tar");
+    let absent_path: PathBuf = version_dir.join(IMAGE_ABSENT_FILE);
+    if package_info.is_none() && checksum.is_none() && (only_metadata || (image_path.exists() && !absent_path.exists())) {
+        debug!("Package '{}' version {} is up-to-date; not downloading it again", name, version);
+        return Ok(version);
+    }
 
-        // Get the package directory
+    // Download the image, unless we only want the metadata
+    let image: Option<(tempfile::NamedTempFile, PackageCompression)> = if only_metadata {
+        debug!("Skipping container download (only pulling metadata)");
+        None
+    } else {
         debug!("Downloading container...");
-        let packages_dir = get_packages_dir().map_err(|source| RegistryError::PackagesDirError { source })?;
-        let package_dir = packages_dir.join(&name);
-        let mut temp_file = tempfile::NamedTempFile::new().expect("Failed to create temporary file.");
-
-        // Create the target endpoint for this package
-        let url = format!("{}/{}/{}", get_packages_endpoint()?, name, version);
-        let mut package_archive: reqwest::Response =
-            reqwest::get(&url).await.map_err(|source| RegistryError::PullRequestError { url: url.clone(), source })?;
-
-        if package_archive.status() != reqwest::StatusCode::OK {
-            return Err(RegistryError::PullRequestFailure { url, status: package_archive.status() });
-        }
-
-        // Fetch the content length from the response headers
-        let content_length =
-            package_archive.headers().get("content-length").ok_or_else(|| RegistryError::MissingContentLength { url: url.clone() })?;
-        let content_length = content_length.to_str().map_err(|source| RegistryError::ContentLengthStrError { url: url.clone(), source })?;
-        let content_length: u64 = content_length.parse().map_err(|source| RegistryError::ContentLengthParseError {
-            url: url.clone(),
-            raw: content_length.into(),
-            source,
-        })?;
-
-        // Write package archive to temporary file
-        let progress = ProgressBar::new(content_length);
-        progress.set_style(
-            ProgressStyle::default_bar()
-                .template("Downloading... [{elapsed_precise}] {bar:40.cyan/blue} {percent}/100%")
-                .unwrap()
-                .progress_chars("##-"),
-        );
-
-        while let Some(chunk) = package_archive.chunk().await.map_err(|source| RegistryError::PackageDownloadError { url: url.clone(), source })? {
-            progress.inc(chunk.len() as u64);
-            temp_file.write_all(&chunk).map_err(|source| RegistryError::PackageWriteError {
-                url: url.clone(),
-                path: temp_file.path().into(),
-                source,
-            })?;
-        }
-
-        progress.finish();
-
-        // Retreive package information from API.
-        let client = reqwest::Client::new();
-        let graphql_endpoint = get_graphql_endpoint()?;
-        debug!("Fetching package metadata from '{}'...", graphql_endpoint);
-
-        // Prepare GraphQL query.
-        let variables = get_package::Variables { name: name.clone(), version: version.to_string() };
-        let graphql_query = GetPackage::build_query(variables);
-
-        // Request/response for GraphQL query.
-        let graphql_response = client
-            .post(&graphql_endpoint)
-            .json(&graphql_query)
-            .send()
-            .await
-            .map_err(|source| RegistryError::GraphQLRequestError { url: graphql_endpoint.clone(), source })?;
-        let graphql_response: Response<get_package::ResponseData> =
-            graphql_response.json().await.map_err(|source| RegistryError::GraphQLResponseError { url: graphql_endpoint.clone(), source })?;
-
-        // Attempt to parse the response data as a PackageInfo
-        let version = if let Some(data) = graphql_response.data {
-            // Extract the packages from the list
-            let package = data.packages.first().ok_or_else(|| RegistryError::NoPackageInfo { url: url.clone() })?;
-
-            // Parse the package kind first
-            let kind = PackageKind::from_str(&package.kind).map_err(|source| RegistryError::KindParseError {
-                url: url.clone(),
-                raw: package.kind.clone(),
-                source,
-            })?;
-
-            // Next, the version
-            let version = Version::from_str(&package.version).map_err(|source| RegistryError::VersionParseError {
-                url: url.clone(),
-                raw: package.version.clone(),
-                source,
-            })?;
+        Some(download_image(&client, &url, checksum, limit).await?)
+    };
 
-            let functions: HashMap<String, specifications::common::Function> = match package.functions_as_json.as_ref() {
-                Some(functions) => serde_json::from_str(functions).map_err(|source| RegistryError::FunctionsParseError {
-                    url: url.clone(),
-                    raw: functions.clone(),
+    // Create the directory
+    fs::create_dir_all(&version_dir).map_err(|source| RegistryError::PackageDirCreateError { path: version_dir.clone(), source })?;
+
+    // Lock it for the remainder of the pull, so we don't clash with concurrent builds, loads or removals
+    let lock: FileLock = FileLock::lock_timeout(version_dir.join(".lock"), PACKAGE_LOCK_TIMEOUT)
+        .await
+        .map_err(|source| RegistryError::PackageLockError { name: name.into(), version, source })?;
+
+    // Decompress the image next to where it is installed, and verify it before we touch anything else
+    let image: Option<tempfile::NamedTempFile> = match image {
+        Some((temp_file, compression)) => {
+            let image_file: tempfile::NamedTempFile =
+                tempfile::NamedTempFile::new_in(&version_dir).map_err(|source| RegistryError::TempFileError { source })?;
+            decompress_archive(compression, temp_file.path(), image_file.path()).map_err(|source| {
+                out_of_space_or(image_file.path(), source, |source| RegistryError::PackageCopyError {
+                    original: temp_file.path().into(),
+                    target: image_file.path().into(),
                     source,
-                })?,
-                None => HashMap::new(),
-            };
-
-            let types: HashMap<String, specifications::common::Type> = match package.types_as_json.as_ref() {
-                Some(types) => serde_json::from_str(types).map_err(|source| RegistryError::TypesParseError { url, raw: types.clone(), source })?,
-                None => HashMap::new(),
-            };
-
-            // Finally, combine everything in a fully-fledged PackageInfo
-            let package_info = PackageInfo {
-                created: package.created,
-                description: package.description.clone().unwrap_or_default(),
-                detached: package.detached,
-                digest: package.digest.clone(),
-                functions,
-                id: package.id,
-                kind,
-                name: package.name.clone(),
-                owners: package.owners.clone(),
-                types,
-                version,
-            };
-
-            // Create the directory
-            let package_dir = package_dir.join(version.to_string());
-            fs::create_dir_all(&package_dir).map_err(|source| RegistryError::PackageDirCreateError { path: package_dir.clone(), source })?;
+                })
+            })?;
+            if let Some(expected) = digest {
+                debug!("Verifying image digest...");
+                let got: String = docker::get_digest(image_file.path())
+                    .await
+                    .map_err(|source| RegistryError::ImageDigestError { path: image_file.path().into(), source })?;
+                if got != expected {
+                    return Err(RegistryError::ImageDigestMismatch { name: name.into(), version, expected: expected.into(), got });
+                }
+            }
+            Some(image_file)
+        },
+        None => None,
+    };
 
-            // Write package.yml to package directory
-            let package_info_path = package_dir.join("package.yml");
+    // Write package.yml to package directory
+    match package_info {
+        Some(package_info) => {
+            let package_info_path = version_dir.join("package.yml");
             let handle = File::create(&package_info_path)
                 .map_err(|source| RegistryError::PackageInfoCreateError { path: package_info_path.clone(), source })?;
             serde_yaml::to_writer(handle, &package_info)
                 .map_err(|source| RegistryError::PackageInfoWriteError { path: package_info_path.clone(), source })?;
+        },
+        None => debug!("Metadata of package '{}' version {} is up-to-date", name, version),
+    }
 
-            // Done!
-            version
-        } else {
-            // The server did not return a package info at all :(
-            return Err(RegistryError::NoPackageInfo { url });
-        };
+    // Install the image in the package directory; or mark it as absent if we didn't download it
+    match image {
+        Some(image_file) => {
+            image_file.persist(&image_path).map_err(|err| RegistryError::ImageInstallError { path: image_path, source: err.error })?;
+            if absent_path.exists() {
+                fs::remove_file(&absent_path).map_err(|source| RegistryError::ImageAbsentRemoveError { path: absent_path, source })?;
+            }
+        },
+        None => {
+            let note: &str =
+                "The image of this package has not been pulled (only its metadata); it is pulled when the package is first loaded or run.\n";
+            fs::write(&absent_path, note).map_err(|source| RegistryError::ImageAbsentWriteError { path: absent_path, source })?;
+        },
+    }
+    lock.release();
 
-        // Copy package to package directory.
-        let package_dir = package_dir.join(version.to_string());
-        fs::copy(temp_file.path(), package_dir.join("image.tar")).map_err(|source| RegistryError::PackageCopyError {
-            original: temp_file.path().into(),
-            target: package_dir,
-            source,
-        })?;
+    // Done
+    Ok(version)
+}
+
+/// Pulls packages from a remote registry to the local registry.
+///
+/// # Arguments
+/// - `packages`: The list of `NAME[:VERSION]` pairs indicating what to pull.
+/// - `checksum`: If given, the checksum that the downloaded (raw) package archive must have. Only allowed when pulling a single package.
+/// - `only_metadata`: If true, only pulls the package info and marks the image as absent (see [`IMAGE_ABSENT_FILE`]). It is then pulled when
+///   the package is first loaded or run.
+/// - `limit`: The [`DownloadLimit`] that each image download must adhere to.
+///
+/// # Errors
+/// This function may error for about a million different reasons, chief of which are the remote not being reachable, the user not being logged-in, not being able to write to the package folder, etc.
+pub async fn pull(
+    packages: Vec<(String, Version)>,
+    checksum: Option<Checksum>,
+    only_metadata: bool,
+    limit: DownloadLimit,
+) -> Result<(), RegistryError> {
+    // A checksum is of a single archive, so it makes no sense for multiple packages
+    if checksum.is_some() && packages.len() > 1 {
+        return Err(RegistryError::ChecksumMultiplePackages { count: packages.len() });
+    }
 
-        println!("\nSuccessfully pulled version {} of package {}.", style(&version).bold().cyan(), style(&name).bold().cyan(),);
+    // Iterate over the packages
+    for (name, version) in packages {
+        let version: Version = pull_package(&name, version, checksum.as_ref(), None, only_metadata, &limit).await?;
+        if only_metadata {
+            println!("\nSuccessfully pulled the metadata of version {} of package {}.", style(&version).bold().cyan(), style(&name).bold().cyan());
+        } else {
+            println!("\nSuccessfully pulled version {} of package {}.", style(&version).bold().cyan(), style(&name).bold().cyan());
+        }
     }
 
     // Done
@@ -238,15 +616,20 @@ pub async fn pull(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
 ///
 /// **Arguments**
 ///  * `packages`: A list with name/ID / version pairs of the packages to push.
+///  * `fail_fast`: If true, stops at the first package that fails to upload instead of attempting the rest.
+///  * `compression`: The [`PackageCompression`] with which to compress the package archives before uploading them.
 ///
 /// **Returns**  
 /// Nothing on success, or an anyhow error on failure.
-pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError> {
+pub async fn push(packages: Vec<(String, Version)>, fail_fast: bool, compression: PackageCompression) -> Result<(), RegistryError> {
     // Try to get the general package directory
     let packages_dir = ensure_packages_dir(false).map_err(|source| RegistryError::PackagesDirError { source })?;
     debug!("Using Brane package directory: {}", packages_dir.display());
 
     // Iterate over the packages
+    let total: usize = packages.len();
+    let mut pushed: Vec<(String, Version)> = Vec::with_capacity(total);
+    let mut failed: Vec<(String, Version)> = Vec::new();
     for (name, version) in packages {
         // Add the package name to the general directory
         let package_dir = packages_dir.join(&name);
@@ -283,9 +666,14 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
         progress.set_style(ProgressStyle::default_bar().template("Compressing... [{elapsed_precise}]").unwrap());
         progress.enable_steady_tick(Duration::from_millis(250));
 
-        // Create package tarball, effectively compressing it
-        let gz = GzEncoder::new(&temp_file, Compression::fast());
-        let mut tar = tar::Builder::new(gz);
+        // Create package tarball, compressing it as requested
+        let writer: Box<dyn Write> = compress_writer(compression, &temp_file).map_err(|source| RegistryError::CompressionError {
+            name: name.clone(),
+            version,
+            path: temp_path.clone(),
+            source,
+        })?;
+        let mut tar = tar::Builder::new(writer);
         tar.append_path_with_name(package_dir.join("package.yml"), "package.yml").map_err(|source| RegistryError::CompressionError {
             name: name.clone(),
             version,
@@ -304,7 +692,7 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
         // Upload file (with progress bar, of course)
         let url = get_packages_endpoint()?;
         debug!("Pushing package '{}' to '{}'...", temp_path.display(), url);
-        let request = Client::new().post(&url);
+        let request = http_client().map_err(|source| RegistryError::HttpClientError { source })?.post(&url);
         let progress = ProgressBar::new(0);
         progress.set_style(ProgressStyle::default_bar().template("Uploading...   [{elapsed_precise}]").unwrap());
         progress.enable_steady_tick(Duration::from_millis(250));
@@ -318,37 +706,92 @@ pub async fn push(packages: Vec<(String, Version)>) -> Result<(), RegistryError>
         // Upload the file as a request
         // let content_length = temp_file.path().metadata().unwrap().len();
         let content_length = temp_path.metadata().unwrap().len();
-        let request = request.body(Body::wrap_stream(file)).header("Content-Type", "application/gzip").header("Content-Length", content_length);
-        let response = request.send().await.map_err(|source| RegistryError::UploadError { path: temp_path, endpoint: url, source })?;
+        let request =
+            request.body(Body::wrap_stream(file)).header("Content-Type", compression.content_type()).header("Content-Length", content_length);
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(source) => {
+                progress.finish();
+                let err = RegistryError::UploadError { path: temp_path, endpoint: url, source };
+                if fail_fast {
+                    report_partial_push(&pushed);
+                    return Err(err);
+                }
+                println!("\nFailed to push package: {err}");
+                failed.push((name, version));
+                continue;
+            },
+        };
         let response_status = response.status();
         progress.finish();
 
         // Analyse the response result
         if response_status.is_success() {
             println!("\nSuccessfully pushed version {} of package {}.", style(&version).bold().cyan(), style(&name).bold().cyan(),);
+            index_cache::invalidate_active();
+            pushed.push((name, version));
         } else {
-            match response.text().await {
+            let text: Option<String> = match response.text().await {
                 Ok(text) => {
                     println!("\nFailed to push package: {text}");
+                    Some(text)
                 },
                 Err(err) => {
                     println!("\nFailed to push package (and failed to retrieve response text: {err})");
+                    None
                 },
             };
+            if fail_fast {
+                report_partial_push(&pushed);
+                return Err(RegistryError::UploadFailure { name, version, endpoint: url, status: response_status, text });
+            }
+            failed.push((name, version));
         }
     }
 
+    // Summarize if anything went wrong
+    if !failed.is_empty() {
+        println!(
+            "\nPushed {} out of {} package(s); failed to push {}.",
+            pushed.len(),
+            total,
+            failed.iter().map(|(name, version)| style(format!("{name}:{version}")).bold().cyan().to_string()).collect::<Vec<String>>().join(", ")
+        );
+    }
+
     // Done!
     Ok(())
 }
+
+/// Reports which packages were already pushed before `push()` aborted in fail-fast mode.
+///
+/// **Arguments**
+///  * `pushed`: The name/version pairs of the packages that were pushed successfully.
+fn report_partial_push(pushed: &[(String, Version)]) {
+    if pushed.is_empty() {
+        println!("\nAborting; no packages were pushed.");
+    } else {
+        println!(
+            "\nAborting; the following package(s) were already pushed: {}",
+            pushed.iter().map(|(name, version)| style(format!("{name}:{version}")).bold().cyan().to_string()).collect::<Vec<String>>().join(", ")
+        );
+    }
+}
 /*******/
 
-pub async fn search(term: Option<String>) -> Result<()> {
+/// Searches the registry of the active instance for packages and prints a page of the results.
+///
+/// # Arguments
+/// - `term`: If given, only lists packages matching this term.
+/// - `limit`: If given, shows at most this many packages.
+/// - `offset`: The number of packages to skip before showing any.
+/// - `json`: If given, prints the page as JSON (including the total number of matches) instead of as a table.
+pub async fn search(term: Option<String>, limit: Option<usize>, offset: usize, json: bool) -> Result<()> {
     #[derive(GraphQLQuery)]
     #[graphql(schema_path = "src/graphql/api_schema.json", query_path = "src/graphql/search_packages.graphql", response_derives = "Debug")]
     pub struct SearchPackages;
 
-    let client = reqwest::Client::new();
+    let client = http_client()?;
     let graphql_endpoint = get_graphql_endpoint()?;
 
     // Prepare GraphQL query.
@@ -358,9 +801,31 @@ pub async fn search(term: Option<String>) -> Result<()> {
     // Request/response for GraphQL query.
     let graphql_response = client.post(graphql_endpoint).json(&graphql_query).send().await?;
     let graphql_response: Response<search_packages::ResponseData> = graphql_response.json().await?;
+    if let Some(errors) = graphql_response.errors.filter(|errors| !errors.is_empty()) {
+        return Err(anyhow!("Failed to search packages: {}", errors.iter().map(|err| err.message.as_str()).collect::<Vec<&str>>().join("; ")));
+    }
 
-    if let Some(data) = graphql_response.data {
-        let packages = data.packages;
+    if let Some(mut data) = graphql_response.data {
+        // Page over a stable order, as the registry does not guarantee one
+        sort_by_name_version(&mut data.packages, |package| (package.name.as_str(), package.version.as_str()));
+        let total: usize = data.packages.len();
+        let packages = paginate(data.packages, offset, limit);
+        if json {
+            let packages: Vec<serde_json::Value> = packages
+                .iter()
+                .map(|package| {
+                    serde_json::json!({
+                        "name": package.name,
+                        "version": package.version,
+                        "kind": package.kind,
+                        "description": package.description,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "total": total, "offset": offset, "packages": packages }))?);
+            return Ok(());
+        }
+        let shown: usize = packages.len();
 
         // Present results in a table.
         let format = FormatBuilder::new().column_separator('\0').borders('\0').padding(1, 1).build();
@@ -380,9 +845,14 @@ pub async fn search(term: Option<String>) -> Result<()> {
         }
 
         table.printstd();
-    } else {
-        eprintln!("{:?}", graphql_response.errors);
-    };
+        if shown < total {
+            if shown == 0 {
+                println!("\nShowing none of {total} matching package(s)");
+            } else {
+                println!("\nShowing {}-{} of {total} matching package(s)", offset + 1, offset + shown);
+            }
+        }
+    }
 
     Ok(())
 }
@@ -392,7 +862,7 @@ pub async fn unpublish(name: String, version: Version, force: bool) -> Result<()
     #[graphql(schema_path = "src/graphql/api_schema.json", query_path = "src/graphql/unpublish_package.graphql", response_derives = "Debug")]
     pub struct UnpublishPackage;
 
-    let client = reqwest::Client::new();
+    let client = http_client()?;
     let graphql_endpoint = get_graphql_endpoint()?;
 
     // Ask for permission, if --force is not provided
@@ -420,6 +890,7 @@ pub async fn unpublish(name: String, version: Version, force: bool) -> Result<()
     let graphql_response: Response<unpublish_package::ResponseData> = graphql_response.json().await?;
 
     if let Some(data) = graphql_response.data {
+        index_cache::invalidate_active();
         println!("{}", data.unpublish_package);
     } else {
         eprintln!("{:?}", graphql_response.errors);
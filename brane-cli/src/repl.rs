@@ -15,6 +15,7 @@
 use std::borrow::Cow::{self, Borrowed, Owned};
 use std::fs;
 use std::io::{Stderr, Stdout};
+use std::path::PathBuf;
 
 use brane_ast::ast::Snippet;
 use brane_ast::{ParserOptions, Workflow};
@@ -29,8 +30,9 @@ use rustyline::highlight::{CmdKind, Highlighter, MatchingBracketHighlighter};
 use rustyline::hint::{Hinter, HistoryHinter};
 use rustyline::history::DefaultHistory;
 use rustyline::validate::{self, MatchingBracketValidator, Validator};
-use rustyline::{CompletionType, Config, Context, EditMode, Editor};
+use rustyline::{ColorMode, CompletionType, Config, Context, EditMode, Editor};
 use rustyline_derive::Helper;
+use specifications::profiling::ProfileScope;
 
 pub use crate::errors::ReplError as Error;
 use crate::instance::InstanceInfo;
@@ -144,6 +146,7 @@ impl Validator for ReplHelper {
 /// - `profile`: If given, prints the profile timings to stdout if available.
 /// - `docker_opts`: The DockerOpts that determines how we connect to the local Docker dameon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `profile_output`: If given, writes the timings of every snippet in the session as JSON to the file at this path when the REPL exits.
 ///
 /// # Errors
 /// This function errors if we could not properly read from/write to the terminal. Additionally, it may error if any of the given statements fails for whatever reason.
@@ -158,9 +161,15 @@ pub async fn start(
     profile: bool,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    profile_output: Option<PathBuf>,
 ) -> Result<(), Error> {
     // Build the config for the rustyline REPL.
-    let config = Config::builder().history_ignore_space(true).completion_type(CompletionType::Circular).edit_mode(EditMode::Emacs).build();
+    let config = Config::builder()
+        .history_ignore_space(true)
+        .completion_type(CompletionType::Circular)
+        .edit_mode(EditMode::Emacs)
+        .color_mode(if console::colors_enabled() { ColorMode::Enabled } else { ColorMode::Disabled })
+        .build();
 
     // Build the helper for the REPL
     let repl_helper = ReplHelper {
@@ -193,14 +202,18 @@ pub async fn start(
 
     // Initialization done; run the REPL
     println!("Welcome to the Brane REPL, press Ctrl+D to exit.\n");
+    let prof: ProfileScope = ProfileScope::new("brane repl");
     if remote {
         // Open the login file to find the remote location
         let info: InstanceInfo = InstanceInfo::from_active_path().map_err(|source| Error::InstanceInfoError { source })?;
 
         // Run the thing
-        remote_repl(&mut rl, info, use_case, proxy_addr, attach, options, profile).await?;
+        remote_repl(&mut rl, info, use_case, proxy_addr, attach, options, profile, &prof).await?;
     } else {
-        local_repl(&mut rl, options, docker_opts, keep_containers).await?;
+        local_repl(&mut rl, options, docker_opts, keep_containers, &prof).await?;
+    }
+    if let Some(profile_output) = &profile_output {
+        run::write_profile(&prof, profile_output).map_err(|source| Error::RunError { what: "repl", source })?;
     }
 
     // Try to save the history if we exited cleanly
@@ -223,9 +236,11 @@ pub async fn start(
 /// - `attach`: If given, uses the given ID to attach to an existing session instead of creating a new one.
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+/// - `prof`: The ProfileScope in which to collect the (client-side) timings of every snippet.
 ///
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
+#[allow(clippy::too_many_arguments)]
 async fn remote_repl(
     rl: &mut Editor<ReplHelper, DefaultHistory>,
     info: InstanceInfo,
@@ -234,14 +249,16 @@ async fn remote_repl(
     attach: Option<AppId>,
     options: ParserOptions,
     profile: bool,
+    prof: &ProfileScope,
 ) -> Result<(), Error> {
     let api_address: String = info.api.to_string();
     let drv_address: String = info.drv.to_string();
 
     // First we initialize the remote thing
-    let mut state: InstanceVmState<Stdout, Stderr> = initialize_instance_vm(&api_address, &drv_address, Some(info.user.clone()), attach, options)
-        .await
-        .map_err(|source| Error::InitializeError { what: "remote instance client", source })?;
+    let mut state: InstanceVmState<Stdout, Stderr> =
+        initialize_instance_vm(std::io::stdout(), std::io::stderr(), &api_address, &drv_address, None, Some(info.user.clone()), attach, options)
+            .await
+            .map_err(|source| Error::InitializeError { what: "remote instance client", source })?;
 
     // Next, enter the L in REPL
     let mut count: u32 = 1;
@@ -271,6 +288,8 @@ async fn remote_repl(
 
                 let line_count = 1 + line.chars().filter(|c| *c == '\n').count();
 
+                let snippet_prof = prof.nest(format!("snippet {count}"));
+                let compile = snippet_prof.time("compilation");
                 let workflow = {
                     let pindex = state.pindex.lock();
                     let dindex = state.dindex.lock();
@@ -286,16 +305,19 @@ async fn remote_repl(
                     )
                     .map_err(|source| Error::RunError { what: "repl", source: run::Error::CompileError(source) })?
                 };
+                compile.stop();
 
                 let snippet = Snippet { lines: line_count, workflow };
 
                 // Next, we run the VM (one snippet only ayway)
-                let Ok(res) = run_instance_vm(&drv_address, &mut state, &snippet.workflow, profile).await else {
+                let Ok(res) =
+                    snippet_prof.time_fut("execution (remote)", run_instance_vm(&drv_address, &mut state, &snippet.workflow, profile)).await
+                else {
                     continue;
                 };
 
                 // Then, we collect and process the result
-                if let Err(source) = process_instance_result(&api_address, &proxy_addr, use_case.clone(), snippet.workflow, res).await {
+                if let Err(source) = process_instance_result(&api_address, &proxy_addr, use_case.clone(), snippet.workflow, res, false).await {
                     error!("{}", Error::ProcessError { what: "remote instance VM", source });
                     continue;
                 }
@@ -331,6 +353,7 @@ async fn remote_repl(
 /// - `parse_opts`: The ParseOptions that specify how to parse the incoming source.
 /// - `docker_opts`: The DockerOpts that determines how we connect to the local Docker dameon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `prof`: The ProfileScope in which to collect the timings of every snippet.
 ///
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
@@ -339,10 +362,11 @@ async fn local_repl(
     parse_opts: ParserOptions,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    prof: &ProfileScope,
 ) -> Result<(), Error> {
     // First we initialize the remote thing
-    let mut state: OfflineVmState =
-        initialize_offline_vm(parse_opts, docker_opts, keep_containers).map_err(|source| Error::InitializeError { what: "offline VM", source })?;
+    let mut state: OfflineVmState = initialize_offline_vm(parse_opts, docker_opts, keep_containers, None, 0, false, true, false)
+        .map_err(|source| Error::InitializeError { what: "offline VM", source })?;
 
     // With the VM setup, enter the L in the REPL
     let mut count: u32 = 1;
@@ -373,22 +397,28 @@ async fn local_repl(
                 // Compile the workflow
                 let line_count = line.chars().filter(|&c| c == '\n').count();
 
-                let workflow = Workflow::from_source(
-                    &mut state.state,
-                    &mut state.source,
-                    &state.pindex,
-                    &state.dindex,
-                    None,
-                    &state.options,
-                    "<test task>",
-                    line.clone(),
-                )
-                .map_err(|source| Error::RunError { what: "local repl", source: run::Error::CompileError(source) })?;
+                let snippet_prof = prof.nest(format!("snippet {count}"));
+                let workflow = snippet_prof
+                    .time_func("compilation", || {
+                        Workflow::from_source(
+                            &mut state.state,
+                            &mut state.source,
+                            &state.pindex,
+                            &state.dindex,
+                            None,
+                            &state.options,
+                            "<test task>",
+                            line.clone(),
+                        )
+                    })
+                    .map_err(|source| Error::RunError { what: "local repl", source: run::Error::CompileError(source) })?;
 
                 let snippet = Snippet { lines: line_count, workflow };
 
                 // Next, we run the VM (one snippet only ayway)
-                let res: FullValue = run_offline_vm(&mut state, snippet).await.map_err(|source| Error::RunError { what: "offline VM", source })?;
+                let res: FullValue = run_offline_vm(&mut state, snippet, snippet_prof.nest("vm"))
+                    .await
+                    .map_err(|source| Error::RunError { what: "offline VM", source })?;
 
                 // Then, we collect and process the result
                 if let Err(source) = process_offline_result(res) {
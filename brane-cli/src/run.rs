@@ -13,26 +13,36 @@
 //
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs;
-use std::io::{Read, Stderr, Stdout, Write};
+use std::io::{Read, Stderr, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use brane_ast::ast::Snippet;
+use brane_ast::ast::{Edge, Snippet};
+use brane_ast::locations::Locations;
 use brane_ast::state::CompileState;
 use brane_ast::{ParserOptions, Workflow};
 use brane_dsl::Language;
 use brane_exe::FullValue;
 use brane_exe::dummy::{DummyVm, Error as DummyVmError};
-use brane_tsk::docker::DockerOptions;
+use brane_exe::value::DataId;
+use brane_shr::utilities::closest_names;
+use brane_tsk::docker::{self, ContainerStats, DockerOptions};
 use brane_tsk::errors::StringError;
+use brane_tsk::retry;
 use brane_tsk::spec::{AppId, LOCALHOST};
 use console::style;
+use error_trace::ErrorTrace as _;
+use indicatif::HumanBytes;
 use parking_lot::{Mutex, MutexGuard};
+use serde::Serialize;
 use specifications::data::{AccessKind, DataIndex, DataInfo};
-use specifications::driving::{CreateSessionRequest, DriverServiceClient, ExecuteRequest};
+use specifications::driving::{CreateSessionRequest, DriverServiceClient, ExecuteReply, ExecuteRequest};
 use specifications::package::PackageIndex;
+use specifications::profiling::{ProfileScope, ProfileScopeHandle};
 use tempfile::{TempDir, tempdir};
 use tonic::Code;
 
@@ -40,11 +50,224 @@ use crate::data;
 use crate::errors::OfflineVmError;
 pub use crate::errors::RunError as Error;
 use crate::instance::InstanceInfo;
-use crate::utils::{ensure_datasets_dir, ensure_packages_dir, get_datasets_dir, get_packages_dir};
-use crate::vm::OfflineVm;
+use crate::spec::DownloadLimit;
+use crate::utils::{connect_driver, ensure_datasets_dir, ensure_packages_dir, get_data_index, get_datasets_dir, get_package_index, get_packages_dir};
+use crate::vm::{OfflineVm, create_dataset};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_local_access() {
+        let info = |locs: &[&str]| -> DataInfo {
+            DataInfo {
+                name: "test".into(),
+                owners: None,
+                description: None,
+                created: chrono::DateTime::UNIX_EPOCH,
+                access: locs.iter().map(|l| (l.to_string(), AccessKind::File { path: PathBuf::from("/data") })).collect(),
+                checksums: None,
+                size: None,
+            }
+        };
+        let name: DataId = DataId::from("test");
+
+        // Local datasets are used as-is in both modes
+        assert!(matches!(local_access(&name, &info(&[LOCALHOST, "hospital"]), false), Ok(Some(_))));
+        assert!(matches!(local_access(&name, &info(&[LOCALHOST, "hospital"]), true), Ok(Some(_))));
+
+        // Remote ones are downloaded, unless strict
+        assert!(matches!(local_access(&name, &info(&["hospital"]), false), Ok(None)));
+        match local_access(&name, &info(&["umc", "hospital"]), true) {
+            Err(Error::UnavailableDataset { name, locs }) => {
+                assert_eq!(name, "test");
+                assert_eq!(locs, vec!["hospital", "umc"]);
+            },
+            res => panic!("Expected UnavailableDataset, got {res:?}"),
+        }
+
+        // The error for a failed download lists the locations in the same order
+        match unavailable_dataset(&name, &info(&["umc", "amc", "hospital"])) {
+            Error::UnavailableDataset { locs, .. } => assert_eq!(locs, vec!["amc", "hospital", "umc"]),
+            err => panic!("Expected UnavailableDataset, got {err:?}"),
+        }
+    }
+
+    #[test]
+    fn test_json_run_stdout() {
+        let reply = |stdout: Option<&str>, value: Option<FullValue>, close: bool| -> ExecuteReply {
+            ExecuteReply {
+                close,
+                debug: None,
+                stdout: stdout.map(String::from),
+                stderr: None,
+                value: value.map(|value| serde_json::to_string(&value).unwrap()),
+            }
+        };
+
+        // With `--json`, the output of the workflow (including the closing newline) goes to stderr...
+        let (mut stdout, mut stderr, mut errors): (Vec<u8>, Vec<u8>, Vec<u8>) = (vec![], vec![], vec![]);
+        let mut res: FullValue = FullValue::Void;
+        {
+            let mut output: Box<dyn Write + Send + '_> = workflow_output(true, &mut stdout, &mut stderr);
+            for reply in [reply(Some("Hello, world!\n"), None, false), reply(None, Some(FullValue::Integer(42)), true)] {
+                process_reply("localhost:50053", reply, &mut output, &mut errors, &mut res).unwrap();
+            }
+        }
+        assert_eq!(String::from_utf8(stderr).unwrap(), "Hello, world!\n\n");
+
+        // ...so that stdout only has the summary
+        RunSummary::success(res, None).emit(Some(&mut stdout), None).unwrap();
+        let summary: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(summary["status"], "success");
+        assert_eq!(summary["value"], serde_json::to_value(FullValue::Integer(42)).unwrap());
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The number of times to re-fetch the package or data index from the API when starting a remote run if it fails with a transient error.
+const INDEX_FETCH_RETRIES: u32 = 3;
+
+
+
 
 
 /***** AUXILLARY FUNCTIONS *****/
+/// Pins all tasks in the given workflow to the given location.
+///
+/// This is equivalent to wrapping the entire workflow in an `On`-struct, except that it also overrides any (nested) `On`-structs or automatic placement
+/// the planner would otherwise do.
+///
+/// # Arguments
+/// - `workflow`: The [`Workflow`] to pin.
+/// - `location`: The location to pin all tasks to.
+fn pin_location(workflow: &mut Workflow, location: &str) {
+    let graph: &mut Vec<Edge> = Arc::make_mut(&mut workflow.graph);
+    let funcs: &mut HashMap<usize, Vec<Edge>> = Arc::make_mut(&mut workflow.funcs);
+    for edge in graph.iter_mut().chain(funcs.values_mut().flatten()) {
+        if let Edge::Node { locs, .. } = edge {
+            *locs = Locations::Restricted(vec![location.into()]);
+        }
+    }
+}
+
+/// Retains all intermediate results produced by a local run as local datasets.
+///
+/// Every result is promoted to a dataset named `<RESULT>-<APP_ID>`, so it can be listed with `brane data list` and removed with `brane data remove`.
+///
+/// # Arguments
+/// - `results_dir`: The (temporary) directory with the intermediate results of the run.
+/// - `app_id`: The [`AppId`] identifying the run.
+///
+/// # Returns
+/// A list of pairs of the retained results and the names of the datasets they were retained as.
+///
+/// # Errors
+/// This function errors if we failed to read the results directory or failed to create any of the datasets.
+async fn keep_intermediate_results(results_dir: &Path, app_id: &AppId) -> Result<Vec<(String, String)>, Error> {
+    let datasets_dir: PathBuf = ensure_datasets_dir(true).map_err(|source| Error::DatasetsDirError { source })?;
+
+    // Collect the results first to have a deterministic order
+    let mut names: Vec<String> = Vec::new();
+    for entry in fs::read_dir(results_dir).map_err(|source| Error::ResultsDirReadError { path: results_dir.into(), source })? {
+        let entry: fs::DirEntry = entry.map_err(|source| Error::ResultsDirReadError { path: results_dir.into(), source })?;
+        names.push(entry.file_name().to_string_lossy().into());
+    }
+    names.sort();
+
+    // Promote each of them
+    let mut kept: Vec<(String, String)> = Vec::with_capacity(names.len());
+    for name in names {
+        let data_name: String = format!("{name}-{app_id}");
+        debug!("Retaining intermediate result '{}' as dataset '{}'", name, data_name);
+        create_dataset(
+            &datasets_dir,
+            &data_name,
+            Some(format!("Intermediate result '{name}' retained from run '{app_id}'")),
+            results_dir.join(&name),
+        )
+        .await
+        .map_err(|source| Error::KeepIntermediateError { name: name.clone(), data_name: data_name.clone(), source })?;
+        kept.push((name, data_name));
+    }
+    Ok(kept)
+}
+
+/// Selects where the output of a workflow itself (e.g., its `println()`s) is written to.
+///
+/// # Arguments
+/// - `json`: Whether stdout is reserved for machine-readable output (e.g., `--json`), in which case the output goes to `stderr` instead.
+/// - `stdout`: The handle to write the output to normally.
+/// - `stderr`: The handle to write the output to if stdout is reserved.
+///
+/// # Returns
+/// The handle to write the workflow's output to.
+fn workflow_output<'w>(json: bool, stdout: impl Write + Send + 'w, stderr: impl Write + Send + 'w) -> Box<dyn Write + Send + 'w> {
+    if json { Box::new(stderr) } else { Box::new(stdout) }
+}
+
+/// Processes a single reply of the driver to an [`ExecuteRequest`].
+///
+/// # Arguments
+/// - `drv_endpoint`: The `brane-drv` endpoint that sent the reply (used for debugging only).
+/// - `reply`: The [`ExecuteReply`] to process.
+/// - `stdout`: The handle to write the output of the workflow to.
+/// - `stderr`: The handle to write the errors of the workflow to.
+/// - `res`: The result of the workflow, which is updated if the remote sends a new one.
+///
+/// # Returns
+/// Whether the remote is done with the request.
+///
+/// # Errors
+/// This function errors if we failed to write to either handle or failed to parse the value sent by the remote.
+fn process_reply(
+    drv_endpoint: &str,
+    reply: ExecuteReply,
+    mut stdout: impl Write,
+    mut stderr: impl Write,
+    res: &mut FullValue,
+) -> Result<bool, Error> {
+    // The remote send us some debug message
+    if let Some(debug) = reply.debug {
+        debug!("Remote: {}", debug);
+    }
+
+    // The remote send us a normal text message
+    if let Some(text) = reply.stdout {
+        debug!("Remote returned stdout");
+        write!(stdout, "{text}").map_err(|source| Error::WriteError { source })?;
+    }
+
+    // The remote send us an error
+    if let Some(text) = reply.stderr {
+        debug!("Remote returned error");
+        writeln!(stderr, "{text}").map_err(|source| Error::WriteError { source })?;
+    }
+
+    // Update the value to the latest if one is sent
+    if let Some(value) = reply.value {
+        debug!("Remote returned new value: '{}'", value);
+
+        // Parse it and set the result
+        *res = serde_json::from_str(&value).map_err(|source| Error::ValueParseError { address: drv_endpoint.into(), raw: value, source })?;
+    }
+
+    // The remote is done with this
+    if reply.close {
+        writeln!(stdout).map_err(|source| Error::WriteError { source })?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
 /// Initializes the state for an instance VM.
 ///
 /// This implements most of [`initialize_instance_vm()`], which we separate to have some clients (\*cough\* IDE \*cough\*) able to create a VM while sharing an index.
@@ -79,9 +302,7 @@ pub async fn initialize_instance<O: Write, E: Write>(
 
     // Connect to the server with gRPC
     debug!("Connecting to driver '{}'...", drv_endpoint);
-    let mut client = DriverServiceClient::connect(drv_endpoint.to_string())
-        .await
-        .map_err(|source| Error::ClientConnectError { address: drv_endpoint.into(), source })?;
+    let mut client = connect_driver(drv_endpoint).await.map_err(|source| Error::ClientConnectError { address: drv_endpoint.into(), source })?;
 
     // Either use the given Session UUID or create a new one (with matching session)
     let session: AppId = if let Some(attach) = attach {
@@ -159,38 +380,8 @@ pub async fn run_instance<O: Write, E: Write>(
                 // Show profile times
                 if profile { /* TODO */ }
 
-                // The remote send us some debug message
-                if let Some(debug) = reply.debug {
-                    debug!("Remote: {}", debug);
-                }
-
-                // The remote send us a normal text message
-                if let Some(stdout) = reply.stdout {
-                    debug!("Remote returned stdout");
-                    write!(&mut state.stdout, "{stdout}").map_err(|source| Error::WriteError { source })?;
-                }
-
-                // The remote send us an error
-                if let Some(stderr) = reply.stderr {
-                    debug!("Remote returned error");
-                    writeln!(&mut state.stderr, "{stderr}").map_err(|source| Error::WriteError { source })?;
-                }
-
-                // Update the value to the latest if one is sent
-                if let Some(value) = reply.value {
-                    debug!("Remote returned new value: '{}'", value);
-
-                    // Parse it
-                    let value: FullValue =
-                        serde_json::from_str(&value).map_err(|source| Error::ValueParseError { address: drv_endpoint.into(), raw: value, source })?;
-
-                    // Set the result, packed
-                    res = value;
-                }
-
-                // The remote is done with this
-                if reply.close {
-                    println!();
+                // Write what the remote sent and stop if it's done
+                if process_reply(drv_endpoint, reply, &mut state.stdout, &mut state.stderr, &mut res)? {
                     break;
                 }
             },
@@ -209,6 +400,142 @@ pub async fn run_instance<O: Write, E: Write>(
     Ok(res)
 }
 
+/// Builds the error for a dataset that is not available locally, listing the locations that do have it in a stable order.
+///
+/// # Arguments
+/// - `name`: The name of the dataset.
+/// - `info`: The [`DataInfo`] describing where the dataset is available.
+///
+/// # Returns
+/// An [`Error::UnavailableDataset`] with the dataset's locations sorted by name.
+fn unavailable_dataset(name: &DataId, info: &DataInfo) -> Error {
+    let mut locs: Vec<String> = info.access.keys().cloned().collect();
+    locs.sort();
+    Error::UnavailableDataset { name: name.to_string(), locs }
+}
+
+/// Returns how to access the given dataset locally, if it is available there.
+///
+/// # Arguments
+/// - `name`: The name of the dataset.
+/// - `info`: The [`DataInfo`] describing where the dataset is available.
+/// - `strict`: If given, fails instead of returning [`None`] if the dataset is not available locally.
+///
+/// # Returns
+/// The [`AccessKind`] describing how to access the dataset locally, or [`None`] if it should be downloaded first.
+///
+/// # Errors
+/// This function errors if the dataset is not available locally and `strict` is given.
+fn local_access(name: &DataId, info: &DataInfo, strict: bool) -> Result<Option<AccessKind>, Error> {
+    match info.access.get(LOCALHOST) {
+        Some(access) => Ok(Some(access.clone())),
+        None if strict => Err(unavailable_dataset(name, info)),
+        None => Ok(None),
+    }
+}
+
+/// Resolves a dataset returned by a workflow on a remote instance, downloading it if it's not yet available locally.
+///
+/// # Arguments
+/// - `api_endpoint`: The remote endpoint where we can potentially download data from (or, that at least knows about it).
+/// - `proxy_addr`: If given, proxies all data transfers through the proxy at the given location.
+/// - `certs_dir`: The directory where certificates are stored. Expected to contain nested directories that store the certs by domain ID.
+/// - `datasets_dir`: The directory where we will download the data to. It will be added under a new folder with its own name.
+/// - `name`: The name of the dataset to resolve.
+/// - `strict`: If given, fails instead of downloading the dataset if it's not yet available locally.
+///
+/// # Returns
+/// The [`AccessKind`] describing how to access the dataset locally.
+///
+/// # Errors
+/// This function may error if the dataset is unknown or we failed to retrieve it.
+#[allow(clippy::too_many_arguments)]
+async fn resolve_instance_dataset(
+    api_endpoint: &str,
+    proxy_addr: &Option<String>,
+    certs_dir: &Path,
+    datasets_dir: &Path,
+    use_case: String,
+    workflow: Workflow,
+    name: &DataId,
+    strict: bool,
+) -> Result<AccessKind, Error> {
+    // Compute the directory to write to
+    let data_dir: PathBuf = datasets_dir.join(name.to_string());
+
+    // Fetch a new, local DataIndex to get up-to-date entries
+    let data_addr: String = format!("{api_endpoint}/data/info");
+    let index: DataIndex = get_data_index(&data_addr).await.map_err(|source| Error::RemoteDataIndexError { address: data_addr, source })?;
+
+    // Fetch the method of its availability
+    let info: &DataInfo = index
+        .get(name)
+        .ok_or_else(|| Error::UnknownDataset { name: name.to_string(), suggestions: closest_names(name, index.iter().map(|info| &info.name)) })?;
+    match local_access(name, info, strict)? {
+        Some(access) => Ok(access),
+        None => {
+            // Attempt to download it instead
+            match data::download_data(
+                api_endpoint,
+                proxy_addr,
+                certs_dir,
+                data_dir,
+                use_case,
+                name,
+                workflow,
+                &info.access,
+                false,
+                &DownloadLimit::implicit(),
+            )
+            .await
+            {
+                Ok(Some(access)) => Ok(access),
+                Ok(None) => Err(unavailable_dataset(name, info)),
+                Err(source) => Err(Error::DataDownloadError { source }),
+            }
+        },
+    }
+}
+
+/// Resolves a dataset returned by a workflow on the local machine.
+///
+/// # Arguments
+/// - `name`: The name of the dataset to resolve.
+///
+/// # Returns
+/// The [`AccessKind`] describing how to access the dataset locally.
+///
+/// # Errors
+/// This function may error if we failed to get an up-to-date data index or the dataset is not available locally.
+fn resolve_offline_dataset(name: &DataId) -> Result<AccessKind, Error> {
+    // Get the directory with the datasets
+    let datasets_dir = ensure_datasets_dir(false).map_err(|source| Error::DatasetsDirError { source })?;
+
+    // Fetch a new, local DataIndex to get up-to-date entries
+    let index: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| Error::LocalDataIndexError { source })?;
+
+    // Fetch the method of its availability
+    let info: &DataInfo = index
+        .get(name)
+        .ok_or_else(|| Error::UnknownDataset { name: name.to_string(), suggestions: closest_names(name, index.iter().map(|info| &info.name)) })?;
+    info.access.get(LOCALHOST).cloned().ok_or_else(|| unavailable_dataset(name, info))
+}
+
+/// Returns the certificate- and datasets directories used when post-processing remote results.
+///
+/// # Returns
+/// A tuple of the active instance's certificate directory and the local datasets directory.
+///
+/// # Errors
+/// This function may error if we failed to find the active instance or the datasets directory.
+fn get_instance_result_dirs() -> Result<(PathBuf, PathBuf), Error> {
+    let instance_name = InstanceInfo::get_active_name().map_err(|source| Error::ActiveInstanceReadError { source })?;
+    let certs_dir =
+        InstanceInfo::get_instance_path(&instance_name).map_err(|source| Error::InstancePathError { name: instance_name, source })?.join("certs");
+    let datasets_dir = ensure_datasets_dir(true).map_err(|source| Error::DatasetsDirError { source })?;
+    Ok((certs_dir, datasets_dir))
+}
+
 /// Post-processes the result of a workflow.
 ///
 /// This does nothing unless it's an IntermediateResult or a Dataset; it emits a warning in the first, attempts to download the referred dataset in the latter.
@@ -219,12 +546,14 @@ pub async fn run_instance<O: Write, E: Write>(
 /// - `certs_dir`: The directory where certificates are stored. Expected to contain nested directories that store the certs by domain ID.
 /// - `datasets_dir`: The directory where we will download the data to. It will be added under a new folder with its own name.
 /// - `result`: The value to process.
+/// - `strict`: If given, fails instead of downloading a returned dataset that is not yet available locally.
 ///
 /// # Returns
 /// Nothing, but does print any result to stdout. It may also download a remote dataset if one is given.
 ///
 /// # Errors
 /// This function may error if the given result was a dataset and we failed to retrieve it.
+#[allow(clippy::too_many_arguments)]
 pub async fn process_instance(
     api_endpoint: impl AsRef<str>,
     proxy_addr: &Option<String>,
@@ -233,6 +562,7 @@ pub async fn process_instance(
     use_case: String,
     workflow: Workflow,
     result: FullValue,
+    strict: bool,
 ) -> Result<(), Error> {
     let api_endpoint: &str = api_endpoint.as_ref();
     let certs_dir: &Path = certs_dir.as_ref();
@@ -252,31 +582,8 @@ pub async fn process_instance(
 
             // If it's a dataset, attempt to download it
             FullValue::Data(name) => {
-                // Compute the directory to write to
-                let data_dir: PathBuf = datasets_dir.join(name.to_string());
-
-                // Fetch a new, local DataIndex to get up-to-date entries
-                let data_addr: String = format!("{api_endpoint}/data/info");
-                let index: DataIndex =
-                    brane_tsk::api::get_data_index(&data_addr).await.map_err(|source| Error::RemoteDataIndexError { address: data_addr, source })?;
-
-                // Fetch the method of its availability
-                let info: &DataInfo = index.get(&name).ok_or_else(|| Error::UnknownDataset { name: name.to_string() })?;
-                let access: AccessKind = match info.access.get(LOCALHOST) {
-                    Some(access) => access.clone(),
-                    None => {
-                        // Attempt to download it instead
-                        match data::download_data(api_endpoint, proxy_addr, certs_dir, data_dir, use_case, &name, workflow, &info.access).await {
-                            Ok(Some(access)) => access,
-                            Ok(None) => {
-                                return Err(Error::UnavailableDataset { name: name.into(), locs: info.access.keys().cloned().collect() });
-                            },
-                            Err(source) => {
-                                return Err(Error::DataDownloadError { source });
-                            },
-                        }
-                    },
-                };
+                let access: AccessKind =
+                    resolve_instance_dataset(api_endpoint, proxy_addr, certs_dir, datasets_dir, use_case, workflow, &name, strict).await?;
 
                 // Write the method of access
                 match access {
@@ -293,6 +600,23 @@ pub async fn process_instance(
     Ok(())
 }
 
+/// Prints the result of a remote workflow execution of which any returned dataset has already been resolved.
+///
+/// # Arguments
+/// - `result`: The value to print.
+/// - `access`: How to access the returned dataset locally, if the value is one.
+fn print_instance_result(result: &FullValue, access: Option<&AccessKind>) {
+    if *result == FullValue::Void {
+        return;
+    }
+    println!("\nWorkflow returned value {}", style(format!("'{result}'")).bold().cyan());
+    match (result, access) {
+        (FullValue::IntermediateResult(_), _) => println!("(Intermediate results are not available locally; promote it using 'commit_result()')"),
+        (FullValue::Data(_), Some(AccessKind::File { path })) => println!("(It's available under '{}')", path.display()),
+        _ => {},
+    }
+}
+
 /***** AUXILLARY *****/
 /// A helper struct that contains what we need to know about a compiler + VM state for the dummy use-case.
 pub struct DummyVmState {
@@ -359,19 +683,163 @@ pub struct InstanceVmState<O, E> {
     pub client:  DriverServiceClient,
 }
 
+/// Describes how a workflow run ended in a [`RunSummary`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// The workflow ran to completion.
+    Success,
+    /// The workflow was denied by the policy of one of the involved domains (see [`Error::ExecDenied`]).
+    Denied,
+    /// The workflow was permitted, but failed during execution (see [`Error::ExecError`]).
+    ExecError,
+    /// Something else went wrong, e.g., reading or compiling the workflow or retrieving its result.
+    Failed,
+}
+
+/// A dataset produced by a workflow, as reported in a [`RunSummary`].
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummaryDataset {
+    /// The name of the dataset.
+    pub name: String,
+    /// Where the dataset is available locally, if it is.
+    pub path: Option<PathBuf>,
+}
+
+/// The outcome of a single task of a workflow, as reported in a [`RunSummary`].
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummaryTask {
+    /// The name of the task.
+    pub name:   String,
+    /// Whether the task completed successfully.
+    pub status: RunStatus,
+    /// The value returned by the task, if it completed and returned one.
+    pub value:  Option<FullValue>,
+    /// Why the task failed, if it did.
+    pub error:  Option<String>,
+}
+
+impl RunSummaryTask {
+    /// Converts the task outcomes recorded by an [`OfflineVm`] into summary entries.
+    ///
+    /// # Arguments
+    /// - `results`: The task names and either their returned value or why they failed, in order of execution.
+    ///
+    /// # Returns
+    /// A list of [`RunSummaryTask`]s in the same order.
+    pub fn from_results(results: Vec<(String, Result<Option<FullValue>, String>)>) -> Vec<Self> {
+        results
+            .into_iter()
+            .map(|(name, res)| match res {
+                Ok(value) => Self { name, status: RunStatus::Success, value, error: None },
+                Err(err) => Self { name, status: RunStatus::ExecError, value: None, error: Some(err) },
+            })
+            .collect()
+    }
+}
+
+/// A machine-readable summary of a single workflow run, emitted by `brane workflow run --json`.
+///
+/// Per-task results are only available for local runs; the driver only reports the final value of a remote workflow.
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummary {
+    /// How the run ended.
+    pub status:   RunStatus,
+    /// The value returned by the workflow, if it ran to completion.
+    pub value:    Option<FullValue>,
+    /// The outcome of every task executed, in order of execution.
+    pub tasks:    Vec<RunSummaryTask>,
+    /// Any datasets produced by the workflow.
+    pub datasets: Vec<RunSummaryDataset>,
+    /// The error that stopped the run, if any.
+    pub error:    Option<String>,
+}
+
+impl RunSummary {
+    /// Constructor for a RunSummary of a successful run.
+    ///
+    /// # Arguments
+    /// - `value`: The value returned by the workflow.
+    /// - `access`: If the value is a dataset, then how to access it locally (if we can).
+    ///
+    /// # Returns
+    /// A new RunSummary with status [`RunStatus::Success`].
+    pub fn success(value: FullValue, access: Option<AccessKind>) -> Self {
+        let datasets: Vec<RunSummaryDataset> = match &value {
+            FullValue::Data(name) => vec![RunSummaryDataset {
+                name: name.to_string(),
+                path: access.map(|access| match access {
+                    AccessKind::File { path } => path,
+                }),
+            }],
+            _ => vec![],
+        };
+        Self { status: RunStatus::Success, value: Some(value), tasks: vec![], datasets, error: None }
+    }
+
+    /// Constructor for a RunSummary of a failed run.
+    ///
+    /// # Arguments
+    /// - `err`: The error that caused the run to fail.
+    ///
+    /// # Returns
+    /// A new RunSummary with a status matching the given error.
+    pub fn failure(err: &Error) -> Self {
+        let status: RunStatus = match err {
+            Error::ExecDenied { .. } => RunStatus::Denied,
+            Error::ExecError { .. } => RunStatus::ExecError,
+            _ => RunStatus::Failed,
+        };
+        Self { status, value: None, tasks: vec![], datasets: vec![], error: Some(err.trace().to_string()) }
+    }
+
+    /// Writes the summary as JSON to stdout and/or the given file.
+    ///
+    /// # Arguments
+    /// - `stdout`: If given, the handle to stdout to write the summary to.
+    /// - `path`: If given, also writes the summary to the file at this path.
+    ///
+    /// # Errors
+    /// This function may error if we failed to serialize the summary or write it to stdout or the given file.
+    pub fn emit(&self, stdout: Option<impl Write>, path: Option<&Path>) -> Result<(), Error> {
+        let summary: String = serde_json::to_string_pretty(self).map_err(|source| Error::SummarySerializeError { source })?;
+        if let Some(mut stdout) = stdout {
+            writeln!(stdout, "{summary}").map_err(|source| Error::WriteError { source })?;
+        }
+        if let Some(path) = path {
+            fs::write(path, summary).map_err(|source| Error::SummaryWriteError { path: path.into(), source })?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the timings collected in the given [`ProfileScope`] as JSON to the given file.
+///
+/// # Arguments
+/// - `prof`: The ProfileScope with the collected timings.
+/// - `path`: The path of the file to write to.
+///
+/// # Errors
+/// This function errors if we failed to serialize the timings or write the file.
+pub fn write_profile(prof: &ProfileScope, path: &Path) -> Result<(), Error> {
+    let sprof: String = serde_json::to_string_pretty(prof).map_err(|source| Error::ProfileSerializeError { source })?;
+    fs::write(path, sprof).map_err(|source| Error::ProfileWriteError { path: path.into(), source })
+}
+
 /// Function that prepares a local, offline virtual machine that never runs any jobs.
 ///
 /// It does read the local index to determine if packages are legal.
 ///
 /// # Arguments
 /// - `options`: The ParserOptions that describe how to parse the given source.
+/// - `stdout_to_stderr`: Whether to write the output of workflows to stderr instead of stdout (e.g., because stdout is reserved for the summary).
 ///
 /// # Returns
 /// The newly created virtual machine together with associated states as a DummyVmState.
 ///
 /// # Errors
 /// This function errors if we failed to get the new package indices or other information.
-pub fn initialize_dummy_vm(options: ParserOptions) -> Result<DummyVmState, Error> {
+pub fn initialize_dummy_vm(options: ParserOptions, stdout_to_stderr: bool) -> Result<DummyVmState, Error> {
     // Get the directory with the packages
     let packages_dir = ensure_packages_dir(false).map_err(|source| Error::PackagesDirError { source })?;
     // Get the directory with the datasets
@@ -411,7 +879,7 @@ pub fn initialize_dummy_vm(options: ParserOptions) -> Result<DummyVmState, Error
         source: String::new(),
         options,
 
-        vm: Some(DummyVm::new()),
+        vm: Some(DummyVm::with_stdout_to_stderr(stdout_to_stderr)),
     })
 }
 
@@ -421,13 +889,28 @@ pub fn initialize_dummy_vm(options: ParserOptions) -> Result<DummyVmState, Error
 /// - `parse_opts`: The ParserOptions that describe how to parse the given source.
 /// - `docker_opts`: The configuration of our Docker client.
 /// - `keep_containers`: Whether to keep the containers after execution or not.
+/// - `cleanup_after`: If given, marks kept containers such that they are removed by a later run once this much time has passed.
+/// - `max_retries`: The number of times to re-execute a task that failed with a retryable error.
+/// - `stats`: Whether to sample the resource usage of every task container.
+/// - `auto_fetch`: Whether to pull the images of packages that have not been pulled yet (e.g., after a metadata-only pull) from the registry.
+/// - `stdout_to_stderr`: Whether to write the output of workflows to stderr instead of stdout (e.g., because stdout is reserved for the summary).
 ///
 /// # Returns
 /// The newly created virtual machine together with associated states as an OfflineVmState.
 ///
 /// # Errors
 /// This function errors if we failed to get the new package indices or other information.
-pub fn initialize_offline_vm(parse_opts: ParserOptions, docker_opts: DockerOptions, keep_containers: bool) -> Result<OfflineVmState, Error> {
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_offline_vm(
+    parse_opts: ParserOptions,
+    docker_opts: DockerOptions,
+    keep_containers: bool,
+    cleanup_after: Option<Duration>,
+    max_retries: u32,
+    stats: bool,
+    auto_fetch: bool,
+    stdout_to_stderr: bool,
+) -> Result<OfflineVmState, Error> {
     // Get the directory with the packages
     let packages_dir = ensure_packages_dir(false).map_err(|source| Error::PackagesDirError { source })?;
     // Get the directory with the datasets
@@ -466,15 +949,34 @@ pub fn initialize_offline_vm(parse_opts: ParserOptions, docker_opts: DockerOptio
         source:  String::new(),
         options: parse_opts,
 
-        vm: Some(OfflineVm::new(docker_opts, keep_containers, packages_dir, datasets_dir, temp_dir_path, package_index, data_index)),
+        vm: Some(OfflineVm::new(
+            docker_opts,
+            keep_containers,
+            cleanup_after,
+            max_retries,
+            stats,
+            auto_fetch,
+            stdout_to_stderr,
+            packages_dir,
+            datasets_dir,
+            temp_dir_path,
+            package_index,
+            data_index,
+        )),
     })
 }
 
 /// Function that prepares a remote, instance-backed virtual machine by initializing the proper indices and whatnot.
 ///
 /// # Arguments
+/// - `stdout_writer`: Some [`Write`]-handle that we use to write stdout to.
+/// - `stderr_writer`: Some [`Write`]-handle that we use to write stderr to.
 /// - `api_endpoint`: The `brane-api` endpoint that we download indices from.
 /// - `drv_endpoint`: The `brane-drv` endpoint that we will connect to to run stuff.
+/// - `index_cache`: If given, the name of the instance under which the indices are cached. Indices cached less than
+///   [`INDEX_CACHE_TTL`](crate::index_cache::INDEX_CACHE_TTL) ago for the same `api_endpoint` are reused instead of fetched, older ones
+///   have their package index revalidated instead of fetched, and freshly fetched ones are cached. The data index is only reused for
+///   [`DATA_INDEX_CACHE_TTL`](crate::index_cache::DATA_INDEX_CACHE_TTL).
 /// - `user`: If given, then this is some tentative identifier of the user receiving the final workflow result.
 /// - `attach`: If given, we will try to attach to a session with that ID. Otherwise, we start a new session.
 /// - `options`: The ParserOptions that describe how to parse the given source.
@@ -483,36 +985,72 @@ pub fn initialize_offline_vm(parse_opts: ParserOptions, docker_opts: DockerOptio
 /// The newly created virtual machine together with associated states as an InstanceVmState.
 ///
 /// # Errors
-/// This function errors if we failed to get the new package indices or other information.
-pub async fn initialize_instance_vm(
+/// This function errors if we failed to get the new package indices or other information. Fetching the indices is retried a few times
+/// (with backoff) if it fails transiently.
+#[allow(clippy::too_many_arguments)]
+pub async fn initialize_instance_vm<O: Write, E: Write>(
+    stdout_writer: O,
+    stderr_writer: E,
     api_endpoint: impl AsRef<str>,
     drv_endpoint: impl AsRef<str>,
+    index_cache: Option<&str>,
     user: Option<String>,
     attach: Option<AppId>,
     options: ParserOptions,
-) -> Result<InstanceVmState<Stdout, Stderr>, Error> {
+) -> Result<InstanceVmState<O, E>, Error> {
     let api_endpoint: &str = api_endpoint.as_ref();
     let drv_endpoint: &str = drv_endpoint.as_ref();
 
-    // We fetch a local copy of the indices for compiling
-    debug!("Fetching global package & data indices from '{}'...", api_endpoint);
-    let package_addr: String = format!("{api_endpoint}/graphql");
-    let pindex: Arc<Mutex<PackageIndex>> = match brane_tsk::api::get_package_index(&package_addr).await {
-        Ok(pindex) => Arc::new(Mutex::new(pindex)),
-        Err(source) => {
-            return Err(Error::RemotePackageIndexError { address: package_addr, source });
+    // We fetch a local copy of the indices for compiling (unless we recently did)
+    let cached: Option<(PackageIndex, Option<DataIndex>)> = index_cache.and_then(|instance| crate::index_cache::load(instance, api_endpoint));
+    let cache_hit: bool = cached.is_some();
+    let (pindex, dindex): (PackageIndex, Option<DataIndex>) = match cached {
+        Some(indices) => indices,
+        None => {
+            debug!("Fetching global package index from '{}'...", api_endpoint);
+            let revalidated: Option<PackageIndex> = match index_cache {
+                Some(instance) => crate::index_cache::revalidate(instance, api_endpoint).await,
+                None => None,
+            };
+            let package_addr: String = format!("{api_endpoint}/graphql");
+            let pindex: PackageIndex = match revalidated {
+                Some(pindex) => pindex,
+                None => {
+                    match retry::execute_with_retries("Fetching package index", INDEX_FETCH_RETRIES, |_| get_package_index(&package_addr)).await {
+                        Ok(pindex) => pindex,
+                        Err(source) => {
+                            return Err(Error::RemotePackageIndexError { address: package_addr, source });
+                        },
+                    }
+                },
+            };
+            (pindex, None)
         },
     };
-    let data_addr: String = format!("{api_endpoint}/data/info");
-    let dindex: Arc<Mutex<DataIndex>> = match brane_tsk::api::get_data_index(&data_addr).await {
-        Ok(dindex) => Arc::new(Mutex::new(dindex)),
-        Err(source) => {
-            return Err(Error::RemoteDataIndexError { address: data_addr, source });
+    let dindex: DataIndex = match dindex {
+        Some(dindex) => dindex,
+        None => {
+            debug!("Fetching global data index from '{}'...", api_endpoint);
+            let data_addr: String = format!("{api_endpoint}/data/info");
+            match retry::execute_with_retries("Fetching data index", INDEX_FETCH_RETRIES, |_| get_data_index(&data_addr)).await {
+                Ok(dindex) => dindex,
+                Err(source) => {
+                    return Err(Error::RemoteDataIndexError { address: data_addr, source });
+                },
+            }
         },
     };
+    // Re-caching indices on a hit would keep the package index around for longer than its lifetime
+    if !cache_hit {
+        if let Some(instance) = index_cache {
+            crate::index_cache::store(instance, api_endpoint, &pindex, &dindex);
+        }
+    }
+    let pindex: Arc<Mutex<PackageIndex>> = Arc::new(Mutex::new(pindex));
+    let dindex: Arc<Mutex<DataIndex>> = Arc::new(Mutex::new(dindex));
 
     // Pass the rest to `initialize_instance`
-    initialize_instance(std::io::stdout(), std::io::stderr(), drv_endpoint, pindex, dindex, user, attach, options).await
+    initialize_instance(stdout_writer, stderr_writer, drv_endpoint, pindex, dindex, user, attach, options).await
 }
 
 
@@ -560,15 +1098,16 @@ pub async fn run_dummy_vm(state: &mut DummyVmState, what: impl AsRef<str>, snipp
 /// - `state`: The OfflineVmState that we use to run the local VM.
 /// - `what`: The thing we're running. Either a filename, or something like stdin.
 /// - `snippet`: The snippet to compile and run.
+/// - `prof`: The ProfileScope in which to collect the planning and execution timings of the VM.
 ///
 /// # Returns
 /// The FullValue that the workflow returned, if any. If there was no value, returns FullValue::Void instead.
 ///
 /// # Errors
 /// This function errors if we failed to compile or run the workflow somehow.
-pub async fn run_offline_vm(state: &mut OfflineVmState, snippet: Snippet) -> Result<FullValue, Error> {
+pub async fn run_offline_vm(state: &mut OfflineVmState, snippet: Snippet, prof: ProfileScopeHandle<'_>) -> Result<FullValue, Error> {
     // Run it in the local VM (which is a bit ugly do to the need to consume the VM itself)
-    let res: (OfflineVm, Result<FullValue, OfflineVmError>) = state.vm.take().unwrap().exec(snippet.workflow).await;
+    let res: (OfflineVm, Result<FullValue, OfflineVmError>) = state.vm.take().unwrap().exec(snippet.workflow, prof).await;
     state.vm = Some(res.0);
     let res: FullValue = match res.1 {
         Ok(res) => res,
@@ -598,9 +1137,9 @@ pub async fn run_offline_vm(state: &mut OfflineVmState, snippet: Snippet) -> Res
 /// # Errors
 /// This function errors if we failed to compile the workflow, communicate with the remote driver or remote execution failed somehow.
 #[inline]
-pub async fn run_instance_vm(
+pub async fn run_instance_vm<O: Write, E: Write>(
     drv_endpoint: impl AsRef<str>,
-    state: &mut InstanceVmState<Stdout, Stderr>,
+    state: &mut InstanceVmState<O, E>,
     workflow: &Workflow,
     profile: bool,
 ) -> Result<FullValue, Error> {
@@ -667,18 +1206,7 @@ pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
 
             // If it's a dataset, attempt to download it
             FullValue::Data(name) => {
-                // Get the directory with the datasets
-                let datasets_dir = ensure_datasets_dir(false).map_err(|source| Error::DatasetsDirError { source })?;
-
-                // Fetch a new, local DataIndex to get up-to-date entries
-                let index: DataIndex = brane_tsk::local::get_data_index(datasets_dir).map_err(|source| Error::LocalDataIndexError { source })?;
-
-                // Fetch the method of its availability
-                let info: &DataInfo = index.get(&name).ok_or_else(|| Error::UnknownDataset { name: name.clone().into() })?;
-                let access: &AccessKind = info
-                    .access
-                    .get(LOCALHOST)
-                    .ok_or_else(|| Error::UnavailableDataset { name: name.into(), locs: info.access.keys().cloned().collect() })?;
+                let access: AccessKind = resolve_offline_dataset(&name)?;
 
                 // Write the method of access
                 match access {
@@ -695,12 +1223,49 @@ pub fn process_offline_result(result: FullValue) -> Result<(), Error> {
     Ok(())
 }
 
+/// Reports the resource usage sampled for the tasks of an offline workflow execution.
+///
+/// # Arguments
+/// - `stats`: The task names and their sampled [`ContainerStats`], in order of execution.
+/// - `stderr`: Whether to write to stderr instead of stdout (e.g., because stdout is reserved for machine-readable output).
+///
+/// # Returns
+/// Nothing, but does print the peak memory and average CPU usage of every task.
+pub fn process_task_stats(stats: &[(String, ContainerStats)], stderr: bool) {
+    let mut report: String = format!("\n{}\n", style("Task resource usage:").bold());
+    if stats.is_empty() {
+        report.push_str("  (no tasks were executed)\n");
+    }
+    for (name, stats) in stats {
+        let cpu: String = match stats.avg_cpu() {
+            Some(cpu) => format!("{cpu:.1}%"),
+            None => "n/a".into(),
+        };
+        report.push_str(&format!(
+            "  - {}: peak memory {}, average CPU {} ({} sample{})\n",
+            style(format!("'{name}'")).bold().cyan(),
+            HumanBytes(stats.peak_memory),
+            cpu,
+            stats.samples,
+            if stats.samples == 1 { "" } else { "s" }
+        ));
+    }
+
+    // Write it to the right place
+    if stderr {
+        eprint!("{report}");
+    } else {
+        print!("{report}");
+    }
+}
+
 /// Processes the given result of a remote workflow execution.
 ///
 /// # Arguments
 /// - `api_endpoint`: The remote endpoint where we can potentially download data from (or, that at least knows about it).
 /// - `proxy_addr`: If given, proxies all data transfers through the proxy at the given location.
 /// - `result`: The value to process.
+/// - `strict`: If given, fails instead of downloading a returned dataset that is not yet available locally.
 ///
 /// # Returns
 /// Nothing, but does print any result to stdout. It may also download a remote dataset if one is given.
@@ -713,15 +1278,12 @@ pub async fn process_instance_result(
     use_case: String,
     workflow: Workflow,
     result: FullValue,
+    strict: bool,
 ) -> Result<(), Error> {
-    let instance_name = InstanceInfo::get_active_name().map_err(|source| Error::ActiveInstanceReadError { source })?;
-    let certs_dir =
-        InstanceInfo::get_instance_path(&instance_name).map_err(|source| Error::InstancePathError { name: instance_name, source })?.join("certs");
-
-    let datasets_dir = ensure_datasets_dir(true).map_err(|source| Error::DatasetsDirError { source })?;
+    let (certs_dir, datasets_dir): (PathBuf, PathBuf) = get_instance_result_dirs()?;
 
     // Run the instance function
-    process_instance(api_endpoint, proxy_addr, certs_dir, datasets_dir, use_case, workflow, result).await
+    process_instance(api_endpoint, proxy_addr, certs_dir, datasets_dir, use_case, workflow, result, strict).await
 }
 
 
@@ -739,8 +1301,25 @@ pub async fn process_instance_result(
 /// - `language`: The language with which to compile the file.
 /// - `file`: The workflow file to read and run. Can also be '-', in which case it is read from stdin instead.
 /// - `profile`: If given, prints the profile timings to stdout if available.
+/// - `location`: If given, pins all tasks to this location instead of letting the planner decide. Only relevant if running remotely.
 /// - `docker_opts`: The options with which we connect to the local Docker daemon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `cleanup_after`: If given, kept containers are removed by a later run once this much time has passed.
+/// - `keep_intermediate`: Whether to retain intermediate results as local datasets after a local run.
+/// - `max_retries`: The number of times to re-execute a task that failed with a retryable error. Only relevant if running locally.
+/// - `stats`: If given, samples the resource usage of every task container and reports it at the end. Only relevant if running locally.
+/// - `auto_fetch`: Whether to pull the images of packages that have not been pulled yet (e.g., after a metadata-only pull) from the registry.
+///   Only relevant if running locally.
+/// - `strict_data`: If given, fails instead of downloading a returned dataset that is not yet available locally. Only relevant if running
+///   remotely.
+/// - `index_cache`: Whether to reuse the indices of the instance fetched by a run less than a minute ago instead of fetching them again. Only
+///   relevant if running remotely.
+/// - `json`: If given, writes a [`RunSummary`] as JSON to stdout instead of the human-readable result.
+/// - `result_json`: If given, writes a [`RunSummary`] as JSON to the file at this path, in addition to any other output.
+/// - `profile_output`: If given, writes the timings of the compile, plan and execute phases (including per-task timings) as JSON to the file at
+///   this path.
+/// - `inputs`: The `NAME=VALUE` inputs to bind to the workflow's top-level parameters.
+/// - `input_env_prefix`: The prefix of the environment variables from which to read further inputs. Ignored if empty.
 ///
 /// # Returns
 /// Nothing, but does print results and such to stdout. Might also produce new datasets.
@@ -753,39 +1332,121 @@ pub async fn handle(
     dummy: bool,
     remote: bool,
     profile: bool,
+    location: Option<String>,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    cleanup_after: Option<Duration>,
+    keep_intermediate: bool,
+    max_retries: u32,
+    stats: bool,
+    auto_fetch: bool,
+    strict_data: bool,
+    index_cache: bool,
+    json: bool,
+    result_json: Option<PathBuf>,
+    profile_output: Option<PathBuf>,
+    inputs: Vec<String>,
+    input_env_prefix: String,
 ) -> Result<(), Error> {
-    // Either read the file or read stdin
-    let (source, source_code): (Cow<str>, String) = if file == PathBuf::from("-") {
-        let mut result: String = String::new();
-        std::io::stdin().read_to_string(&mut result).map_err(|source| Error::StdinReadError { source })?;
-        ("<stdin>".into(), result)
-    } else {
-        match fs::read_to_string(&file) {
-            Ok(res) => (file.to_string_lossy(), res),
-            Err(source) => {
-                return Err(Error::FileReadError { path: file, source });
-            },
+    let summarize: bool = json || result_json.is_some();
+    let prof: ProfileScope = ProfileScope::new("brane run");
+    let mut tasks: Vec<RunSummaryTask> = Vec::new();
+
+    // Run the workflow, collecting the summary if we need one
+    let res: Result<Option<RunSummary>, Error> = async {
+        // Either read the file or read stdin
+        let (source, source_code): (Cow<str>, String) = if file == PathBuf::from("-") {
+            let mut result: String = String::new();
+            std::io::stdin().read_to_string(&mut result).map_err(|source| Error::StdinReadError { source })?;
+            ("<stdin>".into(), result)
+        } else {
+            match fs::read_to_string(&file) {
+                Ok(res) => (file.to_string_lossy(), res),
+                Err(source) => {
+                    return Err(Error::FileReadError { path: file.clone(), source });
+                },
+            }
+        };
+
+        // Bind any inputs to the workflow's parameters
+        let source_code: String = crate::inputs::bind(&source_code, language, inputs, &input_env_prefix)
+            .map_err(|err| Error::InputError { input: source.to_string(), source: err })?;
+
+        // Prepare the parser options
+        let options: ParserOptions = ParserOptions::new(language);
+
+        // Now switch on dummy, local or remote mode
+        if !dummy {
+            if remote {
+                // Open the login file to find the remote location
+                let info: InstanceInfo = InstanceInfo::from_active_path().map_err(|source| Error::InstanceInfoError { source })?;
+                let index_cache: Option<String> = if index_cache { InstanceInfo::get_active_name().ok() } else { None };
+
+                // Run the thing
+                remote_run(
+                    info,
+                    index_cache,
+                    use_case,
+                    proxy_addr,
+                    options,
+                    source,
+                    source_code,
+                    profile,
+                    location,
+                    strict_data,
+                    summarize,
+                    json,
+                    &prof,
+                )
+                .await
+            } else {
+                local_run(
+                    options,
+                    docker_opts,
+                    source,
+                    source_code,
+                    keep_containers,
+                    cleanup_after,
+                    keep_intermediate,
+                    max_retries,
+                    stats,
+                    auto_fetch,
+                    summarize,
+                    json,
+                    &mut tasks,
+                    &prof,
+                )
+                .await
+            }
+        } else {
+            dummy_run(options, source, source_code, summarize, json).await
         }
-    };
-
-    // Prepare the parser options
-    let options: ParserOptions = ParserOptions::new(language);
+    }
+    .await;
 
-    // Now switch on dummy, local or remote mode
-    if !dummy {
-        if remote {
-            // Open the login file to find the remote location
-            let info: InstanceInfo = InstanceInfo::from_active_path().map_err(|source| Error::InstanceInfoError { source })?;
+    // Write the profile timings if asked to, even if the run failed
+    if let Some(profile_output) = &profile_output {
+        write_profile(&prof, profile_output)?;
+    }
 
-            // Run the thing
-            remote_run(info, use_case, proxy_addr, options, source, source_code, profile).await
-        } else {
-            local_run(options, docker_opts, source, source_code, keep_containers).await
-        }
-    } else {
-        dummy_run(options, source, source_code).await
+    // Emit the summary if asked to
+    if !summarize {
+        return res.map(|_| ());
+    }
+    match res {
+        Ok(summary) => {
+            if let Some(mut summary) = summary {
+                summary.tasks = tasks;
+                summary.emit(json.then(std::io::stdout), result_json.as_deref())?;
+            }
+            Ok(())
+        },
+        Err(err) => {
+            let mut summary: RunSummary = RunSummary::failure(&err);
+            summary.tasks = tasks;
+            summary.emit(json.then(std::io::stdout), result_json.as_deref())?;
+            Err(err)
+        },
     }
 }
 
@@ -797,22 +1458,31 @@ pub async fn handle(
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `what`: A description of the source we're reading (e.g., the filename or stdin)
 /// - `source`: The source code to read.
+/// - `summarize`: If given, returns a [`RunSummary`] of the run.
+/// - `quiet`: If given, does not print the result and writes the output of the workflow to stderr (e.g., because stdout is reserved for the
+///   summary).
 ///
 /// # Returns
-/// Nothing, but does print results and such to stdout. Does not produce new datasets.
-async fn dummy_run(options: ParserOptions, what: impl AsRef<str>, source: impl AsRef<str>) -> Result<(), Error> {
+/// A [`RunSummary`] if `summarize` is given. Unless `quiet`, also prints results and such to stdout. Does not produce new datasets.
+async fn dummy_run(
+    options: ParserOptions,
+    what: impl AsRef<str>,
+    source: impl AsRef<str>,
+    summarize: bool,
+    quiet: bool,
+) -> Result<Option<RunSummary>, Error> {
     let what: &str = what.as_ref();
     let source: &str = source.as_ref();
 
     // First we initialize the VM
-    let mut state: DummyVmState = initialize_dummy_vm(options)?;
+    let mut state: DummyVmState = initialize_dummy_vm(options, quiet)?;
     // Next, we run the VM (one snippet only ayway)
     let res: FullValue = run_dummy_vm(&mut state, what, source).await?;
     // Then, we collect and process the result
-    process_dummy_result(res);
-
-    // Done
-    Ok(())
+    if !quiet {
+        process_dummy_result(res.clone());
+    }
+    Ok(if summarize { Some(RunSummary::success(res, None)) } else { None })
 }
 
 /// Runs the given file on the local machine.
@@ -823,81 +1493,190 @@ async fn dummy_run(options: ParserOptions, what: impl AsRef<str>, source: impl A
 /// - `what`: A description of the source we're reading (e.g., the filename or stdin)
 /// - `source`: The source code to read.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `cleanup_after`: If given, marks kept containers such that they are removed by a later run once this much time has passed.
+/// - `keep_intermediate`: Whether to retain intermediate results as local datasets (named after the run's [`AppId`]) instead of discarding them.
+/// - `max_retries`: The number of times to re-execute a task that failed with a retryable error.
+/// - `stats`: If given, samples the resource usage of every task container and reports it at the end (even if the run failed).
+/// - `auto_fetch`: Whether to pull the images of packages that have not been pulled yet (e.g., after a metadata-only pull) from the registry.
+/// - `summarize`: If given, returns a [`RunSummary`] of the run.
+/// - `quiet`: If given, does not print the result and writes the output of the workflow to stderr (e.g., because stdout is reserved for the
+///   summary).
+/// - `tasks`: Filled with the outcome of every task executed, also if the run fails.
+/// - `prof`: The ProfileScope in which to collect the timings of the run.
 ///
 /// # Returns
-/// Nothing, but does print results and such to stdout. Might also produce new datasets.
+/// A [`RunSummary`] if `summarize` is given. Unless `quiet`, also prints results and such to stdout. Might also produce new datasets.
+#[allow(clippy::too_many_arguments)]
 async fn local_run(
     parse_opts: ParserOptions,
     docker_opts: DockerOptions,
     what: impl AsRef<str>,
     source: impl AsRef<str>,
     keep_containers: bool,
-) -> Result<(), Error> {
+    cleanup_after: Option<Duration>,
+    keep_intermediate: bool,
+    max_retries: u32,
+    stats: bool,
+    auto_fetch: bool,
+    summarize: bool,
+    quiet: bool,
+    tasks: &mut Vec<RunSummaryTask>,
+    prof: &ProfileScope,
+) -> Result<Option<RunSummary>, Error> {
     let what: &str = what.as_ref();
     let source: &str = source.as_ref();
 
+    // Clean up any containers kept by previous runs that have outstayed their welcome
+    match docker::remove_expired_containers(&docker_opts).await {
+        Ok(removed) if !removed.is_empty() => info!("Removed {} kept container(s) whose cleanup time has passed", removed.len()),
+        Ok(_) => {},
+        Err(err) => warn!("Failed to remove expired kept containers: {err} (skipping)"),
+    }
+
     // First we initialize the remote thing
-    let mut state: OfflineVmState = initialize_offline_vm(parse_opts, docker_opts, keep_containers)?;
+    let mut state: OfflineVmState =
+        initialize_offline_vm(parse_opts, docker_opts, keep_containers, cleanup_after, max_retries, stats, auto_fetch, quiet)?;
 
     // Compile the workflow
-    let snippet = Snippet::from_source(&mut state.state, &mut state.source, &state.pindex, &state.dindex, None, &state.options, what, source)
+    let snippet = prof
+        .time_func("compilation", || {
+            Snippet::from_source(&mut state.state, &mut state.source, &state.pindex, &state.dindex, None, &state.options, what, source)
+        })
         .map_err(Error::CompileError)?;
 
     // Next, we run the VM (one snippet only ayway)
-    let res: FullValue = run_offline_vm(&mut state, snippet).await?;
+    let res: Result<FullValue, Error> = run_offline_vm(&mut state, snippet, prof.nest("vm")).await;
 
-    // Then, we collect and process the result
-    process_offline_result(res)?;
+    // Report the resource usage of the tasks, also if any of them failed
+    if let Some(vm) = &state.vm {
+        if let Some(stats) = vm.task_stats() {
+            process_task_stats(&stats, quiet);
+        }
+        *tasks = RunSummaryTask::from_results(vm.task_results());
+    }
 
-    // Done
-    Ok(())
+    // Retain the intermediate results if asked to (also if the run failed, to keep whatever the tasks that did succeed produced)
+    if keep_intermediate {
+        let app_id: AppId = AppId::generate();
+        match keep_intermediate_results(state.results_dir.path(), &app_id).await {
+            Ok(kept) => {
+                for (name, data_name) in kept {
+                    if !quiet {
+                        println!(
+                            "Retained intermediate result {} as dataset {}",
+                            style(format!("'{name}'")).bold(),
+                            style(format!("'{data_name}'")).bold().cyan()
+                        );
+                    }
+                }
+            },
+            // Don't let this shadow why the run failed in the first place
+            Err(err) if res.is_err() => warn!("{}", err.trace()),
+            Err(err) => return Err(err),
+        }
+    }
+    let res: FullValue = res?;
+
+    // Then, we collect and process the result
+    if !quiet {
+        process_offline_result(res.clone())?;
+    }
+    if summarize {
+        let access: Option<AccessKind> = if let FullValue::Data(name) = &res { Some(resolve_offline_dataset(name)?) } else { None };
+        return Ok(Some(RunSummary::success(res, access)));
+    }
+    Ok(None)
 }
 
 /// Runs the given file on the remote instance.
 ///
 /// # Arguments
 /// - `info`: Information about the remote instance, including as who we're logged-in.
+/// - `index_cache`: If given, the name of the instance under which to cache its indices (see [`crate::index_cache`]).
 /// - `proxy_addr`: The address to proxy any data transfers through if they occur.
 /// - `options`: The ParseOptions that specify how to parse the incoming source.
 /// - `source`: A description of the source we're reading (e.g., the filename or stdin)
 /// - `workflow_content`: The source code to read.
 /// - `profile`: If given, prints the profile timings to stdout if reported by the remote.
+/// - `location`: If given, pins all tasks to this location instead of letting the planner decide.
+/// - `strict_data`: If given, fails instead of downloading a returned dataset that is not yet available locally.
+/// - `summarize`: If given, returns a [`RunSummary`] of the run.
+/// - `quiet`: If given, does not print the result and writes the output of the workflow to stderr (e.g., because stdout is reserved for the
+///   summary).
+/// - `prof`: The ProfileScope in which to collect the (client-side) timings of the run.
 ///
 /// # Returns
-/// Nothing, but does print results and such to stdout. Might also produce new datasets.
+/// A [`RunSummary`] if `summarize` is given. Unless `quiet`, also prints results and such to stdout. Might also produce new datasets.
+#[allow(clippy::too_many_arguments)]
 async fn remote_run(
     info: InstanceInfo,
+    index_cache: Option<String>,
     use_case: String,
     proxy_addr: Option<String>,
     options: ParserOptions,
     source: impl AsRef<str>,
     workflow_content: impl AsRef<str>,
     profile: bool,
-) -> Result<(), Error> {
+    location: Option<String>,
+    strict_data: bool,
+    summarize: bool,
+    quiet: bool,
+    prof: &ProfileScope,
+) -> Result<Option<RunSummary>, Error> {
     let api_endpoint: String = info.api.to_string();
     let drv_endpoint: String = info.drv.to_string();
     let source: &str = source.as_ref();
     let workflow_content: &str = workflow_content.as_ref();
 
     // First we initialize the remote thing
-    let mut state: InstanceVmState<Stdout, Stderr> =
-        initialize_instance_vm(&api_endpoint, &drv_endpoint, Some(info.user.clone()), None, options).await?;
+    let mut state: InstanceVmState<Box<dyn Write + Send>, Stderr> = initialize_instance_vm(
+        workflow_output(quiet, std::io::stdout(), std::io::stderr()),
+        std::io::stderr(),
+        &api_endpoint,
+        &drv_endpoint,
+        index_cache.as_deref(),
+        Some(info.user.clone()),
+        None,
+        options,
+    )
+    .await?;
 
     // Compile the workflow
-    let snippet: Snippet = {
+    let compile = prof.time("compilation");
+    let mut snippet: Snippet = {
         // Acquire the locks
         let pindex: MutexGuard<PackageIndex> = state.pindex.lock();
         let dindex: MutexGuard<DataIndex> = state.dindex.lock();
         Snippet::from_source(&mut state.state, &mut state.source, &pindex, &dindex, state.user.as_deref(), &state.options, source, workflow_content)
             .map_err(Error::CompileError)?
     };
+    compile.stop();
+    if let Some(location) = location {
+        debug!("Pinning all tasks to location '{}'", location);
+        pin_location(&mut snippet.workflow, &location);
+    }
 
     // Next, we run the VM (one snippet only ayway)
-    let res: FullValue = run_instance_vm(drv_endpoint, &mut state, &snippet.workflow, profile).await?;
+    let res: FullValue = prof.time_fut("execution (remote)", run_instance_vm(drv_endpoint, &mut state, &snippet.workflow, profile)).await?;
 
     // Then, we collect and process the result
-    process_instance_result(api_endpoint, &proxy_addr, use_case, snippet.workflow, res).await?;
+    if summarize {
+        let access: Option<AccessKind> = if let FullValue::Data(name) = &res {
+            let (certs_dir, datasets_dir): (PathBuf, PathBuf) = get_instance_result_dirs()?;
+            Some(
+                resolve_instance_dataset(&api_endpoint, &proxy_addr, &certs_dir, &datasets_dir, use_case, snippet.workflow, name, strict_data)
+                    .await?,
+            )
+        } else {
+            None
+        };
+        if !quiet {
+            print_instance_result(&res, access.as_ref());
+        }
+        return Ok(Some(RunSummary::success(res, access)));
+    }
+    process_instance_result(api_endpoint, &proxy_addr, use_case, snippet.workflow, res, strict_data).await?;
 
     // Done
-    Ok(())
+    Ok(None)
 }
@@ -17,15 +17,108 @@ use std::fmt::{Debug, Display, Formatter, Result as FResult};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use brane_exe::spec::CustomGlobalState;
-use brane_tsk::docker::DockerOptions;
+use brane_exe::value::FullValue;
+use brane_tsk::docker::{ContainerStats, DockerOptions};
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use specifications::data::DataIndex;
 use specifications::package::PackageIndex;
 use specifications::version::Version;
 
-use crate::errors::HostnameParseError;
+use crate::build_common::RESERVED_BUILD_ARGS;
+use crate::errors::{
+    AgeParseError, BuildArgParseError, ByteSizeParseError, ChecksumParseError, CompletionKindParseError, DownloadLimitError,
+    EntrypointArgsParseError, HostnameParseError, LabelParseError, LintRuleParseError, TlsVersionParseError,
+};
+use crate::instance::InstanceDefaults;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_age_parse() {
+        assert_eq!(Age::from_str("7d").unwrap(), Age(Duration::from_secs(7 * 86400)));
+        assert_eq!(Age::from_str("12h").unwrap(), Age(Duration::from_secs(12 * 3600)));
+        assert_eq!(Age::from_str("0m").unwrap(), Age(Duration::ZERO));
+
+        // Errors
+        assert!(matches!(Age::from_str(""), Err(AgeParseError::Empty)));
+        assert!(matches!(Age::from_str("7w"), Err(AgeParseError::UnknownUnit { unit: 'w', .. })));
+        assert!(matches!(Age::from_str("-7d"), Err(AgeParseError::NumberParseError { .. })));
+        assert!(matches!(Age::from_str("d"), Err(AgeParseError::NumberParseError { .. })));
+        assert!(matches!(Age::from_str(&format!("{}d", u64::MAX / 86400 + 1)), Err(AgeParseError::Overflow { .. })));
+
+        // Round-trips
+        for raw in ["7d", "12h", "90m"] {
+            assert_eq!(Age::from_str(raw).unwrap().to_string(), raw);
+        }
+    }
+
+    #[test]
+    fn test_byte_size_parse() {
+        assert_eq!(ByteSize::from_str("512").unwrap(), ByteSize(512));
+        assert_eq!(ByteSize::from_str("512B").unwrap(), ByteSize(512));
+        assert_eq!(ByteSize::from_str("10K").unwrap(), ByteSize(10 << 10));
+        assert_eq!(ByteSize::from_str("10m").unwrap(), ByteSize(10 << 20));
+        assert_eq!(ByteSize::from_str("10GiB").unwrap(), ByteSize(10 << 30));
+        assert_eq!(ByteSize::from_str(" 2tb ").unwrap(), ByteSize(2 << 40));
+
+        // Errors
+        assert!(matches!(ByteSize::from_str(""), Err(ByteSizeParseError::Empty { .. })));
+        assert!(matches!(ByteSize::from_str("G"), Err(ByteSizeParseError::Empty { .. })));
+        assert!(matches!(ByteSize::from_str("-1K"), Err(ByteSizeParseError::NumberParseError { .. })));
+        assert!(matches!(ByteSize::from_str("1.5G"), Err(ByteSizeParseError::NumberParseError { .. })));
+        assert!(matches!(ByteSize::from_str("10P"), Err(ByteSizeParseError::NumberParseError { .. })));
+        assert!(matches!(ByteSize::from_str(&format!("{}T", u64::MAX >> 39)), Err(ByteSizeParseError::Overflow { .. })));
+
+        // Round-trips
+        for raw in ["512B", "10KiB", "1536KiB", "10GiB", "2TiB"] {
+            assert_eq!(ByteSize::from_str(raw).unwrap().to_string(), raw);
+        }
+    }
+
+    #[test]
+    fn test_download_limit() {
+        let unlimited = DownloadLimit::default();
+        assert!(unlimited.check("url", None).is_ok());
+        assert!(unlimited.check("url", Some(u64::MAX)).is_ok());
+        assert!(unlimited.check_received("url", u64::MAX).is_ok());
+
+        let limited = DownloadLimit { max_size: Some(ByteSize(1024)), allow_unknown_size: false };
+        assert!(limited.check("url", Some(1024)).is_ok());
+        assert!(matches!(limited.check("url", Some(1025)), Err(DownloadLimitError::TooLarge { .. })));
+        assert!(matches!(limited.check("url", None), Err(DownloadLimitError::UnknownSize { .. })));
+        assert!(limited.check_received("url", 1024).is_ok());
+        assert!(matches!(limited.check_received("url", 1025), Err(DownloadLimitError::Exceeded { .. })));
+
+        let allowed = DownloadLimit { allow_unknown_size: true, ..limited };
+        assert!(allowed.check("url", None).is_ok());
+        assert!(matches!(allowed.check("url", Some(1025)), Err(DownloadLimitError::TooLarge { .. })));
+    }
+
+    #[test]
+    fn test_entrypoint_args_parse() {
+        assert_eq!(EntrypointArgs::from_str("").unwrap().0, Vec::<String>::new());
+        assert_eq!(EntrypointArgs::from_str("--quiet  -v").unwrap().0, vec!["--quiet", "-v"]);
+        assert_eq!(EntrypointArgs::from_str("--name 'hello world' \"a b\"").unwrap().0, vec!["--name", "hello world", "a b"]);
+        assert_eq!(EntrypointArgs::from_str("--empty ''").unwrap().0, vec!["--empty", ""]);
+        assert_eq!(EntrypointArgs::from_str("a\\ b").unwrap().0, vec!["a b"]);
+
+        // Errors
+        assert!(matches!(EntrypointArgs::from_str("'unterminated"), Err(EntrypointArgsParseError::Unsplittable { .. })));
+        assert!(matches!(EntrypointArgs::from_str("trailing\\"), Err(EntrypointArgsParseError::Unsplittable { .. })));
+    }
+}
+
+
+
 
 
 /***** STATICS *****/
@@ -39,6 +132,19 @@ lazy_static::lazy_static! {
 
 
 /***** LIBRARY *****/
+/// An auxillary struct that defines a list of arguments given as a single, shell-quoted string (e.g., `--name 'hello world'`).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct EntrypointArgs(pub Vec<String>);
+impl FromStr for EntrypointArgs {
+    type Err = EntrypointArgsParseError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split like a POSIX shell would, but without any expansion
+        shlex::split(s).map(Self).ok_or_else(|| EntrypointArgsParseError::Unsplittable { raw: s.into() })
+    }
+}
+
 /// An auxillary struct that defines a hostname-only argument, optionally with some scheme.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Hostname {
@@ -118,6 +224,440 @@ impl FromStr for Hostname {
 
 
 
+/// An auxillary struct that defines an age (i.e., a duration back from now), given as a number with a `d`, `h` or `m` suffix (e.g., `7d`).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Age(pub Duration);
+
+impl Display for Age {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        let secs: u64 = self.0.as_secs();
+        if secs % 86400 == 0 {
+            write!(f, "{}d", secs / 86400)
+        } else if secs % 3600 == 0 {
+            write!(f, "{}h", secs / 3600)
+        } else {
+            write!(f, "{}m", secs / 60)
+        }
+    }
+}
+impl FromStr for Age {
+    type Err = AgeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split the unit off the end
+        let (number, unit): (&str, char) = match s.char_indices().last() {
+            Some((i, unit)) => (&s[..i], unit),
+            None => {
+                return Err(AgeParseError::Empty);
+            },
+        };
+        let multiplier: u64 = match unit {
+            'd' => 86400,
+            'h' => 3600,
+            'm' => 60,
+            _ => {
+                return Err(AgeParseError::UnknownUnit { raw: s.into(), unit });
+            },
+        };
+
+        // Parse the number in front of it
+        let number: u64 = u64::from_str(number).map_err(|source| AgeParseError::NumberParseError { raw: number.into(), source })?;
+        let secs: u64 = number.checked_mul(multiplier).ok_or_else(|| AgeParseError::Overflow { raw: s.into() })?;
+        Ok(Self(Duration::from_secs(secs)))
+    }
+}
+
+
+
+/// An auxillary struct that defines a size in bytes, given as a number with an optional `K`, `M`, `G` or `T` suffix (e.g., `10G`).
+///
+/// The suffixes are powers of 1024, and may be followed by `B` or `iB` (e.g., `10GiB`).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    /// The units we parse and print, from large to small.
+    const UNITS: [(char, u64); 4] = [('T', 1 << 40), ('G', 1 << 30), ('M', 1 << 20), ('K', 1 << 10)];
+}
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        for (unit, multiplier) in Self::UNITS {
+            if self.0 >= multiplier && self.0 % multiplier == 0 {
+                return write!(f, "{}{}iB", self.0 / multiplier, unit);
+            }
+        }
+        write!(f, "{}B", self.0)
+    }
+}
+impl FromStr for ByteSize {
+    type Err = ByteSizeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Strip the optional byte suffix, then see if there's a unit left
+        let raw: &str = s.trim();
+        let upper: String = raw.to_uppercase();
+        let stripped: &str = upper.strip_suffix("IB").or_else(|| upper.strip_suffix('B')).unwrap_or(&upper);
+        let (number, multiplier): (&str, u64) = match Self::UNITS.iter().find(|(unit, _)| stripped.ends_with(*unit)) {
+            Some((_, multiplier)) => (&stripped[..stripped.len() - 1], *multiplier),
+            None => (stripped, 1),
+        };
+        if number.is_empty() {
+            return Err(ByteSizeParseError::Empty { raw: s.into() });
+        }
+
+        // Parse the number in front of it
+        let number: u64 = u64::from_str(number).map_err(|source| ByteSizeParseError::NumberParseError { raw: s.into(), source })?;
+        number.checked_mul(multiplier).map(Self).ok_or_else(|| ByteSizeParseError::Overflow { raw: s.into() })
+    }
+}
+impl TryFrom<String> for ByteSize {
+    type Error = ByteSizeParseError;
+
+    #[inline]
+    fn try_from(value: String) -> Result<Self, Self::Error> { Self::from_str(&value) }
+}
+impl From<ByteSize> for String {
+    #[inline]
+    fn from(value: ByteSize) -> Self { value.to_string() }
+}
+
+
+
+/// Defines the limits that downloads of datasets and packages are held to, to avoid accidentally filling up the disk.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DownloadLimit {
+    /// The maximum size of a single download, if any.
+    pub max_size: Option<ByteSize>,
+    /// Whether to allow downloads of which the remote does not advertise the size (through `Content-Length`).
+    pub allow_unknown_size: bool,
+}
+
+impl DownloadLimit {
+    /// Returns the limits for downloads that the user did not start explicitly (e.g., datasets or images fetched when running a workflow).
+    ///
+    /// These use the active instance's `max_download_size` default, if any. Since there is no flag to allow downloads of unknown size in that
+    /// case, those are allowed and held to the maximum while downloading (see [`DownloadLimit::check_received()`]).
+    pub fn implicit() -> Self { Self { max_size: InstanceDefaults::active().max_download_size, allow_unknown_size: true } }
+
+    /// Checks whether a download may begin.
+    ///
+    /// Downloads of unknown size are only refused if there is a maximum size to enforce and they are not explicitly allowed.
+    ///
+    /// # Arguments
+    /// - `url`: The URL that is downloaded from, used for errors.
+    /// - `size`: The size of the download as advertised by the remote, if it did.
+    ///
+    /// # Errors
+    /// This function errors if the download is larger than the maximum, or if its size is unknown while there is a maximum.
+    pub fn check(&self, url: &str, size: Option<u64>) -> Result<(), DownloadLimitError> {
+        let Some(max) = self.max_size else { return Ok(()) };
+        match size {
+            Some(size) if size > max.0 => Err(DownloadLimitError::TooLarge { url: url.into(), size: ByteSize(size), max }),
+            Some(_) => Ok(()),
+            None if self.allow_unknown_size => Ok(()),
+            None => Err(DownloadLimitError::UnknownSize { url: url.into(), max }),
+        }
+    }
+
+    /// Checks whether a download in progress may continue.
+    ///
+    /// This enforces the maximum size for downloads of which the remote did not advertise the size (or lied about it).
+    ///
+    /// # Arguments
+    /// - `url`: The URL that is downloaded from, used for errors.
+    /// - `received`: The number of bytes received so far.
+    ///
+    /// # Errors
+    /// This function errors if more bytes than the maximum have been received.
+    pub fn check_received(&self, url: &str, received: u64) -> Result<(), DownloadLimitError> {
+        match self.max_size {
+            Some(max) if received > max.0 => Err(DownloadLimitError::Exceeded { url: url.into(), max }),
+            _ => Ok(()),
+        }
+    }
+}
+
+
+
+/// An auxillary struct that defines an expected checksum of a downloaded file, given as `sha256:<HEX>`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Checksum(pub Vec<u8>);
+
+impl Display for Checksum {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "sha256:{}", hex::encode(&self.0)) }
+}
+impl FromStr for Checksum {
+    type Err = ChecksumParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split the algorithm off
+        let (algorithm, digest): (&str, &str) = match s.split_once(':') {
+            Some(pair) => pair,
+            None => {
+                return Err(ChecksumParseError::MissingAlgorithm { raw: s.into() });
+            },
+        };
+        if !algorithm.eq_ignore_ascii_case("sha256") {
+            return Err(ChecksumParseError::UnknownAlgorithm { raw: algorithm.into() });
+        }
+
+        // Parse the digest itself
+        let digest: Vec<u8> = hex::decode(digest).map_err(|source| ChecksumParseError::DigestParseError { raw: digest.into(), source })?;
+        if digest.len() != 32 {
+            return Err(ChecksumParseError::IllegalLength { raw: s.into(), len: digest.len() });
+        }
+        Ok(Self(digest))
+    }
+}
+
+
+
+/// An auxillary struct that defines a `key=value` label to attach to a package image.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Label {
+    /// The key of the label (e.g., `org.opencontainers.image.source`).
+    pub key:   String,
+    /// The value of the label.
+    pub value: String,
+}
+impl Display for Label {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}={}", self.key, self.value) }
+}
+impl FromStr for Label {
+    type Err = LabelParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on the first equals
+        let (key, value): (&str, &str) = match s.split_once('=') {
+            Some(pair) => pair,
+            None => {
+                return Err(LabelParseError::MissingEquals { raw: s.into() });
+            },
+        };
+
+        // Assert the key is sensible
+        if key.is_empty() {
+            return Err(LabelParseError::EmptyKey { raw: s.into() });
+        }
+        for c in key.chars() {
+            if !c.is_ascii_alphanumeric() && c != '.' && c != '-' && c != '_' && c != '/' {
+                return Err(LabelParseError::IllegalKeyChar { raw: key.into(), c });
+            }
+        }
+
+        // Done
+        Ok(Self { key: key.into(), value: value.into() })
+    }
+}
+
+/// An auxillary struct that defines a `KEY=VALUE` build argument to pass to the Docker build of a package image.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BuildArg {
+    /// The name of the argument, as declared by `ARG` in the Dockerfile.
+    pub key:   String,
+    /// The value of the argument.
+    pub value: String,
+}
+impl Display for BuildArg {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}={}", self.key, self.value) }
+}
+impl FromStr for BuildArg {
+    type Err = BuildArgParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split on the first equals, like labels
+        let (key, value): (&str, &str) = match s.split_once('=') {
+            Some(pair) => pair,
+            None => {
+                return Err(BuildArgParseError::Pair { source: LabelParseError::MissingEquals { raw: s.into() } });
+            },
+        };
+        if key.is_empty() {
+            return Err(BuildArgParseError::Pair { source: LabelParseError::EmptyKey { raw: s.into() } });
+        }
+
+        // Assert the key is a sensible variable name that we don't set ourselves
+        for c in key.chars() {
+            if !c.is_ascii_alphanumeric() && c != '_' {
+                return Err(BuildArgParseError::IllegalKeyChar { raw: key.into(), c });
+            }
+        }
+        if RESERVED_BUILD_ARGS.contains(&key) {
+            return Err(BuildArgParseError::Reserved { key: key.into() });
+        }
+
+        // Done
+        Ok(Self { key: key.into(), value: value.into() })
+    }
+}
+
+/// Defines the categories of rules checked by `brane workflow lint`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LintCategory {
+    /// The workflow will likely fail or not do what was intended.
+    Correctness,
+    /// The workflow works, but contains unnecessary or confusing code.
+    Style,
+}
+impl Display for LintCategory {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Correctness => write!(f, "correctness"),
+            Self::Style => write!(f, "style"),
+        }
+    }
+}
+
+/// Defines the rules checked by `brane workflow lint`, which can be suppressed by name.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LintRule {
+    /// A task result is read that is not produced anywhere in the workflow.
+    UnproducedResult,
+    /// A task has no unique location, and the planner cannot pick one for it.
+    UnresolvableLocation,
+    /// A task result is produced but never read, committed or returned.
+    UnusedResult,
+    /// A variable is declared but never read.
+    UnusedVariable,
+}
+impl LintRule {
+    /// All the rules, in the order they are listed to the user.
+    pub const ALL: [Self; 4] = [Self::UnproducedResult, Self::UnresolvableLocation, Self::UnusedResult, Self::UnusedVariable];
+
+    /// Returns the category of this rule.
+    #[inline]
+    pub fn category(&self) -> LintCategory {
+        match self {
+            Self::UnproducedResult | Self::UnresolvableLocation => LintCategory::Correctness,
+            Self::UnusedResult | Self::UnusedVariable => LintCategory::Style,
+        }
+    }
+}
+impl Display for LintRule {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::UnproducedResult => write!(f, "unproduced-result"),
+            Self::UnresolvableLocation => write!(f, "unresolvable-location"),
+            Self::UnusedResult => write!(f, "unused-result"),
+            Self::UnusedVariable => write!(f, "unused-variable"),
+        }
+    }
+}
+impl FromStr for LintRule {
+    type Err = LintRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|rule| rule.to_string() == s).ok_or_else(|| LintRuleParseError::Unknown { raw: s.into() })
+    }
+}
+
+/// Defines the kinds of names that the hidden `brane __complete` helper can complete.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CompletionKind {
+    /// The names of locally available packages (or `NAME:VERSION` pairs).
+    Package,
+    /// The names of locally available datasets.
+    Dataset,
+    /// The names of locally defined instances.
+    Instance,
+}
+impl CompletionKind {
+    /// All the kinds, in the order they are listed to the user.
+    pub const ALL: [Self; 3] = [Self::Package, Self::Dataset, Self::Instance];
+}
+impl Display for CompletionKind {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Package => write!(f, "package"),
+            Self::Dataset => write!(f, "dataset"),
+            Self::Instance => write!(f, "instance"),
+        }
+    }
+}
+impl FromStr for CompletionKind {
+    type Err = CompletionKindParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|kind| kind.to_string() == s).ok_or_else(|| CompletionKindParseError::Unknown { raw: s.into() })
+    }
+}
+
+/// Defines the formats in which `brane workflow compile` can write a compiled workflow.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum CompileFormat {
+    /// Brane's internal workflow representation, as JSON.
+    Json,
+    /// A packed CWL Workflow document.
+    Cwl,
+}
+
+/// Defines the formats in which `brane package inspect` can print a package.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum InspectFormat {
+    /// Pretty-printed to the terminal.
+    Human,
+    /// A Markdown document, e.g., for documentation sites.
+    Markdown,
+}
+
+/// Defines the formats in which reports (e.g., of `brane certs check`) can be printed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// A human-readable table.
+    Human,
+    /// A JSON document, e.g., for monitoring.
+    Json,
+}
+
+/// Defines the TLS versions that can be required as a minimum for connections to an instance.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum TlsVersion {
+    /// TLS 1.2
+    #[serde(rename = "1.2")]
+    Tls12,
+    /// TLS 1.3
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+impl Display for TlsVersion {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Tls12 => write!(f, "1.2"),
+            Self::Tls13 => write!(f, "1.3"),
+        }
+    }
+}
+impl FromStr for TlsVersion {
+    type Err = TlsVersionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1.2" => Ok(Self::Tls12),
+            "1.3" => Ok(Self::Tls13),
+            raw => Err(TlsVersionParseError::Unknown { raw: raw.into() }),
+        }
+    }
+}
+impl From<TlsVersion> for reqwest::tls::Version {
+    #[inline]
+    fn from(value: TlsVersion) -> Self {
+        match value {
+            TlsVersion::Tls12 => Self::TLS_1_2,
+            TlsVersion::Tls13 => Self::TLS_1_3,
+        }
+    }
+}
+
+
+
 /// Parses a version number that scopes a particular operation down. In other words, can be a specific version number or `all`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct VersionFix(pub Option<Version>);
@@ -144,9 +684,21 @@ impl FromStr for VersionFix {
 #[derive(Clone, Debug)]
 pub struct GlobalState {
     /// The information we want to know for Docker
-    pub docker_opts:     DockerOptions,
+    pub docker_opts: DockerOptions,
     /// Whether to keep containers after execution or not
     pub keep_containers: bool,
+    /// If given (and `keep_containers` is), kept containers may be removed by the next run once this much time has passed.
+    pub cleanup_after: Option<Duration>,
+    /// The number of times to re-execute a task that failed with a retryable error.
+    pub max_retries: u32,
+    /// If given, the resource usage of every task container is sampled and collected here, in order of execution.
+    pub stats: Option<Arc<Mutex<Vec<(String, ContainerStats)>>>>,
+    /// The outcome of every task executed so far, in order of execution: either the value it returned or why it failed.
+    pub task_results: Arc<Mutex<Vec<(String, Result<Option<FullValue>, String>)>>>,
+    /// Whether to pull the images of packages that have not been pulled yet (e.g., after a metadata-only pull) from the registry.
+    pub auto_fetch: bool,
+    /// Whether to write the output of the workflow to stderr instead of stdout (e.g., because stdout is reserved for machine-readable output).
+    pub stdout_to_stderr: bool,
 
     /// The path to the directory where packages (and thus container images) are stored for this session.
     pub package_dir: PathBuf,
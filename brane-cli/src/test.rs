@@ -23,11 +23,13 @@ use brane_tsk::input::prompt_for_input;
 use console::style;
 use specifications::data::DataIndex;
 use specifications::package::PackageInfo;
+use specifications::profiling::ProfileScopeHandle;
 use specifications::version::Version;
 
 use crate::errors::TestError;
 use crate::run::{self, OfflineVmState, initialize_offline_vm, run_offline_vm};
 use crate::utils::{ensure_datasets_dir, ensure_package_dir};
+use crate::vm::OfflineVm;
 
 
 /***** HELPER FUNCTIONS *****/
@@ -85,6 +87,9 @@ fn write_value(value: FullValue) -> String {
 /// - `show_result`: Whether or not to `cat` the resulting file if any.
 /// - `docker_opts`: The options we use to connect to the local Docker daemon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `max_retries`: The number of times to re-execute the task if it fails with a retryable error.
+/// - `stats`: Whether to sample the resource usage of the task container and report it afterwards.
+/// - `auto_fetch`: Whether to pull the image of the package from the registry if it has not been pulled yet (e.g., after a metadata-only pull).
 ///
 /// # Returns
 /// Nothing, but does do a whole dance of querying the user and executing a package based on that.
@@ -97,6 +102,9 @@ pub async fn handle(
     show_result: Option<PathBuf>,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    max_retries: u32,
+    stats: bool,
+    auto_fetch: bool,
 ) -> Result<(), TestError> {
     let name: String = name.into();
 
@@ -110,7 +118,7 @@ pub async fn handle(
     })?;
 
     // Run the test for this info
-    let output: FullValue = test_generic(package_info, show_result, docker_opts, keep_containers).await?;
+    let output: FullValue = test_generic(package_info, show_result, docker_opts, keep_containers, max_retries, stats, auto_fetch).await?;
 
     // Print it, done
     println!("Result: {} [{}]", style(format!("{output}")).bold().cyan(), style(format!("{}", output.data_type())).bold());
@@ -126,6 +134,9 @@ pub async fn handle(
 /// - `show_result`: Whether or not to `cat` the resulting file if any.
 /// - `docker_opts`: The options we use to connect to the local Docker daemon.
 /// - `keep_containers`: Whether to keep containers after execution or not.
+/// - `max_retries`: The number of times to re-execute the task if it fails with a retryable error.
+/// - `stats`: Whether to sample the resource usage of the task container and report it afterwards.
+/// - `auto_fetch`: Whether to pull the image of the package from the registry if it has not been pulled yet (e.g., after a metadata-only pull).
 ///
 /// # Returns
 /// The value of the chosen function in that package (which may be Void this time).
@@ -134,6 +145,9 @@ pub async fn test_generic(
     show_result: Option<PathBuf>,
     docker_opts: DockerOptions,
     keep_containers: bool,
+    max_retries: u32,
+    stats: bool,
+    auto_fetch: bool,
 ) -> Result<FullValue, TestError> {
     // Get the local datasets directory
     let datasets_dir: PathBuf = ensure_datasets_dir(true).map_err(|source| TestError::DatasetsDirError { source })?;
@@ -163,7 +177,8 @@ pub async fn test_generic(
 
     // We run it by spinning up an offline VM
     let mut state: OfflineVmState =
-        initialize_offline_vm(ParserOptions::bscript(), docker_opts, keep_containers).map_err(|source| TestError::InitializeError { source })?;
+        initialize_offline_vm(ParserOptions::bscript(), docker_opts, keep_containers, None, max_retries, stats, auto_fetch, false)
+            .map_err(|source| TestError::InitializeError { source })?;
 
     // Compile the workflow
     let snippet = Snippet::from_source(
@@ -178,7 +193,11 @@ pub async fn test_generic(
     )
     .map_err(|source| TestError::RunError { source: run::Error::CompileError(source) })?;
 
-    let result: FullValue = run_offline_vm(&mut state, snippet).await.map_err(|source| TestError::RunError { source })?;
+    let result: Result<FullValue, run::Error> = run_offline_vm(&mut state, snippet, ProfileScopeHandle::dummy()).await;
+    if let Some(stats) = state.vm.as_ref().and_then(OfflineVm::task_stats) {
+        run::process_task_stats(&stats, false);
+    }
+    let result: FullValue = result.map_err(|source| TestError::RunError { source })?;
 
     // Write the intermediate result if told to do so
     if let Some(file) = show_result {
@@ -344,6 +344,8 @@ pub fn data(path: impl Into<PathBuf>, dry_run: bool, overwrite: bool, version: V
                             })
                         })
                         .collect(),
+                    checksums: None,
+                    size: None,
                 })
             }))
         }),
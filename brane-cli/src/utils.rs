@@ -14,13 +14,44 @@
 
 use std::fs::{self, File};
 use std::io::Read;
-use std::path::{Path, PathBuf};
-
-use specifications::package::PackageKind;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Output};
+use std::str::FromStr as _;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use brane_tsk::docker::{self, DockerOptions};
+use brane_tsk::errors::ApiError;
+use console::style;
+use log::warn;
+use reqwest::{Client, ClientBuilder};
+use specifications::data::DataIndex;
+use specifications::driving::{DriverServiceClient, DriverServiceError};
+use specifications::package::{PackageIndex, PackageKind};
 use specifications::version::Version;
+use tonic::transport::Endpoint;
+
+use crate::errors::{OutOfSpaceError, UtilError};
+use crate::spec::TlsVersion;
+use crate::{MIN_BUILDX_VERSION, MIN_DOCKER_VERSION};
+
+
+/***** GLOBALS *****/
+/// Whether the HTTP clients returned by [`http_client()`] skip TLS certificate verification.
+static INSECURE_SKIP_VERIFY: AtomicBool = AtomicBool::new(false);
+/// The minimum TLS version that HTTP clients configured by [`configure_client()`] negotiate, if restricted at all.
+static MIN_TLS_VERSION: OnceLock<TlsVersion> = OnceLock::new();
+/// The `User-Agent` header sent by HTTP clients configured by [`configure_client()`], if overridden.
+static USER_AGENT: OnceLock<String> = OnceLock::new();
+
+/// The `User-Agent` header sent by HTTP clients unless overridden with [`set_user_agent()`].
+pub const DEFAULT_USER_AGENT: &str = concat!("brane-cli/", env!("CARGO_PKG_VERSION"));
+/// How long we wait for the Docker daemon to answer a probe before we consider it hung.
+pub const DOCKER_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+
 
-// use crate::{MIN_DOCKER_VERSION, MIN_BUILDX_VERSION};
-use crate::errors::UtilError;
 
 
 /***** HELPER ENUMS *****/
@@ -43,19 +74,451 @@ pub enum DependencyError {
 }
 
 /***** UTILITIES *****/
+/// Decides whether styled output uses colour for the remainder of this process, and configures [`console`] (and everything styling
+/// through it) accordingly.
+///
+/// Colour is disabled if `no_color` is given or if the `NO_COLOR` environment variable is set to a non-empty value (see
+/// <https://no-color.org>). Otherwise, it is only used for streams that are a terminal, unless overridden with `CLICOLOR`/`CLICOLOR_FORCE`.
+///
+/// # Arguments
+/// - `no_color`: Whether the user passed `--no-color`.
+pub fn init_colors(no_color: bool) {
+    let no_color: bool = no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    // Resolve both streams now, such that every styling site agrees on the outcome
+    console::set_colors_enabled(!no_color && console::colors_enabled());
+    console::set_colors_enabled_stderr(!no_color && console::colors_enabled_stderr());
+}
+
+/// Disables TLS certificate verification for all HTTP clients created by [`http_client()`] for the remainder of this process.
+///
+/// Only intended for throwaway development instances with self-signed certificates; use `brane certs add` for anything else.
+#[inline]
+pub fn set_insecure_skip_verify() { INSECURE_SKIP_VERIFY.store(true, Ordering::SeqCst); }
+
+/// Restricts all HTTP clients configured by [`configure_client()`] to the given TLS version or later for the remainder of this process.
+///
+/// Only the first call has any effect.
+///
+/// # Arguments
+/// - `version`: The minimum [`TlsVersion`] to negotiate.
+#[inline]
+pub fn set_min_tls_version(version: TlsVersion) {
+    if MIN_TLS_VERSION.set(version).is_err() {
+        warn!("Minimum TLS version was already set; ignoring TLS {version}");
+    }
+}
+
+/// Returns the minimum TLS version set with [`set_min_tls_version()`], if any.
+#[inline]
+pub fn min_tls_version() -> Option<TlsVersion> { MIN_TLS_VERSION.get().copied() }
+
+/// Overrides the `User-Agent` header sent by all HTTP clients configured by [`configure_client()`] for the remainder of this process.
+///
+/// Only the first call has any effect.
+///
+/// # Arguments
+/// - `agent`: The value of the header to send instead of [`DEFAULT_USER_AGENT`].
+#[inline]
+pub fn set_user_agent(agent: String) {
+    if let Err(agent) = USER_AGENT.set(agent) {
+        warn!("User agent was already set; ignoring '{agent}'");
+    }
+}
+
+/// Returns the `User-Agent` header set with [`set_user_agent()`], or [`DEFAULT_USER_AGENT`] if it wasn't.
+#[inline]
+pub fn user_agent() -> &'static str { USER_AGENT.get().map(String::as_str).unwrap_or(DEFAULT_USER_AGENT) }
+
+/// Returns whether the given error was caused by a connection not meeting the minimum TLS version set with [`set_min_tls_version()`].
+///
+/// That is the case if the remote refused to negotiate it, if we refused what the remote offered, or if we refused to connect without TLS
+/// at all.
+///
+/// # Arguments
+/// - `err`: The error to inspect, including its sources.
+///
+/// # Returns
+/// The minimum [`TlsVersion`] that was not met, or [`None`] if the error is unrelated (or no minimum was set).
+pub fn refused_min_tls_version(err: &(dyn std::error::Error + 'static)) -> Option<TlsVersion> {
+    let version: TlsVersion = min_tls_version()?;
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        // rustls errors are usually wrapped in an I/O error, which hides them from the source chain
+        let tls_err: Option<&rustls_reqwest::Error> = err
+            .downcast_ref::<rustls_reqwest::Error>()
+            .or_else(|| err.downcast_ref::<std::io::Error>().and_then(std::io::Error::get_ref).and_then(|err| err.downcast_ref()));
+        match tls_err {
+            Some(rustls_reqwest::Error::AlertReceived(rustls_reqwest::AlertDescription::ProtocolVersion))
+            | Some(rustls_reqwest::Error::PeerIncompatible(
+                rustls_reqwest::PeerIncompatible::ServerTlsVersionIsDisabledByOurConfig
+                | rustls_reqwest::PeerIncompatible::ServerDoesNotSupportTls12Or13,
+            )) => return Some(version),
+            _ => {},
+        }
+        if let Some(DriverServiceError::TlsRequired { .. }) = err.downcast_ref::<DriverServiceError>() {
+            return Some(version);
+        }
+        if err.downcast_ref::<reqwest::Error>().is_some_and(|err| err.is_builder() && err.url().is_some_and(|url| url.scheme() != "https")) {
+            return Some(version);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Configures the given client builder like every HTTP client of the CLI.
+///
+/// This sets the `User-Agent` header of the client (see [`set_user_agent()`]) and applies the TLS configuration of this process (see
+/// [`set_min_tls_version()`]). If a minimum TLS version is set, the client also refuses to send requests without TLS.
+///
+/// # Arguments
+/// - `builder`: The [`ClientBuilder`] to configure.
+///
+/// # Returns
+/// The same `builder`, but configured.
+#[inline]
+pub fn configure_client(builder: ClientBuilder) -> ClientBuilder {
+    let builder: ClientBuilder = builder.user_agent(user_agent());
+    match min_tls_version() {
+        Some(version) => builder.min_tls_version(version.into()).https_only(true),
+        None => builder,
+    }
+}
+
+/// Connects to the `brane-drv` of an instance, respecting the configuration of this process like [`configure_client()`] does.
+///
+/// The gRPC channel does not support TLS, so if a minimum TLS version is set (see [`set_min_tls_version()`]), the connection is refused.
+///
+/// # Arguments
+/// - `address`: The address of the driver to connect to.
+///
+/// # Returns
+/// A [`DriverServiceClient`] that is connected to the driver.
+///
+/// # Errors
+/// This function errors if a minimum TLS version is set or if we failed to connect.
+pub async fn connect_driver(address: impl Into<String>) -> Result<DriverServiceClient, DriverServiceError> {
+    let address: String = address.into();
+    if min_tls_version().is_some() {
+        return Err(DriverServiceError::TlsRequired { address });
+    }
+
+    let endpoint: Endpoint = Endpoint::new(address.clone())
+        .and_then(|endpoint| endpoint.user_agent(user_agent()))
+        .map_err(|err| DriverServiceError::EndpointError { address, err })?;
+    DriverServiceClient::connect_with(endpoint).await
+}
+
+/// Prepares the builder for the HTTP clients used to talk to the `brane-api` of an instance.
+///
+/// If [`set_insecure_skip_verify()`] has been called, the client will not verify the instance's TLS certificate, and a warning is printed every time.
+/// It also respects any minimum TLS version set with [`set_min_tls_version()`].
+///
+/// # Returns
+/// A new [`ClientBuilder`].
+fn http_client_builder() -> ClientBuilder {
+    let builder = configure_client(Client::builder());
+    if !INSECURE_SKIP_VERIFY.load(Ordering::SeqCst) {
+        return builder;
+    }
+    eprintln!(
+        "{}",
+        style("WARNING: TLS certificate verification is DISABLED (--insecure-skip-verify); only use this with development instances!").bold().red()
+    );
+    builder.danger_accept_invalid_certs(true)
+}
+
+/// Builds the HTTP client used to talk to the `brane-api` of an instance.
+///
+/// See [`http_client_builder()`] for how it is configured.
+///
+/// # Returns
+/// A new [`reqwest::Client`].
+///
+/// # Errors
+/// This function errors if we failed to build the client.
+#[inline]
+pub fn http_client() -> Result<Client, UtilError> { http_client_builder().build().map_err(|source| UtilError::HttpClientBuildError { source }) }
+
+/// Downloads the package index of an instance, using a client built like [`http_client()`].
+///
+/// # Arguments
+/// - `endpoint`: The GraphQL endpoint of the instance's `brane-api`.
+///
+/// # Returns
+/// The [`PackageIndex`] of the instance.
+///
+/// # Errors
+/// This function errors if we failed to build the client or to retrieve the index.
+pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, ApiError> {
+    let endpoint: &str = endpoint.as_ref();
+    let client: Client = http_client_builder().build().map_err(|source| ApiError::ClientBuildError { address: endpoint.into(), source })?;
+    brane_tsk::api::get_package_index_with(&client, endpoint).await
+}
+
+/// Revalidates a previously downloaded package index of an instance, using a client built like [`http_client()`].
+///
+/// # Arguments
+/// - `endpoint`: The package list endpoint of the instance's `brane-api`.
+/// - `cached`: The [`PackageIndex`] that we have already.
+///
+/// # Returns
+/// [`None`] if `cached` is still current, or the new [`PackageIndex`] of the instance if it has changed.
+///
+/// # Errors
+/// This function errors if we failed to build the client or to revalidate the index.
+pub async fn revalidate_package_index(endpoint: impl AsRef<str>, cached: &PackageIndex) -> Result<Option<PackageIndex>, ApiError> {
+    let endpoint: &str = endpoint.as_ref();
+    let client: Client = http_client_builder().build().map_err(|source| ApiError::ClientBuildError { address: endpoint.into(), source })?;
+    brane_tsk::api::revalidate_package_index_with(&client, endpoint, cached).await
+}
+
+/// Downloads the data index of an instance, using a client built like [`http_client()`].
+///
+/// # Arguments
+/// - `endpoint`: The data info endpoint of the instance's `brane-api`.
+///
+/// # Returns
+/// The [`DataIndex`] of the instance.
+///
+/// # Errors
+/// This function errors if we failed to build the client or to retrieve the index.
+pub async fn get_data_index(endpoint: impl AsRef<str>) -> Result<DataIndex, ApiError> {
+    let endpoint: &str = endpoint.as_ref();
+    let client: Client = http_client_builder().build().map_err(|source| ApiError::ClientBuildError { address: endpoint.into(), source })?;
+    brane_tsk::api::get_data_index_with(&client, endpoint).await
+}
+
+/// Returns whether the given I/O error occurred because the disk is full or the user's disk quota is exceeded.
+pub fn is_out_of_space(err: &std::io::Error) -> bool { matches!(err.kind(), std::io::ErrorKind::StorageFull | std::io::ErrorKind::QuotaExceeded) }
+
+/// Finds the mount point of the volume holding the given path, as far as we can tell.
+///
+/// On Unix, this is the topmost (existing) ancestor of the path on the same device. Elsewhere, it is simply the root of the path.
+fn volume_of(path: &Path) -> PathBuf {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt as _;
+
+        // Start at the closest ancestor that exists (the path itself may not have been created)
+        let Some((mut volume, dev)): Option<(&Path, u64)> = path.ancestors().find_map(|p| fs::metadata(p).ok().map(|meta| (p, meta.dev()))) else {
+            return path.into();
+        };
+        for ancestor in volume.ancestors().skip(1) {
+            match fs::metadata(ancestor) {
+                Ok(meta) if meta.dev() == dev => volume = ancestor,
+                _ => break,
+            }
+        }
+        volume.into()
+    }
+    #[cfg(not(unix))]
+    {
+        path.ancestors().last().unwrap_or(path).into()
+    }
+}
+
+/// Maps an I/O error to an [`OutOfSpaceError`] if it occurred because the disk is full, or to some other error otherwise.
+///
+/// # Arguments
+/// - `path`: The path that we failed to write.
+/// - `source`: The I/O error that occurred.
+/// - `otherwise`: Builds the error to return if the disk is not full.
+///
+/// # Returns
+/// The error to return.
+pub fn out_of_space_or<E: From<OutOfSpaceError>>(path: &Path, source: std::io::Error, otherwise: impl FnOnce(std::io::Error) -> E) -> E {
+    if is_out_of_space(&source) { OutOfSpaceError { path: path.into(), volume: volume_of(path), source }.into() } else { otherwise(source) }
+}
+
+/// Maps a [`brane_shr::fs::Error`] to an [`OutOfSpaceError`] if it occurred because the disk is full, or to some other error otherwise.
+///
+/// See [`out_of_space_or()`] for more information.
+pub fn shr_out_of_space_or<E: From<OutOfSpaceError>>(source: brane_shr::fs::Error, otherwise: impl FnOnce(brane_shr::fs::Error) -> E) -> E {
+    use brane_shr::fs::Error;
+    match source {
+        Error::FileCreateError { path, err, .. } | Error::FileWriteError { path, err, .. } | Error::DirCreateError { path, err, .. }
+            if is_out_of_space(&err) =>
+        {
+            OutOfSpaceError { volume: volume_of(&path), path, source: err }.into()
+        },
+        Error::FileCopyError { target, err, .. } if is_out_of_space(&err) => {
+            OutOfSpaceError { volume: volume_of(&target), path: target, source: err }.into()
+        },
+        source => otherwise(source),
+    }
+}
+
+
 /// **Edited: Now returning UtilErrors.**
 ///
 /// Checks the runtime dependencies of brane-cli (Docker + BuildKit)
 ///
+/// Each dependency can also be checked on its own with [`check_docker_daemon()`], [`check_docker_version()`] and
+/// [`check_buildx_version()`].
+///
+/// **Arguments**
+///  * `docker_opts`: The options with which to connect to the Docker daemon, or [`None`] to use the local defaults.
+///  * `buildx`: Whether to check the Buildx plugin as well (only needed when building images).
+///
 /// **Returns**  
 /// Nothing if the dependencies are met, a DependencyError if it wasn't, or a UtilError if we couldn't determine.
-pub async fn check_dependencies() -> Result<Result<(), DependencyError>, UtilError> {
+pub async fn check_dependencies(docker_opts: Option<&DockerOptions>, buildx: bool) -> Result<Result<(), DependencyError>, UtilError> {
+    // Connect to the daemon that the subcommand will use
+    let docker: bollard::Docker = match docker_opts {
+        Some(opts) => docker::connect_local(opts).map_err(|source| UtilError::DockerConnectError { source })?,
+        None => bollard::Docker::connect_with_local_defaults().map_err(|source| UtilError::DockerConnectionFailed { source })?,
+    };
+
+    // Make sure it's actually there
+    match check_docker_daemon(&docker).await {
+        Ok(()) => {},
+        Err(err @ UtilError::DockerNotResponding { .. }) => return Err(err),
+        Err(_) => return Ok(Err(DependencyError::DockerNotInstalled)),
+    }
+    if let Err(err) = check_docker_version(&docker).await? {
+        return Ok(Err(err));
+    }
+
+    // Only check the plugin if we're going to build with it
+    if buildx {
+        match check_buildx_version() {
+            Ok(Ok(_)) => {},
+            Ok(Err(err)) => return Ok(Err(err)),
+            Err(_) => return Ok(Err(DependencyError::BuildkitNotInstalled)),
+        }
+    }
+
     // We checked all the runtime dependencies! (:sweat:)
     Ok(Ok(()))
 }
 
 
 
+/// Strips any non-numeric suffix (e.g., `+dfsg1` or `-rc1`) from a raw version string such that it can be parsed as a [`Version`].
+///
+/// # Arguments
+/// - `raw`: The raw version string to strip.
+///
+/// # Returns
+/// The longest prefix of `raw` that consists of only digits and dots.
+fn strip_version_suffix(raw: &str) -> &str {
+    let end: usize = raw.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(raw.len());
+    &raw[..end]
+}
+
+/// Queries the version of the Docker daemon we're connected to.
+///
+/// # Arguments
+/// - `docker`: The connection to the Docker daemon to query.
+///
+/// # Returns
+/// The version reported by the Docker daemon.
+///
+/// # Errors
+/// This function errors if we failed to query the daemon, if it did not answer within [`DOCKER_PROBE_TIMEOUT`] or if it reported no (or an
+/// unparseable) version number.
+pub async fn get_docker_version(docker: &bollard::Docker) -> Result<Version, UtilError> {
+    // Get the version from the daemon, without hanging on one that accepts connections but never answers
+    let version: bollard::models::SystemVersion = tokio::time::timeout(DOCKER_PROBE_TIMEOUT, docker.version())
+        .await
+        .map_err(|_| UtilError::DockerNotResponding { timeout: DOCKER_PROBE_TIMEOUT })?
+        .map_err(|source| UtilError::DockerVersionError { source })?;
+    let version: String = version.version.ok_or(UtilError::DockerNoVersion)?;
+
+    // Parse it
+    Version::from_str(strip_version_suffix(&version)).map_err(|source| UtilError::IllegalDockerVersion { version, source })
+}
+
+/// Queries the version of the Docker Buildx plugin by running `docker buildx version`.
+///
+/// # Returns
+/// The version reported by Buildx.
+///
+/// # Errors
+/// This function errors if we failed to run the command or if it reported an unparseable version number.
+pub fn get_buildx_version() -> Result<Version, UtilError> {
+    // Run the command
+    let mut cmd: Command = Command::new("docker");
+    cmd.args(["buildx", "version"]);
+    let output: Output = cmd.output().map_err(|source| UtilError::BuildxLaunchError { command: format!("{cmd:?}"), source })?;
+    if !output.status.success() {
+        return Err(UtilError::BuildxLaunchError {
+            command: format!("{cmd:?}"),
+            source:  std::io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        });
+    }
+
+    // The output is formatted as `github.com/docker/buildx v0.11.2 9872040`; extract the middle part
+    let version: String = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let raw: &str = match version.split(' ').nth(1) {
+        Some(raw) => raw,
+        None => {
+            return Err(UtilError::BuildxVersionNoParts { version });
+        },
+    };
+    let raw: &str = match raw.strip_prefix('v') {
+        Some(raw) => raw,
+        None => {
+            return Err(UtilError::BuildxVersionNoV { version });
+        },
+    };
+
+    // Parse it
+    Version::from_str(strip_version_suffix(raw)).map_err(|source| UtilError::IllegalBuildxVersion { version, source })
+}
+
+/// Checks whether the Docker daemon answers a ping.
+///
+/// # Arguments
+/// - `docker`: The connection to the Docker daemon to check.
+///
+/// # Errors
+/// This function errors if the daemon could not be reached or if it did not answer within [`DOCKER_PROBE_TIMEOUT`].
+pub async fn check_docker_daemon(docker: &bollard::Docker) -> Result<(), UtilError> {
+    tokio::time::timeout(DOCKER_PROBE_TIMEOUT, docker.ping())
+        .await
+        .map_err(|_| UtilError::DockerNotResponding { timeout: DOCKER_PROBE_TIMEOUT })?
+        .map_err(|source| UtilError::DockerConnectionFailed { source })?;
+    Ok(())
+}
+
+/// Checks whether the Docker daemon is at least [`MIN_DOCKER_VERSION`].
+///
+/// # Arguments
+/// - `docker`: The connection to the Docker daemon to check.
+///
+/// # Returns
+/// The version of the daemon if it is recent enough, or a [`DependencyError::DockerMinNotMet`] if it isn't.
+///
+/// # Errors
+/// This function errors if we failed to determine the version (see [`get_docker_version()`]).
+pub async fn check_docker_version(docker: &bollard::Docker) -> Result<Result<Version, DependencyError>, UtilError> {
+    let version: Version = get_docker_version(docker).await?;
+    if version < MIN_DOCKER_VERSION {
+        return Ok(Err(DependencyError::DockerMinNotMet { got: version, expected: MIN_DOCKER_VERSION }));
+    }
+    Ok(Ok(version))
+}
+
+/// Checks whether the Docker Buildx plugin is at least [`MIN_BUILDX_VERSION`].
+///
+/// # Returns
+/// The version of the plugin if it is recent enough, or a [`DependencyError::BuildKitMinNotMet`] if it isn't.
+///
+/// # Errors
+/// This function errors if we failed to determine the version (see [`get_buildx_version()`]), which typically means the plugin is not
+/// installed.
+pub fn check_buildx_version() -> Result<Result<Version, DependencyError>, UtilError> {
+    let version: Version = get_buildx_version()?;
+    if version < MIN_BUILDX_VERSION {
+        return Ok(Err(DependencyError::BuildKitMinNotMet { got: version, expected: MIN_BUILDX_VERSION }));
+    }
+    Ok(Ok(version))
+}
+
+
+
 /// **Edited: now returning CliErrors.**
 ///
 /// Tries to determine the package file in the pulled repository.
@@ -589,6 +1052,20 @@ pub fn get_instance_dir(name: impl AsRef<str>) -> Result<PathBuf, UtilError> {
     Ok(instances_dir.join(name.as_ref()))
 }
 
+/// Gets the names of all instances defined locally.
+///
+/// Since this is only used to suggest names in errors, any failure to read the instances directory is treated as there being no instances.
+///
+/// # Returns
+/// The names of the instances, in no particular order.
+pub fn get_instance_names() -> Vec<String> {
+    let entries = match get_instances_dir().map(fs::read_dir) {
+        Ok(Ok(entries)) => entries,
+        _ => return vec![],
+    };
+    entries.filter_map(Result::ok).filter(|entry| entry.path().is_dir()).map(|entry| entry.file_name().to_string_lossy().into_owned()).collect()
+}
+
 /// Gets the directory where we store the instance definition for the given instance and ensures it exists.
 ///
 /// # Arguments
@@ -662,6 +1139,23 @@ pub fn uppercase_first_letter(s: &str) -> String {
 
 
 
+/// Checks whether the given name can be used as the name of a local package or dataset, i.e., as a single directory name.
+///
+/// This is important for names that come from untrusted sources (e.g., archives), since they are joined with local paths.
+///
+/// # Arguments
+/// - `name`: The name to check.
+///
+/// # Errors
+/// This function errors if the name is empty, contains path separators or is a special directory name (e.g., `..`).
+pub fn assert_valid_entry_name(name: &str) -> Result<(), UtilError> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !name.contains(['/', '\\']) => Ok(()),
+        _ => Err(UtilError::InvalidEntryName { name: name.into() }),
+    }
+}
+
 /// Checks whether the given string is a valid name for Bakery.
 ///
 /// **Arguments**
@@ -21,6 +21,7 @@ use specifications::version::Version;
 
 use crate::errors::VersionError;
 use crate::instance::InstanceInfo;
+use crate::utils::http_client;
 
 
 /***** HELPER STRUCTS *****/
@@ -91,7 +92,8 @@ impl RemoteVersion {
         debug!(" > Querying...");
         let mut url: String = info.api.to_string();
         url.push_str("/version");
-        let response: Response = reqwest::get(&url).await.map_err(|source| VersionError::RequestError { url: url.clone(), source })?;
+        let client: reqwest::Client = http_client().map_err(|source| VersionError::HttpClientError { source })?;
+        let response: Response = client.get(&url).send().await.map_err(|source| VersionError::RequestError { url: url.clone(), source })?;
         if response.status() != StatusCode::OK {
             return Err(VersionError::RequestFailure { url, status: response.status() });
         }
@@ -17,6 +17,7 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD;
@@ -29,11 +30,14 @@ use brane_exe::spec::{RunState, TaskInfo, VmPlugin};
 use brane_exe::value::FullValue;
 use brane_shr::formatters::BlockFormatter;
 use brane_shr::fs::copy_dir_recursively_async;
-use brane_tsk::docker::{self, DockerOptions, ExecuteInfo, ImageSource, Network};
+use brane_shr::utilities::closest_names;
+use brane_tsk::docker::{self, ContainerStats, DockerOptions, ExecuteInfo, ImageSource, Network};
 use brane_tsk::errors::{CommitError, ExecuteError, PreprocessError, StdoutError};
+use brane_tsk::retry;
 use brane_tsk::spec::{LOCALHOST, Planner as _};
 use brane_tsk::tools::decode_base64;
 use chrono::Utc;
+use error_trace::ErrorTrace as _;
 use log::{debug, info};
 use parking_lot::Mutex;
 use specifications::container::{Image, VolumeBind};
@@ -44,48 +48,106 @@ use tokio::fs as tfs;
 use tokio::io::AsyncWriteExt;
 
 pub use crate::errors::OfflineVmError as Error;
+use crate::errors::RegistryError;
 use crate::planner::OfflinePlanner;
-use crate::spec::{GlobalState, LocalState};
+use crate::spec::{DownloadLimit, GlobalState, LocalState};
+use crate::{IMAGE_ABSENT_FILE, registry};
 
 
 /***** AUXILLARY *****/
-/// Defines the plugins used that implement offline task execution.
-pub struct OfflinePlugin;
+/// Promotes the given (intermediate result) file or directory to a new local dataset.
+///
+/// # Arguments
+/// - `dataset_dir`: The directory where all local datasets live.
+/// - `data_name`: The name of the new dataset.
+/// - `description`: An optional description to put in the new dataset's `data.yml`.
+/// - `source`: The path to the file or directory that will become the dataset's contents.
+///
+/// # Errors
+/// This function errors if we failed to create the dataset directory, write its `data.yml` or copy over the data.
+pub async fn create_dataset(dataset_dir: &Path, data_name: &str, description: Option<String>, source: impl AsRef<Path>) -> Result<(), CommitError> {
+    let source: &Path = source.as_ref();
+
+    // Prepare the package directory by creating it if it doesn't exist yet
+    let dir: PathBuf = dataset_dir.join(data_name);
+    if !dir.is_dir() {
+        if dir.exists() {
+            return Err(CommitError::DataDirNotADir { path: dir });
+        }
+        tfs::create_dir_all(&dir).await.map_err(|source| CommitError::DataDirCreateError { path: dir.clone(), source })?;
+    }
 
-#[async_trait::async_trait]
-impl VmPlugin for OfflinePlugin {
-    type CommitError = CommitError;
-    type ExecuteError = ExecuteError;
-    type GlobalState = GlobalState;
-    type LocalState = LocalState;
-    type PreprocessError = PreprocessError;
-    type StdoutError = StdoutError;
+    // Create a new DataInfo struct
+    let info: DataInfo = DataInfo {
+        name: data_name.into(),
+        owners: None, // TODO: Merge parent datasets??
+        description,  // TODO: Add parents & algorithm in description??
+        created: Utc::now(),
+
+        access: HashMap::from([("localhost".into(), AccessKind::File { path: dir.join("data") })]),
+
+        checksums: None,
+        size: None,
+    };
+
+    // Write it to the target folder
+    let info_path: PathBuf = dir.join("data.yml");
+    let mut handle: tfs::File =
+        tfs::File::create(&info_path).await.map_err(|source| CommitError::DataInfoCreateError { path: info_path.clone(), source })?;
+    let sinfo: String = serde_yaml::to_string(&info).map_err(|source| CommitError::DataInfoSerializeError { source })?;
+    handle.write_all(sinfo.as_bytes()).await.map_err(|source| CommitError::DataInfoWriteError { path: info_path.clone(), source })?;
+
+    // Finally, copy the intermediate file to the target
+    let target: PathBuf = dir.join("data");
+    debug!("Copying '{}' to '{}'...", source.display(), target.display());
+    copy_dir_recursively_async(source, target).await.map_err(|source| CommitError::DataCopyError { source })?;
+
+    // The dataset has now been promoted
+    debug!("Dataset created successfully.");
+    Ok(())
+}
 
-    async fn preprocess(
-        _global: Arc<RwLock<Self::GlobalState>>,
-        _local: Self::LocalState,
-        pc: ProgramCounter,
-        _loc: Location,
-        name: DataName,
-        preprocess: PreprocessKind,
-        _prof: ProfileScopeHandle<'_>,
-    ) -> Result<AccessKind, Self::PreprocessError> {
-        info!("Preprocessing data '{name}' for call at {pc} in an offline environment");
-        debug!("Method of preprocessing: {preprocess:?}");
+/// Pulls the image of a package of which only the metadata is present locally, verifying it against the digest in that metadata.
+///
+/// # Arguments
+/// - `name`: The name of the package.
+/// - `pinfo`: The local [`PackageInfo`] of the package, which is what the workflow was compiled against.
+///
+/// # Errors
+/// This function errors if we failed to pull the image or if it does not have the digest in `pinfo`. In the latter case, the local package is
+/// left untouched, such that the mismatching image is never run.
+async fn fetch_image(name: &str, pinfo: &PackageInfo) -> Result<(), ExecuteError> {
+    info!("Image of package '{}' (version {}) has not been pulled yet; pulling it now...", name, pinfo.version);
+    registry::pull_package(name, pinfo.version, None, pinfo.digest.as_deref(), false, &DownloadLimit::implicit()).await.map_err(
+        |source| match source {
+            RegistryError::ImageDigestMismatch { name, version, expected, got } => ExecuteError::ImageDigestMismatch { name, version, expected, got },
+            source => ExecuteError::ImagePullError { name: name.into(), version: pinfo.version, source: Box::new(source) },
+        },
+    )?;
+    Ok(())
+}
 
-        // Match on the type of preprocessing
-        match preprocess {
-            // Anything that requires transfers, fails
-            PreprocessKind::TransferRegistryTar { .. } => Err(PreprocessError::UnavailableData { name }),
-        }
-    }
+/// Defines the plugins used that implement offline task execution.
+pub struct OfflinePlugin;
 
-    async fn execute(
-        global: &Arc<RwLock<Self::GlobalState>>,
-        _local: &Self::LocalState,
+impl OfflinePlugin {
+    /// Executes a task once on the local Docker daemon.
+    ///
+    /// # Arguments
+    /// - `global`: The global VM state to get the Docker options, directories and package index from.
+    /// - `info`: The task to execute.
+    /// - `prof`: The ProfileScopeHandle in which to time the execution.
+    ///
+    /// # Returns
+    /// The value returned by the task, if any.
+    ///
+    /// # Errors
+    /// This function errors if the task could not be run or failed.
+    async fn execute_once(
+        global: &Arc<RwLock<GlobalState>>,
         info: TaskInfo<'_>,
-        prof: ProfileScopeHandle<'_>,
-    ) -> Result<Option<FullValue>, Self::ExecuteError> {
+        prof: &ProfileScopeHandle<'_>,
+    ) -> Result<Option<FullValue>, ExecuteError> {
         let mut info = info;
         info!("Calling task '{}' in an offline environment", info.name);
         debug!("Package: '{}', version {}", info.package_name, info.package_version);
@@ -101,12 +163,25 @@ impl VmPlugin for OfflinePlugin {
             let state: RwLockReadGuard<GlobalState> = global.read().unwrap();
             (state.docker_opts.clone(), state.package_dir.clone(), state.results_dir.clone(), state.pindex.clone(), state.keep_containers)
         };
+        let stats: Option<Arc<Mutex<Vec<(String, ContainerStats)>>>> = global.read().unwrap().stats.clone();
+        let cleanup_after: Option<Duration> = global.read().unwrap().cleanup_after;
+        let auto_fetch: bool = global.read().unwrap().auto_fetch;
 
         // Next, we resolve the package
         let pinfo: &PackageInfo =
             match pindex.get(info.package_name, if info.package_version.is_latest() { None } else { Some(info.package_version) }) {
                 Some(pinfo) => pinfo,
-                None => return Err(ExecuteError::UnknownPackage { name: info.package_name.into(), version: *info.package_version }),
+                None => {
+                    return Err(ExecuteError::UnknownPackage {
+                        name: info.package_name.into(),
+                        version: *info.package_version,
+                        suggestions: if pindex.latest.contains_key(info.package_name) {
+                            vec![]
+                        } else {
+                            closest_names(info.package_name, pindex.latest.keys())
+                        },
+                    });
+                },
             };
         get.stop();
 
@@ -116,12 +191,23 @@ impl VmPlugin for OfflinePlugin {
             .await?;
         let params: String = serde_json::to_string(&info.args).map_err(|source| ExecuteError::ArgsEncodeError { source })?;
 
+        // If the image of the package has not been pulled (e.g., only its metadata was), pull it first
+        let version_dir: PathBuf = package_dir.join(info.package_name).join(pinfo.version.to_string());
+        if version_dir.join(IMAGE_ABSENT_FILE).exists() {
+            if !auto_fetch {
+                return Err(ExecuteError::ImageNotPulled { name: info.package_name.into(), version: pinfo.version });
+            }
+            let fetch = prof.time("Image fetch");
+            fetch_image(info.package_name, pinfo).await?;
+            fetch.stop();
+        }
+
         // Create an ExecuteInfo with that
         let image: Image = Image::new(info.package_name, Some(info.package_version), Some(pinfo.digest.as_ref().unwrap()));
-        let einfo: ExecuteInfo = ExecuteInfo {
+        let mut einfo: ExecuteInfo = ExecuteInfo {
             name: info.name.into(),
             image: image.clone(),
-            image_source: ImageSource::Path(package_dir.join(info.package_name).join(info.package_version.to_string()).join("image.tar")),
+            image_source: ImageSource::Path(version_dir.join("image.tar")),
 
             command: vec![
                 "-d".into(),
@@ -138,14 +224,28 @@ impl VmPlugin for OfflinePlugin {
             binds,
             network: Network::None,
             capabilities: info.requirements.clone(),
+            labels: HashMap::new(),
         };
+        if let (true, Some(after)) = (keep_container, cleanup_after) {
+            einfo.cleanup_after(after);
+        }
 
         // We can now execute the task on the local Docker daemon
         debug!("Executing task '{}'...", info.name);
-        let (code, stdout, stderr) = prof
-            .time_fut("execution", docker::run_and_wait(docker_opts, einfo, keep_container))
-            .await
-            .map_err(|source| ExecuteError::DockerError { name: info.name.into(), image: Box::new(image.clone()), source })?;
+        let (code, stdout, stderr) = match &stats {
+            Some(stats) => {
+                let (res, task_stats): ((i32, String, String), ContainerStats) = prof
+                    .time_fut("execution", docker::run_and_wait_with_stats(docker_opts, einfo, keep_container))
+                    .await
+                    .map_err(|source| ExecuteError::DockerError { name: info.name.into(), image: Box::new(image.clone()), source })?;
+                stats.lock().push((info.name.into(), task_stats));
+                res
+            },
+            None => prof
+                .time_fut("execution", docker::run_and_wait(docker_opts, einfo, keep_container))
+                .await
+                .map_err(|source| ExecuteError::DockerError { name: info.name.into(), image: Box::new(image.clone()), source })?,
+        };
         debug!("Container return code: {}", code);
         debug!("Container stdout/stderr:\n\nstdout:\n{}\n\nstderr:\n{}\n", BlockFormatter::new(&stdout), BlockFormatter::new(&stderr));
 
@@ -165,9 +265,63 @@ impl VmPlugin for OfflinePlugin {
         debug!("Task '{}' returned value: '{:?}'", info.name, value);
         Ok(value)
     }
+}
+
+#[async_trait::async_trait]
+impl VmPlugin for OfflinePlugin {
+    type CommitError = CommitError;
+    type ExecuteError = ExecuteError;
+    type GlobalState = GlobalState;
+    type LocalState = LocalState;
+    type PreprocessError = PreprocessError;
+    type StdoutError = StdoutError;
+
+    async fn preprocess(
+        _global: Arc<RwLock<Self::GlobalState>>,
+        _local: Self::LocalState,
+        pc: ProgramCounter,
+        _loc: Location,
+        name: DataName,
+        preprocess: PreprocessKind,
+        _prof: ProfileScopeHandle<'_>,
+    ) -> Result<AccessKind, Self::PreprocessError> {
+        info!("Preprocessing data '{name}' for call at {pc} in an offline environment");
+        debug!("Method of preprocessing: {preprocess:?}");
+
+        // Match on the type of preprocessing
+        match preprocess {
+            // Anything that requires transfers, fails
+            PreprocessKind::TransferRegistryTar { .. } => Err(PreprocessError::UnavailableData { name }),
+        }
+    }
+
+    async fn execute(
+        global: &Arc<RwLock<Self::GlobalState>>,
+        _local: &Self::LocalState,
+        info: TaskInfo<'_>,
+        prof: ProfileScopeHandle<'_>,
+    ) -> Result<Option<FullValue>, Self::ExecuteError> {
+        let (max_retries, task_results): (u32, Arc<Mutex<Vec<(String, Result<Option<FullValue>, String>)>>>) = {
+            let global: RwLockReadGuard<GlobalState> = global.read().unwrap();
+            (global.max_retries, global.task_results.clone())
+        };
+        let name: String = info.name.into();
+        let prof: &ProfileScopeHandle = &prof;
+        let res: Result<Option<FullValue>, ExecuteError> = retry::execute_with_retries(info.name, max_retries, move |attempt| {
+            if attempt > 1 {
+                info!("Retrying task '{}' (attempt {}/{})", info.name, attempt, max_retries + 1);
+            }
+            Self::execute_once(global, info.clone(), prof)
+        })
+        .await;
+
+        // Remember how it went for the run summary
+        task_results.lock().push((name, res.as_ref().map(Clone::clone).map_err(|err| err.trace().to_string())));
+        res
+    }
 
     async fn stdout(
-        _global: &Arc<RwLock<Self::GlobalState>>,
+        global: &Arc<RwLock<Self::GlobalState>>,
         _local: &Self::LocalState,
         text: &str,
         newline: bool,
@@ -175,11 +329,13 @@ impl VmPlugin for OfflinePlugin {
     ) -> Result<(), Self::StdoutError> {
         info!("Writing '{}' to stdout (newline: {}) in an offline environment...", text, if newline { "yes" } else { "no" });
 
-        // Simply write
-        if !newline {
-            print!("{text}");
-        } else {
-            println!("{text}");
+        // Simply write (to stderr if stdout is reserved)
+        let stdout_to_stderr: bool = global.read().unwrap().stdout_to_stderr;
+        match (stdout_to_stderr, newline) {
+            (false, false) => print!("{text}"),
+            (false, true) => println!("{text}"),
+            (true, false) => eprint!("{text}"),
+            (true, true) => eprintln!("{text}"),
         }
 
         // Done
@@ -244,41 +400,7 @@ impl VmPlugin for OfflinePlugin {
             }
         } else {
             debug!("Dataset '{}' doesn't exist; creating new entry...", data_name);
-
-            // Prepare the package directory by creating it if it doesn't exist yet
-            let dir: PathBuf = dataset_dir.join(data_name);
-            if !dir.is_dir() {
-                if dir.exists() {
-                    return Err(CommitError::DataDirNotADir { path: dir });
-                }
-                tfs::create_dir_all(&dir).await.map_err(|source| CommitError::DataDirCreateError { path: dir.clone(), source })?;
-            }
-
-            // Create a new DataInfo struct
-            let info: DataInfo = DataInfo {
-                name: data_name.into(),
-                owners: None,      // TODO: Merge parent datasets??
-                description: None, // TODO: Add parents & algorithm in description??
-                created: Utc::now(),
-
-                access: HashMap::from([("localhost".into(), AccessKind::File { path: dir.join("data") })]),
-            };
-
-            // Write it to the target folder
-            let info_path: PathBuf = dir.join("data.yml");
-            let mut handle: tfs::File =
-                tfs::File::create(&info_path).await.map_err(|source| CommitError::DataInfoCreateError { path: info_path.clone(), source })?;
-            let sinfo: String = serde_yaml::to_string(&info).map_err(|source| CommitError::DataInfoSerializeError { source })?;
-            handle.write_all(sinfo.as_bytes()).await.map_err(|source| CommitError::DataInfoWriteError { path: info_path.clone(), source })?;
-
-            // Finally, copy the intermediate file to the target
-            let source: PathBuf = results_dir.join(path);
-            let target: PathBuf = dir.join("data");
-            debug!("Copying '{}' to '{}'...", source.display(), target.display());
-            copy_dir_recursively_async(source, target).await.map_err(|source| CommitError::DataCopyError { source })?;
-
-            // The dataset has now been promoted
-            debug!("Dataset created successfully.");
+            create_dataset(&dataset_dir, data_name, None, results_dir.join(path)).await?;
         }
         copy.stop();
 
@@ -304,6 +426,11 @@ impl OfflineVm {
     /// # Arguments
     /// - `docker_opts`: The information we need to connect to the local Docker daemon.
     /// - `keep_containers`: Whether to keep containers after execution completes or not.
+    /// - `cleanup_after`: If given, marks kept containers such that they are removed by a later run once this much time has passed.
+    /// - `max_retries`: The number of times to re-execute a task that failed with a retryable error.
+    /// - `stats`: Whether to sample the resource usage of every task container (see [`OfflineVm::task_stats()`]).
+    /// - `auto_fetch`: Whether to pull the images of packages that have not been pulled yet from the registry, or to fail instead.
+    /// - `stdout_to_stderr`: Whether to write the output of the workflow to stderr instead of stdout.
     /// - `package_dir`: The directory where packages (and thus images) are stored.
     /// - `dataset_dir`: The directory where datasets (and thus committed results) are stored.
     /// - `results_dir`: The directory where temporary results are stored.
@@ -312,10 +439,16 @@ impl OfflineVm {
     ///
     /// # Returns
     /// A new OfflineVm instance with one coherent state.
+    #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn new(
         docker_opts: DockerOptions,
         keep_containers: bool,
+        cleanup_after: Option<Duration>,
+        max_retries: u32,
+        stats: bool,
+        auto_fetch: bool,
+        stdout_to_stderr: bool,
         package_dir: impl Into<PathBuf>,
         dataset_dir: impl Into<PathBuf>,
         results_dir: impl Into<PathBuf>,
@@ -326,6 +459,12 @@ impl OfflineVm {
             state: Self::new_state(GlobalState {
                 docker_opts,
                 keep_containers,
+                cleanup_after,
+                max_retries,
+                stats: if stats { Some(Arc::new(Mutex::new(Vec::new()))) } else { None },
+                task_results: Arc::new(Mutex::new(Vec::new())),
+                auto_fetch,
+                stdout_to_stderr,
 
                 package_dir: package_dir.into(),
                 dataset_dir: dataset_dir.into(),
@@ -344,10 +483,11 @@ impl OfflineVm {
     ///
     /// # Arguments
     /// - `workflow`: The Workflow to execute.
+    /// - `prof`: The ProfileScope that can be used to provide additional information about the timings of the VM.
     ///
     /// # Returns
     /// The result of the workflow, if any. It also returns `self` again for subsequent runs.
-    pub async fn exec(self, workflow: Workflow) -> (Self, Result<FullValue, Error>) {
+    pub async fn exec(self, workflow: Workflow, prof: ProfileScopeHandle<'_>) -> (Self, Result<FullValue, Error>) {
         // Step 1: Plan
         let plan: Result<Workflow, Error> = {
             let planner: OfflinePlanner = {
@@ -356,7 +496,7 @@ impl OfflineVm {
                 // Plan with the previous results
                 OfflinePlanner::new(state.dindex.clone(), state.results.clone())
             };
-            match planner.plan(workflow).await {
+            match prof.time_fut("planning", planner.plan(workflow)).await {
                 Ok(plan) => Ok(plan),
                 Err(source) => Err(Error::PlanError { source }),
             }
@@ -372,7 +512,7 @@ impl OfflineVm {
         let this: Arc<RwLock<Self>> = Arc::new(RwLock::new(self));
 
         // Run the VM and get self back
-        let result: Result<FullValue, VmError> = Self::run::<OfflinePlugin>(this.clone(), plan, ProfileScopeHandle::dummy()).await;
+        let result: Result<FullValue, VmError> = prof.nest_fut("execution", |scope| Self::run::<OfflinePlugin>(this.clone(), plan, scope)).await;
         let this: Self = match Arc::try_unwrap(this) {
             Ok(this) => this.into_inner().unwrap(),
             Err(_) => {
@@ -396,6 +536,22 @@ impl OfflineVm {
     /// Returns the path to the internal temporary folder for results.
     #[inline]
     pub fn results_dir(&self) -> PathBuf { self.state.global.read().unwrap().results_dir.clone() }
+
+    /// Returns the resource usage sampled for every task executed so far, in order of execution.
+    ///
+    /// # Returns
+    /// A list of task names and their [`ContainerStats`], or [`None`] if this VM was not told to sample them.
+    #[inline]
+    pub fn task_stats(&self) -> Option<Vec<(String, ContainerStats)>> {
+        self.state.global.read().unwrap().stats.as_ref().map(|stats| stats.lock().clone())
+    }
+
+    /// Returns the outcome of every task executed so far, in order of execution.
+    ///
+    /// # Returns
+    /// A list of task names and either the value they returned or a description of why they failed.
+    #[inline]
+    pub fn task_results(&self) -> Vec<(String, Result<Option<FullValue>, String>)> { self.state.global.read().unwrap().task_results.lock().clone() }
 }
 
 impl Vm for OfflineVm {
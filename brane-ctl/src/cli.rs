@@ -3,8 +3,8 @@ use std::path::PathBuf;
 
 use brane_cfg::proxy::ProxyProtocol;
 use brane_ctl::spec::{
-    API_DEFAULT_VERSION, DownloadServicesSubcommand, GenerateBackendSubcommand, GenerateCertsSubcommand, GenerateNodeSubcommand, InclusiveRange,
-    Pair, PolicyInputLanguage, ResolvableNodeKind, StartSubcommand, VersionFix,
+    API_DEFAULT_VERSION, ConfigOverride, DownloadServicesSubcommand, GenerateBackendSubcommand, GenerateCertsSubcommand, GenerateNodeSubcommand,
+    InclusiveRange, Pair, PolicyInputLanguage, PullPolicy, ResolvableNodeKind, StartSubcommand, VersionFix,
 };
 use brane_tsk::docker::ClientVersion;
 use clap::{Parser, Subcommand};
@@ -36,6 +36,16 @@ pub(crate) struct Cli {
                 which ports to use, ...)"
     )]
     pub(crate) node_config: PathBuf,
+    /// Overrides for fields in the node config file.
+    #[clap(
+        long = "set",
+        global = true,
+        help = "Overrides a field in the node config file before it is used. Given as '<KEY>=<VALUE>', where '<KEY>' is a dot-separated path to the \
+                field (e.g., 'node.worker.name'). The value is parsed as the type of the field it replaces, unless given explicitly as \
+                '<KEY>:<TYPE>=<VALUE>' with '<TYPE>' one of 'str', 'int', 'float' or 'bool'. Overrides are applied in memory; only 'start' writes \
+                the result next to the node config file as '<NAME>.overrides.yml' for the services to read. Can be repeated."
+    )]
+    pub(crate) overrides: Vec<ConfigOverride>,
 
     /// The subcommand that can be run.
     #[clap(subcommand)]
@@ -88,7 +98,7 @@ pub(crate) enum CtlSubcommand {
             conflicts_with = "skip_import",
             help = "Sets the image directory ($IMG_DIR) to use in the image flags of the `start` command."
         )]
-        image_dir:   PathBuf,
+        image_dir: PathBuf,
         /// If given, will use locally downloaded versions of the auxillary images.
         #[clap(
             long,
@@ -97,7 +107,7 @@ pub(crate) enum CtlSubcommand {
                     change the default value of all auxillary image paths to 'Path<$IMG_DIR/aux-SVC.tar>', where 'SVC' is the specific service \
                     (e.g., 'scylla'). For more information, see the '--aux-scylla' flag."
         )]
-        local_aux:   bool,
+        local_aux: bool,
         /// Whether to skip importing images or not.
         #[clap(
             long,
@@ -113,6 +123,42 @@ pub(crate) enum CtlSubcommand {
                     this to effectively reach the profile files."
         )]
         profile_dir: Option<PathBuf>,
+        /// A manifest of expected image digests.
+        #[clap(
+            long,
+            global = true,
+            conflicts_with = "skip_import",
+            help = "If given, verifies every service image against the digests in this YAML file (mapping image names like 'brane-api' to image \
+                    digests) while loading them, and refuses to start the node if any image is missing from it or does not match."
+        )]
+        digests: Option<PathBuf>,
+        /// When to import/pull the service images.
+        #[clap(
+            long,
+            global = true,
+            default_value = "if-not-present",
+            conflicts_with = "skip_import",
+            help = "Determines when service images are imported or pulled. 'always' reloads every image even if one with the same tag is already \
+                    loaded; 'if-not-present' only loads missing images; 'never' does not pull any image from a registry and fails before starting \
+                    if one is missing (image files are still imported)."
+        )]
+        pull_policy: PullPolicy,
+        /// Whether to only show what would be done.
+        #[clap(
+            long,
+            global = true,
+            help = "If given, resolves the Docker Compose file, environment and generated override file as usual, but only prints the resulting \
+                    command, its environment and the override file's contents instead of loading any images or starting the node."
+        )]
+        dry_run: bool,
+        /// Whether to report the outcome as JSON.
+        #[clap(
+            long,
+            global = true,
+            help = "If given, reports the outcome as a JSON object on the last line of stdout. On failure, it contains the error and, if a Docker \
+                    Compose command failed, that command, its exit code and its captured stderr."
+        )]
+        json: bool,
 
         /// Defines the possible nodes and associated flags to start.
         #[clap(subcommand)]
@@ -126,6 +172,41 @@ pub(crate) enum CtlSubcommand {
         /// The docker-compose file that we start.
         #[clap(short, long, help = concat!("The docker-compose.yml file that defines the services to stop. You can use '$NODE' to match either 'central' or 'worker', depending how we started. If omitted, will use the baked-in counterpart (although that only works for the default version, v", env!("CARGO_PKG_VERSION"), ")."))]
         file: Option<PathBuf>,
+        /// Whether to report the outcome as JSON.
+        #[clap(
+            long,
+            help = "If given, reports the outcome as a JSON object on the last line of stdout. On failure, it contains the error and, if a Docker \
+                    Compose command failed, that command, its exit code and its captured stderr."
+        )]
+        json: bool,
+    },
+    #[clap(
+        name = "validate-compose",
+        about = "Checks the Docker Compose file that 'start' would use (baked-in or given) without starting anything: it must exist, parse and \
+                 reference images that are available locally or pullable."
+    )]
+    ValidateCompose {
+        #[clap(short = 'S', long, default_value = "/var/run/docker.sock", help = "The path of the Docker socket to connect to.")]
+        docker_socket: PathBuf,
+        #[clap(short = 'V', long, default_value = API_DEFAULT_VERSION.as_str(), help = "The version of the Docker client API that we use to connect to the engine.")]
+        docker_version: ClientVersion,
+        /// The docker-compose file that we validate.
+        #[clap(short, long, help = concat!("The docker-compose.yml file to validate. You can use '$NODE' to match either 'central' or 'worker', depending on the node. If omitted, will use the baked-in counterpart (although that only works for the default version, v", env!("CARGO_PKG_VERSION"), ")."))]
+        file: Option<PathBuf>,
+        /// The specific Brane version to validate for.
+        #[clap(short, long, default_value = env!("CARGO_PKG_VERSION"), help = "The Brane version whose images to look for.")]
+        version: Version,
+        /// The image directory that `start` would import images from.
+        #[clap(
+            long,
+            default_value = "./target/release",
+            help = "The image directory ($IMG_DIR) that 'start' would import the service images from. Images that are not loaded yet are available \
+                    if their default file in this directory exists."
+        )]
+        image_dir: PathBuf,
+        /// Whether `start` would use locally downloaded versions of the auxillary images.
+        #[clap(long, help = "If given, checks for the auxillary images as 'start --local-aux' would import them (i.e., '$IMG_DIR/aux-SVC.tar').")]
+        local_aux: bool,
     },
     #[clap(name = "logs", about = "Show the logs for the specficied node")]
     Logs {
@@ -190,10 +271,18 @@ pub(crate) enum DownloadSubcommand {
             global = true,
             help = "The processor architecture for which to download the images. Specify '$LOCAL' to use the architecture of the current machine."
         )]
-        arch:    Arch,
+        arch: Arch,
         /// The version of the services to download.
         #[clap(short, long, default_value=env!("CARGO_PKG_VERSION"), global=true, help="The version of the images to download from GitHub. You can specify 'latest' to download the latest version (but that might be incompatible with this CTL version)")]
         version: Version,
+        /// Whether to refuse downloading services without a known checksum.
+        #[clap(
+            long,
+            global = true,
+            help = "If given, refuses to download central or worker services for which no checksum is known (e.g., for 'latest') instead of only \
+                    warning that they cannot be verified."
+        )]
+        require_checksum: bool,
         /// Whether to overwrite existing images or not.
         #[clap(
             short = 'F',
@@ -202,7 +291,7 @@ pub(crate) enum DownloadSubcommand {
             help = "If given, will overwrite services that are already there. Otherwise, these are not overwritten. Note that regardless, a \
                     download will still be performed."
         )]
-        force:   bool,
+        force: bool,
 
         /// Whether to download the central or the worker VMs.
         #[clap(subcommand)]
@@ -367,10 +456,23 @@ pub(crate) enum GenerateSubcommand {
 
         /// The identifier for this key.
         #[clap(short = 'i', long = "id", default_value = "A", help = "Some identifier to distinguish the key.")]
-        key_id:  String,
+        key_id:      String,
         /// The algorithm used to sign JWTs.
-        #[clap(short = 'a', long = "alg", default_value = "HS256", help = "The algorithm with which to sign JWTs using the generated key.")]
-        jwt_alg: KeyAlgorithm,
+        #[clap(
+            short = 'a',
+            long = "alg",
+            default_value = "HS256",
+            help = "The algorithm with which to sign JWTs using the generated key. Supported are 'HS256' (shared secret) and 'ES256' (ECDSA P-256 \
+                    keypair)."
+        )]
+        jwt_alg:     KeyAlgorithm,
+        /// The path to write the public key to.
+        #[clap(
+            long,
+            help = "If given, also writes the public key of an asymmetric algorithm (e.g., 'ES256') as a JWK Set to this path, so checkers can \
+                    verify tokens without holding the secret."
+        )]
+        public_path: Option<PathBuf>,
     },
 
     #[clap(name = "policy_token", about = "Generates a new JWT for use to access the `brane-chk` service.")]
@@ -414,7 +516,8 @@ pub(crate) enum GenerateSubcommand {
             long,
             default_value = "4200-4299",
             help = "Defines the range of ports that we may allocate when one of the Brane services wants to make an outgoing connection. Given as \
-                    '<START>-<END>', where '<START>' and '<END>' are port numbers, '<START>' >= '<END>'. Both are inclusive."
+                    '<START>-<END>', where '<START>' and '<END>' are port numbers, '<START>' >= '<END>'. Both are inclusive. Either may be omitted \
+                    (e.g., '4200-' or '-4299') to range up to the highest or from the lowest unprivileged port (1024), respectively."
         )]
         outgoing_range: InclusiveRange<u16>,
         /// Defines the map of incoming ports.
@@ -474,6 +577,14 @@ pub(crate) enum UnpackSubcommand {
         /// Whether to fix missing directories (true) or throw errors (false).
         #[clap(short, long, help = "If given, will create missing directories instead of throwing an error.")]
         fix_dirs: bool,
+        /// Whether to only list what would be unpacked.
+        #[clap(
+            short,
+            long,
+            help = "If given, does not write anything but instead lists which embedded file would be written where. Combine with '--kind' to \
+                    preview the files of any node kind."
+        )]
+        list:     bool,
     },
 }
 
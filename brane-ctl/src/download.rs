@@ -17,11 +17,14 @@ use std::ffi::OsString;
 use std::fs::{self, DirEntry, File, ReadDir};
 use std::io::Write as _;
 use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
 
 use brane_shr::fs::{DownloadSecurity, download_file_async, move_path_async, unarchive_async};
 use brane_tsk::docker::{Docker, DockerOptions, ImageSource, connect_local, ensure_image, save_image};
 use console::{Style, style};
 use enum_debug::EnumDebug as _;
+use futures_util::stream::{self, StreamExt as _};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use log::{debug, info, warn};
 use specifications::arch::Arch;
 use specifications::container::Image;
@@ -37,38 +40,119 @@ use crate::spec::DownloadServicesSubcommand;
 const AUXILLARY_DOCKER_IMAGES: [(&str, &str); 3] =
     [("aux-scylla", "scylladb/scylla:4.6.3"), ("aux-kafka", "ubuntu/kafka:3.1-22.04_beta"), ("aux-zookeeper", "ubuntu/zookeeper:3.1-22.04_beta")];
 
+/// Defines the known SHA-256 checksums of the service tarballs per release, as `(version, tarball name, checksum)`.
+///
+/// Add the checksums of a release's `instance-<ARCH>.tar.gz` and `worker-instance-<ARCH>.tar.gz` here when it is published. Tarballs that are
+/// not listed (including those of `latest`) are only protected by HTTPS.
+const SERVICE_CHECKSUMS: &[(&str, &str, [u8; 32])] = &[];
+
+/// The maximum number of auxillary images that we download at the same time.
+const MAX_PARALLEL_PULLS: usize = 4;
+
 
 
 
 
 /***** HELPER FUNCTIONS *****/
+/// Pulls an auxillary image and saves it to a file.
+///
+/// # Arguments
+/// - `docker`: An already connected local instance of Docker.
+/// - `name`: The name of the auxillary image (e.g., `aux-scylla`).
+/// - `image`: The image to pull for it (e.g., `scylladb/scylla:4.6.3`).
+/// - `image_path`: The path to save the image to.
+/// - `prgs`: The progress bar to report what we're doing on.
+///
+/// # Errors
+/// This function errors if we failed to pull or save the image.
+async fn download_auxillary_image(docker: &Docker, name: &str, image: &str, image_path: PathBuf, prgs: &ProgressBar) -> Result<(), Error> {
+    // Make sure the image is pulled
+    prgs.set_message(format!("Downloading auxillary image {}...", style(image).bold().green()));
+    ensure_image(docker, Image::new(name, None::<&str>, None::<&str>), ImageSource::Registry(image.into()))
+        .await
+        .map_err(|source| Error::PullError { name: name.into(), image: image.into(), source })?;
+
+    // Save the image to the correct path
+    prgs.set_message(format!("Exporting auxillary image {}...", style(name).bold().green()));
+    save_image(docker, Image::from(image), &image_path).await.map_err(|source| Error::SaveError {
+        name: name.into(),
+        image: image.into(),
+        path: image_path,
+        source,
+    })
+}
+
+/// Finds the known checksum of a service tarball.
+///
+/// # Arguments
+/// - `version`: The Brane version of the tarball.
+/// - `tar_name`: The base name of the tarball (e.g., `instance-x86_64`).
+///
+/// # Returns
+/// The SHA-256 checksum of the tarball, or [`None`] if we don't know it (e.g., because `version` is `latest`).
+fn known_checksum(version: &Version, tar_name: &str) -> Option<&'static [u8]> {
+    if version.is_latest() {
+        return None;
+    }
+    let version: String = version.to_string();
+    SERVICE_CHECKSUMS.iter().find(|(v, name, _)| *v == version && *name == tar_name).map(|(_, _, checksum)| &checksum[..])
+}
+
 /// Downloads either the central or the worker images (which depends solely on the tar name).
 ///
 /// # Arguments
 /// - `address`: The address of the file to download.
 /// - `path`: The path to the directory where the image files will _eventually_ end up in.
 /// - `tar_name`: The base name of the tarball file, which is also the name if the directory inside it etc.
+/// - `version`: The Brane version that is downloaded, used to find the tarball's checksum.
+/// - `require_checksum`: If given, refuses to download a tarball for which we don't know the checksum instead of only warning about it.
 /// - `force`: If given, overwrites images if they are already there.
 ///
 /// # Errors
-/// This function may error if we failed to reach GitHub, we failed to establish HTTPS or we failed to somehow write the file / create missing directories (if enabled).
-async fn download_brane_services(address: impl AsRef<str>, path: impl AsRef<Path>, tar_name: impl AsRef<str>, force: bool) -> Result<(), Error> {
+/// This function may error if we failed to reach GitHub, we failed to establish HTTPS, the tarball does not have the checksum we know for it
+/// (or we know none and `require_checksum` is given) or we failed to somehow write the file / create missing directories (if enabled).
+async fn download_brane_services(
+    address: impl AsRef<str>,
+    path: impl AsRef<Path>,
+    tar_name: impl AsRef<str>,
+    version: &Version,
+    require_checksum: bool,
+    force: bool,
+) -> Result<(), Error> {
     let address: &str = address.as_ref();
     let path: &Path = path.as_ref();
     let tar_name: &str = tar_name.as_ref();
 
+    // See if we can verify what we download
+    let checksum: Option<&[u8]> = known_checksum(version, tar_name);
+    if checksum.is_none() {
+        if require_checksum {
+            return Err(Error::UnknownChecksum { tar_name: format!("{tar_name}.tar.gz"), version: *version });
+        }
+        warn!(
+            "No known checksum for '{tar_name}.tar.gz' of Brane version {version}; the downloaded services are NOT verified (only HTTPS protects \
+             them). Give '--require-checksum' to refuse unverified downloads instead."
+        );
+    }
+
     // Create a temporary directory to download the tar file to.
     debug!("Creating temporary directory...");
     let temp: TempDir = TempDir::new().map_err(|source| Error::TempDirError { source })?;
     let tar_path: PathBuf = temp.path().join(format!("{tar_name}.tar.gz"));
 
     // Download it
-    if let Err(source) = download_file_async(address, &tar_path, DownloadSecurity::https(), Some(Style::new().green().bold())).await {
+    let security: DownloadSecurity = DownloadSecurity { checksum, https: true };
+    if let Err(source) = download_file_async(address, &tar_path, security, Some(Style::new().green().bold()), None).await {
         // Don't call the destructor of `TempDir`, since it's much easier to debug if it lives after creation
         // SAFETY: This is OK because for our committed version, the destructor of `TempDir` only destroys the directory itself using a normal `std::fs::remove_dir_all()` call, and so nothing will explode if that does not happen.
         // (see https://docs.rs/tempfile/3.3.0/src/tempfile/dir.rs.html#403-407)
         std::mem::forget(temp);
-        return Err(Error::DownloadError { address: address.into(), path: tar_path, source: Box::new(source) });
+        return Err(match source {
+            brane_shr::fs::Error::FileChecksumError { path, expected, got, .. } => {
+                Error::ChecksumMismatch { address: address.into(), path, expected, got }
+            },
+            source => Error::DownloadError { address: address.into(), path: tar_path, source: Box::new(source) },
+        });
     }
 
     // Extract the folder to the same temporary directory
@@ -160,6 +244,7 @@ async fn download_brane_services(address: impl AsRef<str>, path: impl AsRef<Path
 /// - `path`: The path of the folder to download the service images to.
 /// - `version`: The version of the images to download.
 /// - `arch`: The architecture for which to download the images.
+/// - `require_checksum`: If given, refuses to download central or worker services for which we don't know the checksum.
 /// - `force`: If given, overwrites images if they are already there.
 /// - `kind`: The kind of images to download (e.g., central, worker or auxillary).
 ///
@@ -170,6 +255,7 @@ pub async fn services(
     path: impl AsRef<Path>,
     arch: Arch,
     version: Version,
+    require_checksum: bool,
     force: bool,
     kind: DownloadServicesSubcommand,
 ) -> Result<(), Error> {
@@ -240,7 +326,7 @@ pub async fn services(
             debug!("Will download from: {}", address);
 
             // Hand it over the shared code
-            download_brane_services(address, path, format!("instance-{}", arch.brane()), force).await?;
+            download_brane_services(address, path, format!("instance-{}", arch.brane()), &version, require_checksum, force).await?;
         },
 
         DownloadServicesSubcommand::Worker => {
@@ -253,15 +339,16 @@ pub async fn services(
             debug!("Will download from: {}", address);
 
             // Hand it over the shared code
-            download_brane_services(address, path, format!("worker-instance-{}", arch.brane()), force).await?;
+            download_brane_services(address, path, format!("worker-instance-{}", arch.brane()), &version, require_checksum, force).await?;
         },
 
         DownloadServicesSubcommand::Auxillary { socket, client_version } => {
             // Attempt to connect to the local Docker daemon.
-            let docker: Docker = connect_local(DockerOptions { socket: socket.clone(), version: *client_version })
+            let docker: Docker = connect_local(DockerOptions { host: socket.clone().into(), version: *client_version })
                 .map_err(|source| Error::DockerConnectError { source })?;
 
-            // Download the pre-determined set of auxillary images
+            // Find which of the pre-determined set of auxillary images we still need
+            let mut todo: Vec<(&str, &str, PathBuf)> = Vec::with_capacity(AUXILLARY_DOCKER_IMAGES.len());
             for (name, image) in AUXILLARY_DOCKER_IMAGES {
                 // We can skip it if it already exists
                 let image_path: PathBuf = path.join(format!("{name}.tar"));
@@ -269,21 +356,38 @@ pub async fn services(
                     debug!("Image '{}' already exists (skipping)", image_path.display());
                     continue;
                 }
+                todo.push((name, image, image_path));
+            }
 
-                // Make sure the image is pulled
-                println!("Downloading auxillary image {}...", style(image).bold().green());
-                ensure_image(&docker, Image::new(name, None::<&str>, None::<&str>), ImageSource::Registry(image.into()))
-                    .await
-                    .map_err(|source| Error::PullError { name: name.into(), image: image.into(), source })?;
-
-                // Save the image to the correct path
-                println!("Exporting auxillary image {}...", style(name).bold().green());
-                save_image(&docker, Image::from(image), &image_path).await.map_err(|source| Error::SaveError {
-                    name: name.into(),
-                    image: image.into(),
-                    path: image_path,
-                    source,
-                })?;
+            // Download them a few at a time, each with their own line in the progress display
+            let progress: MultiProgress = MultiProgress::new();
+            let errors: Vec<Error> = stream::iter(todo)
+                .map(|(name, image, image_path)| {
+                    let prgs: ProgressBar = progress.add(
+                        ProgressBar::new_spinner()
+                            .with_style(ProgressStyle::with_template("{spinner:.green} {msg}").expect("Progress bar template should be valid")),
+                    );
+                    prgs.enable_steady_tick(Duration::from_millis(100));
+                    let docker: &Docker = &docker;
+                    async move {
+                        let res: Result<(), Error> = download_auxillary_image(docker, name, image, image_path, &prgs).await;
+                        match &res {
+                            Ok(()) => prgs.finish_with_message(format!("Downloaded auxillary image {}", style(name).bold().green())),
+                            Err(_) => prgs.finish_with_message(format!("Failed to download auxillary image {}", style(name).bold().red())),
+                        }
+                        res
+                    }
+                })
+                .buffer_unordered(MAX_PARALLEL_PULLS)
+                .filter_map(|res| async move { res.err() })
+                .collect()
+                .await;
+
+            // Report all failures, not just the first
+            if errors.len() == 1 {
+                return Err(errors.into_iter().next().unwrap());
+            } else if !errors.is_empty() {
+                return Err(Error::AuxillaryImagesError { errors });
             }
         },
     }
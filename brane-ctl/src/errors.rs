@@ -21,10 +21,13 @@ use brane_shr::formatters::Capitalizeable;
 use brane_tsk::docker::ImageSource;
 use console::style;
 use enum_debug::EnumDebug as _;
+use error_trace::ErrorTrace as _;
 use jsonwebtoken::jwk::KeyAlgorithm;
 use specifications::container::Image;
 use specifications::version::Version;
 
+use crate::spec::ConfigValueType;
+
 
 /***** LIBRARY *****/
 /// Errors that relate to downloading stuff (the subcommand, specifically).
@@ -55,6 +58,18 @@ pub enum DownloadError {
     /// Failed to run the actual download command.
     #[error("Failed to download '{}' to '{}'", address, path.display())]
     DownloadError { address: String, path: PathBuf, source: Box<brane_shr::fs::Error> },
+    /// The downloaded file did not have the checksum we know for it.
+    #[error(
+        "Downloaded file '{}' from '{}' has checksum '{}', but expected '{}' (the download may be corrupted or tampered with)",
+        path.display(),
+        address,
+        got,
+        expected
+    )]
+    ChecksumMismatch { address: String, path: PathBuf, expected: String, got: String },
+    /// We don't know the checksum of a tarball, and the user asked us not to download unverified ones.
+    #[error("No known checksum for '{tar_name}' of Brane version {version}; refusing to download it because '--require-checksum' is given")]
+    UnknownChecksum { tar_name: String, version: Version },
     /// Failed to extract the given archive.
     #[error("Failed to unpack '{}' to '{}'", tar.display(), target.display())]
     UnarchiveError { tar: PathBuf, target: PathBuf, source: Box<brane_shr::fs::Error> },
@@ -74,6 +89,13 @@ pub enum DownloadError {
     /// Failed to pull an image.
     #[error("Failed to pull '{image}' as '{name}'")]
     PullError { name: String, image: String, source: brane_tsk::docker::Error },
+    /// Failed to download more than one auxillary image.
+    #[error(
+        "Failed to download {} auxillary images:{}",
+        errors.len(),
+        errors.iter().map(|err| format!("\n - {}", err.trace())).collect::<String>()
+    )]
+    AuxillaryImagesError { errors: Vec<DownloadError> },
     /// Failed to save a pulled image.
     #[error("Failed to save image '{}' to '{}'", name, path.display())]
     SaveError { name: String, image: String, path: PathBuf, source: brane_tsk::docker::Error },
@@ -194,6 +216,12 @@ pub enum GenerateError {
     /// A particular combination of policy secret settings was not supported.
     #[error("Policy key algorithm {key_alg} is unsupported")]
     UnsupportedKeyAlgorithm { key_alg: KeyAlgorithm },
+    /// A public key was requested for a symmetric policy key algorithm.
+    #[error("Policy key algorithm {key_alg} is symmetric and has no public key to export")]
+    NoPublicKey { key_alg: KeyAlgorithm },
+    /// Failed to serialize a policy key to JSON.
+    #[error("Failed to serialize policy key")]
+    SecretSerializeError { source: serde_json::Error },
     /// Failed to generate a new policy token.
     #[error("Failed to generate new policy token")]
     TokenGenerate { source: specifications::policy::Error },
@@ -226,6 +254,18 @@ pub enum LifetimeError {
     /// Failed to write to a Docker Compose file.
     #[error("Failed to write to Docker Compose file '{}'", path.display())]
     DockerComposeWriteError { path: PathBuf, source: std::io::Error },
+    /// Failed to read a Docker Compose file.
+    #[error("Failed to read Docker Compose file '{}'", path.display())]
+    DockerComposeReadError { path: PathBuf, source: std::io::Error },
+    /// Failed to parse a Docker Compose file as YAML.
+    #[error("Failed to parse Docker Compose file '{}' as YAML", path.display())]
+    DockerComposeParseError { path: PathBuf, source: serde_yaml::Error },
+    /// A Docker Compose file did not define any services.
+    #[error("Docker Compose file '{}' does not define any services", path.display())]
+    DockerComposeNoServices { path: PathBuf },
+    /// A Docker Compose file references images that are neither local nor pullable.
+    #[error("Docker Compose file '{}' references images that are not available locally nor pullable: {}", path.display(), images.join(", "))]
+    DockerComposeImagesUnavailable { path: PathBuf, images: Vec<String> },
 
     /// Failed to touch the audit log into existance.
     #[error("Failed to touch audit log '{}' into existance", path.display())]
@@ -240,6 +280,9 @@ pub enum LifetimeError {
     /// Failed to write to the extra hosts file.
     #[error("Failed to write to extra hosts file '{}'", path.display())]
     HostsFileWriteError { path: PathBuf, source: serde_yaml::Error },
+    /// Failed to read back the extra hosts file to show it.
+    #[error("Failed to read extra hosts file '{}'", path.display())]
+    HostsFileReadError { path: PathBuf, source: std::io::Error },
 
     /// Failed to get the digest of the given image file.
     #[error("Failed to get digest of image {}", style(path.display()).bold())]
@@ -247,6 +290,30 @@ pub enum LifetimeError {
     /// Failed to load/import the given image.
     #[error("Failed to load image {} from '{}'", style(image).bold(), style(source).bold())]
     ImageLoadError { image: Box<Image>, image_source: Box<ImageSource>, source: brane_tsk::docker::Error },
+    /// Failed to read the expected-digest manifest.
+    #[error("Failed to read image digest manifest '{}'", path.display())]
+    DigestManifestReadError { path: PathBuf, source: std::io::Error },
+    /// Failed to parse the expected-digest manifest.
+    #[error("Failed to parse image digest manifest '{}' as a YAML map of image names to digests", path.display())]
+    DigestManifestParseError { path: PathBuf, source: serde_yaml::Error },
+    /// An image to load has no expected digest in the manifest.
+    #[error("Image {} has no expected digest in image digest manifest '{}'; refusing to start", style(name).bold(), path.display())]
+    ImageDigestMissing { name: String, path: PathBuf },
+    /// An image does not have the digest we expected it to have.
+    #[error("Image {} has digest '{}', but the image digest manifest expects '{}'; refusing to start", style(image).bold(), got, expected)]
+    ImageDigestMismatch { image: Box<Image>, expected: String, got: String },
+    /// Failed to inspect a loaded image to verify its digest.
+    #[error("Failed to inspect loaded image {}", style(image).bold())]
+    ImageInspectError { image: Box<Image>, source: bollard::errors::Error },
+    /// Failed to check whether an image is already loaded.
+    #[error("Failed to check whether image {} is loaded", style(image).bold())]
+    ImageExistsError { image: Box<Image>, source: brane_tsk::docker::Error },
+    /// Some registry images are not loaded, but the pull policy forbids pulling them.
+    #[error(
+        "Image(s) {} are not loaded, and the pull policy is 'never'; load them manually or use '--pull-policy if-not-present'",
+        images.iter().map(|i| style(i).bold().to_string()).collect::<Vec<String>>().join(", ")
+    )]
+    ImagesNotPresent { images: Vec<Image> },
 
     /// The user gave us a proxy service definition, but not a proxy file path.
     #[error(
@@ -264,6 +331,9 @@ pub enum LifetimeError {
     /// Failed to load the given node config file.
     #[error("Failed to load node.yml file")]
     NodeConfigLoadError { source: brane_cfg::info::YamlError },
+    /// Failed to write the overridden node config file for the services to use.
+    #[error("Failed to write overridden node.yml file")]
+    NodeConfigOverrideError { source: OverrideError },
     /// Failed to connect to the local Docker daemon.
     #[error("Failed to connect to local Docker socket")]
     DockerConnectError { source: brane_tsk::errors::DockerError },
@@ -279,6 +349,32 @@ pub enum LifetimeError {
     JobFailure { command: Command, status: ExitStatus },
 }
 
+/// Errors that relate to applying `--set` overrides to the node config.
+#[derive(Debug, thiserror::Error)]
+pub enum OverrideError {
+    /// Failed to load the node config file to override.
+    #[error("Failed to load node config file '{}'", path.display())]
+    NodeConfigLoadError { path: PathBuf, source: brane_cfg::info::YamlError },
+    /// Failed to serialize the node config to a YAML value.
+    #[error("Failed to serialize node config")]
+    NodeConfigSerializeError { source: serde_yaml::Error },
+    /// The given key does not exist in the node config.
+    #[error("Unknown node config key '{key}'")]
+    UnknownKey { key: String },
+    /// The given value could not be parsed as the type of the field.
+    #[error("Cannot set node config key '{key}' to '{raw}': not a valid {ty}")]
+    IllegalValue { key: String, raw: String, ty: ConfigValueType },
+    /// The given value could not be parsed as YAML.
+    #[error("Cannot set node config key '{key}' to '{raw}': not valid YAML")]
+    IllegalYaml { key: String, raw: String, source: serde_yaml::Error },
+    /// The overridden node config is not a valid node config anymore.
+    #[error("Node config is invalid after applying overrides")]
+    NodeConfigDeserializeError { source: serde_yaml::Error },
+    /// Failed to write the overridden node config file.
+    #[error("Failed to write overridden node config file '{}'", path.display())]
+    NodeConfigWriteError { path: PathBuf, source: brane_cfg::info::YamlError },
+}
+
 /// Errors that relate to package subcommands.
 #[derive(Debug, thiserror::Error)]
 pub enum PackagesError {
@@ -349,6 +445,9 @@ pub enum InclusiveRangeParseError {
     /// Did not find the separating dash
     #[error("Missing '-' in range '{raw}'")]
     MissingDash { raw: String },
+    /// Both ends of the range were omitted
+    #[error("Range '{raw}' must have at least a start or an end")]
+    MissingBounds { raw: String },
     /// Failed to parse one of the numbers
     #[error("Failed to parse '{raw}' as a valid {what}")]
     NumberParseError { what: &'static str, raw: String, source: Box<dyn Send + Sync + Error> },
@@ -368,6 +467,17 @@ pub enum PairParseError {
     IllegalSomething { what: &'static str, raw: String, source: Box<dyn Send + Sync + Error> },
 }
 
+/// Errors that relate to parsing [`ConfigKey`](crate::spec::ConfigKey)s.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigKeyParseError {
+    /// One of the dot-separated fields was empty.
+    #[error("Node config key '{raw}' has an empty field")]
+    EmptySegment { raw: String },
+    /// The explicit type was not recognized.
+    #[error("Unknown value type '{raw}' (options are 'str', 'int', 'float' or 'bool')")]
+    UnknownType { raw: String },
+}
+
 /// Errors that relate to parsing [`PolicyInputLanguage`](crate::spec::PolicyInputLanguage)s.
 #[derive(Debug, thiserror::Error)]
 pub enum PolicyInputLanguageParseError {
@@ -376,6 +486,14 @@ pub enum PolicyInputLanguageParseError {
     Unknown { raw: String },
 }
 
+/// Errors that relate to parsing [`PullPolicy`](crate::spec::PullPolicy)s.
+#[derive(Debug, thiserror::Error)]
+pub enum PullPolicyParseError {
+    /// The given identifier was not recognized.
+    #[error("Unknown pull policy '{raw}' (options are 'always', 'if-not-present' or 'never')")]
+    Unknown { raw: String },
+}
+
 /// Errors that relate to parsing architecture iDs.
 #[derive(Debug, thiserror::Error)]
 pub enum ArchParseError {
@@ -394,7 +512,7 @@ pub enum ArchParseError {
 #[derive(Debug, thiserror::Error)]
 pub enum JwtAlgorithmParseError {
     /// Unknown identifier given.
-    #[error("Unknown JWT algorithm '{raw}' (options are: 'HS256')")]
+    #[error("Unknown JWT algorithm '{raw}' (options are: 'HS256', 'ES256')")]
     Unknown { raw: String },
 }
 
@@ -402,7 +520,7 @@ pub enum JwtAlgorithmParseError {
 #[derive(Debug, thiserror::Error)]
 pub enum KeyTypeParseError {
     /// Unknown identifier given.
-    #[error("Unknown key type '{raw}' (options are: 'oct')")]
+    #[error("Unknown key type '{raw}' (options are: 'oct', 'EC')")]
     Unknown { raw: String },
 }
 
@@ -36,8 +36,11 @@ use console::style;
 use diesel::{Connection as _, SqliteConnection};
 use diesel_migrations::{FileBasedMigrations, MigrationHarness as _};
 use enum_debug::EnumDebug as _;
-use jsonwebtoken::jwk::{self, Jwk, JwkSet, KeyAlgorithm, OctetKeyParameters, OctetKeyType, PublicKeyUse};
+use jsonwebtoken::jwk::{
+    self, EllipticCurve, EllipticCurveKeyParameters, EllipticCurveKeyType, Jwk, JwkSet, KeyAlgorithm, OctetKeyParameters, OctetKeyType, PublicKeyUse,
+};
 use log::{debug, info, warn};
+use p256::elliptic_curve::sec1::ToEncodedPoint as _;
 use rand::distr::Alphanumeric;
 use rand::rngs::OsRng;
 use rand::{Rng as _, TryRngCore};
@@ -58,11 +61,54 @@ const CFSSL_BIN: &[u8] = include_bytes!(env!("CFSSL_PATH"));
 /// The embedded `cfssljson` binary, compiled and/or downloaded by `build.rs`.
 const CFSSLJSON_BIN: &[u8] = include_bytes!(env!("CFSSLJSON_PATH"));
 
+/// The number of times we attempt to apply the policy database migrations while the database is locked.
+const MIGRATIONS_MAX_ATTEMPTS: u32 = 5;
+/// The time to wait before retrying a locked policy database for the first time. Doubles with every attempt.
+const MIGRATIONS_BACKOFF: Duration = Duration::from_millis(200);
+
 
 
 
 
 /***** HELPER FUNCTIONS ******/
+/// Checks whether the given error was caused by SQLite reporting that the database is busy or locked (i.e., `SQLITE_BUSY` or
+/// `SQLITE_LOCKED`).
+///
+/// # Arguments
+/// - `err`: The error to check the sources of.
+///
+/// # Returns
+/// True if any of its sources is a locked database, or false otherwise.
+fn is_database_locked(err: &(dyn 'static + std::error::Error)) -> bool {
+    let mut source: Option<&(dyn 'static + std::error::Error)> = err.source();
+    while let Some(err) = source {
+        // SQLite reports these as "database is locked" and "database table is locked", respectively
+        if err.to_string().contains("is locked") {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Connects to the given SQLite database and applies the given migrations to it.
+///
+/// # Arguments
+/// - `path`: The path of the database file.
+/// - `migrations`: The migrations to apply.
+///
+/// # Errors
+/// This function errors if we failed to connect to the database or to apply the migrations.
+fn apply_migrations(path: &Path, migrations: FileBasedMigrations) -> Result<(), Error> {
+    // Connect to the database
+    let mut conn: SqliteConnection =
+        SqliteConnection::establish(&path.display().to_string()).map_err(|source| Error::DatabaseConnect { path: path.into(), source })?;
+
+    // Attempt to run the migration
+    conn.run_pending_migrations(migrations).map_err(|source| Error::MigrationsApply { path: path.into(), source })?;
+    Ok(())
+}
+
 /// Ensures that the directory where the given file lives exists.
 ///
 /// # Arguments
@@ -1278,9 +1324,11 @@ pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String) -> R
         // Download the file
         let tar_path: PathBuf = dir.path().join("repo.tar.gz");
         let dir_path: PathBuf = dir.path().join("repo");
-        brane_shr::fs::download_file_async(&url, &tar_path, DownloadSecurity { checksum: None, https: true }, None)
-            .await
-            .map_err(|source| Error::RepoDownloadError { repo: url, target: dir_path.clone(), source })?;
+        let (prgs, mut progress) = brane_shr::fs::download_progress_bar(format!("Downloading policy-reasoner ({branch})..."));
+        let res =
+            brane_shr::fs::download_file_async(&url, &tar_path, DownloadSecurity { checksum: None, https: true }, None, Some(&mut progress)).await;
+        prgs.finish_and_clear();
+        res.map_err(|source| Error::RepoDownloadError { repo: url, target: dir_path.clone(), source })?;
         brane_shr::fs::unarchive_async(&tar_path, &dir_path).await.map_err(|source| Error::RepoUnpackError {
             tar: tar_path,
             target: dir_path.clone(),
@@ -1299,15 +1347,26 @@ pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String) -> R
         (dir, migrations)
     };
 
-    // Apply that with diesel
-    {
-        // Connect to the database
-        debug!("Applying migrations...");
-        let mut conn: SqliteConnection =
-            SqliteConnection::establish(&path.display().to_string()).map_err(|source| Error::DatabaseConnect { path: path.clone(), source })?;
-
-        // Attempt to run the migration
-        conn.run_pending_migrations(migrations).map_err(|source| Error::MigrationsApply { path: path.clone(), source })?;
+    // Apply that with diesel, giving whoever else has the database locked a moment to finish
+    debug!("Applying migrations...");
+    let mut attempt: u32 = 1;
+    loop {
+        match apply_migrations(&path, migrations.clone()) {
+            Ok(()) => break,
+            Err(err) if attempt < MIGRATIONS_MAX_ATTEMPTS && is_database_locked(&err) => {
+                let backoff: Duration = MIGRATIONS_BACKOFF * 2u32.pow(attempt - 1);
+                warn!(
+                    "Policy database '{}' is locked (attempt {}/{}); retrying in {}ms...",
+                    path.display(),
+                    attempt,
+                    MIGRATIONS_MAX_ATTEMPTS,
+                    backoff.as_millis()
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            },
+            Err(err) => return Err(err),
+        }
     }
 
     // Done
@@ -1320,47 +1379,85 @@ pub async fn policy_database(fix_dirs: bool, path: PathBuf, branch: String) -> R
 /// # Arguments
 /// - `fix_dirs`: if true, will generate missing directories instead of complaining.
 /// - `path`: The path to write the `policy_secret.json` to.
+/// - `public_path`: If given, the path to write the public half of an asymmetric key to, for checkers to verify tokens with.
 /// - `key_id`: Some identifier to use for this key.
 /// - `key_alg`: The JWT algorithm that should be used for signing.
 ///
 /// # Errors
-/// This function may error if we encountered any I/O errors.
-pub fn policy_secret(fix_dirs: bool, path: PathBuf, key_id: String, key_alg: KeyAlgorithm) -> Result<(), Error> {
+/// This function may error if we encountered any I/O errors, or if a public key is requested for a symmetric algorithm.
+pub fn policy_secret(fix_dirs: bool, path: PathBuf, public_path: Option<PathBuf>, key_id: String, key_alg: KeyAlgorithm) -> Result<(), Error> {
     info!("Generating policy_secret.json at '{}'...", path.display());
+    let common: jwk::CommonParameters = jwk::CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_operations: None,
+        key_algorithm: Some(key_alg),
+        key_id: Some(key_id),
+        x509_url: None,
+        x509_chain: None,
+        x509_sha1_fingerprint: None,
+        x509_sha256_fingerprint: None,
+    };
 
-    // Generate a new key with the given properties
+    // Generate a new key with the given properties, as the secret and (optionally) public key sets
     debug!("Generating secret key for {key_alg}...");
-    let key: String = match key_alg {
+    let (secret, public): (serde_json::Value, Option<JwkSet>) = match key_alg {
         KeyAlgorithm::HS256 => {
+            if public_path.is_some() {
+                return Err(Error::NoPublicKey { key_alg });
+            }
+
             // Generate a 256-bit, base64-encoded random string of bytes
             // See: <https://datatracker.ietf.org/doc/html/rfc7518#section-6.4.1>
             let mut key: [u8; 32] = [0; 32];
             OsRng.try_fill_bytes(&mut key).expect("OsRng should be available and must be able to fill array");
-            base64ct::Base64Url::encode_string(&key)
+            let key: String = base64ct::Base64Url::encode_string(&key);
+
+            debug!("Constructing JSON Web Key Set...");
+            let secret: JwkSet = JwkSet {
+                keys: vec![Jwk {
+                    common,
+                    algorithm: jwk::AlgorithmParameters::OctetKey(OctetKeyParameters { key_type: OctetKeyType::Octet, value: key }),
+                }],
+            };
+            (serde_json::to_value(secret).map_err(|source| Error::SecretSerializeError { source })?, None)
+        },
+
+        KeyAlgorithm::ES256 => {
+            // Generate a random P-256 scalar, retrying in the (astronomically unlikely) case it's out of range
+            // See: <https://datatracker.ietf.org/doc/html/rfc7518#section-6.2>
+            let key: p256::SecretKey = loop {
+                let mut d: [u8; 32] = [0; 32];
+                OsRng.try_fill_bytes(&mut d).expect("OsRng should be available and must be able to fill array");
+                if let Ok(key) = p256::SecretKey::from_slice(&d) {
+                    break key;
+                }
+            };
+            let point: p256::EncodedPoint = key.public_key().to_encoded_point(false);
+
+            // The public half is representable as a normal JWK...
+            debug!("Constructing JSON Web Key Sets...");
+            let public: JwkSet = JwkSet {
+                keys: vec![Jwk {
+                    common,
+                    algorithm: jwk::AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                        key_type: EllipticCurveKeyType::EC,
+                        curve: EllipticCurve::P256,
+                        x: base64ct::Base64UrlUnpadded::encode_string(point.x().expect("uncompressed point should have an x-coordinate")),
+                        y: base64ct::Base64UrlUnpadded::encode_string(point.y().expect("uncompressed point should have a y-coordinate")),
+                    }),
+                }],
+            };
+
+            // ...but `Jwk` has no field for the private scalar, so we add it ourselves
+            let mut secret: serde_json::Value = serde_json::to_value(&public).map_err(|source| Error::SecretSerializeError { source })?;
+            secret["keys"][0]["d"] = serde_json::Value::String(base64ct::Base64UrlUnpadded::encode_string(&key.to_bytes()));
+            (secret, Some(public))
         },
 
         // Rest is unknown to us
         key_alg => return Err(Error::UnsupportedKeyAlgorithm { key_alg }),
     };
 
-    // Create the to-be-serialized JSON with this
-    debug!("Constructing JSON Web Key Set...");
-    let secret: JwkSet = JwkSet {
-        keys: vec![Jwk {
-            common:    jwk::CommonParameters {
-                public_key_use: Some(PublicKeyUse::Signature),
-                key_operations: None,
-                key_algorithm: Some(key_alg),
-                key_id: Some(key_id),
-                x509_url: None,
-                x509_chain: None,
-                x509_sha1_fingerprint: None,
-                x509_sha256_fingerprint: None,
-            },
-            algorithm: jwk::AlgorithmParameters::OctetKey(OctetKeyParameters { key_type: OctetKeyType::Octet, value: key }),
-        }],
-    };
-
     // Write it to a file
     debug!("Writing secret to '{}'...", path.display());
     ensure_dir_of(&path, fix_dirs)?;
@@ -1370,9 +1467,23 @@ pub fn policy_secret(fix_dirs: bool, path: PathBuf, key_id: String, key_alg: Key
         path: path.clone(),
         source,
     })?;
+    println!("Successfully generated {}", style(path.display()).bold().green());
+
+    // Write the public key, if any
+    if let (Some(public_path), Some(public)) = (public_path, public) {
+        debug!("Writing public key to '{}'...", public_path.display());
+        ensure_dir_of(&public_path, fix_dirs)?;
+        let handle: File =
+            File::create(&public_path).map_err(|source| Error::FileCreateError { what: "policy public key", path: public_path.clone(), source })?;
+        serde_json::to_writer_pretty(handle, &public).map_err(|source| Error::FileSerializeError {
+            what: "policy public key",
+            path: public_path.clone(),
+            source,
+        })?;
+        println!("Successfully generated {}", style(public_path.display()).bold().green());
+    }
 
     // OK
-    println!("Successfully generated {}", style(path.display()).bold().green());
     Ok(())
 }
 
@@ -20,6 +20,7 @@ pub mod errors;
 pub mod generate;
 pub mod lifetime;
 pub mod old_configs;
+pub mod overrides;
 pub mod packages;
 pub mod policies;
 pub mod spec;
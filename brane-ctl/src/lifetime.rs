@@ -14,13 +14,13 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Display;
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read as _, Write};
 use std::net::IpAddr;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output, Stdio};
+use std::process::{Command, ExitStatus, Stdio};
 use std::str::FromStr as _;
 
 use bollard::Docker;
@@ -30,7 +30,7 @@ use brane_cfg::node::{
     ProxyServices, WorkerConfig, WorkerPaths, WorkerServices,
 };
 use brane_cfg::proxy;
-use brane_tsk::docker::{DockerOptions, ImageSource, ensure_image, get_digest};
+use brane_tsk::docker::{DockerOptions, ImageSource, ensure_image, get_digest, image_exists, load_image};
 use console::style;
 use log::{debug, info};
 use rand::Rng;
@@ -40,7 +40,90 @@ use specifications::container::Image;
 use specifications::version::Version;
 
 pub use crate::errors::LifetimeError as Error;
-use crate::spec::{LogsOpts, StartOpts, StartSubcommand};
+use crate::overrides;
+use crate::spec::{LogsOpts, PullPolicy, StartOpts, StartSubcommand};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_interpolate_compose_vars() {
+        let envs: HashMap<&'static str, OsString> = HashMap::from([("BRANE_VERSION", OsString::from("3.0.0")), ("EMPTY", OsString::new())]);
+        assert_eq!(interpolate_compose_vars("brane-api:${BRANE_VERSION:-latest}", &envs), "brane-api:3.0.0");
+        assert_eq!(interpolate_compose_vars("brane-api:$BRANE_VERSION", &envs), "brane-api:3.0.0");
+        assert_eq!(interpolate_compose_vars("a:${BRANE_TEST_UNSET_VAR:-latest}", &envs), "a:latest");
+        assert_eq!(interpolate_compose_vars("a:${EMPTY:-latest}", &envs), "a:latest");
+        assert_eq!(interpolate_compose_vars("a:${EMPTY-latest}", &envs), "a:");
+        assert_eq!(interpolate_compose_vars("$$HOME ${unterminated", &envs), "$HOME ${unterminated");
+    }
+
+    #[test]
+    fn test_compose_services() {
+        let compose: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+services:
+  brane-api:
+    image: brane-api:${BRANE_VERSION:-latest}
+  brane-prx:
+    image: brane-prx:${BRANE_VERSION:-latest}
+  custom:
+    build: .
+  debug:
+    image: busybox
+    profiles: [debug]
+"#,
+        )
+        .unwrap();
+        let overrides: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+version: "3.6"
+services:
+  brane-api: { extra_hosts: [], profiles: [] }
+  brane-prx: { extra_hosts: [], profiles: [donotstart] }
+"#,
+        )
+        .unwrap();
+
+        let service = |name: &str, image: Option<&str>, build: bool, started: bool| ComposeService {
+            name: name.into(),
+            image: image.map(String::from),
+            build,
+            started,
+        };
+        assert_eq!(
+            compose_services(&compose, Some(&overrides)),
+            Some(vec![
+                service("brane-api", Some("brane-api:${BRANE_VERSION:-latest}"), false, true),
+                service("brane-prx", Some("brane-prx:${BRANE_VERSION:-latest}"), false, false),
+                service("custom", None, true, true),
+                service("debug", Some("busybox"), false, false),
+            ])
+        );
+        assert_eq!(compose_services(&compose, None).unwrap()[1], service("brane-prx", Some("brane-prx:${BRANE_VERSION:-latest}"), false, true));
+
+        // Files without services are refused
+        assert_eq!(compose_services(&serde_yaml::from_str("services: {}").unwrap(), None), None);
+        assert_eq!(compose_services(&serde_yaml::from_str("version: '3.6'").unwrap(), None), None);
+    }
+
+    #[test]
+    fn test_default_image_source() {
+        let dir: &Path = Path::new("/images");
+        assert!(matches!(default_image_source("brane-api", dir, false), Some(ImageSource::Path(path)) if path == Path::new("/images/brane-api.tar")));
+        assert!(matches!(default_image_source("aux-scylla", dir, false), Some(ImageSource::Registry(source)) if source == "scylladb/scylla:4.6.3"));
+        assert!(
+            matches!(default_image_source("aux-scylla", dir, true), Some(ImageSource::Path(path)) if path == Path::new("/images/aux-scylla.tar"))
+        );
+        assert!(default_image_source("custom", dir, true).is_none());
+    }
+}
+
+
+
 
 
 /***** HELPER STRUCTS *****/
@@ -451,30 +534,94 @@ fn generate_override_file(node_config: &NodeConfig, hosts: &HashMap<String, IpAd
     }
 }
 
+/// Reads a manifest of expected image digests.
+///
+/// The manifest is a YAML map of image names (e.g., `brane-api`) to the digest (i.e., image ID) that image must have. Digests may be given
+/// with or without the `sha256:` prefix.
+///
+/// # Arguments
+/// - `path`: The path to the manifest.
+///
+/// # Returns
+/// A map of image names to expected digests, without `sha256:` prefix.
+///
+/// # Errors
+/// This function errors if the manifest could not be read or parsed.
+fn read_digest_manifest(path: &Path) -> Result<HashMap<String, String>, Error> {
+    let raw: String = std::fs::read_to_string(path).map_err(|source| Error::DigestManifestReadError { path: path.into(), source })?;
+    let manifest: HashMap<String, String> =
+        serde_yaml::from_str(&raw).map_err(|source| Error::DigestManifestParseError { path: path.into(), source })?;
+    Ok(manifest.into_iter().map(|(name, digest)| (name, digest.trim().trim_start_matches("sha256:").to_string())).collect())
+}
+
 /// Loads the given images.
 ///
 /// # Arguments
 /// - `docker`: The already connected Docker daemon.
 /// - `images`: The map of image name -> image paths to load.
 /// - `version`: The Brane version of the images to pull.
+/// - `digests`: If given, the path to a manifest of expected digests (see [`read_digest_manifest()`]) to verify every image against, both
+///   before loading (for image files) and after.
+/// - `pull_policy`: When to import/pull the images. With [`PullPolicy::Never`], registry images are never pulled, and all of them must
+///   already be loaded before anything is done.
 ///
 /// # Returns
 /// Nothing, but does load them in the local docker daemon if everything goes alright.
 ///
 /// # Errors
-/// This function errors if the given images could not be loaded.
-async fn load_images(docker: &Docker, images: HashMap<impl AsRef<str>, ImageSource>, version: &Version) -> Result<(), Error> {
+/// This function errors if the given images could not be loaded, if any of them does not match its expected digest, or if the pull
+/// policy forbids pulling an image that is not yet loaded.
+async fn load_images(
+    docker: &Docker,
+    images: HashMap<impl AsRef<str>, ImageSource>,
+    version: &Version,
+    digests: Option<&Path>,
+    pull_policy: PullPolicy,
+) -> Result<(), Error> {
+    let expected: Option<HashMap<String, String>> = digests.map(read_digest_manifest).transpose()?;
+
+    // If we may not pull, make sure every registry image is already there before touching anything
+    if pull_policy == PullPolicy::Never {
+        let mut missing: Vec<Image> = vec![];
+        for (name, image_source) in &images {
+            if let ImageSource::Registry(_) = image_source {
+                let image: Image = Image::new(name.as_ref(), Some(version), None::<&str>);
+                if !image_exists(docker, &image).await.map_err(|source| Error::ImageExistsError { image: Box::new(image.clone()), source })? {
+                    missing.push(image);
+                }
+            }
+        }
+        if !missing.is_empty() {
+            return Err(Error::ImagesNotPresent { images: missing });
+        }
+    }
+
     // Iterate over the images
     for (name, image_source) in images {
         let name: &str = name.as_ref();
+        let expected: Option<&str> = match (&expected, digests) {
+            (Some(expected), Some(path)) => {
+                Some(expected.get(name).ok_or_else(|| Error::ImageDigestMissing { name: name.into(), path: path.into() })?.as_str())
+            },
+            _ => None,
+        };
 
         // Determine whether to pull as file or as a repo thing
         let image: Image = match &image_source {
             ImageSource::Path(path) => {
                 println!("Loading image {} from file {}...", style(name).green().bold(), style(path.display().to_string()).bold());
 
-                // Load the digest, too
+                // Load the digest, too, and refuse to even load the file if it's not the one we expect
                 let digest: String = get_digest(path).await.map_err(|source| Error::ImageDigestError { path: path.into(), source })?;
+                if let Some(expected) = expected {
+                    if digest != expected {
+                        return Err(Error::ImageDigestMismatch {
+                            image:    Box::new(Image::new(name, Some(version), None::<&str>)),
+                            expected: expected.into(),
+                            got:      digest,
+                        });
+                    }
+                }
 
                 // Return it
                 Image::new(name, Some(version), Some(digest))
@@ -486,18 +633,53 @@ async fn load_images(docker: &Docker, images: HashMap<impl AsRef<str>, ImageSour
             },
         };
 
-        // Simply rely on ensure_image
-        ensure_image(docker, &image, &image_source).await.map_err(|source| Error::ImageLoadError {
-            image: Box::new(image),
-            image_source: Box::new(image_source),
-            source,
-        })?;
+        // Simply rely on ensure_image, unless we're told to replace whatever is loaded
+        let res: Result<(), brane_tsk::docker::Error> = match pull_policy {
+            PullPolicy::Always => load_image(docker, &image, &image_source).await,
+            PullPolicy::IfNotPresent | PullPolicy::Never => ensure_image(docker, &image, &image_source).await,
+        };
+        res.map_err(|source| Error::ImageLoadError { image: Box::new(image.clone()), image_source: Box::new(image_source), source })?;
+
+        // Verify what's loaded now, as `ensure_image()` won't replace an existing image with the same tag
+        if let Some(expected) = expected {
+            let info = docker
+                .inspect_image(&image.docker().to_string())
+                .await
+                .map_err(|source| Error::ImageInspectError { image: Box::new(image.clone()), source })?;
+            let got: String = info.id.unwrap_or_default().trim_start_matches("sha256:").to_string();
+            if got != expected {
+                return Err(Error::ImageDigestMismatch { image: Box::new(image), expected: expected.into(), got });
+            }
+            debug!("Image '{}' matches expected digest '{}'", image.docker(), expected);
+        }
     }
 
     // Done
     Ok(())
 }
 
+/// Loads the given images for starting a node, or only lists them if this is a dry run.
+///
+/// # Arguments
+/// - `docker_opts`: Configuration for connecting to the local Docker daemon.
+/// - `images`: The map of image name -> image paths to load.
+/// - `opts`: The [`StartOpts`] that determine how to load the images (and whether to do so at all).
+///
+/// # Errors
+/// This function errors if we failed to connect to the Docker daemon or to load the images (see [`load_images()`]).
+async fn start_images(docker_opts: DockerOptions, images: HashMap<&'static str, ImageSource>, opts: &StartOpts) -> Result<(), Error> {
+    if opts.dry_run {
+        for (name, image_source) in images {
+            println!("{} Would load image {} from {}", style("[dry run]").bold().yellow(), style(name).green().bold(), style(image_source).bold());
+        }
+        return Ok(());
+    }
+
+    // Connect to the Docker client & load them
+    let docker: Docker = brane_tsk::docker::connect_local(docker_opts).map_err(|source| Error::DockerConnectError { source })?;
+    load_images(&docker, images, &opts.version, opts.digests.as_deref(), opts.pull_policy).await
+}
+
 /// Constructs the environment variables for Docker compose.
 ///
 /// # Arguments
@@ -651,16 +833,57 @@ impl DockerComposeCommand {
     }
 }
 
+/// Runs the given job to completion, optionally capturing its stderr.
+///
+/// The job's stderr is still shown on our stderr as it comes in; capturing only keeps a copy around. When capturing, the job's stdout is
+/// shown on our stderr as well, keeping our stdout free for machine-readable output.
+///
+/// # Arguments
+/// - `cmd`: The command to run.
+/// - `stderr`: If given, the job's stderr is appended to this string.
+///
+/// # Returns
+/// The exit status of the job.
+///
+/// # Errors
+/// This function fails if we failed to launch the job or to read its stderr.
+fn run_job(cmd: &mut Command, stderr: Option<&mut String>) -> Result<ExitStatus, std::io::Error> {
+    let Some(stderr) = stderr else {
+        return cmd.status();
+    };
+
+    // Tee the job's stderr to ours and to the buffer
+    cmd.stdout(Stdio::from(std::io::stderr()));
+    cmd.stderr(Stdio::piped());
+    let mut child = cmd.spawn()?;
+    let mut job_stderr = child.stderr.take().expect("Piped stderr of child is missing");
+    let mut captured: Vec<u8> = Vec::new();
+    let mut buffer: [u8; 4096] = [0; 4096];
+    loop {
+        let n: usize = job_stderr.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        // Failing to echo is not a reason to fail the job
+        let _ = std::io::stderr().write_all(&buffer[..n]);
+        captured.extend_from_slice(&buffer[..n]);
+    }
+    stderr.push_str(&String::from_utf8_lossy(&captured));
+    child.wait()
+}
+
 /// Runs Docker compose on the given Docker file.
 ///
 /// # Arguments
 /// - `compose_verbose`: If given, attempts to enable additional debug prints in the Docker Compose executable.
+/// - `dry_run`: If given, only prints the command, its environment and the contents of `overridefile` instead of running it.
 /// - `exe`: The `docker-compose` executable to run.
 /// - `file`: The DockerFile to run.
 /// - `project`: The project name to launch the containers for.
 /// - `proxyfile`: If given, an additional `docker-compose` file that will add the proxy service.
 /// - `overridefile`: If given, an additional `docker-compose` file that overrides the default one with extra hosts and other properties.
 /// - `envs`: The map of environment variables to set.
+/// - `stderr`: If given, the stderr of Docker compose is captured in this string (besides being shown).
 ///
 /// # Returns
 /// Nothing upon success, although obviously the Docker containers do get launched if so.
@@ -669,12 +892,14 @@ impl DockerComposeCommand {
 /// This function fails if we failed to launch the command, or the command itself failed.
 fn run_compose(
     compose_verbose: bool,
+    dry_run: bool,
     exe: (String, Vec<String>),
     command: DockerComposeCommand,
     file: impl AsRef<Path>,
     project: impl AsRef<str>,
     overridefile: Option<PathBuf>,
     envs: HashMap<&'static str, OsString>,
+    stderr: Option<&mut String>,
 ) -> Result<(), Error> {
     let file: &Path = file.as_ref();
     let project: &str = project.as_ref();
@@ -687,7 +912,7 @@ fn run_compose(
     }
     cmd.args(["-p", project, "-f"]);
     cmd.arg(file.as_os_str());
-    if let Some(overridefile) = overridefile {
+    if let Some(overridefile) = &overridefile {
         cmd.arg("-f");
         cmd.arg(overridefile);
     }
@@ -697,6 +922,30 @@ fn run_compose(
     cmd.stdout(Stdio::inherit());
     cmd.stderr(Stdio::inherit());
 
+    // If we're only pretending, show what we would've done instead
+    if dry_run {
+        let quote = |arg: &OsStr| -> String {
+            let arg: Cow<str> = arg.to_string_lossy();
+            shlex::try_quote(&arg).map(Cow::into_owned).unwrap_or_else(|_| arg.to_string())
+        };
+        println!("{} Would run:", style("[dry run]").bold().yellow());
+        println!("  {}", std::iter::once(cmd.get_program()).chain(cmd.get_args()).map(quote).collect::<Vec<String>>().join(" "));
+
+        println!("\nWith environment:");
+        let mut envs: Vec<(&OsStr, Option<&OsStr>)> = cmd.get_envs().collect();
+        envs.sort();
+        for (key, value) in envs {
+            println!("  {}={}", key.to_string_lossy(), value.map(quote).unwrap_or_default());
+        }
+
+        if let Some(overridefile) = overridefile {
+            let contents: String =
+                std::fs::read_to_string(&overridefile).map_err(|source| Error::HostsFileReadError { path: overridefile.clone(), source })?;
+            println!("\nGenerated override file {}:\n{}", style(overridefile.display()).bold(), contents.trim_end());
+        }
+        return Ok(());
+    }
+
     // Run it
     println!(
         "Running '{}{}' {} on {}...",
@@ -706,15 +955,15 @@ fn run_compose(
         style(file.display()).bold()
     );
     debug!("Command: {:?}", cmd);
-    let output: Output = match cmd.output() {
-        Ok(output) => output,
+    let status: ExitStatus = match run_job(&mut cmd, stderr) {
+        Ok(status) => status,
         Err(source) => {
             return Err(Error::JobLaunchError { command: cmd, source });
         },
     };
 
-    if !output.status.success() {
-        return Err(Error::JobFailure { command: cmd, status: output.status });
+    if !status.success() {
+        return Err(Error::JobFailure { command: cmd, status });
     }
 
     // Done
@@ -735,6 +984,7 @@ fn run_compose(
 /// - `docker_opts`: Configuration for connecting to the local Docker daemon. See `DockerOptions` for more information.
 /// - `opts`: Miscellaneous configuration for starting the images. See `StartOpts` for more information.
 /// - `command`: The `StartSubcommand` that carries additional information, including which of the node types to launch.
+/// - `stderr`: If given, the stderr of Docker Compose is captured in this string (besides being shown).
 ///
 /// # Returns
 /// Nothing, but does change the local Docker daemon to load and then run the given files.
@@ -748,6 +998,7 @@ pub async fn start(
     docker_opts: DockerOptions,
     opts: StartOpts,
     command: StartSubcommand,
+    stderr: Option<&mut String>,
 ) -> Result<(), Error> {
     let exe: &str = exe.as_ref();
     let node_config_path: PathBuf = node_config_path.into();
@@ -759,7 +1010,9 @@ pub async fn start(
 
     // Start by loading the node config file
     debug!("Loading node config file '{}'...", node_config_path.display());
-    let node_config: NodeConfig = NodeConfig::from_path(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
+    let node_config: NodeConfig = overrides::load_node_config(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
+    // The services read the node config file themselves, so they have to see any overrides too
+    let node_config_path: PathBuf = overrides::materialize(node_config_path).map_err(|source| Error::NodeConfigOverrideError { source })?;
 
     // Resolve the Docker Compose file
     debug!("Resolving Docker Compose file...");
@@ -773,11 +1026,8 @@ pub async fn start(
                 return Err(Error::UnmatchedNodeKind { got: NodeKind::Central, expected: node_config.node.kind() });
             }
 
-            // Connect to the Docker client
-            let docker: Docker = brane_tsk::docker::connect_local(docker_opts).map_err(|source| Error::DockerConnectError { source })?;
-
             // Generate hosts file
-            let overridefile: Option<PathBuf> = generate_override_file(&node_config, &node_config.hostnames, opts.profile_dir)?;
+            let overridefile: Option<PathBuf> = generate_override_file(&node_config, &node_config.hostnames, opts.profile_dir.clone())?;
 
             // Map the images & load them
             if !opts.skip_import {
@@ -791,7 +1041,7 @@ pub async fn start(
                 if node_config.node.central().services.prx.is_private() {
                     images.insert("brane-prx", resolve_image_dir(brane_prx, &opts.image_dir));
                 }
-                load_images(&docker, images, &opts.version).await?;
+                start_images(docker_opts, images, &opts).await?;
             }
 
             // Construct the environment variables
@@ -800,12 +1050,14 @@ pub async fn start(
             // Launch the docker-compose command
             run_compose(
                 opts.compose_verbose,
+                opts.dry_run,
                 resolve_exe(exe)?,
                 DockerComposeCommand::Up,
                 resolve_node(file, "central"),
                 &node_config.namespace,
                 overridefile,
                 envs,
+                stderr,
             )?;
         },
 
@@ -815,14 +1067,13 @@ pub async fn start(
                 return Err(Error::UnmatchedNodeKind { got: NodeKind::Worker, expected: node_config.node.kind() });
             }
 
-            // Connect to the Docker client
-            let docker: Docker = brane_tsk::docker::connect_local(docker_opts).map_err(|source| Error::DockerConnectError { source })?;
-
             // Generate some things that we might need before we actually hit run
-            prepare_host(&node_config)?;
+            if !opts.dry_run {
+                prepare_host(&node_config)?;
+            }
 
             // Generate hosts file
-            let overridefile: Option<PathBuf> = generate_override_file(&node_config, &node_config.hostnames, opts.profile_dir)?;
+            let overridefile: Option<PathBuf> = generate_override_file(&node_config, &node_config.hostnames, opts.profile_dir.clone())?;
 
             // Map the images & load them
             if !opts.skip_import {
@@ -834,7 +1085,7 @@ pub async fn start(
                 if node_config.node.worker().services.prx.is_private() {
                     images.insert("brane-prx", resolve_image_dir(brane_prx, &opts.image_dir));
                 }
-                load_images(&docker, images, &opts.version).await?;
+                start_images(docker_opts, images, &opts).await?;
             }
 
             // Construct the environment variables
@@ -843,12 +1094,14 @@ pub async fn start(
             // Launch the docker-compose command
             run_compose(
                 opts.compose_verbose,
+                opts.dry_run,
                 resolve_exe(exe)?,
                 DockerComposeCommand::Up,
                 resolve_node(file, "worker"),
                 &node_config.namespace,
                 overridefile,
                 envs,
+                stderr,
             )?;
         },
 
@@ -858,16 +1111,13 @@ pub async fn start(
                 return Err(Error::UnmatchedNodeKind { got: NodeKind::Proxy, expected: node_config.node.kind() });
             }
 
-            // Connect to the Docker client
-            let docker: Docker = brane_tsk::docker::connect_local(docker_opts).map_err(|source| Error::DockerConnectError { source })?;
-
             // Generate hosts file
-            let overridefile: Option<PathBuf> = generate_override_file(&node_config, &node_config.hostnames, opts.profile_dir)?;
+            let overridefile: Option<PathBuf> = generate_override_file(&node_config, &node_config.hostnames, opts.profile_dir.clone())?;
 
             // Map the images & load them
             if !opts.skip_import {
                 let images: HashMap<&'static str, ImageSource> = HashMap::from([("brane-prx", resolve_image_dir(brane_prx, &opts.image_dir))]);
-                load_images(&docker, images, &opts.version).await?;
+                start_images(docker_opts, images, &opts).await?;
             }
 
             // Construct the environment variables
@@ -876,17 +1126,23 @@ pub async fn start(
             // Launch the docker-compose command
             run_compose(
                 opts.compose_verbose,
+                opts.dry_run,
                 resolve_exe(exe)?,
                 DockerComposeCommand::Up,
                 resolve_node(file, "proxy"),
                 &node_config.namespace,
                 overridefile,
                 envs,
+                stderr,
             )?;
         },
     }
 
     // Done
+    if opts.dry_run {
+        println!("\nDry run complete; did not launch node of type {}", style(node_config.node.kind()).bold().green());
+        return Ok(());
+    }
     println!("\nSuccessfully launched node of type {}", style(node_config.node.kind()).bold().green());
     Ok(())
 }
@@ -902,13 +1158,20 @@ pub async fn start(
 /// - `exe`: The `docker-compose` executable to run.
 /// - `file`: The docker-compose file file to use to stop.
 /// - `node_config_path`: The path to the node config file that we use to deduce the project name.
+/// - `stderr`: If given, the stderr of Docker Compose is captured in this string (besides being shown).
 ///
 /// # Returns
 /// Nothing, but does change the local Docker daemon to stop the services if they are running.
 ///
 /// # Errors
 /// This function errors if we failed to run docker-compose.
-pub fn stop(compose_verbose: bool, exe: impl AsRef<str>, file: Option<PathBuf>, node_config_path: impl Into<PathBuf>) -> Result<(), Error> {
+pub fn stop(
+    compose_verbose: bool,
+    exe: impl AsRef<str>,
+    file: Option<PathBuf>,
+    node_config_path: impl Into<PathBuf>,
+    stderr: Option<&mut String>,
+) -> Result<(), Error> {
     let exe: &str = exe.as_ref();
     let node_config_path: PathBuf = node_config_path.into();
     info!(
@@ -919,7 +1182,7 @@ pub fn stop(compose_verbose: bool, exe: impl AsRef<str>, file: Option<PathBuf>,
 
     // Start by loading the node config file
     debug!("Loading node config file '{}'...", node_config_path.display());
-    let node_config: NodeConfig = NodeConfig::from_path(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
+    let node_config: NodeConfig = overrides::load_node_config(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
 
     // Resolve the Docker Compose file
     debug!("Resolving Docker Compose file...");
@@ -959,14 +1222,14 @@ pub fn stop(compose_verbose: bool, exe: impl AsRef<str>, file: Option<PathBuf>,
         style(file.display()).bold()
     );
     debug!("Command: {:?}", cmd);
-    let output: Output = match cmd.output() {
-        Ok(output) => output,
+    let status: ExitStatus = match run_job(&mut cmd, stderr) {
+        Ok(status) => status,
         Err(source) => {
             return Err(Error::JobLaunchError { command: cmd, source });
         },
     };
-    if !output.status.success() {
-        return Err(Error::JobFailure { command: cmd, status: output.status });
+    if !status.success() {
+        return Err(Error::JobFailure { command: cmd, status });
     }
 
     // Done
@@ -984,7 +1247,7 @@ pub async fn logs(exe: impl AsRef<str>, file: Option<PathBuf>, node_config_path:
 
     // Start by loading the node config file
     debug!("Loading node config file '{}'...", node_config_path.display());
-    let node_config: NodeConfig = NodeConfig::from_path(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
+    let node_config: NodeConfig = overrides::load_node_config(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
 
     let version = Version::from_str(env!("CARGO_PKG_VERSION")).unwrap();
 
@@ -998,13 +1261,237 @@ pub async fn logs(exe: impl AsRef<str>, file: Option<PathBuf>, node_config_path:
     // Launch the docker-compose command
     run_compose(
         opts.compose_verbose,
+        false,
         resolve_exe(exe)?,
         DockerComposeCommand::Logs,
         resolve_node(file, "$NODE"),
         &node_config.namespace,
         None,
         envs,
+        None,
     )?;
 
     Ok(())
 }
+
+
+
+/// Interpolates environment variables in a value from a Docker Compose file, like Docker Compose itself would.
+///
+/// Supports `$VAR`, `${VAR}`, `${VAR:-default}`, `${VAR-default}` and `$$` (a literal `$`). Variables are first looked up in `envs`, then in
+/// our own environment.
+///
+/// # Arguments
+/// - `raw`: The value to interpolate.
+/// - `envs`: The environment variables that we would pass to Docker Compose.
+///
+/// # Returns
+/// The interpolated value.
+fn interpolate_compose_vars(raw: &str, envs: &HashMap<&'static str, OsString>) -> String {
+    let lookup = |name: &str| -> Option<String> {
+        envs.get(name).map(|v| v.to_string_lossy().into_owned()).or_else(|| std::env::var(name).ok()).filter(|v| !v.is_empty())
+    };
+
+    let mut res: String = String::with_capacity(raw.len());
+    let mut rest: &str = raw;
+    while let Some(pos) = rest.find('$') {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            res.push('$');
+            rest = after;
+        } else if let Some(braced) = rest.strip_prefix('{') {
+            let Some(end) = braced.find('}') else {
+                // Unterminated; leave as-is
+                res.push('$');
+                continue;
+            };
+            let expr: &str = &braced[..end];
+            let value: String = if let Some((name, default)) = expr.split_once(":-") {
+                lookup(name).unwrap_or_else(|| default.into())
+            } else if let Some((name, default)) = expr.split_once('-') {
+                envs.get(name).map(|v| v.to_string_lossy().into_owned()).or_else(|| std::env::var(name).ok()).unwrap_or_else(|| default.into())
+            } else {
+                lookup(expr).unwrap_or_default()
+            };
+            res.push_str(&value);
+            rest = &braced[end + 1..];
+        } else {
+            let len: usize = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            if len == 0 {
+                res.push('$');
+                continue;
+            }
+            res.push_str(&lookup(&rest[..len]).unwrap_or_default());
+            rest = &rest[len..];
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// A service in a Docker Compose file, as far as validating it is concerned.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ComposeService {
+    /// The name of the service.
+    name:    String,
+    /// The image it runs, if any (not yet interpolated).
+    image:   Option<String>,
+    /// Whether Docker Compose builds its image itself.
+    build:   bool,
+    /// Whether Docker Compose starts it by default, i.e., whether it has no profiles.
+    started: bool,
+}
+
+/// Lists the services of a Docker Compose file, taking an override file into account like Docker Compose would.
+///
+/// # Arguments
+/// - `compose`: The parsed Docker Compose file.
+/// - `overrides`: The parsed override file, if any (see [`generate_override_file()`]).
+///
+/// # Returns
+/// The services in the file, or [`None`] if it defines none.
+fn compose_services(compose: &serde_yaml::Value, overrides: Option<&serde_yaml::Value>) -> Option<Vec<ComposeService>> {
+    let services: &serde_yaml::Mapping = compose.get("services")?.as_mapping().filter(|services| !services.is_empty())?;
+    let overrides: Option<&serde_yaml::Mapping> = overrides.and_then(|o| o.get("services")).and_then(serde_yaml::Value::as_mapping);
+    let has_profiles = |service: Option<&serde_yaml::Value>| {
+        service.and_then(|s| s.get("profiles")).and_then(serde_yaml::Value::as_sequence).is_some_and(|profiles| !profiles.is_empty())
+    };
+
+    let mut res: Vec<ComposeService> = Vec::with_capacity(services.len());
+    for (name, service) in services {
+        let name: String = name.as_str().map(String::from).unwrap_or_else(|| format!("{name:?}"));
+        let over: Option<&serde_yaml::Value> = overrides.and_then(|o| o.get(name.as_str()));
+        let get = |key: &str| over.and_then(|o| o.get(key)).or_else(|| service.get(key));
+        res.push(ComposeService {
+            image: get("image").and_then(serde_yaml::Value::as_str).map(String::from),
+            build: get("build").is_some(),
+            started: !has_profiles(Some(service)) && !has_profiles(over),
+            name,
+        });
+    }
+    Some(res)
+}
+
+/// Resolves where `start` imports the image of a service from by default (see [`StartSubcommand`]).
+///
+/// # Arguments
+/// - `service`: The name of the service.
+/// - `image_dir`: The directory to resolve `$IMG_DIR` with.
+/// - `local_aux`: Whether auxillary images are imported from `$IMG_DIR` instead of pulled.
+///
+/// # Returns
+/// The source of the image, or [`None`] if `start` doesn't import the image of this service.
+fn default_image_source(service: &str, image_dir: &Path, local_aux: bool) -> Option<ImageSource> {
+    match service {
+        "aux-scylla" => Some(resolve_aux_svc(None, local_aux, "scylla", image_dir, "scylladb/scylla:4.6.3")),
+        "brane-api" | "brane-drv" | "brane-plr" | "brane-prx" | "brane-chk" | "brane-reg" | "brane-job" => {
+            Some(resolve_image_dir(ImageSource::Path(PathBuf::from(format!("$IMG_DIR/{service}.tar"))), image_dir))
+        },
+        _ => None,
+    }
+}
+
+/// Validates the Docker Compose file that `start` would use, without starting anything.
+///
+/// This resolves the (given or baked-in) Docker Compose file in the same way as `start`, checks that it parses and defines services, and
+/// checks that every image of a service that is started either exists in the local Docker daemon, would be imported by `start` from an
+/// existing file, or can be pulled from its registry. Services disabled by `start`'s override file (e.g., an external proxy) are skipped.
+///
+/// # Arguments
+/// - `file`: The `docker-compose.yml` file to validate. If omitted, validates the baked-in one.
+/// - `node_config_path`: The path to the node config file that determines the node kind and the environment for the file.
+/// - `docker_opts`: Configuration for connecting to the local Docker daemon. See `DockerOptions` for more information.
+/// - `version`: The Brane version to validate for.
+/// - `image_dir`: The directory to resolve `$IMG_DIR` with in the default image sources of `start`.
+/// - `local_aux`: Whether `start` would import the auxillary images from `$IMG_DIR` instead of pulling them.
+///
+/// # Returns
+/// Nothing, but does print the availability of every image to stdout.
+///
+/// # Errors
+/// This function errors if the Docker Compose file cannot be resolved, read or parsed, or if any of its images is unavailable.
+pub async fn validate_compose(
+    file: Option<PathBuf>,
+    node_config_path: impl Into<PathBuf>,
+    docker_opts: DockerOptions,
+    version: Version,
+    image_dir: PathBuf,
+    local_aux: bool,
+) -> Result<(), Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+
+    // Resolve the Docker Compose file as `start` would
+    debug!("Loading node config file '{}'...", node_config_path.display());
+    let node_config: NodeConfig = overrides::load_node_config(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
+    let file: PathBuf = resolve_docker_compose_file(file, node_config.node.kind(), version)?;
+    let file: PathBuf = resolve_node(file, match node_config.node.kind() {
+        NodeKind::Central => "central",
+        NodeKind::Worker => "worker",
+        NodeKind::Proxy => "proxy",
+    });
+    let version: Version = if version.is_latest() { Version::from_str(env!("CARGO_PKG_VERSION")).unwrap() } else { version };
+    let envs: HashMap<&str, OsString> = construct_envs(&version, &node_config_path, &node_config)?;
+
+    // Parse it
+    let raw: String = std::fs::read_to_string(&file).map_err(|source| Error::DockerComposeReadError { path: file.clone(), source })?;
+    let compose: serde_yaml::Value = serde_yaml::from_str(&raw).map_err(|source| Error::DockerComposeParseError { path: file.clone(), source })?;
+
+    // Apply the override file that `start` would add
+    let overrides: Option<serde_yaml::Value> = match generate_override_file(&node_config, &node_config.hostnames, None)? {
+        Some(path) => {
+            let raw: String = std::fs::read_to_string(&path).map_err(|source| Error::DockerComposeReadError { path: path.clone(), source })?;
+            Some(serde_yaml::from_str(&raw).map_err(|source| Error::DockerComposeParseError { path, source })?)
+        },
+        None => None,
+    };
+    let Some(services) = compose_services(&compose, overrides.as_ref()) else {
+        return Err(Error::DockerComposeNoServices { path: file });
+    };
+    println!("Docker Compose file {} defines {} service(s)", style(file.display()).bold(), services.len());
+
+    // Check the images
+    let docker: Docker = brane_tsk::docker::connect_local(docker_opts).map_err(|source| Error::DockerConnectError { source })?;
+    let mut unavailable: Vec<String> = Vec::new();
+    for service in services {
+        let name: &str = &service.name;
+        if !service.started {
+            println!(" - {}: not started", style(name).bold());
+            continue;
+        }
+        let Some(image) = &service.image else {
+            if service.build {
+                println!(" - {}: built by Docker Compose", style(name).bold());
+            } else {
+                println!(" - {}: {}", style(name).bold(), style("no image").bold().red());
+                unavailable.push(format!("<none> (service '{name}')"));
+            }
+            continue;
+        };
+        let image: String = interpolate_compose_vars(image, &envs);
+
+        // Images that aren't there yet are imported by `start` if it knows the service, or pulled by Docker Compose otherwise
+        if docker.inspect_image(&image).await.is_ok() {
+            println!(" - {}: {} {}", style(name).bold(), style(&image).bold(), style("(local)").green());
+            continue;
+        }
+        let (available, source): (bool, String) = match default_image_source(name, &image_dir, local_aux) {
+            Some(ImageSource::Path(path)) => (path.is_file(), format!("file {}", path.display())),
+            Some(ImageSource::Registry(source)) => (docker.inspect_registry_image(&source, None).await.is_ok(), format!("registry {source}")),
+            None => (docker.inspect_registry_image(&image, None).await.is_ok(), "registry".into()),
+        };
+        if available {
+            println!(" - {}: {} {}", style(name).bold(), style(&image).bold(), style(format!("(from {source})")).green());
+        } else {
+            println!(" - {}: {} {}", style(name).bold(), style(&image).bold(), style(format!("(unavailable from {source})")).bold().red());
+            unavailable.push(image);
+        }
+    }
+    if !unavailable.is_empty() {
+        return Err(Error::DockerComposeImagesUnavailable { path: file, images: unavailable });
+    }
+
+    // Done
+    println!("\nDocker Compose file {} is valid", style(file.display()).bold().green());
+    Ok(())
+}
@@ -15,15 +15,49 @@
 pub mod cli;
 
 use brane_cfg::proxy::ForwardConfig;
+use brane_ctl::errors::{LifetimeError, UnpackError};
 use brane_ctl::spec::{LogsOpts, StartOpts};
-use brane_ctl::{download, generate, lifetime, packages, policies, unpack, upgrade, wizard};
+use brane_ctl::{download, generate, lifetime, overrides, packages, policies, unpack, upgrade, wizard};
 use brane_tsk::docker::DockerOptions;
 use clap::Parser;
 use cli::*;
 use dotenvy::dotenv;
 use error_trace::ErrorTrace as _;
 use humanlog::{DebugMode, HumanLogger};
-use log::error;
+use log::{error, warn};
+use serde_json::json;
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Reports the outcome of a lifetime subcommand (`start` or `stop`) as a JSON object on stdout.
+///
+/// # Arguments
+/// - `subcommand`: The name of the subcommand that ran.
+/// - `res`: The result of the subcommand.
+/// - `stderr`: The captured stderr of the Docker Compose command (if any ran).
+fn report_lifetime_json(subcommand: &str, res: &Result<(), LifetimeError>, stderr: String) {
+    let report = match res {
+        Ok(()) => json!({ "subcommand": subcommand, "success": true }),
+        Err(err) => {
+            let (command, exit_code, stderr) = match err {
+                LifetimeError::JobLaunchError { command, .. } => (Some(format!("{command:?}")), None, None),
+                LifetimeError::JobFailure { command, status } => (Some(format!("{command:?}")), status.code(), Some(stderr)),
+                _ => (None, None, None),
+            };
+            json!({
+                "subcommand": subcommand,
+                "success": false,
+                "error": err.to_string(),
+                "trace": err.trace().to_string(),
+                "command": command,
+                "exit_code": exit_code,
+                "stderr": stderr,
+            })
+        },
+    };
+    println!("{report}");
+}
 
 
 
@@ -54,12 +88,22 @@ async fn main() {
         human_panic::setup_panic!();
     }
 
+    // Apply any node config overrides (except when generating the node config, which doesn't read it)
+    if !args.overrides.is_empty() {
+        if matches!(args.subcommand, CtlSubcommand::Generate(_)) {
+            warn!("Ignoring node config overrides when generating files");
+        } else if let Err(err) = overrides::resolve(&args.node_config, &args.overrides) {
+            error!("{}", err.trace());
+            std::process::exit(1);
+        }
+    }
+
     // Now match on the command
     match args.subcommand {
         CtlSubcommand::Download(subcommand) => match *subcommand {
-            DownloadSubcommand::Services { fix_dirs, path, arch, version, force, kind } => {
+            DownloadSubcommand::Services { fix_dirs, path, arch, version, require_checksum, force, kind } => {
                 // Run the subcommand
-                if let Err(err) = download::services(fix_dirs, path, arch, version, force, kind).await {
+                if let Err(err) = download::services(fix_dirs, path, arch, version, require_checksum, force, kind).await {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -105,9 +149,9 @@ async fn main() {
                     std::process::exit(1);
                 }
             },
-            GenerateSubcommand::PolicySecret { fix_dirs, path, key_id, jwt_alg } => {
+            GenerateSubcommand::PolicySecret { fix_dirs, path, key_id, jwt_alg, public_path } => {
                 // Call the thing
-                if let Err(err) = generate::policy_secret(fix_dirs, path, key_id, jwt_alg) {
+                if let Err(err) = generate::policy_secret(fix_dirs, path, public_path, key_id, jwt_alg) {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -143,8 +187,13 @@ async fn main() {
             },
         },
         CtlSubcommand::Unpack(subcommand) => match *subcommand {
-            UnpackSubcommand::Compose { kind, path, fix_dirs } => {
-                if let Err(err) = unpack::compose(kind, fix_dirs, path, args.node_config) {
+            UnpackSubcommand::Compose { kind, path, fix_dirs, list } => {
+                let res: Result<(), UnpackError> = if list {
+                    unpack::list_compose(kind, fix_dirs, path, args.node_config)
+                } else {
+                    unpack::compose(kind, fix_dirs, path, args.node_config)
+                };
+                if let Err(err) = res {
                     error!("{}", err.trace());
                     std::process::exit(1);
                 }
@@ -195,23 +244,78 @@ async fn main() {
             },
         },
 
-        CtlSubcommand::Start { exe, file, docker_socket, docker_version, version, image_dir, local_aux, skip_import, profile_dir, kind } => {
-            if let Err(err) = lifetime::start(
+        CtlSubcommand::Start {
+            exe,
+            file,
+            docker_socket,
+            docker_version,
+            version,
+            image_dir,
+            local_aux,
+            skip_import,
+            profile_dir,
+            digests,
+            pull_policy,
+            dry_run,
+            json,
+            kind,
+        } => {
+            let mut stderr: String = String::new();
+            let res: Result<(), LifetimeError> = lifetime::start(
                 exe,
                 file,
                 args.node_config,
-                DockerOptions { socket: docker_socket, version: docker_version },
-                StartOpts { compose_verbose: args.debug || args.trace, version, image_dir, local_aux, skip_import, profile_dir },
+                DockerOptions { host: docker_socket.into(), version: docker_version },
+                StartOpts {
+                    compose_verbose: args.debug || args.trace,
+                    version,
+                    image_dir,
+                    local_aux,
+                    skip_import,
+                    profile_dir,
+                    digests,
+                    pull_policy,
+                    dry_run,
+                },
                 *kind,
+                if json { Some(&mut stderr) } else { None },
             )
-            .await
-            {
-                error!("{}", err.trace());
+            .await;
+            if json {
+                report_lifetime_json("start", &res, stderr);
+            }
+            if let Err(err) = res {
+                if !json {
+                    error!("{}", err.trace());
+                }
+                std::process::exit(1);
+            }
+        },
+        CtlSubcommand::Stop { exe, file, json } => {
+            let mut stderr: String = String::new();
+            let res: Result<(), LifetimeError> =
+                lifetime::stop(args.debug || args.trace, exe, file, args.node_config, if json { Some(&mut stderr) } else { None });
+            if json {
+                report_lifetime_json("stop", &res, stderr);
+            }
+            if let Err(err) = res {
+                if !json {
+                    error!("{}", err.trace());
+                }
                 std::process::exit(1);
             }
         },
-        CtlSubcommand::Stop { exe, file } => {
-            if let Err(err) = lifetime::stop(args.debug || args.trace, exe, file, args.node_config) {
+        CtlSubcommand::ValidateCompose { docker_socket, docker_version, file, version, image_dir, local_aux } => {
+            if let Err(err) = lifetime::validate_compose(
+                file,
+                args.node_config,
+                DockerOptions { host: docker_socket.into(), version: docker_version },
+                version,
+                image_dir,
+                local_aux,
+            )
+            .await
+            {
                 error!("{}", err.trace());
                 std::process::exit(1);
             }
@@ -0,0 +1,311 @@
+//  OVERRIDES.rs
+//
+//  Description:
+//!   Implements applying `--set <KEY>=<VALUE>` overrides to the node
+//!   config before it is used.
+//
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use brane_cfg::info::{Info as _, YamlError};
+use brane_cfg::node::NodeConfig;
+use log::{debug, info};
+use serde_yaml::{Mapping, Value};
+
+pub use crate::errors::OverrideError as Error;
+use crate::spec::{ConfigOverride, ConfigValueType, Pair};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::str::FromStr as _;
+
+    use brane_cfg::node::{NodeSpecificConfig, PrivateOrExternalService, PrivateService, PublicService, WorkerConfig, WorkerPaths, WorkerServices};
+    use specifications::address::Address;
+
+    use super::*;
+
+    /// Returns a minimal worker node config to override.
+    fn worker() -> NodeConfig {
+        let public = |name: &str, port: u16| PublicService {
+            name: name.into(),
+            address: Address::hostname(name, port),
+            bind: format!("0.0.0.0:{port}").parse().unwrap(),
+            external_address: Address::hostname("example.com", port),
+        };
+        NodeConfig {
+            hostnames: HashMap::new(),
+            namespace: "brane-worker".into(),
+            node:      NodeSpecificConfig::Worker(WorkerConfig {
+                name:     "amy".into(),
+                usecases: HashMap::new(),
+                paths:    WorkerPaths {
+                    certs: "/certs".into(),
+                    packages: "/packages".into(),
+                    backend: "/config/backend.yml".into(),
+                    policy_database: "/policies.db".into(),
+                    policy_deliberation_secret: "/config/policy_deliberation_secret.json".into(),
+                    policy_expert_secret: "/config/policy_expert_secret.json".into(),
+                    policy_audit_log: None,
+                    proxy: None,
+                    data: "/data".into(),
+                    results: "/results".into(),
+                    temp_data: "/tmp/data".into(),
+                    temp_results: "/tmp/results".into(),
+                },
+                services: WorkerServices {
+                    reg: public("brane-reg", 50051),
+                    job: public("brane-job", 50052),
+                    chk: PrivateService {
+                        name:    "brane-chk".into(),
+                        address: Address::hostname("brane-chk", 50053),
+                        bind:    "0.0.0.0:50053".parse().unwrap(),
+                    },
+                    prx: PrivateOrExternalService::Private(PrivateService {
+                        name:    "brane-prx".into(),
+                        address: Address::hostname("brane-prx", 50050),
+                        bind:    "0.0.0.0:50050".parse().unwrap(),
+                    }),
+                },
+            }),
+        }
+    }
+
+    /// Parses the given `<KEY>=<VALUE>` overrides.
+    fn overrides(raw: &[&str]) -> Vec<ConfigOverride> { raw.iter().map(|raw| ConfigOverride::from_str(raw).unwrap()).collect() }
+
+    #[test]
+    fn test_apply() {
+        // Known keys, with the type inferred from the field
+        let config: NodeConfig = apply(worker(), &overrides(&["namespace=foo", "node.worker.services.reg.bind=127.0.0.1:1234"])).unwrap();
+        assert_eq!(config.namespace, "foo");
+        assert_eq!(config.node.try_worker().unwrap().services.reg.bind, "127.0.0.1:1234".parse::<SocketAddr>().unwrap());
+
+        // Unset optional fields
+        let config: NodeConfig = apply(worker(), &overrides(&["node.worker.paths.proxy=/config/proxy.yml"])).unwrap();
+        assert_eq!(config.node.try_worker().unwrap().paths.proxy, Some(PathBuf::from("/config/proxy.yml")));
+
+        // Explicit types
+        let config: NodeConfig = apply(worker(), &overrides(&["namespace:str=42"])).unwrap();
+        assert_eq!(config.namespace, "42");
+    }
+
+    #[test]
+    fn test_apply_errors() {
+        assert!(matches!(apply(worker(), &overrides(&["nmespace=foo"])), Err(Error::UnknownKey { .. })));
+        assert!(matches!(apply(worker(), &overrides(&["node.worker.paths.foo=/foo"])), Err(Error::UnknownKey { .. })));
+        assert!(matches!(apply(worker(), &overrides(&["namespace.foo=bar"])), Err(Error::UnknownKey { .. })));
+        assert!(matches!(apply(worker(), &overrides(&["namespace:int=foo"])), Err(Error::IllegalValue { .. })));
+        assert!(matches!(apply(worker(), &overrides(&["node.worker.services.reg.bind=foo"])), Err(Error::NodeConfigDeserializeError { .. })));
+    }
+}
+
+
+
+
+
+/***** GLOBALS *****/
+/// The node config file that was overridden by [`resolve()`], together with the result.
+static OVERRIDDEN: OnceLock<(PathBuf, NodeConfig)> = OnceLock::new();
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Finds the value at the given path in a serialized config.
+///
+/// # Arguments
+/// - `value`: The (serialized) config to search.
+/// - `path`: The fields (or array indices) to walk.
+///
+/// # Returns
+/// The value at the given path, or [`None`] if there is no such value.
+fn lookup<'v>(mut value: &'v Value, path: &[String]) -> Option<&'v Value> {
+    for segment in path {
+        value = match value {
+            Value::Mapping(map) => map.get(segment.as_str())?,
+            Value::Sequence(seq) => seq.get(segment.parse::<usize>().ok()?)?,
+            // Enums (e.g., the node-specific config) are serialized as tagged values
+            Value::Tagged(tagged) if tagged.tag == segment.as_str() => &tagged.value,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// Finds the value at the given path in a serialized config, creating any fields that are missing along the way.
+///
+/// Missing fields are created as `null`, such that unset optional fields can be overridden. Whether the created fields actually exist in
+/// the node config is checked by [`apply()`] after deserializing it again.
+///
+/// # Arguments
+/// - `value`: The (serialized) config to search.
+/// - `path`: The fields (or array indices) to walk.
+///
+/// # Returns
+/// The value at the given path, or [`None`] if the path walks into a non-mapping value.
+fn lookup_or_insert<'v>(mut value: &'v mut Value, path: &[String]) -> Option<&'v mut Value> {
+    for segment in path {
+        if value.is_null() {
+            *value = Value::Mapping(Mapping::new());
+        }
+        value = match value {
+            Value::Mapping(map) => {
+                if !map.contains_key(segment.as_str()) {
+                    map.insert(Value::String(segment.clone()), Value::Null);
+                }
+                map.get_mut(segment.as_str())?
+            },
+            Value::Sequence(seq) => seq.get_mut(segment.parse::<usize>().ok()?)?,
+            Value::Tagged(tagged) if tagged.tag == segment.as_str() => &mut tagged.value,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// Parses an override value to the YAML value to put in the config.
+///
+/// # Arguments
+/// - `key`: The key that is overridden, used for errors.
+/// - `raw`: The value to parse.
+/// - `ty`: The explicit type to parse it as, if any.
+/// - `current`: The value that is being replaced, which determines the type if none is given explicitly.
+///
+/// # Errors
+/// This function errors if `raw` is not a valid value of the chosen type.
+fn coerce(key: &str, raw: &str, ty: Option<ConfigValueType>, current: &Value) -> Result<Value, Error> {
+    let ty: ConfigValueType = match (ty, current) {
+        (Some(ty), _) => ty,
+        (None, Value::Bool(_)) => ConfigValueType::Bool,
+        (None, Value::Number(n)) if n.is_f64() => ConfigValueType::Float,
+        (None, Value::Number(_)) => ConfigValueType::Int,
+        (None, Value::String(_)) => ConfigValueType::String,
+        // E.g., an unset optional field; let YAML decide
+        (None, _) => {
+            return serde_yaml::from_str(raw).map_err(|source| Error::IllegalYaml { key: key.into(), raw: raw.into(), source });
+        },
+    };
+
+    let illegal = || Error::IllegalValue { key: key.into(), raw: raw.into(), ty };
+    match ty {
+        ConfigValueType::String => Ok(Value::String(raw.into())),
+        ConfigValueType::Int => match raw.parse::<i64>() {
+            Ok(value) => Ok(Value::Number(value.into())),
+            Err(_) => raw.parse::<u64>().map(|value| Value::Number(value.into())).map_err(|_| illegal()),
+        },
+        ConfigValueType::Float => raw.parse::<f64>().map(|value| Value::Number(value.into())).map_err(|_| illegal()),
+        ConfigValueType::Bool => raw.parse::<bool>().map(Value::Bool).map_err(|_| illegal()),
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Applies the given overrides to a node config.
+///
+/// Keys are resolved against the node config itself: fields that are unset (e.g., optional ones) may be overridden, but keys that the
+/// node config does not know about are rejected.
+///
+/// # Arguments
+/// - `node_config`: The [`NodeConfig`] to override fields of.
+/// - `overrides`: The overrides to apply, in order.
+///
+/// # Returns
+/// The overridden [`NodeConfig`].
+///
+/// # Errors
+/// This function errors if a key does not exist in the node config, if a value does not match the type of the field it replaces, or if
+/// the result is not a valid node config anymore.
+pub fn apply(node_config: NodeConfig, overrides: &[ConfigOverride]) -> Result<NodeConfig, Error> {
+    let mut config: Value = serde_yaml::to_value(&node_config).map_err(|source| Error::NodeConfigSerializeError { source })?;
+    for Pair(key, raw) in overrides {
+        debug!("Overriding node config key '{key}' with '{raw}'...");
+
+        // Walk to the value to replace, and replace it
+        let target: &mut Value = lookup_or_insert(&mut config, &key.path).ok_or_else(|| Error::UnknownKey { key: key.to_string() })?;
+        *target = coerce(&key.to_string(), raw, key.ty, target)?;
+    }
+    let node_config: NodeConfig = serde_yaml::from_value(config).map_err(|source| Error::NodeConfigDeserializeError { source })?;
+
+    // Any keys that serde ignored while deserializing are not part of the node config
+    let config: Value = serde_yaml::to_value(&node_config).map_err(|source| Error::NodeConfigSerializeError { source })?;
+    if let Some(Pair(key, _)) = overrides.iter().find(|Pair(key, _)| lookup(&config, &key.path).is_none()) {
+        return Err(Error::UnknownKey { key: key.to_string() });
+    }
+    Ok(node_config)
+}
+
+/// Loads the node config file at the given path and applies the given overrides to it in memory.
+///
+/// Subsequent calls to [`load_node_config()`] for the same path return the overridden node config instead of reading the file. Only the
+/// first call to this function has any effect.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file to override.
+/// - `overrides`: The overrides to apply, in order.
+///
+/// # Errors
+/// This function errors if we failed to load the node config, or if any of the overrides failed to apply (see [`apply()`]).
+pub fn resolve(node_config_path: impl AsRef<Path>, overrides: &[ConfigOverride]) -> Result<(), Error> {
+    let node_config_path: &Path = node_config_path.as_ref();
+
+    let node_config: NodeConfig =
+        NodeConfig::from_path(node_config_path).map_err(|source| Error::NodeConfigLoadError { path: node_config_path.into(), source })?;
+    let node_config: NodeConfig = apply(node_config, overrides)?;
+    info!("Applied {} override(s) to node config file '{}'", overrides.len(), node_config_path.display());
+    let _ = OVERRIDDEN.set((node_config_path.into(), node_config));
+    Ok(())
+}
+
+/// Loads the node config file at the given path, with any overrides applied by [`resolve()`].
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file to load.
+///
+/// # Returns
+/// The (overridden) [`NodeConfig`].
+///
+/// # Errors
+/// This function errors if the node config was not overridden and we failed to load it from disk.
+pub fn load_node_config(node_config_path: impl AsRef<Path>) -> Result<NodeConfig, YamlError> {
+    let node_config_path: &Path = node_config_path.as_ref();
+    match OVERRIDDEN.get() {
+        Some((path, node_config)) if path == node_config_path => Ok(node_config.clone()),
+        _ => NodeConfig::from_path(node_config_path),
+    }
+}
+
+/// Writes the overridden node config for the given path to disk, for services that read it themselves.
+///
+/// The result is written as `<NAME>.overrides.yml` in the same directory, such that relative paths in it still resolve the same. It has to
+/// outlive the services using it, so it is not removed afterwards.
+///
+/// # Arguments
+/// - `node_config_path`: The path to the node config file that may have been overridden.
+///
+/// # Returns
+/// The path of the overridden node config file, or `node_config_path` itself if it was not overridden.
+///
+/// # Errors
+/// This function errors if we failed to write the overridden node config.
+pub fn materialize(node_config_path: impl Into<PathBuf>) -> Result<PathBuf, Error> {
+    let node_config_path: PathBuf = node_config_path.into();
+    let node_config: &NodeConfig = match OVERRIDDEN.get() {
+        Some((path, node_config)) if *path == node_config_path => node_config,
+        _ => return Ok(node_config_path),
+    };
+
+    let stem: String = node_config_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_else(|| "node".into());
+    let path: PathBuf = node_config_path.with_file_name(format!("{stem}.overrides.yml"));
+    node_config.to_path(&path).map_err(|source| Error::NodeConfigWriteError { path: path.clone(), source })?;
+    debug!("Wrote overridden node config file to '{}'", path.display());
+    Ok(path)
+}
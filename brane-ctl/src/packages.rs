@@ -18,13 +18,13 @@ use std::fs::{self, DirEntry, ReadDir};
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use brane_cfg::info::Info as _;
 use brane_cfg::node::{NodeConfig, NodeKind, NodeSpecificConfig};
 use brane_tsk::docker;
 use log::{debug, info, warn};
 use specifications::version::Version;
 
 pub use crate::errors::PackagesError as Error;
+use crate::overrides;
 
 
 /***** LIBRARY *****/
@@ -45,7 +45,7 @@ pub async fn hash(node_config_path: impl Into<PathBuf>, image: impl Into<String>
 
     // Load the node config file
     debug!("Loading node config file '{}'...", node_config_path.display());
-    let node_config: NodeConfig = NodeConfig::from_path(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
+    let node_config: NodeConfig = overrides::load_node_config(&node_config_path).map_err(|source| Error::NodeConfigLoadError { source })?;
     let packages_path: PathBuf = match node_config.node {
         NodeSpecificConfig::Central(node) => node.paths.packages,
         NodeSpecificConfig::Worker(node) => node.paths.packages,
@@ -18,7 +18,6 @@ use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use brane_cfg::info::Info;
 use brane_cfg::node::{NodeConfig, NodeSpecificConfig, WorkerConfig};
 use brane_shr::formatters::BlockFormatter;
 use console::style;
@@ -38,6 +37,7 @@ use specifications::checking::{
 use srv::models::{AddPolicyPostModel, PolicyContentPostModel, SetVersionPostModel};
 use tokio::fs::{self as tfs, File as TFile};
 
+use crate::overrides;
 use crate::spec::PolicyInputLanguage;
 
 
@@ -204,7 +204,7 @@ fn resolve_worker_config(node_config_path: impl AsRef<Path>, worker: Option<Work
         let node_config_path: &Path = node_config_path.as_ref();
 
         debug!("Loading node configuration file '{}'...", node_config_path.display());
-        let node: NodeConfig = match NodeConfig::from_path(node_config_path) {
+        let node: NodeConfig = match overrides::load_node_config(node_config_path) {
             Ok(node) => node,
             Err(err) => return Err(Error::NodeConfigLoad { path: node_config_path.into(), err }),
         };
@@ -24,7 +24,53 @@ use enum_debug::EnumDebug;
 use specifications::address::Address;
 use specifications::version::Version;
 
-use crate::errors::{InclusiveRangeParseError, PairParseError, PolicyInputLanguageParseError};
+use crate::errors::{ConfigKeyParseError, InclusiveRangeParseError, PairParseError, PolicyInputLanguageParseError, PullPolicyParseError};
+
+
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Parses a range of ports, unwrapping it into its bounds.
+    fn parse(raw: &str) -> Result<(u16, u16), InclusiveRangeParseError> {
+        let range: InclusiveRange<u16> = InclusiveRange::from_str(raw)?;
+        Ok((*range.0.start(), *range.0.end()))
+    }
+
+
+
+    #[test]
+    fn test_inclusive_range_bounded() {
+        assert_eq!(parse("4200-4299").unwrap(), (4200, 4299));
+        assert_eq!(parse("4200-4200").unwrap(), (4200, 4200));
+        assert!(matches!(parse("4299-4200"), Err(InclusiveRangeParseError::StartLargerThanEnd { .. })));
+    }
+
+    #[test]
+    fn test_inclusive_range_open_start() {
+        assert_eq!(parse("-4299").unwrap(), (1024, 4299));
+        assert_eq!(parse("-1024").unwrap(), (1024, 1024));
+        assert!(matches!(parse("-1000"), Err(InclusiveRangeParseError::StartLargerThanEnd { .. })));
+    }
+
+    #[test]
+    fn test_inclusive_range_open_end() {
+        assert_eq!(parse("4200-").unwrap(), (4200, u16::MAX));
+        assert_eq!(parse("65535-").unwrap(), (u16::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn test_inclusive_range_malformed() {
+        assert!(matches!(parse("4200"), Err(InclusiveRangeParseError::MissingDash { .. })));
+        assert!(matches!(parse("-"), Err(InclusiveRangeParseError::MissingBounds { .. })));
+        assert!(matches!(parse("a-4299"), Err(InclusiveRangeParseError::NumberParseError { .. })));
+        assert!(matches!(parse("4200-b"), Err(InclusiveRangeParseError::NumberParseError { .. })));
+        assert!(matches!(parse("4200-4299-"), Err(InclusiveRangeParseError::NumberParseError { .. })));
+        assert!(matches!(parse("4200-65536"), Err(InclusiveRangeParseError::NumberParseError { .. })));
+    }
+}
 
 
 /***** STATICS *****/
@@ -114,7 +160,26 @@ impl FromStr for VersionFix {
 
 
 
+/// Abstracts over types that define what the open ends of an [`InclusiveRange`] default to.
+pub trait OpenRangeBounds {
+    /// The start of a range that omits it.
+    const OPEN_START: Self;
+    /// The end of a range that omits it.
+    const OPEN_END: Self;
+}
+/// Ranges of `u16`s are port ranges, which are open towards the unprivileged ports.
+impl OpenRangeBounds for u16 {
+    /// The first port that doesn't need privileges to bind to (port 0 isn't a port at all).
+    const OPEN_START: Self = 1024;
+    const OPEN_END: Self = u16::MAX;
+}
+
+
+
 /// Defines an _inclusive_ range of numbers.
+///
+/// When parsed, either end may be omitted (e.g., `-4299` or `4200-`), in which case it defaults to [`OpenRangeBounds::OPEN_START`] or
+/// [`OpenRangeBounds::OPEN_END`], respectively.
 #[derive(Clone, Debug)]
 pub struct InclusiveRange<T>(pub RangeInclusive<T>);
 impl<T> InclusiveRange<T> {
@@ -148,7 +213,7 @@ impl<T: Display> Display for InclusiveRange<T> {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}-{}", self.0.start(), self.0.end()) }
 }
-impl<T: FromStr + PartialOrd> FromStr for InclusiveRange<T>
+impl<T: FromStr + OpenRangeBounds + PartialOrd> FromStr for InclusiveRange<T>
 where
     T::Err: 'static + Send + Sync + std::error::Error,
 {
@@ -166,18 +231,23 @@ where
         // Split into the start and end number
         let sstart: &str = &s[..dpos];
         let send: &str = &s[dpos + 1..];
+        if sstart.is_empty() && send.is_empty() {
+            return Err(InclusiveRangeParseError::MissingBounds { raw: s.into() });
+        }
 
-        // Parse them
-        let start: T = T::from_str(sstart).map_err(|source| InclusiveRangeParseError::NumberParseError {
-            what:   std::any::type_name::<T>(),
-            raw:    sstart.into(),
-            source: Box::new(source),
-        })?;
-        let end: T = T::from_str(send).map_err(|source| InclusiveRangeParseError::NumberParseError {
-            what:   std::any::type_name::<T>(),
-            raw:    send.into(),
-            source: Box::new(source),
-        })?;
+        // Parse them, defaulting omitted ends
+        let parse = |raw: &str, default: T| -> Result<T, InclusiveRangeParseError> {
+            if raw.is_empty() {
+                return Ok(default);
+            }
+            T::from_str(raw).map_err(|source| InclusiveRangeParseError::NumberParseError {
+                what:   std::any::type_name::<T>(),
+                raw:    raw.into(),
+                source: Box::new(source),
+            })
+        };
+        let start: T = parse(sstart, T::OPEN_START)?;
+        let end: T = parse(send, T::OPEN_END)?;
 
         // Assert the order is correct
         if start > end {
@@ -244,6 +314,76 @@ where
 
 
 /***** LIBRARY *****/
+/// Defines the types a node config override value may be explicitly given as.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConfigValueType {
+    /// It's a string.
+    String,
+    /// It's a (signed or unsigned) integer.
+    Int,
+    /// It's a floating-point number.
+    Float,
+    /// It's a boolean.
+    Bool,
+}
+impl Display for ConfigValueType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ConfigValueType::*;
+        match self {
+            String => write!(f, "str"),
+            Int => write!(f, "int"),
+            Float => write!(f, "float"),
+            Bool => write!(f, "bool"),
+        }
+    }
+}
+
+/// Defines the key of a node config override, i.e., a dot-separated path to a field with an optional explicit type (e.g.,
+/// `node.worker.ports.reg` or `namespace:str`).
+#[derive(Clone, Debug)]
+pub struct ConfigKey {
+    /// The fields (or array indices) to walk to get to the overridden value.
+    pub path: Vec<String>,
+    /// The type to parse the value as, if given. Otherwise, it's inferred from the value that is replaced.
+    pub ty:   Option<ConfigValueType>,
+}
+impl Display for ConfigKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{}", self.path.join("."))?;
+        if let Some(ty) = self.ty {
+            write!(f, ":{ty}")?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for ConfigKey {
+    type Err = ConfigKeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Split off the type, if any
+        let (path, ty): (&str, Option<ConfigValueType>) = match s.split_once(':') {
+            Some((path, "str")) => (path, Some(ConfigValueType::String)),
+            Some((path, "int")) => (path, Some(ConfigValueType::Int)),
+            Some((path, "float")) => (path, Some(ConfigValueType::Float)),
+            Some((path, "bool")) => (path, Some(ConfigValueType::Bool)),
+            Some((_, raw)) => return Err(ConfigKeyParseError::UnknownType { raw: raw.into() }),
+            None => (s, None),
+        };
+
+        // Split the path into its segments
+        let path: Vec<String> = path.split('.').map(String::from).collect();
+        if path.iter().any(String::is_empty) {
+            return Err(ConfigKeyParseError::EmptySegment { raw: s.into() });
+        }
+        Ok(Self { path, ty })
+    }
+}
+
+/// Defines a `--set <KEY>=<VALUE>` override for a field in the node config.
+pub type ConfigOverride = Pair<ConfigKey, '=', String>;
+
+
+
 /// Defines recognized input language identifiers for policy files.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum PolicyInputLanguage {
@@ -273,6 +413,40 @@ impl FromStr for PolicyInputLanguage {
     }
 }
 
+/// Defines when to fetch service images while starting a node.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum PullPolicy {
+    /// Always import/pull images, even if an image with the same tag is already loaded.
+    Always,
+    /// Only import/pull images that are not yet loaded.
+    #[default]
+    IfNotPresent,
+    /// Never pull images from a registry; they must already be loaded (local image files are still imported).
+    Never,
+}
+impl Display for PullPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use PullPolicy::*;
+        match self {
+            Always => write!(f, "always"),
+            IfNotPresent => write!(f, "if-not-present"),
+            Never => write!(f, "never"),
+        }
+    }
+}
+impl FromStr for PullPolicy {
+    type Err = PullPolicyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(Self::Always),
+            "if-not-present" => Ok(Self::IfNotPresent),
+            "never" => Ok(Self::Never),
+            raw => Err(PullPolicyParseError::Unknown { raw: raw.into() }),
+        }
+    }
+}
+
 
 
 /// Defines a collection of options to pass to the `start`-subcommand handler.
@@ -291,6 +465,12 @@ pub struct StartOpts {
     pub skip_import: bool,
     /// If given, mounts the given profile directory to examine profiling results conveniently.
     pub profile_dir: Option<PathBuf>,
+    /// If given, verifies every loaded image against the digests in this manifest (a YAML map of image names to digests).
+    pub digests:     Option<PathBuf>,
+    /// When to import/pull the service images.
+    pub pull_policy: PullPolicy,
+    /// If true, only resolves everything and prints the Docker Compose command instead of loading images and running it.
+    pub dry_run:     bool,
 }
 
 /// Defines a collection of options to pass to the `logs`-subcommand handler.
@@ -15,14 +15,52 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use brane_cfg::info::Info as _;
 use brane_cfg::node::{NodeConfig, NodeKind};
+use console::style;
 use log::{debug, info};
 
 pub use crate::errors::UnpackError as Error;
+use crate::overrides;
 use crate::spec::ResolvableNodeKind;
 
 
+/***** HELPER FUNCTIONS *****/
+/// Resolves the node kind to unpack for, reading it from the `node.yml` file if necessary.
+///
+/// # Arguments
+/// - `kind`: The NodeKind given by the user, if any.
+/// - `node_config_path`: The path to the `node.yml` file.
+///
+/// # Errors
+/// This function errors if we had to read the `node.yml` file but failed to do so.
+fn resolve_kind(kind: ResolvableNodeKind, node_config_path: &Path) -> Result<NodeKind, Error> {
+    match kind.0 {
+        Some(kind) => Ok(kind),
+        None => {
+            debug!("Resolving node kind using '{}'...", node_config_path.display());
+
+            // Load the node config file to resolve the kind
+            let node_config: NodeConfig = overrides::load_node_config(node_config_path).map_err(|source| Error::NodeConfigError { source })?;
+
+            // Return the kind
+            Ok(node_config.node.kind())
+        },
+    }
+}
+
+/// Returns the Docker Compose file embedded for the given node kind.
+fn compose_file(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Central => include_str!("../../docker-compose-central.yml"),
+        NodeKind::Worker => include_str!("../../docker-compose-worker.yml"),
+        NodeKind::Proxy => include_str!("../../docker-compose-proxy.yml"),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Unpacks the target Docker Compose file that we embedded in this executable.
 ///
@@ -40,18 +78,7 @@ pub fn compose(kind: ResolvableNodeKind, fix_dirs: bool, path: impl AsRef<Path>,
     info!("Extracting Docker Compose file for '{}' to '{}'", kind, path.display());
 
     // Resolve the kind, if necessary
-    let kind: NodeKind = match kind.0 {
-        Some(kind) => kind,
-        None => {
-            debug!("Resolving node kind using '{}'...", node_config_path.display());
-
-            // Load the node config file to resolve the kind
-            let node_config: NodeConfig = NodeConfig::from_path(node_config_path).map_err(|source| Error::NodeConfigError { source })?;
-
-            // Return the kind
-            node_config.node.kind()
-        },
-    };
+    let kind: NodeKind = resolve_kind(kind, node_config_path)?;
 
     // Resolve the path
     let path: PathBuf = path.to_string_lossy().replace("$NODE", &kind.to_string()).into();
@@ -76,16 +103,53 @@ pub fn compose(kind: ResolvableNodeKind, fix_dirs: bool, path: impl AsRef<Path>,
         }
     }
 
-    // Get the correct file
-    let compose: &str = match kind {
-        NodeKind::Central => include_str!("../../docker-compose-central.yml"),
-        NodeKind::Worker => include_str!("../../docker-compose-worker.yml"),
-        NodeKind::Proxy => include_str!("../../docker-compose-proxy.yml"),
-    };
-
-    // Attempt to write it
+    // Attempt to write the correct file
     debug!("Writing file to '{}'...", path.display());
-    fs::write(&path, compose).map_err(|source| Error::FileWriteError { what: "Docker Compose", path, source })?;
+    fs::write(&path, compose_file(kind)).map_err(|source| Error::FileWriteError { what: "Docker Compose", path, source })?;
+
+    // OK, done
+    Ok(())
+}
+
+/// Lists which embedded Docker Compose file would be unpacked where, without touching the filesystem.
+///
+/// # Arguments
+/// - `kind`: The NodeKind that determines the specific file to unpack to.
+/// - `fix_dirs`: Whether missing directories would be fixed.
+/// - `path`: The path the new file would be written to.
+/// - `node_config_path`: The path to the `node.yml` file.
+///
+/// # Errors
+/// This function errors if we failed to read the `node.yml` file.
+pub fn list_compose(kind: ResolvableNodeKind, fix_dirs: bool, path: impl AsRef<Path>, node_config_path: impl AsRef<Path>) -> Result<(), Error> {
+    let path: &Path = path.as_ref();
+    let node_config_path: &Path = node_config_path.as_ref();
+
+    // Resolve the kind and path like we would when unpacking
+    let kind: NodeKind = resolve_kind(kind, node_config_path)?;
+    let path: PathBuf = path.to_string_lossy().replace("$NODE", &kind.to_string()).into();
+
+    // Report what would happen
+    println!(
+        "Would write embedded Docker Compose file for {} nodes ({} bytes) to {}",
+        style(kind).bold().green(),
+        compose_file(kind).len(),
+        style(path.display()).bold()
+    );
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        if !parent.exists() {
+            if fix_dirs {
+                println!(" - Would create missing directory {}", style(parent.display()).bold());
+            } else {
+                println!(" - Target directory {} does not exist (use '--fix-dirs' to create it)", style(parent.display()).bold());
+            }
+        } else if !parent.is_dir() {
+            println!(" - Target directory {} is not a directory", style(parent.display()).bold());
+        }
+    }
+    if path.exists() {
+        println!(" - Would overwrite existing file {}", style(path.display()).bold());
+    }
 
     // OK, done
     Ok(())
@@ -28,7 +28,7 @@ use brane_tsk::spec::AppId;
 use dashmap::DashMap;
 use enum_debug::EnumDebug as _;
 use error_trace::{ErrorTrace as _, trace};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use specifications::driving::{CheckReply, CheckRequest, CreateSessionReply, CreateSessionRequest, DriverService, ExecuteReply, ExecuteRequest};
 use specifications::profiling::ProfileReport;
 use tokio::sync::mpsc;
@@ -223,6 +223,7 @@ impl DriverService for DriverHandler {
                         who: Some(domain),
                         reasons,
                         profile: serde_json::to_string(report.scope()).ok(),
+                        plan: None,
                     }));
                 },
                 Err(err) => {
@@ -275,12 +276,19 @@ impl DriverService for DriverHandler {
         }
         req_join.stop();
 
-        // Send back the verdict to the user!
+        // Send back the verdict to the user, together with the plan the checkers judged
         info!("Checkers verdict for workflow '{}' is {}", workflow.id, if result.is_none() { "ALLOW" } else { "DENY" });
+        let plan: Option<String> = match serde_json::to_string(&workflow) {
+            Ok(plan) => Some(plan),
+            Err(err) => {
+                warn!("{}", trace!(("Failed to serialize planned workflow '{}'", workflow.id), err));
+                None
+            },
+        };
         if let Some((who, reasons)) = result {
-            Ok(Response::new(CheckReply { verdict: false, who: Some(who), reasons, profile: serde_json::to_string(report.scope()).ok() }))
+            Ok(Response::new(CheckReply { verdict: false, who: Some(who), reasons, profile: serde_json::to_string(report.scope()).ok(), plan }))
         } else {
-            Ok(Response::new(CheckReply { verdict: true, who: None, reasons: vec![], profile: serde_json::to_string(report.scope()).ok() }))
+            Ok(Response::new(CheckReply { verdict: true, who: None, reasons: vec![], profile: serde_json::to_string(report.scope()).ok(), plan }))
         }
     }
 
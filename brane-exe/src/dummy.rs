@@ -332,6 +332,8 @@ impl DummyPlanner {
 pub struct DummyVm {
     /// The internal state of the VM in between runs.
     state: RunState<DummyState>,
+    /// Whether to write the buffered text to stderr instead of stdout.
+    stdout_to_stderr: bool,
 }
 
 impl DummyVm {
@@ -340,13 +342,25 @@ impl DummyVm {
     /// # Returns
     /// A new instance of a DummyVm.
     #[inline]
-    pub fn new() -> Self {
+    pub fn new() -> Self { Self::with_stdout_to_stderr(false) }
+
+    /// Constructor for the DummyVm that initializes it to an never-used-before-but-ready-for-everything VM, optionally writing what the workflow
+    /// writes to stdout to stderr instead.
+    ///
+    /// # Arguments
+    /// - `stdout_to_stderr`: Whether to write the output of the workflow to stderr instead of stdout (e.g., because stdout is reserved for
+    ///   machine-readable output).
+    ///
+    /// # Returns
+    /// A new instance of a DummyVm.
+    pub fn with_stdout_to_stderr(stdout_to_stderr: bool) -> Self {
         Self {
             state: Self::new_state(DummyState {
                 workflow: None,
                 text:     Arc::new(Mutex::new(String::new())),
                 results:  Arc::new(Mutex::new(HashMap::new())),
             }),
+            stdout_to_stderr,
         }
     }
 
@@ -403,12 +417,16 @@ impl DummyVm {
     /// Prints the buffered text, clearing it again.
     ///
     /// # Returns
-    /// Nothing, but does print to stdout.
+    /// Nothing, but does print to stdout (or stderr, if the VM was created with [`DummyVm::with_stdout_to_stderr()`]).
     pub fn flush_stdout(&self) {
         // Fetch the global state if there is one
         let state: RwLockWriteGuard<DummyState> = self.state.global.write().unwrap();
         let mut text: MutexGuard<String> = state.text.lock().unwrap();
-        print!("{text}");
+        if self.stdout_to_stderr {
+            eprint!("{text}");
+        } else {
+            print!("{text}");
+        }
         *text = String::new();
     }
 }
@@ -244,6 +244,14 @@ pub enum VmError {
     /// The given input (dataset, result) was not yet planned at the time of execution.
     #[error("{} '{}' as input for task '{}' is not yet planned", name.variant(), name.name(), task)]
     UnplannedInput { pc: ProgramCounter, task: String, name: DataName },
+    /// The given input pattern matched more than one intermediate result, while only one was expected.
+    #[error(
+        "Intermediate result pattern '{}' as input for task '{}' is ambiguous; it matches {}",
+        name.name(),
+        task,
+        matches.iter().map(|m| format!("'{}'", m.name())).collect::<Vec<String>>().join(", ")
+    )]
+    AmbiguousInput { pc: ProgramCounter, task: String, name: DataName, matches: Vec<DataName> },
     /// Attempted to call a function but the framestack thought otherwise.
     #[error("Failed to push to frame stack")]
     FrameStackPushError { pc: ProgramCounter, source: FrameStackError },
@@ -311,6 +319,7 @@ impl VmError {
             UnresolvedLocation { pc, .. } => prettyprint_err(*pc, self),
             UnknownInput { pc, .. } => prettyprint_err(*pc, self),
             UnplannedInput { pc, .. } => prettyprint_err(*pc, self),
+            AmbiguousInput { pc, .. } => prettyprint_err(*pc, self),
             // UnavailableDataset{ pc, .. }  => prettyprint_err(*pc, self),
             FrameStackPushError { pc, .. } => prettyprint_err(*pc, self),
             FrameStackPopError { pc, .. } => prettyprint_err(*pc, self),
@@ -215,16 +215,20 @@ async fn preprocess_value<'p: 'async_recursion, P: VmPlugin>(
         },
     };
 
-    // Fetch it from the input
-    let avail: AvailabilityKind = match input.get(&name) {
-        Some(avail) => match avail {
-            Some(avail) => avail.clone(),
-            None => {
-                return Err(Error::UnplannedInput { pc, task: task.name().into(), name });
-            },
+    // Fetch it from the input (resolving it to the result it matches if it is a pattern)
+    let (name, avail): (DataName, &Option<AvailabilityKind>) = match name.resolve_in(input) {
+        Ok(Some((name, avail))) => (name.clone(), avail),
+        Ok(None) => {
+            return Err(Error::UnknownInput { pc, task: task.name().into(), name });
+        },
+        Err(matches) => {
+            return Err(Error::AmbiguousInput { pc, task: task.name().into(), name, matches });
         },
+    };
+    let avail: AvailabilityKind = match avail {
+        Some(avail) => avail.clone(),
         None => {
-            return Err(Error::UnknownInput { pc, task: task.name().into(), name });
+            return Err(Error::UnplannedInput { pc, task: task.name().into(), name });
         },
     };
 
@@ -39,6 +39,7 @@ use brane_prx::client::ProxyClient;
 use brane_prx::spec::NewPathRequestTlsOptions;
 use brane_shr::formatters::BlockFormatter;
 use brane_shr::fs::{copy_dir_recursively_async, unarchive_async};
+use brane_shr::utilities::closest_names;
 use brane_tsk::caches::DomainRegistryCache;
 use brane_tsk::docker::{self, ClientVersion, DockerOptions, ExecuteInfo, ImageSource, Network};
 use brane_tsk::errors::{AuthorizeError, CommitError, ExecuteError, PreprocessError};
@@ -1306,7 +1307,9 @@ async fn execute_task(
     let info: &PackageInfo = match index.get(&tinfo.package_name, Some(&tinfo.package_version)) {
         Some(info) => info,
         None => {
-            return err!(tx, ExecuteError::UnknownPackage { name: tinfo.package_name.clone(), version: tinfo.package_version });
+            let suggestions: Vec<String> =
+                if index.latest.contains_key(&tinfo.package_name) { vec![] } else { closest_names(&tinfo.package_name, index.latest.keys()) };
+            return err!(tx, ExecuteError::UnknownPackage { name: tinfo.package_name.clone(), version: tinfo.package_version, suggestions });
         },
     };
     idx.stop();
@@ -1373,7 +1376,7 @@ async fn execute_task(
         Credentials::Local { path, version } => {
             // Prepare the DockerInfo
             let dinfo: DockerOptions = DockerOptions {
-                socket:  path.unwrap_or_else(|| PathBuf::from("/var/run/docker.sock")),
+                host:    path.unwrap_or_else(|| PathBuf::from("/var/run/docker.sock")).into(),
                 version: ClientVersion(
                     version
                         .map(|(major, minor)| bollard::ClientVersion { major_version: major, minor_version: minor })
@@ -1585,6 +1588,7 @@ async fn commit_result(
             created: Utc::now(),
 
             access: AccessKind::File { path: dir.join("data") },
+            size:   None,
         };
 
         // Now write that
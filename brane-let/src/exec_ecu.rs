@@ -247,7 +247,10 @@ fn start(
     debug!("Using environment variables:\n{:#?}", envs);
     let envs: Vec<_> = envs.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
 
-    // Finally, prepare the subprocess
+    // Finally, prepare the subprocess; the default arguments are passed as-is (i.e., without a shell), so need no escaping
+    if let Some(args) = &container_info.entrypoint.args {
+        exec_command.args(args);
+    }
     exec_command.args(&command.args);
     exec_command.envs(envs);
     exec_command.stdout(Stdio::piped());
@@ -28,8 +28,10 @@ use brane_cfg::info::Info as _;
 use brane_cfg::infra::{InfraFile, InfraLocation};
 use brane_cfg::node::{CentralConfig, NodeConfig};
 use brane_prx::client::ProxyClient;
+use brane_shr::utilities::closest_names;
 use brane_tsk::api::get_data_index;
 use brane_tsk::errors::PlanError;
+use brane_tsk::tools::expand_result_patterns;
 use error_trace::trace;
 use log::{debug, error, info};
 use rand::prelude::IteratorRandom;
@@ -85,6 +87,57 @@ macro_rules! err_response {
 
 
 /***** HELPER FUNCTIONS *****/
+/// Fetches the capabilities supported by the given location from the API service.
+///
+/// # Arguments
+/// - `api_addr`: The address of the API service to query.
+/// - `location`: The location to fetch the capabilities of.
+///
+/// # Returns
+/// The set of [`Capability`]s supported by `location`.
+///
+/// # Errors
+/// This function errors if we failed to send the request, or if the API service responded with a failure or garbage.
+async fn get_capabilities(api_addr: &Address, location: &str) -> Result<HashSet<Capability>, PlanError> {
+    let address: String = format!("{api_addr}/infra/capabilities/{location}");
+    let res: reqwest::Response = reqwest::get(&address).await.map_err(|source| PlanError::RequestError { address: address.clone(), source })?;
+    if !res.status().is_success() {
+        return Err(PlanError::RequestFailure { address, code: res.status(), err: res.text().await.ok() });
+    }
+    let capabilities: String = res.text().await.map_err(|source| PlanError::RequestBodyError { address: address.clone(), source })?;
+    serde_json::from_str(&capabilities).map_err(|source| PlanError::RequestParseError { address: address.clone(), raw: capabilities, source })
+}
+
+/// Finds all locations in the infrastructure that support the given capabilities.
+///
+/// This is used to give the user suggestions when a task is planned on a location that does not support it. As such, it is best-effort: locations
+/// for which we fail to fetch the capabilities are silently skipped.
+///
+/// # Arguments
+/// - `api_addr`: The address of the API service to query.
+/// - `infra`: The [`InfraFile`] listing all locations.
+/// - `requirements`: The capabilities that the locations must support.
+///
+/// # Returns
+/// The (sorted) names of the locations supporting all of `requirements`.
+async fn find_capable_locations(api_addr: &Address, infra: &InfraFile, requirements: &HashSet<Capability>) -> Vec<String> {
+    let mut candidates: Vec<String> = Vec::with_capacity(infra.len());
+    for (name, _) in infra.iter() {
+        match get_capabilities(api_addr, name).await {
+            Ok(capabilities) => {
+                if capabilities.is_superset(requirements) {
+                    candidates.push(name.clone());
+                }
+            },
+            Err(err) => {
+                debug!("Not considering location '{}' as a candidate: {}", name, trace!(("Failed to get capabilities"), err));
+            },
+        }
+    }
+    candidates.sort();
+    candidates
+}
+
 /// Helper function that plans the given list of edges.
 ///
 /// # Arguments
@@ -142,7 +195,10 @@ async fn plan_edges(
                                 // Simply add all locations where it lives
                                 data_locs.append(&mut info.access.keys().collect::<Vec<&String>>());
                             } else {
-                                return Err(PlanError::UnknownDataset { name: name.clone() });
+                                return Err(PlanError::UnknownDataset {
+                                    name: name.clone(),
+                                    suggestions: closest_names(name, dindex.iter().map(|info| &info.name)),
+                                });
                             }
                         }
                     }
@@ -160,27 +216,17 @@ async fn plan_edges(
                 let location: &str = &locs.restricted()[0];
 
                 // Fetch the list of capabilities supported by the planned location
-                let address: String = format!("{api_addr}/infra/capabilities/{location}");
-                let res: reqwest::Response =
-                    reqwest::get(&address).await.map_err(|source| PlanError::RequestError { address: address.clone(), source })?;
-                if !res.status().is_success() {
-                    return Err(PlanError::RequestFailure { address, code: res.status(), err: res.text().await.ok() });
-                }
-                let capabilities: String = res.text().await.map_err(|source| PlanError::RequestBodyError { address: address.clone(), source })?;
-                let capabilities: HashSet<Capability> = serde_json::from_str(&capabilities).map_err(|source| PlanError::RequestParseError {
-                    address: address.clone(),
-                    raw: capabilities,
-                    source,
-                })?;
+                let capabilities: HashSet<Capability> = get_capabilities(api_addr, location).await?;
 
                 // Assert that this is what we need
                 if let TaskDef::Compute(ComputeTaskDef { function, requirements, .. }) = &table.tasks[*task] {
                     if !capabilities.is_superset(requirements) {
                         return Err(PlanError::UnsupportedCapabilities {
-                            task:     function.name.clone(),
-                            loc:      location.into(),
+                            task: function.name.clone(),
+                            loc: location.into(),
                             expected: requirements.clone(),
-                            got:      capabilities,
+                            got: capabilities,
+                            candidates: find_capable_locations(api_addr, infra, requirements).await,
                         });
                     }
                 } else {
@@ -191,7 +237,9 @@ async fn plan_edges(
                 *at = Some(location.into());
                 debug!("Task '{}' planned at '{}'", table.tasks[*task].name(), location);
 
-                // For all dataset/intermediate result inputs, we check if these are available on the planned location.
+                // For all dataset/intermediate result inputs (with patterns expanded to the results they match), we check if these are available on the
+                // planned location.
+                expand_result_patterns(input, &table.results, deferred)?;
                 for (name, avail) in input {
                     match name {
                         DataName::Data(dname) => {
@@ -214,7 +262,10 @@ async fn plan_edges(
                                     });
                                 }
                             } else {
-                                return Err(PlanError::UnknownDataset { name: dname.clone() });
+                                return Err(PlanError::UnknownDataset {
+                                    name: dname.clone(),
+                                    suggestions: closest_names(dname, dindex.iter().map(|info| &info.name)),
+                                });
                             }
                         },
 
@@ -382,6 +433,7 @@ fn plan_deferred(
             // This is the node where it all revolves around, in the end
             Edge::Node { at, input, next, .. } => {
                 // This next trick involves checking if the node has any unresolved results as input, then trying to resolve them
+                expand_result_patterns(input, &table.results, false)?;
                 for (name, avail) in input {
                     // Continue if it already has a resolved availability
                     if avail.is_some() {
@@ -31,7 +31,7 @@ use brane_tsk::errors::AuthorizeError;
 use deliberation::spec::Verdict;
 use enum_debug::EnumDebug as _;
 use error_trace::{ErrorTrace as _, trace};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use reqwest::header;
 use rustls::Certificate;
 use serde::{Deserialize, Serialize};
@@ -203,6 +203,52 @@ pub async fn assert_asset_permission(
     }
 }
 
+/// Computes the total size of the files at the given path.
+///
+/// # Arguments
+/// - `path`: The path of the file or directory to compute the size of. Directories are traversed recursively, but links in them are not
+///   followed.
+///
+/// # Returns
+/// The total size of the files, in bytes.
+///
+/// # Errors
+/// This function errors if we failed to read the metadata of the path or of any of its entries.
+async fn path_size(path: &Path) -> Result<u64, std::io::Error> {
+    let mut size: u64 = 0;
+    let mut todo: Vec<PathBuf> = vec![path.into()];
+    while let Some(entry_path) = todo.pop() {
+        // Only the path itself may be a link (e.g., to a dataset elsewhere)
+        let meta = if entry_path == path { tfs::metadata(&entry_path).await? } else { tfs::symlink_metadata(&entry_path).await? };
+        if meta.is_dir() {
+            let mut entries: tfs::ReadDir = tfs::read_dir(&entry_path).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                todo.push(entry.path());
+            }
+        } else {
+            size += meta.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Sets the size advertised in the given [`AssetInfo`] to the total size of the dataset's files.
+///
+/// If the size cannot be computed, this is logged and the size is left unadvertised (which clients treat as unknown).
+///
+/// # Arguments
+/// - `info`: The [`AssetInfo`] of the dataset to advertise the size of.
+/// - `data_dir`: The directory with all the datasets of this node.
+async fn advertise_size(info: &mut AssetInfo, data_dir: &Path) {
+    let path: PathBuf = match &info.access {
+        AccessKind::File { path } => data_dir.join(&info.name).join(path),
+    };
+    match path_size(&path).await {
+        Ok(size) => info.size = Some(size),
+        Err(err) => warn!("Failed to compute the size of dataset '{}' at '{}': {} (not advertising it)", info.name, path.display(), err),
+    }
+}
+
 /***** HELPER STRUCTURES *****/
 /// Manual copy of the [policy-reasoner](https://github.com/braneframework/policy-reasoner)'s `AccessDataRequest`-struct.
 ///
@@ -261,10 +307,16 @@ pub async fn list(context: Arc<Context>) -> Result<impl Reply, Rejection> {
         node_config.node.worker().paths.data.display(),
         node_config.node.worker().paths.results.display()
     );
-    let store: Store = Store::from_dirs(&node_config.node.worker().paths.data, &node_config.node.worker().paths.results).await.map_err(|source| {
-        error!("{}", trace!(("Failed to load the store"), source));
-        warp::reject::reject()
-    })?;
+    let mut store: Store =
+        Store::from_dirs(&node_config.node.worker().paths.data, &node_config.node.worker().paths.results).await.map_err(|source| {
+            error!("{}", trace!(("Failed to load the store"), source));
+            warp::reject::reject()
+        })?;
+
+    // Tell clients how large each dataset is, so they know what they're in for before downloading
+    for info in store.datasets.values_mut() {
+        advertise_size(info, &node_config.node.worker().paths.data).await;
+    }
 
     // Simply parse to a string
     debug!("Writing list of datasets as response...");
@@ -324,17 +376,18 @@ pub async fn get(name: String, context: Arc<Context>) -> Result<impl Reply, Reje
     })?;
 
     // Attempt to resolve the name in the given store
-    let info: &AssetInfo = match store.get_data(&name) {
-        Some(info) => info,
+    let mut info: AssetInfo = match store.get_data(&name) {
+        Some(info) => info.clone(),
         None => {
             error!("Unknown dataset '{}'", name);
             return Err(warp::reject::not_found());
         },
     };
+    advertise_size(&mut info, &node_config.node.worker().paths.data).await;
 
     // Serialize it (or at least, try so)
     debug!("Dataset found, returning results");
-    let body: String = serde_json::to_string(info).map_err(|source| warp::reject::custom(Error::AssetSerializeError { name, source }))?;
+    let body: String = serde_json::to_string(&info).map_err(|source| warp::reject::custom(Error::AssetSerializeError { name, source }))?;
     let body_len: usize = body.len();
 
     // Construct a response with the body and the content-length header
@@ -481,6 +534,16 @@ pub async fn download_data(
             })?;
             arch.stop();
 
+            // Find its size, so the client knows what it's getting into
+            let tar_len: u64 = tfs::metadata(&tar_path)
+                .await
+                .map_err(|source| {
+                    let err = Error::TarMetadataError { path: tar_path.clone(), source };
+                    error!("{}", err.trace());
+                    warp::reject::custom(err)
+                })?
+                .len();
+
             // Now we send the tarball as a file in the reply
             debug!("Sending back reply with compressed archive...");
             let (mut body_sender, body): (Sender, Body) = Body::channel();
@@ -525,8 +588,10 @@ pub async fn download_data(
                 Ok::<_, Rejection>(())
             });
 
-            // We use the handle as a stream.
-            Ok(reply::with_status(Response::new(body), StatusCode::OK))
+            // We use the handle as a stream, with the content-length header set
+            let mut response = Response::new(body);
+            response.headers_mut().insert("Content-Length", HeaderValue::from(tar_len));
+            Ok(reply::with_status(response, StatusCode::OK))
         },
     }
 }
@@ -657,6 +722,16 @@ pub async fn download_result(
     })?;
     arch.stop();
 
+    // Find its size, so the client knows what it's getting into
+    let tar_len: u64 = tfs::metadata(&tar_path)
+        .await
+        .map_err(|source| {
+            let err = Error::TarMetadataError { path: tar_path.clone(), source };
+            error!("{}", err.trace());
+            warp::reject::custom(err)
+        })?
+        .len();
+
     // Now we send the tarball as a file in the reply
     debug!("Sending back reply with compressed archive...");
     let (mut body_sender, body): (Sender, Body) = Body::channel();
@@ -701,6 +776,8 @@ pub async fn download_result(
         Ok::<_, Rejection>(())
     });
 
-    // We use the handle as a stream.
-    Ok(reply::with_status(Response::new(body), StatusCode::OK))
+    // We use the handle as a stream, with the content-length header set
+    let mut response = Response::new(body);
+    response.headers_mut().insert("Content-Length", HeaderValue::from(tar_len));
+    Ok(reply::with_status(response, StatusCode::OK))
 }
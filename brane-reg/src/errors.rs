@@ -74,6 +74,9 @@ pub enum DataError {
     /// Failed to archive the given dataset.
     #[error("Failed to archive data")]
     DataArchiveError { source: brane_shr::fs::Error },
+    /// Failed to get the size of the tar file after compressing.
+    #[error("Failed to get metadata of tarball file '{}'", path.display())]
+    TarMetadataError { path: PathBuf, source: std::io::Error },
     /// Failed to re-open the tar file after compressing.
     #[error("Failed to re-open tarball file '{}'", path.display())]
     TarOpenError { path: PathBuf, source: std::io::Error },
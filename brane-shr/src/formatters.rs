@@ -177,3 +177,32 @@ where
         Ok(())
     }
 }
+
+
+
+/// Formats a "did you mean" suggestion for an unknown name, e.g., as found by [`closest_names()`](crate::utilities::closest_names).
+///
+/// Prints nothing if there are no suggestions, such that it can be appended to any "unknown name" error unconditionally.
+pub struct DidYouMeanFormatter<'a> {
+    /// The names to suggest.
+    suggestions: &'a [String],
+}
+impl<'a> DidYouMeanFormatter<'a> {
+    /// Constructor for the DidYouMeanFormatter.
+    ///
+    /// # Arguments
+    /// - `suggestions`: The names to suggest. May be empty.
+    ///
+    /// # Returns
+    /// A new instance of the DidYouMeanFormatter that implements Display.
+    #[inline]
+    pub fn new(suggestions: &'a [String]) -> Self { Self { suggestions } }
+}
+impl Display for DidYouMeanFormatter<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        if self.suggestions.is_empty() {
+            return Ok(());
+        }
+        write!(f, " (did you mean {}?)", PrettyListFormatter::new(self.suggestions.iter().map(|s| format!("'{s}'")), "or"))
+    }
+}
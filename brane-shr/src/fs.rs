@@ -18,13 +18,14 @@ use std::fs::{self, Permissions};
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
 use std::path::{Path, PathBuf};
 use std::str::FromStr as _;
+use std::time::{Duration, Instant};
 
 use async_compression::tokio::bufread::GzipDecoder;
 use async_compression::tokio::write::GzipEncoder;
 use console::{Style, style};
 use fs2::FileExt as _;
 use indicatif::{ProgressBar, ProgressStyle};
-use log::{debug, warn};
+use log::{debug, info, warn};
 use reqwest::{Client, Request, Response, StatusCode, Url};
 use sha2::{Digest as _, Sha256};
 use specifications::version::Version;
@@ -630,12 +631,55 @@ pub mod tests {
     /// Test if archiving / unarchiving works, skipping the root folder in the result _and_ having a folder that is far away to test removing the intermediate directories.
     #[tokio::test]
     async fn test_tarball_without_root_extra_path() { test_archive_unarchive("some/extra/folders/lol".into(), true).await; }
+
+
+
+    /// Test if a timed lock gives up when another lock is already held, and succeeds once that lock is released.
+    #[tokio::test]
+    async fn test_file_lock_timeout() {
+        let tempdir: TempDir = TempDir::new().unwrap();
+        let path: PathBuf = tempdir.path().join(".lock");
+        let version: Version = Version::new(1, 0, 0);
+
+        // Hold the lock, then try to acquire it again
+        let lock: FileLock = FileLock::lock("test", version, &path).unwrap();
+        assert!(FileLock::try_lock(&path).unwrap().is_none());
+        match FileLock::lock_timeout(&path, Duration::from_millis(250)).await {
+            Err(Error::FileLockTimeout { .. }) => {},
+            Err(err) => panic!("Expected a FileLockTimeout error, got '{err}'"),
+            Ok(_) => panic!("Acquired lock on '{}' while it is already held", path.display()),
+        }
+
+        // After releasing, it should succeed
+        lock.release();
+        FileLock::lock_timeout(&path, Duration::from_millis(250)).await.unwrap();
+    }
+
+    /// Test if the download progress bar follows the reported progress, switching to a bar once the total is known.
+    #[test]
+    fn test_download_progress_bar() {
+        let (prgs, mut progress) = download_progress_bar("test");
+        progress(DownloadProgress { done: 10, total: None });
+        assert_eq!((prgs.position(), prgs.length()), (10, None));
+        progress(DownloadProgress { done: 20, total: Some(100) });
+        assert_eq!((prgs.position(), prgs.length()), (20, Some(100)));
+        progress(DownloadProgress { done: 100, total: Some(100) });
+        assert_eq!((prgs.position(), prgs.length()), (100, Some(100)));
+    }
 }
 
 
 
 
 
+/***** CONSTANTS *****/
+/// The minimum time between two calls of the progress callback of [`download_file_async()`].
+pub const DOWNLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+
+
+
+
 /***** ERRORS *****/
 /// Defines the errors tha may occur when dealing with the filesystem operations.
 #[derive(thiserror::Error, Debug)]
@@ -690,6 +734,9 @@ pub enum Error {
     FileChecksumError { what: &'static str, path: PathBuf, got: String, expected: String },
     #[error("Failed to lock file '{}': {}", path.display(), err)]
     FileLockError { path: PathBuf, err: std::io::Error },
+    /// Gave up waiting for a file lock held by someone else.
+    #[error("Lock file '{}' is still held by another process after {}s; try again later", path.display(), timeout.as_secs())]
+    FileLockTimeout { path: PathBuf, timeout: Duration },
 
     /// Directory not found.
     #[error("Directory '{}' not found", path.display())]
@@ -889,6 +936,17 @@ impl Display for DownloadSecurity<'_> {
 
 
 
+/// Reports how far along a download is (see [`download_file_async()`]).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DownloadProgress {
+    /// The number of bytes downloaded so far.
+    pub done:  u64,
+    /// The total number of bytes to download, if the remote told us (through the `Content-Length` header).
+    pub total: Option<u64>,
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -950,6 +1008,68 @@ impl FileLock {
         Ok(Self { path, _handle: handle })
     }
 
+    /// Constructor for the FileLock that attempts to lock the given file once, without waiting for it.
+    ///
+    /// Note that this lock is an exclusive lock.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to use a lockfile.
+    ///
+    /// # Returns
+    /// A new instance of the FileLock that acts as a guard of the lock, or [`None`] if another process holds it.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the lock file or failed to lock it.
+    pub fn try_lock(path: impl Into<PathBuf>) -> Result<Option<Self>, Error> {
+        let path: PathBuf = path.into();
+        let handle: fs::File = fs::File::create(&path).map_err(|err| Error::FileCreateError { what: "lock file", path: path.clone(), err })?;
+        match handle.try_lock_exclusive() {
+            Ok(_) => {
+                debug!("Lock '{}' acquired", path.display());
+                Ok(Some(Self { path, _handle: handle }))
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+            Err(err) => Err(Error::FileLockError { path, err }),
+        }
+    }
+
+    /// Constructor for the FileLock that attempts to lock the given file, but gives up after the given timeout.
+    ///
+    /// Unlike [`FileLock::lock()`], this function does not block indefinitely if another process holds the lock. Instead, it polls the lock
+    /// (yielding to the runtime in between) until `timeout` has passed, and then returns an [`Error::FileLockTimeout`].
+    ///
+    /// Note that this lock is an exclusive lock.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file to use a lockfile.
+    /// - `timeout`: The maximum time to wait for the lock to become available.
+    ///
+    /// # Returns
+    /// A new instance of the FileLock that acts as a guard of the lock. As long as it's in scope, the exclusive lock will be held.
+    ///
+    /// # Errors
+    /// This function errors if we failed to create the lock file, failed to lock it or if the lock was not released within `timeout`.
+    pub async fn lock_timeout(path: impl Into<PathBuf>, timeout: Duration) -> Result<Self, Error> {
+        let path: PathBuf = path.into();
+
+        // Poll the lock until we get it or run out of time
+        let start: Instant = Instant::now();
+        let mut waiting: bool = false;
+        loop {
+            if let Some(lock) = Self::try_lock(&path)? {
+                return Ok(lock);
+            }
+            if start.elapsed() >= timeout {
+                return Err(Error::FileLockTimeout { path, timeout });
+            }
+            if !waiting {
+                info!("Lock file '{}' is held by another process; waiting up to {}s for it to be released...", path.display(), timeout.as_secs());
+                waiting = true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
     /// Releases this file lock.
     ///
     /// This works by simply consuming ourself, forcing a drop.
@@ -1246,6 +1366,33 @@ pub async fn copy_dir_recursively_async(source: impl AsRef<Path>, target: impl A
 
 
 
+/// Creates a progress bar that follows a download, together with a callback for [`download_file_async()`] that updates it.
+///
+/// The bar starts as a spinner and becomes a proper bar once the size of the download is known. Like any [`ProgressBar`], it is not drawn if
+/// stderr is not a terminal.
+///
+/// # Arguments
+/// - `what`: A short description of what is being downloaded, shown before the bar.
+///
+/// # Returns
+/// The [`ProgressBar`] (to finish once the download is done) and the callback to pass to [`download_file_async()`].
+pub fn download_progress_bar(what: impl Into<String>) -> (ProgressBar, impl FnMut(DownloadProgress) + Send) {
+    let prgs: ProgressBar = ProgressBar::new_spinner()
+        .with_style(ProgressStyle::with_template("{msg} {elapsed_precise} {bytes} {binary_bytes_per_sec}").unwrap())
+        .with_message(what.into());
+    let bar: ProgressBar = prgs.clone();
+    let mut sized: bool = false;
+    let callback = move |progress: DownloadProgress| {
+        if let (false, Some(total)) = (sized, progress.total) {
+            bar.set_length(total);
+            bar.set_style(ProgressStyle::with_template("{msg} {bar:60} {bytes}/{total_bytes} {bytes_per_sec} ETA {eta_precise}").unwrap());
+            sized = true;
+        }
+        bar.set_position(progress.done);
+    };
+    (prgs, callback)
+}
+
 /// Downloads some file from the interwebs to the given location.
 ///
 /// # Arguments
@@ -1253,6 +1400,8 @@ pub async fn copy_dir_recursively_async(source: impl AsRef<Path>, target: impl A
 /// - `target`: The location to download the file to.
 /// - `verification`: Some method to verify the file is what we think it is. See the `VerifyMethod`-enum for more information.
 /// - `verbose`: If not `None`, will print to the output with accents given in the given `Style` (use a non-exciting Style to print without styles).
+/// - `progress`: If not `None`, is called with the [`DownloadProgress`] at most every [`DOWNLOAD_PROGRESS_INTERVAL`] while downloading, and
+///   once more when the download completes.
 ///
 /// # Returns
 /// Nothing, except that when it does you can assume a file exists at the given location.
@@ -1264,6 +1413,7 @@ pub async fn download_file_async(
     target: impl AsRef<Path>,
     security: DownloadSecurity<'_>,
     verbose: Option<Style>,
+    mut progress: Option<&mut (dyn FnMut(DownloadProgress) + Send)>,
 ) -> Result<(), Error> {
     let source: &str = source.as_ref();
     let target: &Path = target.as_ref();
@@ -1367,6 +1517,8 @@ pub async fn download_file_async(
     let mut hasher: Option<Sha256> = if security.checksum.is_some() { Some(Sha256::new()) } else { None };
 
     // Download the response to the opened output file
+    let mut done: u64 = 0;
+    let mut last_report: Instant = Instant::now();
     let mut stream = res.bytes_stream();
     while let Some(next) = stream.next().await {
         // Unwrap the result
@@ -1388,13 +1540,23 @@ pub async fn download_file_async(
         }
 
         // Update what we've written if needed
+        done += next.len() as u64;
         if let Some(prgs) = &prgs {
-            prgs.update(|state| state.set_pos(state.pos() + next.len() as u64));
+            prgs.set_position(done);
+        }
+        if let Some(progress) = &mut progress {
+            if last_report.elapsed() >= DOWNLOAD_PROGRESS_INTERVAL {
+                progress(DownloadProgress { done, total: len });
+                last_report = Instant::now();
+            }
         }
     }
     if let Some(prgs) = &prgs {
         prgs.finish_and_clear();
     }
+    if let Some(progress) = &mut progress {
+        progress(DownloadProgress { done, total: len });
+    }
 
     // Assert the checksums are the same if we're doing that
     if let Some(checksum) = security.checksum {
@@ -1528,8 +1690,33 @@ pub async fn archive_async(source: impl AsRef<Path>, tarball: impl AsRef<Path>,
 /// This function errors if we failed to read or write anything or if some directories do or do not exist.
 pub async fn unarchive_async(tarball: impl AsRef<Path>, target: impl AsRef<Path>) -> Result<(), Error> {
     let tarball: &Path = tarball.as_ref();
+
+    // Open the source tarfile
+    let handle: tfs::File = match tfs::File::open(tarball).await {
+        Ok(handle) => handle,
+        Err(err) => {
+            return Err(Error::FileOpenError { what: "source tarball", path: tarball.into(), err });
+        },
+    };
+
+    // Extract it as a stream
+    unarchive_reader_async(tarball, tio::BufReader::new(handle), target).await
+}
+
+/// Unarchives a `.tar.gz` stream to the given location, without requiring the archive to be on disk first.
+///
+/// # Arguments
+/// - `source`: Some path-like identifier of where the stream comes from (e.g., the tarball or an address). Only used for debugging and errors.
+/// - `reader`: The reader that produces the (gzip-compressed) tarball.
+/// - `target`: The target directory to write to. Note that we will throw all sorts of nasty errors if it already exists somehow.
+///
+/// # Errors
+/// This function errors if we failed to read or write anything or if some directories do or do not exist. Note that anything already
+/// extracted is left as-is; it is up to the caller to clean `target` if that's undesired.
+pub async fn unarchive_reader_async(source: impl AsRef<Path>, reader: impl tio::AsyncBufRead + Unpin, target: impl AsRef<Path>) -> Result<(), Error> {
+    let source: &Path = source.as_ref();
     let target: &Path = target.as_ref();
-    debug!("Extracting '{}' to '{}'...", tarball.display(), target.display());
+    debug!("Extracting '{}' to '{}'...", source.display(), target.display());
 
     // Whine if the target already exists
     if target.exists() {
@@ -1539,21 +1726,13 @@ pub async fn unarchive_async(tarball: impl AsRef<Path>, target: impl AsRef<Path>
         return Err(Error::DirCreateError { what: "target", path: target.into(), err });
     }
 
-    // Open the source tarfile
-    let handle: tfs::File = match tfs::File::open(tarball).await {
-        Ok(handle) => handle,
-        Err(err) => {
-            return Err(Error::FileOpenError { what: "source tarball", path: tarball.into(), err });
-        },
-    };
-
-    // Create the decoder & tarfile around this file
-    let dec: GzipDecoder<_> = GzipDecoder::new(tio::BufReader::new(handle));
+    // Create the decoder & tarfile around this reader
+    let dec: GzipDecoder<_> = GzipDecoder::new(reader);
     let mut tar: Archive<GzipDecoder<_>> = Archive::new(dec);
     let mut entries: Entries<GzipDecoder<_>> = match tar.entries() {
         Ok(entries) => entries,
         Err(err) => {
-            return Err(Error::TarEntriesError { path: tarball.into(), err });
+            return Err(Error::TarEntriesError { path: source.into(), err });
         },
     };
 
@@ -1565,7 +1744,7 @@ pub async fn unarchive_async(tarball: impl AsRef<Path>, target: impl AsRef<Path>
         let mut entry: Entry<Archive<_>> = match entry {
             Ok(entry) => entry,
             Err(err) => {
-                return Err(Error::TarEntryError { path: tarball.into(), entry: i, err });
+                return Err(Error::TarEntryError { path: source.into(), entry: i, err });
             },
         };
         i += 1;
@@ -1574,20 +1753,20 @@ pub async fn unarchive_async(tarball: impl AsRef<Path>, target: impl AsRef<Path>
         let entry_path: PathBuf = match entry.path() {
             Ok(entry_path) => entry_path.into(),
             Err(err) => {
-                return Err(Error::TarEntryPathError { path: tarball.into(), entry: i, err });
+                return Err(Error::TarEntryPathError { path: source.into(), entry: i, err });
             },
         };
 
         // Unpack the thing
         let target_path: PathBuf = target.join(&entry_path);
-        debug!("Extracting '{}/{}' to '{}'...", tarball.display(), entry_path.display(), target_path.display());
+        debug!("Extracting '{}/{}' to '{}'...", source.display(), entry_path.display(), target_path.display());
         match entry.unpack_in(&target).await {
             Ok(true) => {},
             Ok(false) => {
                 return Err(Error::PathWithParentDir { what: "nested target", path: target_path });
             },
             Err(err) => {
-                return Err(Error::TarExtractError { tarball: tarball.into(), entry: entry_path, target: target_path, err });
+                return Err(Error::TarExtractError { tarball: source.into(), entry: entry_path, target: target_path, err });
             },
         }
 
@@ -478,11 +478,83 @@ pub fn is_ip_addr(address: impl AsRef<str>) -> bool {
 
 
 
+/***** SUGGESTIONS *****/
+/// Computes the edit (Levenshtein) distance between two strings, i.e., the number of single-character insertions, deletions or
+/// substitutions needed to turn one into the other.
+///
+/// # Arguments
+/// - `lhs`: The first string.
+/// - `rhs`: The second string.
+///
+/// # Returns
+/// The edit distance between `lhs` and `rhs`.
+pub fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+    let mut prev: Vec<usize> = (0..=rhs.len()).collect();
+    let mut curr: Vec<usize> = vec![0; rhs.len() + 1];
+    for (i, l) in lhs.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, r) in rhs.iter().enumerate() {
+            let subst: usize = prev[j] + usize::from(l != *r);
+            curr[j + 1] = subst.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[rhs.len()]
+}
+
+/// Finds the known names that are closest to an unknown one, for use in "did you mean" suggestions.
+///
+/// Only names within an edit distance of a third of the unknown name's length (but at least 1) are considered, such that we don't suggest
+/// anything if nothing is reasonably close.
+///
+/// # Arguments
+/// - `name`: The unknown name.
+/// - `known`: The names that do exist.
+///
+/// # Returns
+/// At most three of the closest `known` names, ordered by distance (and then alphabetically).
+pub fn closest_names<S: AsRef<str>>(name: &str, known: impl IntoIterator<Item = S>) -> Vec<String> {
+    let max_distance: usize = (name.chars().count() / 3).max(1);
+    let mut candidates: Vec<(usize, String)> = known
+        .into_iter()
+        .filter_map(|known| {
+            let known: &str = known.as_ref();
+            let distance: usize = edit_distance(&name.to_lowercase(), &known.to_lowercase());
+            if known != name && distance <= max_distance { Some((distance, known.to_string())) } else { None }
+        })
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    candidates.into_iter().take(3).map(|(_, known)| known).collect()
+}
+
+
+
+
+
 /***** TESTS *****/
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("hello_world", "hello_wrold"), 2);
+        assert_eq!(edit_distance("abc", ""), 3);
+    }
+
+    #[test]
+    fn test_closest_names() {
+        let known = ["test_data", "testdata", "text_data", "other"];
+        assert_eq!(closest_names("test_dta", known), vec!["test_data".to_string(), "testdata".to_string(), "text_data".to_string()]);
+        assert_eq!(closest_names("Other", known), vec!["other".to_string()]);
+        assert!(closest_names("completely_different", known).is_empty());
+        assert!(closest_names("other", known).is_empty());
+    }
+
     /// Test some basic HTTP schemas
     #[test]
     fn ensurehttpschema_noschema_added() {
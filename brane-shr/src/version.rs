@@ -222,8 +222,6 @@ pub enum ParseError {
     /// Could not parse the patch version number
     PatchParseError{ raw: String, err: std::num::ParseIntError },
 
-    /// Got a NAME:VERSION pair with too many colons
-    TooManyColons{ raw: String, got: usize },
     /// Could not parse the Version in a given NAME:VERSION pair.
     IllegalVersion{ raw: String, raw_version: String, err: Box<Self> },
 }
@@ -237,7 +235,6 @@ impl Display for ParseError {
             MinorParseError{ raw, err } => write!(f, "Could not parse minor version number '{raw}': {err}"),
             PatchParseError{ raw, err } => write!(f, "Could not parse patch version number '{raw}': {err}"),
 
-            TooManyColons{ raw, got }               => write!(f, "Given 'NAME[:VERSION]' pair '{raw}' has too many colons (got {got}, expected at most 1)"),
             IllegalVersion{ raw, raw_version, err } => write!(f, "Could not parse version '{raw_version}' in '{raw}': {err}"),
         }
     }
@@ -318,40 +315,30 @@ impl Version {
 
     /// Special factory method that creates a package name and a version from a `NAME[:VERSION]` pair.
     /// 
-    /// If the `VERSION` is omitted, returns `Version::latest()`.
+    /// If the `VERSION` is omitted or empty (i.e., `NAME` or `NAME:`), returns `Version::latest()`. The pair is split on the _last_ colon,
+    /// so names that contain colons themselves can still be given as long as a `:VERSION` (or a trailing colon) follows.
     /// 
     /// # Arguments
     /// - `package`: The package `NAME[:VERSION]` pair to parse.
     /// 
     /// # Errors
-    /// This function may error if parsing failed, somehow.
+    /// This function may error if the version part is not a valid version.
     pub fn from_package_pair(package: &str) -> Result<(String, Self), ParseError> {
-        // Get the number of colons in the string
-        let colons: usize = package.matches(':').count();
-
-        // Switch on version present or not
-        if colons == 0 {
-            // Simply return the name with the latest version
-            Ok((package.into(), Self::latest()))
-
-        } else if colons == 1 {
-            // Split on the colon
-            let colon_pos     = package.find(':').unwrap();
-            let name: &str    = &package[..colon_pos];
-            let version: &str = &package[colon_pos + 1..];
-
-            // Attempt to parse the Version
-            let version: Self = match Self::from_str(version) {
-                Ok(version) => version,
-                Err(err)    => { return Err(ParseError::IllegalVersion{ raw: package.into(), raw_version: version.into(), err: Box::new(err) }); },
-            };
+        // Split on the last colon, if any
+        let (name, version): (&str, &str) = match package.rsplit_once(':') {
+            Some((name, version)) => (name, version),
+            None                  => { return Ok((package.into(), Self::latest())); },
+        };
+        if version.is_empty() { return Ok((name.into(), Self::latest())); }
 
-            // Return them as a pair
-            Ok((name.to_string(), version))
+        // Attempt to parse the Version
+        let version: Self = match Self::from_str(version) {
+            Ok(version) => version,
+            Err(err)    => { return Err(ParseError::IllegalVersion{ raw: package.into(), raw_version: version.into(), err: Box::new(err) }); },
+        };
 
-        } else {
-            Err(ParseError::TooManyColons{ raw: package.into(), got: colons })
-        }
+        // Return them as a pair
+        Ok((name.to_string(), version))
     }
 
 
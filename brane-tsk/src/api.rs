@@ -47,7 +47,23 @@ pub type DateTimeUtc = DateTime<Utc>;
 ///
 /// # Errors
 /// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
-pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> {
+#[inline]
+pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> { get_package_index_with(&Client::new(), endpoint).await }
+
+/// Downloads the current package index from the Brane API service using the given client.
+///
+/// Use this over [`get_package_index()`] to control how the request is sent (e.g., its TLS configuration or headers).
+///
+/// # Arguments
+/// - `client`: The [`Client`] with which to send the request.
+/// - `endpoint`: The endpoint to send the request to.
+///
+/// # Returns
+/// The PackageIndex that represents the packages currently known to the instance at the time of the call.
+///
+/// # Errors
+/// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
+pub async fn get_package_index_with(client: &Client, endpoint: impl AsRef<str>) -> Result<PackageIndex, Error> {
     // Load up the query
     #[derive(GraphQLQuery)]
     #[graphql(schema_path = "graphql/api_schema.json", query_path = "graphql/get_packages.graphql", response_derives = "Debug")]
@@ -56,14 +72,18 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
     // Resolve &str-like to &str
     let endpoint: &str = endpoint.as_ref();
 
-    // Start preparing the client to send the GraphQL request
-    let client = Client::new();
+    // Prepare the GraphQL request
     let variables = get_packages::Variables {};
     let graphql_query = GetPackages::build_query(variables);
 
     // Request/response for GraphQL query.
-    let graphql_response: reqwest::Response =
-        client.post(endpoint).json(&graphql_query).send().await.map_err(|source| Error::RequestError { address: endpoint.into(), source })?;
+    let graphql_response: reqwest::Response = client
+        .post(endpoint)
+        .json(&graphql_query)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|source| Error::RequestError { address: endpoint.into(), source })?;
     let body: String = graphql_response.text().await.map_err(|source| Error::ResponseBodyError { address: endpoint.into(), source })?;
     let graphql_response: Response<get_packages::ResponseData> =
         serde_json::from_str(&body).map_err(|source| Error::ResponseJsonParseError { address: endpoint.into(), raw: body, source })?;
@@ -94,6 +114,12 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
             raw: p.version,
             source,
         })?;
+        let labels: HashMap<String, String> = match p.labels_as_json {
+            Some(labels) => {
+                serde_json::from_str(&labels).map_err(|source| Error::LabelsParseError { address: endpoint.into(), index: i, raw: labels, source })?
+            },
+            None => HashMap::new(),
+        };
 
         // Throw it in a PackageInfo
         infos.push(PackageInfo {
@@ -110,6 +136,7 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
             detached: p.detached,
             functions,
             types,
+            labels,
         });
     }
 
@@ -119,6 +146,46 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
 
 
 
+/// Revalidates a previously downloaded package index against the Brane API service, only downloading it again if it has changed.
+///
+/// This sends the index's entity tag (see [`PackageIndex::etag()`]) to the package list endpoint of the API (`<api>/packages`), which replies
+/// with `304 Not Modified` if it is still current.
+///
+/// # Arguments
+/// - `client`: The [`Client`] with which to send the request.
+/// - `endpoint`: The package list endpoint to send the request to.
+/// - `cached`: The [`PackageIndex`] that we have already.
+///
+/// # Returns
+/// [`None`] if `cached` is still current, or the new PackageIndex if it has changed.
+///
+/// # Errors
+/// This function errors if the endpoint is unavailable (e.g., because the API predates this endpoint) or its response was ill-formed. In that
+/// case, the index can still be downloaded with [`get_package_index_with()`].
+pub async fn revalidate_package_index_with(client: &Client, endpoint: impl AsRef<str>, cached: &PackageIndex) -> Result<Option<PackageIndex>, Error> {
+    let endpoint: &str = endpoint.as_ref();
+
+    // Send the request with the tag of what we have
+    let res: reqwest::Response = client
+        .get(endpoint)
+        .header("If-None-Match", cached.etag())
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|source| Error::RequestError { address: endpoint.into(), source })?;
+    if res.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+
+    // Otherwise, parse the new index
+    let body: String = res.text().await.map_err(|source| Error::ResponseBodyError { address: endpoint.into(), source })?;
+    let infos: Vec<PackageInfo> =
+        serde_json::from_str(&body).map_err(|source| Error::ResponseJsonParseError { address: endpoint.into(), raw: body, source })?;
+    PackageIndex::from_packages(infos).map(Some).map_err(|source| Error::PackageIndexError { address: endpoint.into(), source })
+}
+
+
+
 /// Downloads the current data index from the Brane API service.
 ///
 /// # Arguments
@@ -129,11 +196,32 @@ pub async fn get_package_index(endpoint: impl AsRef<str>) -> Result<PackageIndex
 ///
 /// # Errors
 /// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
-pub async fn get_data_index(endpoint: impl AsRef<str>) -> Result<DataIndex, Error> {
+#[inline]
+pub async fn get_data_index(endpoint: impl AsRef<str>) -> Result<DataIndex, Error> { get_data_index_with(&Client::new(), endpoint).await }
+
+/// Downloads the current data index from the Brane API service using the given client.
+///
+/// Use this over [`get_data_index()`] to control how the request is sent (e.g., its TLS configuration or headers).
+///
+/// # Arguments
+/// - `client`: The [`Client`] with which to send the request.
+/// - `endpoint`: The endpoint to send the request to.
+///
+/// # Returns
+/// The DataIndex that represents the datasets currently known to the instance at the time of the call.
+///
+/// # Errors
+/// This function errors for many reasons, chief of which may be that the endpoint is unavailable or its response was ill-formed.
+pub async fn get_data_index_with(client: &Client, endpoint: impl AsRef<str>) -> Result<DataIndex, Error> {
     let endpoint: &str = endpoint.as_ref();
 
     // Send the reqwest
-    let res: reqwest::Response = reqwest::get(endpoint).await.map_err(|source| Error::RequestError { address: endpoint.into(), source })?;
+    let res: reqwest::Response = client
+        .get(endpoint)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|source| Error::RequestError { address: endpoint.into(), source })?;
 
     // Fetch the body
     let body: String = res.text().await.map_err(|source| Error::ResponseBodyError { address: endpoint.into(), source })?;
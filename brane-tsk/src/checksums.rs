@@ -0,0 +1,260 @@
+//  CHECKSUMS.rs
+//
+//  Description:
+//!   Implements generating and verifying `SHA256SUMS`-style checksum
+//!   manifests for directories, e.g., to check the integrity of package
+//!   directories transferred between machines.
+//!
+//!   Note that manifests are not signed. They detect files corrupted or
+//!   lost in transit, but anyone who can modify a directory can also
+//!   regenerate its manifest.
+//
+
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use log::debug;
+use tokio::fs as tfs;
+
+use crate::docker::sha256_file;
+pub use crate::errors::ChecksumError as Error;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    /// Returns a fresh directory in the system's temporary directory with some nested files in it.
+    async fn test_dir() -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-tsk-checksums-{}", uuid::Uuid::new_v4()));
+        tfs::create_dir_all(dir.join("nested")).await.unwrap();
+        tfs::write(dir.join("package.yml"), "name: test\n").await.unwrap();
+        tfs::write(dir.join("nested").join("image.tar"), vec![42u8; 64 * 1024]).await.unwrap();
+        dir
+    }
+
+
+    /// Tests whether a generated manifest lists all files with their hex digests.
+    #[tokio::test]
+    async fn test_generate_manifest() {
+        let dir: PathBuf = test_dir().await;
+        let manifest: String = generate_manifest(&dir).await.unwrap();
+        let lines: Vec<&str> = manifest.lines().collect();
+        assert_eq!(lines.len(), 2);
+        // Compare with `sha256sum`
+        assert_eq!(lines[0], "a1cbdb30233f8ca5786202446a21157e21da0ba7dab4bd98740e785b69c7fc7f  nested/image.tar");
+        assert_eq!(lines[1], "b4b785ee519ceb6a284f99c1ec3b7874e75a8aa8630b7516cb7ea1e49db99087  package.yml");
+
+        // The manifest itself is never listed
+        write_manifest(&dir).await.unwrap();
+        assert_eq!(generate_manifest(&dir).await.unwrap(), manifest);
+        tfs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    /// Tests whether verification accepts an untouched directory and rejects tampered, missing and unlisted files.
+    #[tokio::test]
+    async fn test_verify_manifest() {
+        let dir: PathBuf = test_dir().await;
+        write_manifest(&dir).await.unwrap();
+        verify_manifest(&dir).await.unwrap();
+
+        // Tampered
+        tfs::write(dir.join("package.yml"), "name: evil\n").await.unwrap();
+        assert!(matches!(verify_manifest(&dir).await, Err(Error::DigestMismatch { .. })));
+        tfs::write(dir.join("package.yml"), "name: test\n").await.unwrap();
+        verify_manifest(&dir).await.unwrap();
+
+        // Unlisted
+        tfs::write(dir.join("extra.sh"), "#!/bin/sh\n").await.unwrap();
+        assert!(matches!(verify_manifest(&dir).await, Err(Error::UnlistedFile { .. })));
+        tfs::remove_file(dir.join("extra.sh")).await.unwrap();
+
+        // Missing
+        tfs::remove_file(dir.join("nested").join("image.tar")).await.unwrap();
+        assert!(matches!(verify_manifest(&dir).await, Err(Error::MissingFile { .. })));
+        tfs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    /// Tests whether verification refuses manifests that list paths outside of the directory.
+    #[tokio::test]
+    async fn test_verify_manifest_illegal_path() {
+        let dir: PathBuf = test_dir().await;
+        let manifest: String = generate_manifest(&dir).await.unwrap();
+        for file in ["../package.yml", "/etc/passwd", "nested/../package.yml", "./package.yml", ""] {
+            tfs::write(dir.join(MANIFEST_NAME), format!("{manifest}{}  {file}\n", "0".repeat(64))).await.unwrap();
+            assert!(matches!(verify_manifest(&dir).await, Err(Error::IllegalPath { line: 3, .. })), "Accepted '{file}'");
+        }
+        tfs::remove_dir_all(&dir).await.unwrap();
+    }
+}
+
+
+
+
+
+/***** CONSTANTS *****/
+/// The name of the manifest file in a directory.
+pub const MANIFEST_NAME: &str = "SHA256SUMS";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Collects the paths of all files in the given directory (recursively), except for the manifest itself.
+///
+/// # Arguments
+/// - `dir`: The directory to collect the files of.
+///
+/// # Returns
+/// The paths of all files, relative to `dir` and using forward slashes, in sorted order.
+///
+/// # Errors
+/// This function errors if we failed to read any of the (nested) directories.
+async fn collect_files(dir: &Path) -> Result<Vec<String>, Error> {
+    let mut files: Vec<String> = Vec::new();
+    let mut todo: Vec<PathBuf> = vec![dir.into()];
+    while let Some(path) = todo.pop() {
+        let mut entries: tfs::ReadDir = tfs::read_dir(&path).await.map_err(|source| Error::DirReadError { path: path.clone(), source })?;
+        while let Some(entry) = entries.next_entry().await.map_err(|source| Error::DirReadError { path: path.clone(), source })? {
+            let entry_path: PathBuf = entry.path();
+            if entry_path.is_dir() {
+                todo.push(entry_path);
+                continue;
+            }
+
+            // Note it relative to the directory
+            let rel_path: String = entry_path
+                .strip_prefix(dir)
+                .unwrap_or(&entry_path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if rel_path != MANIFEST_NAME {
+                files.push(rel_path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Computes the hex-encoded SHA-256 digest of the given file.
+///
+/// # Arguments
+/// - `path`: The path of the file to hash.
+///
+/// # Returns
+/// The digest as a lowercase hexadecimal string.
+///
+/// # Errors
+/// This function errors if we failed to open or read the file.
+async fn hash_file(path: &Path) -> Result<String, Error> {
+    let mut handle: tfs::File = tfs::File::open(path).await.map_err(|source| Error::FileOpenError { path: path.into(), source })?;
+    let digest: Vec<u8> = sha256_file(&mut handle).await.map_err(|source| Error::FileReadError { path: path.into(), source })?;
+    Ok(digest.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Generates a `SHA256SUMS`-style manifest for the given directory.
+///
+/// The manifest has one line per file in the directory (recursively, and excluding any existing manifest), formatted as `<HEX DIGEST>  <PATH>` just
+/// like `sha256sum` does. Paths are relative to the directory.
+///
+/// # Arguments
+/// - `dir`: The directory to generate the manifest for.
+///
+/// # Returns
+/// The contents of the manifest.
+///
+/// # Errors
+/// This function errors if we failed to read the directory or any of the files in it.
+pub async fn generate_manifest(dir: impl AsRef<Path>) -> Result<String, Error> {
+    let dir: &Path = dir.as_ref();
+    debug!("Generating checksum manifest for '{}'...", dir.display());
+
+    let mut manifest: String = String::new();
+    for file in collect_files(dir).await? {
+        manifest.push_str(&format!("{}  {}\n", hash_file(&dir.join(&file)).await?, file));
+    }
+    Ok(manifest)
+}
+
+/// Generates a `SHA256SUMS`-style manifest for the given directory and writes it to [`MANIFEST_NAME`] in that directory.
+///
+/// # Arguments
+/// - `dir`: The directory to generate the manifest for.
+///
+/// # Returns
+/// The path of the written manifest.
+///
+/// # Errors
+/// This function errors if we failed to generate the manifest (see [`generate_manifest()`]) or failed to write it.
+pub async fn write_manifest(dir: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let dir: &Path = dir.as_ref();
+    let manifest: String = generate_manifest(dir).await?;
+    let path: PathBuf = dir.join(MANIFEST_NAME);
+    tfs::write(&path, manifest).await.map_err(|source| Error::ManifestWriteError { path: path.clone(), source })?;
+    Ok(path)
+}
+
+/// Verifies the files in the given directory against the [`MANIFEST_NAME`] manifest in it.
+///
+/// This only checks integrity, not authenticity; see the module documentation.
+///
+/// # Arguments
+/// - `dir`: The directory to verify.
+///
+/// # Errors
+/// This function errors if the manifest could not be read or parsed, if it lists paths outside of the directory, if any listed file is missing or has
+/// a different digest, or if the directory contains files that aren't listed in the manifest.
+pub async fn verify_manifest(dir: impl AsRef<Path>) -> Result<(), Error> {
+    let dir: &Path = dir.as_ref();
+    debug!("Verifying '{}' against its checksum manifest...", dir.display());
+
+    // Read the manifest
+    let path: PathBuf = dir.join(MANIFEST_NAME);
+    let manifest: String = tfs::read_to_string(&path).await.map_err(|source| Error::ManifestReadError { path: path.clone(), source })?;
+
+    // Check every file in it
+    let mut listed: HashSet<String> = HashSet::new();
+    for (i, line) in manifest.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (expected, file): (&str, &str) =
+            line.split_once("  ").ok_or_else(|| Error::ManifestParseError { path: path.clone(), line: i + 1, raw: line.into() })?;
+
+        // Only accept plain relative paths, so the manifest cannot make us look outside the directory
+        if file.is_empty() || !Path::new(file).components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(Error::IllegalPath { path: path.clone(), line: i + 1, file: file.into() });
+        }
+
+        // Hash the file
+        let file_path: PathBuf = dir.join(file);
+        if !file_path.is_file() {
+            return Err(Error::MissingFile { path: file_path });
+        }
+        let got: String = hash_file(&file_path).await?;
+        if got != expected.to_lowercase() {
+            return Err(Error::DigestMismatch { path: file_path, expected: expected.into(), got });
+        }
+        listed.insert(file.into());
+    }
+
+    // Make sure nothing has been added
+    for file in collect_files(dir).await? {
+        if !listed.contains(&file) {
+            return Err(Error::UnlistedFile { path: dir.join(file) });
+        }
+    }
+    debug!("All files in '{}' match the manifest", dir.display());
+    Ok(())
+}
@@ -16,10 +16,12 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use base64ct::{Base64, Encoding};
 use bollard::container::{
-    Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions, StartContainerOptions, WaitContainerOptions,
+    Config, CreateContainerOptions, ListContainersOptions, LogOutput, LogsOptions, RemoveContainerOptions, StartContainerOptions, Stats,
+    StatsOptions, WaitContainerOptions,
 };
 use bollard::image::{CreateImageOptions, ImportImageOptions, RemoveImageOptions, TagImageOptions};
 use bollard::models::{DeviceRequest, EndpointSettings, HostConfig};
@@ -28,7 +30,7 @@ use brane_exe::FullValue;
 use enum_debug::EnumDebug;
 use futures_util::StreamExt as _;
 use futures_util::stream::TryStreamExt as _;
-use log::debug;
+use log::{debug, warn};
 use serde::de::{Deserializer, Visitor};
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
@@ -42,7 +44,139 @@ use tokio_tar::Archive;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
 pub use crate::errors::DockerError as Error;
-use crate::errors::{ClientVersionParseError, ExecuteError};
+use crate::errors::{ClientVersionParseError, DockerHostParseError, ExecuteError};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use futures_util::stream;
+    use rand::RngCore as _;
+
+    use super::*;
+
+
+    /// Generates `n` chunks of `size` random bytes each.
+    fn random_chunks(n: usize, size: usize) -> Vec<Vec<u8>> {
+        let mut rng = rand::rng();
+        (0..n)
+            .map(|_| {
+                let mut chunk: Vec<u8> = vec![0; size];
+                rng.fill_bytes(&mut chunk);
+                chunk
+            })
+            .collect()
+    }
+
+    /// Returns a fresh path in the system's temporary directory.
+    fn temp_path() -> PathBuf { std::env::temp_dir().join(format!("brane-tsk-image-export-{}.tar", uuid::Uuid::new_v4())) }
+
+
+    /// Tests whether writing an image stream results in exactly the concatenation of its chunks.
+    #[tokio::test]
+    async fn test_write_image_stream() {
+        // Use differently-sized chunks, including some larger than the write buffer
+        let mut chunks: Vec<Vec<u8>> = random_chunks(100, 32 * 1024);
+        chunks.extend(random_chunks(2, IMAGE_EXPORT_BUFFER_SIZE + 17));
+        chunks.push(vec![]);
+        chunks.extend(random_chunks(10, 3));
+        let expected: Vec<u8> = chunks.concat();
+
+        // Write it
+        let path: PathBuf = temp_path();
+        let total: usize = write_image_stream(stream::iter(chunks.into_iter().map(Ok)), "test", &path).await.unwrap();
+        let got: Vec<u8> = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Compare
+        assert_eq!(total, expected.len());
+        assert!(got == expected, "Written image file differs from the streamed chunks");
+    }
+
+    /// Benchmarks writing a 512 MiB image stream with the pipelined writer against the previous sequential, default-buffered approach.
+    ///
+    /// Run with `cargo test --release -p brane-tsk -- --ignored --nocapture bench_write_image_stream`.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn bench_write_image_stream() {
+        // Docker hands out chunks of roughly 32 KiB
+        let chunks: Vec<Vec<u8>> = random_chunks(16 * 1024, 32 * 1024);
+        let size: usize = chunks.iter().map(Vec::len).sum();
+
+        // The old way: a default BufWriter that is written to in between receiving chunks
+        let path: PathBuf = temp_path();
+        let start: Instant = Instant::now();
+        {
+            let mut handle: tio::BufWriter<tfs::File> = tio::BufWriter::new(tfs::File::create(&path).await.unwrap());
+            let mut stream = stream::iter(chunks.iter().cloned().map(Ok::<_, bollard::errors::Error>));
+            while let Some(chunk) = stream.next().await {
+                handle.write_all(&chunk.unwrap()).await.unwrap();
+            }
+            handle.flush().await.unwrap();
+            handle.shutdown().await.unwrap();
+        }
+        let sequential: f64 = start.elapsed().as_secs_f64();
+        std::fs::remove_file(&path).unwrap();
+
+        // The new way
+        let path: PathBuf = temp_path();
+        let start: Instant = Instant::now();
+        write_image_stream(stream::iter(chunks.into_iter().map(Ok)), "bench", &path).await.unwrap();
+        let pipelined: f64 = start.elapsed().as_secs_f64();
+        std::fs::remove_file(&path).unwrap();
+
+        let mib: f64 = size as f64 / (1024.0 * 1024.0);
+        println!("Sequential: {:.3}s ({:.1} MiB/s)", sequential, mib / sequential);
+        println!("Pipelined:  {:.3}s ({:.1} MiB/s)", pipelined, mib / pipelined);
+    }
+
+    #[test]
+    fn test_docker_host_parse() {
+        assert_eq!("unix:///var/run/docker.sock".parse::<DockerHost>().unwrap(), DockerHost::Unix("/var/run/docker.sock".into()));
+        assert_eq!("tcp://10.0.0.1:2375".parse::<DockerHost>().unwrap(), DockerHost::Tcp("10.0.0.1:2375".into()));
+        assert_eq!("http://10.0.0.1:2375".parse::<DockerHost>().unwrap(), DockerHost::Tcp("10.0.0.1:2375".into()));
+        assert_eq!("/run/user/1000/docker.sock".parse::<DockerHost>().unwrap(), DockerHost::from(PathBuf::from("/run/user/1000/docker.sock")));
+        assert_eq!(DockerHost::Tcp("10.0.0.1:2375".into()).to_string(), "tcp://10.0.0.1:2375");
+        assert!("ftp://host".parse::<DockerHost>().is_err());
+        assert!("ssh://user@host".parse::<DockerHost>().is_err());
+        assert!("tcp://".parse::<DockerHost>().is_err());
+    }
+
+    #[test]
+    fn test_docker_host_from_env() {
+        let env = |host: Option<&str>, tls_verify: Option<&str>| DockerHost::from_env_values(host.map(String::from), tls_verify.map(String::from));
+        assert_eq!(env(None, None), None);
+        assert_eq!(env(Some(""), None), None);
+        assert_eq!(env(Some("tcp://10.0.0.1:2375"), None), Some(DockerHost::Tcp("10.0.0.1:2375".into())));
+        assert_eq!(env(Some("tcp://10.0.0.1:2375"), Some("")), Some(DockerHost::Tcp("10.0.0.1:2375".into())));
+        assert_eq!(env(Some("unix:///var/run/docker.sock"), Some("1")), Some(DockerHost::Unix("/var/run/docker.sock".into())));
+
+        // Hosts we cannot serve are ignored instead of failing every command
+        assert_eq!(env(Some("tcp://10.0.0.1:2376"), Some("1")), None);
+        assert_eq!(env(Some("ssh://user@host"), None), None);
+        assert_eq!(env(Some("ftp://host"), None), None);
+    }
+
+    #[test]
+    fn test_container_stats_cpu() {
+        let mut stats: ContainerStats = ContainerStats::default();
+        assert_eq!(stats.avg_cpu(), None);
+
+        // Idle samples count as 0%
+        stats.add_cpu(0, 100, 2);
+        stats.add_cpu(50, 100, 2);
+        assert_eq!(stats.avg_cpu(), Some(50.0));
+
+        // Samples without elapsed system time are skipped
+        stats.add_cpu(10, 0, 2);
+        assert_eq!(stats.avg_cpu(), Some(50.0));
+    }
+}
+
+
+
 
 
 /***** CONSTANTS *****/
@@ -54,6 +188,17 @@ pub(crate) const MANIFEST_CONFIG_PREFIX: &str = "blobs/sha256/";
 /// This one is actually used in saved images.
 pub(crate) const MANIFEST_CONFIG_POSTFIX: &str = ".json";
 
+/// The size (in bytes) of the write buffer used when saving images to disk.
+///
+/// Exported images are typically hundreds of MBs, so we buffer generously to keep the number of write syscalls low.
+const IMAGE_EXPORT_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+/// The number of chunks that may be in flight between the Docker daemon and the disk writer when saving images.
+const IMAGE_EXPORT_QUEUE_DEPTH: usize = 64;
+
+/// The label on kept containers that records after when (as a UNIX timestamp in seconds) they may be removed by
+/// [`remove_expired_containers()`].
+pub const CLEANUP_AT_LABEL: &str = "brane.cleanup-at";
+
 
 
 
@@ -72,6 +217,98 @@ struct DockerImageManifest {
 
 
 /***** AUXILLARY STRUCTS *****/
+/// Defines where the Docker daemon lives, as given by a plain socket path or a `DOCKER_HOST`-style URL.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DockerHost {
+    /// A Unix socket (`unix://<PATH>`, or a plain path on Unix).
+    Unix(PathBuf),
+    /// A Windows named pipe (`npipe://<PATH>`, or a plain path on Windows).
+    NamedPipe(PathBuf),
+    /// A daemon listening on (plain) TCP (`tcp://<HOST>:<PORT>` or `http://<HOST>:<PORT>`).
+    Tcp(String),
+}
+impl DockerHost {
+    /// Resolves the Docker host to connect to from the one given explicitly (if any), falling back to the one in `DOCKER_HOST` (see
+    /// [`DockerHost::from_env()`]) and then to the platform's default socket.
+    ///
+    /// # Arguments
+    /// - `host`: The host given explicitly, e.g., by a command-line flag.
+    ///
+    /// # Returns
+    /// The host to connect to.
+    #[inline]
+    pub fn resolve(host: Option<Self>) -> Self { host.or_else(Self::from_env).unwrap_or_default() }
+
+    /// Reads the Docker host from the `DOCKER_HOST` environment variable, if it is set to a host we can connect to.
+    ///
+    /// Hosts we cannot connect to are ignored (with a warning) such that the default socket is used instead. These are hosts that fail to parse
+    /// (including `ssh://`-hosts) and TCP hosts while `DOCKER_TLS_VERIFY` is set, since we do not support TLS connections to the daemon.
+    ///
+    /// # Returns
+    /// The host in `DOCKER_HOST`, or [`None`] if it is unset, empty or a host we cannot connect to.
+    #[inline]
+    pub fn from_env() -> Option<Self> { Self::from_env_values(std::env::var("DOCKER_HOST").ok(), std::env::var("DOCKER_TLS_VERIFY").ok()) }
+
+    /// Implements [`DockerHost::from_env()`] for the given values of `DOCKER_HOST` and `DOCKER_TLS_VERIFY`.
+    fn from_env_values(host: Option<String>, tls_verify: Option<String>) -> Option<Self> {
+        let raw: String = host.filter(|raw| !raw.is_empty())?;
+        let host: Self = match raw.parse() {
+            Ok(host) => host,
+            Err(err) => {
+                warn!("Ignoring DOCKER_HOST: {err}");
+                return None;
+            },
+        };
+        if matches!(host, Self::Tcp(_)) && tls_verify.is_some_and(|verify| !verify.is_empty()) {
+            warn!("Ignoring DOCKER_HOST '{host}' because DOCKER_TLS_VERIFY is set and TLS connections to the Docker daemon are not supported");
+            return None;
+        }
+        Some(host)
+    }
+}
+impl Default for DockerHost {
+    /// Returns the platform's default local socket.
+    #[inline]
+    fn default() -> Self { if cfg!(windows) { Self::NamedPipe("//./pipe/docker_engine".into()) } else { Self::Unix("/var/run/docker.sock".into()) } }
+}
+impl Display for DockerHost {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use DockerHost::*;
+        match self {
+            Unix(path) => write!(f, "unix://{}", path.display()),
+            NamedPipe(path) => write!(f, "npipe://{}", path.display()),
+            Tcp(addr) => write!(f, "tcp://{addr}"),
+        }
+    }
+}
+impl FromStr for DockerHost {
+    type Err = DockerHostParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Anything without a scheme is a path to a socket
+        let (scheme, addr): (&str, &str) = match s.split_once("://") {
+            Some(parts) => parts,
+            None => return Ok(Self::from(PathBuf::from(s))),
+        };
+        if addr.is_empty() {
+            return Err(DockerHostParseError::EmptyAddress { raw: s.into() });
+        }
+        match scheme {
+            "unix" => Ok(Self::Unix(addr.into())),
+            "npipe" => Ok(Self::NamedPipe(addr.into())),
+            "tcp" | "http" => Ok(Self::Tcp(addr.into())),
+            // bollard cannot tunnel over SSH itself, so point the user to forwarding the socket instead
+            "ssh" => Err(DockerHostParseError::SshUnsupported { raw: s.into() }),
+            scheme => Err(DockerHostParseError::UnknownScheme { raw: s.into(), scheme: scheme.into() }),
+        }
+    }
+}
+impl From<PathBuf> for DockerHost {
+    /// Interprets the path as the platform's kind of local socket.
+    #[inline]
+    fn from(value: PathBuf) -> Self { if cfg!(windows) { Self::NamedPipe(value) } else { Self::Unix(value) } }
+}
+
 /// Defines a wrapper around ClientVersion that allows it to be parsed.
 #[derive(Clone, Copy, Debug)]
 pub struct ClientVersion(pub bollard::ClientVersion);
@@ -365,11 +602,11 @@ impl From<&Network> for String {
 
 
 
-/// Collects information we need to know to connect to the (local) Docker daemon.
+/// Collects information we need to know to connect to the (local or remote) Docker daemon.
 #[derive(Clone, Debug)]
 pub struct DockerOptions {
-    /// The path to the socket with which we connect.
-    pub socket:  PathBuf,
+    /// Where the daemon lives.
+    pub host:    DockerHost,
     /// The client API version we use.
     pub version: ClientVersion,
 }
@@ -408,6 +645,8 @@ pub struct ExecuteInfo {
     pub capabilities: HashSet<Capability>,
     /// The netwok to connect the container to.
     pub network: Network,
+    /// Any labels to attach to the container (e.g., [`CLEANUP_AT_LABEL`]).
+    pub labels: HashMap<String, String>,
 }
 impl ExecuteInfo {
     /// Constructor for the ExecuteInfo.
@@ -422,7 +661,7 @@ impl ExecuteInfo {
     /// - `network`: The netwok to connect the container to.
     ///
     /// # Returns
-    /// A new ExecuteInfo instance populated with the given values.
+    /// A new ExecuteInfo instance populated with the given values and without any labels.
     #[inline]
     pub fn new(
         name: impl Into<String>,
@@ -433,8 +672,90 @@ impl ExecuteInfo {
         capabilities: HashSet<Capability>,
         network: Network,
     ) -> Self {
-        ExecuteInfo { name: name.into(), image: image.into(), image_source: image_source.into(), command, binds, capabilities, network }
+        ExecuteInfo {
+            name: name.into(),
+            image: image.into(),
+            image_source: image_source.into(),
+            command,
+            binds,
+            capabilities,
+            network,
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Marks the container-to-be as removable by [`remove_expired_containers()`] once the given time has passed since now.
+    ///
+    /// Only useful for containers that are kept after execution.
+    ///
+    /// # Arguments
+    /// - `after`: The time after which the container may be removed.
+    pub fn cleanup_after(&mut self, after: Duration) {
+        let at: u64 = (SystemTime::now() + after).duration_since(UNIX_EPOCH).map(|at| at.as_secs()).unwrap_or(0);
+        self.labels.insert(CLEANUP_AT_LABEL.into(), at.to_string());
+    }
+}
+
+
+
+/// Summarizes the resource usage of a container as sampled while it ran.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ContainerStats {
+    /// The number of samples taken.
+    pub samples: usize,
+    /// The highest memory usage seen, in bytes.
+    pub peak_memory: u64,
+    /// The sum of the CPU usages (as percentage of one core) of all samples that had one.
+    cpu_total: f64,
+    /// The number of samples that had a CPU usage.
+    cpu_samples: usize,
+}
+impl ContainerStats {
+    /// Adds a sample reported by the Docker daemon.
+    ///
+    /// The CPU usage is computed the same way as `docker stats` does, i.e., relative to the previous sample the daemon took.
+    ///
+    /// # Arguments
+    /// - `stats`: The sample to add.
+    fn add(&mut self, stats: &Stats) {
+        self.samples += 1;
+        if let Some(usage) = stats.memory_stats.usage {
+            self.peak_memory = self.peak_memory.max(usage);
+        }
+
+        // Without a previous sample, there is nothing to compute the CPU usage relative to
+        if stats.precpu_stats.system_cpu_usage.unwrap_or(0) == 0 {
+            return;
+        }
+        let cpu_delta: u64 = stats.cpu_stats.cpu_usage.total_usage.saturating_sub(stats.precpu_stats.cpu_usage.total_usage);
+        let system_delta: u64 = stats.cpu_stats.system_cpu_usage.unwrap_or(0).saturating_sub(stats.precpu_stats.system_cpu_usage.unwrap_or(0));
+        let cpus: u64 =
+            stats.cpu_stats.online_cpus.or_else(|| stats.cpu_stats.cpu_usage.percpu_usage.as_ref().map(|percpu| percpu.len() as u64)).unwrap_or(1);
+        self.add_cpu(cpu_delta, system_delta, cpus);
+    }
+
+    /// Adds a CPU usage sample.
+    ///
+    /// Samples in which the container did not use the CPU at all count as 0%; only samples in which no system time passed are skipped.
+    ///
+    /// # Arguments
+    /// - `cpu_delta`: The CPU time used by the container since the previous sample.
+    /// - `system_delta`: The CPU time used by the whole system since the previous sample.
+    /// - `cpus`: The number of CPUs in the system.
+    fn add_cpu(&mut self, cpu_delta: u64, system_delta: u64, cpus: u64) {
+        if system_delta == 0 {
+            return;
+        }
+        self.cpu_total += (cpu_delta as f64 / system_delta as f64) * cpus as f64 * 100.0;
+        self.cpu_samples += 1;
     }
+
+    /// Returns the average CPU usage over all samples, as a percentage of one core.
+    ///
+    /// # Returns
+    /// The average usage, or [`None`] if no sample had a usable CPU usage (e.g., because the container exited too quickly).
+    #[inline]
+    pub fn avg_cpu(&self) -> Option<f64> { if self.cpu_samples > 0 { Some(self.cpu_total / self.cpu_samples as f64) } else { None } }
 }
 
 
@@ -496,12 +817,15 @@ fn preprocess_arg(
     };
     debug!("Resolving argument '{}' ({})", name, data_name.variant());
 
-    // Get the method of access for this data type
-    let access: &AccessKind = match input.get(&data_name) {
-        Some(access) => access,
-        None => {
+    // Get the method of access for this data type (resolving it to the result it matches if it is a pattern)
+    let (data_name, access): (DataName, &AccessKind) = match data_name.resolve_in(input) {
+        Ok(Some((name, access))) => (name.clone(), access),
+        Ok(None) => {
             return Err(ExecuteError::UnknownData { name: data_name });
         },
+        Err(matches) => {
+            return Err(ExecuteError::AmbiguousData { name: data_name, matches });
+        },
     };
 
     // Match on that to replace the value and generate a binding (possibly)
@@ -586,8 +910,13 @@ async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Resu
     };
 
     // Create the container confic
-    let create_config =
-        Config { image: Some(info.image.name()), cmd: Some(info.command.clone()), host_config: Some(host_config), ..Default::default() };
+    let create_config = Config {
+        image: Some(info.image.name()),
+        cmd: Some(info.command.clone()),
+        labels: Some(info.labels.clone()),
+        host_config: Some(host_config),
+        ..Default::default()
+    };
 
     // Run it with that config
     debug!("Launching container with name '{}' (image: {})...", info.name, info.image.name());
@@ -611,22 +940,74 @@ async fn create_and_start_container(docker: &Docker, info: &ExecuteInfo) -> Resu
 /// # Arguments
 /// - `docker`: The Docker instance to use for accessing the container.
 /// - `name`: The name of the container to wait on.
-/// - `image`: The image that was run (used for debugging).
-/// - `keep_container`: Whether to keep the container around after it's finished or not.
-///
-/// # Returns
-/// The return code of the docker container, its stdout and its stderr (in that order).
 ///
 /// # Errors
-/// This function may error for many reasons, which usually means that the container is unknown or the Docker engine is unreachable.
-async fn join_container(docker: &Docker, name: &str, keep_container: bool) -> Result<(i32, String, String), Error> {
-    // Wait for the container to complete
+/// This function errors if the container is unknown or the Docker engine is unreachable.
+async fn wait_for_container(docker: &Docker, name: &str) -> Result<(), Error> {
     docker
         .wait_container(name, None::<WaitContainerOptions<String>>)
         .try_collect::<Vec<_>>()
         .await
         .map_err(|source| Error::WaitError { name: name.into(), source })?;
+    Ok(())
+}
 
+/// Waits for the given container to complete while sampling its resource usage.
+///
+/// Sampling stops as soon as the container exits, even if the daemon would keep the stats stream open.
+///
+/// # Arguments
+/// - `docker`: The Docker instance to use for accessing the container.
+/// - `name`: The name of the container to wait on.
+///
+/// # Returns
+/// The [`ContainerStats`] sampled while the container ran.
+///
+/// # Errors
+/// This function errors if the container is unknown or the Docker engine is unreachable. Failing to sample is not an error; it just results in
+/// fewer samples.
+async fn sample_container(docker: &Docker, name: &str) -> Result<ContainerStats, Error> {
+    let mut stream =
+        docker.stats(name, Some(StatsOptions { stream: true, one_shot: false })).boxed().take_until(Box::pin(wait_for_container(docker, name)));
+
+    // Collect samples until the container is done
+    let mut stats: ContainerStats = ContainerStats::default();
+    while let Some(sample) = stream.next().await {
+        match sample {
+            Ok(sample) => stats.add(&sample),
+            Err(err) => {
+                debug!("Failed to sample stats of container '{name}' (stopping sampling): {err}");
+                break;
+            },
+        }
+    }
+
+    // Either the wait is already done, or the stats stream ended first and we still wait
+    match stream.take_result() {
+        Some(res) => res?,
+        None => {
+            if let Some(wait) = stream.take_future() {
+                wait.await?;
+            }
+        },
+    }
+    debug!("Took {} stats sample(s) of container '{name}'", stats.samples);
+    Ok(stats)
+}
+
+/// Collects the results of a container that has completed.
+///
+/// # Arguments
+/// - `docker`: The Docker instance to use for accessing the container.
+/// - `name`: The name of the (completed) container.
+/// - `keep_container`: Whether to keep the container around after it's finished or not.
+///
+/// # Returns
+/// The return code of the docker container, its stdout and its stderr (in that order).
+///
+/// # Errors
+/// This function may error for many reasons, which usually means that the container is unknown or the Docker engine is unreachable.
+async fn collect_container(docker: &Docker, name: &str, keep_container: bool) -> Result<(i32, String, String), Error> {
     // Get stdout and stderr logs from container
     let logs_options = Some(LogsOptions::<String> { stdout: true, stderr: true, ..Default::default() });
     let log_outputs =
@@ -657,6 +1038,26 @@ async fn join_container(docker: &Docker, name: &str, keep_container: bool) -> Re
     Ok((code, stdout, stderr))
 }
 
+/// Waits for the given container to complete.
+///
+/// # Arguments
+/// - `docker`: The Docker instance to use for accessing the container.
+/// - `name`: The name of the container to wait on.
+/// - `keep_container`: Whether to keep the container around after it's finished or not.
+///
+/// # Returns
+/// The return code of the docker container, its stdout and its stderr (in that order).
+///
+/// # Errors
+/// This function may error for many reasons, which usually means that the container is unknown or the Docker engine is unreachable.
+async fn join_container(docker: &Docker, name: &str, keep_container: bool) -> Result<(i32, String, String), Error> {
+    // Wait for the container to complete
+    wait_for_container(docker, name).await?;
+
+    // Then collect its results
+    collect_container(docker, name, keep_container).await
+}
+
 /// Returns the exit code of a container is (hopefully) already stopped.
 ///
 /// # Arguments
@@ -784,37 +1185,109 @@ async fn pull_image(docker: &Docker, image: impl Into<Image>, image_source: impl
 
 
 
+/// Writes a stream of image chunks to the given file.
+///
+/// Reading chunks from the stream and writing them to disk happens concurrently: a separate task owns the (heavily buffered) file handle, and chunks
+/// are handed to it over a bounded queue. The order of chunks is preserved, so the resulting file is byte-identical to writing them sequentially.
+///
+/// # Arguments
+/// - `stream`: The stream of chunks to write (typically coming from [`Docker::export_image()`]).
+/// - `name`: The name of the image being exported (used for debugging purposes).
+/// - `target`: The path of the file to write to. Will be created or truncated.
+///
+/// # Returns
+/// The total number of bytes written.
+///
+/// # Errors
+/// This function errors if the stream yields an error or if we failed to create or write the target file.
+async fn write_image_stream<S, B>(mut stream: S, name: &str, target: &Path) -> Result<usize, Error>
+where
+    S: futures_util::Stream<Item = Result<B, bollard::errors::Error>> + Unpin,
+    B: 'static + AsRef<[u8]> + Send,
+{
+    // Open the output file
+    let mut handle: tio::BufWriter<tfs::File> = match tfs::File::create(target).await {
+        Ok(handle) => tio::BufWriter::with_capacity(IMAGE_EXPORT_BUFFER_SIZE, handle),
+        Err(source) => {
+            return Err(Error::ImageFileCreateError { path: target.into(), source });
+        },
+    };
+
+    // Spawn the writer
+    let (tx, mut rx): (tokio::sync::mpsc::Sender<B>, tokio::sync::mpsc::Receiver<B>) = tokio::sync::mpsc::channel(IMAGE_EXPORT_QUEUE_DEPTH);
+    let wtarget: PathBuf = target.into();
+    let writer: tokio::task::JoinHandle<Result<(), Error>> = tokio::spawn(async move {
+        while let Some(chunk) = rx.recv().await {
+            handle.write_all(chunk.as_ref()).await.map_err(|source| Error::ImageFileWriteError { path: wtarget.clone(), source })?;
+        }
+
+        // Finish the handle
+        handle.flush().await.map_err(|source| Error::ImageFileShutdownError { path: wtarget.clone(), source })?;
+        handle.shutdown().await.map_err(|source| Error::ImageFileShutdownError { path: wtarget, source })
+    });
+
+    // Feed it chunks from the stream
+    let mut total: usize = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk: B = match chunk {
+            Ok(chunk) => chunk,
+            Err(source) => {
+                writer.abort();
+                return Err(Error::ImageExportError { name: name.into(), source });
+            },
+        };
+        total += chunk.as_ref().len();
+        if tx.send(chunk).await.is_err() {
+            // The writer quit early, which means it errored; we'll find out below
+            break;
+        }
+    }
+    drop(tx);
+
+    // Wait for the writer to finish
+    match writer.await {
+        Ok(res) => res?,
+        Err(source) => return Err(Error::ImageFileWriterJoinError { path: target.into(), source }),
+    }
+    debug!("Wrote {} bytes of image '{}' to '{}'", total, name, target.display());
+    Ok(total)
+}
+
+
+
+
+
 /***** AUXILLARY FUNCTIONS *****/
-/// Creates a new connection to the local Docker daemon.
+/// Creates a new connection to the Docker daemon.
+///
+/// Despite the name, the daemon may also be a remote one if the [`DockerHost`] in `opts` says so.
 ///
 /// # Arguments
-/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `opts`: The DockerOptions that contains information on how we can connect to the daemon.
 ///
 /// # Returns
 /// A new `Docker`-instance that may be used in some of the other functions in this module.
 ///
 /// # Errors
-/// This function errors if we could not connect to the local daemon.
+/// This function errors if we could not connect to the daemon, or if the host is of a kind we cannot connect to on this platform.
 pub fn connect_local(opts: impl AsRef<DockerOptions>) -> Result<Docker, Error> {
     let opts: &DockerOptions = opts.as_ref();
+    let err = |source: bollard::errors::Error| Error::ConnectionError { host: opts.host.to_string(), version: opts.version.0, source };
 
     // Connect to docker
-    #[cfg(unix)]
-    return Docker::connect_with_unix(&opts.socket.to_string_lossy(), 900, &opts.version.0).map_err(|source| Error::ConnectionError {
-        path: opts.socket.clone(),
-        version: opts.version.0,
-        source,
-    });
-
-    #[cfg(windows)]
-    return Docker::connect_with_named_pipe(&opts.socket.to_string_lossy(), 900, &opts.version.0).map_err(|source| Error::ConnectionError {
-        path: opts.socket.clone(),
-        version: opts.version.0,
-        source,
-    });
-
-    #[cfg(not(any(unix, windows)))]
-    compile_error!("Non-Unix, non-Windows OS not supported.");
+    match &opts.host {
+        #[cfg(unix)]
+        DockerHost::Unix(path) => Docker::connect_with_unix(&path.to_string_lossy(), 900, &opts.version.0).map_err(err),
+        #[cfg(windows)]
+        DockerHost::NamedPipe(path) => Docker::connect_with_named_pipe(&path.to_string_lossy(), 900, &opts.version.0).map_err(err),
+        DockerHost::Tcp(addr) => Docker::connect_with_http(&format!("tcp://{addr}"), 900, &opts.version.0).map_err(err),
+        #[cfg(not(unix))]
+        DockerHost::Unix(_) => Err(Error::UnsupportedHost { host: opts.host.to_string(), reason: "Unix sockets are only supported on Unix" }),
+        #[cfg(not(windows))]
+        DockerHost::NamedPipe(_) => {
+            Err(Error::UnsupportedHost { host: opts.host.to_string(), reason: "named pipes are only supported on Windows" })
+        },
+    }
 }
 
 /// Helps any VM aiming to use Docker by preprocessing the given list of arguments and function result into a list of bindings (and resolving the the arguments while at it).
@@ -950,30 +1423,24 @@ pub async fn get_digest(path: impl AsRef<Path>) -> Result<String, Error> {
     Err(Error::ImageTarNoManifest { path: path.to_path_buf() })
 }
 
-/// Given an already downloaded container, computes the SHA-256 hash of it.
+/// Computes the SHA-256 digest of the contents of an already opened file.
+///
+/// The file is read in chunks, so it works for files of arbitrary size.
 ///
 /// # Arguments
-/// - `container_path`: The path to the container image file to hash.
+/// - `handle`: The file to read and hash.
 ///
 /// # Returns
-/// The hash, as a `sha2::Digest`.
+/// The raw digest bytes.
 ///
 /// # Errors
-/// This function may error if we failed to read the given file.
-pub async fn hash_container(container_path: impl AsRef<Path>) -> Result<String, Error> {
-    let container_path: &Path = container_path.as_ref();
-    debug!("Hashing image file '{}'...", container_path.display());
-
-    // Attempt to open the file
-    let mut handle: tfs::File =
-        tfs::File::open(container_path).await.map_err(|source| Error::ImageTarOpenError { path: container_path.into(), source })?;
-
-    // Read through it in chunks
+/// This function errors if we failed to read from the given file.
+pub async fn sha256_file(handle: &mut tfs::File) -> Result<Vec<u8>, std::io::Error> {
     let mut hasher: Sha256 = Sha256::new();
     let mut buf: [u8; 1024 * 16] = [0; 1024 * 16];
     loop {
         // Read the next chunk
-        let n_bytes: usize = handle.read(&mut buf).await.map_err(|source| Error::ImageTarReadError { path: container_path.into(), source })?;
+        let n_bytes: usize = handle.read(&mut buf).await?;
         // Stop if we read nothing
         if n_bytes == 0 {
             break;
@@ -982,42 +1449,68 @@ pub async fn hash_container(container_path: impl AsRef<Path>) -> Result<String,
         // Hash that
         hasher.update(&buf[..n_bytes]);
     }
-    let result: String = Base64::encode_string(&hasher.finalize());
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Given an already downloaded container, computes the SHA-256 hash of it.
+///
+/// # Arguments
+/// - `container_path`: The path to the container image file to hash.
+///
+/// # Returns
+/// The hash, as a `sha2::Digest`.
+///
+/// # Errors
+/// This function may error if we failed to read the given file.
+pub async fn hash_container(container_path: impl AsRef<Path>) -> Result<String, Error> {
+    let container_path: &Path = container_path.as_ref();
+    debug!("Hashing image file '{}'...", container_path.display());
+
+    // Attempt to open the file
+    let mut handle: tfs::File =
+        tfs::File::open(container_path).await.map_err(|source| Error::ImageTarOpenError { path: container_path.into(), source })?;
+
+    // Hash it
+    let digest: Vec<u8> = sha256_file(&mut handle).await.map_err(|source| Error::ImageTarReadError { path: container_path.into(), source })?;
+    let result: String = Base64::encode_string(&digest);
     debug!("Image file '{}' hash: '{}'", container_path.display(), result);
 
     // Done
     Ok(result)
 }
 
-/// Tries to import/pull the given image if it does not exist in the local Docker instance.
+/// Checks whether the given image exists in the local Docker instance.
 ///
 /// # Arguments
 /// - `docker`: An already connected local instance of Docker.
-/// - `image`: The Docker image name, version & potential digest to pull.
-/// - `source`: Where to get the image from should it not be present already.
+/// - `image`: The Docker image name, version & potential digest to look for.
+///
+/// # Returns
+/// True if the image is known to the daemon, or false otherwise.
 ///
 /// # Errors
-/// This function errors if it failed to ensure the image existed (i.e., import or pull failed).
-pub async fn ensure_image(docker: &Docker, image: impl Into<Image>, source: impl Into<ImageSource>) -> Result<(), Error> {
+/// This function errors if we failed to inspect the image for reasons other than it not existing.
+pub async fn image_exists(docker: &Docker, image: impl Into<Image>) -> Result<bool, Error> {
     let image: Image = image.into();
-    let source: ImageSource = source.into();
-
-    // Abort if image is already loaded
     match docker.inspect_image(&image.docker().to_string()).await {
-        Ok(_) => {
-            debug!("Image '{}' already exists in Docker deamon.", image.docker());
-            return Ok(());
-        },
-        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, message: _ }) => {
-            debug!("Image '{}' doesn't exist in Docker daemon.", image.docker());
-        },
-        Err(source) => {
-            return Err(Error::ImageInspectError { image: Box::new(image), source });
-        },
+        Ok(_) => Ok(true),
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, message: _ }) => Ok(false),
+        Err(source) => Err(Error::ImageInspectError { image: Box::new(image), source }),
     }
+}
 
-    // Otherwise, import it if it is described or pull it
-    match source {
+/// Imports/pulls the given image, regardless of whether it already exists in the local Docker instance.
+///
+/// # Arguments
+/// - `docker`: An already connected local instance of Docker.
+/// - `image`: The Docker image name, version & potential digest to pull.
+/// - `source`: Where to get the image from.
+///
+/// # Errors
+/// This function errors if the import or pull failed.
+pub async fn load_image(docker: &Docker, image: impl Into<Image>, source: impl Into<ImageSource>) -> Result<(), Error> {
+    let image: Image = image.into();
+    match source.into() {
         ImageSource::Path(path) => {
             debug!(" > Importing file '{}'...", path.display());
             import_image(docker, image, path).await
@@ -1030,6 +1523,29 @@ pub async fn ensure_image(docker: &Docker, image: impl Into<Image>, source: impl
     }
 }
 
+/// Tries to import/pull the given image if it does not exist in the local Docker instance.
+///
+/// # Arguments
+/// - `docker`: An already connected local instance of Docker.
+/// - `image`: The Docker image name, version & potential digest to pull.
+/// - `source`: Where to get the image from should it not be present already.
+///
+/// # Errors
+/// This function errors if it failed to ensure the image existed (i.e., import or pull failed).
+pub async fn ensure_image(docker: &Docker, image: impl Into<Image>, source: impl Into<ImageSource>) -> Result<(), Error> {
+    let image: Image = image.into();
+
+    // Abort if image is already loaded
+    if image_exists(docker, image.clone()).await? {
+        debug!("Image '{}' already exists in Docker deamon.", image.docker());
+        return Ok(());
+    }
+    debug!("Image '{}' doesn't exist in Docker daemon.", image.docker());
+
+    // Otherwise, import it if it is described or pull it
+    load_image(docker, image, source).await
+}
+
 /// Saves an already pulled image to some file on disk.
 ///
 /// # Arguments
@@ -1046,14 +1562,6 @@ pub async fn save_image(docker: &Docker, image: impl Into<Image>, target: impl A
         target.display()
     );
 
-    // Open the output file
-    let mut handle: tio::BufWriter<tfs::File> = match tfs::File::create(target).await {
-        Ok(handle) => tio::BufWriter::new(handle),
-        Err(source) => {
-            return Err(Error::ImageFileCreateError { path: target.into(), source });
-        },
-    };
-
     // Decide the name of the image
     let name: String = if let Some(digest) = image.digest {
         digest
@@ -1061,25 +1569,10 @@ pub async fn save_image(docker: &Docker, image: impl Into<Image>, target: impl A
         format!("{}{}", image.name, if let Some(version) = image.version { format!(":{version}") } else { String::new() })
     };
 
-    // Read the image tar as raw bytes from the Daemon
-    let mut total: usize = 0;
-    let mut stream = docker.export_image(&name);
-    while let Some(chunk) = stream.next().await {
-        // Unwrap the chunk
-        let chunk = chunk.map_err(|source| Error::ImageExportError { name: name.clone(), source })?;
-        debug!("Next chunk: {} bytes", chunk.len());
-
-        // Write it to the file
-        handle.write(&chunk).await.map_err(|source| Error::ImageFileWriteError { path: target.into(), source })?;
-        debug!("Write OK");
-        total += chunk.len();
-    }
+    // Stream the image tar as raw bytes from the Daemon to the file
+    let total: usize = write_image_stream(docker.export_image(&name), &name, target).await?;
     println!("Total downloaded size: {total} bytes");
 
-    // Finish the stream & the handle
-    handle.flush().await.map_err(|source| Error::ImageFileShutdownError { path: target.into(), source })?;
-    handle.shutdown().await.map_err(|source| Error::ImageFileShutdownError { path: target.into(), source })?;
-
     // Done
     Ok(())
 }
@@ -1164,6 +1657,38 @@ pub async fn run_and_wait(opts: impl AsRef<DockerOptions>, exec: ExecuteInfo, ke
     join_container(&docker, &name, keep_container).await
 }
 
+/// Launches the given container and waits until its completed, sampling its resource usage while it runs.
+///
+/// Note that this function makes its own connection to the local Docker daemon.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `exec`: The ExecuteInfo describing what to launch and how.
+/// - `keep_container`: If true, then will not remove the container after it has been launched. This is very useful for debugging.
+///
+/// # Returns
+/// The return code of the docker container, its stdout and its stderr (in that order), together with the [`ContainerStats`] sampled while it
+/// ran.
+///
+/// # Errors
+/// This function errors for many reasons, some of which include not being able to connect to Docker or the container failing.
+pub async fn run_and_wait_with_stats(
+    opts: impl AsRef<DockerOptions>,
+    exec: ExecuteInfo,
+    keep_container: bool,
+) -> Result<((i32, String, String), ContainerStats), Error> {
+    // Connect to docker
+    let docker: Docker = connect_local(opts)?;
+
+    // Either import or pull image, if not already present
+    ensure_image(&docker, &exec.image, &exec.image_source).await?;
+
+    // Start the container, then sample it until it's done
+    let name: String = create_and_start_container(&docker, &exec).await?;
+    let stats: ContainerStats = sample_container(&docker, &name).await?;
+    Ok((collect_container(&docker, &name, keep_container).await?, stats))
+}
+
 /// Tries to return the (IP-)address of the container with the given name.
 ///
 /// Note that this function makes a separate connection to the local Docker instance.
@@ -1196,6 +1721,55 @@ pub async fn get_container_address(opts: impl AsRef<DockerOptions>, name: impl A
     }
 }
 
+/// Removes any (stopped) containers that were marked with a [`CLEANUP_AT_LABEL`] that has passed.
+///
+/// Note that this function makes a separate connection to the local Docker instance.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+///
+/// # Returns
+/// The names of the containers that were removed.
+///
+/// # Errors
+/// This function errors if we failed to list the containers or failed to remove any of them.
+pub async fn remove_expired_containers(opts: impl AsRef<DockerOptions>) -> Result<Vec<String>, Error> {
+    // Try to connect to the local instance
+    let docker: Docker = connect_local(opts)?;
+
+    // Find all containers with the label
+    let list_options = Some(ListContainersOptions::<String> {
+        all: true,
+        filters: HashMap::from([("label".into(), vec![CLEANUP_AT_LABEL.into()])]),
+        ..Default::default()
+    });
+    let containers = docker.list_containers(list_options).await.map_err(|source| Error::ListContainersError { source })?;
+
+    // Remove those that have expired
+    let now: u64 = SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or(0);
+    let mut removed: Vec<String> = Vec::new();
+    for container in containers {
+        let expired: bool = container
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(CLEANUP_AT_LABEL))
+            .and_then(|at| at.parse::<u64>().ok())
+            .is_some_and(|at| at <= now);
+        if !expired || container.state.as_deref() == Some("running") {
+            continue;
+        }
+        // Docker reports names with a leading slash
+        let name: String = match container.names.as_ref().and_then(|names| names.first()) {
+            Some(name) => name.trim_start_matches('/').into(),
+            None => continue,
+        };
+        debug!("Removing kept container '{}' (cleanup time has passed)...", name);
+        remove_container(&docker, &name).await?;
+        removed.push(name);
+    }
+    Ok(removed)
+}
+
 /// Tries to remove the docker image with the given name.
 ///
 /// Note that this function makes a separate connection to the local Docker instance.
@@ -1227,3 +1801,41 @@ pub async fn remove_image(opts: impl AsRef<DockerOptions>, image: &Image) -> Res
         Err(source) => Err(Error::ImageRemoveError { image: Box::new(image.clone()), id: info.id.clone().unwrap(), source }),
     }
 }
+
+/// Tags the given (already loaded) image with an additional version, i.e., `<name>:<version>`.
+///
+/// Note that this function makes a separate connection to the local Docker instance.
+///
+/// # Arguments
+/// - `opts`: The DockerOptions that contains information on how we can connect to the local daemon.
+/// - `image`: The image to tag.
+/// - `version`: The new version to tag it with.
+///
+/// # Returns
+/// Whether the image was loaded in the Docker engine and thus tagged.
+///
+/// # Errors
+/// This function errors if the Docker engine was not reachable, or if it failed to inspect (for reasons other than the image not being there) or to
+/// tag the image.
+pub async fn tag_image(opts: impl AsRef<DockerOptions>, image: &Image, version: impl Into<String>) -> Result<bool, Error> {
+    // Try to connect to the local instance
+    let docker: Docker = connect_local(opts)?;
+
+    // Check if the image exists
+    let info = match docker.inspect_image(&image.name()).await {
+        Ok(info) => info,
+        // It's not loaded, so nothing to tag
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, message: _ }) => return Ok(false),
+        Err(source) => return Err(Error::ImageInspectError { image: Box::new(image.clone()), source }),
+    };
+    let image_source: String = info.id.unwrap_or_else(|| image.name());
+
+    // Tag it
+    let options = Some(TagImageOptions { repo: image.name.clone(), tag: version.into() });
+    docker.tag_image(&image_source, options).await.map_err(|source| Error::ImageTagError {
+        image: Box::new(image.clone()),
+        image_source: image_source.clone(),
+        source,
+    })?;
+    Ok(true)
+}
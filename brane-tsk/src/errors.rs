@@ -22,7 +22,7 @@ use brane_ast::Workflow;
 use brane_ast::func_id::FunctionId;
 use brane_ast::locations::{Location, Locations};
 use brane_exe::pc::ProgramCounter;
-use brane_shr::formatters::{BlockFormatter, Capitalizeable};
+use brane_shr::formatters::{BlockFormatter, Capitalizeable, DidYouMeanFormatter};
 use enum_debug::EnumDebug as _;
 use reqwest::StatusCode;
 use serde_json::Value;
@@ -84,11 +84,21 @@ pub enum PlanError {
     #[error("Failed to parse response '{raw}' from '{address}' as valid JSON")]
     RequestParseError { address: String, raw: String, source: serde_json::Error },
     /// The planned domain does not support the task.
-    #[error("Location '{loc}' only supports capabilities {got:?}, whereas task '{task}' requires capabilities {expected:?}")]
-    UnsupportedCapabilities { task: String, loc: String, expected: HashSet<Capability>, got: HashSet<Capability> },
+    ///
+    /// `candidates` lists the locations that _do_ support all capabilities required by the task (if any).
+    #[error(
+        "Location '{loc}' only supports capabilities {got:?}, whereas task '{task}' requires capabilities {expected:?} (missing {:?}){}",
+        expected.difference(got).collect::<Vec<&Capability>>(),
+        if !candidates.is_empty() {
+            format!("; available at {}", candidates.iter().map(|l| format!("'{l}'")).collect::<Vec<String>>().join(", "))
+        } else {
+            String::new()
+        }
+    )]
+    UnsupportedCapabilities { task: String, loc: String, expected: HashSet<Capability>, got: HashSet<Capability>, candidates: Vec<String> },
     /// The given dataset was unknown to us.
-    #[error("Unknown dataset '{name}'")]
-    UnknownDataset { name: String },
+    #[error("Unknown dataset '{name}'{}", DidYouMeanFormatter::new(suggestions))]
+    UnknownDataset { name: String, suggestions: Vec<String> },
     /// The given intermediate result was unknown to us.
     #[error("Unknown intermediate result '{name}'")]
     UnknownIntermediateResult { name: String },
@@ -242,11 +252,18 @@ pub enum PreprocessError {
 pub enum ExecuteError {
     // General errors
     /// We encountered a package call that we didn't know.
-    #[error("Unknown package '{name}' (or it does not have version {version})")]
-    UnknownPackage { name: String, version: Version },
+    #[error("Unknown package '{name}' (or it does not have version {version}){}", DidYouMeanFormatter::new(suggestions))]
+    UnknownPackage { name: String, version: Version, suggestions: Vec<String> },
     /// We encountered a dataset/result that we didn't know.
     #[error("Unknown {} '{}'", name.variant(), name.name())]
     UnknownData { name: DataName },
+    /// A pattern for intermediate results matched more than one result, while only one was expected.
+    #[error(
+        "Intermediate result pattern '{}' is ambiguous; it matches {}",
+        name.name(),
+        matches.iter().map(|m| format!("'{}'", m.name())).collect::<Vec<String>>().join(", ")
+    )]
+    AmbiguousData { name: DataName, matches: Vec<DataName> },
     /// Failed to serialize task's input arguments
     #[error("Failed to serialize input arguments")]
     ArgsEncodeError { source: serde_json::Error },
@@ -329,6 +346,17 @@ pub enum ExecuteError {
     /// Failed to fetch the digest of an already existing image.
     #[error("Failed to read digest of image '{}'", path.display())]
     DigestError { path: PathBuf, source: DockerError },
+    /// Failed to pull the image of a package of which only the metadata was present.
+    #[error("Failed to pull the image of package '{name}' (version {version})")]
+    ImagePullError { name: String, version: Version, source: Box<dyn 'static + Send + Sync + Error> },
+    /// The image of a package has not been pulled, and we're not allowed to pull it.
+    #[error(
+        "The image of package '{name}' (version {version}) has not been pulled; pull it with `brane package pull` or don't pass '--no-auto-fetch'"
+    )]
+    ImageNotPulled { name: String, version: Version },
+    /// The pulled image of a package does not match the digest of the package we compiled against.
+    #[error("Pulled image of package '{name}' (version {version}) has digest '{got}', but the local package info expects '{expected}'")]
+    ImageDigestMismatch { name: String, version: Version, expected: String, got: String },
     /// Failed to create a reqwest proxy object.
     #[error("Failed to create proxy to '{address}'")]
     ProxyCreateError { address: Address, source: reqwest::Error },
@@ -379,6 +407,64 @@ pub enum ExecuteError {
     #[error("Failed to load backend file '{}'", path.display())]
     BackendFileError { path: PathBuf, source: brane_cfg::backend::Error },
 }
+impl ExecuteError {
+    /// Returns whether this error may be transient, i.e., whether executing the task again might succeed.
+    ///
+    /// Errors in the task itself or in talking to Docker, delegates or registries are considered retryable. Errors caused by the workflow or
+    /// the environment (e.g., unknown packages or data, or authorization denials) are not, as they will fail the same way again.
+    pub fn is_retryable(&self) -> bool {
+        use ExecuteError::*;
+        match self {
+            ExternalCallFailed { .. }
+            | DockerError { .. }
+            | GrpcConnectError { .. }
+            | GrpcRequestError { .. }
+            | ExecuteError { .. }
+            | DownloadRequestError { .. }
+            | DownloadStreamError { .. }
+            | PackageIndexError { .. }
+            | ImagePullError { .. }
+            | ProxyError { .. } => true,
+            // Only server-side errors might go away by themselves
+            DownloadRequestFailure { code, .. } => code.is_server_error(),
+
+            UnknownPackage { .. }
+            | UnknownData { .. }
+            | AmbiguousData { .. }
+            | ArgsEncodeError { .. }
+            | Base64DecodeError { .. }
+            | Utf8DecodeError { .. }
+            | JsonDecodeError { .. }
+            | VolumeBindError { .. }
+            | ResultDirNotADir { .. }
+            | ResultDirRemoveError { .. }
+            | ResultDirCreateError { .. }
+            | StatusEmptyStringError { .. }
+            | StatusValueParseError { .. }
+            | StatusTripletParseError { .. }
+            | ClientUpdateError { .. }
+            | NodeConfigReadError { .. }
+            | InfraReadError { .. }
+            | UnknownLocationError { .. }
+            | DigestReadError { .. }
+            | DigestError { .. }
+            | ImageNotPulled { .. }
+            | ImageDigestMismatch { .. }
+            | ProxyCreateError { .. }
+            | ClientCreateError { .. }
+            | ImageCreateError { .. }
+            | ImageWriteError { .. }
+            | IdWriteError { .. }
+            | IdReadError { .. }
+            | HashError { .. }
+            | HashWriteError { .. }
+            | HashReadError { .. }
+            | AuthorizationFailure { .. }
+            | AuthorizationError { .. }
+            | BackendFileError { .. } => false,
+        }
+    }
+}
 
 /// A special case of the execute error, this relates to authorization errors in the backend eFLINT reasoner (or other reasoners).
 #[derive(Debug, thiserror::Error)]
@@ -516,9 +602,12 @@ pub enum IdError {
 /// Note: we've boxed `Image` to reduce the size of the error (and avoid running into `clippy::result_large_err`).
 #[derive(Debug, thiserror::Error)]
 pub enum DockerError {
-    /// We failed to connect to the local Docker daemon.
-    #[error("Failed to connect to the local Docker daemon through socket '{}' and with client version {}", path.display(), version)]
-    ConnectionError { path: PathBuf, version: ClientVersion, source: bollard::errors::Error },
+    /// We failed to connect to the Docker daemon.
+    #[error("Failed to connect to the Docker daemon at '{host}' with client version {version}")]
+    ConnectionError { host: String, version: ClientVersion, source: bollard::errors::Error },
+    /// We cannot connect to the given kind of Docker host.
+    #[error("Cannot connect to the Docker daemon at '{host}': {reason}")]
+    UnsupportedHost { host: String, reason: &'static str },
 
     /// Failed to wait for the container with the given name.
     #[error("Failed to wait for Docker container with name '{name}'")]
@@ -551,6 +640,9 @@ pub enum DockerError {
     /// Failed to remove the given container.
     #[error("Fialed to remove Docker container with name '{name}'")]
     ContainerRemoveError { name: String, source: bollard::errors::Error },
+    /// Failed to list the containers known to the Docker daemon.
+    #[error("Failed to list Docker containers")]
+    ListContainersError { source: bollard::errors::Error },
 
     /// Failed to open the given image file.
     #[error("Failed to open image file '{}'", path.display())]
@@ -567,6 +659,9 @@ pub enum DockerError {
     /// Failed to write a chunk of the exported image.
     #[error("Failed to write to image file '{}'", path.display())]
     ImageFileWriteError { path: PathBuf, source: std::io::Error },
+    /// The task writing the exported image to disk panicked or was cancelled.
+    #[error("Failed to wait for the writer of image file '{}'", path.display())]
+    ImageFileWriterJoinError { path: PathBuf, source: tokio::task::JoinError },
     /// Failed to shutdown the given file.
     #[error("Failed to shut image file '{}' down", path.display())]
     ImageFileShutdownError { path: PathBuf, source: std::io::Error },
@@ -617,6 +712,41 @@ pub enum DockerError {
     ImageTarNoManifest { path: PathBuf },
 }
 
+/// Collects errors that relate to generating or verifying checksum manifests.
+#[derive(Debug, thiserror::Error)]
+pub enum ChecksumError {
+    /// A file's digest does not match the one in the manifest.
+    #[error("Checksum mismatch for '{}': manifest lists '{}', but file has '{}'", path.display(), expected, got)]
+    DigestMismatch { path: PathBuf, expected: String, got: String },
+    /// Failed to read a directory.
+    #[error("Failed to read directory '{}'", path.display())]
+    DirReadError { path: PathBuf, source: std::io::Error },
+    /// Failed to open a file to hash.
+    #[error("Failed to open file '{}'", path.display())]
+    FileOpenError { path: PathBuf, source: std::io::Error },
+    /// Failed to read a file to hash.
+    #[error("Failed to read file '{}'", path.display())]
+    FileReadError { path: PathBuf, source: std::io::Error },
+    /// A line in the manifest lists a path that would escape the directory.
+    #[error("Line {line} in manifest '{}' lists '{file}', which is not a relative path inside the directory", path.display())]
+    IllegalPath { path: PathBuf, line: usize, file: String },
+    /// A line in the manifest was not a `<DIGEST>  <PATH>` pair.
+    #[error("Line {line} in manifest '{}' is not a '<DIGEST>  <PATH>' pair: '{raw}'", path.display())]
+    ManifestParseError { path: PathBuf, line: usize, raw: String },
+    /// Failed to read the manifest.
+    #[error("Failed to read manifest '{}'", path.display())]
+    ManifestReadError { path: PathBuf, source: std::io::Error },
+    /// Failed to write the manifest.
+    #[error("Failed to write manifest '{}'", path.display())]
+    ManifestWriteError { path: PathBuf, source: std::io::Error },
+    /// A file listed in the manifest does not exist.
+    #[error("File '{}' is listed in the manifest but does not exist", path.display())]
+    MissingFile { path: PathBuf },
+    /// A file exists that is not listed in the manifest.
+    #[error("File '{}' is not listed in the manifest", path.display())]
+    UnlistedFile { path: PathBuf },
+}
+
 /// Collects errors that relate to local index interaction.
 #[derive(Debug, thiserror::Error)]
 pub enum LocalError {
@@ -639,6 +769,12 @@ pub enum LocalError {
     /// We tried to load a package YML but failed
     #[error("Could not read '{}' for package '{}'", path.display(), package)]
     InvalidPackageYml { package: String, path: PathBuf, source: specifications::package::PackageInfoError },
+    /// A version directory of a package has no package YML
+    #[error("Could not find '{}' for package '{}'", path.display(), package)]
+    MissingPackageYml { package: String, path: PathBuf },
+    /// A package YML describes another package than the directory it is stored in
+    #[error("'{}' describes package '{got_name}' version {got_version}, but is stored as package '{name}' version {version}", path.display())]
+    PackageYmlMismatch { path: PathBuf, name: String, version: Version, got_name: String, got_version: Version },
     /// We tried to load a Package Index from a JSON value with PackageInfos but we failed
     #[error("Could not create PackageIndex")]
     PackageIndexError { source: specifications::package::PackageIndexError },
@@ -660,6 +796,9 @@ pub enum LocalError {
 /// Collects errors that relate to API interaction.
 #[derive(Debug, thiserror::Error)]
 pub enum ApiError {
+    /// Failed to build the client with which to send a request.
+    #[error("Failed to build HTTP client for '{address}'")]
+    ClientBuildError { address: String, source: reqwest::Error },
     /// Failed to send a GraphQL request.
     #[error("Failed to post request to '{address}'")]
     RequestError { address: String, source: reqwest::Error },
@@ -679,6 +818,9 @@ pub enum ApiError {
     /// Failed to parse the package's version in a package info.
     #[error("Failed to parse '{raw}' as version in package {index} returned by '{address}'")]
     VersionParseError { address: String, index: usize, raw: String, source: specifications::version::ParseError },
+    /// Failed to parse the package's labels in a package info.
+    #[error("Failed to parse '{raw}' as labels in package {index} returned by '{address}'")]
+    LabelsParseError { address: String, index: usize, raw: String, source: serde_json::Error },
     /// Failed to create a package index from the given infos.
     #[error("Failed to create a package index from the package infos given by '{address}'")]
     PackageIndexError { address: String, source: specifications::package::PackageIndexError },
@@ -688,6 +830,50 @@ pub enum ApiError {
     DataIndexError { address: String, source: specifications::data::DataIndexError },
 }
 
+impl ApiError {
+    /// Returns whether this error may be transient, i.e., whether sending the request again might succeed.
+    ///
+    /// Only failures to reach the API (connection errors and timeouts), server-side errors, rate limiting and interrupted bodies are
+    /// considered retryable. Anything the API did answer with (e.g., an ill-formed index) will fail the same way again.
+    pub fn is_retryable(&self) -> bool {
+        use ApiError::*;
+        match self {
+            RequestError { source, .. } => {
+                source.is_connect()
+                    || source.is_timeout()
+                    || source.status().map(|code| code.is_server_error() || code == reqwest::StatusCode::TOO_MANY_REQUESTS).unwrap_or(false)
+            },
+            ResponseBodyError { .. } => true,
+
+            ClientBuildError { .. }
+            | ResponseJsonParseError { .. }
+            | NoResponse { .. }
+            | PackageKindParseError { .. }
+            | VersionParseError { .. }
+            | LabelsParseError { .. }
+            | PackageIndexError { .. }
+            | DataIndexError { .. } => false,
+        }
+    }
+}
+
+/// Errors that relate to parsing Docker host addresses.
+#[derive(Debug, thiserror::Error)]
+pub enum DockerHostParseError {
+    /// The address after the scheme was empty.
+    #[error("Missing address after the scheme in Docker host '{raw}'")]
+    EmptyAddress { raw: String },
+    /// The scheme is not one we know.
+    #[error("Unknown scheme '{scheme}' in Docker host '{raw}' (expected 'unix', 'npipe', 'tcp' or 'http')")]
+    UnknownScheme { raw: String, scheme: String },
+    /// The host is an SSH host, which we cannot connect to.
+    #[error(
+        "Cannot connect to Docker host '{raw}' over SSH; forward the remote socket with 'ssh -NL /tmp/docker.sock:/var/run/docker.sock <HOST>' and \
+         use 'unix:///tmp/docker.sock' instead"
+    )]
+    SshUnsupported { raw: String },
+}
+
 /// Errors that relate to parsing Docker client version numbers.
 #[derive(Debug, thiserror::Error)]
 pub enum ClientVersionParseError {
@@ -42,7 +42,7 @@ use serde_json::json;
 use tokio::fs as tfs;
 
 use brane_shr::errors::ErrorTrace as _;
-use brane_shr::fs::{download_file_async, set_executable, unarchive_async, DownloadSecurity};
+use brane_shr::fs::{download_file_async, download_progress_bar, set_executable, unarchive_async, DownloadSecurity};
 use specifications::address::Address;
 use specifications::container::Image;
 use specifications::version::Version;
@@ -350,7 +350,10 @@ async fn ensure_crane_exe(path: impl AsRef<Path>, temp_dir: impl AsRef<Path>) ->
     // Otherwise, we should attempt to download the crane executable's tarball
     let tar_path: PathBuf = temp_dir.join("go-containerregistry_Linux.tar.gz");
     debug!("Downloading '{}' to '{}'...", URL, tar_path.display());
-    if let Err(err) = download_file_async(URL, &tar_path, DownloadSecurity::all(&CRANE_TAR_CHECKSUM), None).await {
+    let (prgs, mut progress) = download_progress_bar("Downloading crane...");
+    let res = download_file_async(URL, &tar_path, DownloadSecurity::all(&CRANE_TAR_CHECKSUM), None, Some(&mut progress)).await;
+    prgs.finish_and_clear();
+    if let Err(err) = res {
         return Err(CraneError::DownloadCraneTar { from: URL, to: tar_path, err });
     }
 
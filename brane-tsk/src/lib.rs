@@ -17,11 +17,13 @@
 // Declare modules
 pub mod api;
 pub mod caches;
+pub mod checksums;
 pub mod docker;
 pub mod errors;
 pub mod input;
 // pub mod k8s;
 pub mod local;
+pub mod retry;
 pub mod spec;
 pub mod tools;
 
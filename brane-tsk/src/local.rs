@@ -24,6 +24,58 @@ use specifications::version::Version;
 pub use crate::errors::LocalError as Error;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use specifications::package::PackageKind;
+
+    use super::*;
+
+
+    /// Writes a valid package with the given name and version to the given packages directory.
+    fn write_package(packages_dir: &Path, name: &str, version: &str) {
+        let version: Version = Version::from_str(version).unwrap();
+        let info: PackageInfo =
+            PackageInfo::new(name.into(), version, PackageKind::Ecu, vec![], String::new(), false, HashMap::new(), HashMap::new());
+        let dir: PathBuf = packages_dir.join(name).join(version.to_string());
+        fs::create_dir_all(&dir).unwrap();
+        info.to_path(dir.join("package.yml")).unwrap();
+    }
+
+
+    /// Tests whether broken entries are skipped (and reported) instead of failing the whole index.
+    #[test]
+    fn test_scan_package_index() {
+        let dir: PathBuf = std::env::temp_dir().join(format!("brane-tsk-local-{}", uuid::Uuid::new_v4()));
+        write_package(&dir, "hello", "1.0.0");
+        write_package(&dir, "hello", "2.0.0");
+        // A half-pulled version, a garbage package.yml, one that describes another package, and an empty package
+        fs::create_dir_all(dir.join("hello").join("3.0.0")).unwrap();
+        fs::create_dir_all(dir.join("world").join("1.0.0")).unwrap();
+        fs::write(dir.join("world").join("1.0.0").join("package.yml"), "this is not a package").unwrap();
+        write_package(&dir, "other", "1.0.0");
+        fs::rename(dir.join("other").join("1.0.0"), dir.join("other").join("1.0.1")).unwrap();
+        fs::create_dir_all(dir.join("empty")).unwrap();
+
+        let (index, mut broken): (PackageIndex, Vec<(PathBuf, Error)>) = scan_package_index(&dir).unwrap();
+        assert_eq!(index.packages.len(), 2);
+        broken.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        assert!(matches!(broken.as_slice(), [
+            (_, Error::NoVersions { .. }),
+            (_, Error::MissingPackageYml { .. }),
+            (_, Error::PackageYmlMismatch { .. }),
+            (_, Error::InvalidPackageYml { .. }),
+        ]));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+
+
+
+
 /***** AUXILLARY FUNCTIONS *****/
 /// Collects a list of versions in the given package directory.
 ///
@@ -133,6 +185,108 @@ pub fn get_package_index(packages: impl AsRef<Path>) -> Result<PackageIndex, Err
 
 
 
+/// Scans the local packages directory for an index like [`get_package_index()`] does, except that broken entries are skipped instead of
+/// failing the whole index.
+///
+/// A version directory is broken if it is not named after a version, if it has no `package.yml`, or if that file is unreadable or describes
+/// another package. A package directory is broken if it has no version directories at all.
+///
+/// # Arguments
+/// - `packages`: The path to the directory that we read the packages from.
+///
+/// # Returns
+/// A [`PackageIndex`] with all valid packages, together with the path of every broken entry and why it is broken.
+///
+/// # Errors
+/// This function errors if we failed to read the local packages folder itself or to create the index.
+pub fn scan_package_index(packages: impl AsRef<Path>) -> Result<(PackageIndex, Vec<(PathBuf, Error)>), Error> {
+    let packages_path: &Path = packages.as_ref();
+
+    // Open an iterator to the list of files
+    let package_dirs = fs::read_dir(packages_path).map_err(|source| Error::PackagesDirReadError { path: packages_path.into(), source })?;
+
+    // Start iterating through all the packages
+    let mut packages: Vec<PackageInfo> = vec![];
+    let mut broken: Vec<(PathBuf, Error)> = vec![];
+    for package in package_dirs {
+        let package = package.map_err(|source| Error::PackagesDirReadError { path: packages_path.into(), source })?;
+
+        // Make sure it's a directory
+        let package_path: PathBuf = package.path();
+        if !package_path.is_dir() {
+            continue;
+        }
+        let package_name: String = package.file_name().to_string_lossy().into_owned();
+        let version_dirs: ReadDir = match fs::read_dir(&package_path) {
+            Ok(version_dirs) => version_dirs,
+            Err(source) => {
+                broken.push((package_path.clone(), Error::PackageDirReadError { path: package_path, source }));
+                continue;
+            },
+        };
+
+        // Check every version in it
+        let mut found: usize = 0;
+        for dir in version_dirs {
+            let version_path: PathBuf = match dir {
+                Ok(dir) => dir.path(),
+                Err(source) => {
+                    broken.push((package_path.clone(), Error::PackageDirReadError { path: package_path.clone(), source }));
+                    break;
+                },
+            };
+            if !version_path.is_dir() {
+                continue;
+            }
+            found += 1;
+
+            // The directory must be named after its version...
+            let dir_name: String = version_path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let version: Version = match Version::from_str(&dir_name) {
+                Ok(version) => version,
+                Err(source) => {
+                    broken.push((version_path, Error::IllegalVersionEntry { package: package_name.clone(), version: dir_name, source }));
+                    continue;
+                },
+            };
+
+            // ...and have a package.yml that describes it
+            let package_file: PathBuf = version_path.join("package.yml");
+            if !package_file.exists() {
+                broken.push((version_path, Error::MissingPackageYml { package: package_name.clone(), path: package_file }));
+                continue;
+            }
+            let info: PackageInfo = match PackageInfo::from_path(package_file.clone()) {
+                Ok(info) => info,
+                Err(source) => {
+                    broken.push((version_path, Error::InvalidPackageYml { package: package_name.clone(), path: package_file, source }));
+                    continue;
+                },
+            };
+            if info.name != package_name || info.version != version {
+                broken.push((version_path, Error::PackageYmlMismatch {
+                    path: package_file,
+                    name: package_name.clone(),
+                    version,
+                    got_name: info.name,
+                    got_version: info.version,
+                }));
+                continue;
+            }
+            packages.push(info);
+        }
+        if found == 0 {
+            broken.push((package_path, Error::NoVersions { package: package_name }));
+        }
+    }
+
+    // Generate the package index from the valid packages
+    let index: PackageIndex = PackageIndex::from_value(json!(packages)).map_err(|source| Error::PackageIndexError { source })?;
+    Ok((index, broken))
+}
+
+
+
 /// Returns the an index of locally available datasets.
 ///
 /// # Arguments
@@ -163,7 +317,8 @@ pub fn get_data_index(datasets_path: impl AsRef<Path>) -> Result<DataIndex, Erro
             let handle = File::open(&info_path).map_err(|source| Error::DataInfoOpenError { path: info_path.clone(), source })?;
 
             // Attempt to parse it
-            let info: DataInfo = serde_yaml::from_reader(handle).map_err(|source| Error::DataInfoReadError { path: info_path, source })?;
+            let mut info: DataInfo = serde_yaml::from_reader(handle).map_err(|source| Error::DataInfoReadError { path: info_path, source })?;
+            info.fill_legacy_created(&d_path);
 
             // Add it to the index
             infos.push(info);
@@ -0,0 +1,146 @@
+//  RETRY.rs
+//
+//  Description:
+//!   Implements retrying tasks (or other remote operations) that failed
+//!   with a transient error (see [`Retryable`]).
+//
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::errors::{ApiError, ExecuteError};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Formatter, Result as FResult};
+
+    use super::*;
+
+
+    /// An error that is retryable or not.
+    #[derive(Debug, Eq, PartialEq)]
+    struct TestError(bool);
+    impl Display for TestError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}", if self.0 { "retryable" } else { "fatal" }) }
+    }
+    impl Retryable for TestError {
+        fn is_retryable(&self) -> bool { self.0 }
+    }
+
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retries() {
+        // Succeeds on the third attempt, after waiting for the backoff of the first two
+        let start = tokio::time::Instant::now();
+        let mut attempts: Vec<u32> = vec![];
+        let res = execute_with_retries("test", 3, |i| {
+            attempts.push(i);
+            async move { if i < 3 { Err(TestError(true)) } else { Ok(i) } }
+        })
+        .await;
+        assert_eq!(res, Ok(3));
+        assert_eq!(attempts, vec![1, 2, 3]);
+        assert_eq!(start.elapsed(), RETRY_BACKOFF * 3);
+
+        // Gives up after the retries run out, with the last error
+        let mut attempts: u32 = 0;
+        let res: Result<(), TestError> = execute_with_retries("test", 2, |_| {
+            attempts += 1;
+            async { Err(TestError(true)) }
+        })
+        .await;
+        assert_eq!(res, Err(TestError(true)));
+        assert_eq!(attempts, 3);
+
+        // Never retries without retries
+        let mut attempts: u32 = 0;
+        let res: Result<(), TestError> = execute_with_retries("test", 0, |_| {
+            attempts += 1;
+            async { Err(TestError(true)) }
+        })
+        .await;
+        assert_eq!(res, Err(TestError(true)));
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_execute_with_retries_fatal() {
+        // Non-retryable errors are returned immediately, even if they follow a retryable one
+        let mut attempts: u32 = 0;
+        let res: Result<(), TestError> = execute_with_retries("test", 5, |i| {
+            attempts += 1;
+            async move { Err(TestError(i == 1)) }
+        })
+        .await;
+        assert_eq!(res, Err(TestError(false)));
+        assert_eq!(attempts, 2);
+    }
+}
+
+
+/***** CONSTANTS *****/
+/// The time to wait before the first retry. Every next retry waits this long times the attempt number.
+pub const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+
+
+
+
+/***** LIBRARY *****/
+/// Abstracts over errors that know whether they may be transient.
+pub trait Retryable {
+    /// Returns whether this error may be transient, i.e., whether trying again might succeed.
+    fn is_retryable(&self) -> bool;
+}
+impl Retryable for ExecuteError {
+    #[inline]
+    fn is_retryable(&self) -> bool { ExecuteError::is_retryable(self) }
+}
+impl Retryable for ApiError {
+    #[inline]
+    fn is_retryable(&self) -> bool { ApiError::is_retryable(self) }
+}
+
+
+
+/// Executes a task, retrying it up to `max_retries` times if it fails with a retryable error.
+///
+/// # Arguments
+/// - `name`: The name of the task (or operation), used for logging.
+/// - `max_retries`: The maximum number of times to re-execute the task after the first attempt. `0` means no retries.
+/// - `attempt`: A closure that executes the task once. It is given the (one-indexed) number of the attempt.
+///
+/// # Returns
+/// The result of the first successful attempt.
+///
+/// # Errors
+/// This function errors with the error of the last attempt if it failed with a non-retryable error or if we ran out of retries.
+pub async fn execute_with_retries<T, E, F, Fut>(name: &str, max_retries: u32, mut attempt: F) -> Result<T, E>
+where
+    E: Retryable + Display,
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut i: u32 = 1;
+    loop {
+        match attempt(i).await {
+            Ok(res) => return Ok(res),
+            Err(err) if i <= max_retries && err.is_retryable() => {
+                warn!("'{}' failed on attempt {}/{}; retrying in {}s: {}", name, i, max_retries + 1, (RETRY_BACKOFF * i).as_secs(), err);
+                tokio::time::sleep(RETRY_BACKOFF * i).await;
+                i += 1;
+            },
+            Err(err) => {
+                if i > 1 {
+                    warn!("'{}' failed on attempt {}/{}; giving up", name, i, max_retries + 1);
+                }
+                return Err(err);
+            },
+        }
+    }
+}
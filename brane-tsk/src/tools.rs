@@ -12,9 +12,12 @@
 //!   Contains generic tools to use across the use-cases.
 //
 
+use std::collections::HashMap;
+
 use base64::Engine as _;
+use specifications::data::{AvailabilityKind, DataName};
 
-use crate::errors::ExecuteError;
+use crate::errors::{ExecuteError, PlanError};
 
 
 /***** LIBRARY *****/
@@ -40,3 +43,44 @@ pub fn decode_base64(raw: impl AsRef<str>) -> Result<String, ExecuteError> {
 
     // We leave JSON for another day
 }
+
+/// Expands any intermediate result patterns in the input of a task to the (known) intermediate results they match.
+///
+/// Every pattern (see [`DataName::is_pattern()`]) that matches at least one result is replaced by one (unplanned) input per matching result, such that
+/// the planner may plan them like any other input.
+///
+/// # Arguments
+/// - `input`: The input map of the task to expand.
+/// - `results`: The map of intermediate results known so far (mapped to their locations).
+/// - `deferred`: If true, patterns that do not match anything (yet) are kept for a later planning pass instead of erroring.
+///
+/// # Errors
+/// This function errors if a pattern does not match any known intermediate result and `deferred` is false.
+pub fn expand_result_patterns(
+    input: &mut HashMap<DataName, Option<AvailabilityKind>>,
+    results: &HashMap<String, String>,
+    deferred: bool,
+) -> Result<(), PlanError> {
+    // Collect the patterns first to dodge the borrow checker
+    let patterns: Vec<DataName> = input.iter().filter(|(name, avail)| avail.is_none() && name.is_pattern()).map(|(name, _)| name.clone()).collect();
+    for pattern in patterns {
+        // Find all known results matching it
+        let matches: Vec<DataName> =
+            results.keys().map(|name| DataName::IntermediateResult(name.clone())).filter(|name| pattern.matches(name)).collect();
+        if matches.is_empty() {
+            if !deferred {
+                return Err(PlanError::UnknownIntermediateResult { name: pattern.into_name() });
+            }
+            continue;
+        }
+
+        // Replace the pattern with its matches
+        input.remove(&pattern);
+        for name in matches {
+            input.entry(name).or_insert(None);
+        }
+    }
+
+    // Done
+    Ok(())
+}
@@ -13,6 +13,48 @@ use crate::package::{Capability, PackageKind};
 use crate::version::Version;
 
 
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_package_manager_from_base_image() {
+        assert_eq!(PackageManager::from_base_image("ubuntu:20.04"), PackageManager::Apt);
+        assert_eq!(PackageManager::from_base_image("alpine"), PackageManager::Apk);
+        assert_eq!(PackageManager::from_base_image("alpine:3.19"), PackageManager::Apk);
+        assert_eq!(PackageManager::from_base_image("alpine@sha256:0123abcd"), PackageManager::Apk);
+        assert_eq!(PackageManager::from_base_image("docker.io/library/alpine:latest"), PackageManager::Apk);
+        assert_eq!(PackageManager::from_base_image("python:3.12-alpine"), PackageManager::Apk);
+        assert_eq!(PackageManager::from_base_image("localhost:5000/python:3.12-alpine3.19"), PackageManager::Apk);
+        assert_eq!(PackageManager::from_base_image("scratch"), PackageManager::Unmanaged);
+        assert_eq!(PackageManager::from_base_image("gcr.io/distroless/cc-debian12"), PackageManager::Unmanaged);
+
+        // Images without one are still declared as `none`
+        assert_eq!(serde_yaml::from_str::<PackageManager>("none").unwrap(), PackageManager::Unmanaged);
+        assert_eq!(serde_yaml::to_string(&PackageManager::Unmanaged).unwrap().trim(), "none");
+    }
+
+    #[test]
+    fn test_image_digest() {
+        let image = Image::new("hello", Some("1.0.0"), Some("sha256:0123abcd"));
+        assert!(image.has_digest("sha256:0123abcd"));
+        assert!(image.has_digest("0123abcd"));
+        assert!(!image.has_digest("sha256:4567ef01"));
+        assert!(!Image::new("hello", Some("1.0.0"), None::<String>).has_digest("0123abcd"));
+
+        // Names don't matter, but both images need a digest
+        assert!(image.same_digest(&Image::new("hello", Some("1.1.0"), Some("0123abcd"))));
+        assert!(!image.same_digest(&Image::new("hello", Some("1.0.0"), Some("sha256:4567ef01"))));
+        assert!(!image.same_digest(&Image::new("hello", Some("1.0.0"), None::<String>)));
+    }
+}
+
+
+
+
+
 /***** CUSTOM TYPES *****/
 type Map<T> = std::collections::HashMap<String, T>;
 
@@ -20,6 +62,22 @@ type Map<T> = std::collections::HashMap<String, T>;
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Compares two image digests, where either may omit the `sha256:`-prefix.
+///
+/// # Arguments
+/// - `lhs`: The one digest to compare.
+/// - `rhs`: The other digest to compare.
+///
+/// # Returns
+/// True if both denote the same digest, or false otherwise.
+#[inline]
+pub fn digests_equal(lhs: &str, rhs: &str) -> bool { lhs.trim_start_matches("sha256:") == rhs.trim_start_matches("sha256:") }
+
+
+
+
+
 /***** ERRORS *****/
 /// Defines error(s) for the [`VolumeBind`] struct.
 #[derive(Debug, thiserror::Error)]
@@ -255,6 +313,26 @@ impl Image {
     #[inline]
     pub fn digest(&self) -> Option<&str> { self.digest.as_deref() }
 
+    /// Checks whether this Image has the given digest.
+    ///
+    /// # Arguments
+    /// - `digest`: The digest to compare with. The `sha256:`-prefix is optional, on either side.
+    ///
+    /// # Returns
+    /// True if this Image has a digest and it equals `digest`, or false otherwise.
+    #[inline]
+    pub fn has_digest(&self, digest: &str) -> bool { self.digest.as_deref().is_some_and(|own| digests_equal(own, digest)) }
+
+    /// Checks whether this Image is the same image as another by digest, regardless of their names or versions.
+    ///
+    /// # Arguments
+    /// - `other`: The other Image to compare with.
+    ///
+    /// # Returns
+    /// True if both Images have a digest and they are equal, or false otherwise.
+    #[inline]
+    pub fn same_digest(&self, other: &Image) -> bool { other.digest.as_deref().is_some_and(|digest| self.has_digest(digest)) }
+
     /// Returns the Docker-compatible serialization of this Image.
     ///
     /// # Returns
@@ -450,6 +528,9 @@ pub struct ContainerInfo {
 
     /// The base image to use for the package image.
     pub base: Option<String>,
+    /// The package manager with which to install dependencies in the base image. Deduced from the base image if omitted.
+    #[serde(alias = "package_manager", alias = "package-manager")]
+    pub package_manager: Option<PackageManager>,
     /// The dependencies, as install commands for sudo apt-get install -y <...>
     pub dependencies: Option<Vec<String>>,
     /// Any environment variables that the user wants to be set
@@ -548,6 +629,59 @@ impl ContainerInfo {
 
 
 
+/// Defines the package managers with which dependencies can be installed in a package image.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    /// Debian-family images (`apt-get`).
+    Apt,
+    /// Alpine-family images (`apk`).
+    Apk,
+    /// The image has no package manager (e.g., distroless images); it must provide everything itself. Written as `none`.
+    #[serde(rename = "none")]
+    Unmanaged,
+}
+
+impl PackageManager {
+    /// Deduces the package manager of a base image from its name.
+    ///
+    /// Images named `alpine`, or with `alpine` in their tag (e.g., `python:3.12-alpine`), use `apk`; `scratch` and distroless images have
+    /// none; anything else is assumed to use `apt`.
+    ///
+    /// # Arguments
+    /// - `base`: The base image reference (e.g., `ubuntu:20.04`, `docker.io/library/alpine@sha256:...`).
+    ///
+    /// # Returns
+    /// The deduced [`PackageManager`].
+    pub fn from_base_image(base: &str) -> Self {
+        // Strip any digest, then split off the registry/repository path and the tag
+        let base: &str = base.split_once('@').map(|(base, _)| base).unwrap_or(base);
+        let (path, name): (&str, &str) = base.rsplit_once('/').unwrap_or(("", base));
+        let (name, tag): (&str, &str) = name.split_once(':').unwrap_or((name, ""));
+
+        if name == "alpine" || tag.contains("alpine") {
+            Self::Apk
+        } else if (path.is_empty() && name == "scratch") || path.split('/').any(|segment| segment == "distroless") {
+            Self::Unmanaged
+        } else {
+            Self::Apt
+        }
+    }
+}
+
+impl Display for PackageManager {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Self::Apt => write!(f, "apt"),
+            Self::Apk => write!(f, "apk"),
+            Self::Unmanaged => write!(f, "none"),
+        }
+    }
+}
+
+
+
 /// Defines the YAML of an action in a package.
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -595,4 +729,6 @@ pub struct Entrypoint {
     pub exec:    String,
     pub content: Option<String>,
     pub delay:   Option<u64>,
+    /// Default arguments that are always passed to `exec`, before any of the action's own arguments.
+    pub args:    Option<Vec<String>>,
 }
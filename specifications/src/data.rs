@@ -13,17 +13,131 @@
 //!   registries and datasets.
 //
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use enum_debug::EnumDebug;
 use serde::{Deserialize, Serialize};
 
 
+/***** UNIT TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn test_glob_matches() {
+        // Literal names
+        assert!(glob_matches("result_foo", "result_foo"));
+        assert!(!glob_matches("result_foo", "result_fo"));
+        assert!(!glob_matches("result_foo", "result_foo2"));
+
+        // Stars
+        assert!(glob_matches("result_*", "result_"));
+        assert!(glob_matches("result_*", "result_foo"));
+        assert!(glob_matches("*_foo", "result_foo"));
+        assert!(glob_matches("r*_*o", "result_foo"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("result_*", "resul"));
+        assert!(!glob_matches("*_bar", "result_foo"));
+
+        // Question marks
+        assert!(glob_matches("result_???", "result_foo"));
+        assert!(!glob_matches("result_??", "result_foo"));
+        assert!(glob_matches("r?sult_*", "result_foo"));
+    }
+
+    #[test]
+    fn test_data_name_resolve_in() {
+        let mut map: HashMap<DataName, u32> = HashMap::new();
+        map.insert(DataName::IntermediateResult("result_a1".into()), 1);
+        map.insert(DataName::IntermediateResult("result_a2".into()), 2);
+        map.insert(DataName::IntermediateResult("result_b1".into()), 3);
+        map.insert(DataName::Data("result_c1".into()), 4);
+
+        // Literal lookups
+        assert_eq!(DataName::IntermediateResult("result_a1".into()).resolve_in(&map).unwrap().map(|(_, v)| *v), Some(1));
+        assert_eq!(DataName::IntermediateResult("result_z".into()).resolve_in(&map).unwrap(), None);
+
+        // Patterns matching one result (datasets are never matched)
+        assert_eq!(DataName::IntermediateResult("result_b*".into()).resolve_in(&map).unwrap().map(|(_, v)| *v), Some(3));
+        assert_eq!(DataName::IntermediateResult("result_c*".into()).resolve_in(&map).unwrap(), None);
+
+        // Ambiguous patterns
+        let mut matches: Vec<DataName> = DataName::IntermediateResult("result_a?".into()).resolve_in(&map).unwrap_err();
+        matches.sort_by(|lhs, rhs| lhs.name().cmp(rhs.name()));
+        assert_eq!(matches, vec![DataName::IntermediateResult("result_a1".into()), DataName::IntermediateResult("result_a2".into())]);
+    }
+
+    #[test]
+    fn test_data_name_parse() {
+        // Prefixed and bare names
+        assert_eq!(DataName::from_str("data:test").unwrap(), DataName::Data("test".into()));
+        assert_eq!(DataName::from_str("result:result_foo").unwrap(), DataName::IntermediateResult("result_foo".into()));
+        assert_eq!(DataName::from_str("test").unwrap(), DataName::Data("test".into()));
+
+        // Errors
+        assert!(matches!(DataName::from_str(""), Err(DataNameParseError::EmptyName { .. })));
+        assert!(matches!(DataName::from_str("result:"), Err(DataNameParseError::EmptyName { .. })));
+        assert!(matches!(DataName::from_str("dataset:test"), Err(DataNameParseError::UnknownKind { .. })));
+
+        // Round-trips
+        for name in [DataName::Data("test".into()), DataName::IntermediateResult("result_a?".into())] {
+            assert_eq!(DataName::from_str(&name.to_string()).unwrap(), name);
+        }
+    }
+
+    #[test]
+    fn test_data_info_legacy_created() {
+        let raw: &str = "name: test\nowners: null\ndescription: null\naccess:\n  localhost:\n    file:\n      path: /data/test\n";
+        let mut info: DataInfo = DataInfo::from_reader(raw.as_bytes()).unwrap();
+        assert_eq!(info.created, DateTime::<Utc>::UNIX_EPOCH);
+
+        // It falls back to the time the directory was last modified
+        let dir: PathBuf = std::env::temp_dir();
+        info.fill_legacy_created(&dir);
+        assert_eq!(info.created, DateTime::<Utc>::from(std::fs::metadata(&dir).unwrap().modified().unwrap()));
+
+        // Recorded timestamps are left alone
+        let created: DateTime<Utc> = Utc::now();
+        info.created = created;
+        info.fill_legacy_created(&dir);
+        assert_eq!(info.created, created);
+    }
+
+    #[test]
+    fn test_data_index_layouts() {
+        let info: &str =
+            r#"{ "name": "test", "owners": null, "description": null, "access": { "localhost": { "file": { "path": "/data/test" } } } }"#;
+
+        // Serialized indices
+        let index: DataIndex = DataIndex::from_reader(format!(r#"{{ "index": {{ "test": {info} }} }}"#).as_bytes()).unwrap();
+        assert!(index.get("test").is_some());
+
+        // Flat maps, as returned by `/data/info`
+        let index: DataIndex = DataIndex::from_reader(format!(r#"{{ "test": {info} }}"#).as_bytes()).unwrap();
+        assert!(index.get("test").is_some());
+
+        // Flat maps that happen to contain a dataset called 'index'
+        let info: String = info.replace(r#""test""#, r#""index""#);
+        let index: DataIndex = DataIndex::from_reader(format!(r#"{{ "index": {info} }}"#).as_bytes()).unwrap();
+        assert!(index.get("index").is_some());
+
+        // Neither
+        assert!(matches!(DataIndex::from_reader(r#"{ "test": 42 }"#.as_bytes()), Err(DataIndexError::ReaderParseError { .. })));
+    }
+}
+
+
+
+
+
 /***** ERRORS *****/
 /// Defines (parsing) errors that relate to the [`DataIndex`] struct.
 #[derive(Debug, thiserror::Error)]
@@ -43,6 +157,17 @@ pub enum DataIndexError {
     DuplicateAsset { location: String, name: String },
 }
 
+/// Defines errors that relate to parsing [`DataName`]s.
+#[derive(Debug, thiserror::Error)]
+pub enum DataNameParseError {
+    /// The name part was empty.
+    #[error("Data name '{raw}' has an empty name")]
+    EmptyName { raw: String },
+    /// The kind prefix was not one we know.
+    #[error("Unknown data kind '{kind}' in data name '{raw}' (expected 'data' or 'result')")]
+    UnknownKind { raw: String, kind: String },
+}
+
 /// Defines errors that relate to the [`RuntimeDataIndex`] struct.
 #[derive(Debug, thiserror::Error)]
 pub enum RuntimeDataIndexError {
@@ -152,6 +277,47 @@ pub type Location = String;
 
 
 
+/// Returns whether the given name matches the given glob-like pattern.
+///
+/// In the pattern, `*` matches any (possibly empty) sequence of characters and `?` matches exactly one character. All other characters match
+/// themselves.
+///
+/// # Arguments
+/// - `pattern`: The pattern to match with.
+/// - `name`: The name to match.
+///
+/// # Returns
+/// True if `name` matches `pattern`, or false otherwise.
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // Classic greedy matching that backtracks to the last star if needed
+    let (mut p, mut n): (usize, usize) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((sp, sn)) = star {
+            // Let the last star eat one more character
+            p = sp + 1;
+            n = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+
+    // Any remaining pattern must be stars only
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+
+
 /// Defines an enum that represents either a `Data` or an `IntermediateResult`.
 #[derive(Clone, Debug, Deserialize, EnumDebug, Eq, Hash, PartialEq, Serialize)]
 pub enum DataName {
@@ -186,16 +352,87 @@ impl DataName {
             Data(name) | IntermediateResult(name) => name,
         }
     }
+
+    /// Returns whether this name is a pattern that may refer to multiple intermediate results.
+    ///
+    /// Only intermediate results can be patterns; they are if they contain a `*` or `?` (see [`glob_matches()`]).
+    #[inline]
+    pub fn is_pattern(&self) -> bool { matches!(self, Self::IntermediateResult(name) if name.contains(['*', '?'])) }
+
+    /// Returns whether this name refers to the given other name.
+    ///
+    /// If this name is a pattern (see [`DataName::is_pattern()`]), then it matches any intermediate result it globs to. Otherwise, the names must be
+    /// equal.
+    ///
+    /// # Arguments
+    /// - `other`: The other name to match with.
+    ///
+    /// # Returns
+    /// True if this name refers to `other`, or false otherwise.
+    #[inline]
+    pub fn matches(&self, other: &DataName) -> bool {
+        match (self, other) {
+            (Self::IntermediateResult(pattern), Self::IntermediateResult(name)) if self.is_pattern() => glob_matches(pattern, name),
+            _ => self == other,
+        }
+    }
+
+    /// Looks up this name in a map keyed by `DataName`s.
+    ///
+    /// If the name is not found literally but is a pattern, then it is resolved to the only intermediate result in the map that matches it.
+    ///
+    /// # Arguments
+    /// - `map`: The map to search.
+    ///
+    /// # Returns
+    /// The key and value of the found entry, or [`None`] if there is no such entry.
+    ///
+    /// # Errors
+    /// This function errors if this name is a pattern that matches more than one key in the map. The error contains the matching keys.
+    pub fn resolve_in<'m, V>(&self, map: &'m HashMap<DataName, V>) -> Result<Option<(&'m DataName, &'m V)>, Vec<DataName>> {
+        // Try the literal one first
+        if let Some(entry) = map.get_key_value(self) {
+            return Ok(Some(entry));
+        }
+        if !self.is_pattern() {
+            return Ok(None);
+        }
+
+        // Otherwise, find the only match
+        let mut matches: Vec<(&DataName, &V)> = map.iter().filter(|(name, _)| self.matches(name)).collect();
+        match matches.len() {
+            0 => Ok(None),
+            1 => Ok(matches.pop()),
+            _ => Err(matches.into_iter().map(|(name, _)| name.clone()).collect()),
+        }
+    }
 }
 impl Display for DataName {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use DataName::*;
         match self {
-            Data(name) => write!(f, "Data<{name}>"),
-            IntermediateResult(name) => write!(f, "IntermediateResult<{name}>"),
+            Data(name) => write!(f, "data:{name}"),
+            IntermediateResult(name) => write!(f, "result:{name}"),
         }
     }
 }
+impl FromStr for DataName {
+    type Err = DataNameParseError;
+
+    /// Parses a `DataName` from a `data:NAME` or `result:NAME` string. A bare `NAME` is assumed to refer to a dataset.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let res: Self = match s.split_once(':') {
+            Some(("data", name)) => Self::Data(name.into()),
+            Some(("result", name)) => Self::IntermediateResult(name.into()),
+            Some((kind, _)) => return Err(DataNameParseError::UnknownKind { raw: s.into(), kind: kind.into() }),
+            None => Self::Data(s.into()),
+        };
+        if res.name().is_empty() {
+            return Err(DataNameParseError::EmptyName { raw: s.into() });
+        }
+        Ok(res)
+    }
+}
 
 
 
@@ -313,6 +550,9 @@ impl DataIndex {
 
     /// Constructor for the `DataIndex` that reads it from the given reader.
     ///
+    /// Besides the serialized `DataIndex` itself, this also accepts the flat map of dataset identifiers to [`DataInfo`]s that the `/data/info`
+    /// endpoint of `brane-api` returns.
+    ///
     /// # Generic arguments
     /// - `R`: The Read-enabled type of the `reader`.
     ///
@@ -324,9 +564,21 @@ impl DataIndex {
     ///
     /// # Errors
     /// This function errors if we could not read or parse the reader.
-    #[inline]
     pub fn from_reader<R: Read>(reader: R) -> Result<Self, DataIndexError> {
-        serde_yaml::from_reader(reader).map_err(|source| DataIndexError::ReaderParseError { source })
+        /// The layouts in which a data index may be given.
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields, untagged)]
+        enum DataIndexLayout {
+            /// A serialized `DataIndex`.
+            Index { index: HashMap<String, DataInfo> },
+            /// A flat map, like `/data/info` returns.
+            Flat(HashMap<String, DataInfo>),
+        }
+
+        match serde_yaml::from_reader(reader).map_err(|source| DataIndexError::ReaderParseError { source })? {
+            DataIndexLayout::Index { index } => Ok(Self { index }),
+            DataIndexLayout::Flat(infos) => Self::from_infos(infos.into_values().collect()),
+        }
     }
 
     /// Constructor for the `DataIndex` that creates it from a list of [`DataInfo`]s.
@@ -507,6 +759,19 @@ impl Default for RuntimeDataIndex {
 
 
 
+/// Records what a single file in a dataset looked like when the dataset was built, such that corruption can be detected later.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FileChecksum {
+    /// The size of the file, in bytes.
+    pub size:   u64,
+    /// The hex-encoded SHA-256 hash of the file's contents.
+    pub sha256: String,
+}
+
+/// Returns the `created` timestamp of [`DataInfo`]s that don't record one.
+#[inline]
+fn legacy_created() -> DateTime<Utc> { DateTime::<Utc>::UNIX_EPOCH }
+
 /// Defines a single `DataInfo` file that describes a dataset and how to access it.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DataInfo {
@@ -516,11 +781,22 @@ pub struct DataInfo {
     pub owners: Option<Vec<String>>,
     /// A (short) description of the asset.
     pub description: Option<String>,
-    /// The created timestamp of the asset.
+    /// The created timestamp of the asset. Datasets built before this was recorded get [`DateTime::UNIX_EPOCH`] when read; see
+    /// [`DataInfo::fill_legacy_created()`].
+    #[serde(default = "legacy_created")]
     pub created: DateTime<Utc>,
 
     /// Defines how to access this `DataInfo` per location that advertises it.
     pub access: HashMap<Location, AccessKind>,
+
+    /// The files of the dataset as it was built locally, relative to its path (a dataset that is a single file has one entry with an empty
+    /// path). Absent for datasets built before checksums were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksums: Option<BTreeMap<PathBuf, FileChecksum>>,
+    /// The total size of the dataset's files in bytes, as advertised by the registries that have it. Absent for local datasets (see
+    /// `checksums` instead) and for registries that don't advertise it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size:      Option<u64>,
 }
 
 impl DataInfo {
@@ -544,9 +820,28 @@ impl DataInfo {
         let handle: File = File::open(path).map_err(|source| DataInfoError::FileOpenError { path: path.into(), source })?;
 
         // Pass to the reader for the heavy lifting
-        match Self::from_reader(handle) {
-            Err(DataInfoError::ReaderParseError { source }) => Err(DataInfoError::FileParseError { path: path.into(), source }),
-            x => x,
+        let mut info: Self = match Self::from_reader(handle) {
+            Err(DataInfoError::ReaderParseError { source }) => return Err(DataInfoError::FileParseError { path: path.into(), source }),
+            x => x?,
+        };
+        if let Some(dir) = path.parent() {
+            info.fill_legacy_created(dir);
+        }
+        Ok(info)
+    }
+
+    /// Replaces a missing `created` timestamp with the modification time of the dataset's directory.
+    ///
+    /// This is for datasets built before their creation time was recorded, which are read with a `created` of [`DateTime::UNIX_EPOCH`]. If the
+    /// modification time cannot be read, the timestamp is left as-is.
+    ///
+    /// # Arguments
+    /// - `dir`: The directory of the dataset (i.e., the one with its `data.yml`).
+    pub fn fill_legacy_created(&mut self, dir: impl AsRef<Path>) {
+        if self.created == legacy_created() {
+            if let Ok(modified) = std::fs::metadata(dir.as_ref()).and_then(|meta| meta.modified()) {
+                self.created = modified.into();
+            }
         }
     }
 
@@ -622,6 +917,9 @@ pub struct AssetInfo {
 
     /// Defines the way how to access & distribute this asset to containers.
     pub access: AccessKind,
+    /// The total size of the asset's files in bytes. Not part of the asset's file, but filled in by the registry when it lists its assets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size:   Option<u64>,
 }
 
 impl AssetInfo {
@@ -682,6 +980,9 @@ impl AssetInfo {
             created: self.created,
 
             access: HashMap::from([(location.into(), self.access)]),
+
+            checksums: None,
+            size:      self.size,
         }
     }
 }
@@ -696,6 +997,9 @@ impl From<AssetInfo> for DataInfo {
             created: value.created,
 
             access: HashMap::from([("localhost".into(), value.access)]),
+
+            checksums: None,
+            size:      value.size,
         }
     }
 }
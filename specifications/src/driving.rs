@@ -37,6 +37,8 @@ pub enum DriverServiceError {
     EndpointError { address: String, err: tonic::transport::Error },
     /// Failed to connect to the given address.
     ConnectError { address: String, err: tonic::transport::Error },
+    /// The connection is required to use TLS, which the gRPC channel does not support.
+    TlsRequired { address: String },
 }
 impl Display for DriverServiceError {
     #[inline]
@@ -45,6 +47,7 @@ impl Display for DriverServiceError {
         match self {
             EndpointError { address, err } => write!(f, "Failed to create a new Endpoint from '{address}': {err}"),
             ConnectError { address, err } => write!(f, "Failed to connect to gRPC endpoint '{address}': {err}"),
+            TlsRequired { address } => write!(f, "Cannot connect to gRPC endpoint '{address}' using TLS (gRPC connections are not encrypted)"),
         }
     }
 }
@@ -93,6 +96,9 @@ pub struct CheckReply {
     /// If any, contains profile results of the driver.
     #[prost(tag = "4", optional, string)]
     pub profile: Option<String>,
+    /// The planned workflow, serialized as JSON, if the planner accepted it.
+    #[prost(tag = "5", optional, string)]
+    pub plan:    Option<String>,
 }
 
 
@@ -156,15 +162,29 @@ impl DriverServiceClient {
         let address: String = address.into();
 
         // Attempt to make the connection
-        let conn: Channel = match Endpoint::new(address.clone()) {
-            Ok(endpoint) => match endpoint.connect().await {
-                Ok(conn) => conn,
-                Err(err) => {
-                    return Err(Error::ConnectError { address, err });
-                },
-            },
+        match Endpoint::new(address.clone()) {
+            Ok(endpoint) => Self::connect_with(endpoint).await,
+            Err(err) => Err(Error::EndpointError { address, err }),
+        }
+    }
+
+    /// Attempts to connect to the remote endpoint, using an already configured [`Endpoint`].
+    ///
+    /// Use this over [`DriverServiceClient::connect()`] to control how the channel is set up (e.g., its headers or timeouts).
+    ///
+    /// # Arguments
+    /// - `endpoint`: The [`Endpoint`] describing the remote endpoint to connect to.
+    ///
+    /// # Returns
+    /// A new `DriverServiceClient` instance that is connected to the remove endpoint.
+    ///
+    /// # Errors
+    /// This function errors if the connection could not be established for whatever reason.
+    pub async fn connect_with(endpoint: Endpoint) -> Result<Self, Error> {
+        let conn: Channel = match endpoint.connect().await {
+            Ok(conn) => conn,
             Err(err) => {
-                return Err(Error::EndpointError { address, err });
+                return Err(Error::ConnectError { address: endpoint.uri().to_string(), err });
             },
         };
 
@@ -24,12 +24,13 @@ use enum_debug::EnumDebug;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JValue;
 use serde_with::skip_serializing_none;
+use sha2::{Digest as _, Sha256};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use uuid::Uuid;
 
 use crate::common::{Function, Type};
-use crate::container::ContainerInfo;
+use crate::container::{ContainerInfo, digests_equal};
 use crate::version::Version;
 
 
@@ -67,6 +68,14 @@ impl PackageKindError {
     }
 }
 
+/// Lists the error for parsing a [`PackageCompression`] from a string.
+#[derive(Debug, thiserror::Error)]
+pub enum PackageCompressionParseError {
+    /// An unknown compression scheme was given.
+    #[error("Unknown compression scheme '{raw}' (expected 'gzip', 'zstd' or 'none')")]
+    Unknown { raw: String },
+}
+
 /// Lists the error for parsing a [`Capability`] from a string.
 #[derive(Debug, thiserror::Error)]
 pub enum CapabilityParseError {
@@ -231,6 +240,74 @@ impl FromStr for Capability {
 
 
 
+/// Defines the compression schemes of package archives sent to or from a registry.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum PackageCompression {
+    /// The archive is compressed with gzip.
+    #[default]
+    Gzip,
+    /// The archive is compressed with Zstandard.
+    Zstd,
+    /// The archive is not compressed.
+    None,
+}
+impl PackageCompression {
+    /// The header with which a registry advertises the compression of a downloaded package archive.
+    ///
+    /// If absent, the archive is not compressed.
+    pub const HEADER: &'static str = "X-Brane-Compression";
+
+    /// Returns the `Content-Type` with which an archive with this compression is uploaded.
+    #[inline]
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Gzip => "application/gzip",
+            Self::Zstd => "application/zstd",
+            Self::None => "application/x-tar",
+        }
+    }
+
+    /// Returns the compression of an uploaded archive based on its `Content-Type`.
+    ///
+    /// # Arguments
+    /// - `content_type`: The `Content-Type` of the upload.
+    ///
+    /// # Returns
+    /// The matching compression, or [`None`] if the content type is not one of ours.
+    #[inline]
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next().unwrap_or_default().trim() {
+            "application/gzip" | "application/x-gzip" => Some(Self::Gzip),
+            "application/zstd" => Some(Self::Zstd),
+            "application/x-tar" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+impl std::fmt::Display for PackageCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Gzip => write!(f, "gzip"),
+            Self::Zstd => write!(f, "zstd"),
+            Self::None => write!(f, "none"),
+        }
+    }
+}
+impl FromStr for PackageCompression {
+    type Err = PackageCompressionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gzip" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            "none" => Ok(Self::None),
+            _ => Err(PackageCompressionParseError::Unknown { raw: s.into() }),
+        }
+    }
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -263,6 +340,9 @@ pub struct PackageInfo {
     pub functions: Map<Function>,
     /// The types that this package adds.
     pub types:     Map<Type>,
+    /// Any custom labels (key/value metadata) attached to the package image.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub labels:    Map<String>,
 }
 
 #[allow(unused)]
@@ -294,7 +374,7 @@ impl PackageInfo {
         let created = Utc::now();
 
         // Return the package
-        PackageInfo { created, id, digest: None, name, version, kind, owners, description, detached, functions, types }
+        PackageInfo { created, id, digest: None, name, version, kind, owners, description, detached, functions, types, labels: Map::new() }
     }
 
     /// **Edited: changed to return appropriate errors. Also added docstring.**
@@ -366,6 +446,17 @@ impl PackageInfo {
         // Simply write with serde
         serde_yaml::to_writer(writer, self).map_err(|source| PackageInfoError::FileWriteError { source })
     }
+
+    /// Returns the HTTP entity tag (`ETag`) with which a registry identifies this build of the package.
+    ///
+    /// It is derived from the image digest and the package ID, such that it changes whenever the package is rebuilt, even if the image
+    /// itself happens to be identical.
+    ///
+    /// **Returns**  
+    /// The (quoted) entity tag, or [`None`] if the image has not been generated yet (i.e., there is no digest).
+    pub fn etag(&self) -> Option<String> {
+        self.digest.as_ref().map(|digest| format!("\"{}-{}\"", digest.trim_start_matches("sha256:"), self.id.simple()))
+    }
 }
 
 impl From<ContainerInfo> for PackageInfo {
@@ -587,6 +678,27 @@ impl PackageIndex {
         Ok(PackageIndex::new(packages))
     }
 
+    /// Returns the HTTP entity tag (`ETag`) with which a registry identifies this set of packages.
+    ///
+    /// It is derived from the entity tags of every package in the index (see [`PackageInfo::etag()`]), such that it changes whenever a
+    /// package is added, removed or rebuilt.
+    ///
+    /// **Returns**  
+    /// The (quoted) entity tag.
+    pub fn etag(&self) -> String {
+        let mut keys: Vec<&String> = self.packages.keys().collect();
+        keys.sort();
+
+        let mut hasher: Sha256 = Sha256::new();
+        for key in keys {
+            let package: &PackageInfo = &self.packages[key];
+            hasher.update(key.as_bytes());
+            hasher.update(package.etag().unwrap_or_else(|| package.id.simple().to_string()).as_bytes());
+            hasher.update(b"\n");
+        }
+        format!("\"{:x}\"", hasher.finalize())
+    }
+
     /// Returns the package with the given name and (optional) version.
     ///
     /// **Arguments**
@@ -622,6 +734,31 @@ impl PackageIndex {
         self.packages.get(&format!("{name}-{version}"))
     }
 
+    /// Returns the package whose image has the given digest.
+    ///
+    /// **Arguments**
+    ///  * `digest`: The digest of the image to search for. The `sha256:`-prefix is optional.
+    ///
+    /// **Returns**  
+    /// An (immuteable) reference to the package if one has that digest, or else None. Versions created with `brane package tag` share their
+    /// image, in which case the most recent of them is returned.
+    pub fn find_by_digest(&self, digest: &str) -> Option<&PackageInfo> {
+        self.packages
+            .values()
+            .filter(|info| info.digest.as_deref().is_some_and(|own| digests_equal(own, digest)))
+            .max_by(|lhs, rhs| lhs.version.cmp(&rhs.version).then_with(|| rhs.name.cmp(&lhs.name)))
+    }
+
+    /// Returns whether any package in the index has an image with the given digest.
+    ///
+    /// **Arguments**
+    ///  * `digest`: The digest of the image to search for. The `sha256:`-prefix is optional.
+    ///
+    /// **Returns**  
+    /// True if [`PackageIndex::find_by_digest()`] would find a package, or false otherwise.
+    #[inline]
+    pub fn contains_digest(&self, digest: &str) -> bool { self.find_by_digest(digest).is_some() }
+
     /// Returns the latest version of the given package.
     ///
     /// **Arguments**
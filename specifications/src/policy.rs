@@ -21,9 +21,10 @@ use std::str::FromStr as _;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use base64ct::Encoding as _;
-use jsonwebtoken::jwk::{self, Jwk, JwkSet, KeyAlgorithm, OctetKeyParameters};
+use jsonwebtoken::jwk::{self, EllipticCurve, EllipticCurveKeyParameters, Jwk, JwkSet, KeyAlgorithm, OctetKeyParameters};
 use jsonwebtoken::{Algorithm, EncodingKey, Header};
 use log::{debug, info, warn};
+use p256::pkcs8::EncodePrivateKey as _;
 use serde::{Deserialize, Serialize};
 
 
@@ -45,6 +46,14 @@ pub enum Error {
     Base64Decode { raw: String, err: base64ct::Error },
     /// Unsupported key type encountered
     UnsupportedKeyType { ty: &'static str },
+    /// An elliptic curve key was not on a curve we support.
+    UnsupportedCurve { curve: EllipticCurve },
+    /// An elliptic curve key did not carry its private part.
+    MissingPrivateKey { path: PathBuf },
+    /// The private part of an elliptic curve key was not a valid scalar for its curve.
+    EcKeyParse { err: p256::elliptic_curve::Error },
+    /// Failed to encode an elliptic curve key as PKCS#8.
+    EcKeyEncode { err: p256::pkcs8::Error },
     /// Failed to encode the final JWT
     JwtEncode { alg: Algorithm, err: jsonwebtoken::errors::Error },
 }
@@ -61,6 +70,12 @@ impl Display for Error {
             TooManySecrets { path, got } => write!(f, "Policy secret '{}' has too many keys: expected 1, got {}", path.display(), got),
             Base64Decode { raw, .. } => write!(f, "Failed to parse '{raw}' as a valid URL-safe base64"),
             UnsupportedKeyType { ty } => write!(f, "Unsupported policy secret type '{ty}'"),
+            UnsupportedCurve { curve } => write!(f, "Unsupported policy secret curve {curve:?} (only P-256 is supported)"),
+            MissingPrivateKey { path } => {
+                write!(f, "Policy secret '{}' does not contain a private key (did you pass the public key instead?)", path.display())
+            },
+            EcKeyParse { .. } => write!(f, "Failed to parse policy secret as a P-256 private key"),
+            EcKeyEncode { .. } => write!(f, "Failed to encode P-256 private key as PKCS#8"),
             JwtEncode { alg, .. } => write!(f, "Failed to create JWT using {alg:?}"),
         }
     }
@@ -76,6 +91,10 @@ impl error::Error for Error {
             TooManySecrets { .. } => None,
             Base64Decode { err, .. } => Some(err),
             UnsupportedKeyType { .. } => None,
+            UnsupportedCurve { .. } => None,
+            MissingPrivateKey { .. } => None,
+            EcKeyParse { err } => Some(err),
+            EcKeyEncode { err } => Some(err),
             JwtEncode { err, .. } => Some(err),
         }
     }
@@ -111,14 +130,19 @@ pub fn generate_policy_token(
     info!("Generating new JWT access token from secret '{}'...", secret_path.display());
 
     // Read the secret
+    // NOTE: We read it as a raw value first, because `Jwk` does not carry private elliptic curve parameters
     debug!("Reading secret '{}'...", secret_path.display());
-    let secret: JwkSet = match File::open(secret_path) {
+    let raw: serde_json::Value = match File::open(secret_path) {
         Ok(handle) => match serde_json::from_reader(handle) {
-            Ok(secret) => secret,
+            Ok(raw) => raw,
             Err(err) => return Err(Error::SecretDeserializeError { path: secret_path.into(), err }),
         },
         Err(err) => return Err(Error::SecretOpenError { path: secret_path.into(), err }),
     };
+    let secret: JwkSet = match serde_json::from_value(raw.clone()) {
+        Ok(secret) => secret,
+        Err(err) => return Err(Error::SecretDeserializeError { path: secret_path.into(), err }),
+    };
 
     // Resolve the set to a single key
     let key: &Jwk = match secret.keys.len().cmp(&1) {
@@ -162,8 +186,28 @@ pub fn generate_policy_token(
                 EncodingKey::from_secret(&value)
             },
 
+            jwk::AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters { curve, .. }) => {
+                if *curve != EllipticCurve::P256 {
+                    return Err(Error::UnsupportedCurve { curve: curve.clone() });
+                }
+
+                // Find the private scalar next to the public parameters
+                let d: &str = match raw["keys"][0]["d"].as_str() {
+                    Some(d) => d,
+                    None => return Err(Error::MissingPrivateKey { path: secret_path.into() }),
+                };
+                let d: Vec<u8> = match base64ct::Base64UrlUnpadded::decode_vec(d) {
+                    Ok(raw) => raw,
+                    Err(err) => return Err(Error::Base64Decode { raw: d.into(), err }),
+                };
+
+                // jsonwebtoken wants it as PKCS#8
+                let key: p256::SecretKey = p256::SecretKey::from_slice(&d).map_err(|err| Error::EcKeyParse { err })?;
+                let der: p256::pkcs8::SecretDocument = key.to_pkcs8_der().map_err(|err| Error::EcKeyEncode { err })?;
+                EncodingKey::from_ec_der(der.as_bytes())
+            },
+
             // The rest is unsupported
-            jwk::AlgorithmParameters::EllipticCurve(_) => return Err(Error::UnsupportedKeyType { ty: "EllipticCurve" }),
             jwk::AlgorithmParameters::OctetKeyPair(_) => return Err(Error::UnsupportedKeyType { ty: "OctetKeyPair" }),
             jwk::AlgorithmParameters::RSA(_) => return Err(Error::UnsupportedKeyType { ty: "RSA" }),
         };
@@ -14,7 +14,7 @@
 //
 
 use std::cmp::Ordering;
-use std::fmt::{Display, Formatter, Result as FResult};
+use std::fmt::{Debug, Display, Formatter, Result as FResult};
 use std::str::FromStr;
 
 use serde::de::{self, Visitor};
@@ -71,6 +71,101 @@ mod tests {
         assert_eq!(Version::from_str("42.b.c"), Err(ParseError::MinorParseError { raw: String::from("b"), source: u64::from_str("b").unwrap_err() }));
     }
 
+    #[test]
+    fn test_prerelease_parse() {
+        // Test if prereleases are parsed
+        let rc1: Version = Version::new(1, 0, 0).with_prerelease("rc1").unwrap();
+        assert_eq!(Version::from_str("1.0.0-rc1"), Ok(rc1));
+        assert_eq!(Version::from_str("v1.0.0-rc1"), Ok(rc1));
+        assert_eq!(Version::from_str("1.0.0-rc1").unwrap().prerelease(), "rc1");
+        assert_eq!(Version::from_str("1.0.0-alpha.1.x-y").unwrap().prerelease(), "alpha.1.x-y");
+        assert_eq!(Version::from_str("1.0.0").unwrap().prerelease(), "");
+
+        // Build metadata is accepted but ignored
+        assert_eq!(Version::from_str("1.0.0+build.5"), Ok(Version::new(1, 0, 0)));
+        assert_eq!(Version::from_str("1.0.0+001"), Ok(Version::new(1, 0, 0)));
+        assert_eq!(Version::from_str("1.0.0-rc1+build.5"), Ok(rc1));
+        assert_eq!(Version::from_str("1.0.0+build.5"), Version::from_str("1.0.0+build.6"));
+
+        // Test if they are written back
+        assert_eq!(rc1.to_string(), "1.0.0-rc1");
+        assert_eq!(Version::from_str("1.0.0-rc1+build.5").unwrap().to_string(), "1.0.0-rc1");
+
+        // Test if illegal ones fail
+        assert_eq!(Version::from_str("1.0.0-"), Err(ParseError::IllegalPrerelease { raw: String::from("") }));
+        assert_eq!(Version::from_str("1.0.0-rc..1"), Err(ParseError::IllegalPrerelease { raw: String::from("rc..1") }));
+        assert_eq!(Version::from_str("1.0.0-rc.01"), Err(ParseError::IllegalPrerelease { raw: String::from("rc.01") }));
+        assert_eq!(Version::from_str("1.0.0-rc_1"), Err(ParseError::IllegalPrerelease { raw: String::from("rc_1") }));
+        assert_eq!(Version::from_str("1.0.0+"), Err(ParseError::IllegalBuildMetadata { raw: String::from("") }));
+        assert_eq!(Version::from_str("1.0.0+a+b"), Err(ParseError::IllegalBuildMetadata { raw: String::from("a+b") }));
+        assert_eq!(Version::new(1, 0, 0).with_prerelease("rc.01"), Err(ParseError::IllegalPrerelease { raw: String::from("rc.01") }));
+
+        // Test if prereleases that don't fit fail
+        let long: String = "a".repeat(MAX_PRERELEASE_LEN);
+        assert_eq!(Version::new(1, 0, 0).with_prerelease(&long).unwrap().prerelease(), long);
+        let long: String = "a".repeat(MAX_PRERELEASE_LEN + 1);
+        assert_eq!(Version::from_str(&format!("1.0.0-{long}")), Err(ParseError::PrereleaseTooLong { raw: long.clone() }));
+        assert_eq!(Version::try_from(semver::Version::parse(&format!("1.0.0-{long}")).unwrap()), Err(ParseError::PrereleaseTooLong { raw: long }));
+    }
+
+    #[test]
+    fn test_prerelease_ord() {
+        // The example ordering from the semver spec (§11)
+        let versions: Vec<Version> = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1-rc1",
+            "1.0.1",
+        ]
+        .into_iter()
+        .map(|v| Version::from_str(v).unwrap())
+        .collect();
+        for (i, lhs) in versions.iter().enumerate() {
+            for (j, rhs) in versions.iter().enumerate() {
+                assert_eq!(lhs.cmp(rhs), i.cmp(&j), "Expected {lhs} {:?} {rhs}", i.cmp(&j));
+            }
+        }
+
+        // Prereleases of a later version are still later than earlier releases
+        assert!(Version::from_str("2.0.0-rc1").unwrap() > Version::new(1, 9, 9));
+        // Prereleases differ from the release itself
+        assert_ne!(Version::from_str("1.0.0-rc1").unwrap(), Version::new(1, 0, 0));
+
+        // Same with semver's
+        assert!(Version::from_str("1.0.0-rc1").unwrap() < semver::Version::parse("1.0.0").unwrap());
+        assert!(Version::from_str("1.0.0-rc.2").unwrap() < semver::Version::parse("1.0.0-rc.10").unwrap());
+        assert!(Version::new(1, 0, 0) > semver::Version::parse("1.0.0-rc1").unwrap());
+        assert_eq!(Version::from_str("1.0.0-rc1").unwrap(), semver::Version::parse("1.0.0-rc1").unwrap());
+        assert_eq!(Version::try_from(semver::Version::parse("1.0.0-rc1+build").unwrap()), Ok(Version::from_str("1.0.0-rc1").unwrap()));
+    }
+
+    #[test]
+    fn test_prerelease_resolve() {
+        // A release candidate should never be picked over its release
+        let mut latest = Version::latest();
+        let versions = vec![Version::from_str("1.0.0-rc1").unwrap(), Version::new(1, 0, 0), Version::from_str("1.0.0-rc2").unwrap()];
+        assert!(latest.resolve_latest(versions).is_ok());
+        assert_eq!(latest, Version::new(1, 0, 0));
+
+        // But it is picked if it's for a newer version
+        let mut latest = Version::latest();
+        let versions = vec![Version::new(1, 0, 0), Version::from_str("1.1.0-rc1").unwrap(), Version::new(0, 9, 0)];
+        assert!(latest.resolve_latest(versions).is_ok());
+        assert_eq!(latest, Version::from_str("1.1.0-rc1").unwrap());
+
+        // And between prereleases, the latest one wins
+        let mut latest = Version::latest();
+        let versions = vec![Version::from_str("1.0.0-beta.11").unwrap(), Version::from_str("1.0.0-beta.2").unwrap()];
+        assert!(latest.resolve_latest(versions).is_ok());
+        assert_eq!(latest, Version::from_str("1.0.0-beta.11").unwrap());
+    }
+
     #[test]
     fn test_resolve() {
         // Create a 'latest' version
@@ -95,20 +190,39 @@ mod tests {
         assert_eq!(latest.resolve_latest(versions), Err(ResolveError::NoVersions));
     }
 
+    #[test]
+    fn test_package_pair() {
+        // Bare names and empty versions are latest
+        assert_eq!(Version::from_package_pair("foo").unwrap(), ("foo".into(), Version::latest()));
+        assert_eq!(Version::from_package_pair("foo:").unwrap(), ("foo".into(), Version::latest()));
+        assert_eq!(Version::from_package_pair("foo:latest").unwrap(), ("foo".into(), Version::latest()));
+
+        // Normal pairs
+        assert_eq!(Version::from_package_pair("foo:1.2.3").unwrap(), ("foo".into(), Version::new(1, 2, 3)));
+        assert_eq!(Version::from_package_pair("foo:1.0.0-rc.1").unwrap(), ("foo".into(), Version::from_str("1.0.0-rc.1").unwrap()));
+
+        // Names with colons are split on the last one
+        assert_eq!(Version::from_package_pair("ns:foo:1.2.3").unwrap(), ("ns:foo".into(), Version::new(1, 2, 3)));
+        assert_eq!(Version::from_package_pair("ns:foo:").unwrap(), ("ns:foo".into(), Version::latest()));
+
+        // Illegal versions still error
+        assert!(matches!(Version::from_package_pair("foo:bar"), Err(ParseError::IllegalVersion { .. })));
+    }
+
 
 
     #[test]
     fn test_semver() {
         // Make sure the from (consuming) makes sense
         let semversion = semver::Version::new(42, 21, 10);
-        let version = Version::from(semversion.clone());
+        let version = Version::try_from(semversion.clone()).unwrap();
         assert_eq!(semversion.major, version.major);
         assert_eq!(semversion.minor, version.minor);
         assert_eq!(semversion.patch, version.patch);
 
         // Make sure the from (reference) makes sense
         let semversion = semver::Version::new(10, 21, 42);
-        let version = Version::from(&semversion);
+        let version = Version::try_from(&semversion).unwrap();
         assert_eq!(semversion.major, version.major);
         assert_eq!(semversion.minor, version.minor);
         assert_eq!(semversion.patch, version.patch);
@@ -128,12 +242,35 @@ mod tests {
 
 
 
+    #[test]
+    fn test_constraint() {
+        // Test if it matches what it should
+        let constraint = VersionConstraint::from_str("<1.0").unwrap();
+        assert!(constraint.matches(&Version::new(0, 9, 3)));
+        assert!(!constraint.matches(&Version::new(1, 0, 0)));
+        assert!(!constraint.matches(&Version::new(1, 2, 0)));
+        let constraint = VersionConstraint::from_str(">=1.2, <2").unwrap();
+        assert!(!constraint.matches(&Version::new(1, 1, 9)));
+        assert!(constraint.matches(&Version::new(1, 2, 0)));
+        assert!(constraint.matches(&Version::new(1, 9, 0)));
+        assert!(!constraint.matches(&Version::new(2, 0, 0)));
+
+        // Prereleases only match if the constraint mentions them, and latest never does
+        assert!(!VersionConstraint::from_str("<1.0").unwrap().matches(&Version::new(0, 9, 0).with_prerelease("rc.1").unwrap()));
+        assert!(VersionConstraint::from_str(">=1.0.0-rc.1").unwrap().matches(&Version::new(1, 0, 0).with_prerelease("rc.2").unwrap()));
+        assert!(!VersionConstraint::from_str("*").unwrap().matches(&Version::latest()));
+
+        // Test if it fails properly too
+        assert!(VersionConstraint::from_str("<<1").is_err());
+    }
+
     #[test]
     fn test_serde_serialize() {
         // Try to convert some versions to serde tokens
         assert_ser_tokens(&Version::new(42, 21, 10), &[Token::Str("42.21.10")]);
         assert_ser_tokens(&Version::new(42, 0, 10), &[Token::Str("42.0.10")]);
         assert_ser_tokens(&Version::latest(), &[Token::Str("latest")]);
+        assert_ser_tokens(&Version::new(1, 0, 0).with_prerelease("rc.1").unwrap(), &[Token::Str("1.0.0-rc.1")]);
     }
 
     #[test]
@@ -142,6 +279,7 @@ mod tests {
         assert_de_tokens(&Version::new(42, 21, 10), &[Token::Str("42.21.10")]);
         assert_de_tokens(&Version::new(42, 0, 10), &[Token::Str("42.0.10")]);
         assert_de_tokens(&Version::latest(), &[Token::Str("latest")]);
+        assert_de_tokens(&Version::new(1, 0, 0).with_prerelease("rc.1").unwrap(), &[Token::Str("1.0.0-rc.1+build")]);
 
         // Check for the same errors as test_parse()
         assert_de_tokens_error::<Version>(&[Token::Str(ACCIDENTAL_LATEST_STRING)], &format!("{}", ParseError::AccidentalLatest));
@@ -172,6 +310,14 @@ mod tests {
 
 
 
+/***** CONSTANTS *****/
+/// The maximum length (in bytes) of the prerelease of a [`Version`].
+pub const MAX_PRERELEASE_LEN: usize = 31;
+
+
+
+
+
 /***** ERRORS *****/
 /// Collects errors that relate to the Version.
 #[derive(Debug, Eq, PartialEq, thiserror::Error)]
@@ -202,16 +348,125 @@ pub enum ParseError {
     /// Could not parse the patch version number
     #[error("Could not parse patch version number '{raw}'")]
     PatchParseError { raw: String, source: std::num::ParseIntError },
+    /// The prerelease part was not a valid semver prerelease
+    #[error(
+        "Illegal prerelease '{raw}' (expected dot-separated, non-empty identifiers of ASCII alphanumerics and hyphens, without leading zeroes in \
+         numeric ones)"
+    )]
+    IllegalPrerelease { raw: String },
+    /// The prerelease part was too long to store
+    #[error("Prerelease '{raw}' is too long (expected at most {MAX_PRERELEASE_LEN} characters)")]
+    PrereleaseTooLong { raw: String },
+    /// The build metadata part was not valid semver build metadata
+    #[error("Illegal build metadata '{raw}' (expected dot-separated, non-empty identifiers of ASCII alphanumerics and hyphens)")]
+    IllegalBuildMetadata { raw: String },
 
-    /// Got a NAME:VERSION pair with too many colons
-    #[error("Given 'NAME[:VERSION]' pair '{raw}' has too many colons (got {got}, expected at most 1)")]
-    TooManyColons { raw: String, got: usize },
     /// Could not parse the Version in a given NAME:VERSION pair.
     #[error("Could not parse version '{raw_version}' in '{raw}'")]
     IllegalVersion { raw: String, raw_version: String, source: Box<Self> },
 }
 
+/// Could not parse a [`VersionConstraint`].
+#[derive(Debug, thiserror::Error)]
+#[error("Illegal version constraint '{raw}'")]
+pub struct ConstraintParseError {
+    /// The raw constraint that we failed to parse.
+    pub raw:    String,
+    /// The reason why.
+    pub source: semver::Error,
+}
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given string is a list of valid semver identifiers (i.e., prerelease or build metadata).
+///
+/// # Arguments
+/// - `raw`: The string to check.
+/// - `numeric_leading_zeroes`: Whether purely numeric identifiers may have leading zeroes (only true for build metadata).
+fn is_valid_identifiers(raw: &str, numeric_leading_zeroes: bool) -> bool {
+    raw.split('.').all(|id| {
+        !id.is_empty()
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && (numeric_leading_zeroes || id.len() == 1 || !id.starts_with('0') || !id.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Compares two prereleases according to semver precedence.
+///
+/// An empty prerelease (i.e., a normal release) is larger than any non-empty one. Otherwise, identifiers are compared one by one, where
+/// numeric identifiers compare numerically and are lower than alphanumeric ones, which compare lexically in ASCII order. If all identifiers
+/// are equal, the prerelease with more of them is larger.
+fn cmp_prerelease(lhs: &str, rhs: &str) -> Ordering {
+    match (lhs.is_empty(), rhs.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {},
+    }
+
+    let mut lhs = lhs.split('.');
+    let mut rhs = rhs.split('.');
+    loop {
+        let order: Ordering = match (lhs.next(), rhs.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) => match (l.parse::<u64>(), r.parse::<u64>()) {
+                (Ok(l), Ok(r)) => l.cmp(&r),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => l.cmp(r),
+            },
+        };
+        if order.is_ne() {
+            return order;
+        }
+    }
+}
+
+
+
+
+
 /***** HELPER STRUCTS *****/
+/// Stores the prerelease of a [`Version`] inline, such that [`Version`] can stay [`Copy`].
+#[derive(Clone, Copy, Eq, PartialEq)]
+struct Prerelease {
+    /// The number of bytes in `buf` that are used.
+    len: u8,
+    /// The prerelease itself, followed by zeroes.
+    buf: [u8; MAX_PRERELEASE_LEN],
+}
+
+impl Prerelease {
+    /// The prerelease of a normal release.
+    const EMPTY: Self = Self { len: 0, buf: [0; MAX_PRERELEASE_LEN] };
+
+    /// Constructor for the Prerelease.
+    ///
+    /// # Arguments
+    /// - `pre`: The (already validated) prerelease to store.
+    ///
+    /// # Errors
+    /// This function errors if `pre` is longer than [`MAX_PRERELEASE_LEN`].
+    fn new(pre: &str) -> Result<Self, ParseError> {
+        if pre.len() > MAX_PRERELEASE_LEN {
+            return Err(ParseError::PrereleaseTooLong { raw: pre.into() });
+        }
+        let mut buf: [u8; MAX_PRERELEASE_LEN] = [0; MAX_PRERELEASE_LEN];
+        buf[..pre.len()].copy_from_slice(pre.as_bytes());
+        Ok(Self { len: pre.len() as u8, buf })
+    }
+
+    /// Returns the prerelease as a string.
+    #[inline]
+    fn as_str(&self) -> &str { std::str::from_utf8(&self.buf[..self.len as usize]).expect("Prerelease is not valid UTF-8") }
+}
+
+impl Debug for Prerelease {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{:?}", self.as_str()) }
+}
+
 /// Implements a Visitor for the Version.
 struct VersionVisitor;
 
@@ -235,6 +490,9 @@ impl Visitor<'_> for VersionVisitor {
 
 /***** VERSION *****/
 /// Implements the Version, which is used to keep track of package versions.
+///
+/// Like semver, it may have a prerelease (e.g., `1.0.0-rc.1`), which orders it before the same version without one. Build metadata (e.g.,
+/// `1.0.0+build.5`) is accepted when parsing but not kept, as it does not take part in comparisons anyway.
 #[derive(Clone, Copy, Debug, Eq)]
 pub struct Version {
     /// The major version number. If all three are set to `u64::MAX`, is interpreted as an unresolved 'latest' version number.
@@ -243,6 +501,8 @@ pub struct Version {
     pub minor: u64,
     /// The patch version number. If all three are set to `u64::MAX`, is interpreted as an unresolved 'latest' version number.
     pub patch: u64,
+    /// The prerelease, which is empty if this is a normal release.
+    pre: Prerelease,
 }
 
 impl Version {
@@ -255,7 +515,7 @@ impl Version {
     ///  * `patch`: The patch version number.
     pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
         // Create the version
-        let result = Self { major, minor, patch };
+        let result = Self { major, minor, patch, pre: Prerelease::EMPTY };
 
         // If it's latest, panic; otherwise, return
         if result.is_latest() {
@@ -268,43 +528,55 @@ impl Version {
 
     /// Constructor for the Version that sets it to an (unresolved) 'latest' version.
     #[inline]
-    pub const fn latest() -> Self { Self { major: u64::MAX, minor: u64::MAX, patch: u64::MAX } }
+    pub const fn latest() -> Self { Self { major: u64::MAX, minor: u64::MAX, patch: u64::MAX, pre: Prerelease::EMPTY } }
+
+    /// Returns a copy of this version with the given prerelease.
+    ///
+    /// # Arguments
+    /// - `pre`: The prerelease to set (e.g., `rc.1`). An empty string makes it a normal release.
+    ///
+    /// # Errors
+    /// This function errors if `pre` is not a valid semver prerelease.
+    pub fn with_prerelease(self, pre: &str) -> Result<Self, ParseError> {
+        if !pre.is_empty() && !is_valid_identifiers(pre, false) {
+            return Err(ParseError::IllegalPrerelease { raw: pre.into() });
+        }
+        Ok(Self { pre: Prerelease::new(pre)?, ..self })
+    }
+
+    /// Returns the prerelease of this version (e.g., `rc.1`), or an empty string if it is a normal release.
+    #[inline]
+    pub fn prerelease(&self) -> &str { self.pre.as_str() }
 
     /// Special factory method that creates a package name and a version from a `NAME[:VERSION]` pair.
     ///
-    /// If the `VERSION` is omitted, returns `Version::latest()`.
+    /// If the `VERSION` is omitted or empty (i.e., `NAME` or `NAME:`), returns `Version::latest()`. The pair is split on the _last_ colon,
+    /// so names that contain colons themselves can still be given as long as a `:VERSION` (or a trailing colon) follows.
     ///
     /// # Arguments
     /// - `package`: The package `NAME[:VERSION]` pair to parse.
     ///
     /// # Errors
-    /// This function may error if parsing failed, somehow.
+    /// This function may error if the version part is not a valid version.
     pub fn from_package_pair(package: &str) -> Result<(String, Self), ParseError> {
-        // Get the number of colons in the string
-        let colons: usize = package.matches(':').count();
-
-        // Switch on version present or not
-        if colons == 0 {
-            // Simply return the name with the latest version
-            Ok((package.into(), Self::latest()))
-        } else if colons == 1 {
-            // Split on the colon
-            let colon_pos = package.find(':').unwrap();
-            let name: &str = &package[..colon_pos];
-            let version: &str = &package[colon_pos + 1..];
-
-            // Attempt to parse the Version
-            let version: Self = Self::from_str(version).map_err(|source| ParseError::IllegalVersion {
-                raw: package.into(),
-                raw_version: version.into(),
-                source: Box::new(source),
-            })?;
-
-            // Return them as a pair
-            Ok((name.to_string(), version))
-        } else {
-            Err(ParseError::TooManyColons { raw: package.into(), got: colons })
+        // Split on the last colon, if any
+        let (name, version): (&str, &str) = match package.rsplit_once(':') {
+            Some((name, version)) => (name, version),
+            None => return Ok((package.into(), Self::latest())),
+        };
+        if version.is_empty() {
+            return Ok((name.into(), Self::latest()));
         }
+
+        // Attempt to parse the Version
+        let version: Self = Self::from_str(version).map_err(|source| ParseError::IllegalVersion {
+            raw: package.into(),
+            raw_version: version.into(),
+            source: Box::new(source),
+        })?;
+
+        // Return them as a pair
+        Ok((name.into(), version))
     }
 
     /// Resolves this version in case it's a 'latest' version.
@@ -365,7 +637,9 @@ impl Default for Version {
 
 impl PartialEq for Version {
     #[inline]
-    fn eq(&self, other: &Self) -> bool { self.major == other.major && self.minor == other.minor && self.patch == other.patch }
+    fn eq(&self, other: &Self) -> bool {
+        self.major == other.major && self.minor == other.minor && self.patch == other.patch && self.pre == other.pre
+    }
 }
 
 impl Ord for Version {
@@ -383,7 +657,13 @@ impl Ord for Version {
         }
 
         // Compare the patch
-        self.patch.cmp(&other.patch)
+        let order = self.patch.cmp(&other.patch);
+        if order.is_ne() {
+            return order;
+        }
+
+        // Finally, a prerelease comes before the release itself
+        cmp_prerelease(self.pre.as_str(), other.pre.as_str())
     }
 }
 
@@ -401,6 +681,26 @@ impl FromStr for Version {
             return Ok(Self::latest());
         }
 
+        // Split off the build metadata, which we only validate, and the prerelease
+        let s: &str = match s.split_once('+') {
+            Some((s, build)) => {
+                if !is_valid_identifiers(build, true) {
+                    return Err(ParseError::IllegalBuildMetadata { raw: build.into() });
+                }
+                s
+            },
+            None => s,
+        };
+        let (s, pre): (&str, &str) = match s.split_once('-') {
+            Some((s, pre)) => {
+                if !is_valid_identifiers(pre, false) {
+                    return Err(ParseError::IllegalPrerelease { raw: pre.into() });
+                }
+                (s, pre)
+            },
+            None => (s, ""),
+        };
+
         // Otherwise, see if we can split the string into multiple slices
         // Compute the possible dot posses first
         let dot1 = s.find('.');
@@ -445,7 +745,7 @@ impl FromStr for Version {
         };
 
         // Put them together in a Version
-        let result = Self { major, minor, patch };
+        let result = Self { major, minor, patch, pre: Prerelease::new(pre)? };
 
         // If this version is latest, then error
         if result.is_latest() {
@@ -457,7 +757,13 @@ impl FromStr for Version {
 
 impl Display for Version {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
-        if self.is_latest() { write!(f, "latest") } else { write!(f, "{}.{}.{}", self.major, self.minor, self.patch) }
+        if self.is_latest() {
+            write!(f, "latest")
+        } else if self.pre.len == 0 {
+            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+        } else {
+            write!(f, "{}.{}.{}-{}", self.major, self.minor, self.patch, self.pre.as_str())
+        }
     }
 }
 
@@ -479,7 +785,11 @@ impl From<&mut Version> for Version {
 impl PartialEq<semver::Version> for Version {
     #[inline]
     fn eq(&self, other: &semver::Version) -> bool {
-        !self.is_latest() && self.major == other.major && self.minor == other.minor && self.patch == other.patch
+        !self.is_latest()
+            && self.major == other.major
+            && self.minor == other.minor
+            && self.patch == other.patch
+            && self.pre.as_str() == other.pre.as_str()
     }
 }
 
@@ -504,18 +814,30 @@ impl PartialOrd<semver::Version> for Version {
         }
 
         // Compare the patch
-        Some(self.patch.cmp(&other.patch))
+        let order = self.patch.cmp(&other.patch);
+        if order.is_ne() {
+            return Some(order);
+        }
+
+        // Finally, a prerelease comes before the release itself
+        Some(cmp_prerelease(self.pre.as_str(), other.pre.as_str()))
     }
 }
 
-impl From<semver::Version> for Version {
+impl TryFrom<semver::Version> for Version {
+    type Error = ParseError;
+
     #[inline]
-    fn from(version: semver::Version) -> Self { Self { major: version.major, minor: version.minor, patch: version.patch } }
+    fn try_from(version: semver::Version) -> Result<Self, Self::Error> { Self::try_from(&version) }
 }
 
-impl From<&semver::Version> for Version {
+impl TryFrom<&semver::Version> for Version {
+    type Error = ParseError;
+
     #[inline]
-    fn from(version: &semver::Version) -> Self { Self { major: version.major, minor: version.minor, patch: version.patch } }
+    fn try_from(version: &semver::Version) -> Result<Self, Self::Error> {
+        Ok(Self { major: version.major, minor: version.minor, patch: version.patch, pre: Prerelease::new(version.pre.as_str())? })
+    }
 }
 
 
@@ -549,3 +871,53 @@ impl<'de> Deserialize<'de> for Version {
         deserializer.deserialize_str(VersionVisitor)
     }
 }
+
+
+
+
+
+/***** VERSION CONSTRAINT *****/
+/// A constraint on [`Version`]s, such as `<1.0` or `>=1.2, <2`, using the same syntax as Cargo's dependency requirements.
+///
+/// Like in Cargo, prereleases only match if one of the comparators mentions a prerelease of the same version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionConstraint(semver::VersionReq);
+
+impl VersionConstraint {
+    /// Checks whether the given version satisfies this constraint.
+    ///
+    /// # Arguments
+    /// - `version`: The version to check.
+    ///
+    /// # Returns
+    /// True if it does, or false otherwise. An unresolved 'latest' version never does.
+    pub fn matches(&self, version: &Version) -> bool {
+        if version.is_latest() {
+            return false;
+        }
+        // Our prereleases are validated like semver's, so this should never fail
+        let Ok(pre) = semver::Prerelease::new(version.pre.as_str()) else {
+            return false;
+        };
+        self.0.matches(&semver::Version {
+            major: version.major,
+            minor: version.minor,
+            patch: version.patch,
+            pre,
+            build: semver::BuildMetadata::EMPTY,
+        })
+    }
+}
+
+impl Display for VersionConstraint {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}", self.0) }
+}
+
+impl FromStr for VersionConstraint {
+    type Err = ConstraintParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        semver::VersionReq::parse(s).map(Self).map_err(|source| ConstraintParseError { raw: s.into(), source })
+    }
+}
@@ -1,20 +1,34 @@
 //! Module with all things related to building Brane targets.
 use std::collections::HashSet;
 use std::env::consts::{ARCH, OS};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
-use tracing::{info, warn};
+use tracing::{debug, error, info, warn};
 
-use crate::registry::{BuildFuncInfo, REGISTRY};
+use crate::registry::{BuildFuncInfo, REGISTRY, TargetKind, build_binaries};
 
 /// Build all given targets for the current operating system and architecture.
 /// # Arguments
 /// - `targets`: A list of targets to build.
+/// - `jobs`: The number of images to build concurrently. Defaults to the available parallelism of
+///   this machine.
+/// - `fail_fast`: If true, no new targets are started once one of them failed to build. Targets that
+///   are already building are still allowed to finish.
 ///
 /// Note that a target can be both a package name (e.g. 'brane-ctl') or a group name (e.g.
 /// 'binaries').
-pub fn build(targets: &[String]) -> anyhow::Result<()> {
-    let build_targets: HashSet<_> = targets
+///
+/// All binaries are built first, with a single cargo invocation (cargo parallelizes that itself,
+/// and would serialize concurrent invocations on its target directory anyway). Only the images are
+/// then built concurrently.
+///
+/// Failures of individual targets are reported as they happen, and collected into a single error
+/// once all (started) targets are done.
+pub fn build(targets: &[String], jobs: Option<NonZeroUsize>, fail_fast: bool) -> anyhow::Result<()> {
+    let build_targets: Vec<_> = targets
         .iter()
         .flat_map(|target| {
             let mut found = REGISTRY.search_for_system(target, OS, ARCH).peekable();
@@ -25,11 +39,55 @@ pub fn build(targets: &[String]) -> anyhow::Result<()> {
 
             found
         })
+        .collect::<HashSet<_>>()
+        .into_iter()
         .collect();
+    let (binaries, images): (Vec<_>, Vec<_>) = build_targets.into_iter().partition(|target| target.kind == TargetKind::Binary);
+
+    let failures: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    // Build all binaries at once
+    if !binaries.is_empty() {
+        let packages: Vec<&str> = binaries.iter().map(|target| target.package_name.as_str()).collect();
+        info!("Building {packages}", packages = packages.join(", "));
+        if let Err(err) = build_binaries(&packages) {
+            error!("Failed to build {packages}: {err:#}", packages = packages.join(", "));
+            failures.lock().unwrap().extend(packages.iter().map(|package| package.to_string()));
+        }
+    }
+
+    // Then build the images concurrently, where every job picks the next image that has not been
+    // started yet
+    let jobs = jobs.unwrap_or_else(|| std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)).get().min(images.len());
+    debug!("Building {n} image(s) using {jobs} job(s)", n = images.len());
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(!failures.lock().unwrap().is_empty());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                loop {
+                    if fail_fast && failed.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Some(target) = images.get(next.fetch_add(1, Ordering::SeqCst)) else {
+                        break;
+                    };
+
+                    info!("Building {target}", target = target.package_name);
+                    if let Err(err) = (target.build_command)(BuildFuncInfo { out_dir: PathBuf::from("./target/release") }) {
+                        error!("Failed to build {target}: {err:#}", target = target.package_name);
+                        failed.store(true, Ordering::SeqCst);
+                        failures.lock().unwrap().push(target.package_name.clone());
+                    }
+                }
+            });
+        }
+    });
 
-    for target in build_targets {
-        info!("Building {target}", target = target.package_name);
-        (target.build_command)(BuildFuncInfo { out_dir: PathBuf::from("./target/release") })?
+    let failures = failures.into_inner().unwrap();
+    if !failures.is_empty() {
+        anyhow::bail!("Failed to build {n} target(s): {list}", n = failures.len(), list = failures.join(", "));
     }
 
     Ok(())
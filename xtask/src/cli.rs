@@ -4,6 +4,7 @@
 use {
     crate::registry::{REGISTRY, Target},
     clap::{ValueEnum, builder::PossibleValue},
+    clap_complete::Shell,
     std::{
         env::consts::{ARCH, OS},
         sync::OnceLock,
@@ -13,11 +14,9 @@ use {
 /// Module containing the command line interface of xtask.
 pub(crate) mod xtask {
     use clap::{Parser, Subcommand, ValueEnum};
-    #[cfg(feature = "cli")]
-    use clap_complete::Shell;
 
     #[cfg(feature = "cli")]
-    use super::ClapTarget;
+    use super::{ClapShell, ClapTarget};
 
     // xtask is the main build tool for Brane. If there is something you have to repeatedly or
     // something you have to do in CI, this is probably the place to do so.
@@ -36,18 +35,20 @@ pub(crate) mod xtask {
         /// Builds completion files for shells for either specified or all binaries
         Completions {
             #[clap(short, long)]
-            /// The shell for which to build the completion
-            shell:  Option<Shell>,
-            #[clap(short, long)]
-            /// The binary for which to build the completion
-            target: Option<ClapTarget>,
+            /// The shell for which to build the completion, or 'auto' to detect it from `$SHELL`
+            shell:   Option<ClapShell>,
+            #[clap(short, long = "target", value_delimiter = ',')]
+            /// The binaries for which to build the completion (all if omitted). Can be given
+            /// multiple times or as a comma-separated list
+            targets: Vec<ClapTarget>,
         },
         #[cfg(feature = "cli")]
         /// Builds man pages for all Brane binaries
         Man {
-            /// What target to create a manpage for
-            #[clap(short, long)]
-            target:     Option<ClapTarget>,
+            /// What targets to create a manpage for (all if omitted). Can be given multiple times
+            /// or as a comma-separated list
+            #[clap(short, long = "target", value_delimiter = ',')]
+            targets:    Vec<ClapTarget>,
             /// Whether or not to compress the generated manpages
             #[clap(short, long)]
             compressed: bool,
@@ -66,6 +67,18 @@ pub(crate) mod xtask {
             /// Overwrite files if they already exist
             #[clap(short, long)]
             force:   bool,
+            /// Only print where every file would be installed, without writing anything
+            #[clap(long)]
+            dry_run: bool,
+        },
+        #[cfg(feature = "cli")]
+        /// Checks that the generated completions and man pages are up to date with the CLIs,
+        /// without writing any files
+        Verify {
+            /// Compare with the files installed by `xtask install` instead of those in
+            /// `./target/completions` and `./target/man`
+            #[clap(short, long)]
+            installed: bool,
         },
         /// Packages brane for the specified platform
         Package {
@@ -76,7 +89,14 @@ pub(crate) mod xtask {
         Build {
             /// The targets to build
             #[arg(required=true, num_args=1..)]
-            targets: Vec<String>,
+            targets:   Vec<String>,
+            /// The number of images to build concurrently. Defaults to the available parallelism
+            /// of this machine. Binaries are always built by a single cargo invocation
+            #[clap(short, long)]
+            jobs:      Option<std::num::NonZeroUsize>,
+            /// Stop starting new targets as soon as one of them fails to build
+            #[clap(long)]
+            fail_fast: bool,
         },
         #[cfg(feature = "ci")]
         /// Sets updates the verion of the package.
@@ -103,6 +123,12 @@ pub(crate) mod xtask {
         /// For GitHub releases.
         #[clap(name = "github")]
         GitHub,
+        #[cfg(feature = "cli")]
+        /// A generic tarball with a `bin`- and `share`-directory, to be unpacked in some prefix.
+        Tarball,
+        #[cfg(feature = "cli")]
+        /// A Homebrew bottle.
+        Homebrew,
     }
 }
 
@@ -124,3 +150,49 @@ impl ValueEnum for ClapTarget {
 
     fn to_possible_value(&self) -> Option<PossibleValue> { Some(self.0.package_name.clone().into()) }
 }
+
+#[cfg(feature = "cli")]
+/// Wrapper for [`Shell`]s that additionally accepts 'auto', which detects the shell of the user
+/// from the environment.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ClapShell {
+    /// Detect the shell from `$SHELL`.
+    Auto,
+    /// A specific shell.
+    Shell(Shell),
+}
+
+#[cfg(feature = "cli")]
+impl ClapShell {
+    /// Resolves this choice to a specific shell.
+    ///
+    /// # Errors
+    /// This function errors if this is [`ClapShell::Auto`] and the shell could not be detected.
+    pub(crate) fn resolve(self) -> anyhow::Result<Shell> {
+        match self {
+            Self::Auto => Shell::from_env().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Could not detect your shell from $SHELL (it is unset or not one of: {shells}); give it explicitly with --shell (-s)",
+                    shells = Shell::value_variants().iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+                )
+            }),
+            Self::Shell(shell) => Ok(shell),
+        }
+    }
+}
+
+#[cfg(feature = "cli")]
+impl ValueEnum for ClapShell {
+    fn value_variants<'a>() -> &'a [Self] {
+        static INSTANCE: OnceLock<Box<[ClapShell]>> = OnceLock::new();
+
+        INSTANCE.get_or_init(|| std::iter::once(Self::Auto).chain(Shell::value_variants().iter().copied().map(Self::Shell)).collect())
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            Self::Auto => Some(PossibleValue::new("auto")),
+            Self::Shell(shell) => shell.to_possible_value(),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 //! all workspace members.
 use std::env::consts::{ARCH, OS};
 use std::fs::File;
+use std::io::Write as _;
 use std::path::Path;
 
 use anyhow::Context as _;
@@ -14,10 +15,10 @@ use crate::registry::{REGISTRY, Target};
 /// Queryies the registry and builds completion files for the specified targets
 ///
 /// # Arguments:
-/// - target: Either a group or a package for which to build the completions
+/// - targets: The packages for which to build the completions, will build for all of them if empty
 /// - shell: The shell for which to build the completions, will build for all of them if omitted
 /// - destination: The directory in which to put the generated completion files
-pub(crate) fn generate_by_target(target: Option<Target>, shell: Option<Shell>, destination: impl AsRef<Path>) -> anyhow::Result<()> {
+pub(crate) fn generate_by_target(targets: Vec<Target>, shell: Option<Shell>, destination: impl AsRef<Path>) -> anyhow::Result<()> {
     let destination = destination.as_ref();
 
     let shells_to_do = match shell {
@@ -25,13 +26,10 @@ pub(crate) fn generate_by_target(target: Option<Target>, shell: Option<Shell>, d
         None => Shell::value_variants(),
     };
 
-    let targets_to_do = match target {
-        Some(target) => &[target][..],
-        None => &REGISTRY.list_targets(OS, ARCH).cloned().collect::<Vec<_>>(),
-    };
+    let targets_to_do = if targets.is_empty() { REGISTRY.list_targets(OS, ARCH).cloned().collect() } else { targets };
 
     for shell in shells_to_do {
-        for target in targets_to_do {
+        for target in &targets_to_do {
             let Some(command) = target.command.clone() else { continue };
             generate(command, shell, destination)?
         }
@@ -40,14 +38,79 @@ pub(crate) fn generate_by_target(target: Option<Target>, shell: Option<Shell>, d
     Ok(())
 }
 
-pub(crate) fn generate(mut command: Command, shell: &Shell, destination: impl AsRef<Path>) -> anyhow::Result<()> {
+pub(crate) fn generate(command: Command, shell: &Shell, destination: impl AsRef<Path>) -> anyhow::Result<()> {
     let destination = destination.as_ref();
     info!("Generating {} completions for {} (in {}).", shell, command.get_name(), destination.display());
 
     let bin_name = command.get_name().to_owned();
     let mut file = File::create(destination.join(shell.file_name(&bin_name)))
         .with_context(|| format!("Could not open/create completions file for {bin_name}"))?;
-    clap_complete::generate(*shell, &mut command, &bin_name, &mut file);
+    file.write_all(&render(command, shell)).with_context(|| format!("Could not write completions for {bin_name}"))?;
 
     Ok(())
 }
+
+/// Renders the completion file for the given command and shell in-memory, including any dynamic
+/// completions.
+///
+/// # Arguments:
+/// - command: What `Command` to render the completions for
+/// - shell: The shell for which to render the completions
+pub(crate) fn render(mut command: Command, shell: &Shell) -> Vec<u8> {
+    let bin_name = command.get_name().to_owned();
+
+    let mut buffer = Vec::new();
+    clap_complete::generate(*shell, &mut command, &bin_name, &mut buffer);
+    if let Some(dynamic) = dynamic_completions(&bin_name, shell) {
+        buffer.extend_from_slice(dynamic.as_bytes());
+    }
+
+    buffer
+}
+
+/// Returns the extra completion script that completes package, dataset and instance names by calling the hidden `brane __complete`
+/// helper, if any exists for the given binary and shell.
+///
+/// The static completions generated by clap only know about subcommands and flags, not about what is available locally.
+fn dynamic_completions(bin_name: &str, shell: &Shell) -> Option<&'static str> {
+    match (bin_name, shell) {
+        ("brane", Shell::Bash) => Some(BRANE_BASH_DYNAMIC),
+        ("brane", Shell::Fish) => Some(BRANE_FISH_DYNAMIC),
+        _ => None,
+    }
+}
+
+/// Wraps the generated `_brane` bash function to complete names for the subcommands that take them.
+const BRANE_BASH_DYNAMIC: &str = r#"
+_brane_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" kind=""
+    # Complete `NAME:VERSION` as one word if bash-completion is available
+    if declare -F _get_comp_words_by_ref >/dev/null; then
+        _get_comp_words_by_ref -n : cur
+    fi
+    case "${COMP_WORDS[1]} ${COMP_WORDS[2]}" in
+        "package export"|"package inspect"|"package load"|"package push"|"package remove"|"package tag"|"package test"|"package which")
+            kind="package" ;;
+        "data path"|"data remove"|"data verify")
+            kind="dataset" ;;
+        "instance edit"|"instance remove"|"instance select")
+            kind="instance" ;;
+    esac
+    if [[ -n "${kind}" && "${cur}" != -* && ${COMP_CWORD} -gt 2 ]]; then
+        COMPREPLY=($(brane __complete "${kind}" "${cur}" 2>/dev/null))
+        if declare -F __ltrim_colon_completions >/dev/null; then
+            __ltrim_colon_completions "${cur}"
+        fi
+        return 0
+    fi
+    _brane "$@"
+}
+complete -F _brane_dynamic -o bashdefault -o default brane
+"#;
+
+/// Adds fish completions for the subcommands that take names.
+const BRANE_FISH_DYNAMIC: &str = r#"
+complete -c brane -n "__fish_seen_subcommand_from package; and __fish_seen_subcommand_from export inspect load push remove tag test which" -f -a "(brane __complete package (commandline -ct) 2>/dev/null)"
+complete -c brane -n "__fish_seen_subcommand_from data; and __fish_seen_subcommand_from path remove verify" -f -a "(brane __complete dataset (commandline -ct) 2>/dev/null)"
+complete -c brane -n "__fish_seen_subcommand_from instance; and __fish_seen_subcommand_from edit remove select" -f -a "(brane __complete instance (commandline -ct) 2>/dev/null)"
+"#;
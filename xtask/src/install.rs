@@ -1,10 +1,9 @@
 //! Module containing all logic to install Brane locally.
 use std::env::consts::{ARCH, OS};
-use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context as _, bail};
-use clap_complete::{Generator, Shell, generate};
+use clap_complete::{Generator, Shell};
 use tracing::{debug, info, warn};
 
 use crate::registry::REGISTRY;
@@ -26,13 +25,16 @@ pub fn completion_locations() -> anyhow::Result<[(Shell, PathBuf); 3]> {
 /// # Arguments
 /// - parents: Creates the relevant directories if they don't exist yet
 /// - force: overwrite files if they already exist
-pub(crate) fn completions(parents: bool, force: bool) -> anyhow::Result<()> {
+/// - dry_run: only print where the files would be installed
+pub(crate) fn completions(parents: bool, force: bool, dry_run: bool) -> anyhow::Result<()> {
     info!("Installing completions");
     let completion_locations = completion_locations().expect("Could not get completion locations");
 
     for (shell, location) in completion_locations {
         if !location.exists() {
-            if parents {
+            if dry_run {
+                print_dry_run_dir(&location, parents);
+            } else if parents {
                 std::fs::create_dir_all(&location).context("Attempted to create completion directory")?;
             } else {
                 bail!("Completion directory for {shell} does not exist, and command was not ran with --parents (-p)");
@@ -42,7 +44,7 @@ pub(crate) fn completions(parents: bool, force: bool) -> anyhow::Result<()> {
         // We do not need completions for the binaries ran inside the images, as we cannot
         // auto-complete those anyway.
         for target in REGISTRY.search_for_system("binaries", OS, ARCH) {
-            let Some(mut command) = target.command else {
+            let Some(command) = target.command else {
                 continue;
             };
 
@@ -51,13 +53,16 @@ pub(crate) fn completions(parents: bool, force: bool) -> anyhow::Result<()> {
             let completion_filename = shell.file_name(&bin_name);
 
             let path = location.join(completion_filename);
+            if dry_run {
+                print_dry_run(&format!("{shell} completions for {bin_name}"), &path, force);
+                continue;
+            }
             debug!("Creating {path:?}");
 
             if !force && path.exists() {
                 warn!("File: {path} already exists and --force (-f) was not provided, skipping.", path = path.display());
             } else {
-                let mut file = File::create(path).context("Attempted to create completion file")?;
-                generate(shell, &mut command, bin_name, &mut file);
+                std::fs::write(path, crate::completions::render(command, &shell)).context("Attempted to create completion file")?;
             }
         }
     }
@@ -70,11 +75,15 @@ pub(crate) fn completions(parents: bool, force: bool) -> anyhow::Result<()> {
 /// # Arguments
 /// - parents: Creates the relevant directories if they don't exist yet
 /// - force: overwrite files if they already exist
-pub(crate) fn binaries(parents: bool, force: bool) -> anyhow::Result<()> {
+/// - dry_run: only print where the files would be installed
+pub(crate) fn binaries(parents: bool, force: bool, dry_run: bool) -> anyhow::Result<()> {
     info!("Installing binaries");
     let target_directory = PathBuf::from("./target/release");
     let base_dir = directories::BaseDirs::new().context("Could not determine directories in which to install")?;
     let dest_dir = base_dir.executable_dir().context("Could not determine the directories in which to install")?;
+    if dry_run && !dest_dir.exists() {
+        print_dry_run_dir(dest_dir, parents);
+    }
 
     for target in REGISTRY.search_for_system("binaries", OS, ARCH) {
         let Some(command) = target.command else { continue };
@@ -83,6 +92,10 @@ pub(crate) fn binaries(parents: bool, force: bool) -> anyhow::Result<()> {
         let src_path = target_directory.join(&bin_name);
 
         let dest_path = dest_dir.join(&bin_name);
+        if dry_run {
+            print_dry_run(&src_path.display().to_string(), &dest_path, force);
+            continue;
+        }
         debug!("Installing to {}", dest_path.display());
 
         match copy(src_path, dest_path, force, parents) {
@@ -100,13 +113,16 @@ pub(crate) fn binaries(parents: bool, force: bool) -> anyhow::Result<()> {
 /// # Arguments
 /// - parents: Creates the relevant directories if they don't exist yet
 /// - force: overwrite files if they already exist
-pub(crate) fn manpages(parents: bool, force: bool) -> anyhow::Result<()> {
+/// - dry_run: only print where the files would be installed
+pub(crate) fn manpages(parents: bool, force: bool, dry_run: bool) -> anyhow::Result<()> {
     info!("Installing manpages");
     let base_dir = directories::BaseDirs::new().context("Could not determine directories in which to install")?;
     let dest_dir = base_dir.data_local_dir().join("man/man1");
 
     if !dest_dir.exists() {
-        if parents {
+        if dry_run {
+            print_dry_run_dir(&dest_dir, parents);
+        } else if parents {
             debug!("Creating directory {}", dest_dir.display());
             std::fs::create_dir_all(&dest_dir).context("Could not create man page target directory")?;
         } else {
@@ -117,12 +133,49 @@ pub(crate) fn manpages(parents: bool, force: bool) -> anyhow::Result<()> {
     for target in REGISTRY.search_for_system("binaries", OS, ARCH) {
         let Some(command) = target.command else { continue };
 
+        if dry_run {
+            for command in SubCommandIter::new(command) {
+                let name = command.get_name().to_owned();
+                let filename = format!("{}.gz", clap_mangen::Man::new(command).get_filename());
+                print_dry_run(&format!("the man page for {name}"), &dest_dir.join(filename), force);
+            }
+            continue;
+        }
         crate::man::generate_recursively(command, &dest_dir, true, force)?;
     }
 
     Ok(())
 }
 
+/// Prints where a dry run would install an artifact, and what would happen with a file that
+/// already exists there.
+///
+/// # Arguments
+/// - what: A description of the artifact that would be installed
+/// - dest: The path at which it would be installed
+/// - force: Whether existing files would be overwritten
+fn print_dry_run(what: &str, dest: &Path, force: bool) {
+    let note = match (dest.exists(), force) {
+        (false, _) => "",
+        (true, true) => " (overwriting the existing file)",
+        (true, false) => " (skipped, since it already exists and --force (-f) was not provided)",
+    };
+    println!("Would install {what} to {}{note}", dest.display());
+}
+
+/// Prints what a dry run would do with a destination directory that does not exist.
+///
+/// # Arguments
+/// - dir: The directory that does not exist
+/// - parents: Whether missing directories would be created
+fn print_dry_run_dir(dir: &Path, parents: bool) {
+    if parents {
+        println!("Would create directory {}", dir.display());
+    } else {
+        println!("Would fail, since directory {} does not exist and --parents (-p) was not provided", dir.display());
+    }
+}
+
 /// Uninstall Brane from all installation locations we could have installed.
 ///
 /// Note that Brane does not know if those files are actually created by Brane, so if something
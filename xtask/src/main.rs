@@ -14,6 +14,8 @@ mod completions;
 mod install;
 #[cfg(feature = "cli")]
 mod man;
+#[cfg(feature = "cli")]
+mod verify;
 
 #[cfg(feature = "ci")]
 mod set_version;
@@ -44,34 +46,47 @@ async fn main() -> anyhow::Result<()> {
     use cli::xtask::XTaskSubcommand;
     match opts.subcommand {
         #[cfg(feature = "cli")]
-        XTaskSubcommand::Completions { target, shell } => {
+        XTaskSubcommand::Completions { targets, shell } => {
             let destination = PathBuf::from("./target/completions");
             ensure_dir_with_cachetag(&destination).context("Could not create directory with CACHEDIR.TAG")?;
-            completions::generate_by_target(target.map(|x| x.0), shell, destination)?;
+            let shell = shell.map(cli::ClapShell::resolve).transpose()?;
+            completions::generate_by_target(targets.into_iter().map(|x| x.0).collect(), shell, destination)?;
         },
         #[cfg(feature = "cli")]
-        XTaskSubcommand::Man { target, compressed } => {
+        XTaskSubcommand::Man { targets, compressed } => {
             let destination = PathBuf::from("./target/man");
             ensure_dir_with_cachetag(&destination).context("Could not create directory with CACHEDIR.TAG")?;
-            man::generate_by_target(target.map(|x| x.0), destination, compressed, true)?
+            man::generate_by_target(targets.into_iter().map(|x| x.0).collect(), destination, compressed, true)?
         },
         #[cfg(feature = "cli")]
-        XTaskSubcommand::Install { parents, force } => {
-            install::completions(parents, force)?;
-            install::binaries(parents, force)?;
-            install::manpages(parents, force)?;
+        XTaskSubcommand::Install { parents, force, dry_run } => {
+            install::completions(parents, force, dry_run)?;
+            install::binaries(parents, force, dry_run)?;
+            install::manpages(parents, force, dry_run)?;
         },
         #[cfg(feature = "cli")]
         XTaskSubcommand::Uninstall {} => {
             install::uninstall()?;
         },
+        #[cfg(feature = "cli")]
+        XTaskSubcommand::Verify { installed } => {
+            verify::verify(installed)?;
+        },
         XTaskSubcommand::Package { platform } => match platform {
             cli::xtask::PackagePlatform::GitHub => {
                 package::create_github_package().await.context("Could not create package for GitHub")?;
             },
+            #[cfg(feature = "cli")]
+            cli::xtask::PackagePlatform::Tarball => {
+                package::create_tarball_package().context("Could not create tarball package")?;
+            },
+            #[cfg(feature = "cli")]
+            cli::xtask::PackagePlatform::Homebrew => {
+                package::create_homebrew_package().context("Could not create Homebrew bottle")?;
+            },
         },
-        XTaskSubcommand::Build { targets } => {
-            build::build(&targets).context("Could not build all targets")?;
+        XTaskSubcommand::Build { targets, jobs, fail_fast } => {
+            build::build(&targets, jobs, fail_fast).context("Could not build all targets")?;
         },
         #[cfg(feature = "ci")]
         XTaskSubcommand::SetVersion { semver, prerelease, metadata } => {
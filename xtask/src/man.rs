@@ -13,19 +13,16 @@ use crate::utilities::SubCommandIter;
 /// Generates all man pages for all commands (and subcommands) that are part of the given target.
 ///
 /// # Arguments:
-/// - target: Either a package name or a group name for which to generate the man pages
+/// - targets: The packages for which to generate the man pages, will generate for all of them if empty
 /// - destination: The location where to store the man pages
 /// - compressed: Whether or not to compress the man pages using gzip encoding
 /// - force: Overwrites the old files if they already exist
-pub(crate) fn generate_by_target(target: Option<Target>, destination: impl AsRef<Path>, compressed: bool, force: bool) -> anyhow::Result<()> {
-    let targets = match target {
-        Some(target) => &[target][..],
-        None => &REGISTRY.list_targets(OS, ARCH).cloned().collect::<Vec<_>>(),
-    };
+pub(crate) fn generate_by_target(targets: Vec<Target>, destination: impl AsRef<Path>, compressed: bool, force: bool) -> anyhow::Result<()> {
+    let targets = if targets.is_empty() { REGISTRY.list_targets(OS, ARCH).cloned().collect() } else { targets };
 
     for target in targets {
-        // clap will ensure the target contains a command if a target is specified
-        let Some(command) = target.clone().command else {
+        // clap will ensure the targets contain a command if they are specified
+        let Some(command) = target.command else {
             continue;
         };
 
@@ -108,3 +105,19 @@ pub(crate) fn generate(command: Command, destination: impl AsRef<Path>, compress
 
     Ok(())
 }
+
+/// Renders a single, uncompressed man page for the given command in-memory.
+///
+/// # Arguments:
+/// - command: What `Command` to render the man page for
+///
+/// # Returns
+/// The file name the man page would be stored under, and its contents.
+pub(crate) fn render(command: Command) -> std::io::Result<(String, Vec<u8>)> {
+    let man = clap_mangen::Man::new(command);
+
+    let mut buffer = Vec::new();
+    man.render(&mut buffer)?;
+
+    Ok((man.get_filename(), buffer))
+}
@@ -1,11 +1,17 @@
 //! Module with everything related to creating packages for various platforms / distributions.
 use std::env::consts::*;
+#[cfg(feature = "cli")]
+use std::path::Path;
 use std::path::PathBuf;
 
 use anyhow::Context;
+#[cfg(feature = "cli")]
+use clap_complete::Shell;
 use tracing::info;
 
 use crate::registry::REGISTRY;
+#[cfg(feature = "cli")]
+use crate::utilities::create_tar_gz_from_dir;
 use crate::utilities::{
     compress_file, create_tar_gz, format_release_binary_name, format_release_library_name, format_src_binary_name, format_src_library_name,
 };
@@ -54,3 +60,89 @@ pub(crate) async fn create_github_package() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Collects all binaries from a previous build, together with their man pages and shell
+/// completions, into a generic tarball that can be unpacked in any prefix (e.g. `/usr/local`).
+///
+/// The tarball is written to `target/package/tarball/brane-<version>-<os>-<arch>.tar.gz`.
+///
+/// Note that this function does not build any packages itself. If you want to build the packages
+/// take a look at: [`crate::build::build()`].
+#[cfg(feature = "cli")]
+pub(crate) fn create_tarball_package() -> anyhow::Result<()> {
+    info!("Creating a tarball package for: {os} {arch}", os = OS, arch = ARCH);
+
+    let name = format!("brane-{version}-{os}-{arch}", version = env!("CARGO_PKG_VERSION"), os = OS, arch = ARCH);
+    let dst_dir = PathBuf::from("target/package/tarball");
+    let prefix = dst_dir.join(&name);
+
+    assemble_prefix(&prefix, &[
+        (Shell::Bash, "share/bash-completion/completions"),
+        (Shell::Fish, "share/fish/vendor_completions.d"),
+        (Shell::Zsh, "share/zsh/site-functions"),
+    ])?;
+    create_tar_gz_from_dir(dst_dir.join(format!("{name}.tar.gz")), &prefix, &name).context("Could not create tarball")?;
+
+    Ok(())
+}
+
+/// Collects all binaries from a previous build, together with their man pages and shell
+/// completions, into a Homebrew bottle.
+///
+/// The bottle is written to `target/package/homebrew/brane-<version>.<arch>_<os>.bottle.tar.gz`.
+/// Homebrew tags macOS bottles by release name (e.g. `arm64_sonoma`) instead, so those have to be
+/// renamed when they are uploaded.
+///
+/// Note that this function does not build any packages itself. If you want to build the packages
+/// take a look at: [`crate::build::build()`].
+#[cfg(feature = "cli")]
+pub(crate) fn create_homebrew_package() -> anyhow::Result<()> {
+    info!("Creating a Homebrew bottle for: {os} {arch}", os = OS, arch = ARCH);
+
+    let version = env!("CARGO_PKG_VERSION");
+    let arch = if OS == "macos" && ARCH == "aarch64" { "arm64" } else { ARCH };
+    let dst_dir = PathBuf::from("target/package/homebrew");
+    let keg = PathBuf::from("brane").join(version);
+    let prefix = dst_dir.join(&keg);
+
+    assemble_prefix(&prefix, &[
+        (Shell::Bash, "etc/bash_completion.d"),
+        (Shell::Fish, "share/fish/vendor_completions.d"),
+        (Shell::Zsh, "share/zsh/site-functions"),
+    ])?;
+    create_tar_gz_from_dir(dst_dir.join(format!("brane-{version}.{arch}_{os}.bottle.tar.gz", os = OS)), &prefix, &keg)
+        .context("Could not create Homebrew bottle")?;
+
+    Ok(())
+}
+
+/// Assembles the binaries from a previous build in the `bin`-directory of the given prefix, their
+/// (compressed) man pages in `share/man/man1` and their shell completions in the given directories.
+///
+/// Any previous contents of the prefix are removed first.
+#[cfg(feature = "cli")]
+fn assemble_prefix(prefix: &Path, completion_dirs: &[(Shell, &str)]) -> anyhow::Result<()> {
+    let src_dir = PathBuf::from("target/release");
+    let bin_dir = prefix.join("bin");
+    let man_dir = prefix.join("share/man/man1");
+
+    if prefix.exists() {
+        std::fs::remove_dir_all(prefix).with_context(|| format!("Could not remove old package directory {}", prefix.display()))?;
+    }
+    for dir in [bin_dir.clone(), man_dir.clone()].into_iter().chain(completion_dirs.iter().map(|(_, dir)| prefix.join(dir))) {
+        std::fs::create_dir_all(&dir).with_context(|| format!("Could not create directory {}", dir.display()))?;
+    }
+
+    for target in REGISTRY.search_for_system("binaries", OS, ARCH) {
+        let src = format_src_binary_name(&target.output_name);
+        std::fs::copy(src_dir.join(&src), bin_dir.join(&src)).with_context(|| format!("Could not copy over file: {src}"))?;
+
+        let Some(command) = target.command else { continue };
+        crate::man::generate_recursively(command.clone(), &man_dir, true, true)?;
+        for (shell, dir) in completion_dirs {
+            crate::completions::generate(command.clone(), shell, prefix.join(dir))?;
+        }
+    }
+
+    Ok(())
+}
@@ -27,6 +27,15 @@ pub struct BuildFuncInfo {
     pub out_dir: PathBuf,
 }
 
+/// How a [`Target`] is built.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TargetKind {
+    /// A binary or library that is built by cargo.
+    Binary,
+    /// A Docker image that is built by buildx.
+    Image,
+}
+
 /// A unit that can be compiled using xtask.
 #[derive(Clone)]
 pub struct Target {
@@ -39,6 +48,8 @@ pub struct Target {
     pub platforms: Vec<(String, String)>,
     /// Groups that this `Target` belongs to.
     pub groups:    Vec<String>,
+    /// How this `Target` is built.
+    pub kind:      TargetKind,
 
     /// Command to build this `Target`.
     pub build_command: Arc<BuildFunc>,
@@ -53,6 +64,7 @@ impl std::fmt::Debug for Target {
             .field("output_name", &self.output_name)
             .field("platforms", &self.platforms)
             .field("groups", &self.groups)
+            .field("kind", &self.kind)
             .field("command", &self.command)
             .finish()
     }
@@ -64,6 +76,7 @@ impl Hash for Target {
         self.output_name.hash(state);
         self.platforms.hash(state);
         self.groups.hash(state);
+        self.kind.hash(state);
     }
 }
 
@@ -74,26 +87,25 @@ impl PartialEq for Target {
             && self.output_name == other.output_name
             && self.platforms == other.platforms
             && self.groups == other.groups
+            && self.kind == other.kind
     }
 }
 
 impl Eq for Target {}
 
 impl Target {
-    /// Constructs a new `Target`.
-    pub fn new(
-        name: &str,
-        output_name: &str,
-        groups: &[&str],
-        platforms: &[(&str, &str)],
-        build_command: Arc<BuildFunc>,
-        command: Option<clap::Command>,
-    ) -> Self {
+    /// Constructs a new `Target`, which is built according to its `kind`.
+    pub fn new(name: &str, output_name: &str, groups: &[&str], platforms: &[(&str, &str)], kind: TargetKind, command: Option<clap::Command>) -> Self {
+        let build_command = match kind {
+            TargetKind::Binary => build_binary_builder(name),
+            TargetKind::Image => build_image_builder(name),
+        };
         Self {
             package_name: name.to_owned(),
             output_name: output_name.to_owned(),
             platforms: platforms.iter().map(|(x, y)| (x.to_string(), y.to_string())).collect(),
             groups: groups.iter().map(|x| x.to_string()).collect(),
+            kind,
             build_command,
             command,
         }
@@ -144,7 +156,7 @@ pub fn build_registry() -> Registry {
         "branec",
         &["all", "binaries"],
         &[("linux", "x86_64"), ("linux", "aarch64"), ("macos", "x86_64"), ("macos", "aarch64")],
-        build_binary_builder("brane-cc"),
+        TargetKind::Binary,
         get_cc_command(),
     ));
     registry.register(Target::new(
@@ -152,7 +164,7 @@ pub fn build_registry() -> Registry {
         "brane",
         &["all", "binaries"],
         &[("linux", "x86_64"), ("linux", "aarch64"), ("macos", "aarch64"), ("macos", "x86_64"), ("windows", "x86_64")],
-        build_binary_builder("brane-cli"),
+        TargetKind::Binary,
         get_cli_command(),
     ));
     registry.register(Target::new(
@@ -160,7 +172,7 @@ pub fn build_registry() -> Registry {
         "branectl",
         &["all", "binaries"],
         &[("linux", "x86_64"), ("linux", "aarch64"), ("macos", "x86_64"), ("macos", "aarch64")],
-        build_binary_builder("brane-ctl"),
+        TargetKind::Binary,
         get_ctl_command(),
     ));
     registry.register(Target::new(
@@ -168,7 +180,7 @@ pub fn build_registry() -> Registry {
         "branelet",
         &["all", "binaries"],
         &[("linux", "x86_64"), ("linux", "aarch64")],
-        build_binary_builder("brane-let"),
+        TargetKind::Binary,
         get_let_command(),
     ));
 
@@ -177,7 +189,7 @@ pub fn build_registry() -> Registry {
         "brane-api.tar",
         &["all", "images", "central"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-api"),
+        TargetKind::Image,
         get_api_command(),
     ));
     registry.register(Target::new(
@@ -185,7 +197,7 @@ pub fn build_registry() -> Registry {
         "brane-drv.tar",
         &["all", "images", "central"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-drv"),
+        TargetKind::Image,
         get_drv_command(),
     ));
     registry.register(Target::new(
@@ -193,7 +205,7 @@ pub fn build_registry() -> Registry {
         "brane-plr.tar",
         &["all", "images", "central"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-plr"),
+        TargetKind::Image,
         get_plr_command(),
     ));
     registry.register(Target::new(
@@ -201,7 +213,7 @@ pub fn build_registry() -> Registry {
         "brane-chk.tar",
         &["all", "images", "worker"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-chk"),
+        TargetKind::Image,
         // brane-chk is currently not part of the brane repository. If this ever changes, it should
         // be included here as well.
         None,
@@ -211,7 +223,7 @@ pub fn build_registry() -> Registry {
         "brane-job.tar",
         &["all", "images", "worker"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-job"),
+        TargetKind::Image,
         get_job_command(),
     ));
     registry.register(Target::new(
@@ -219,7 +231,7 @@ pub fn build_registry() -> Registry {
         "brane-reg.tar",
         &["all", "images", "worker"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-reg"),
+        TargetKind::Image,
         get_reg_command(),
     ));
     registry.register(Target::new(
@@ -227,7 +239,7 @@ pub fn build_registry() -> Registry {
         "brane-prx.tar",
         &["all", "images", "worker", "central"],
         &[("linux", "x86_64")],
-        build_image_builder("brane-prx"),
+        TargetKind::Image,
         get_prx_command(),
     ));
 
@@ -236,7 +248,7 @@ pub fn build_registry() -> Registry {
         "brane_cli",
         &["all", "library"],
         &[("linux", "x86_64"), ("macos", "x86_64"), ("macos", "aarch64"), ("windows", "x86_64")],
-        build_binary_builder("brane-cli-c"),
+        TargetKind::Binary,
         None,
     ));
 
@@ -281,14 +293,29 @@ pub fn build_image_builder(package: &str) -> Arc<BuildFunc> {
 
 /// A higher-order function that creates a function that in turn builds binaries in the Brane
 /// Framework.
+///
+/// To build multiple binaries, prefer [`build_binaries`], as cargo builds them in one go.
 pub fn build_binary_builder(package: &str) -> Arc<BuildFunc> {
     let package = package.to_owned();
 
-    Arc::new(move |_info: BuildFuncInfo| {
-        if !std::process::Command::new("cargo").args(["build", "--package", &package, "--release"]).spawn()?.wait_with_output()?.status.success() {
-            anyhow::bail!("{package} compilation process failed")
-        }
+    Arc::new(move |_info: BuildFuncInfo| build_binaries(&[package.as_str()]))
+}
 
-        Ok(())
-    })
+/// Builds the given binaries in the Brane Framework with a single cargo invocation.
+///
+/// Cargo locks its target directory while building, so this is faster than building them one by
+/// one (or concurrently), and it lets cargo share the work on common dependencies.
+pub fn build_binaries(packages: &[&str]) -> anyhow::Result<()> {
+    let mut cmd = std::process::Command::new("cargo");
+    cmd.arg("build");
+    for package in packages {
+        cmd.args(["--package", package]);
+    }
+    cmd.arg("--release");
+
+    if !cmd.spawn()?.wait_with_output()?.status.success() {
+        anyhow::bail!("{packages} compilation process failed", packages = packages.join(", "))
+    }
+
+    Ok(())
 }
@@ -75,6 +75,21 @@ pub fn create_tar_gz(archive_name: impl AsRef<Path>, files: impl IntoIterator<It
     Ok(())
 }
 
+/// Create a .tar.gz compressed archive from a directory. Unlike [`create_tar_gz`], the structure of
+/// the directory is kept. Its contents are stored under `name` in the archive.
+pub fn create_tar_gz_from_dir(archive_name: impl AsRef<Path>, dir: impl AsRef<Path>, name: impl AsRef<Path>) -> anyhow::Result<()> {
+    let archive_name = archive_name.as_ref();
+    let file = std::io::BufWriter::new(std::fs::File::create(archive_name).context("Couldn't create the archive")?);
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut archive = Builder::new(encoder);
+
+    trace!("Creating archive: {:?} from {}", name.as_ref(), dir.as_ref().display());
+    archive.append_dir_all(name, dir).context("Could not add directory to archive")?;
+    archive.finish().context("Could not finish writing archive")?;
+
+    Ok(())
+}
+
 /// Ensure that a given directory contains a CACHEDIR.TAG. If the directory does not yet exist, the
 /// function will create the directory. The most 'parent' newly created directory will store the
 /// CACHEDIR.TAG. If no directories have to be created, it will try to create a CACHEDIR.TAG in the
@@ -0,0 +1,102 @@
+//! Module containing the logic to check that generated completions and man pages are up to date
+//! with the CLIs of all workspace members.
+use std::env::consts::{ARCH, OS};
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use clap::ValueEnum as _;
+use clap_complete::{Generator as _, Shell};
+use tracing::{debug, error, info};
+
+use crate::registry::{REGISTRY, Target};
+use crate::utilities::SubCommandIter;
+
+/// Regenerates the completions and man pages of all targets in-memory, and compares them with the
+/// ones on disk. Nothing is written.
+///
+/// # Arguments:
+/// - installed: If true, compares with the files installed by `xtask install` instead of those
+///   generated in `./target/completions` and `./target/man`.
+///
+/// # Errors
+/// This function errors if any of the files is missing or differs from what would be generated now.
+pub(crate) fn verify(installed: bool) -> anyhow::Result<()> {
+    let (targets, completion_dirs, man_dir): (Vec<Target>, Vec<(Shell, PathBuf)>, PathBuf) = if installed {
+        let base_dir = directories::BaseDirs::new().context("Could not determine directories in which Brane is installed")?;
+        (
+            REGISTRY.search_for_system("binaries", OS, ARCH).collect(),
+            crate::install::completion_locations()?.into(),
+            base_dir.data_local_dir().join("man/man1"),
+        )
+    } else {
+        (
+            REGISTRY.list_targets(OS, ARCH).cloned().collect(),
+            Shell::value_variants().iter().map(|shell| (*shell, PathBuf::from("./target/completions"))).collect(),
+            PathBuf::from("./target/man"),
+        )
+    };
+
+    let mut outdated: usize = 0;
+    for target in targets {
+        let Some(command) = target.command else { continue };
+        info!("Verifying completions and manpages for {}", command.get_name());
+
+        for (shell, dir) in &completion_dirs {
+            let path = dir.join(shell.file_name(command.get_name()));
+            let expected = crate::completions::render(command.clone(), shell);
+            if !matches(&path, &expected, false)? {
+                outdated += 1;
+            }
+        }
+
+        for command in SubCommandIter::new(command) {
+            let (filename, expected) = crate::man::render(command).context("Could not render man page")?;
+
+            // Man pages may have been generated compressed or not
+            let path = man_dir.join(&filename);
+            let gz_path = man_dir.join(format!("{filename}.gz"));
+            let matched = if !path.exists() && gz_path.exists() { matches(&gz_path, &expected, true)? } else { matches(&path, &expected, false)? };
+            if !matched {
+                outdated += 1;
+            }
+        }
+    }
+
+    if outdated > 0 {
+        anyhow::bail!("{outdated} generated file(s) are missing or out of date; regenerate them with 'xtask completions' and 'xtask man'");
+    }
+    info!("All completions and manpages are up to date");
+
+    Ok(())
+}
+
+/// Compares the file at the given path with the expected contents, reporting any difference.
+///
+/// # Arguments:
+/// - path: The path of the file to compare
+/// - expected: The contents the file should have
+/// - compressed: Whether the file is compressed using gzip encoding
+///
+/// # Returns
+/// Whether the file exists and has the expected contents.
+fn matches(path: &Path, expected: &[u8], compressed: bool) -> anyhow::Result<bool> {
+    debug!("Comparing {}", path.display());
+    if !path.exists() {
+        error!("{} is missing", path.display());
+        return Ok(false);
+    }
+
+    let mut actual = Vec::new();
+    let file = std::fs::File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    let read =
+        if compressed { flate2::read::GzDecoder::new(file).read_to_end(&mut actual) } else { std::io::BufReader::new(file).read_to_end(&mut actual) };
+    read.with_context(|| format!("Could not read {}", path.display()))?;
+
+    if actual != expected {
+        error!("{} is out of date", path.display());
+        return Ok(false);
+    }
+
+    Ok(true)
+}